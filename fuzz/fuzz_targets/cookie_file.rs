@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustcurl::curl::cookie;
+
+// First line is the request host, the rest is the cookie file.
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    let (host, content) = s.split_once('\n').unwrap_or(("example.com", s));
+    let cookies = cookie::parse_cookie_file(content, host, "/", data.len() % 2 == 0);
+    let _ = cookie::cookie_header(&cookies);
+    let _ = cookie::parse_inline(content);
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustcurl::curl::glob;
+
+// First line is the URL pattern, the rest is the -o name template.
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    let (pattern, template) = s.split_once('\n').unwrap_or((s, "#1"));
+    let Ok(expanded) = glob::expand(pattern) else {
+        return;
+    };
+    assert!(!expanded.is_empty() && expanded.len() <= glob::MAX_URLS);
+    for url in &expanded {
+        assert_eq!(url.values.len(), expanded[0].values.len());
+        let _ = glob::output_name(template, &url.values);
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustcurl::curl::response::Response;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    for line in s.lines() {
+        let _ = rustcurl::curl::config::split_header(line);
+    }
+    let resp = Response {
+        status_code: 200,
+        headers: s.lines().map(str::to_string).collect(),
+        ..Default::default()
+    };
+    let _ = resp.header_map();
+    let _ = resp.get_header("content-type");
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Arguments are NUL-separated so the fuzzer can produce values containing
+// spaces. Help flags exit the process and `@file` values read from disk,
+// so both are skipped.
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    let args: Vec<String> = s.split('\0').map(str::to_string).collect();
+    if args
+        .iter()
+        .any(|a| a == "-h" || a == "--help" || a.starts_with('@'))
+    {
+        return;
+    }
+    let _ = rustcurl::curl::parse_args(&args);
+});
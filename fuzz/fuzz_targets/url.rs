@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(parts) = rustcurl::curl::url::parse(s) {
+        assert!(!parts.host.is_empty());
+    }
+    let _ = rustcurl::curl::url::host(s);
+});
@@ -2,14 +2,104 @@
 
 //! curl backend - wraps the existing curl implementation
 
+use std::borrow::{Borrow, BorrowMut};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use curl::easy::Easy;
+
 use super::HttpBackend;
-use crate::curl::{config::RequestConfig, error::RequestError, response::Response};
+use crate::curl::{
+    body::RequestBody,
+    capabilities::Capabilities,
+    config::RequestConfig,
+    error::RequestError,
+    relay::Tunnel,
+    response::{ParallelOutcome, Response, StreamingResponse, TransferSummary},
+};
+
+/// Idle handles kept for reuse; extra handles are dropped after use.
+const MAX_IDLE_HANDLES: usize = 8;
+
+/// Keeps a pool of `Easy` handles so concurrent callers sharing one
+/// backend reuse libcurl's per-handle connection and DNS caches.
+#[derive(Default)]
+pub struct CurlBackend {
+    idle: Arc<Mutex<Vec<Easy>>>,
+}
+
+/// A checked-out handle that outlives the call, for streamed bodies and
+/// tunnels; it goes back to the pool when dropped.
+struct Pooled {
+    easy: Option<Easy>,
+    idle: Arc<Mutex<Vec<Easy>>>,
+}
+
+impl Borrow<Easy> for Pooled {
+    fn borrow(&self) -> &Easy {
+        self.easy.as_ref().expect("handle present until drop")
+    }
+}
+
+impl BorrowMut<Easy> for Pooled {
+    fn borrow_mut(&mut self) -> &mut Easy {
+        self.easy.as_mut().expect("handle present until drop")
+    }
+}
+
+impl Drop for Pooled {
+    fn drop(&mut self) {
+        if let Some(easy) = self.easy.take() {
+            checkin(&self.idle, easy);
+        }
+    }
+}
 
-pub struct CurlBackend;
+fn checkin(idle: &Mutex<Vec<Easy>>, easy: Easy) {
+    if let Ok(mut idle) = idle.lock()
+        && idle.len() < MAX_IDLE_HANDLES
+    {
+        idle.push(easy);
+    }
+}
 
 impl CurlBackend {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    fn checkout(&self) -> Easy {
+        self.idle
+            .lock()
+            .ok()
+            .and_then(|mut idle| idle.pop())
+            .unwrap_or_else(Easy::new)
+    }
+
+    fn checkin(&self, easy: Easy) {
+        checkin(&self.idle, easy);
+    }
+
+    /// Check out a handle that returns itself to the pool when dropped.
+    fn pooled(&self) -> Pooled {
+        Pooled {
+            easy: Some(self.checkout()),
+            idle: Arc::clone(&self.idle),
+        }
+    }
+
+    /// Run `f` on a pooled handle, returning it to the pool afterwards.
+    fn with_handle<T>(&self, f: impl FnOnce(&mut Easy) -> T) -> T {
+        let mut easy = self.checkout();
+        let result = f(&mut easy);
+        self.checkin(easy);
+        result
+    }
+
+    /// Number of idle handles, for tests.
+    #[cfg(test)]
+    fn idle_handles(&self) -> usize {
+        self.idle.lock().unwrap().len()
     }
 }
 
@@ -22,8 +112,103 @@ impl HttpBackend for CurlBackend {
         curl::Version::num()
     }
 
+    fn capabilities(&self) -> Capabilities {
+        let v = curl::Version::get();
+        let tls = v.ssl_version().map(str::to_string);
+        let mut auth = vec!["basic", "bearer", "digest"];
+        if v.feature_ntlm() {
+            auth.push("ntlm");
+        }
+        if v.feature_spnego() || v.feature_gss_negotiate() || v.feature_sspi() {
+            auth.push("negotiate");
+        }
+        let flags = [
+            ("http2", v.feature_http2()),
+            ("http3", v.feature_http3()),
+            ("gzip", v.feature_libz()),
+            ("brotli", v.feature_brotli()),
+            ("zstd", v.feature_zstd()),
+            ("ipv6", v.feature_ipv6()),
+            ("unix-socket", v.feature_unix_domain_socket()),
+            ("https-proxy", v.feature_https_proxy()),
+            ("alt-svc", v.feature_altsvc()),
+            ("hsts", v.feature_hsts()),
+            ("server-push", v.feature_http2()),
+            // --keylog sets OpenSSL's key log callback
+            ("keylog", !cfg!(windows) && tls.as_deref().is_some_and(|t| t.starts_with("OpenSSL"))),
+            ("connect-tunnel", true),
+        ];
+        Capabilities {
+            backend: self.name().to_string(),
+            backend_version: v.version().to_string(),
+            tls,
+            protocols: v.protocols().map(str::to_string).collect(),
+            auth,
+            features: flags.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect(),
+        }
+    }
+
     fn perform_request(&self, config: &RequestConfig) -> Result<Response, RequestError> {
-        // Delegate to the existing curl implementation
-        crate::curl::request::perform_request(config)
+        // Only the multi interface can receive pushes
+        if config.accept_push.is_some() {
+            return crate::curl::request::perform_accepting_push(config);
+        }
+        self.with_handle(|easy| crate::curl::request::perform_request(easy, config))
+    }
+
+    fn perform_upload(&self, config: &RequestConfig, body: RequestBody) -> Result<Response, RequestError> {
+        self.with_handle(|easy| crate::curl::request::perform_upload(easy, config, body))
+    }
+
+    fn perform_to(&self, config: &RequestConfig, sink: &mut dyn Write) -> Result<TransferSummary, RequestError> {
+        self.with_handle(|easy| crate::curl::request::perform_to(easy, config, sink))
+    }
+
+    fn perform_parallel(&self, configs: &[RequestConfig]) -> ParallelOutcome {
+        crate::curl::request::perform_parallel(configs)
+    }
+
+    fn perform_streaming(&self, config: &RequestConfig) -> Result<StreamingResponse, RequestError> {
+        crate::curl::request::perform_streaming(self.pooled(), config)
+    }
+
+    fn open_tunnel(&self, config: &RequestConfig, authority: &str) -> Result<Tunnel, RequestError> {
+        crate::curl::request::open_tunnel(self.pooled(), config, authority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_return_to_pool() {
+        let backend = CurlBackend::new();
+        assert_eq!(backend.idle_handles(), 0);
+        let first = backend.checkout();
+        let second = backend.checkout();
+        backend.checkin(first);
+        backend.checkin(second);
+        assert_eq!(backend.idle_handles(), 2);
+        let _reused = backend.checkout();
+        assert_eq!(backend.idle_handles(), 1);
+    }
+
+    #[test]
+    fn pool_is_bounded() {
+        let backend = CurlBackend::new();
+        for _ in 0..MAX_IDLE_HANDLES + 3 {
+            backend.checkin(Easy::new());
+        }
+        assert_eq!(backend.idle_handles(), MAX_IDLE_HANDLES);
+    }
+
+    #[test]
+    fn pooled_handle_returns_on_drop() {
+        let backend = CurlBackend::new();
+        let handle = backend.pooled();
+        assert_eq!(backend.idle_handles(), 0);
+        std::thread::spawn(move || drop(handle)).join().unwrap();
+        assert_eq!(backend.idle_handles(), 1);
     }
 }
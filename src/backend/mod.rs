@@ -12,18 +12,87 @@ pub mod curl_backend;
 #[cfg(feature = "reqwest")]
 pub mod reqwest_backend;
 
-use crate::curl::{config::RequestConfig, error::RequestError, response::Response};
+use std::io::Write;
 
-/// HTTP backend trait that both curl and reqwest implement
-pub trait HttpBackend {
+use crate::curl::{
+    body::RequestBody,
+    capabilities::Capabilities,
+    config::RequestConfig,
+    error::RequestError,
+    relay::Tunnel,
+    response::{ParallelOutcome, Response, StreamingResponse, TransferSummary},
+};
+
+/// HTTP backend trait that both curl and reqwest implement. Backends are
+/// shared across threads by `Client`, so they must be `Send + Sync`.
+pub trait HttpBackend: Send + Sync {
     /// Name of the backend (for --version output)
     fn name(&self) -> &'static str;
 
     /// Version of the backend library
     fn version(&self) -> &'static str;
 
+    /// Protocols, auth schemes, TLS library and transfer features this
+    /// backend supports (`rustcurl capabilities`).
+    fn capabilities(&self) -> Capabilities;
+
     /// Execute an HTTP request
     fn perform_request(&self, config: &RequestConfig) -> Result<Response, RequestError>;
+
+    /// Execute an HTTP request whose body is read from `body` rather than
+    /// taken from the config's data.
+    fn perform_upload(&self, config: &RequestConfig, body: RequestBody) -> Result<Response, RequestError>;
+
+    /// Execute an HTTP request, writing the body to `sink` as it arrives.
+    fn perform_to(&self, config: &RequestConfig, sink: &mut dyn Write) -> Result<TransferSummary, RequestError>;
+
+    /// Execute requests concurrently. The default runs one thread per
+    /// request, which for a backend with a shared connection pool (reqwest)
+    /// already multiplexes requests to the same HTTP/2 origin.
+    fn perform_parallel(&self, configs: &[RequestConfig]) -> ParallelOutcome {
+        let responses = std::thread::scope(|scope| {
+            let handles: Vec<_> = configs
+                .iter()
+                .map(|config| scope.spawn(move || self.perform_request(config)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| {
+                    h.join().unwrap_or_else(|_| {
+                        Err(RequestError::Transfer {
+                            message: "transfer thread panicked".to_string(),
+                        })
+                    })
+                })
+                .collect()
+        });
+        ParallelOutcome {
+            responses,
+            connections: None,
+        }
+    }
+
+    /// Execute an HTTP request, returning once the response head is in and
+    /// leaving the body to be read incrementally. The default buffers the
+    /// whole response first.
+    fn perform_streaming(&self, config: &RequestConfig) -> Result<StreamingResponse, RequestError> {
+        let response = self.perform_request(config)?;
+        Ok(StreamingResponse {
+            status_code: response.status_code,
+            headers: response.headers,
+            body: Box::new(std::io::Cursor::new(response.body)),
+        })
+    }
+
+    /// Connect to `authority` (`host:port`) through the configured proxy
+    /// for a `CONNECT` tunnel. The default cannot.
+    fn open_tunnel(&self, config: &RequestConfig, authority: &str) -> Result<Tunnel, RequestError> {
+        let _ = (config, authority);
+        Err(RequestError::Config {
+            option: "relay".to_string(),
+            message: format!("the {} backend cannot open CONNECT tunnels", self.name()),
+        })
+    }
 }
 
 /// Get the active backend based on compile-time features
@@ -4,21 +4,86 @@
 
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+
 use super::HttpBackend;
 use crate::curl::{
-    config::{Method, RequestConfig},
-    error::RequestError,
-    response::Response,
+    body::RequestBody,
+    capabilities::Capabilities,
+    config::{Http2, Method, RequestConfig, TlsVersion},
+    error::{RequestError, TimeoutPhase},
+    identity::{self, CertType},
+    proxy::ProxyKind,
+    recorder,
+    response::{Response, StreamingResponse, TransferInfo, TransferSummary},
+    sink::{BodySink, saved_body},
+    warning::{Warning, WarningKind},
 };
 
-pub struct ReqwestBackend;
+/// Distinct client configurations kept before the cache is flushed.
+const MAX_CACHED_CLIENTS: usize = 16;
+
+/// Shares one `reqwest::blocking::Client` (and so one connection pool)
+/// per distinct client-level configuration across all callers.
+#[derive(Default)]
+pub struct ReqwestBackend {
+    clients: Mutex<HashMap<String, reqwest::blocking::Client>>,
+}
 
 impl ReqwestBackend {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Client for `config`, resolving the host through the DNS cache first
+    /// so the addresses can be pinned.
+    fn client_for(&self, config: &RequestConfig) -> Result<reqwest::blocking::Client, RequestError> {
+        let pinned = crate::curl::dns::pre_resolve(config)?;
+        let key = format!("{}{pinned:?}", client_key(config));
+        if let Some(client) = self.clients.lock().ok().and_then(|c| c.get(&key).cloned()) {
+            return Ok(client);
+        }
+        let client = build_client(config, pinned.as_ref())?;
+        if let Ok(mut clients) = self.clients.lock() {
+            if clients.len() >= MAX_CACHED_CLIENTS {
+                clients.clear();
+            }
+            clients.insert(key, client.clone());
+        }
+        Ok(client)
     }
 }
 
+/// Everything `build_client` reads; requests with equal keys can share a
+/// client.
+fn client_key(config: &RequestConfig) -> String {
+    format!(
+        "{:?}",
+        (
+            (config.negotiate, config.ntlm, &config.username, &config.password),
+            (config.insecure, &config.cacert, config.provenance || config.show_certs, config.http2),
+            (&config.cert, &config.key, &config.key_password),
+            (config.cert_type, config.key_type, config.tls_min, config.tls_max),
+            (
+                crate::curl::request::resolve_proxy(config),
+                crate::curl::request::resolve_noproxy(config).is_some(),
+                config.env_isolated(),
+            ),
+            (
+                config.proxy_negotiate,
+                config.proxy_ntlm,
+                &config.proxy_user,
+                &config.proxy_password,
+                &config.proxy_headers,
+            ),
+            (config.connect_timeout, config.read_timeout, config.follow_location, config.max_redirs),
+            config.effective_user_agent(),
+        )
+    )
+}
+
 impl HttpBackend for ReqwestBackend {
     fn name(&self) -> &'static str {
         "reqwest"
@@ -28,68 +93,482 @@ impl HttpBackend for ReqwestBackend {
         env!("CARGO_PKG_VERSION")
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            backend: self.name().to_string(),
+            backend_version: self.version().to_string(),
+            tls: Some("native-tls".to_string()),
+            protocols: vec!["http".to_string(), "https".to_string()],
+            // NTLM goes through the negotiate (SPNEGO) exchange
+            auth: vec!["basic", "bearer", "negotiate", "ntlm"],
+            features: vec!["http2"],
+        }
+    }
+
     fn perform_request(&self, config: &RequestConfig) -> Result<Response, RequestError> {
-        // Use blocking reqwest since rustcurl is synchronous
-        let client = build_client(config)?;
-        let mut request_builder = match &config.method {
-            Method::Get => client.get(&config.url),
-            Method::Post => client.post(&config.url),
-            Method::Put => client.put(&config.url),
-            Method::Delete => client.delete(&config.url),
-            Method::Head => client.head(&config.url),
-            Method::Patch => client.patch(&config.url),
-            Method::Options => client.request(reqwest::Method::OPTIONS, &config.url),
-            Method::Custom(method) => {
-                let method = reqwest::Method::from_bytes(method.as_bytes())
-                    .map_err(|e| RequestError::Config(format!("Invalid method: {}", e)))?;
-                client.request(method, &config.url)
-            }
+        let started = std::time::Instant::now();
+        let response = send(self.client_for(config)?, config, None)?;
+        read_response(config, response, started)
+    }
+
+    fn perform_upload(&self, config: &RequestConfig, body: RequestBody) -> Result<Response, RequestError> {
+        let started = std::time::Instant::now();
+        let body = match body.len {
+            Some(len) => reqwest::blocking::Body::sized(body.reader, len),
+            None => reqwest::blocking::Body::new(body.reader),
         };
+        let response = send(self.client_for(config)?, config, Some(body))?;
+        read_response(config, response, started)
+    }
+
+    fn perform_to(
+        &self,
+        config: &RequestConfig,
+        sink: &mut dyn std::io::Write,
+    ) -> Result<TransferSummary, RequestError> {
+        let mut response = send(self.client_for(config)?, config, None)?;
+        let status_code = response.status().as_u16() as u32;
+        let headers = header_lines(&response);
+        let bytes = response.copy_to(sink)?;
+        Ok(TransferSummary {
+            status_code,
+            headers,
+            bytes,
+            timing: None,
+        })
+    }
+
+    fn perform_streaming(&self, config: &RequestConfig) -> Result<StreamingResponse, RequestError> {
+        // The blocking response already reads the body on demand
+        let response = send(self.client_for(config)?, config, None)?;
+        Ok(StreamingResponse {
+            status_code: response.status().as_u16() as u32,
+            headers: header_lines(&response),
+            body: Box::new(response),
+        })
+    }
+}
+
+/// The ALPN protocol, going by the HTTP version of an https response.
+fn alpn(response: &reqwest::blocking::Response) -> Option<String> {
+    if response.url().scheme() != "https" {
+        return None;
+    }
+    match response.version() {
+        reqwest::Version::HTTP_2 => Some("h2".to_string()),
+        reqwest::Version::HTTP_11 => Some("http/1.1".to_string()),
+        _ => None,
+    }
+}
 
-        // Add headers
-        for header_str in &config.headers {
-            if let Some((name, value)) = header_str.split_once(':') {
-                request_builder = request_builder.header(name.trim(), value.trim());
+fn read_response(
+    config: &RequestConfig,
+    mut response: reqwest::blocking::Response,
+    started: std::time::Instant,
+) -> Result<Response, RequestError> {
+    // Convert response
+    let status_code = response.status().as_u16() as u32;
+    let response_version = response.version();
+    let headers = header_lines(&response);
+    let mut warnings = ignored_options(config);
+    let dropped = response.headers().len() + 1 - headers.len();
+    if dropped > 0 {
+        warnings.push(Warning::new(
+            WarningKind::Header,
+            format!("{dropped} response header line(s) could not be read as text and were left out"),
+        ));
+    }
+    let mut transfer = TransferInfo {
+        url_effective: response.url().to_string(),
+        // Streamed uploads are not counted
+        size_upload: config.body().map_or(0, |b| b.len() as u64),
+        size_header: headers.iter().map(|h| h.len() as u64 + 2).sum::<u64>() + 2,
+        remote_ip: response.remote_addr().map(|a| a.ip().to_string()),
+        remote_port: response.remote_addr().map(|a| a.port()),
+        alpn: alpn(&response),
+        ..Default::default()
+    };
+    // native-tls only exposes the leaf
+    let peer_certificates: Vec<Vec<u8>> = response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .map(<[u8]>::to_vec)
+        .into_iter()
+        .collect();
+
+    // Read incrementally: the client timeout (--read-timeout) applies per
+    // read, and the deadline check enforces --max-time across the body.
+    // Chunks go straight to the -o file (or stdout) when there is one.
+    let mut sink = BodySink::open(config);
+    let mut received = 0u64;
+    let mut chunk = [0u8; 16 * 1024];
+    let expected = response.content_length();
+    loop {
+        let n = match std::io::Read::read(&mut response, &mut chunk) {
+            Ok(n) => n,
+            // Connection closed before the announced Content-Length arrived
+            Err(_) if expected.is_some_and(|len| received < len) => {
+                if config.accept_partial {
+                    config.note(format_args!(
+                        "Accepting partial body: received {received} of {} bytes",
+                        expected.unwrap_or_default()
+                    ));
+                    break;
+                }
+                return Err(RequestError::PartialBody { expected, received });
             }
+            Err(e) => return Err(e.into()),
+        };
+        if n == 0 {
+            break;
         }
+        if let Err(e) = sink.write_all(&chunk[..n]) {
+            return Err(sink.write_error(e));
+        }
+        received += n as u64;
+        if config.max_time.is_some_and(|d| started.elapsed() > d) {
+            return Err(RequestError::Timeout {
+                phase: TimeoutPhase::Total,
+                message: format!("operation exceeded --max-time of {:?}", started.elapsed()),
+            });
+        }
+    }
 
-        // Add bearer token
-        if let Some(ref token) = config.bearer {
-            request_builder = request_builder.bearer_auth(token);
+    transfer.size_download = received;
+    let body = sink.finish()?;
+    let body = match config.output_path() {
+        // Byte range replies are buffered to be reassembled, then saved
+        Some(path) if !BodySink::streams(config) => {
+            let body = saved_body(status_code, &headers, body, &mut warnings);
+            std::fs::write(&path, &body).map_err(|source| RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            })?;
+            Vec::new()
         }
+        _ => body,
+    };
+    Ok(Response {
+        status_code,
+        headers,
+        body,
+        timing: None, // reqwest doesn't expose detailed timing
+        attempts: Vec::new(),
+        informational: Vec::new(),
+        peer_certificates,
+        saved_as: None,
+        http_version: Some(version_number(response_version).to_string()),
+        transfer: Some(transfer),
+        warnings,
+    })
+}
 
-        // Add body
-        if let Some(ref data) = config.data {
-            request_builder = request_builder.body(data.clone());
+/// Options this backend leaves out rather than rejects.
+fn ignored_options(config: &RequestConfig) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if config.ignore_content_length {
+        warnings.push(Warning::new(
+            WarningKind::IgnoredOption,
+            "--ignore-content-length is not supported with the reqwest backend. \
+             Use --accept-partial to keep truncated bodies instead.",
+        ));
+    }
+    if config.http2 == Some(Http2::Negotiate) && config.url.to_ascii_lowercase().starts_with("http://") {
+        warnings.push(Warning::new(
+            WarningKind::IgnoredOption,
+            "--http2 cannot upgrade http:// connections with the reqwest backend; HTTP/1.1 was used. \
+             Use --http2-prior-knowledge for a server known to speak h2c.",
+        ));
+    }
+    let proxy = crate::curl::request::resolve_proxy(config).and_then(|p| ProxyKind::of(&p));
+    if config.proxy_user.is_some() && matches!(proxy, Some(ProxyKind::Socks4 | ProxyKind::Socks4a)) {
+        warnings.push(Warning::new(
+            WarningKind::IgnoredOption,
+            "--proxy-user is not sent to SOCKS4 proxies by the reqwest backend. \
+             Use the curl backend for a SOCKS4 user ID.",
+        ));
+    }
+    if config.proxy_ntlm && proxy.is_some() {
+        warnings.push(Warning::new(
+            WarningKind::IgnoredOption,
+            "--proxy-ntlm is not supported with the reqwest backend; proxy credentials were sent with Basic. \
+             Use the curl backend for proxy NTLM authentication.",
+        ));
+    }
+    warnings
+}
+
+/// Status line then headers, in the shape libcurl hands them over.
+fn header_lines(response: &reqwest::blocking::Response) -> Vec<String> {
+    std::iter::once(format!("HTTP/{} {}", version_number(response.version()), response.status()))
+        .chain(
+            response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some(format!("{}: {}", name, value.to_str().ok()?))),
+        )
+        .collect()
+}
+
+/// `1.1`, `2` and so on, as in the status line.
+fn version_number(version: reqwest::Version) -> &'static str {
+    match version {
+        reqwest::Version::HTTP_09 => "0.9",
+        reqwest::Version::HTTP_10 => "1.0",
+        reqwest::Version::HTTP_2 => "2",
+        reqwest::Version::HTTP_3 => "3",
+        _ => "1.1",
+    }
+}
+
+/// Build and send the request, returning once the response head arrives.
+/// `upload` replaces the config's data as the body when given.
+fn send(
+    client: reqwest::blocking::Client,
+    config: &RequestConfig,
+    upload: Option<reqwest::blocking::Body>,
+) -> Result<reqwest::blocking::Response, RequestError> {
+    if config.accept_push.is_some() {
+        return Err(RequestError::Config {
+            option: "--accept-push".to_string(),
+            message: "the reqwest backend does not support HTTP/2 server push".to_string(),
+        });
+    }
+    if config.h2_priority.is_some() {
+        return Err(RequestError::Config {
+            option: "--h2-priority".to_string(),
+            message: "the reqwest backend does not set HTTP/2 stream priorities".to_string(),
+        });
+    }
+    if config.tls_session_cache.is_some() {
+        return Err(RequestError::Config {
+            option: "--tls-session-cache".to_string(),
+            message: "the reqwest backend cannot save or resume TLS sessions".to_string(),
+        });
+    }
+    if config.keylog.is_some() {
+        return Err(RequestError::Config {
+            option: "--keylog".to_string(),
+            message: "the reqwest backend (native-tls) cannot log TLS secrets; use the curl backend".to_string(),
+        });
+    }
+    if config.ciphers.is_some() || config.tls13_ciphers.is_some() {
+        let option = if config.ciphers.is_some() { "--ciphers" } else { "--tls13-ciphers" };
+        return Err(RequestError::Config {
+            option: option.to_string(),
+            message: "the reqwest backend (native-tls) cannot choose cipher suites; use the curl backend".to_string(),
+        });
+    }
+    if config.anyauth {
+        return Err(RequestError::Config {
+            option: "--anyauth".to_string(),
+            message: "the reqwest backend cannot pick a method from the challenge; use --negotiate, --ntlm or -u".to_string(),
+        });
+    }
+    if config.ech.is_some() {
+        return Err(RequestError::Config {
+            option: "--ech".to_string(),
+            message: "the reqwest backend does not support Encrypted Client Hello".to_string(),
+        });
+    }
+    if config.proxy_digest || config.proxy_anyauth {
+        let option = if config.proxy_digest { "--proxy-digest" } else { "--proxy-anyauth" };
+        return Err(RequestError::Config {
+            option: option.to_string(),
+            message: "the reqwest backend cannot answer a proxy's Digest challenge; use the curl backend".to_string(),
+        });
+    }
+    if config.preproxy.is_some() {
+        return Err(RequestError::Config {
+            option: "--preproxy".to_string(),
+            message: "the reqwest backend cannot chain proxies; use the curl backend".to_string(),
+        });
+    }
+    if config.tofu || config.pinned_pubkey.is_some() {
+        return Err(RequestError::Config {
+            option: "--tofu".to_string(),
+            message: "the reqwest backend cannot pin a server's public key; use the curl backend".to_string(),
+        });
+    }
+    // reqwest tunnels https:// URLs only; plain ones are always forwarded
+    if config.proxy_tunnel
+        && !config.url.to_ascii_lowercase().starts_with("https://")
+        && crate::curl::request::resolve_proxy(config).is_some()
+    {
+        return Err(RequestError::Config {
+            option: "-p".to_string(),
+            message: "the reqwest backend cannot tunnel http:// URLs through the proxy; use the curl backend"
+                .to_string(),
+        });
+    }
+
+    // Use blocking reqwest since rustcurl is synchronous
+    let url = config.request_url();
+    let url = url.as_ref();
+    let mut request_builder = match &config.method {
+        Method::Get => client.get(url),
+        Method::Post => client.post(url),
+        Method::Put => client.put(url),
+        Method::Delete => client.delete(url),
+        Method::Head => client.head(url),
+        Method::Patch => client.patch(url),
+        Method::Options => client.request(reqwest::Method::OPTIONS, url),
+        Method::Custom(method) => {
+            let method = reqwest::Method::from_bytes(method.as_bytes())
+                .map_err(|e| RequestError::Config {
+                    option: "-X".to_string(),
+                    message: format!("invalid method: {e}"),
+                })?;
+            client.request(method, url)
         }
+    };
 
-        // Execute request
-        let response = request_builder.send()?;
+    // Add headers
+    for header_str in &config.headers {
+        if let Some((name, value)) = crate::curl::config::split_header(header_str) {
+            request_builder = request_builder.header(name, value);
+        }
+    }
 
-        // Convert response
-        let status_code = response.status().as_u16() as u32;
+    for (name, value) in config.impersonated_headers() {
+        request_builder = request_builder.header(name, value);
+    }
 
-        let mut headers = Vec::new();
-        for (name, value) in response.headers() {
-            if let Ok(value_str) = value.to_str() {
-                headers.push(format!("{}: {}", name, value_str));
-            }
+    // Add cookies merged from all -b sources
+    if !config.cookies.is_empty() {
+        let merged = crate::curl::cookie::merge_cookies(&config.cookies, &config.url)?;
+        if !merged.is_empty() {
+            request_builder = request_builder
+                .header("Cookie", crate::curl::cookie::cookie_header(&merged));
         }
+    }
 
-        let body = response.bytes()?.to_vec();
+    // Add bearer token; otherwise Basic credentials go out below, up
+    // front as libcurl does unless --auth-on-challenge holds them back
+    let mut basic = None;
+    if let Some(ref token) = config.bearer {
+        request_builder = request_builder.bearer_auth(token);
+    } else if !config.negotiate && !config.ntlm {
+        basic = crate::curl::request::resolve_username(config)
+            .map(|user| (user, crate::curl::request::resolve_password(config)));
+    }
 
-        Ok(Response {
-            status_code,
-            headers,
-            body,
-            timing: None, // reqwest doesn't expose detailed timing
-        })
+    if let Some(ref spec) = config.range {
+        request_builder = request_builder.header("Range", format!("bytes={spec}"));
     }
+
+    if let Some(content_type) = config.implied_content_type() {
+        request_builder = request_builder.header("Content-Type", content_type);
+    }
+
+    // Add body
+    if let Some(upload) = upload {
+        request_builder = request_builder.body(upload);
+    } else if let Some(data) = config.body() {
+        request_builder = request_builder.body(data);
+    }
+
+    // --max-time bounds the whole exchange, not each read
+    if let Some(d) = config.max_time {
+        request_builder = request_builder.timeout(d);
+    }
+
+    let Some((user, pass)) = basic else {
+        return dispatch(request_builder);
+    };
+    // A streamed upload cannot be replayed, so its credentials go up front
+    if config.auth_on_challenge
+        && let Some(challenged) = request_builder.try_clone()
+    {
+        let response = dispatch(request_builder)?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED || !offers_basic(&response) {
+            return Ok(response);
+        }
+        return dispatch(challenged.basic_auth(user, pass));
+    }
+    dispatch(request_builder.basic_auth(user, pass))
 }
 
-fn build_client(config: &RequestConfig) -> Result<reqwest::blocking::Client, RequestError> {
+/// Send a finished request, noting it for `recorder::record` first.
+fn dispatch(builder: reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response, RequestError> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+    if recorder::active() {
+        let url = request.url();
+        let target = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        let mut head = format!("{} {target} HTTP/1.1\r\n", request.method()).into_bytes();
+        for (name, value) in request.headers() {
+            head.extend_from_slice(format!("{name}: ").as_bytes());
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+        recorder::head(&head);
+        if let Some(bytes) = request.body().and_then(|body| body.as_bytes()) {
+            recorder::body(bytes);
+        }
+    }
+    Ok(client.execute(request)?)
+}
+
+/// The `--cert` identity. native-tls reads PKCS#12 with its passphrase,
+/// but only an unencrypted PKCS#8 key next to a PEM certificate; DER
+/// files are wrapped as PEM first.
+fn client_identity(config: &RequestConfig, path: &str) -> Result<reqwest::Identity, RequestError> {
+    if config.cert_format() == CertType::P12 {
+        let bundle = std::fs::read(path)?;
+        return Ok(reqwest::Identity::from_pkcs12_der(&bundle, config.key_password.as_deref().unwrap_or(""))?);
+    }
+    if config.key_password.is_some() {
+        return Err(RequestError::Config {
+            option: "--pass".to_string(),
+            message: "the reqwest backend cannot read an encrypted PEM key; use a .p12 file".to_string(),
+        });
+    }
+    let (cert, key) = identity::pem_pair(config)?;
+    Ok(reqwest::Identity::from_pkcs8_pem(&cert, &key)?)
+}
+
+/// native-tls cannot be limited to TLS 1.3 only, so `--tlsv1.3` is
+/// refused; TLS 1.3 as the maximum is simply no limit.
+fn tls_version(version: TlsVersion, option: &str) -> Result<reqwest::tls::Version, RequestError> {
+    Ok(match version {
+        TlsVersion::V1_0 => reqwest::tls::Version::TLS_1_0,
+        TlsVersion::V1_1 => reqwest::tls::Version::TLS_1_1,
+        TlsVersion::V1_2 => reqwest::tls::Version::TLS_1_2,
+        TlsVersion::V1_3 => {
+            return Err(RequestError::Config {
+                option: option.to_string(),
+                message: "the reqwest backend (native-tls) cannot require TLS 1.3; use the curl backend".to_string(),
+            });
+        }
+    })
+}
+
+fn offers_basic(response: &reqwest::blocking::Response) -> bool {
+    let challenges: Vec<String> = response
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .map(|v| format!("WWW-Authenticate: {}", String::from_utf8_lossy(v.as_bytes())))
+        .collect();
+    crate::curl::auth::offered(&challenges).contains(&crate::curl::auth::Mechanism::Basic)
+}
+
+fn build_client(
+    config: &RequestConfig,
+    pinned: Option<&crate::curl::dns::Pinned>,
+) -> Result<reqwest::blocking::Client, RequestError> {
     let mut builder = reqwest::blocking::Client::builder();
 
+    if let Some(pinned) = pinned {
+        builder = builder.resolve_to_addrs(&pinned.host, &pinned.addrs);
+    }
+
     // Authentication
     if config.negotiate {
         if config.username.is_some() || config.password.is_some() {
@@ -118,19 +597,34 @@ fn build_client(config: &RequestConfig) -> Result<reqwest::blocking::Client, Req
         let cert = std::fs::read(path)?;
         builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&cert)?);
     }
+    if let Some(ref path) = config.cert {
+        builder = builder.identity(client_identity(config, path)?);
+    }
+    if let Some(min) = config.tls_min {
+        builder = builder.min_tls_version(tls_version(min, "--tlsv1.3")?);
+    }
+    if let Some(max) = config.tls_max
+        && max < TlsVersion::V1_3
+    {
+        builder = builder.max_tls_version(tls_version(max, "--tls-max")?);
+    }
+    if config.provenance || config.show_certs {
+        builder = builder.tls_info(true);
+    }
+    if config.http2 == Some(Http2::PriorKnowledge) {
+        builder = builder.http2_prior_knowledge();
+    }
 
     // Proxy
-    if let Some(ref proxy_url) = crate::curl::request::resolve_proxy(config) {
+    // SOCKS proxies need reqwest's `socks` feature
+    if let Some((ref proxy_url, _)) = crate::curl::proxy::resolve(config)? {
         let mut proxy = reqwest::Proxy::all(proxy_url)?;
 
-        // Proxy authentication
+        // Proxy authentication; for a SOCKS5 proxy, basic_auth sets its
+        // username and password
         if config.proxy_negotiate {
             proxy = proxy.negotiate_auth();
         } else if config.proxy_ntlm {
-            eprintln!(
-                "Warning: --proxy-ntlm is not supported with the reqwest backend. \
-                 Use the curl backend for proxy NTLM authentication."
-            );
             if let Some(ref user) = config.proxy_user {
                 let pass = config.proxy_password.as_deref().unwrap_or("");
                 proxy = proxy.basic_auth(user, pass);
@@ -140,10 +634,36 @@ fn build_client(config: &RequestConfig) -> Result<reqwest::blocking::Client, Req
             proxy = proxy.basic_auth(user, pass);
         }
 
+        if !config.proxy_headers.is_empty() {
+            let mut headers = reqwest::header::HeaderMap::new();
+            for h in &config.proxy_headers {
+                let Some((name, value)) = crate::curl::config::split_header(h) else {
+                    continue;
+                };
+                match (
+                    reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+                    reqwest::header::HeaderValue::from_str(value),
+                ) {
+                    (Ok(name), Ok(value)) => headers.append(name, value),
+                    _ => {
+                        return Err(RequestError::Config {
+                            option: "--proxy-header".to_string(),
+                            message: format!("invalid header '{h}'"),
+                        });
+                    }
+                };
+            }
+            proxy = proxy.headers(headers);
+        }
+
         builder = builder.proxy(proxy);
+    } else if config.env_isolated() {
+        // Keep reqwest from picking up *_proxy from the process
+        builder = builder.no_proxy();
     }
 
-    if let Some(_noproxy) = crate::curl::request::resolve_noproxy(config) {
+    // An empty list bypasses nothing (tunnels set it to override NO_PROXY)
+    if crate::curl::request::resolve_noproxy(config).is_some_and(|hosts| !hosts.trim().is_empty()) {
         builder = builder.no_proxy();
     }
 
@@ -152,9 +672,10 @@ fn build_client(config: &RequestConfig) -> Result<reqwest::blocking::Client, Req
         builder = builder.connect_timeout(d);
     }
 
-    if let Some(d) = config.max_time {
-        builder = builder.timeout(d);
-    }
+    // The blocking client applies this timeout to each connect/read/write,
+    // which is exactly an idle timeout. Without --read-timeout, don't let
+    // reqwest's 30s default kill long streaming downloads.
+    builder = builder.timeout(config.read_timeout);
 
     // SSL revocation checks: rustls (used by reqwest) does not perform
     // online CRL/OCSP revocation checks by default, so --ssl-no-revoke
@@ -162,22 +683,72 @@ fn build_client(config: &RequestConfig) -> Result<reqwest::blocking::Client, Req
     // Windows which does check revocation, hence needing the flag there.
 
     // Redirects
-    if let Some(max) = config.max_redirs {
+    if !config.follow_location {
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+    } else if let Some(max) = config.max_redirs {
         builder = builder.redirect(reqwest::redirect::Policy::limited(max as usize));
     }
 
-    // User agent
-    if let Some(ref ua) = config.user_agent {
-        builder = builder.user_agent(ua);
-    }
+    // User agent. ALPN already offers h2 ahead of http/1.1 as browsers
+    // do, so --impersonate has nothing to change at the TLS level here.
+    builder = builder.user_agent(config.effective_user_agent());
 
     Ok(builder.build()?)
 }
 
+/// Flatten an error and its sources into one message, since reqwest keeps
+/// the useful detail (hyper/rustls/io) in the source chain.
+fn error_chain(e: &reqwest::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
+
 // Convert reqwest errors to RequestError
 impl From<reqwest::Error> for RequestError {
     fn from(e: reqwest::Error) -> Self {
-        RequestError::Http(e.to_string())
+        let message = error_chain(&e);
+        let lower = message.to_lowercase();
+        let host = e
+            .url()
+            .and_then(|u| u.host_str())
+            .unwrap_or_default()
+            .to_string();
+
+        if e.is_timeout() {
+            let phase = if e.is_connect() {
+                TimeoutPhase::Connect
+            } else if e.is_body() || e.is_decode() {
+                TimeoutPhase::Read
+            } else {
+                TimeoutPhase::Total
+            };
+            RequestError::Timeout { phase, message }
+        } else if e.is_redirect() {
+            RequestError::TooManyRedirects { message }
+        } else if e.status().is_some_and(|s| s.as_u16() == 407) || lower.contains("407") {
+            RequestError::ProxyAuth { message }
+        } else if e.is_builder() {
+            RequestError::Config {
+                option: "client".to_string(),
+                message,
+            }
+        } else if lower.contains("dns error") || lower.contains("failed to lookup address") {
+            RequestError::Dns { host, proxy: false }
+        } else if lower.contains("certificate") || lower.contains("unknownissuer") {
+            RequestError::TlsVerify { message }
+        } else if lower.contains("tls") || lower.contains("handshake") {
+            RequestError::TlsHandshake { message }
+        } else if e.is_connect() {
+            RequestError::Connect { message }
+        } else {
+            RequestError::Transfer { message }
+        }
     }
 }
 
@@ -0,0 +1,763 @@
+// src/client.rs
+
+//! Entry point for library users: sends a `RequestConfig` through the
+//! compiled-in backend, with the same `--retry` handling as the CLI.
+//! A `Client` is `Send + Sync`; share one (e.g. in an `Arc`) so concurrent
+//! requests reuse the backend's pooled connections.
+
+use std::borrow::Cow;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::backend::{self, HttpBackend};
+use crate::curl::auth;
+use crate::curl::azure;
+use crate::curl::batch::{self, Spec};
+use crate::curl::body::RequestBody;
+use crate::curl::config::RequestConfig;
+use crate::curl::diagnose::{self, Check, Report};
+use crate::curl::env::EnvSource;
+use crate::curl::eol::{self, CrlfReader};
+use crate::curl::error::RequestError;
+use crate::curl::changed;
+use crate::curl::charset;
+use crate::curl::collection::{self, Collection};
+use crate::curl::content_digest;
+use crate::curl::extract;
+use crate::curl::gcs;
+use crate::curl::jwe;
+use crate::curl::messages;
+use crate::curl::mime;
+use crate::curl::notify::{self, Debouncer, StatusClass, Transition};
+use crate::curl::oauth;
+use crate::curl::pac;
+use crate::curl::prom;
+use crate::curl::provenance;
+use crate::curl::registry;
+use crate::curl::relay::{self, Head, Tunnel};
+use crate::curl::response::{ParallelOutcome, Response, StreamingResponse, TransferSummary};
+use crate::curl::retry;
+use crate::curl::s3;
+use crate::curl::session;
+use crate::curl::signature;
+use crate::curl::sla;
+use crate::curl::rng::Rng;
+use crate::curl::ssh;
+use crate::curl::tail::{self, Poll};
+use crate::curl::tofu;
+use crate::curl::tor;
+use crate::curl::url;
+use crate::curl::warning::{self, Warning, WarningKind};
+use crate::curl::watch::Watcher;
+
+pub struct Client {
+    backend: Box<dyn HttpBackend>,
+    env: Option<EnvSource>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// Client using the default backend for this build.
+    pub fn new() -> Self {
+        Self::with_backend(backend::get_backend())
+    }
+
+    pub fn with_backend(backend: Box<dyn HttpBackend>) -> Self {
+        Self { backend, env: None }
+    }
+
+    /// Resolve environment fallbacks for every request from `env`, unless
+    /// the request sets its own with `RequestConfig::env`.
+    pub fn env(mut self, env: EnvSource) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    pub fn backend(&self) -> &dyn HttpBackend {
+        self.backend.as_ref()
+    }
+
+    fn scoped<'a>(&self, config: &'a RequestConfig) -> Cow<'a, RequestConfig> {
+        match (&self.env, &config.env) {
+            (Some(env), None) => Cow::Owned(config.clone().env(env.clone())),
+            _ => Cow::Borrowed(config),
+        }
+    }
+
+    /// Send the request and buffer the whole response (the body is written
+    /// out as it arrives instead when `output` is set), retrying as
+    /// configured with `RequestConfig::retry` and, with `auth_chain`,
+    /// escalating through the listed mechanisms on 401. A bearer token
+    /// rejected with 401 is refreshed once when `oauth2_token_url` is set;
+    /// with `registry_auth` one is fetched from the challenge's realm.
+    /// With `ssh_tunnel` the exchange goes through an SSH jump host, with
+    /// `tor` through a local Tor SOCKS port, with `proxy_pac` through the
+    /// proxy its script picks.
+    ///
+    /// Afterwards, in order: timing assertions are checked, a `remote_name`
+    /// body is saved, the download is verified against `verify_sig`, a
+    /// JWE body is decrypted with `decrypt_jwe`, a text body is transcoded
+    /// to `output_charset` and its line endings rewritten per
+    /// `normalize_eol`, a `provenance` record is written and, with
+    /// `extract`, a saved archive is unpacked. With `prom_out` the outcome,
+    /// success or not, is written as Prometheus metrics, and with `session`
+    /// it is added to that session's totals. Problems that do not fail the
+    /// request are collected in `Response::warnings`.
+    pub fn send(&self, config: &RequestConfig) -> Result<Response, RequestError> {
+        let clock = Instant::now();
+        let mut result = self.send_checked(config, clock);
+        if let Some(ref path) = config.prom_out {
+            let bytes = result.as_ref().map_or(0, body_size);
+            let written = prom::write(path, &prom::render(&config.url, &result, bytes, clock.elapsed()));
+            // A failed probe is still reported as the request's error
+            if let (Err(e), Ok(_)) = (written, &result) {
+                return Err(e);
+            }
+        }
+        if let Some(ref name) = config.session
+            && let Err(e) = session::record(&self.scoped(config).env.clone().unwrap_or_default(), name, &result)
+        {
+            // Accounting never fails the request itself
+            let warning = Warning::new(WarningKind::Session, format!("--session {name}: {e}"));
+            match result {
+                Ok(ref mut response) => response.warnings.push(warning),
+                Err(_) => warn(config, &warning),
+            }
+        }
+        if !config.show_timing
+            && config.write_out.is_none()
+            && let Ok(ref mut response) = result
+        {
+            // Collected only for assertions and metrics
+            response.timing = None;
+        }
+        result
+    }
+
+    /// `config` sent through its `ssh_tunnel` (opened here, and closed
+    /// when the returned tunnel is dropped), through Tor or through the
+    /// proxy its PAC script picks.
+    fn route<'a>(
+        &self,
+        config: &'a RequestConfig,
+    ) -> Result<(Cow<'a, RequestConfig>, Option<ssh::Tunnel>), RequestError> {
+        if let Some(ref dest) = config.ssh_tunnel {
+            let tunnel = ssh::Tunnel::open(dest, config.connect_timeout)?;
+            config.note(format_args!("SSH tunnel through {dest} at {}", tunnel.proxy_url()));
+            Ok((Cow::Owned(tunnel.route(config)), Some(tunnel)))
+        } else if config.tor {
+            let port = tor::detect().ok_or_else(|| RequestError::Connect {
+                message: "no Tor SOCKS proxy on 127.0.0.1:9050 or 9150; is tor running?".to_string(),
+            })?;
+            config.note(format_args!("Routing through Tor at 127.0.0.1:{port}"));
+            Ok((Cow::Owned(tor::apply(config, port, &mut Rng::new(config.seed))), None))
+        } else if let Some(ref location) = config.proxy_pac
+            && config.proxy.is_none()
+        {
+            let script = pac::load(location, |url| {
+                let fetch = RequestConfig::new(url).follow_location(true).noproxy("*");
+                let response = self.backend.perform_request(&self.scoped(&fetch))?;
+                match response.status_code {
+                    200..=299 => Ok(response.body),
+                    code => Err(RequestError::Config {
+                        option: "--proxy-pac".to_string(),
+                        message: format!("{url}: server returned HTTP {code}"),
+                    }),
+                }
+            })?;
+            let routed = match pac::route(&script, location, &config.request_url())? {
+                pac::Route::Direct => config.clone().noproxy("*"),
+                pac::Route::Proxy(ref proxy) => config.clone().proxy(proxy),
+            };
+            let via = routed.proxy.as_deref().unwrap_or("DIRECT");
+            config.note(format_args!("PAC {location} chose {via} for {}", config.url));
+            Ok((Cow::Owned(routed), None))
+        } else {
+            Ok((Cow::Borrowed(config), None))
+        }
+    }
+
+    fn send_checked(&self, config: &RequestConfig, clock: Instant) -> Result<Response, RequestError> {
+        let started = SystemTime::now();
+        // The SSH tunnel is held until the exchange (and any token refresh) is done
+        let (routed, _tunnel) = self.route(config)?;
+        let config = &*routed;
+        let mut response = self.perform_tofu(config)?;
+        response.warnings.splice(0..0, warning::for_config(config));
+        if let Some(ref version) = response.http_version {
+            config.note(format_args!("Response over HTTP/{version}"));
+        }
+        sla::check(config, response.timing.as_ref(), clock.elapsed())?;
+        let named;
+        let config = if config.remote_name && config.output.is_none() {
+            named = save_remote_name(config, &mut response)?;
+            &named
+        } else {
+            config
+        };
+        response.saved_as = config.output_path();
+        if let (Some(sig), Some(key)) = (&config.verify_sig, &config.verify_key) {
+            self.verify_signature(config, &response, sig, key)?;
+        }
+        if let Some(ref key) = config.decrypt_jwe {
+            match jwe::open(key, &mut response)? {
+                Some(enc) => config.note(format_args!("Decrypted the JWE body ({})", enc.name())),
+                None => config.note("The body is not a JWE; left as it is"),
+            }
+        }
+        if let Some(ref label) = config.output_charset
+            && let Some(from) = charset::apply(label, &mut response)?
+        {
+            config.note(format_args!("Transcoded body from {} to {label}", from.name()));
+        }
+        if let Some(eol) = config.normalize_eol
+            && eol::apply(eol, &mut response)?
+        {
+            config.note(format_args!("Normalized line endings to {eol}"));
+        }
+        if config.provenance
+            && let Some(file) = config.output_path()
+        {
+            let record = provenance::write(config, &response, &file, self.backend.name(), started)?;
+            config.note(format_args!("Provenance written to {}", record.display()));
+        }
+        if config.extract
+            && let (Some(archive), Some(dest)) = (config.output_path(), config.extract_dir())
+        {
+            let written = extract::extract(&archive, &dest, config.extract_overwrite)?;
+            config.note(format_args!("Extracted {} entries into {}", written.len(), dest.display()));
+        }
+        Ok(response)
+    }
+
+    /// `perform`, with `tofu` on an https URL: the key the store holds for
+    /// the server is pinned, or, the first time, whatever key it presents
+    /// is accepted and stored.
+    fn perform_tofu(&self, config: &RequestConfig) -> Result<Response, RequestError> {
+        let Some(authority) = config.tofu.then(|| tofu::authority(&config.url)).flatten() else {
+            return self.perform(config);
+        };
+        let store_error = |e: std::io::Error| RequestError::Config {
+            option: "--tofu".to_string(),
+            message: format!("trust store: {e}"),
+        };
+        let path = tofu::store(&self.scoped(config).env.clone().unwrap_or_default()).map_err(store_error)?;
+        // libcurl checks the pin during the handshake, in place of the CA chain
+        let unchecked = config.clone().insecure(true);
+        if let Some(entry) = tofu::find(&path, &authority).map_err(store_error)? {
+            return self.perform(&unchecked.pinned_pubkey(&entry.pin)).map_err(|e| tofu::check_failure(&entry, e));
+        }
+        let mut response = self.perform(&unchecked)?;
+        let leaf = response.peer_certificates.first().ok_or_else(|| RequestError::Config {
+            option: "--tofu".to_string(),
+            message: format!("{} did not report the server certificate", self.backend.name()),
+        })?;
+        let (entry, added) = tofu::remember(&path, &authority, leaf).map_err(store_error)?;
+        if !added && !tofu::matches(&entry, leaf) {
+            // Another run trusted a different key in the meantime
+            return Err(tofu::check_failure(&entry, RequestError::TlsVerify { message: String::new() }));
+        }
+        if added {
+            response.warnings.push(Warning::new(
+                WarningKind::Tofu,
+                format!("trusted {authority} on first use; certificate sha256 {}", entry.sha256),
+            ));
+        }
+        Ok(response)
+    }
+
+    /// One exchange with retries, walking `auth_chain` on 401s if set,
+    /// or refreshing a rejected bearer token at `oauth2_token_url`. The
+    /// body is encrypted for `encrypt_jwe` first, then digest headers added. An `s3://` URL is signed for S3, a
+    /// `gs://` or `az://` one swapped for a signed https URL, and an
+    /// `upload_file` streamed.
+    fn perform(&self, config: &RequestConfig) -> Result<Response, RequestError> {
+        if let Some(sealed) = jwe::seal(config)? {
+            return self.perform(&sealed);
+        }
+        if let Some(digested) = content_digest::apply(config)? {
+            return self.perform(&digested);
+        }
+        let attempt = |c: &RequestConfig| retry::perform_with_retry(&self.scoped(c), |c| self.backend.perform_request(c));
+        if gcs::is_gcs(&config.url) {
+            return self.perform(&gcs::request(&self.scoped(config))?);
+        }
+        if azure::is_azure(&config.url) {
+            return self.perform(&azure::request(&self.scoped(config))?);
+        }
+        if s3::is_s3(&config.url) {
+            return s3::perform(&self.scoped(config), attempt);
+        }
+        if let Some(ref path) = config.upload_file {
+            let file = fs::File::open(path)?;
+            let len = file.metadata()?.len();
+            return self.send_body(config, RequestBody::sized(file, len));
+        }
+        if !config.auth_chain.is_empty() {
+            return auth::perform(&self.scoped(config), attempt);
+        }
+        if config.registry_auth {
+            return registry::perform(&self.scoped(config), attempt);
+        }
+        let response = attempt(config)?;
+        match config.oauth2_token_url {
+            Some(ref token_url) if response.status_code == 401 && config.bearer.is_some() => {
+                let fetch = oauth::refresh_request(&self.scoped(config), token_url)?;
+                let token = oauth::access_token(token_url, &attempt(&fetch)?)?;
+                config.note(format_args!("Bearer token rejected (401), refreshed at {token_url}; retrying"));
+                attempt(&config.clone().bearer(&token))
+            }
+            _ => Ok(response),
+        }
+    }
+
+    fn verify_signature(
+        &self,
+        config: &RequestConfig,
+        response: &Response,
+        sig: &str,
+        key: &str,
+    ) -> Result<(), RequestError> {
+        let err = |message: String| RequestError::Signature {
+            signature: sig.to_string(),
+            message,
+        };
+        let signature = if sig.starts_with("http://") || sig.starts_with("https://") {
+            let fetch = signature::fetch_config(config, sig);
+            let fetched = self.backend.perform_request(&self.scoped(&fetch))?;
+            if !(200..300).contains(&fetched.status_code) {
+                return Err(err(format!("server returned HTTP {}", fetched.status_code)));
+            }
+            fetched.body
+        } else {
+            fs::read(sig).map_err(|e| err(e.to_string()))?
+        };
+        let artifact = match config.output_path() {
+            Some(path) => fs::read(&path).map_err(|e| err(format!("{}: {e}", path.display())))?,
+            None => response.body.clone(),
+        };
+        let format = signature::verify(&artifact, &signature, key, sig)?;
+        config.note(format_args!("{format:?} signature {sig} verified"));
+        Ok(())
+    }
+
+    /// A URL granting `config`'s method on its `gs://` or `az://` object
+    /// for `expires`, to hand to someone without the credentials.
+    pub fn presign(&self, config: &RequestConfig, expires: Duration) -> Result<String, RequestError> {
+        let config = self.scoped(config);
+        let signed = if gcs::is_gcs(&config.url) {
+            gcs::signed_url(&config, expires, SystemTime::now())
+        } else if azure::is_azure(&config.url) {
+            azure::signed_url(&config, expires, SystemTime::now())
+        } else {
+            Err("needs a gs:// or az:// URL".to_string())
+        };
+        signed.map_err(|message| RequestError::Config {
+            option: "--presign".to_string(),
+            message,
+        })
+    }
+
+    /// Send the request with a body read from `body` instead of the
+    /// config's data. Not retried, since the body cannot be replayed.
+    /// With `crlf` the body is sent chunked, its length having changed.
+    pub fn send_body(&self, config: &RequestConfig, body: RequestBody) -> Result<Response, RequestError> {
+        if config.body().is_some() {
+            return Err(RequestError::Config {
+                option: "body".to_string(),
+                message: "a streamed body cannot be combined with -d/form data".to_string(),
+            });
+        }
+        let body = if config.crlf {
+            RequestBody::new(CrlfReader::new(body.reader))
+        } else {
+            body
+        };
+        self.backend.perform_upload(&self.scoped(config), body)
+    }
+
+    /// Send the request and write the body into `writer` as it arrives
+    /// (a socket, compressor or hasher), without buffering it or touching
+    /// the filesystem. Not retried, since part of the body may already be
+    /// written.
+    pub fn send_to(&self, config: &RequestConfig, mut writer: impl Write) -> Result<TransferSummary, RequestError> {
+        self.backend.perform_to(&self.scoped(config), &mut writer)
+    }
+
+    /// Send all requests concurrently, reusing connections where the
+    /// backend can (one multiplexed connection per HTTP/2 origin).
+    /// Results are in request order; retries are not applied.
+    pub fn send_parallel(&self, configs: &[RequestConfig]) -> ParallelOutcome {
+        let scoped: Vec<RequestConfig> = configs.iter().map(|c| self.scoped(c).into_owned()).collect();
+        self.backend.perform_parallel(&scoped)
+    }
+
+    /// Send the request and return as soon as the status and headers are
+    /// in; the body is read from the returned `StreamingResponse`.
+    /// Retries are not applied, since the body cannot be replayed.
+    pub fn send_streaming(&self, config: &RequestConfig) -> Result<StreamingResponse, RequestError> {
+        self.backend.perform_streaming(&self.scoped(config))
+    }
+
+    /// Read one JSON request spec per line from `input`, send each with
+    /// `config`'s options, and write one JSON summary line per request to
+    /// `out` as soon as it completes. Bad specs and failed requests get an
+    /// `error` line and the batch goes on; blank lines are skipped.
+    pub fn batch(&self, config: &RequestConfig, input: impl BufRead, mut out: impl Write) -> std::io::Result<()> {
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let summary = match Spec::parse(&line) {
+                Ok(spec) => {
+                    let clock = Instant::now();
+                    let result = self.send(&spec.apply(config));
+                    batch::summary(i + 1, &spec.id, result.as_ref(), clock.elapsed())
+                }
+                Err(message) => batch::invalid(i + 1, &message),
+            };
+            writeln!(out, "{summary}")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Run `suite`'s steps with `config`'s options, each once its
+    /// `depends_on` steps have passed and up to `parallel` at a time, and
+    /// write one JSON line per step to `out` as it finishes. A step that
+    /// does not pass is tried again up to its `retry` count, waiting
+    /// `--retry-delay` in between. Returns whether every step passed.
+    pub fn collection(&self, config: &RequestConfig, suite: &Collection, mut out: impl Write) -> std::io::Result<bool> {
+        let steps = &suite.steps;
+        let run = |i: usize| {
+            let step = &steps[i];
+            let request = step.apply(config);
+            let clock = Instant::now();
+            let mut attempts = 1;
+            loop {
+                let result = self.send(&request);
+                let passed = step.passed(result.as_ref());
+                if passed || attempts > step.retry {
+                    return (passed, collection::summary(step, result.as_ref(), attempts, clock.elapsed()));
+                }
+                let tries = step.retry + 1;
+                config.note(format_args!("step '{}' did not pass, attempt {attempts} of {tries}", step.name));
+                std::thread::sleep(config.retry_delay.unwrap_or(collection::DEFAULT_RETRY_DELAY));
+                attempts += 1;
+            }
+        };
+        collection::schedule(&suite.dependencies(), suite.parallel, run, |i, result| {
+            let line = match result {
+                Ok(summary) => summary,
+                Err(dep) => collection::skipped(&steps[i], &steps[dep]),
+            };
+            writeln!(out, "{line}")?;
+            out.flush()
+        })
+    }
+
+    /// Serve `listener` as a forward proxy: each client's request is sent
+    /// on with `config`'s options and its response streamed back, and
+    /// `CONNECT` opens a tunnel through the configured proxy. Connections
+    /// are served on their own threads, at most `relay::MAX_CONNECTIONS`
+    /// at once; returns only if accepting fails. A listener bound to
+    /// anything but loopback is refused unless `config.relay_remote`.
+    /// An `ssh_tunnel` or Tor route is set up once and serves every
+    /// connection, tunnels included.
+    pub fn relay(&self, config: &RequestConfig, listener: TcpListener) -> std::io::Result<()> {
+        relay::check_listener(&listener, config.relay_remote)?;
+        let (routed, _tunnel) = self.route(config).map_err(std::io::Error::other)?;
+        let config = &*routed;
+        let slots = relay::Slots::new(relay::MAX_CONNECTIONS);
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = stream?;
+                let slot = slots.acquire();
+                scope.spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = self.relay_one(config, stream) {
+                        config.note(format_args!("relay: {e}"));
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+
+    fn relay_one(&self, config: &RequestConfig, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let head = match Head::read(&mut reader) {
+            Ok(Some(head)) => head,
+            Ok(None) => return Ok(()),
+            Err(message) => return stream.write_all(relay::error_reply("400 Bad Request", &message).as_bytes()),
+        };
+        config.note(format_args!("relay: {} {}", head.method, head.target));
+        if head.method.eq_ignore_ascii_case("CONNECT") {
+            let tunnel = match self.open_tunnel(config, &head.target) {
+                Ok(tunnel) => tunnel,
+                // The backend cannot tunnel at all
+                Err(e @ RequestError::Config { .. }) => {
+                    return stream.write_all(relay::error_reply("501 Not Implemented", &e.to_string()).as_bytes());
+                }
+                Err(e) => return stream.write_all(relay::error_reply("502 Bad Gateway", &e.to_string()).as_bytes()),
+            };
+            stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")?;
+            return relay::splice(&mut reader, stream, &tunnel);
+        }
+        let length = match head.content_length() {
+            Ok(length) => length,
+            Err(message) => return stream.write_all(relay::error_reply("411 Length Required", &message).as_bytes()),
+        };
+        if length > relay::MAX_BODY {
+            let message = format!("request bodies over {} bytes are not relayed", relay::MAX_BODY);
+            return stream.write_all(relay::error_reply("413 Content Too Large", &message).as_bytes());
+        }
+        if length > 0
+            && head
+                .get_header("Expect")
+                .is_some_and(|v| v.eq_ignore_ascii_case("100-continue"))
+        {
+            stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let forwarded = match head.forward_config(config, body) {
+            Ok(forwarded) => forwarded,
+            Err(message) => return stream.write_all(relay::error_reply("400 Bad Request", &message).as_bytes()),
+        };
+        match self.send_streaming(&forwarded) {
+            Ok(mut response) => {
+                let head = relay::response_head(response.status_code, &response.headers);
+                relay::reply(&stream, &head, &mut response.body)
+            }
+            Err(e) => stream.write_all(relay::error_reply("502 Bad Gateway", &e.to_string()).as_bytes()),
+        }
+    }
+
+    /// Retrace the way to `config.url` one step at a time for
+    /// `--diagnose`: the proxy and, for https, the time its `CONNECT`
+    /// takes beyond reaching it; or DNS and a TCP connect to every
+    /// address. Then TLS and a single HEAD through the backend with the
+    /// request's proxy, auth and TLS options.
+    pub fn diagnose(&self, config: &RequestConfig) -> Report {
+        let (routed, _tunnel) = match self.route(config) {
+            Ok(route) => route,
+            Err(e) => {
+                return Report {
+                    url: config.url.clone(),
+                    checks: vec![Check::failed("route", &config.url, e.to_string())],
+                };
+            }
+        };
+        let mut checks = diagnose::network(&routed);
+        let to_proxy = checks.iter().find(|c| c.step == "proxy" && c.result.is_ok()).map(|c| c.elapsed);
+        if let (Some(to_proxy), Some(authority)) = (to_proxy, diagnose::tunnel_authority(&routed.url, routed.proxy_tunnel)) {
+            let clock = Instant::now();
+            let tunnel = self.open_tunnel(&routed, &authority);
+            checks.push(diagnose::tunnel_check(&authority, to_proxy, tunnel, clock.elapsed()));
+        }
+        let probe = diagnose::probe_config(&routed);
+        let clock = Instant::now();
+        let result = self.backend.perform_to(&self.scoped(&probe), &mut std::io::sink());
+        checks.push(diagnose::request_check(&probe, result, clock.elapsed()));
+        Report {
+            url: config.url.clone(),
+            checks,
+        }
+    }
+
+    /// Connect to `authority` (`host:port`) through `config`'s proxy, with
+    /// its proxy authentication, for a `CONNECT` tunnel. Needs the curl
+    /// backend.
+    pub fn open_tunnel(&self, config: &RequestConfig, authority: &str) -> Result<Tunnel, RequestError> {
+        self.backend.open_tunnel(&self.scoped(config), authority)
+    }
+
+    /// Send the request every `RequestConfig::watch` interval, writing a
+    /// status line per attempt to `out`. Failed attempts are reported and
+    /// watching goes on; stops after `attempts`, or never with `None`.
+    /// With `if_changed`, attempts whose body matches the stored state
+    /// print nothing.
+    pub fn watch(
+        &self,
+        config: &RequestConfig,
+        attempts: Option<u64>,
+        color: bool,
+        mut out: impl Write,
+    ) -> std::io::Result<()> {
+        let interval = config.watch.unwrap_or_default();
+        let mut watcher = Watcher::new(config.watch_diff, color);
+        let notifying = config.notify_cmd.is_some() || config.notify_desktop;
+        let mut debouncer = Debouncer::new(config.notify_debounce);
+        let mut made = 0;
+        loop {
+            let clock = Instant::now();
+            let result = self.send(config);
+            let total = clock.elapsed();
+            let body = match result {
+                Ok(ref r) => r.received_body(),
+                Err(_) => Cow::Borrowed(&[][..]),
+            };
+            let changed = match (&result, &config.if_changed) {
+                (Ok(_), Some(state)) => changed::update(state, &body).map_err(std::io::Error::other)?,
+                _ => true,
+            };
+            if changed {
+                writeln!(out, "{}", watcher.line(SystemTime::now(), &result, total, &body))?;
+                out.flush()?;
+            }
+            if notifying && let Some(transition) = debouncer.observe(StatusClass::of(&result)) {
+                notify_transition(config, &transition, &result);
+            }
+            made += 1;
+            if attempts.is_some_and(|max| made >= max) {
+                return Ok(());
+            }
+            std::thread::sleep(interval.saturating_sub(clock.elapsed()));
+        }
+    }
+    /// Follow the file at `config`'s URL as it grows (`--tail`), writing
+    /// its bytes to `out` as they appear: all of it first, then what each
+    /// ranged poll finds. Failed polls are reported and following goes
+    /// on; stops after `polls`, or never with `None`.
+    pub fn tail(&self, config: &RequestConfig, polls: Option<u64>, mut out: impl Write) -> std::io::Result<()> {
+        let interval = config.tail.unwrap_or_default();
+        let mut follower = tail::Follower::new();
+        let mut made = 0;
+        loop {
+            let mut request = config.clone();
+            request.range = follower.range();
+            match self.send(&request) {
+                Ok(response) => match follower.observe(&response) {
+                    Poll::New(bytes) => {
+                        out.write_all(bytes)?;
+                        out.flush()?;
+                    }
+                    Poll::Truncated(bytes) => {
+                        let message = format!("{} was truncated; following from the start", config.url);
+                        warn(config, &Warning::new(WarningKind::Tail, message));
+                        out.write_all(bytes)?;
+                        out.flush()?;
+                    }
+                    Poll::Unchanged => {}
+                    Poll::Failed(status) => {
+                        warn(config, &Warning::new(WarningKind::Tail, format!("server returned HTTP {status}")));
+                    }
+                },
+                Err(e) => {
+                    follower.failed();
+                    warn(config, &Warning::new(WarningKind::Tail, e.to_string()));
+                }
+            }
+            made += 1;
+            if polls.is_some_and(|max| made >= max) {
+                return Ok(());
+            }
+            std::thread::sleep(follower.delay(interval));
+        }
+    }
+}
+
+/// Run the configured `--notify-*` hooks; a failing hook is reported but
+/// does not stop watching.
+fn notify_transition(config: &RequestConfig, transition: &Transition, result: &Result<Response, RequestError>) {
+    let detail = match result {
+        Ok(response) => response.status_code.to_string(),
+        Err(e) => e.code().to_string(),
+    };
+    if let Some(ref cmd) = config.notify_cmd
+        && let Err(e) = notify::run_command(cmd, &config.url, transition, &detail)
+    {
+        warn(config, &Warning::new(WarningKind::Notify, e.to_string()));
+    }
+    if config.notify_desktop
+        && let Err(e) = notify::desktop(&config.url, transition, &detail)
+    {
+        warn(config, &Warning::new(WarningKind::Notify, e.to_string()));
+    }
+}
+
+/// Print a warning that has no `Response` to ride on, the way the CLI
+/// prints a response's: in `--message-format`, unless `-s`.
+fn warn(config: &RequestConfig, warning: &Warning) {
+    if !config.silent {
+        eprintln!("{}", messages::render_warning(warning, config.message_format, messages::Locale::from_env()));
+    }
+}
+
+/// Body bytes received, as the backend counted them (`Stats::add` in
+/// `session` does the same), since a body streamed to stdout leaves
+/// nothing behind to measure. Without a count: the buffered body, or the
+/// saved file's size.
+fn body_size(response: &Response) -> u64 {
+    if let Some(ref transfer) = response.transfer {
+        return transfer.size_download;
+    }
+    match response.saved_as {
+        Some(ref path) if response.body.is_empty() => fs::metadata(path).map_or(0, |m| m.len()),
+        _ => response.body.len() as u64,
+    }
+}
+
+/// `-O`: write the buffered body to a file named after the URL, or with
+/// `-J` the Content-Disposition name, with the extension inferred by
+/// `mime::infer_name`, and return the config with `output` pointing at it.
+/// A server-chosen name never overwrites an existing file.
+fn save_remote_name(config: &RequestConfig, response: &mut Response) -> Result<RequestConfig, RequestError> {
+    let from_header = response
+        .get_header("content-disposition")
+        .filter(|_| config.remote_header_name)
+        .and_then(|value| mime::disposition_filename(&value));
+    let base = from_header.as_deref().or_else(|| url::file_name(&config.url));
+    let content_type = response.get_header("content-type");
+    let inferred = mime::infer_name(base, content_type.as_deref(), &response.body, config.mime_sniff);
+    if let Some(warning) = inferred.warning {
+        response.warnings.push(Warning::new(WarningKind::FileName, warning));
+    }
+    let named = config.clone().output(&inferred.name);
+    let path = named.output_path().unwrap_or_default();
+    let written = if from_header.is_some() {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&response.body))
+    } else {
+        fs::write(&path, &response.body)
+    };
+    written.map_err(|source| RequestError::WriteOutput {
+        path: path.display().to_string(),
+        source,
+    })?;
+    response.body.clear();
+    Ok(named)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Client>();
+    }
+
+    #[test]
+    fn client_env_applies_unless_request_overrides() {
+        let client = Client::new().env(EnvSource::Disabled);
+        let plain = RequestConfig::new("https://x.com");
+        assert_eq!(client.scoped(&plain).env, Some(EnvSource::Disabled));
+
+        let own = RequestConfig::new("https://x.com").env(EnvSource::from_pairs([("A", "1")]));
+        assert_eq!(client.scoped(&own).env, own.env);
+    }
+}
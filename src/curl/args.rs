@@ -1,6 +1,26 @@
 // src/curl/args.rs
 
-use super::config::{Method, RequestConfig};
+use std::path::{Path, PathBuf};
+
+use super::auth::Mechanism;
+use super::capture::Capture;
+use super::charset;
+use super::config::{Http2, Method, RequestConfig, TlsVersion, urlencode_bytes};
+use super::content_digest::DigestAlgorithm;
+use super::docs;
+use super::env::EnvSource;
+use super::eol::Eol;
+use super::glob;
+use super::identity::CertType;
+use super::messages::MessageFormat;
+use super::netrc;
+use super::presets::{self, Preset};
+use super::s3;
+use super::secret::{self, Secret};
+use super::session;
+use super::writeout;
+#[cfg(test)]
+use super::cookie::CookieSource;
 
 pub fn parse_credentials(input: &str) -> (String, Option<String>) {
     match input.split_once(':') {
@@ -10,62 +30,7 @@ pub fn parse_credentials(input: &str) -> (String, Option<String>) {
 }
 
 pub fn print_usage() {
-    eprintln!("Usage: rustcurl [OPTIONS] <URL>");
-    eprintln!();
-    eprintln!("Options:");
-    eprintln!("  -X, --request <METHOD>   HTTP method (GET, POST, PUT, DELETE, HEAD, PATCH, OPTIONS)");
-    eprintln!("  -H, --header <HEADER>    Add header (repeatable), e.g. -H \"Content-Type: application/json\"");
-    eprintln!("  -d, --data <DATA>        Request body data (auto-sets POST if no -X given)");
-    eprintln!("  -o, --output <FILE>      Write response body to file");
-    eprintln!("  -I, --head               Send HEAD request (show headers only)");
-    eprintln!("  -s, --silent             Silent mode (only output body)");
-    eprintln!("  -A, --user-agent <STR>   Set User-Agent header");
-    eprintln!("  -b, --cookie <FILE>      Read cookies from file");
-    eprintln!("  -c, --cookie-jar <FILE>  Write cookies to file after request");
-    eprintln!("  --negotiate              Enable Kerberos/SPNEGO authentication");
-    eprintln!("  --ntlm                   Enable NTLM authentication");
-    eprintln!("  -k, --insecure           Ignore SSL certificate verification");
-    eprintln!("  --cacert <PATH>          Path to CA certificate bundle");
-    eprintln!("  -u, --user <USER:PASS>   Credentials (user:password)");
-    eprintln!("  --bearer <TOKEN>         Bearer token authentication");
-    eprintln!("  -x, --proxy <URL>        Proxy URL");
-    eprintln!("  --proxy-user <USER:PASS> Proxy credentials");
-    eprintln!("  --proxy-negotiate        Enable Kerberos/SPNEGO proxy authentication");
-    eprintln!("  --proxy-ntlm             Enable NTLM proxy authentication");
-    eprintln!("  --proxy-insecure         Skip SSL verification for proxy connection");
-    eprintln!("  --proxy-cacert <PATH>    CA certificate for proxy SSL verification");
-    eprintln!("  --noproxy <HOSTS>        Comma-separated list of hosts to bypass proxy");
-    eprintln!("  --connect-timeout <SECS> Connection timeout in seconds");
-    eprintln!("  --max-time <SECS>        Maximum total time in seconds");
-    eprintln!("  --max-redirs <N>         Maximum number of redirects");
-    eprintln!("  -L, --location           Follow redirects (always enabled)");
-    eprintln!("  --ssl-no-revoke          Disable certificate revocation checks");
-    eprintln!("  --compressed             Request compressed response");
-    eprintln!("  --timing                 Show timing information");
-    eprintln!("  --resolve <H:P:A>        Resolve host:port to address (repeatable)");
-    eprintln!("  -v, --verbose            Verbose output");
-    eprintln!("  -h, --help               Show this help");
-    eprintln!();
-    eprintln!("Environment variables:");
-    eprintln!("  RUSTCURL_USER            Username fallback");
-    eprintln!("  RUSTCURL_PASSWORD        Password fallback");
-    eprintln!("  HTTPS_PROXY              HTTPS proxy URL");
-    eprintln!("  HTTP_PROXY               HTTP proxy URL");
-    eprintln!("  ALL_PROXY                Proxy for all protocols");
-    eprintln!("  NO_PROXY                 Hosts to bypass proxy");
-}
-
-fn parse_method(s: &str) -> Method {
-    match s.to_uppercase().as_str() {
-        "GET" => Method::Get,
-        "POST" => Method::Post,
-        "PUT" => Method::Put,
-        "DELETE" => Method::Delete,
-        "HEAD" => Method::Head,
-        "PATCH" => Method::Patch,
-        "OPTIONS" => Method::Options,
-        other => Method::Custom(other.to_string()),
-    }
+    eprint!("{}", docs::usage());
 }
 
 fn next_arg<'a>(args: &'a [String], i: &mut usize, name: &str) -> Result<&'a str, String> {
@@ -75,11 +40,194 @@ fn next_arg<'a>(args: &'a [String], i: &mut usize, name: &str) -> Result<&'a str
         .ok_or_else(|| format!("{name} requires a value"))
 }
 
-fn parse_seconds(s: &str, name: &str) -> Result<std::time::Duration, String> {
-    let secs: u64 = s
-        .parse()
-        .map_err(|_| format!("{name} requires a number of seconds"))?;
-    Ok(std::time::Duration::from_secs(secs))
+/// Parse a duration flag value: plain (possibly fractional) seconds like
+/// `30` or `0.5`, or a number with a `ms`, `s`, `m` or `h` suffix.
+/// Nonzero values under a millisecond round up to 1ms, since libcurl
+/// takes milliseconds and reads 0 as no timeout.
+pub fn parse_duration(s: &str, name: &str) -> Result<std::time::Duration, String> {
+    let err = || format!("{name} requires a duration, e.g. 30, 0.5, 500ms, 2m, 1h");
+    let s = s.trim();
+    let (num, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60.0)
+    } else if let Some(n) = s.strip_suffix('h') {
+        (n, 3600.0)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = num.trim().parse().map_err(|_| err())?;
+    let duration = std::time::Duration::try_from_secs_f64(value * scale).map_err(|_| err())?;
+    if !duration.is_zero() && duration < std::time::Duration::from_millis(1) {
+        return Ok(std::time::Duration::from_millis(1));
+    }
+    Ok(duration)
+}
+
+/// Resolve a `-d`/`--data-binary` value: `@file` reads the file (or stdin
+/// for `@-`). With `strip`, carriage returns and newlines are removed, like
+/// curl does for `--data`; `--data-binary` keeps the bytes as they are.
+fn read_data_arg(val: &str, strip: bool) -> Result<Vec<u8>, String> {
+    let Some(path) = val.strip_prefix('@') else {
+        return Ok(val.as_bytes().to_vec());
+    };
+    let mut content = if path == "-" {
+        let mut buf = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut buf)
+            .map_err(|e| format!("failed to read data from stdin: {e}"))?;
+        buf
+    } else {
+        std::fs::read(path).map_err(|e| format!("failed to read data file {path}: {e}"))?
+    };
+    if strip {
+        content.retain(|&b| b != b'\r' && b != b'\n');
+    }
+    Ok(content)
+}
+
+/// The headers in `path` (stdin for `-`), one per line, so long tokens
+/// stay out of the process list. Blank lines are skipped and a trailing
+/// carriage return is dropped.
+fn read_header_file(path: &str) -> Result<Vec<String>, String> {
+    let text = if path == "-" {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| format!("failed to read headers from stdin: {e}"))?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("failed to read header file {path}: {e}"))?
+    };
+    Ok(text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Fail early on a netrc file that is missing (unless `optional`) or
+/// cannot be parsed, since lookups later treat either as no entry.
+fn check_netrc(path: &Path, optional: bool) -> Result<(), String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => netrc::parse(&text).map(|_| ()).map_err(|e| format!("{}: {e}", path.display())),
+        Err(e) if optional && e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("cannot read {}: {e}", path.display())),
+    }
+}
+
+/// Resolve a `--data-urlencode` value like curl: `content`, `=content`,
+/// `name=content`, `@file` or `name@file`. The content is percent-encoded;
+/// the name is sent as given.
+fn encode_data_arg(val: &str) -> Result<Vec<u8>, String> {
+    let (name, content) = match val.find(['=', '@']) {
+        Some(at) if val.as_bytes()[at] == b'@' => (&val[..at], read_data_arg(&val[at..], false)?),
+        Some(at) => (&val[..at], val.as_bytes()[at + 1..].to_vec()),
+        None => ("", val.as_bytes().to_vec()),
+    };
+    let encoded = urlencode_bytes(&content);
+    Ok(if name.is_empty() {
+        encoded.into_bytes()
+    } else {
+        format!("{name}={encoded}").into_bytes()
+    })
+}
+
+/// Check an `Accept` value: comma-separated `type/subtype` media ranges,
+/// each with optional `;name=value` parameters.
+/// An `--ech` mode: `false`, `grease`, `true` or `hard`, or an explicit
+/// `ecl:<base64 ECHConfigList>` or `pn:<public name>` (which imply `hard`).
+fn parse_ech(val: &str) -> Result<String, String> {
+    let explicit = |prefix: &str| val.strip_prefix(prefix).is_some_and(|rest| !rest.is_empty());
+    if matches!(val, "false" | "grease" | "true" | "hard") || explicit("ecl:") || explicit("pn:") {
+        Ok(val.to_string())
+    } else {
+        Err(format!("--ech must be false, grease, true, hard, ecl:<CONFIG> or pn:<NAME>, got '{val}'"))
+    }
+}
+
+fn parse_accept(val: &str) -> Result<String, String> {
+    parse_weighted_list(val, "--accept", "a media type like text/html or */*", |range| {
+        let Some((ty, sub)) = range.split_once('/') else {
+            return false;
+        };
+        is_token(ty) && is_token(sub) && (ty != "*" || sub == "*")
+    })
+}
+
+/// Check an `Accept-Language` value: comma-separated language tags such
+/// as `en`, `de-CH` or `*`, each with an optional `;q=` weight.
+fn parse_accept_language(val: &str) -> Result<String, String> {
+    parse_weighted_list(val, "--accept-language", "a language tag like en or de-CH", |tag| {
+        tag == "*"
+            || tag.split('-').enumerate().all(|(i, sub)| {
+                (1..=8).contains(&sub.len())
+                    && sub.bytes().all(|b| if i == 0 { b.is_ascii_alphabetic() } else { b.is_ascii_alphanumeric() })
+            })
+    })
+}
+
+/// Shared shape of `Accept` and `Accept-Language`: comma-separated items
+/// accepted by `item_ok`, with `;name=value` parameters where a `q`
+/// weight must be between 0 and 1 with at most three decimals.
+fn parse_weighted_list(val: &str, flag: &str, expected: &str, item_ok: fn(&str) -> bool) -> Result<String, String> {
+    let items: Vec<&str> = val.split(',').map(str::trim).collect();
+    for item in &items {
+        let mut parts = item.split(';').map(str::trim);
+        let head = parts.next().unwrap_or_default();
+        if !item_ok(head) {
+            return Err(format!("{flag}: '{head}' is not {expected}"));
+        }
+        for param in parts {
+            let Some((name, value)) = param.split_once('=').filter(|(n, v)| is_token(n.trim()) && !v.trim().is_empty())
+            else {
+                return Err(format!("{flag}: invalid parameter '{param}' in '{item}'"));
+            };
+            let is_q = name.trim().eq_ignore_ascii_case("q");
+            if is_q && !is_qvalue(value.trim()) {
+                return Err(format!("{flag}: q={} must be between 0 and 1 with at most 3 decimals", value.trim()));
+            }
+            if !is_q && flag == "--accept-language" {
+                return Err(format!("{flag}: only q= is allowed after a language tag, got '{param}'"));
+            }
+        }
+    }
+    Ok(items.join(","))
+}
+
+/// RFC 9110 `token`.
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// RFC 9110 `qvalue`: `0`, `1`, `0.5`, `1.000` and so on.
+fn is_qvalue(s: &str) -> bool {
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    frac.len() <= 3
+        && frac.bytes().all(|b| b.is_ascii_digit())
+        && match int {
+            "0" => true,
+            "1" => frac.bytes().all(|b| b == b'0'),
+            _ => false,
+        }
+}
+
+/// Parse a size flag value: bytes, or a number with a `k`, `m` or `g`
+/// suffix (powers of 1024), like curl's sizes.
+fn parse_size(s: &str, name: &str) -> Result<u64, String> {
+    let err = || format!("{name} requires a size, e.g. 5000000, 16m, 1g");
+    let s = s.trim();
+    let (num, scale) = match s.char_indices().last() {
+        Some((at, c)) if c.is_ascii_alphabetic() => {
+            let shift = match c.to_ascii_lowercase() {
+                'k' => 10,
+                'm' => 20,
+                'g' => 30,
+                _ => return Err(err()),
+            };
+            (&s[..at], 1u64 << shift)
+        }
+        _ => (s, 1),
+    };
+    num.parse::<u64>().ok().and_then(|n| n.checked_mul(scale)).ok_or_else(err)
 }
 
 fn parse_u32(s: &str, name: &str) -> Result<u32, String> {
@@ -87,44 +235,213 @@ fn parse_u32(s: &str, name: &str) -> Result<u32, String> {
         .map_err(|_| format!("{name} requires a positive integer"))
 }
 
+fn parse_u64(s: &str, name: &str) -> Result<u64, String> {
+    s.parse()
+        .map_err(|_| format!("{name} requires a positive integer"))
+}
+
+/// Options that apply to the whole invocation rather than to the
+/// `--next` group they appear in, and whether each takes a value.
+const GLOBAL_OPTIONS: &[(&str, bool)] = &[
+    ("-v", false),
+    ("--verbose", false),
+    ("-s", false),
+    ("--silent", false),
+    ("--diagnose", false),
+    ("--message-format", true),
+    ("--seed", true),
+];
+
+/// Parse a command line whose URLs may be split into groups by `--next`
+/// (or `-:`), each with its own method, headers, data and so on, as in
+/// curl. Options in [`GLOBAL_OPTIONS`] apply to every group wherever they
+/// appear; nothing else carries over from one group to the next.
+pub fn parse_groups(args: &[String]) -> Result<Vec<RequestConfig>, String> {
+    let mut groups: Vec<Vec<String>> = vec![Vec::new()];
+    let mut globals = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "--next" || arg == "-:" {
+            groups.push(Vec::new());
+        } else if let Some(&(_, takes_value)) = GLOBAL_OPTIONS.iter().find(|(name, _)| *name == arg) {
+            globals.push(args[i].clone());
+            if takes_value {
+                globals.push(next_arg(args, &mut i, arg)?.to_string());
+            }
+        } else if let Some(group) = groups.last_mut() {
+            group.push(args[i].clone());
+        }
+        i += 1;
+    }
+    if groups.len() == 1 {
+        return parse_args(args).map(|config| vec![config]);
+    }
+    let mut configs = Vec::with_capacity(groups.len());
+    for (n, group) in groups.into_iter().enumerate() {
+        if group.is_empty() {
+            return Err(format!("--next group {} has no URL", n + 1));
+        }
+        let config = parse_args(&[group, globals.clone()].concat()).map_err(|e| format!("--next group {}: {e}", n + 1))?;
+        if config.relay.is_some()
+            || config.batch.is_some()
+            || config.collection.is_some()
+            || config.watch.is_some()
+            || config.tail.is_some()
+        {
+            return Err("--next cannot be combined with relay, --batch, --collection, --watch or --tail".to_string());
+        }
+        configs.push(config);
+    }
+    Ok(configs)
+}
+
 pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
     if args.is_empty() {
         return Err("no arguments provided".to_string());
     }
+    // `rustcurl relay --listen ADDR [OPTIONS]` serves instead of sending
+    let (relay, args) = match args.split_first() {
+        Some((command, rest)) if command == "relay" => (true, rest),
+        _ => (false, args),
+    };
 
-    let mut url = None;
+    let mut urls: Vec<String> = Vec::new();
     let mut method = None;
     let mut negotiate = false;
     let mut insecure = false;
     let mut cacert = None;
+    let mut cert = None;
+    let mut cert_type = None;
+    let mut key = None;
+    let mut key_type = None;
+    let mut key_password = None;
     let mut username = None;
     let mut password = None;
     let mut proxy = None;
+    let mut proxy_pac = None;
+    let mut ssh_tunnel = None;
+    let mut tor = false;
     let mut verbose = false;
+    let mut diagnose = false;
+    let mut globoff = false;
     let mut headers: Vec<String> = Vec::new();
-    let mut data = None;
+    let mut proxy_headers: Vec<String> = Vec::new();
+    let mut secrets: Vec<Secret> = Vec::new();
+    let mut data: Vec<Vec<u8>> = Vec::new();
+    let mut get = false;
     let mut connect_timeout = None;
     let mut max_time = None;
-    let mut output = None;
+    let mut read_timeout = None;
+    let mut dns_timeout = None;
+    let mut dns_cache = true;
+    let mut dns_cache_timeout = None;
+    // One per -o, paired in order with the URLs; `None` for `-o -`
+    let mut outputs: Vec<Option<String>> = Vec::new();
+    let mut remote_name = false;
+    let mut remote_header_name = false;
+    let mut range = None;
+    let mut output_dir = None;
+    let mut accept_push = None;
+    let mut extract = false;
+    let mut extract_overwrite = false;
+    let mut verify_sig = None;
+    let mut verify_key = None;
+    let mut provenance = false;
+    let mut mime_sniff = true;
+    let mut output_charset = None;
+    let mut crlf = false;
+    let mut content_digest = Vec::new();
+    let mut content_md5 = false;
+    let mut encrypt_jwe = None;
+    let mut decrypt_jwe = None;
+    let mut normalize_eol = None;
     let mut head_only = false;
     let mut ntlm = false;
     let mut proxy_user = None;
     let mut proxy_password = None;
     let mut noproxy = None;
-    let mut cookie = None;
+    let mut preproxy = None;
+    let mut cookies: Vec<String> = Vec::new();
     let mut cookie_jar = None;
     let mut bearer = None;
+    let mut auth_chain = Vec::new();
+    let mut anyauth = false;
+    let mut registry_auth = false;
+    let mut netrc = false;
+    let mut netrc_optional = false;
+    let mut netrc_file: Option<String> = None;
+    let mut upload_file: Option<String> = None;
+    let mut s3_region: Option<String> = None;
+    let mut s3_endpoint: Option<String> = None;
+    let mut s3_path_style = false;
+    let mut s3_part_size: Option<u64> = None;
+    let mut presign = None;
+    let mut oauth2_token_url = None;
+    let mut oauth2_refresh_token = None;
+    let mut oauth2_client_id = None;
+    let mut oauth2_client_secret = None;
+    let mut auth_preemptive = false;
+    let mut auth_on_challenge = false;
     let mut compressed = false;
+    let mut ignore_content_length = false;
+    let mut accept_partial = false;
     let mut show_timing = false;
+    let mut assert_time_total_max = None;
+    let mut assert_ttfb_max = None;
+    let mut prom_out = None;
+    let mut watch = None;
+    let mut batch = None;
+    let mut collection = None;
+    let mut listen = None;
+    let mut listen_remote = false;
+    let mut watch_diff = false;
+    let mut tail = None;
+    let mut session = None;
+    let mut print_config = false;
+    let mut assume_yes = false;
+    let mut if_changed = None;
+    let mut captures = Vec::new();
+    let mut capture_file = None;
+    let mut notify_cmd = None;
+    let mut notify_desktop = false;
+    let mut notify_debounce = None;
     let mut user_agent = None;
+    let mut accept = None;
+    let mut accept_language = None;
+    let mut user_agent_preset = None;
+    let mut impersonate = None;
+    let mut h2_priority = None;
+    let mut http2 = None;
     let mut silent = false;
+    let mut include = false;
+    let mut write_out = None;
     let mut max_redirs = None;
     let mut resolve: Vec<String> = Vec::new();
     let mut proxy_negotiate = false;
     let mut proxy_ntlm = false;
+    let mut proxy_digest = false;
+    let mut proxy_basic = false;
+    let mut proxy_anyauth = false;
     let mut proxy_insecure = false;
+    let mut proxy_tunnel = false;
     let mut proxy_cacert = None;
     let mut ssl_no_revoke = false;
+    let mut tls_session_cache = None;
+    let mut tls_early_data = false;
+    let mut tls_min = None;
+    let mut tls_max = None;
+    let mut ciphers = None;
+    let mut tls13_ciphers = None;
+    let mut keylog = None;
+    let mut show_certs = false;
+    let mut tofu = false;
+    let mut ech = None;
+    let mut retry = 0;
+    let mut retry_delay = None;
+    let mut retry_report = false;
+    let mut message_format = MessageFormat::Long;
+    let mut seed = None;
 
     let mut i = 0;
     while i < args.len() {
@@ -135,29 +452,156 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
             }
             "-X" | "--request" => {
                 let val = next_arg(args, &mut i, "-X")?;
-                method = Some(parse_method(val));
+                method = Some(Method::parse(val));
             }
             "-H" | "--header" => {
                 let val = next_arg(args, &mut i, "-H")?;
-                headers.push(val.to_string());
+                match val.strip_prefix('@') {
+                    Some(path) => headers.extend(read_header_file(path)?),
+                    None => headers.push(val.to_string()),
+                }
+            }
+            "--header-file" => {
+                let val = next_arg(args, &mut i, "--header-file")?;
+                headers.extend(read_header_file(val)?);
+            }
+            "--secret" => {
+                let val = next_arg(args, &mut i, "--secret")?;
+                secrets.push(Secret::parse(val).map_err(|e| format!("--secret: {e}"))?);
             }
             "-d" | "--data" => {
                 let val = next_arg(args, &mut i, "-d")?;
-                data = Some(val.to_string());
+                data.push(read_data_arg(val, true)?);
+            }
+            "--data-binary" => {
+                let val = next_arg(args, &mut i, "--data-binary")?;
+                data.push(read_data_arg(val, false)?);
+            }
+            "-G" | "--get" => get = true,
+            "--data-urlencode" => {
+                let val = next_arg(args, &mut i, "--data-urlencode")?;
+                data.push(encode_data_arg(val)?);
+            }
+            "-T" | "--upload-file" => {
+                let val = next_arg(args, &mut i, "-T")?;
+                upload_file = Some(val.to_string());
+            }
+            "--data-raw" => {
+                let val = next_arg(args, &mut i, "--data-raw")?;
+                data.push(val.as_bytes().to_vec());
             }
             "-o" | "--output" => {
                 let val = next_arg(args, &mut i, "-o")?;
-                output = Some(val.to_string());
+                // stdout, as without -o
+                outputs.push(Some(val.to_string()).filter(|o| o != "-"));
+            }
+            "-O" | "--remote-name" => remote_name = true,
+            "-J" | "--remote-header-name" => remote_header_name = true,
+            "--output-dir" => {
+                let val = next_arg(args, &mut i, "--output-dir")?;
+                output_dir = Some(val.to_string());
+            }
+            "--accept-push" => {
+                let val = next_arg(args, &mut i, "--accept-push")?;
+                accept_push = Some(val.to_string());
+            }
+            "--extract" => extract = true,
+            "--extract-overwrite" => extract_overwrite = true,
+            "--provenance" => provenance = true,
+            "--verify-sig" => {
+                let val = next_arg(args, &mut i, "--verify-sig")?;
+                verify_sig = Some(val.to_string());
+            }
+            "--verify-key" => {
+                let val = next_arg(args, &mut i, "--verify-key")?;
+                verify_key = Some(val.to_string());
+            }
+            "-r" | "--range" => {
+                let val = next_arg(args, &mut i, "-r")?;
+                super::ranges::validate_spec(val)?;
+                range = Some(val.to_string());
+            }
+            "--no-mime-sniff" => mime_sniff = false,
+            "--crlf" => crlf = true,
+            "--content-digest" => {
+                let val = next_arg(args, &mut i, "--content-digest")?;
+                for name in val.split(',') {
+                    let algorithm = DigestAlgorithm::parse(name)
+                        .ok_or_else(|| format!("--content-digest: unknown algorithm {name} (sha-256, sha-512)"))?;
+                    content_digest.push(algorithm);
+                }
+            }
+            "--content-md5" => content_md5 = true,
+            "--encrypt-jwe" => {
+                let val = next_arg(args, &mut i, "--encrypt-jwe")?;
+                encrypt_jwe = Some(val.to_string());
+            }
+            "--decrypt-jwe" => {
+                let val = next_arg(args, &mut i, "--decrypt-jwe")?;
+                decrypt_jwe = Some(val.to_string());
+            }
+            "--normalize-eol" => {
+                let val = next_arg(args, &mut i, "--normalize-eol")?;
+                normalize_eol = Some(Eol::parse(val).ok_or_else(|| format!("--normalize-eol must be lf or crlf, got {val}"))?);
+            }
+            "--output-charset" => {
+                let val = next_arg(args, &mut i, "--output-charset")?;
+                if charset::encoding_for(val).is_none() {
+                    return Err(format!("--output-charset: unknown charset {val}"));
+                }
+                output_charset = Some(val.to_string());
             }
             "-I" | "--head" => head_only = true,
+            "-i" | "--include" => include = true,
+            "-w" | "--write-out" => {
+                let val = next_arg(args, &mut i, "-w")?;
+                let template = match val.strip_prefix('@') {
+                    Some(path) => std::fs::read_to_string(path).map_err(|e| format!("-w: cannot read {path}: {e}"))?,
+                    None => val.to_string(),
+                };
+                writeout::parse(&template)?;
+                write_out = Some(template);
+            }
             "-s" | "--silent" => silent = true,
             "-A" | "--user-agent" => {
                 let val = next_arg(args, &mut i, "-A")?;
                 user_agent = Some(val.to_string());
             }
+            "--accept" => {
+                let val = next_arg(args, &mut i, "--accept")?;
+                accept = Some(parse_accept(val)?);
+            }
+            "--accept-language" => {
+                let val = next_arg(args, &mut i, "--accept-language")?;
+                accept_language = Some(parse_accept_language(val)?);
+            }
+            "--user-agent-preset" => {
+                let val = next_arg(args, &mut i, "--user-agent-preset")?;
+                let preset = Preset::parse(val)
+                    .ok_or_else(|| format!("--user-agent-preset: unknown preset '{val}', expected {}", presets::names()))?;
+                user_agent_preset = Some(preset);
+            }
+            "--http2" => http2 = Some(Http2::Negotiate),
+            "--http2-prior-knowledge" => http2 = Some(Http2::PriorKnowledge),
+            "--h2-priority" => {
+                let val = next_arg(args, &mut i, "--h2-priority")?;
+                h2_priority = Some(
+                    val.parse::<u32>()
+                        .ok()
+                        .filter(|w| (1..=256).contains(w))
+                        .ok_or("--h2-priority must be a weight from 1 to 256")?,
+                );
+            }
+            "--impersonate" => {
+                let val = next_arg(args, &mut i, "--impersonate")?;
+                let browser = Preset::parse(val)
+                    .filter(Preset::is_browser)
+                    .ok_or_else(|| format!("--impersonate: unknown browser '{val}', expected chrome|firefox|safari"))?;
+                impersonate = Some(browser);
+            }
             "-b" | "--cookie" => {
                 let val = next_arg(args, &mut i, "-b")?;
-                cookie = Some(val.to_string());
+                cookies.push(val.to_string());
             }
             "-c" | "--cookie-jar" => {
                 let val = next_arg(args, &mut i, "-c")?;
@@ -170,6 +614,31 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
                 let val = next_arg(args, &mut i, "--cacert")?;
                 cacert = Some(val.to_string());
             }
+            "-E" | "--cert" => {
+                let val = next_arg(args, &mut i, "--cert")?;
+                cert = Some(val.to_string());
+            }
+            "--cert-type" => {
+                let val = next_arg(args, &mut i, "--cert-type")?;
+                let parsed = CertType::parse(val);
+                cert_type = Some(parsed.ok_or(format!("--cert-type: unknown type '{val}' (PEM, DER, P12)"))?);
+            }
+            "--key" => {
+                let val = next_arg(args, &mut i, "--key")?;
+                key = Some(val.to_string());
+            }
+            "--key-type" => {
+                let val = next_arg(args, &mut i, "--key-type")?;
+                key_type = match CertType::parse(val) {
+                    Some(CertType::P12) => return Err("--key-type: a PKCS#12 bundle goes in --cert".to_string()),
+                    Some(t) => Some(t),
+                    None => return Err(format!("--key-type: unknown type '{val}' (PEM, DER)")),
+                };
+            }
+            "--pass" => {
+                let val = next_arg(args, &mut i, "--pass")?;
+                key_password = Some(val.to_string());
+            }
             "-u" | "--user" => {
                 let val = next_arg(args, &mut i, "-u")?;
                 let (u, p) = parse_credentials(val);
@@ -180,21 +649,128 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
                 let val = next_arg(args, &mut i, "--bearer")?;
                 bearer = Some(val.to_string());
             }
+            "--oauth2-token-url" => {
+                let val = next_arg(args, &mut i, "--oauth2-token-url")?;
+                oauth2_token_url = Some(val.to_string());
+            }
+            "--oauth2-refresh-token" => {
+                let val = next_arg(args, &mut i, "--oauth2-refresh-token")?;
+                oauth2_refresh_token = Some(val.to_string());
+            }
+            "--oauth2-client-id" => {
+                let val = next_arg(args, &mut i, "--oauth2-client-id")?;
+                oauth2_client_id = Some(val.to_string());
+            }
+            "--oauth2-client-secret" => {
+                let val = next_arg(args, &mut i, "--oauth2-client-secret")?;
+                oauth2_client_secret = Some(val.to_string());
+            }
+            "--auth-preemptive" => auth_preemptive = true,
+            "--auth-on-challenge" => auth_on_challenge = true,
+            "--auth-chain" => {
+                let val = next_arg(args, &mut i, "--auth-chain")?;
+                auth_chain = Mechanism::parse_chain(val).map_err(|e| format!("--auth-chain: {e}"))?;
+            }
+            "--anyauth" => anyauth = true,
+            "--registry-auth" => registry_auth = true,
+            "-n" | "--netrc" => netrc = true,
+            "--netrc-optional" => netrc_optional = true,
+            "--netrc-file" => {
+                let val = next_arg(args, &mut i, "--netrc-file")?;
+                netrc_file = Some(val.to_string());
+            }
+            "--s3-region" => {
+                let val = next_arg(args, &mut i, "--s3-region")?;
+                s3_region = Some(val.to_string());
+            }
+            "--s3-endpoint" => {
+                let val = next_arg(args, &mut i, "--s3-endpoint")?;
+                s3_endpoint = Some(val.to_string());
+            }
+            "--s3-path-style" => s3_path_style = true,
+            "--presign" => {
+                let val = next_arg(args, &mut i, "--presign")?;
+                presign = Some(parse_duration(val, "--presign")?);
+            }
+            "--s3-part-size" => {
+                let val = next_arg(args, &mut i, "--s3-part-size")?;
+                let size = parse_size(val, "--s3-part-size")?;
+                if size < s3::MIN_PART_SIZE {
+                    return Err("--s3-part-size must be at least 5m, the smallest part S3 accepts".to_string());
+                }
+                s3_part_size = Some(size);
+            }
             "-x" | "--proxy" => {
                 let val = next_arg(args, &mut i, "-x")?;
                 proxy = Some(val.to_string());
             }
+            "--preproxy" => {
+                let val = next_arg(args, &mut i, "--preproxy")?;
+                preproxy = Some(val.to_string());
+            }
+            "--proxy-pac" => {
+                let val = next_arg(args, &mut i, "--proxy-pac")?;
+                proxy_pac = Some(val.to_string());
+            }
+            "--tor" => tor = true,
+            "--ssh-tunnel" => {
+                let val = next_arg(args, &mut i, "--ssh-tunnel")?;
+                ssh_tunnel = Some(val.to_string());
+            }
             "--proxy-user" => {
                 let val = next_arg(args, &mut i, "--proxy-user")?;
                 let (u, p) = parse_credentials(val);
                 proxy_user = Some(u);
                 proxy_password = p;
             }
+            "--proxy-header" => {
+                let val = next_arg(args, &mut i, "--proxy-header")?;
+                match val.strip_prefix('@') {
+                    Some(path) => proxy_headers.extend(read_header_file(path)?),
+                    None => proxy_headers.push(val.to_string()),
+                }
+            }
             "--proxy-negotiate" => proxy_negotiate = true,
             "--proxy-ntlm" => proxy_ntlm = true,
+            "--proxy-digest" => proxy_digest = true,
+            "--proxy-basic" => proxy_basic = true,
+            "--proxy-anyauth" => proxy_anyauth = true,
             "-L" | "--location" => {} // follow redirects (always on)
             "--ssl-no-revoke" => ssl_no_revoke = true,
+            "--tls-session-cache" => {
+                let val = next_arg(args, &mut i, "--tls-session-cache")?;
+                tls_session_cache = Some(val.to_string());
+            }
+            "--tls-early-data" => tls_early_data = true,
+            "-1" | "--tlsv1" | "--tlsv1.0" => tls_min = Some(TlsVersion::V1_0),
+            "--tlsv1.1" => tls_min = Some(TlsVersion::V1_1),
+            "--tlsv1.2" => tls_min = Some(TlsVersion::V1_2),
+            "--tlsv1.3" => tls_min = Some(TlsVersion::V1_3),
+            "--ciphers" => {
+                let val = next_arg(args, &mut i, "--ciphers")?;
+                ciphers = Some(val.to_string());
+            }
+            "--tls13-ciphers" => {
+                let val = next_arg(args, &mut i, "--tls13-ciphers")?;
+                tls13_ciphers = Some(val.to_string());
+            }
+            "--show-certs" => show_certs = true,
+            "--tofu" => tofu = true,
+            "--keylog" => {
+                let val = next_arg(args, &mut i, "--keylog")?;
+                keylog = Some(val.to_string());
+            }
+            "--tls-max" => {
+                let val = next_arg(args, &mut i, "--tls-max")?;
+                let parsed = TlsVersion::parse(val);
+                tls_max = Some(parsed.ok_or(format!("--tls-max: unknown version '{val}' (1.0 to 1.3)"))?);
+            }
+            "--ech" => {
+                let val = next_arg(args, &mut i, "--ech")?;
+                ech = Some(parse_ech(val)?);
+            }
             "--proxy-insecure" => proxy_insecure = true,
+            "-p" | "--proxytunnel" => proxy_tunnel = true,
             "--proxy-cacert" => {
                 let val = next_arg(args, &mut i, "--proxy-cacert")?;
                 proxy_cacert = Some(val.to_string());
@@ -205,37 +781,284 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
             }
             "--connect-timeout" => {
                 let val = next_arg(args, &mut i, "--connect-timeout")?;
-                connect_timeout = Some(parse_seconds(val, "--connect-timeout")?);
+                connect_timeout = Some(parse_duration(val, "--connect-timeout")?);
             }
             "--max-time" => {
                 let val = next_arg(args, &mut i, "--max-time")?;
-                max_time = Some(parse_seconds(val, "--max-time")?);
+                max_time = Some(parse_duration(val, "--max-time")?);
+            }
+            "--read-timeout" => {
+                let val = next_arg(args, &mut i, "--read-timeout")?;
+                read_timeout = Some(parse_duration(val, "--read-timeout")?);
+            }
+            "--dns-timeout" => {
+                let val = next_arg(args, &mut i, "--dns-timeout")?;
+                dns_timeout = Some(parse_duration(val, "--dns-timeout")?);
             }
+            "--dns-cache-timeout" => {
+                let val = next_arg(args, &mut i, "--dns-cache-timeout")?;
+                dns_cache_timeout = Some(parse_duration(val, "--dns-cache-timeout")?);
+            }
+            "--no-dns-cache" => dns_cache = false,
             "--max-redirs" => {
                 let val = next_arg(args, &mut i, "--max-redirs")?;
                 max_redirs = Some(parse_u32(val, "--max-redirs")?);
             }
+            "--retry" => {
+                let val = next_arg(args, &mut i, "--retry")?;
+                retry = parse_u32(val, "--retry")?;
+            }
+            "--retry-delay" => {
+                let val = next_arg(args, &mut i, "--retry-delay")?;
+                retry_delay = Some(parse_duration(val, "--retry-delay")?);
+            }
+            "--retry-report" => retry_report = true,
             "--compressed" => compressed = true,
+            "--ignore-content-length" => ignore_content_length = true,
+            "--accept-partial" => accept_partial = true,
             "--timing" => show_timing = true,
+            "--assert-time-total-max" => {
+                let val = next_arg(args, &mut i, "--assert-time-total-max")?;
+                assert_time_total_max = Some(parse_duration(val, "--assert-time-total-max")?);
+            }
+            "--assert-ttfb-max" => {
+                let val = next_arg(args, &mut i, "--assert-ttfb-max")?;
+                assert_ttfb_max = Some(parse_duration(val, "--assert-ttfb-max")?);
+            }
+            "--print-config" => print_config = true,
+            "--yes" => assume_yes = true,
+            "--session" => {
+                let val = next_arg(args, &mut i, "--session")?;
+                session::check_name(val).map_err(|e| format!("--session: {e}"))?;
+                session = Some(val.to_string());
+            }
+            "--prom-out" => {
+                let val = next_arg(args, &mut i, "--prom-out")?;
+                prom_out = Some(val.to_string());
+            }
+            "--watch" => {
+                let val = next_arg(args, &mut i, "--watch")?;
+                watch = Some(parse_duration(val, "--watch")?);
+            }
+            "--watch-diff" => watch_diff = true,
+            "--tail" => {
+                let val = next_arg(args, &mut i, "--tail")?;
+                tail = Some(parse_duration(val, "--tail")?);
+            }
+            "--batch" => {
+                let val = next_arg(args, &mut i, "--batch")?;
+                batch = Some(val.to_string());
+            }
+            "--collection" => {
+                let val = next_arg(args, &mut i, "--collection")?;
+                collection = Some(val.to_string());
+            }
+            "--listen" => {
+                let val = next_arg(args, &mut i, "--listen")?;
+                listen = Some(val.to_string());
+            }
+            "--listen-remote" => listen_remote = true,
+            "--if-changed" => {
+                let val = next_arg(args, &mut i, "--if-changed")?;
+                if_changed = Some(val.to_string());
+            }
+            "--capture" => {
+                let val = next_arg(args, &mut i, "--capture")?;
+                captures.push(Capture::parse(val).map_err(|e| format!("--capture: {e}"))?);
+            }
+            "--capture-file" => {
+                let val = next_arg(args, &mut i, "--capture-file")?;
+                capture_file = Some(val.to_string());
+            }
+            "--notify-cmd" => {
+                let val = next_arg(args, &mut i, "--notify-cmd")?;
+                notify_cmd = Some(val.to_string());
+            }
+            "--notify-desktop" => notify_desktop = true,
+            "--notify-debounce" => {
+                let val = next_arg(args, &mut i, "--notify-debounce")?;
+                notify_debounce = Some(parse_u32(val, "--notify-debounce")?);
+            }
             "--resolve" => {
                 let val = next_arg(args, &mut i, "--resolve")?;
                 resolve.push(val.to_string());
             }
+            "--message-format" => {
+                let val = next_arg(args, &mut i, "--message-format")?;
+                message_format = MessageFormat::parse(val)
+                    .ok_or_else(|| format!("--message-format must be short, long or json, got {val}"))?;
+            }
+            "--seed" => {
+                let val = next_arg(args, &mut i, "--seed")?;
+                seed = Some(parse_u64(val, "--seed")?);
+            }
+            "--url" => {
+                let val = next_arg(args, &mut i, "--url")?;
+                urls.push(val.to_string());
+            }
+            // Only meaningful first, where main reads it before parsing
+            "-q" | "--no-curlrc" => {}
+            "--next" | "-:" => return Err("--next is only valid between URL groups".to_string()),
             "-v" | "--verbose" => verbose = true,
+            "--diagnose" => diagnose = true,
+            "-g" | "--globoff" => globoff = true,
             arg if arg.starts_with('-') => {
                 return Err(format!("unknown option: {arg}"));
             }
-            arg => {
-                url = Some(arg.to_string());
-            }
+            arg => urls.push(arg.to_string()),
         }
         i += 1;
     }
 
-    let url = url.ok_or("URL is required")?;
+    let writes_file = outputs.iter().any(Option::is_some);
+    match (relay, &listen) {
+        (true, None) => return Err("relay requires --listen <ADDR>".to_string()),
+        (false, Some(_)) => return Err("--listen is only valid with 'rustcurl relay'".to_string()),
+        _ => {}
+    }
+    if listen_remote && !relay {
+        return Err("--listen-remote is only valid with 'rustcurl relay'".to_string());
+    }
+    // --batch and --collection both run requests from a file instead of URLs
+    let runner = match (&batch, &collection) {
+        (Some(_), Some(_)) => return Err("--batch and --collection cannot be combined".to_string()),
+        (Some(_), None) => Some("--batch"),
+        (None, Some(_)) => Some("--collection"),
+        (None, None) => None,
+    };
+    if relay
+        && (!data.is_empty()
+            || writes_file
+            || remote_name
+            || watch.is_some()
+            || runner.is_some()
+            || if_changed.is_some()
+            || !captures.is_empty()
+            || accept_push.is_some())
+    {
+        return Err("relay cannot be combined with -d, -o, -O, --watch, --batch, --collection, --if-changed, \
+             --capture or --accept-push"
+            .to_string());
+    }
+    if outputs.len() > urls.len().max(1) {
+        return Err("more -o options than URLs".to_string());
+    }
+    // Each URL with its -o, globs expanded and `#N` filled in
+    let mut targets: Vec<(String, Option<String>)> = Vec::new();
+    let mut outputs = outputs.into_iter().chain(std::iter::repeat(None));
+    for url in urls {
+        let output = outputs.next().flatten();
+        if globoff {
+            targets.push((url, output));
+            continue;
+        }
+        let expanded = glob::expand(&url).map_err(|e| format!("bad URL glob in {url}: {e} (use --globoff)"))?;
+        targets.extend(expanded.into_iter().map(|e| {
+            let output = output.as_deref().map(|o| glob::output_name(o, &e.values));
+            (e.url, output)
+        }));
+    }
+    if targets.len() > 1 && (watch.is_some() || tail.is_some() || if_changed.is_some() || !captures.is_empty()) {
+        return Err("--watch, --tail, --if-changed and --capture take a single URL".to_string());
+    }
+    if tail.is_some() && (writes_file || remote_name || range.is_some() || watch.is_some() || runner.is_some() || relay)
+    {
+        return Err("--tail cannot be combined with -o, -O, -r, --watch, --batch, --collection or relay".to_string());
+    }
+    let mut targets = targets.into_iter();
+    let (url, output) = match (targets.next(), runner) {
+        (Some(_), _) if relay => return Err("relay takes its URLs from its clients' requests".to_string()),
+        (None, _) if relay => (String::new(), None),
+        (Some(_), Some(flag)) => return Err(format!("{flag} takes its URLs from the request specs")),
+        (Some(target), None) => target,
+        (None, Some(_)) => (String::new(), None),
+        (None, None) => return Err("URL is required".to_string()),
+    };
+    let more_urls: Vec<(String, Option<String>)> = targets.collect();
+    if let Some(flag) = runner
+        && (writes_file || remote_name || watch.is_some() || if_changed.is_some() || !captures.is_empty())
+    {
+        return Err(format!("{flag} cannot be combined with -o, -O, --watch, --if-changed or --capture"));
+    }
+
+    if writes_file && remote_name {
+        return Err("-o and -O cannot be combined".to_string());
+    }
+    if tofu && insecure {
+        return Err("--tofu cannot be combined with -k".to_string());
+    }
+    if remote_header_name && !remote_name {
+        return Err("-J requires -O".to_string());
+    }
+    if !auth_chain.is_empty() && (negotiate || ntlm) {
+        return Err("--auth-chain cannot be combined with --negotiate or --ntlm".to_string());
+    }
+    if anyauth && (!auth_chain.is_empty() || negotiate || ntlm || auth_preemptive || auth_on_challenge) {
+        return Err("--anyauth cannot be combined with --auth-chain, --negotiate, --ntlm or --auth-*".to_string());
+    }
+    if [proxy_negotiate, proxy_ntlm, proxy_digest, proxy_basic, proxy_anyauth].iter().filter(|&&on| on).count() > 1 {
+        return Err(
+            "choose one of --proxy-negotiate, --proxy-ntlm, --proxy-digest, --proxy-basic and --proxy-anyauth"
+                .to_string(),
+        );
+    }
+    if registry_auth && (anyauth || !auth_chain.is_empty() || bearer.is_some() || negotiate || ntlm) {
+        return Err("--registry-auth cannot be combined with --anyauth, --auth-chain, --bearer, --negotiate or --ntlm"
+            .to_string());
+    }
+    if oauth2_token_url.is_some() && bearer.is_none() {
+        return Err("--oauth2-token-url requires --bearer".to_string());
+    }
+    if (oauth2_refresh_token.is_some() || oauth2_client_id.is_some() || oauth2_client_secret.is_some())
+        && oauth2_token_url.is_none()
+    {
+        return Err("--oauth2-refresh-token, --oauth2-client-id and --oauth2-client-secret require --oauth2-token-url".to_string());
+    }
+    if ssh_tunnel.is_some() && proxy.is_some() {
+        return Err("--ssh-tunnel cannot be combined with -x".to_string());
+    }
+    if tor && (proxy.is_some() || ssh_tunnel.is_some()) {
+        return Err("--tor cannot be combined with -x or --ssh-tunnel".to_string());
+    }
+    if proxy_pac.is_some() && (proxy.is_some() || ssh_tunnel.is_some() || tor) {
+        return Err("--proxy-pac cannot be combined with -x, --ssh-tunnel or --tor".to_string());
+    }
+    if auth_preemptive && auth_on_challenge {
+        return Err("--auth-preemptive and --auth-on-challenge cannot be combined".to_string());
+    }
+    let saving = writes_file || remote_name;
+    if extract && !saving {
+        return Err("--extract requires -o or -O".to_string());
+    }
+    if extract_overwrite && !extract {
+        return Err("--extract-overwrite requires --extract".to_string());
+    }
+    if provenance && !saving {
+        return Err("--provenance requires -o or -O".to_string());
+    }
+    if verify_sig.is_some() != verify_key.is_some() {
+        return Err("--verify-sig and --verify-key must be used together".to_string());
+    }
+    if tls_early_data && tls_session_cache.is_none() {
+        return Err("--tls-early-data requires --tls-session-cache".to_string());
+    }
+    if let (Some(min), Some(max)) = (tls_min, tls_max)
+        && max < min
+    {
+        return Err(format!("--tls-max {max} is older than the minimum {min}"));
+    }
+    if tls_early_data && tls_max.is_some_and(|max| max < TlsVersion::V1_3) {
+        return Err("--tls-early-data needs TLS 1.3, which --tls-max rules out".to_string());
+    }
+    if watch_diff && watch.is_none() {
+        return Err("--watch-diff requires --watch".to_string());
+    }
+    if (notify_cmd.is_some() || notify_desktop || notify_debounce.is_some()) && watch.is_none() {
+        return Err("--notify-cmd, --notify-desktop and --notify-debounce require --watch".to_string());
+    }
 
     // Auto-set POST when data provided without explicit method (like curl)
-    if data.is_some() && method.is_none() {
+    if !data.is_empty() && method.is_none() && !get {
         method = Some(Method::Post);
     }
 
@@ -244,27 +1067,89 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
         method = Some(Method::Head);
     }
 
+    if upload_file.is_some() {
+        if !data.is_empty() || get {
+            return Err("-T cannot be combined with -d or -G".to_string());
+        }
+        method.get_or_insert(Method::Put);
+    }
+
     let mut config = RequestConfig::new(&url)
         .method(method.unwrap_or(Method::Get))
         .negotiate(negotiate)
         .insecure(insecure)
         .verbose(verbose)
+        .diagnose(diagnose)
         .head_only(head_only)
         .ntlm(ntlm)
         .compressed(compressed)
+        .ignore_content_length(ignore_content_length)
+        .accept_partial(accept_partial)
         .show_timing(show_timing)
         .silent(silent)
+        .include(include)
         .proxy_negotiate(proxy_negotiate)
         .proxy_ntlm(proxy_ntlm)
+        .proxy_digest(proxy_digest)
+        .proxy_basic(proxy_basic)
+        .proxy_anyauth(proxy_anyauth)
         .proxy_insecure(proxy_insecure)
-        .ssl_no_revoke(ssl_no_revoke);
+        .proxy_tunnel(proxy_tunnel)
+        .ssl_no_revoke(ssl_no_revoke)
+        .tls_early_data(tls_early_data)
+        .show_certs(show_certs)
+        .tofu(tofu)
+        .retry(retry)
+        .dns_cache(dns_cache)
+        .mime_sniff(mime_sniff)
+        .extract(extract)
+        .extract_overwrite(extract_overwrite)
+        .provenance(provenance)
+        .retry_report(retry_report)
+        .message_format(message_format);
 
     config.headers = headers;
+    config.proxy_headers = proxy_headers;
+    // Like -H these replace the backend's default and --impersonate's values
+    for (flag, name, value) in [
+        ("--accept", "Accept", accept),
+        ("--accept-language", "Accept-Language", accept_language),
+    ] {
+        let Some(value) = value else { continue };
+        if config.has_header(name) {
+            return Err(format!("{flag} cannot be combined with -H '{name}: ...'"));
+        }
+        config.headers.push(format!("{name}: {value}"));
+    }
     config.resolve = resolve;
 
     if let Some(path) = cacert {
         config = config.cacert(&path);
     }
+    if cert.is_none() && (key.is_some() || key_password.is_some() || cert_type.is_some() || key_type.is_some()) {
+        return Err("--key, --pass, --cert-type and --key-type require --cert".to_string());
+    }
+    if key_type.is_some() && key.is_none() {
+        return Err("--key-type requires --key".to_string());
+    }
+    if let Some(path) = cert {
+        config = config.cert(&path);
+    }
+    if let Some(t) = cert_type {
+        config = config.cert_type(t);
+    }
+    if config.cert.is_some() && config.cert_format() == CertType::P12 && key.is_some() {
+        return Err("--key: a PKCS#12 --cert already holds the key".to_string());
+    }
+    if let Some(path) = key {
+        config = config.key(&path);
+    }
+    if let Some(t) = key_type {
+        config = config.key_type(t);
+    }
+    if let Some(pass) = key_password {
+        config = config.key_password(&pass);
+    }
     if let Some(u) = username {
         config = config.username(&u);
     }
@@ -274,18 +1159,119 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
     if let Some(px) = proxy {
         config = config.proxy(&px);
     }
-    if let Some(d) = data {
-        config = config.data(&d);
+    if let Some(location) = proxy_pac {
+        config = config.proxy_pac(&location);
+    }
+    if let Some(dest) = ssh_tunnel {
+        config = config.ssh_tunnel(&dest);
+    }
+    config = config.tor(tor);
+    for d in &data {
+        config = config.data_bytes(d);
     }
+    config = config.query_data(get);
     if let Some(ct) = connect_timeout {
         config = config.connect_timeout(ct);
     }
     if let Some(mt) = max_time {
         config = config.max_time(mt);
     }
+    if let Some(rt) = read_timeout {
+        config = config.read_timeout(rt);
+    }
+    if let Some(dt) = dns_timeout {
+        config = config.dns_timeout(dt);
+    }
+    if let Some(dct) = dns_cache_timeout {
+        config = config.dns_cache_timeout(dct);
+    }
+    if let Some(limit) = assert_time_total_max {
+        config = config.assert_time_total_max(limit);
+    }
+    if let Some(limit) = assert_ttfb_max {
+        config = config.assert_ttfb_max(limit);
+    }
+    if let Some(cs) = output_charset {
+        config = config.output_charset(&cs);
+    }
+    if let Some(eol) = normalize_eol {
+        config = config.normalize_eol(eol);
+    }
+    config = config.crlf(crlf).content_md5(content_md5);
+    for algorithm in content_digest {
+        config = config.content_digest(algorithm);
+    }
+    if let Some(key) = encrypt_jwe {
+        config = config.encrypt_jwe(&key);
+    }
+    if let Some(key) = decrypt_jwe {
+        config = config.decrypt_jwe(&key);
+    }
+    if let Some(p) = prom_out {
+        config = config.prom_out(&p);
+    }
+    if let Some(interval) = watch {
+        config = config.watch(interval).watch_diff(watch_diff).notify_desktop(notify_desktop);
+    }
+    if let Some(interval) = tail {
+        config = config.tail(interval);
+    }
+    if let Some(name) = session {
+        config = config.session(&name);
+    }
+    config = config.print_config(print_config).assume_yes(assume_yes);
+    if let Some(source) = batch {
+        config = config.batch(&source);
+    }
+    if let Some(path) = collection {
+        config = config.collection(&path);
+    }
+    if let Some(addr) = listen {
+        config = config.relay(&addr).relay_remote(listen_remote);
+    }
+    if let Some(state) = if_changed {
+        config = config.if_changed(&state);
+    }
+    match capture_file {
+        Some(_) if captures.is_empty() => return Err("--capture-file requires --capture".to_string()),
+        None if !captures.is_empty() => return Err("--capture requires --capture-file".to_string()),
+        Some(path) => config = config.capture_file(&path),
+        None => {}
+    }
+    for capture in captures {
+        config = config.capture(capture);
+    }
+    if let Some(cmd) = notify_cmd {
+        config = config.notify_cmd(&cmd);
+    }
+    if let Some(n) = notify_debounce {
+        config = config.notify_debounce(n);
+    }
+    if let Some(rd) = retry_delay {
+        config = config.retry_delay(rd);
+    }
+    if let Some(s) = seed {
+        config = config.seed(s);
+    }
     if let Some(o) = output {
         config = config.output(&o);
     }
+    for (url, output) in more_urls {
+        config = config.more_url(&url, output.as_deref());
+    }
+    config = config.remote_name(remote_name).remote_header_name(remote_header_name);
+    if let Some(d) = output_dir {
+        config = config.output_dir(&d);
+    }
+    if let Some(d) = accept_push {
+        config = config.accept_push(&d);
+    }
+    if let (Some(sig), Some(key)) = (verify_sig, verify_key) {
+        config = config.verify_sig(&sig).verify_key(&key);
+    }
+    if let Some(r) = range {
+        config = config.range(&r);
+    }
     if let Some(pu) = proxy_user {
         config = config.proxy_user(&pu);
     }
@@ -295,8 +1281,11 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
     if let Some(np) = noproxy {
         config = config.noproxy(&np);
     }
-    if let Some(c) = cookie {
-        config = config.cookie(&c);
+    if let Some(pre) = preproxy {
+        config = config.preproxy(&pre);
+    }
+    for c in &cookies {
+        config = config.cookie(c);
     }
     if let Some(cj) = cookie_jar {
         config = config.cookie_jar(&cj);
@@ -304,15 +1293,95 @@ pub fn parse_args(args: &[String]) -> Result<RequestConfig, String> {
     if let Some(b) = bearer {
         config = config.bearer(&b);
     }
-    if let Some(ua) = user_agent {
+    if let Some(url) = oauth2_token_url {
+        config = config.oauth2_token_url(&url);
+    }
+    if let Some(token) = oauth2_refresh_token {
+        config = config.oauth2_refresh_token(&token);
+    }
+    if let Some(id) = oauth2_client_id {
+        config = config.oauth2_client_id(&id);
+    }
+    if let Some(secret) = oauth2_client_secret {
+        config = config.oauth2_client_secret(&secret);
+    }
+    if let Some(template) = write_out {
+        config = config.write_out(&template);
+    }
+    config = config.auth_chain(auth_chain).auth_on_challenge(auth_on_challenge).anyauth(anyauth);
+    config = config.registry_auth(registry_auth);
+    if user_agent.is_some() && user_agent_preset.is_some() {
+        return Err("-A cannot be combined with --user-agent-preset".to_string());
+    }
+    if let Some(ua) = user_agent.or(user_agent_preset.map(|p| p.user_agent().to_string())) {
         config = config.user_agent(&ua);
     }
+    if let Some(browser) = impersonate {
+        config = config.impersonate(browser);
+    }
+    if let Some(mode) = ech {
+        config = config.ech(&mode);
+    }
+    if let Some(version) = tls_min {
+        config = config.tls_min(version);
+    }
+    if let Some(version) = tls_max {
+        config = config.tls_max(version);
+    }
+    if let Some(list) = ciphers {
+        config = config.ciphers(&list);
+    }
+    if let Some(list) = tls13_ciphers {
+        config = config.tls13_ciphers(&list);
+    }
+    if let Some(path) = keylog {
+        config = config.keylog(&path);
+    }
+    if let Some(path) = tls_session_cache {
+        config = config.tls_session_cache(&path);
+    }
+    if let Some(weight) = h2_priority {
+        config = config.h2_priority(weight);
+    }
+    if let Some(mode) = http2 {
+        config = config.http2(mode);
+    }
     if let Some(mr) = max_redirs {
         config = config.max_redirs(mr);
     }
     if let Some(pc) = proxy_cacert {
         config = config.proxy_cacert(&pc);
     }
+    if netrc || netrc_optional || netrc_file.is_some() {
+        config = config.netrc(true);
+        let path = match netrc_file {
+            Some(path) => {
+                config = config.netrc_file(&path);
+                PathBuf::from(path)
+            }
+            None => netrc::default_path(&EnvSource::Process).ok_or("-n: cannot locate ~/.netrc without $HOME")?,
+        };
+        check_netrc(&path, netrc_optional)?;
+    }
+    if let Some(path) = upload_file {
+        config = config.upload_file(&path);
+    }
+    if let Some(region) = s3_region {
+        config = config.s3_region(&region);
+    }
+    if let Some(url) = s3_endpoint {
+        config = config.s3_endpoint(&url);
+    }
+    if let Some(size) = s3_part_size {
+        config = config.s3_part_size(size);
+    }
+    config = config.s3_path_style(s3_path_style);
+    if let Some(expires) = presign {
+        config = config.presign(expires);
+    }
+    if !secrets.is_empty() {
+        config = secret::apply(config, &secrets)?;
+    }
 
     Ok(config)
 }
@@ -326,6 +1395,15 @@ mod tests {
         strs.iter().map(|s| s.to_string()).collect()
     }
 
+    /// `contents` in a file under this test run's own temp directory.
+    fn scratch_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustcurl-args-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
     #[test]
     fn credentials_with_password() {
         let (user, pass) = parse_credentials("admin:secret");
@@ -419,6 +1497,35 @@ mod tests {
         assert!(parse_args(&args(&["--bogus", "https://x.com"])).is_err());
     }
 
+    #[test]
+    fn client_cert_flags() {
+        let cfg = parse_args(&args(&[
+            "--cert", "client.pem", "--key", "client.key", "--pass", "s3cret", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.cert.as_deref(), Some("client.pem"));
+        assert_eq!(cfg.key.as_deref(), Some("client.key"));
+        assert_eq!(cfg.key_password.as_deref(), Some("s3cret"));
+        assert_eq!(cfg.cert_format(), CertType::Pem);
+        assert_eq!(parse_args(&args(&["-E", "id.PFX", "https://x.com"])).unwrap().cert_format(), CertType::P12);
+        assert!(parse_args(&args(&["--key", "client.key", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn cert_and_key_types() {
+        let cfg = parse_args(&args(&[
+            "--cert", "client.crt", "--cert-type", "der", "--key", "k.bin", "--key-type", "DER", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!((cfg.cert_format(), cfg.key_format()), (CertType::Der, CertType::Der));
+        let p12 = parse_args(&args(&["--cert", "id.bin", "--cert-type", "p12", "--pass", "x", "https://x.com"]));
+        assert_eq!(p12.unwrap().cert_format(), CertType::P12);
+        assert!(parse_args(&args(&["--cert", "id.p12", "--key", "k.pem", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--cert", "c.pem", "--key", "k", "--key-type", "p12", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--cert", "c.pem", "--cert-type", "jks", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--cert", "c.pem", "--key-type", "der", "https://x.com"])).is_err());
+    }
+
     #[test]
     fn cacert_missing_path_is_error() {
         assert!(parse_args(&args(&["--cacert"])).is_err());
@@ -450,46 +1557,204 @@ mod tests {
     fn data_auto_sets_post() {
         let cfg = parse_args(&args(&["-d", "{}", "https://x.com"])).unwrap();
         assert_eq!(cfg.method, Method::Post);
-        assert_eq!(cfg.data.as_deref(), Some("{}"));
+        assert_eq!(cfg.data.as_deref(), Some(&b"{}"[..]));
     }
 
     #[test]
     fn data_with_explicit_method_keeps_method() {
         let cfg = parse_args(&args(&["-X", "PUT", "-d", "body", "https://x.com"])).unwrap();
         assert_eq!(cfg.method, Method::Put);
-        assert_eq!(cfg.data.as_deref(), Some("body"));
+        assert_eq!(cfg.data.as_deref(), Some(&b"body"[..]));
     }
 
     #[test]
-    fn head_flag_sets_head_method() {
-        let cfg = parse_args(&args(&["-I", "https://x.com"])).unwrap();
-        assert_eq!(cfg.method, Method::Head);
-        assert!(cfg.head_only);
+    fn multiple_data_joined_with_ampersand() {
+        let cfg = parse_args(&args(&[
+            "-d", "name=john", "-d", "age=30", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.method, Method::Post);
+        assert_eq!(cfg.data.as_deref(), Some(&b"name=john&age=30"[..]));
     }
 
     #[test]
-    fn head_long_form() {
-        let cfg = parse_args(&args(&["--head", "https://x.com"])).unwrap();
-        assert_eq!(cfg.method, Method::Head);
+    fn data_from_file_mixed_with_inline() {
+        let path = scratch_file("data_part.txt", b"b=2\r\nc=3\n");
+        let file_arg = format!("@{}", path.display());
+        let cfg = parse_args(&args(&["-d", "a=1", "-d", &file_arg, "https://x.com"])).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(cfg.data.as_deref(), Some(&b"a=1&b=2c=3"[..]));
     }
 
     #[test]
-    fn multiple_headers() {
-        let cfg = parse_args(&args(&[
-            "-H", "Accept: text/html",
-            "-H", "X-Custom: foo",
-            "https://x.com",
-        ]))
-        .unwrap();
-        assert_eq!(cfg.headers.len(), 2);
-        assert_eq!(cfg.headers[0], "Accept: text/html");
-        assert_eq!(cfg.headers[1], "X-Custom: foo");
+    fn data_missing_file_is_error() {
+        assert!(parse_args(&args(&["-d", "@/nonexistent/rustcurl", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--data-binary", "@/nonexistent/rustcurl", "https://x.com"])).is_err());
     }
 
     #[test]
-    fn connect_timeout_and_max_time() {
-        let cfg = parse_args(&args(&[
-            "--connect-timeout", "10", "--max-time", "30", "https://x.com",
+    fn data_binary_file_kept_intact() {
+        let bytes = [0x00, 0xff, b'\r', b'\n', 0x80, b'\n'];
+        let path = scratch_file("data_binary.bin", &bytes);
+        let file_arg = format!("@{}", path.display());
+        let cfg = parse_args(&args(&["--data-binary", &file_arg, "https://x.com"])).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(cfg.method, Method::Post);
+        assert_eq!(cfg.data.as_deref(), Some(&bytes[..]));
+    }
+
+    #[test]
+    fn data_urlencode_forms() {
+        assert_eq!(encode_data_arg("a b&c").unwrap(), b"a%20b%26c");
+        assert_eq!(encode_data_arg("=x=1 2").unwrap(), b"x%3D1%202");
+        assert_eq!(encode_data_arg("q=caf\u{e9} & co").unwrap(), b"q=caf%C3%A9%20%26%20co");
+        assert_eq!(encode_data_arg("msg=a@b").unwrap(), b"msg=a%40b");
+
+        let path = scratch_file("data_urlencode.txt", b"line 1\nline=2");
+        let named = encode_data_arg(&format!("text@{}", path.display()));
+        let bare = encode_data_arg(&format!("@{}", path.display()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(named.unwrap(), b"text=line%201%0Aline%3D2");
+        assert_eq!(bare.unwrap(), b"line%201%0Aline%3D2");
+        assert!(encode_data_arg("f@/nonexistent/rustcurl").is_err());
+    }
+
+    #[test]
+    fn data_urlencode_merges_with_data() {
+        let cfg = parse_args(&args(&["-d", "a=1", "--data-urlencode", "q=x y", "https://x.com"])).unwrap();
+        assert_eq!(cfg.method, Method::Post);
+        assert_eq!(cfg.data.as_deref(), Some(&b"a=1&q=x%20y"[..]));
+    }
+
+    #[test]
+    fn get_sends_data_as_query() {
+        let cfg = parse_args(&args(&["-G", "-d", "a=1", "--data-urlencode", "q=x y", "https://x.com/s"])).unwrap();
+        assert_eq!(cfg.method, Method::Get);
+        assert!(cfg.body().is_none());
+        assert_eq!(cfg.request_url(), "https://x.com/s?a=1&q=x%20y");
+        let head = parse_args(&args(&["--get", "-I", "-d", "a=1", "https://x.com"])).unwrap();
+        assert_eq!(head.method, Method::Head);
+    }
+
+    #[test]
+    fn data_raw_keeps_leading_at() {
+        let cfg = parse_args(&args(&["--data-raw", "@handle", "-d", "x=1", "https://x.com"])).unwrap();
+        assert_eq!(cfg.method, Method::Post);
+        assert_eq!(cfg.data.as_deref(), Some(&b"@handle&x=1"[..]));
+    }
+
+    #[test]
+    fn head_flag_sets_head_method() {
+        let cfg = parse_args(&args(&["-I", "https://x.com"])).unwrap();
+        assert_eq!(cfg.method, Method::Head);
+        assert!(cfg.head_only);
+    }
+
+    #[test]
+    fn head_long_form() {
+        let cfg = parse_args(&args(&["--head", "https://x.com"])).unwrap();
+        assert_eq!(cfg.method, Method::Head);
+    }
+
+    #[test]
+    fn multiple_headers() {
+        let cfg = parse_args(&args(&[
+            "-H", "Accept: text/html",
+            "-H", "X-Custom: foo",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.headers.len(), 2);
+        assert_eq!(cfg.headers[0], "Accept: text/html");
+        assert_eq!(cfg.headers[1], "X-Custom: foo");
+    }
+
+    #[test]
+    fn proxy_headers_kept_apart() {
+        let cfg = parse_args(&args(&[
+            "--proxy-header", "X-Corp-Token: abc",
+            "-H", "Accept: text/html",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.proxy_headers, ["X-Corp-Token: abc"]);
+        assert_eq!(cfg.headers, ["Accept: text/html"]);
+    }
+
+    #[test]
+    fn upload_and_s3_flags() {
+        let cfg = parse_args(&args(&[
+            "-T", "build.tar.gz", "--s3-endpoint", "http://minio:9000", "--s3-path-style",
+            "--s3-part-size", "16m", "--s3-region", "eu-west-1", "s3://artifacts/ci/",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.method, Method::Put);
+        assert_eq!(cfg.upload_file.as_deref(), Some("build.tar.gz"));
+        assert_eq!(cfg.s3_endpoint.as_deref(), Some("http://minio:9000"));
+        assert!(cfg.s3_path_style);
+        assert_eq!(cfg.s3_part_size, Some(16 << 20));
+        assert_eq!(cfg.s3_region.as_deref(), Some("eu-west-1"));
+        assert!(parse_args(&args(&["--s3-part-size", "1m", "s3://a/b"])).is_err());
+        assert!(parse_args(&args(&["-T", "f", "-d", "x", "https://x.com"])).is_err());
+        assert_eq!(parse_size("2K", "x"), Ok(2048));
+        let cfg = parse_args(&args(&["--presign", "1h", "-X", "PUT", "gs://b/o"])).unwrap();
+        assert_eq!(cfg.presign, Some(std::time::Duration::from_secs(3600)));
+        assert!(parse_size("2x", "x").is_err());
+    }
+
+    #[test]
+    fn netrc_flags() {
+        let path = std::env::temp_dir().join(format!("rustcurl-args-netrc-{}", std::process::id()));
+        std::fs::write(&path, "machine x.com login u password p\n").unwrap();
+        let file = path.to_str().unwrap();
+        let cfg = parse_args(&args(&["--netrc-file", file, "https://x.com"])).unwrap();
+        assert!(cfg.netrc);
+        assert_eq!(cfg.netrc_file.as_deref(), Some(file));
+        std::fs::write(&path, "machine x.com login\n").unwrap();
+        assert!(parse_args(&args(&["--netrc-file", file, "https://x.com"])).unwrap_err().contains("without a value"));
+        std::fs::remove_file(&path).unwrap();
+        assert!(parse_args(&args(&["--netrc-file", file, "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--netrc-optional", "--netrc-file", file, "https://x.com"])).is_ok());
+    }
+
+    #[test]
+    fn secret_flag() {
+        let path = std::env::temp_dir().join(format!("rustcurl-secret-{}", std::process::id()));
+        std::fs::write(&path, "tok-123\n").unwrap();
+        let spec = format!("tok=file:{}", path.display());
+        let cfg = parse_args(&args(&["--secret", &spec, "-H", "Authorization: Bearer {{secret.tok}}", "https://x.com"]))
+            .unwrap();
+        assert_eq!(cfg.headers, ["Authorization: Bearer tok-123"]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(parse_args(&args(&["-H", "X: {{secret.tok}}", "--secret", "t=env:HOME", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--secret", "tok", "https://x.com"])).unwrap_err().starts_with("--secret"));
+    }
+
+    #[test]
+    fn headers_from_file() {
+        let path = std::env::temp_dir().join(format!("rustcurl-headers-{}", std::process::id()));
+        std::fs::write(&path, "Authorization: Bearer eyJ.x.y\r\n\nX-Signature: abc\n").unwrap();
+        let path = path.to_str().unwrap();
+        let at = format!("@{path}");
+        let cfg = parse_args(&args(&["-H", "Accept: */*", "-H", &at, "--header-file", path, "https://x.com"])).unwrap();
+        assert_eq!(
+            cfg.headers,
+            [
+                "Accept: */*",
+                "Authorization: Bearer eyJ.x.y",
+                "X-Signature: abc",
+                "Authorization: Bearer eyJ.x.y",
+                "X-Signature: abc",
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+        assert!(parse_args(&args(&["--header-file", path, "https://x.com"])).unwrap_err().contains(path));
+    }
+
+    #[test]
+    fn connect_timeout_and_max_time() {
+        let cfg = parse_args(&args(&[
+            "--connect-timeout", "10", "--max-time", "30", "https://x.com",
         ]))
         .unwrap();
         assert_eq!(cfg.connect_timeout, Some(Duration::from_secs(10)));
@@ -501,10 +1766,151 @@ mod tests {
         assert!(parse_args(&args(&["--connect-timeout", "abc", "https://x.com"])).is_err());
     }
 
+    #[test]
+    fn max_time_fractional_seconds() {
+        let cfg = parse_args(&args(&["--max-time", "0.5", "https://x.com"])).unwrap();
+        assert_eq!(cfg.max_time, Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn read_and_dns_timeouts() {
+        let cfg = parse_args(&args(&[
+            "--read-timeout", "20s", "--dns-timeout", "250ms", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.read_timeout, Some(Duration::from_secs(20)));
+        assert_eq!(cfg.dns_timeout, Some(Duration::from_millis(250)));
+        assert!(cfg.max_time.is_none());
+    }
+
+    #[test]
+    fn retry_flags() {
+        let cfg = parse_args(&args(&[
+            "--retry", "5", "--retry-delay", "2s", "--retry-report", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.retry, 5);
+        assert_eq!(cfg.retry_delay, Some(Duration::from_secs(2)));
+        assert!(cfg.retry_report);
+    }
+
+    #[test]
+    fn retry_bad_value() {
+        assert!(parse_args(&args(&["--retry", "many", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn dns_cache_flags() {
+        let cfg = parse_args(&args(&["--dns-cache-timeout", "5m", "https://x.com"])).unwrap();
+        assert!(cfg.dns_cache);
+        assert_eq!(cfg.dns_cache_timeout, Duration::from_secs(300));
+        let cfg = parse_args(&args(&["--no-dns-cache", "https://x.com"])).unwrap();
+        assert!(!cfg.dns_cache);
+    }
+
+    #[test]
+    fn seed_flag() {
+        let cfg = parse_args(&args(&["--seed", "1234", "https://x.com"])).unwrap();
+        assert_eq!(cfg.seed, Some(1234));
+        assert!(parse_args(&args(&["--seed", "-1", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn message_format_flag() {
+        let cfg = parse_args(&args(&["--message-format", "json", "https://x.com"])).unwrap();
+        assert_eq!(cfg.message_format, MessageFormat::Json);
+        assert!(parse_args(&args(&["--message-format", "xml", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn duration_suffixes() {
+        assert_eq!(parse_duration("500ms", "t"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s", "t"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m", "t"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h", "t"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("1.5m", "t"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("10", "t"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("0", "t"), Ok(Duration::ZERO));
+        assert_eq!(parse_duration("0.0001", "t"), Ok(Duration::from_millis(1)));
+        assert_eq!(parse_duration("0.4ms", "t"), Ok(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn duration_rejects_bad_values() {
+        assert!(parse_duration("", "t").is_err());
+        assert!(parse_duration("ms", "t").is_err());
+        assert!(parse_duration("-1", "t").is_err());
+        assert!(parse_duration("5d", "t").is_err());
+        assert!(parse_duration("NaN", "t").is_err());
+        assert!(parse_duration("inf", "t").is_err());
+    }
+
     #[test]
     fn output_flag() {
         let cfg = parse_args(&args(&["-o", "/tmp/out.html", "https://x.com"])).unwrap();
         assert_eq!(cfg.output.as_deref(), Some("/tmp/out.html"));
+        assert!(parse_args(&args(&["-o", "-", "https://x.com"])).unwrap().output.is_none());
+    }
+
+    #[test]
+    fn multiple_urls() {
+        let cfg = parse_args(&args(&["-o", "a.html", "https://x.com/a", "-o", "-", "https://x.com/b", "https://x.com/c"]))
+            .unwrap();
+        assert_eq!(cfg.url, "https://x.com/a");
+        assert_eq!(cfg.output.as_deref(), Some("a.html"));
+        assert_eq!(
+            cfg.more_urls,
+            [("https://x.com/b".to_string(), None), ("https://x.com/c".to_string(), None)]
+        );
+        assert!(parse_args(&args(&["https://x.com"])).unwrap().more_urls.is_empty());
+
+        assert!(parse_args(&args(&["-o", "a", "-o", "b", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--watch", "30s", "https://x.com/a", "https://x.com/b"])).is_err());
+    }
+
+    #[test]
+    fn accept_push_flag() {
+        let cfg = parse_args(&args(&["--accept-push", "pushed", "https://x.com"])).unwrap();
+        assert_eq!(cfg.accept_push.as_deref(), Some("pushed"));
+        assert!(parse_args(&args(&["https://x.com"])).unwrap().accept_push.is_none());
+        assert!(parse_args(&args(&["relay", "--listen", "127.0.0.1:0", "--accept-push", "-"])).is_err());
+    }
+
+    #[test]
+    fn url_globs() {
+        let cfg = parse_args(&args(&["-o", "p#1_#2.html", "https://{a,b}.x.com/p[1-2]"])).unwrap();
+        assert_eq!(cfg.url, "https://a.x.com/p1");
+        assert_eq!(cfg.output.as_deref(), Some("pa_1.html"));
+        let more: Vec<_> = cfg.more_urls.iter().map(|(u, o)| (u.as_str(), o.as_deref())).collect();
+        assert_eq!(
+            more,
+            [
+                ("https://a.x.com/p2", Some("pa_2.html")),
+                ("https://b.x.com/p1", Some("pb_1.html")),
+                ("https://b.x.com/p2", Some("pb_2.html")),
+            ]
+        );
+
+        let cfg = parse_args(&args(&["-g", "https://x.com/?a[]=1"])).unwrap();
+        assert_eq!(cfg.url, "https://x.com/?a[]=1");
+        assert!(cfg.more_urls.is_empty());
+        assert!(parse_args(&args(&["https://x.com/?a[]=1"])).unwrap_err().contains("--globoff"));
+        assert!(parse_args(&args(&["--capture", "x=$.a", "https://x.com/[1-2]"])).is_err());
+    }
+
+    #[test]
+    fn include_flag() {
+        assert!(parse_args(&args(&["-i", "https://x.com"])).unwrap().include);
+        assert!(parse_args(&args(&["--include", "https://x.com"])).unwrap().include);
+    }
+
+    #[test]
+    fn write_out_flag() {
+        let cfg = parse_args(&args(&["-w", "%{http_code}\\n", "https://x.com"])).unwrap();
+        assert_eq!(cfg.write_out.as_deref(), Some("%{http_code}\\n"));
+        assert!(cfg.wants_timing());
+        let err = parse_args(&args(&["-w", "%{nope}", "https://x.com"])).unwrap_err();
+        assert!(err.contains("nope"));
     }
 
     #[test]
@@ -526,6 +1932,14 @@ mod tests {
         assert_eq!(cfg.proxy_password.as_deref(), Some("ppass"));
     }
 
+    #[test]
+    fn preproxy_flag() {
+        let cfg = parse_args(&args(&["--preproxy", "socks5://jump:1080", "-x", "http://p:3128", "https://x.com"]));
+        let cfg = cfg.unwrap();
+        assert_eq!(cfg.preproxy.as_deref(), Some("socks5://jump:1080"));
+        assert!(parse_args(&args(&["--preproxy"])).is_err());
+    }
+
     #[test]
     fn noproxy_flag() {
         let cfg = parse_args(&args(&["--noproxy", "localhost,127.0.0.1", "https://x.com"])).unwrap();
@@ -538,16 +1952,342 @@ mod tests {
             "-b", "/tmp/cookies", "-c", "/tmp/jar", "https://x.com",
         ]))
         .unwrap();
-        assert_eq!(cfg.cookie.as_deref(), Some("/tmp/cookies"));
+        assert_eq!(cfg.cookies, vec![CookieSource::File("/tmp/cookies".into())]);
         assert_eq!(cfg.cookie_jar.as_deref(), Some("/tmp/jar"));
     }
 
+    #[test]
+    fn cookie_repeatable_file_and_inline() {
+        let cfg = parse_args(&args(&[
+            "-b", "/tmp/cookies", "-b", "sid=abc; lang=en", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cfg.cookies,
+            vec![
+                CookieSource::File("/tmp/cookies".into()),
+                CookieSource::Inline("sid=abc; lang=en".into()),
+            ]
+        );
+    }
+
     #[test]
     fn bearer_flag() {
         let cfg = parse_args(&args(&["--bearer", "tok123", "https://x.com"])).unwrap();
         assert_eq!(cfg.bearer.as_deref(), Some("tok123"));
     }
 
+    #[test]
+    fn auth_timing_flags() {
+        let cfg = parse_args(&args(&["-u", "u:p", "https://x.com"])).unwrap();
+        assert!(!cfg.auth_on_challenge);
+        let cfg = parse_args(&args(&["--auth-on-challenge", "-u", "u:p", "https://x.com"])).unwrap();
+        assert!(cfg.auth_on_challenge);
+        let cfg = parse_args(&args(&["--auth-preemptive", "-u", "u:p", "https://x.com"])).unwrap();
+        assert!(!cfg.auth_on_challenge);
+        assert!(parse_args(&args(&["--auth-preemptive", "--auth-on-challenge", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn oauth2_refresh_flags() {
+        let cfg = parse_args(&args(&[
+            "--bearer", "old",
+            "--oauth2-token-url", "https://auth.x.com/token",
+            "--oauth2-refresh-token", "r1",
+            "--oauth2-client-id", "cli",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.oauth2_token_url.as_deref(), Some("https://auth.x.com/token"));
+        assert_eq!(cfg.oauth2_refresh_token.as_deref(), Some("r1"));
+        assert_eq!(cfg.oauth2_client_id.as_deref(), Some("cli"));
+        assert!(parse_args(&args(&["--oauth2-token-url", "https://a/t", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--bearer", "t", "--oauth2-client-id", "c", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn ssh_tunnel_flag() {
+        let cfg = parse_args(&args(&["--ssh-tunnel", "ops@bastion:2222", "http://internal/"])).unwrap();
+        assert_eq!(cfg.ssh_tunnel.as_deref(), Some("ops@bastion:2222"));
+        assert!(parse_args(&args(&["--ssh-tunnel", "b", "-x", "http://p:8080", "http://internal/"])).is_err());
+    }
+
+    #[test]
+    fn proxy_pac_flag() {
+        let cfg = parse_args(&args(&["--proxy-pac", "http://wpad/wpad.dat", "https://x.com"])).unwrap();
+        assert_eq!(cfg.proxy_pac.as_deref(), Some("http://wpad/wpad.dat"));
+        assert!(parse_args(&args(&["--proxy-pac", "p.pac", "-x", "http://p:8080", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn tor_flag() {
+        assert!(parse_args(&args(&["--tor", "https://x.com"])).unwrap().tor);
+        assert!(parse_args(&args(&["--tor", "-x", "http://p:8080", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn auth_chain_flag() {
+        let cfg = parse_args(&args(&["--auth-chain", "negotiate,basic", "-u", "u:p", "https://x.com"])).unwrap();
+        assert_eq!(cfg.auth_chain, vec![Mechanism::Negotiate, Mechanism::Basic]);
+        assert!(parse_args(&args(&["--auth-chain", "basic,kerberos", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--auth-chain", "basic", "--ntlm", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn anyauth_flag() {
+        let cfg = parse_args(&args(&["--anyauth", "-u", "u:p", "https://x.com"])).unwrap();
+        assert!(cfg.anyauth);
+        assert!(!parse_args(&args(&["https://x.com"])).unwrap().anyauth);
+        assert!(parse_args(&args(&["--anyauth", "--ntlm", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--anyauth", "--auth-chain", "basic", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn registry_auth_flag() {
+        let cfg = parse_args(&args(&["--registry-auth", "https://ghcr.io/v2/o/r/tags/list"])).unwrap();
+        assert!(cfg.registry_auth);
+        assert!(parse_args(&args(&["--registry-auth", "--bearer", "t", "https://ghcr.io/v2/"])).is_err());
+    }
+
+    #[test]
+    fn partial_body_flags() {
+        let cfg = parse_args(&args(&["--ignore-content-length", "--accept-partial", "https://x.com"])).unwrap();
+        assert!(cfg.ignore_content_length);
+        assert!(cfg.accept_partial);
+    }
+
+    #[test]
+    fn extract_flags() {
+        let cfg = parse_args(&args(&["--extract", "--output-dir", "/dl", "-o", "a.tgz", "https://x.com"])).unwrap();
+        assert!(cfg.extract);
+        assert_eq!(cfg.output_dir.as_deref(), Some("/dl"));
+        let err = parse_args(&args(&["--extract", "https://x.com"])).unwrap_err();
+        assert!(err.contains("requires -o"));
+        assert!(parse_args(&args(&["--extract", "-O", "https://x.com/a.tgz"])).is_ok());
+        assert!(!cfg.extract_overwrite);
+        let cfg = parse_args(&args(&["--extract", "--extract-overwrite", "-O", "https://x.com/a.tgz"])).unwrap();
+        assert!(cfg.extract_overwrite);
+        let err = parse_args(&args(&["--extract-overwrite", "-O", "https://x.com/a.tgz"])).unwrap_err();
+        assert!(err.contains("requires --extract"));
+    }
+
+    #[test]
+    fn remote_name_flag() {
+        assert!(parse_args(&args(&["-O", "https://x.com/a.bin"])).unwrap().remote_name);
+        assert!(parse_args(&args(&["--remote-name", "https://x.com/a.bin"])).unwrap().remote_name);
+        let err = parse_args(&args(&["-O", "-o", "b.bin", "https://x.com/a.bin"])).unwrap_err();
+        assert!(err.contains("cannot be combined"));
+    }
+
+    #[test]
+    fn remote_header_name_flag() {
+        let cfg = parse_args(&args(&["-O", "-J", "https://x.com/dl?id=7"])).unwrap();
+        assert!(cfg.remote_header_name);
+        let err = parse_args(&args(&["-J", "https://x.com/a.bin"])).unwrap_err();
+        assert!(err.contains("requires -O"));
+    }
+
+    #[test]
+    fn assert_timing_flags() {
+        let cfg = parse_args(&args(&[
+            "--assert-time-total-max",
+            "800ms",
+            "--assert-ttfb-max",
+            "0.2",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.assert_time_total_max, Some(std::time::Duration::from_millis(800)));
+        assert_eq!(cfg.assert_ttfb_max, Some(std::time::Duration::from_millis(200)));
+        assert!(cfg.wants_timing());
+        assert!(!cfg.show_timing);
+    }
+
+    #[test]
+    fn session_flag() {
+        let cfg = parse_args(&args(&["--session", "etl-nightly", "https://x.com"])).unwrap();
+        assert_eq!(cfg.session.as_deref(), Some("etl-nightly"));
+        assert!(parse_args(&args(&["--session", "../etc", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn print_config_flag() {
+        assert!(parse_args(&args(&["--print-config", "https://x.com"])).unwrap().print_config);
+        assert!(!parse_args(&args(&["https://x.com"])).unwrap().print_config);
+    }
+
+    #[test]
+    fn yes_flag() {
+        assert!(parse_args(&args(&["-k", "--yes", "https://x.com"])).unwrap().assume_yes);
+        assert!(!parse_args(&args(&["-k", "https://x.com"])).unwrap().assume_yes);
+    }
+
+    #[test]
+    fn prom_out_flag() {
+        let cfg = parse_args(&args(&["--prom-out", "/var/lib/node_exporter/x.prom", "https://x.com"])).unwrap();
+        assert_eq!(cfg.prom_out.as_deref(), Some("/var/lib/node_exporter/x.prom"));
+        assert!(cfg.wants_timing());
+    }
+
+    #[test]
+    fn tail_flag() {
+        let cfg = parse_args(&args(&["--tail", "2s", "https://ci.x.com/build/42/log"])).unwrap();
+        assert_eq!(cfg.tail, Some(Duration::from_secs(2)));
+        assert!(parse_args(&args(&["--tail", "2s", "-o", "log", "https://x.com/log"])).is_err());
+        assert!(parse_args(&args(&["--tail", "2s", "https://x.com/a", "https://x.com/b"])).is_err());
+    }
+
+    #[test]
+    fn watch_flags() {
+        let cfg = parse_args(&args(&["--watch", "30s", "--watch-diff", "https://x.com"])).unwrap();
+        assert_eq!(cfg.watch, Some(Duration::from_secs(30)));
+        assert!(cfg.watch_diff);
+        let err = parse_args(&args(&["--watch-diff", "https://x.com"])).unwrap_err();
+        assert!(err.contains("requires --watch"));
+    }
+
+    #[test]
+    fn capture_flags() {
+        let cfg = parse_args(&args(&[
+            "--capture", "token=$.access_token",
+            "--capture", "rid=header:X-Request-Id",
+            "--capture-file", "vars.env",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.captures.len(), 2);
+        assert_eq!(cfg.captures[1].name, "rid");
+        assert_eq!(cfg.capture_file.as_deref(), Some("vars.env"));
+        assert!(parse_args(&args(&["--capture", "t=$.a", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--capture-file", "v.env", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--capture", "t=.a", "--capture-file", "v.env", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn batch_flag() {
+        let cfg = parse_args(&args(&["--batch", "-", "-H", "X-A: 1"])).unwrap();
+        assert_eq!(cfg.batch.as_deref(), Some("-"));
+        assert!(cfg.url.is_empty());
+        assert!(parse_args(&args(&["--batch", "-", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--batch", "-", "-o", "out"])).is_err());
+    }
+
+    #[test]
+    fn collection_flag() {
+        let cfg = parse_args(&args(&["--collection", "suite.yaml", "-H", "X-A: 1"])).unwrap();
+        assert_eq!(cfg.collection.as_deref(), Some("suite.yaml"));
+        assert!(parse_args(&args(&["--collection", "suite.yaml", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--collection", "suite.yaml", "--batch", "-"])).is_err());
+        assert!(parse_args(&args(&["--collection", "suite.yaml", "-O"])).is_err());
+    }
+
+    #[test]
+    fn diagnose_flag() {
+        assert!(parse_args(&args(&["--diagnose", "https://x.com"])).unwrap().diagnose);
+        assert!(!parse_args(&args(&["https://x.com"])).unwrap().diagnose);
+    }
+
+    #[test]
+    fn relay_command() {
+        let cfg = parse_args(&args(&["relay", "--listen", "127.0.0.1:9999", "-x", "http://p:8080", "--proxy-negotiate"]))
+            .unwrap();
+        assert_eq!(cfg.relay.as_deref(), Some("127.0.0.1:9999"));
+        assert!(cfg.proxy_negotiate);
+        assert!(!cfg.relay_remote);
+        assert!(parse_args(&args(&["relay", "--listen", "0.0.0.0:9999", "--listen-remote"])).unwrap().relay_remote);
+        assert!(parse_args(&args(&["--listen-remote", "https://x.com"])).is_err());
+        assert!(cfg.url.is_empty());
+        assert!(parse_args(&args(&["relay", "-x", "http://p:8080"])).is_err());
+        assert!(parse_args(&args(&["relay", "--listen", ":9999", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["relay", "--listen", ":9999", "-o", "out"])).is_err());
+        assert!(parse_args(&args(&["--listen", ":9999", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn if_changed_flag() {
+        let cfg = parse_args(&args(&["--if-changed", "page.sha256", "https://x.com"])).unwrap();
+        assert_eq!(cfg.if_changed.as_deref(), Some("page.sha256"));
+    }
+
+    #[test]
+    fn notify_flags() {
+        let cfg = parse_args(&args(&[
+            "--watch", "1m", "--notify-cmd", "logger down", "--notify-desktop",
+            "--notify-debounce", "3", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.notify_cmd.as_deref(), Some("logger down"));
+        assert!(cfg.notify_desktop);
+        assert_eq!(cfg.notify_debounce, 3);
+        assert_eq!(parse_args(&args(&["--watch", "1m", "https://x.com"])).unwrap().notify_debounce, 1);
+        assert!(parse_args(&args(&["--notify-desktop", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn provenance_flag() {
+        let cfg = parse_args(&args(&["--provenance", "-o", "a.bin", "https://x.com"])).unwrap();
+        assert!(cfg.provenance);
+        assert!(parse_args(&args(&["--provenance", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn verify_sig_flags() {
+        let cfg = parse_args(&args(&["--verify-sig", "a.minisig", "--verify-key", "k.pub", "https://x.com"])).unwrap();
+        assert_eq!(cfg.verify_sig.as_deref(), Some("a.minisig"));
+        assert_eq!(cfg.verify_key.as_deref(), Some("k.pub"));
+        let err = parse_args(&args(&["--verify-sig", "a.minisig", "https://x.com"])).unwrap_err();
+        assert!(err.contains("together"));
+    }
+
+    #[test]
+    fn range_flag() {
+        let cfg = parse_args(&args(&["-r", "0-99,200-299", "https://x.com"])).unwrap();
+        assert_eq!(cfg.range.as_deref(), Some("0-99,200-299"));
+        assert!(parse_args(&args(&["--range", "99-0", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn no_mime_sniff_flag() {
+        assert!(parse_args(&args(&["https://x.com"])).unwrap().mime_sniff);
+        let cfg = parse_args(&args(&["--no-mime-sniff", "https://x.com"])).unwrap();
+        assert!(!cfg.mime_sniff);
+    }
+
+    #[test]
+    fn output_charset_flag() {
+        let cfg = parse_args(&args(&["--output-charset", "latin1", "https://x.com"])).unwrap();
+        assert_eq!(cfg.output_charset.as_deref(), Some("latin1"));
+        let err = parse_args(&args(&["--output-charset", "klingon", "https://x.com"])).unwrap_err();
+        assert!(err.contains("unknown charset"));
+    }
+
+    #[test]
+    fn eol_flags() {
+        let cfg = parse_args(&args(&["--crlf", "--normalize-eol", "CRLF", "-d", "a", "https://x.com"])).unwrap();
+        assert!(cfg.crlf);
+        assert_eq!(cfg.normalize_eol, Some(Eol::Crlf));
+        assert!(parse_args(&args(&["--normalize-eol", "cr", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn content_digest_flags() {
+        let flags = ["--content-digest", "sha-256,SHA-512", "--content-md5", "-d", "a", "https://x.com"];
+        let cfg = parse_args(&args(&flags)).unwrap();
+        assert_eq!(cfg.content_digest, [DigestAlgorithm::Sha256, DigestAlgorithm::Sha512]);
+        assert!(cfg.content_md5);
+        let err = parse_args(&args(&["--content-digest", "md5", "https://x.com"])).unwrap_err();
+        assert!(err.contains("unknown algorithm md5"), "{err}");
+    }
+
+    #[test]
+    fn jwe_flags() {
+        let flags = ["--encrypt-jwe", "psp.pem", "--decrypt-jwe", "ours.pem", "-d", "{}", "https://x.com"];
+        let cfg = parse_args(&args(&flags)).unwrap();
+        assert_eq!(cfg.encrypt_jwe.as_deref(), Some("psp.pem"));
+        assert_eq!(cfg.decrypt_jwe.as_deref(), Some("ours.pem"));
+        assert!(parse_args(&args(&["https://x.com", "--decrypt-jwe"])).is_err());
+    }
+
     #[test]
     fn compressed_flag() {
         let cfg = parse_args(&args(&["--compressed", "https://x.com"])).unwrap();
@@ -566,6 +2306,58 @@ mod tests {
         assert_eq!(cfg.user_agent.as_deref(), Some("myagent/1.0"));
     }
 
+    #[test]
+    fn accept_flags() {
+        let cfg = parse_args(&args(&[
+            "--accept", "text/html, application/xhtml+xml;level=1;q=0.9, */*;q=0.8",
+            "--accept-language", "de-CH,de;q=0.9,*;q=0.1",
+            "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cfg.headers,
+            vec![
+                "Accept: text/html,application/xhtml+xml;level=1;q=0.9,*/*;q=0.8",
+                "Accept-Language: de-CH,de;q=0.9,*;q=0.1",
+            ]
+        );
+        for bad in ["json", "*/json", "text/html;q=1.5", "text/html;q=0.1234", "text/html,", "text/html;charset"] {
+            assert!(parse_args(&args(&["--accept", bad, "https://x.com"])).is_err(), "{bad}");
+        }
+        for bad in ["en_US", "toolongtag", "en;level=1", "de;q=2", "en-"] {
+            assert!(parse_args(&args(&["--accept-language", bad, "https://x.com"])).is_err(), "{bad}");
+        }
+        assert!(parse_args(&args(&["--accept", "*/*", "-H", "accept: text/html", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn accept_flags_override_impersonation() {
+        let cfg = parse_args(&args(&["--impersonate", "chrome", "--accept-language", "fr", "https://x.com"])).unwrap();
+        let language: Vec<_> = cfg
+            .impersonated_headers()
+            .into_iter()
+            .filter(|(name, _)| *name == "Accept-Language")
+            .collect();
+        assert!(language.is_empty());
+        assert_eq!(cfg.headers, vec!["Accept-Language: fr"]);
+    }
+
+    #[test]
+    fn user_agent_preset_flag() {
+        let cfg = parse_args(&args(&["--user-agent-preset", "googlebot", "https://x.com"])).unwrap();
+        assert_eq!(cfg.user_agent.as_deref(), Some(Preset::Googlebot.user_agent()));
+        assert!(parse_args(&args(&["--user-agent-preset", "lynx", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["--user-agent-preset", "curl", "-A", "me", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn impersonate_flag() {
+        let cfg = parse_args(&args(&["--impersonate", "firefox", "https://x.com"])).unwrap();
+        assert_eq!(cfg.impersonate, Some(Preset::Firefox));
+        assert!(cfg.user_agent.is_none());
+        assert!(parse_args(&args(&["--impersonate", "googlebot", "https://x.com"])).is_err());
+    }
+
     #[test]
     fn max_redirs_flag() {
         let cfg = parse_args(&args(&["--max-redirs", "3", "https://x.com"])).unwrap();
@@ -617,12 +2409,12 @@ mod tests {
 
         assert_eq!(cfg.method, Method::Post);
         assert_eq!(cfg.headers, vec!["Content-Type: application/json"]);
-        assert_eq!(cfg.data.as_deref(), Some("{\"a\":1}"));
+        assert_eq!(cfg.data.as_deref(), Some(&b"{\"a\":1}"[..]));
         assert_eq!(cfg.output.as_deref(), Some("/tmp/out"));
         assert!(cfg.head_only);
         assert!(cfg.silent);
         assert_eq!(cfg.user_agent.as_deref(), Some("agent/1"));
-        assert_eq!(cfg.cookie.as_deref(), Some("/cookies"));
+        assert_eq!(cfg.cookies, vec![CookieSource::File("/cookies".into())]);
         assert_eq!(cfg.cookie_jar.as_deref(), Some("/jar"));
         assert!(cfg.ntlm);
         assert_eq!(cfg.bearer.as_deref(), Some("token"));
@@ -649,6 +2441,16 @@ mod tests {
         assert!(cfg.proxy_ntlm);
     }
 
+    #[test]
+    fn proxy_auth_method_flags() {
+        let parse = |flag: &str| parse_args(&args(&[flag, "-x", "http://proxy:8080", "https://x.com"])).unwrap();
+        assert!(parse("--proxy-digest").proxy_digest);
+        assert!(parse("--proxy-basic").proxy_basic);
+        assert!(parse("--proxy-anyauth").proxy_anyauth);
+        let err = parse_args(&args(&["--proxy-digest", "--proxy-ntlm", "https://x.com"])).unwrap_err();
+        assert!(err.starts_with("choose one of"), "{err}");
+    }
+
     #[test]
     fn proxy_insecure_flag() {
         let cfg = parse_args(&args(&["--proxy-insecure", "-x", "http://proxy:8080", "https://x.com"])).unwrap();
@@ -678,4 +2480,116 @@ mod tests {
         let cfg = parse_args(&args(&["--ssl-no-revoke", "https://x.com"])).unwrap();
         assert!(cfg.ssl_no_revoke);
     }
+
+    #[test]
+    fn url_option_and_no_curlrc() {
+        let cfg = parse_args(&args(&["-q", "--url", "https://x.com/a", "https://x.com/b"])).unwrap();
+        assert_eq!(cfg.url, "https://x.com/a");
+        assert_eq!(cfg.more_urls, vec![("https://x.com/b".to_string(), None)]);
+        assert!(parse_args(&args(&["--url"])).is_err());
+    }
+
+    #[test]
+    fn http2_flags() {
+        assert_eq!(parse_args(&args(&["--http2", "https://x.com"])).unwrap().http2, Some(Http2::Negotiate));
+        let cfg = parse_args(&args(&["--http2", "--http2-prior-knowledge", "http://x.com"])).unwrap();
+        assert_eq!(cfg.http2, Some(Http2::PriorKnowledge));
+        assert_eq!(parse_args(&args(&["https://x.com"])).unwrap().http2, None);
+    }
+
+    #[test]
+    fn h2_priority_flag() {
+        let cfg = parse_args(&args(&["--h2-priority", "256", "https://x.com"])).unwrap();
+        assert_eq!(cfg.h2_priority, Some(256));
+        for bad in ["0", "257", "high"] {
+            assert!(parse_args(&args(&["--h2-priority", bad, "https://x.com"])).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn next_starts_a_group() {
+        let cfgs = parse_groups(&args(&[
+            "-v",
+            "-X",
+            "PUT",
+            "-d",
+            "a=1",
+            "https://x.com/a",
+            "https://x.com/b",
+            "--next",
+            "-H",
+            "X-B: 1",
+            "https://x.com/c",
+            "-:",
+            "--message-format",
+            "short",
+            "-I",
+            "https://x.com/d",
+        ]))
+        .unwrap();
+        assert_eq!(cfgs.len(), 3);
+        assert_eq!(cfgs[0].method, Method::Put);
+        assert_eq!(cfgs[0].more_urls.len(), 1);
+        assert_eq!(cfgs[1].method, Method::Get);
+        assert!(cfgs[1].body().is_none());
+        assert_eq!(cfgs[1].headers, vec!["X-B: 1"]);
+        assert_eq!(cfgs[2].method, Method::Head);
+        assert!(cfgs.iter().all(|c| c.verbose && c.message_format == MessageFormat::Short));
+
+        assert_eq!(parse_groups(&args(&["https://x.com"])).unwrap().len(), 1);
+        assert!(parse_groups(&args(&["https://x.com", "--next"])).is_err());
+        assert!(parse_groups(&args(&["-X", "PUT", "--next", "https://x.com"])).is_err());
+        assert!(parse_groups(&args(&["https://x.com", "--next", "--watch", "30s", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["https://x.com", "--next", "https://y.com"])).is_err());
+    }
+
+    #[test]
+    fn tls_version_flags() {
+        let cfg = parse_args(&args(&["--tlsv1.2", "--tls-max", "1.3", "https://x.com"])).unwrap();
+        assert_eq!((cfg.tls_min, cfg.tls_max), (Some(TlsVersion::V1_2), Some(TlsVersion::V1_3)));
+        assert_eq!(parse_args(&args(&["-1", "https://x.com"])).unwrap().tls_min, Some(TlsVersion::V1_0));
+        let err = parse_args(&args(&["--tlsv1.3", "--tls-max", "1.2", "https://x.com"])).unwrap_err();
+        assert_eq!(err, "--tls-max TLS 1.2 is older than the minimum TLS 1.3");
+        assert!(parse_args(&args(&["--tls-max", "2", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn cipher_flags() {
+        let cfg = parse_args(&args(&[
+            "--ciphers", "ECDHE-RSA-AES128-GCM-SHA256", "--tls13-ciphers", "TLS_AES_256_GCM_SHA384", "https://x.com",
+        ]))
+        .unwrap();
+        assert_eq!(cfg.ciphers.as_deref(), Some("ECDHE-RSA-AES128-GCM-SHA256"));
+        assert_eq!(cfg.tls13_ciphers.as_deref(), Some("TLS_AES_256_GCM_SHA384"));
+    }
+
+    #[test]
+    fn keylog_and_show_certs_flags() {
+        let cfg = parse_args(&args(&["--keylog", "/tmp/tls.keys", "https://x.com"])).unwrap();
+        assert_eq!(cfg.keylog.as_deref(), Some("/tmp/tls.keys"));
+        assert!(parse_args(&args(&["--show-certs", "https://x.com"])).unwrap().show_certs);
+        assert!(parse_args(&args(&["--tofu", "https://x.com"])).unwrap().tofu);
+        assert!(parse_args(&args(&["--tofu", "-k", "https://x.com"])).is_err());
+        assert!(parse_args(&args(&["https://x.com", "--keylog"])).is_err());
+    }
+
+    #[test]
+    fn tls_session_flags() {
+        let cfg =
+            parse_args(&args(&["--tls-session-cache", "s.txt", "--tls-early-data", "https://x.com"])).unwrap();
+        assert_eq!(cfg.tls_session_cache.as_deref(), Some("s.txt"));
+        assert!(cfg.tls_early_data);
+        assert!(parse_args(&args(&["--tls-early-data", "https://x.com"])).is_err());
+    }
+
+    #[test]
+    fn ech_flag() {
+        for mode in ["grease", "hard", "ecl:AED+DQA8", "pn:public.example"] {
+            let cfg = parse_args(&args(&["--ech", mode, "https://x.com"])).unwrap();
+            assert_eq!(cfg.ech.as_deref(), Some(mode));
+        }
+        for bad in ["yes", "ecl:", "pn:"] {
+            assert!(parse_args(&args(&["--ech", bad, "https://x.com"])).is_err(), "{bad}");
+        }
+    }
 }
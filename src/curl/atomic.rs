@@ -0,0 +1,215 @@
+// src/curl/atomic.rs
+
+//! Replacing state files that several rustcurl runs may share: the cookie
+//! jar, `--tls-session-cache`, `--session` totals, `--if-changed` state and
+//! `--capture-to` files. New content goes to a temporary file beside the
+//! target, is synced, then renamed over it, so a reader sees the old file
+//! or the new one, never a torn write. Read-modify-write updates also hold
+//! `<file>.lock`, waiting at most `LOCK_TIMEOUT` for it rather than hanging
+//! behind a stuck run. The lock file is left in place: removing it would
+//! let a waiter lock a file nobody else sees.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long `lock` waits for another run to let go.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Temporary files older than this are taken to belong to a run that died
+/// mid-write, and are removed by the next write beside them.
+const STALE_TEMP: Duration = Duration::from_secs(600);
+
+/// Held while a state file is read and rewritten; dropping it unlocks.
+#[derive(Debug)]
+pub struct Lock {
+    _file: File,
+}
+
+/// Take `<path>.lock`, creating it if needed.
+pub fn lock(path: &Path) -> io::Result<Lock> {
+    lock_within(path, LOCK_TIMEOUT)
+}
+
+fn lock_within(path: &Path, timeout: Duration) -> io::Result<Lock> {
+    let lock_path = sibling(path, ".lock");
+    let file = OpenOptions::new().write(true).create(true).truncate(false).open(&lock_path)?;
+    let start = Instant::now();
+    let mut pause = Duration::from_millis(5);
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(Lock { _file: file }),
+            Err(TryLockError::Error(e)) => return Err(e),
+            Err(TryLockError::WouldBlock) if start.elapsed() >= timeout => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("timed out after {}s waiting for {}", timeout.as_secs(), lock_path.display()),
+                ));
+            }
+            Err(TryLockError::WouldBlock) => {
+                thread::sleep(pause);
+                pause = (pause * 2).min(Duration::from_millis(200));
+            }
+        }
+    }
+}
+
+/// Replace `path` with `data`. An existing file's permissions carry over.
+pub fn write(path: &Path, data: &[u8]) -> io::Result<()> {
+    replace(path, data, false)
+}
+
+/// Like `write`, but the file is readable by its owner only.
+pub fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    replace(path, data, true)
+}
+
+fn replace(path: &Path, data: &[u8], private: bool) -> io::Result<()> {
+    remove_stale_temps(path);
+    let tmp = temp_path(path);
+    let result = write_temp(&tmp, path, data, private).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+    sync_dir(path);
+    Ok(())
+}
+
+fn write_temp(tmp: &Path, target: &Path, data: &[u8], private: bool) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    let mut file = options.open(tmp)?;
+    if !private && let Ok(meta) = fs::metadata(target) {
+        file.set_permissions(meta.permissions())?;
+    }
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// `dir/.name.<pid>.<n>.tmp`, unique within and across runs.
+fn temp_path(path: &Path) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let mut name = temp_prefix(path);
+    name.push(format!("{}.{n}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+fn temp_prefix(path: &Path) -> OsString {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".");
+    name
+}
+
+fn remove_stale_temps(path: &Path) {
+    let prefix = temp_prefix(path);
+    let prefix = prefix.to_string_lossy();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let ours = name.starts_with(&*prefix) && name.ends_with(".tmp");
+        let old = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() > STALE_TEMP);
+        if ours && old {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Make the rename itself durable. Best effort: not every platform or
+/// file system lets a directory be opened and synced.
+fn sync_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        let _ = File::open(dir).and_then(|dir| dir.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustcurl-atomic-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn replaces_and_cleans_up() {
+        let dir = scratch("replace");
+        let path = dir.join("state");
+        write(&path, b"one").unwrap();
+        write(&path, b"two").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+        let stale = dir.join(".state.1.0.tmp");
+        fs::write(&stale, "half").unwrap();
+        File::options()
+            .write(true)
+            .open(&stale)
+            .unwrap()
+            .set_modified(SystemTime::now() - STALE_TEMP * 2)
+            .unwrap();
+        let fresh = dir.join(".state.2.0.tmp");
+        fs::write(&fresh, "in flight").unwrap();
+        write(&path, b"three").unwrap();
+        assert!(!stale.exists() && fresh.exists());
+        assert!(write(&dir.join("missing").join("state"), b"x").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_or_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = scratch("mode");
+        let path = dir.join("shared");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        write(&path, b"x").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        write_private(&path, b"y").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lock_times_out_while_held() {
+        let dir = scratch("lock");
+        let path = dir.join("jar");
+        let held = lock(&path).unwrap();
+        let err = lock_within(&path, Duration::from_millis(50)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        drop(held);
+        lock_within(&path, Duration::from_millis(50)).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
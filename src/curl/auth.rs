@@ -0,0 +1,224 @@
+// src/curl/auth.rs
+
+//! `--auth-chain`: try authentication mechanisms in the configured order,
+//! moving on to the next one the server offers in `WWW-Authenticate`
+//! whenever an attempt comes back 401.
+
+use std::fmt;
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::request::resolve_username;
+use super::response::Response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mechanism {
+    Negotiate,
+    Ntlm,
+    Basic,
+    Bearer,
+}
+
+impl Mechanism {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "negotiate" => Some(Mechanism::Negotiate),
+            "ntlm" => Some(Mechanism::Ntlm),
+            "basic" => Some(Mechanism::Basic),
+            "bearer" => Some(Mechanism::Bearer),
+            _ => None,
+        }
+    }
+
+    /// Parse a comma-separated chain, rejecting unknown and repeated names.
+    pub fn parse_chain(s: &str) -> Result<Vec<Self>, String> {
+        let mut chain = Vec::new();
+        for name in s.split(',') {
+            let mechanism = Mechanism::parse(name).ok_or_else(|| format!("unknown mechanism '{}'", name.trim()))?;
+            if chain.contains(&mechanism) {
+                return Err(format!("{mechanism} is listed twice"));
+            }
+            chain.push(mechanism);
+        }
+        Ok(chain)
+    }
+
+    /// Whether the request has what this mechanism needs: a user for NTLM
+    /// and Basic, a token for Bearer. Negotiate can fall back on the
+    /// current Kerberos ticket.
+    pub fn has_credentials(&self, config: &RequestConfig) -> bool {
+        match self {
+            Mechanism::Negotiate => true,
+            Mechanism::Ntlm | Mechanism::Basic => resolve_username(config).is_some(),
+            Mechanism::Bearer => config.bearer.is_some(),
+        }
+    }
+
+    /// `config` set up to authenticate with this mechanism only.
+    pub fn configure(&self, config: &RequestConfig) -> RequestConfig {
+        let mut config = config.clone();
+        config.negotiate = *self == Mechanism::Negotiate;
+        config.ntlm = *self == Mechanism::Ntlm;
+        if *self == Mechanism::Bearer {
+            // Otherwise the user would go out as Basic alongside the token
+            config.username = None;
+            config.password = None;
+        } else {
+            config.bearer = None;
+        }
+        config
+    }
+}
+
+impl fmt::Display for Mechanism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Mechanism::Negotiate => "Negotiate",
+            Mechanism::Ntlm => "NTLM",
+            Mechanism::Basic => "Basic",
+            Mechanism::Bearer => "Bearer",
+        })
+    }
+}
+
+/// Mechanisms offered in the `WWW-Authenticate` headers of the final
+/// response, skipping schemes this chain does not know.
+pub fn offered(headers: &[String]) -> Vec<Mechanism> {
+    let last = headers.iter().rposition(|h| h.starts_with("HTTP/")).map_or(0, |i| i + 1);
+    let mut mechanisms = Vec::new();
+    for header in &headers[last..] {
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        if !name.trim().eq_ignore_ascii_case("www-authenticate") {
+            continue;
+        }
+        // Each challenge starts with a scheme token; its parameters
+        // (`realm="x"`) and token68 values follow it.
+        for part in value.split(',') {
+            let word = part.split_whitespace().next().unwrap_or("");
+            if let Some(m) = Mechanism::parse(word).filter(|m| !word.contains('=') && !mechanisms.contains(m)) {
+                mechanisms.push(m);
+            }
+        }
+    }
+    mechanisms
+}
+
+/// Send with the first mechanism in the chain that has credentials, then
+/// on each 401 escalate to the next untried one the server offers.
+/// Returns the last response, which is still a 401 when the chain runs out.
+pub fn perform(
+    config: &RequestConfig,
+    mut send: impl FnMut(&RequestConfig) -> Result<Response, RequestError>,
+) -> Result<Response, RequestError> {
+    let mut tried = Vec::new();
+    let mut candidates: Vec<Mechanism> = config.auth_chain.clone();
+    let mut response = None;
+    while let Some(mechanism) = candidates
+        .iter()
+        .copied()
+        .find(|m| !tried.contains(m) && m.has_credentials(config))
+    {
+        tried.push(mechanism);
+        config.note(format_args!("Trying {mechanism} authentication"));
+        let attempt = send(&mechanism.configure(config))?;
+        if attempt.status_code != 401 {
+            return Ok(attempt);
+        }
+        let challenges = offered(&attempt.headers);
+        candidates.retain(|m| challenges.contains(m));
+        response = Some(attempt);
+    }
+    match response {
+        Some(response) => Ok(response),
+        None => Err(RequestError::Config {
+            option: "--auth-chain".to_string(),
+            message: "no mechanism in the chain has credentials (-u for ntlm/basic, --bearer for bearer)"
+                .to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unauthorized(challenges: &[&str]) -> Response {
+        Response {
+            status_code: 401,
+            headers: challenges.iter().map(|c| format!("WWW-Authenticate: {c}")).collect(),
+            attempts: vec![401],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_chain() {
+        assert_eq!(
+            Mechanism::parse_chain("negotiate, NTLM,basic").unwrap(),
+            vec![Mechanism::Negotiate, Mechanism::Ntlm, Mechanism::Basic]
+        );
+        assert!(Mechanism::parse_chain("basic,digest").unwrap_err().contains("digest"));
+        assert!(Mechanism::parse_chain("basic,basic").unwrap_err().contains("twice"));
+    }
+
+    #[test]
+    fn offered_schemes() {
+        let headers = vec![
+            "HTTP/1.1 401 Unauthorized".to_string(),
+            "WWW-Authenticate: Negotiate".to_string(),
+            "www-authenticate: Basic realm=\"a, b\", Bearer realm=\"x\", error=\"invalid_token\"".to_string(),
+            "WWW-Authenticate: Digest realm=\"d\", nonce=\"n\"".to_string(),
+        ];
+        assert_eq!(offered(&headers), vec![Mechanism::Negotiate, Mechanism::Basic, Mechanism::Bearer]);
+    }
+
+    #[test]
+    fn offered_uses_final_response() {
+        let headers = vec![
+            "HTTP/1.1 401 Unauthorized".to_string(),
+            "WWW-Authenticate: NTLM".to_string(),
+            "HTTP/1.1 401 Unauthorized".to_string(),
+            "WWW-Authenticate: Basic realm=\"r\"".to_string(),
+        ];
+        assert_eq!(offered(&headers), vec![Mechanism::Basic]);
+    }
+
+    #[test]
+    fn escalates_to_offered_mechanism() {
+        let config = RequestConfig::new("https://x.com")
+            .auth_chain(vec![Mechanism::Negotiate, Mechanism::Ntlm, Mechanism::Basic])
+            .username("u");
+        let mut sent = Vec::new();
+        let response = perform(&config, |c| {
+            sent.push((c.negotiate, c.ntlm));
+            Ok(if sent.len() == 1 {
+                unauthorized(&["Basic realm=\"r\""])
+            } else {
+                Response { status_code: 200, ..unauthorized(&[]) }
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status_code, 200);
+        // NTLM is skipped because the server did not offer it
+        assert_eq!(sent, vec![(true, false), (false, false)]);
+    }
+
+    #[test]
+    fn skips_mechanisms_without_credentials() {
+        let config = RequestConfig::new("https://x.com").auth_chain(vec![Mechanism::Bearer, Mechanism::Basic]);
+        let err = perform(&config, |_| panic!("nothing to send")).unwrap_err();
+        assert_eq!(err.code(), "config");
+
+        let config = config.bearer("tok");
+        let mut calls = 0;
+        let response = perform(&config, |c| {
+            calls += 1;
+            assert_eq!(c.bearer.as_deref(), Some("tok"));
+            Ok(unauthorized(&["Bearer", "Basic"]))
+        })
+        .unwrap();
+        assert_eq!((response.status_code, calls), (401, 1));
+    }
+}
@@ -0,0 +1,176 @@
+// src/curl/batch.rs
+
+//! `--batch <FILE|->`: one JSON request spec per input line, one JSON
+//! summary per output line, so another program can drive rustcurl as a
+//! co-process. A spec is
+//! `{"id": 7, "url": "...", "method": "POST", "headers": ["A: b"], "data": "..."}`
+//! where only `url` is required; `headers` may also be an object. The
+//! other command-line options apply to every request.
+
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+use super::config::{Method, RequestConfig};
+use super::encoding::base64;
+use super::error::RequestError;
+use super::response::Response;
+
+/// One input line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    /// Echoed back so replies can be matched to requests.
+    pub id: Value,
+    pub url: String,
+    pub method: Option<Method>,
+    pub headers: Vec<String>,
+    pub data: Option<String>,
+}
+
+impl Spec {
+    pub fn parse(line: &str) -> Result<Spec, String> {
+        let json: Value = serde_json::from_str(line).map_err(|e| format!("not JSON: {e}"))?;
+        let Value::Object(ref fields) = json else {
+            return Err("expected a JSON object".to_string());
+        };
+        if let Some(unknown) = fields.keys().find(|k| !["id", "url", "method", "headers", "data"].contains(&k.as_str())) {
+            return Err(format!("unknown field '{unknown}'"));
+        }
+        let text = |name: &str| match fields.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(format!("'{name}' must be a string")),
+        };
+        let url = text("url")?.filter(|u| !u.is_empty()).ok_or("'url' is required")?;
+        let headers = match fields.get("headers") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(lines)) => lines
+                .iter()
+                .map(|h| h.as_str().map(str::to_string).ok_or("'headers' entries must be strings"))
+                .collect::<Result<_, _>>()?,
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(name, value)| match value {
+                    Value::String(v) => Ok(format!("{name}: {v}")),
+                    _ => Err(format!("header '{name}' must be a string")),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("'headers' must be an array or an object".to_string()),
+        };
+        Ok(Spec {
+            id: fields.get("id").cloned().unwrap_or(Value::Null),
+            url,
+            method: text("method")?.map(|m| Method::parse(&m)),
+            headers,
+            data: text("data")?,
+        })
+    }
+
+    /// `base` (the command-line options) aimed at this spec's request.
+    /// The spec's data replaces any `-d`; without a method it is POSTed.
+    pub fn apply(&self, base: &RequestConfig) -> RequestConfig {
+        let mut config = base.clone();
+        config.url = self.url.clone();
+        config.headers.extend(self.headers.iter().cloned());
+        if let Some(ref data) = self.data {
+            config.data = Some(data.clone().into_bytes());
+        }
+        config.method = match self.method {
+            Some(ref method) => method.clone(),
+            None if self.data.is_some() => Method::Post,
+            None => config.method,
+        };
+        config
+    }
+}
+
+/// The output line for input line `line` (1-based). Bodies that are not
+/// UTF-8 go out as `body_base64`.
+pub fn summary(line: usize, id: &Value, result: Result<&Response, &RequestError>, total: Duration) -> String {
+    let mut out = json!({ "line": line, "id": id });
+    match result {
+        Ok(response) => {
+            out["status"] = json!(response.status_code);
+            out["headers"] = json!(response.headers);
+            match std::str::from_utf8(&response.body) {
+                Ok(text) => out["body"] = json!(text),
+                Err(_) => out["body_base64"] = json!(base64(&response.body)),
+            }
+            out["time_total"] = json!(total.as_secs_f64());
+            if !response.warnings.is_empty() {
+                let warnings: Vec<Value> = response
+                    .warnings
+                    .iter()
+                    .map(|w| json!({ "code": w.code(), "message": w.message }))
+                    .collect();
+                out["warnings"] = json!(warnings);
+            }
+        }
+        Err(e) => out["error"] = json!({ "code": e.code(), "message": e.to_string() }),
+    }
+    out.to_string()
+}
+
+/// The output line for an input line that is not a valid spec.
+pub fn invalid(line: usize, message: &str) -> String {
+    json!({ "line": line, "id": null, "error": { "code": "invalid_spec", "message": message } }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_specs() {
+        let spec = Spec::parse(r#"{"id":"a","url":"https://x.com","headers":{"Accept":"text/plain"},"data":"q=1"}"#)
+            .unwrap();
+        assert_eq!(spec.id, json!("a"));
+        assert_eq!(spec.headers, vec!["Accept: text/plain"]);
+        let config = spec.apply(&RequestConfig::new("").header("X-Base: 1"));
+        assert_eq!(config.url, "https://x.com");
+        assert_eq!(config.method, Method::Post);
+        assert_eq!(config.headers, vec!["X-Base: 1", "Accept: text/plain"]);
+
+        let spec = Spec::parse(r#"{"url":"https://x.com","method":"delete","headers":["A: b"]}"#).unwrap();
+        assert_eq!(spec.id, Value::Null);
+        assert_eq!(spec.apply(&RequestConfig::new("")).method, Method::Delete);
+
+        for bad in ["", "[]", "{}", r#"{"url":5}"#, r#"{"url":"u","timeout":1}"#, r#"{"url":"u","headers":"A: b"}"#] {
+            assert!(Spec::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn summarizes_results() {
+        use crate::curl::warning::{Warning, WarningKind};
+        let response = Response {
+            status_code: 200,
+            headers: vec!["HTTP/1.1 200 OK".into()],
+            body: b"hi".to_vec(),
+            attempts: vec![200],
+            ..Default::default()
+        };
+        let line: Value = serde_json::from_str(&summary(1, &json!(7), Ok(&response), Duration::ZERO)).unwrap();
+        assert_eq!(line["id"], 7);
+        assert_eq!(line["status"], 200);
+        assert_eq!(line["body"], "hi");
+        assert!(line.get("warnings").is_none());
+
+        let warned = Response {
+            warnings: vec![Warning::new(WarningKind::InsecureTls, "no verification")],
+            ..response.clone()
+        };
+        let line: Value = serde_json::from_str(&summary(1, &json!(7), Ok(&warned), Duration::ZERO)).unwrap();
+        assert_eq!(line["warnings"], json!([{ "code": "insecure_tls", "message": "no verification" }]));
+
+        let binary = Response { body: vec![0xff, 0, 1], ..response };
+        let line: Value = serde_json::from_str(&summary(2, &Value::Null, Ok(&binary), Duration::ZERO)).unwrap();
+        assert_eq!(line["body_base64"], "/wAB");
+        assert!(line.get("body").is_none());
+
+        let err = RequestError::Connect { message: "refused".into() };
+        let line: Value = serde_json::from_str(&summary(3, &Value::Null, Err(&err), Duration::ZERO)).unwrap();
+        assert_eq!(line["error"]["code"], "connect");
+        assert!(line.get("status").is_none());
+    }
+}
@@ -0,0 +1,40 @@
+// src/curl/body.rs
+
+//! Request bodies supplied by library users as a reader instead of
+//! in-memory `-d` data.
+
+use std::fmt;
+use std::io::Read;
+
+/// Streamed request body. With a known length the request carries
+/// `Content-Length`; otherwise it is sent with chunked encoding.
+pub struct RequestBody {
+    pub reader: Box<dyn Read + Send>,
+    pub len: Option<u64>,
+}
+
+impl RequestBody {
+    /// Body of unknown length, sent chunked.
+    pub fn new(reader: impl Read + Send + 'static) -> Self {
+        Self {
+            reader: Box::new(reader),
+            len: None,
+        }
+    }
+
+    /// Body of exactly `len` bytes.
+    pub fn sized(reader: impl Read + Send + 'static, len: u64) -> Self {
+        Self {
+            reader: Box::new(reader),
+            len: Some(len),
+        }
+    }
+}
+
+impl fmt::Debug for RequestBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestBody")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,142 @@
+// src/curl/capabilities.rs
+
+//! `rustcurl capabilities [--json]`: what this build can do, so wrapper
+//! tools and tests can skip what it cannot instead of parsing usage text.
+//! The backend reports its protocols, auth schemes, TLS library and
+//! transfer features. rustcurl's own subsystems are listed here; those
+//! that run an external program are only available when it is on `PATH`.
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use super::env::EnvSource;
+
+/// What a backend supports, from `HttpBackend::capabilities`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub backend: String,
+    pub backend_version: String,
+    /// TLS library and version, e.g. `OpenSSL/3.0.13`.
+    pub tls: Option<String>,
+    pub protocols: Vec<String>,
+    /// `-u` and challenge schemes: basic, digest, ntlm, negotiate, bearer.
+    pub auth: Vec<&'static str>,
+    /// Transfer features such as http2, brotli or keylog.
+    pub features: Vec<&'static str>,
+}
+
+/// Subsystems built into every rustcurl, whatever the backend.
+const SUBSYSTEMS: &[&str] = &[
+    "aws-sigv4",
+    "azure",
+    "batch",
+    "capture",
+    "extract",
+    "gcs",
+    "jwe",
+    "minisign",
+    "oauth2-refresh",
+    "proxy-pac",
+    "provenance",
+    "registry-auth",
+    "relay",
+    "self-update",
+    "session",
+    "tail",
+    "tor",
+    "watch",
+];
+
+/// Subsystems that run an external program, and the program.
+const TOOLS: &[(&str, &str)] = &[("jwe-rsa", "openssl"), ("openpgp", "gpgv"), ("ssh-tunnel", "ssh")];
+
+/// Whether `program` is found in `env`'s `PATH`.
+fn on_path(env: &EnvSource, program: &str) -> bool {
+    let Some(path) = env.get("PATH") else {
+        return false;
+    };
+    let name = if cfg!(windows) { format!("{program}.exe") } else { program.to_string() };
+    std::env::split_paths(&path).any(|dir| Path::new(&dir).join(&name).is_file())
+}
+
+/// Each subsystem and whether it is usable.
+pub fn subsystems(env: &EnvSource) -> Vec<(&'static str, bool)> {
+    let mut all: Vec<(&str, bool)> = SUBSYSTEMS.iter().map(|name| (*name, true)).collect();
+    all.extend(TOOLS.iter().map(|(name, program)| (*name, on_path(env, program))));
+    all.sort();
+    all
+}
+
+pub fn to_json(caps: &Capabilities, subsystems: &[(&str, bool)]) -> Value {
+    let subsystems: serde_json::Map<String, Value> =
+        subsystems.iter().map(|(name, ok)| (name.to_string(), Value::Bool(*ok))).collect();
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "backend": {
+            "name": caps.backend,
+            "version": caps.backend_version,
+            "tls": caps.tls,
+        },
+        "protocols": caps.protocols,
+        "auth": caps.auth,
+        "features": caps.features,
+        "subsystems": subsystems,
+    })
+}
+
+pub fn render(caps: &Capabilities, subsystems: &[(&str, bool)]) -> String {
+    let tls = caps.tls.as_deref().map_or(String::new(), |tls| format!(" ({tls})"));
+    let available: Vec<&str> = subsystems.iter().filter(|(_, ok)| *ok).map(|(name, _)| *name).collect();
+    let missing: Vec<&str> = subsystems.iter().filter(|(_, ok)| !ok).map(|(name, _)| *name).collect();
+    let mut out = format!(
+        "rustcurl    {}\nbackend     {} {}{tls}\nprotocols   {}\nauth        {}\nfeatures    {}\nsubsystems  {}\n",
+        env!("CARGO_PKG_VERSION"),
+        caps.backend,
+        caps.backend_version,
+        caps.protocols.join(" "),
+        caps.auth.join(" "),
+        caps.features.join(" "),
+        available.join(" "),
+    );
+    if !missing.is_empty() {
+        out.push_str(&format!("missing     {}\n", missing.join(" ")));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps() -> Capabilities {
+        Capabilities {
+            backend: "curl".into(),
+            backend_version: "8.9.1".into(),
+            tls: Some("OpenSSL/3.0.13".into()),
+            protocols: vec!["http".into(), "https".into()],
+            auth: vec!["basic", "bearer"],
+            features: vec!["http2"],
+        }
+    }
+
+    #[test]
+    fn tools_need_path() {
+        let subsystems = subsystems(&EnvSource::from_pairs([("PATH", "/nonexistent")]));
+        assert!(subsystems.contains(&("ssh-tunnel", false)));
+        assert!(subsystems.contains(&("aws-sigv4", true)));
+        assert!(subsystems.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn json_and_text() {
+        let subsystems = [("openpgp", false), ("tail", true)];
+        let json = to_json(&caps(), &subsystems);
+        assert_eq!(json["backend"]["tls"], "OpenSSL/3.0.13");
+        assert_eq!(json["protocols"], json!(["http", "https"]));
+        assert_eq!(json["subsystems"], json!({"openpgp": false, "tail": true}));
+        let text = render(&caps(), &subsystems);
+        assert!(text.contains("backend     curl 8.9.1 (OpenSSL/3.0.13)\n"));
+        assert!(text.ends_with("subsystems  tail\nmissing     openpgp\n"));
+    }
+}
@@ -0,0 +1,240 @@
+// src/curl/capture.rs
+
+//! `--capture NAME=EXPR --capture-file vars.env`: pull values out of the
+//! response and store them as `export NAME='value'` lines, so the next
+//! command in a shell script can `source` them. `EXPR` is a JSON path
+//! into the body (`$.data.items[0].id`) or `header:Name`.
+
+use std::fmt;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use super::atomic;
+use super::error::RequestError;
+use super::response::Response;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub name: String,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Json(Vec<Step>),
+    Header(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Key(String),
+    Index(usize),
+}
+
+impl Capture {
+    /// Parse `NAME=$.path` or `NAME=header:Name`. The name must be usable
+    /// as a shell variable.
+    pub fn parse(spec: &str) -> Result<Capture, String> {
+        let (name, expr) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("'{spec}' is not NAME=EXPR"))?;
+        let starts_ok = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if !starts_ok || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            return Err(format!("'{name}' is not a valid shell variable name"));
+        }
+        let source = match expr.strip_prefix("header:") {
+            Some(header) if !header.trim().is_empty() => Source::Header(header.trim().to_string()),
+            Some(_) => return Err(format!("'{expr}' names no header")),
+            None => Source::Json(parse_path(expr)?),
+        };
+        Ok(Capture {
+            name: name.to_string(),
+            source,
+        })
+    }
+
+    /// The captured value: strings as they are, other JSON values in
+    /// their compact JSON form, `null` as an empty string.
+    pub fn extract(&self, response: &Response) -> Result<String, RequestError> {
+        let err = |message: String| RequestError::Capture {
+            name: self.name.clone(),
+            message,
+        };
+        match self.source {
+            Source::Header(ref header) => response
+                .get_header(header)
+                .ok_or_else(|| err(format!("no {header} header in the response"))),
+            Source::Json(ref path) => {
+                let json: serde_json::Value = serde_json::from_slice(&response.received_body())
+                    .map_err(|e| err(format!("body is not JSON: {e}")))?;
+                let mut value = &json;
+                for step in path {
+                    value = match step {
+                        Step::Key(key) => value.get(key.as_str()),
+                        Step::Index(i) => value.get(*i),
+                    }
+                    .ok_or_else(|| err(format!("{} not found in the body", self.source)))?;
+                }
+                Ok(match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Header(name) => write!(f, "header:{name}"),
+            Source::Json(path) => {
+                f.write_str("$")?;
+                for step in path {
+                    match step {
+                        Step::Key(key) => write!(f, ".{key}")?,
+                        Step::Index(i) => write!(f, "[{i}]")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `$`, then any mix of `.key`, `["key"]` and `[index]`.
+fn parse_path(expr: &str) -> Result<Vec<Step>, String> {
+    let invalid = || format!("'{expr}' is not a JSON path like $.items[0].id or header:Name");
+    let mut rest = expr.strip_prefix('$').ok_or_else(invalid)?;
+    let mut path = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            path.push(Step::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(invalid)?;
+            let inner = &after[..end];
+            let quoted = inner
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .or_else(|| inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')));
+            path.push(match quoted {
+                Some(key) => Step::Key(key.to_string()),
+                None => Step::Index(inner.parse().map_err(|_| invalid())?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(path)
+}
+
+/// `export NAME='value'`, quoted so any value survives `source`.
+pub fn export_line(name: &str, value: &str) -> String {
+    format!("export {name}='{}'\n", value.replace('\'', r"'\''"))
+}
+
+/// Extract every capture and write it to `path` (`-` for stdout). Lines
+/// already in the file for other names are kept, so several commands can
+/// build up one file, concurrently too.
+pub fn write(path: &str, captures: &[Capture], response: &Response) -> Result<(), RequestError> {
+    let mut lines = String::new();
+    for capture in captures {
+        lines.push_str(&export_line(&capture.name, &capture.extract(response)?));
+    }
+    let write_err = |source| RequestError::WriteOutput {
+        path: path.to_string(),
+        source,
+    };
+    if path == "-" {
+        return std::io::stdout().write_all(lines.as_bytes()).map_err(write_err);
+    }
+    let _lock = atomic::lock(Path::new(path)).map_err(write_err)?;
+    let previous = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(write_err(e)),
+    };
+    let kept: String = previous
+        .lines()
+        .filter(|line| !captures.iter().any(|c| line.starts_with(&format!("export {}=", c.name))))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    atomic::write(Path::new(path), (kept + &lines).as_bytes()).map_err(write_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> Response {
+        Response {
+            status_code: 200,
+            headers: vec!["HTTP/1.1 200 OK".into(), "X-Request-Id: abc".into()],
+            body: body.as_bytes().to_vec(),
+            attempts: vec![200],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_specs() {
+        let capture = Capture::parse("id=$.items[0][\"the id\"].v").unwrap();
+        assert_eq!(
+            capture.source,
+            Source::Json(vec![
+                Step::Key("items".into()),
+                Step::Index(0),
+                Step::Key("the id".into()),
+                Step::Key("v".into()),
+            ])
+        );
+        assert_eq!(Capture::parse("rid=header:X-Request-Id").unwrap().source, Source::Header("X-Request-Id".into()));
+        assert_eq!(Capture::parse("all=$").unwrap().source, Source::Json(vec![]));
+        for bad in ["token", "1x=$.a", "my-var=$.a", "x=.a", "x=$..a", "x=$[a]", "x=$[0", "x=header:"] {
+            assert!(Capture::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn extracts_values() {
+        let r = response(r#"{"access_token":"t0k","n":3,"ok":true,"none":null,"list":[{"id":7}]}"#);
+        let get = |spec: &str| Capture::parse(spec).unwrap().extract(&r);
+        assert_eq!(get("t=$.access_token").unwrap(), "t0k");
+        assert_eq!(get("n=$.n").unwrap(), "3");
+        assert_eq!(get("b=$.ok").unwrap(), "true");
+        assert_eq!(get("z=$.none").unwrap(), "");
+        assert_eq!(get("l=$.list").unwrap(), r#"[{"id":7}]"#);
+        assert_eq!(get("i=$.list[0].id").unwrap(), "7");
+        assert_eq!(get("h=header:x-request-id").unwrap(), "abc");
+        let missing = get("m=$.list[1].id").unwrap_err();
+        assert_eq!(missing.code(), "capture");
+        assert!(missing.to_string().contains("$.list[1].id"));
+        assert!(get("h=header:ETag").is_err());
+        assert!(Capture::parse("t=$.a").unwrap().extract(&response("<html>")).is_err());
+    }
+
+    #[test]
+    fn quotes_for_the_shell() {
+        assert_eq!(export_line("v", "it's $HOME"), "export v='it'\\''s $HOME'\n");
+    }
+
+    #[test]
+    fn merges_into_existing_file() {
+        let path = std::env::temp_dir().join(format!("rustcurl-capture-{}.env", std::process::id()));
+        let file = path.to_str().unwrap();
+        fs::write(file, "export token='old'\nexport user='me'\n").unwrap();
+        let r = response(r#"{"access_token":"new"}"#);
+        write(file, &[Capture::parse("token=$.access_token").unwrap()], &r).unwrap();
+        assert_eq!(fs::read_to_string(file).unwrap(), "export user='me'\nexport token='new'\n");
+        let _ = fs::remove_file(file);
+        let _ = fs::remove_file(format!("{file}.lock"));
+    }
+}
@@ -0,0 +1,92 @@
+// src/curl/changed.rs
+
+//! `--if-changed <state-file>`: remember the SHA-256 of the last body seen
+//! and report whether the current one differs, for page change detection.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use super::atomic;
+use super::encoding::sha256_hex;
+use super::error::RequestError;
+
+/// Process exit status for a run whose body matched the stored state.
+pub const EXIT_UNCHANGED: i32 = 100;
+
+/// Exit status for a run from each URL's status, in order: the last
+/// failure's, else `EXIT_UNCHANGED` when every URL was unchanged, else 0.
+/// One changed URL is enough for the run to count as changed.
+pub fn run_status(statuses: &[i32]) -> i32 {
+    if let Some(&failed) = statuses.iter().rev().find(|&&s| s != 0 && s != EXIT_UNCHANGED) {
+        return failed;
+    }
+    if !statuses.is_empty() && statuses.iter().all(|&s| s == EXIT_UNCHANGED) {
+        EXIT_UNCHANGED
+    } else {
+        0
+    }
+}
+
+/// Compare `body` with the hash stored in `state`, then store the new
+/// hash. Returns whether the body changed; a missing state file counts
+/// as a change. Runs sharing `state` take turns.
+pub fn update(state: &str, body: &[u8]) -> Result<bool, RequestError> {
+    let digest = sha256_hex(body);
+    let write_err = |source| RequestError::WriteOutput {
+        path: state.to_string(),
+        source,
+    };
+    let _lock = atomic::lock(Path::new(state)).map_err(write_err)?;
+    let previous = match fs::read_to_string(state) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(RequestError::Config {
+                option: "--if-changed".to_string(),
+                message: format!("cannot read state file {state}: {e}"),
+            });
+        }
+    };
+    if previous.as_deref().map(str::trim) == Some(digest.as_str()) {
+        return Ok(false);
+    }
+    atomic::write(Path::new(state), (digest + "\n").as_bytes()).map_err(write_err)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_changes_across_runs() {
+        let path = std::env::temp_dir().join(format!("rustcurl-changed-{}.state", std::process::id()));
+        let state = path.to_str().unwrap();
+        let _ = fs::remove_file(state);
+        assert!(update(state, b"v1").unwrap());
+        assert!(!update(state, b"v1").unwrap());
+        assert!(update(state, b"v2").unwrap());
+        assert_eq!(fs::read_to_string(state).unwrap(), format!("{}\n", sha256_hex(b"v2")));
+        let _ = fs::remove_file(state);
+        let _ = fs::remove_file(format!("{state}.lock"));
+    }
+
+    #[test]
+    fn run_is_unchanged_only_when_every_url_is() {
+        assert_eq!(run_status(&[]), 0);
+        assert_eq!(run_status(&[EXIT_UNCHANGED, EXIT_UNCHANGED]), EXIT_UNCHANGED);
+        assert_eq!(run_status(&[EXIT_UNCHANGED, 0]), 0);
+        assert_eq!(run_status(&[0, EXIT_UNCHANGED]), 0);
+        assert_eq!(run_status(&[7, EXIT_UNCHANGED, 22, EXIT_UNCHANGED]), 22);
+    }
+
+    #[test]
+    fn unreadable_state_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("rustcurl-changed-{}.dir", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(update(dir.to_str().unwrap(), b"x").unwrap_err().code(), "config");
+        fs::remove_dir(&dir).unwrap();
+        let _ = fs::remove_file(format!("{}.lock", dir.display()));
+    }
+}
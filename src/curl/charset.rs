@@ -0,0 +1,143 @@
+// src/curl/charset.rs
+
+//! `--output-charset`: transcode a text body from the charset it arrived
+//! in (BOM, `Content-Type` or `<meta charset>`) to the one a downstream
+//! tool expects. Labels follow the WHATWG Encoding Standard, so `latin1`
+//! means windows-1252.
+
+use std::fs;
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+use super::error::RequestError;
+use super::mime;
+use super::response::Response;
+
+/// Encoding for a `--output-charset` label; `None` if unknown or not
+/// usable as an output encoding.
+pub fn encoding_for(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+        .filter(|&e| e.output_encoding() == e || e == UTF_16LE || e == UTF_16BE)
+}
+
+/// Charset the body was sent in: a byte order mark wins, then the
+/// `Content-Type` parameter, then an HTML `charset=` near the start;
+/// otherwise UTF-8 when it decodes cleanly, else windows-1252.
+pub fn detect(content_type: Option<&str>, body: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(body) {
+        return encoding;
+    }
+    content_type
+        .and_then(|ct| charset_param(ct.as_bytes()))
+        .or_else(|| charset_param(&body[..body.len().min(1024)]))
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(if std::str::from_utf8(body).is_ok() { UTF_8 } else { WINDOWS_1252 })
+}
+
+/// Value of the first `charset=` in `text`, without quotes.
+fn charset_param(text: &[u8]) -> Option<String> {
+    let lower = String::from_utf8_lossy(text).to_ascii_lowercase();
+    let rest = &lower[lower.find("charset=")? + "charset=".len()..];
+    let value: String = rest
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Re-encode `body` from `from` to `to`. Characters `to` cannot
+/// represent are an error rather than silently replaced.
+pub fn transcode(body: &[u8], from: &'static Encoding, to: &'static Encoding) -> Result<Vec<u8>, String> {
+    let (text, _) = from.decode_with_bom_removal(body);
+    if to == UTF_16LE || to == UTF_16BE {
+        let units = text.encode_utf16();
+        return Ok(if to == UTF_16LE {
+            units.flat_map(u16::to_le_bytes).collect()
+        } else {
+            units.flat_map(u16::to_be_bytes).collect()
+        });
+    }
+    let (bytes, _, unmappable) = to.encode(&text);
+    if unmappable {
+        return Err(format!("the body has characters that {} cannot represent", to.name()));
+    }
+    Ok(bytes.into_owned())
+}
+
+/// Transcode the response to `label`, in memory or in the file it was
+/// saved to. Binary types are left alone. Returns the detected source
+/// encoding when the body was converted.
+pub fn apply(label: &str, response: &mut Response) -> Result<Option<&'static Encoding>, RequestError> {
+    let err = |message: String| RequestError::Config {
+        option: "--output-charset".to_string(),
+        message,
+    };
+    let to = encoding_for(label).ok_or_else(|| err(format!("unknown charset {label}")))?;
+    let content_type = response.get_header("content-type");
+    if !mime::is_text(content_type.as_deref()) {
+        return Ok(None);
+    }
+    let body = response.received_body().into_owned();
+    let from = detect(content_type.as_deref(), &body);
+    let converted = transcode(&body, from, to).map_err(err)?;
+    match response.saved_as {
+        Some(ref path) if response.body.is_empty() => {
+            fs::write(path, converted).map_err(|source| RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            })?;
+        }
+        _ => response.body = converted,
+    }
+    Ok(Some(from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels() {
+        assert_eq!(encoding_for("utf-8"), Some(UTF_8));
+        assert_eq!(encoding_for("latin1"), Some(WINDOWS_1252));
+        assert_eq!(encoding_for("UTF-16LE"), Some(UTF_16LE));
+        assert_eq!(encoding_for("klingon"), None);
+        assert_eq!(encoding_for("replacement"), None);
+    }
+
+    #[test]
+    fn detection_order() {
+        assert_eq!(detect(Some("text/html; charset=ISO-8859-1"), b"caf\xc3\xa9"), WINDOWS_1252);
+        assert_eq!(detect(Some("text/html; charset=latin1"), b"\xef\xbb\xbfhi"), UTF_8);
+        assert_eq!(detect(Some("text/html"), b"<meta charset=\"shift_jis\">"), encoding_rs::SHIFT_JIS);
+        assert_eq!(detect(None, "caf\u{e9}".as_bytes()), UTF_8);
+        assert_eq!(detect(None, b"caf\xe9"), WINDOWS_1252);
+    }
+
+    #[test]
+    fn transcodes_between_charsets() {
+        assert_eq!(transcode("café".as_bytes(), UTF_8, WINDOWS_1252).unwrap(), b"caf\xe9");
+        assert_eq!(transcode(b"caf\xe9", WINDOWS_1252, UTF_8).unwrap(), "café".as_bytes());
+        assert_eq!(transcode(b"hi", UTF_8, UTF_16BE).unwrap(), b"\x00h\x00i");
+        let err = transcode("€ and 日本".as_bytes(), UTF_8, encoding_rs::ISO_8859_2).unwrap_err();
+        assert!(err.contains("ISO-8859-2"));
+    }
+
+    #[test]
+    fn binary_bodies_untouched() {
+        let mut response = Response {
+            status_code: 200,
+            headers: vec!["Content-Type: image/png".into()],
+            body: b"\x89PNG\xe9".to_vec(),
+            attempts: vec![200],
+            ..Default::default()
+        };
+        assert_eq!(apply("utf-8", &mut response).unwrap(), None);
+        assert_eq!(response.body, b"\x89PNG\xe9");
+
+        response.headers = vec!["Content-Type: text/plain; charset=windows-1252".into()];
+        assert_eq!(apply("utf-8", &mut response).unwrap(), Some(WINDOWS_1252));
+        assert_eq!(response.body, "\u{2030}PNGé".as_bytes());
+    }
+}
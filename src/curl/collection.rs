@@ -0,0 +1,460 @@
+// src/curl/collection.rs
+
+//! `--collection <FILE>`: a YAML file of named request steps run as a
+//! small workflow for API test suites. A step waits for the steps in its
+//! `depends_on`, independent steps run in parallel (`parallel`, default
+//! 4), and each step may set its expected `status`, a `retry` count and
+//! a `timeout`. A step whose dependency did not pass is skipped.
+//!
+//! ```yaml
+//! parallel: 2
+//! steps:
+//!   - name: login
+//!     url: https://api.example.com/login
+//!     data: user=ops
+//!     status: 200
+//!   - name: orders
+//!     url: https://api.example.com/orders
+//!     headers: { Accept: application/json }
+//!     depends_on: login
+//!     retry: 2
+//!     timeout: 5s
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_yaml::{Mapping, Value};
+
+use super::args::parse_duration;
+use super::config::{Method, RequestConfig};
+use super::error::RequestError;
+use super::response::Response;
+
+/// Steps run at once when the file does not set `parallel`.
+pub const DEFAULT_PARALLEL: usize = 4;
+
+/// Wait between attempts of a step when `--retry-delay` is not set.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+const STEP_FIELDS: [&str; 9] = ["name", "url", "method", "headers", "data", "depends_on", "status", "retry", "timeout"];
+
+/// One named request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub name: String,
+    pub url: String,
+    pub method: Option<Method>,
+    pub headers: Vec<String>,
+    pub data: Option<String>,
+    /// Indexes of the steps this one waits for.
+    pub depends_on: Vec<usize>,
+    /// Status the response must have; otherwise any status below 400.
+    pub status: Option<u32>,
+    /// Extra attempts after a step does not pass.
+    pub retry: u32,
+    pub timeout: Option<Duration>,
+}
+
+/// A parsed collection: steps in file order, dependencies checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collection {
+    pub steps: Vec<Step>,
+    pub parallel: usize,
+}
+
+impl Collection {
+    pub fn parse(text: &str) -> Result<Collection, String> {
+        let yaml: Value = serde_yaml::from_str(text).map_err(|e| format!("not YAML: {e}"))?;
+        let Value::Mapping(ref top) = yaml else {
+            return Err("expected a mapping with 'steps'".to_string());
+        };
+        check_fields(top, &["steps", "parallel"], "collection")?;
+        let parallel = match top.get("parallel") {
+            None | Some(Value::Null) => DEFAULT_PARALLEL,
+            Some(v) => v
+                .as_u64()
+                .filter(|n| *n > 0)
+                .ok_or("'parallel' must be a positive integer")? as usize,
+        };
+        let Some(Value::Sequence(entries)) = top.get("steps") else {
+            return Err("'steps' must be a list".to_string());
+        };
+        let mut names = HashMap::new();
+        let mut raw = Vec::with_capacity(entries.len());
+        for (n, entry) in entries.iter().enumerate() {
+            let (step, depends_on) = parse_step(entry).map_err(|e| format!("step {}: {e}", n + 1))?;
+            if names.insert(step.name.clone(), n).is_some() {
+                return Err(format!("step {}: duplicate name '{}'", n + 1, step.name));
+            }
+            raw.push((step, depends_on));
+        }
+        let mut steps = Vec::with_capacity(raw.len());
+        for (mut step, depends_on) in raw {
+            for dep in depends_on {
+                let index = *names
+                    .get(&dep)
+                    .ok_or_else(|| format!("step '{}' depends on unknown step '{dep}'", step.name))?;
+                if !step.depends_on.contains(&index) {
+                    step.depends_on.push(index);
+                }
+            }
+            steps.push(step);
+        }
+        let collection = Collection { steps, parallel };
+        if let Some(step) = collection.cycle() {
+            return Err(format!("step '{}' is part of a depends_on cycle", step.name));
+        }
+        Ok(collection)
+    }
+
+    pub fn dependencies(&self) -> Vec<Vec<usize>> {
+        self.steps.iter().map(|s| s.depends_on.clone()).collect()
+    }
+
+    /// A step that can never start because its dependencies loop.
+    fn cycle(&self) -> Option<&Step> {
+        let mut waiting: Vec<usize> = self.steps.iter().map(|s| s.depends_on.len()).collect();
+        let mut ready: Vec<usize> = (0..waiting.len()).filter(|&i| waiting[i] == 0).collect();
+        while let Some(done) = ready.pop() {
+            for (i, step) in self.steps.iter().enumerate() {
+                if step.depends_on.contains(&done) {
+                    waiting[i] -= 1;
+                    if waiting[i] == 0 {
+                        ready.push(i);
+                    }
+                }
+            }
+        }
+        waiting.iter().position(|&n| n > 0).map(|i| &self.steps[i])
+    }
+}
+
+fn check_fields(map: &Mapping, known: &[&str], what: &str) -> Result<(), String> {
+    for key in map.keys() {
+        match key.as_str() {
+            Some(k) if known.contains(&k) => {}
+            Some(k) => return Err(format!("unknown {what} field '{k}'")),
+            None => return Err(format!("{what} field names must be strings")),
+        }
+    }
+    Ok(())
+}
+
+/// A string, number or boolean as text.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_step(entry: &Value) -> Result<(Step, Vec<String>), String> {
+    let Value::Mapping(fields) = entry else {
+        return Err("expected a mapping".to_string());
+    };
+    check_fields(fields, &STEP_FIELDS, "step")?;
+    let text = |name: &str| match fields.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => scalar(v).map(Some).ok_or(format!("'{name}' must be a string")),
+    };
+    let name = text("name")?.filter(|n| !n.is_empty()).ok_or("'name' is required")?;
+    let url = text("url")?.filter(|u| !u.is_empty()).ok_or("'url' is required")?;
+    let headers = match fields.get("headers") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Sequence(lines)) => lines
+            .iter()
+            .map(|h| h.as_str().map(str::to_string).ok_or("'headers' entries must be strings"))
+            .collect::<Result<_, _>>()?,
+        Some(Value::Mapping(map)) => map
+            .iter()
+            .map(|(name, value)| match (name.as_str(), scalar(value)) {
+                (Some(name), Some(value)) => Ok(format!("{name}: {value}")),
+                _ => Err("'headers' must map names to values".to_string()),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("'headers' must be a list or a mapping".to_string()),
+    };
+    let depends_on = match fields.get("depends_on") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(dep)) => vec![dep.clone()],
+        Some(Value::Sequence(deps)) => deps
+            .iter()
+            .map(|d| d.as_str().map(str::to_string).ok_or("'depends_on' entries must be step names"))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("'depends_on' must be a step name or a list of them".to_string()),
+    };
+    let number = |name: &str| match fields.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or(format!("'{name}' must be a non-negative integer")),
+    };
+    let timeout = match fields.get("timeout") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            scalar(v)
+                .ok_or("'timeout' must be a duration".to_string())
+                .and_then(|t| parse_duration(&t, "'timeout'"))?,
+        ),
+    };
+    let step = Step {
+        name,
+        url,
+        method: text("method")?.map(|m| Method::parse(&m)),
+        headers,
+        data: text("data")?,
+        depends_on: Vec::new(),
+        status: number("status")?,
+        retry: number("retry")?.unwrap_or(0),
+        timeout,
+    };
+    Ok((step, depends_on))
+}
+
+impl Step {
+    /// `base` (the command-line options) aimed at this step's request,
+    /// like a `--batch` spec; `timeout` replaces `--max-time`.
+    pub fn apply(&self, base: &RequestConfig) -> RequestConfig {
+        let mut config = base.clone();
+        config.url = self.url.clone();
+        config.headers.extend(self.headers.iter().cloned());
+        if let Some(ref data) = self.data {
+            config.data = Some(data.clone().into_bytes());
+        }
+        config.method = match self.method {
+            Some(ref method) => method.clone(),
+            None if self.data.is_some() => Method::Post,
+            None => config.method,
+        };
+        if let Some(timeout) = self.timeout {
+            config.max_time = Some(timeout);
+        }
+        config
+    }
+
+    pub fn passed(&self, result: Result<&Response, &RequestError>) -> bool {
+        match (result, self.status) {
+            (Ok(response), Some(status)) => response.status_code == status,
+            (Ok(response), None) => response.status_code < 400,
+            (Err(_), _) => false,
+        }
+    }
+}
+
+/// The output line for a step that ran.
+pub fn summary(step: &Step, result: Result<&Response, &RequestError>, attempts: u32, total: Duration) -> String {
+    let mut out = json!({ "step": step.name, "passed": step.passed(result), "attempts": attempts });
+    match result {
+        Ok(response) => out["status"] = json!(response.status_code),
+        Err(e) => out["error"] = json!({ "code": e.code(), "message": e.to_string() }),
+    }
+    if let Some(status) = step.status {
+        out["expected"] = json!(status);
+    }
+    out["time_total"] = json!(total.as_secs_f64());
+    out.to_string()
+}
+
+/// The output line for a step skipped because `dependency` did not pass.
+pub fn skipped(step: &Step, dependency: &Step) -> String {
+    let reason = format!("dependency '{}' did not pass", dependency.name);
+    json!({ "step": step.name, "passed": false, "skipped": reason }).to_string()
+}
+
+/// Run steps `0..deps.len()` in dependency order, at most `parallel` at a
+/// time. `run` executes one step and says whether it passed; `finished`
+/// sees every step as it ends, with `Err(dependency)` for one skipped
+/// because that dependency did not pass. Returns whether all passed.
+pub fn schedule<T: Send>(
+    deps: &[Vec<usize>],
+    parallel: usize,
+    run: impl Fn(usize) -> (bool, T) + Sync,
+    mut finished: impl FnMut(usize, Result<T, usize>) -> io::Result<()>,
+) -> io::Result<bool> {
+    let mut waiting: Vec<usize> = deps.iter().map(Vec::len).collect();
+    let mut dependents = vec![Vec::new(); deps.len()];
+    for (step, on) in deps.iter().enumerate() {
+        for &dep in on {
+            dependents[dep].push(step);
+        }
+    }
+    let mut ready: VecDeque<usize> = (0..deps.len()).filter(|&i| waiting[i] == 0).collect();
+    let mut skipped = vec![false; deps.len()];
+    let mut left = deps.len();
+    let mut all_passed = true;
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        let mut running = 0;
+        while left > 0 {
+            while running < parallel.max(1)
+                && let Some(step) = ready.pop_front()
+            {
+                let (tx, run) = (tx.clone(), &run);
+                scope.spawn(move || {
+                    let _ = tx.send((step, run(step)));
+                });
+                running += 1;
+            }
+            let Ok((step, (passed, value))) = rx.recv() else {
+                return Err(io::Error::other("a collection step stopped without a result"));
+            };
+            running -= 1;
+            left -= 1;
+            finished(step, Ok(value))?;
+            if passed {
+                for &next in &dependents[step] {
+                    waiting[next] -= 1;
+                    if waiting[next] == 0 && !skipped[next] {
+                        ready.push_back(next);
+                    }
+                }
+                continue;
+            }
+            all_passed = false;
+            let mut failed = vec![step];
+            while let Some(dep) = failed.pop() {
+                for &next in &dependents[dep] {
+                    if !skipped[next] {
+                        skipped[next] = true;
+                        left -= 1;
+                        finished(next, Err(dep))?;
+                        failed.push(next);
+                    }
+                }
+            }
+        }
+        Ok(all_passed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value as Json;
+    use std::sync::Mutex;
+
+    const SUITE: &str = "
+parallel: 2
+steps:
+  - name: login
+    url: https://x.com/login
+    data: user=ops
+    status: 201
+  - name: orders
+    url: https://x.com/orders
+    headers: { Accept: application/json, X-Page: 2 }
+    depends_on: login
+    retry: 2
+    timeout: 500ms
+  - name: health
+    url: https://x.com/health
+    method: head
+    headers: [\"X-A: 1\"]
+  - name: report
+    url: https://x.com/report
+    depends_on: [orders, health]
+";
+
+    #[test]
+    fn parses_collections() {
+        let collection = Collection::parse(SUITE).unwrap();
+        assert_eq!(collection.parallel, 2);
+        assert_eq!(collection.dependencies(), vec![vec![], vec![0], vec![], vec![1, 2]]);
+        let orders = &collection.steps[1];
+        assert_eq!(orders.headers, vec!["Accept: application/json", "X-Page: 2"]);
+        assert_eq!((orders.retry, orders.timeout), (2, Some(Duration::from_millis(500))));
+
+        let config = collection.steps[0].apply(&RequestConfig::new("").header("X-Base: 1"));
+        assert_eq!((config.url.as_str(), config.method), ("https://x.com/login", Method::Post));
+        assert_eq!(orders.apply(&RequestConfig::new("")).max_time, Some(Duration::from_millis(500)));
+        assert_eq!(collection.steps[2].apply(&RequestConfig::new("")).method, Method::Head);
+        assert_eq!(Collection::parse("steps: []").unwrap().parallel, DEFAULT_PARALLEL);
+    }
+
+    #[test]
+    fn rejects_bad_collections() {
+        for bad in [
+            "",
+            "- name: a",
+            "steps: {}",
+            "steps: [{url: u}]",
+            "steps: [{name: a}]",
+            "steps: [{name: a, url: u, expect: 200}]",
+            "steps: [{name: a, url: u}, {name: a, url: v}]",
+            "steps: [{name: a, url: u, depends_on: b}]",
+            "steps: [{name: a, url: u, depends_on: b}, {name: b, url: v, depends_on: [a]}]",
+            "steps: [{name: a, url: u, depends_on: a}]",
+            "steps: [{name: a, url: u, retry: -1}]",
+            "steps: [{name: a, url: u, timeout: soon}]",
+            "parallel: 0\nsteps: []",
+        ] {
+            assert!(Collection::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn checks_expected_status() {
+        let step = Collection::parse("steps: [{name: a, url: u}]").unwrap().steps.remove(0);
+        let response = |status_code| Response { status_code, ..Default::default() };
+        assert!(step.passed(Ok(&response(302))));
+        assert!(!step.passed(Ok(&response(404))));
+        assert!(!step.passed(Err(&RequestError::Connect { message: "refused".into() })));
+        let strict = Step { status: Some(204), ..step.clone() };
+        assert!(!strict.passed(Ok(&response(200))));
+        assert!(strict.passed(Ok(&response(204))));
+
+        let line: Json = serde_json::from_str(&summary(&strict, Ok(&response(200)), 3, Duration::ZERO)).unwrap();
+        assert_eq!(line["passed"], false);
+        assert_eq!((line["status"].clone(), line["expected"].clone()), (json!(200), json!(204)));
+        assert_eq!(line["attempts"], 3);
+        let line: Json = serde_json::from_str(&skipped(&strict, &step)).unwrap();
+        assert_eq!(line["skipped"], "dependency 'a' did not pass");
+    }
+
+    #[test]
+    fn runs_dependencies_first_and_skips_after_failures() {
+        // 0 -> 1 -> 3, 2 fails and takes 4 and 5 (which needs 4) with it
+        let deps = vec![vec![], vec![0], vec![], vec![1], vec![2], vec![4, 0]];
+        let mut order = Vec::new();
+        let passed = schedule(&deps, 2, |step| (step != 2, step), |step, result| {
+            order.push((step, result.err()));
+            Ok(())
+        })
+        .unwrap();
+        assert!(!passed);
+        let position = |step| order.iter().position(|&(s, _)| s == step).unwrap();
+        assert!(position(0) < position(1) && position(1) < position(3));
+        assert!(order.contains(&(4, Some(2))) && order.contains(&(5, Some(4))));
+        assert_eq!(order.len(), deps.len());
+    }
+
+    #[test]
+    fn runs_independent_steps_in_parallel() {
+        let deps = vec![Vec::new(); 6];
+        let (running, peak) = (Mutex::new(0), Mutex::new(0));
+        let run = |_| {
+            let now = {
+                let mut running = running.lock().unwrap();
+                *running += 1;
+                *running
+            };
+            let mut highest = peak.lock().unwrap();
+            *highest = (*highest).max(now);
+            drop(highest);
+            thread::sleep(Duration::from_millis(50));
+            *running.lock().unwrap() -= 1;
+            (true, ())
+        };
+        assert!(schedule(&deps, 3, run, |_, _| Ok(())).unwrap());
+        assert_eq!(*peak.lock().unwrap(), 3);
+    }
+}
@@ -1,7 +1,25 @@
 // src/curl/config.rs
 
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use super::auth::Mechanism;
+use super::capture::Capture;
+use super::content_digest::DigestAlgorithm;
+use super::cookie::CookieSource;
+use super::dns::DEFAULT_DNS_CACHE_TIMEOUT;
+use super::env::EnvSource;
+use super::eol::{self, Eol};
+use super::identity::CertType;
+use super::messages::MessageFormat;
+use super::presets::Preset;
+use super::url;
+use super::verbose::VerboseSink;
+
+/// User-Agent sent by every backend unless `-A` overrides it.
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36 Edg/131.0.0.0";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Method {
     Get,
@@ -15,6 +33,20 @@ pub enum Method {
 }
 
 impl Method {
+    /// Method named `s`, case-insensitively; unknown names are sent as given.
+    pub fn parse(s: &str) -> Method {
+        match s.to_uppercase().as_str() {
+            "GET" => Method::Get,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            "HEAD" => Method::Head,
+            "PATCH" => Method::Patch,
+            "OPTIONS" => Method::Options,
+            other => Method::Custom(other.to_string()),
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             Method::Get => "GET",
@@ -35,79 +67,450 @@ impl std::fmt::Display for Method {
     }
 }
 
+/// How to ask for HTTP/2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Http2 {
+    /// `--http2`: offer h2 in ALPN, or ask an `http://` server to upgrade.
+    Negotiate,
+    /// `--http2-prior-knowledge`: speak h2 from the start, even in clear
+    /// text (h2c), for servers known to support it.
+    PriorKnowledge,
+}
+
+impl Http2 {
+    pub fn flag(self) -> &'static str {
+        match self {
+            Http2::Negotiate => "--http2",
+            Http2::PriorKnowledge => "--http2-prior-knowledge",
+        }
+    }
+}
+
+/// TLS protocol versions, in order, for `--tlsv1.x` and `--tls-max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+}
+
+impl TlsVersion {
+    /// `1.0` through `1.3`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "1.0" => Some(TlsVersion::V1_0),
+            "1.1" => Some(TlsVersion::V1_1),
+            "1.2" => Some(TlsVersion::V1_2),
+            "1.3" => Some(TlsVersion::V1_3),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TlsVersion::V1_0 => "1.0",
+            TlsVersion::V1_1 => "1.1",
+            TlsVersion::V1_2 => "1.2",
+            TlsVersion::V1_3 => "1.3",
+        }
+    }
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TLS {}", self.as_str())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RequestConfig {
     pub url: String,
+    /// Further URLs from the command line, each with its own `-o` (`None`
+    /// for stdout), fetched in order after `url` with the same options.
+    pub more_urls: Vec<(String, Option<String>)>,
     pub method: Method,
     pub negotiate: bool,
     pub insecure: bool,
     pub cacert: Option<String>,
+    /// Client certificate for mutual TLS.
+    pub cert: Option<String>,
+    /// Format of `cert`; guessed from its name when `None`.
+    pub cert_type: Option<CertType>,
+    /// Private key for `cert`, when it is not in the same file.
+    pub key: Option<String>,
+    /// Format of `key`, PEM or DER; guessed from its name when `None`.
+    pub key_type: Option<CertType>,
+    /// Passphrase for an encrypted `key` or PKCS#12 `cert`.
+    pub key_password: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
     pub proxy: Option<String>,
+    /// SOCKS proxy the connection to `proxy` goes through (`--preproxy`).
+    pub preproxy: Option<String>,
+    /// PAC script (URL or file) choosing the proxy when `proxy` is unset.
+    pub proxy_pac: Option<String>,
+    /// SSH jump host, `[user@]host[:port]`, to send the request through.
+    pub ssh_tunnel: Option<String>,
+    /// Route through a local Tor SOCKS port; see `tor::apply`.
+    pub tor: bool,
     pub verbose: bool,
+    /// Where `verbose` notes go; stderr unless an embedder says otherwise.
+    pub verbose_sink: VerboseSink,
+    /// On a connection failure, check each step of the way and report it.
+    pub diagnose: bool,
     pub headers: Vec<String>,
-    pub data: Option<String>,
+    pub data: Option<Vec<u8>>,
+    pub form_fields: Vec<(String, String)>,
+    /// Send `data` and form fields as the URL query string (`-G`).
+    pub query_data: bool,
+    /// Convert LF to CRLF in the request body (`--crlf`).
+    pub crlf: bool,
+    /// File to send as the body, streamed rather than read into memory (`-T`).
+    pub upload_file: Option<String>,
+    /// Send a `Content-Digest` of the body with these algorithms.
+    pub content_digest: Vec<DigestAlgorithm>,
+    /// Send a `Content-MD5` of the body.
+    pub content_md5: bool,
+    /// Send the body as a JWE for the key in this file; see `jwe`.
+    pub encrypt_jwe: Option<String>,
+    /// Decrypt a response body that is a JWE with the key in this file.
+    pub decrypt_jwe: Option<String>,
     pub connect_timeout: Option<Duration>,
     pub max_time: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub dns_timeout: Option<Duration>,
+    /// Reuse host lookups across requests in this process.
+    pub dns_cache: bool,
+    pub dns_cache_timeout: Duration,
+    /// File the body is written to as it arrives; `-` for stdout.
     pub output: Option<String>,
+    /// Save the body under the URL's file name (`-O`) when `output` is unset.
+    pub remote_name: bool,
+    /// With `remote_name`, prefer the Content-Disposition file name (`-J`).
+    pub remote_header_name: bool,
+    /// Directory that relative `-o` paths and `--extract` are rooted in.
+    pub output_dir: Option<String>,
+    /// Directory HTTP/2 pushed responses are saved in, or `-` to only
+    /// list them; pushes are refused when unset.
+    pub accept_push: Option<String>,
+    /// Unpack the saved file if it is a tar, tar.gz or zip archive.
+    pub extract: bool,
+    /// Let `extract` replace files that already exist.
+    pub extract_overwrite: bool,
+    /// Detached signature (file or URL) the download must verify against.
+    pub verify_sig: Option<String>,
+    /// Minisign public key or OpenPGP keyring for `verify_sig`.
+    pub verify_key: Option<String>,
+    /// Write `<output>.provenance.json` describing the download.
+    pub provenance: bool,
+    /// Byte ranges to request, e.g. `0-99` or `0-99,200-299`.
+    pub range: Option<String>,
+    /// Infer and check download file name extensions from the content.
+    pub mime_sniff: bool,
+    /// Charset to transcode text bodies to before output.
+    pub output_charset: Option<String>,
+    /// Line endings to rewrite text bodies to before output.
+    pub normalize_eol: Option<Eol>,
     pub head_only: bool,
+    /// Print the status line and headers ahead of the body (`-i`).
+    pub include: bool,
+    /// `-w` template printed after the transfer; see `writeout`.
+    pub write_out: Option<String>,
     pub ntlm: bool,
     pub proxy_user: Option<String>,
     pub proxy_password: Option<String>,
+    /// Headers for the proxy only (`--proxy-header`): sent on the CONNECT
+    /// request, never through the tunnel to the origin.
+    pub proxy_headers: Vec<String>,
     pub noproxy: Option<String>,
-    pub cookie: Option<String>,
+    pub cookies: Vec<CookieSource>,
     pub cookie_jar: Option<String>,
     pub bearer: Option<String>,
+    /// Token endpoint to refresh a rejected `bearer` at, with the
+    /// refresh token and client credentials below.
+    pub oauth2_token_url: Option<String>,
+    pub oauth2_client_id: Option<String>,
+    pub oauth2_client_secret: Option<String>,
+    pub oauth2_refresh_token: Option<String>,
+    /// Send Basic credentials only after a 401 challenge, not up front.
+    pub auth_on_challenge: bool,
+    /// Mechanisms to escalate through on 401, in order.
+    pub auth_chain: Vec<Mechanism>,
+    /// Let the server's challenge pick the strongest of Negotiate, NTLM,
+    /// Digest and Basic (`--anyauth`).
+    pub anyauth: bool,
+    /// On a 401 with a `Bearer realm=...` challenge, fetch a token from the
+    /// realm the way container registries expect, and retry with it
+    /// (`--registry-auth`).
+    pub registry_auth: bool,
+    /// Take credentials `-u` leaves out from the netrc file (`-n`).
+    pub netrc: bool,
+    /// That file, instead of `~/.netrc`.
+    pub netrc_file: Option<String>,
+    /// Region, endpoint and addressing for `s3://` URLs; see `s3::target`.
+    pub s3_region: Option<String>,
+    pub s3_endpoint: Option<String>,
+    pub s3_path_style: bool,
+    /// Part size for multipart uploads to `s3://` URLs.
+    pub s3_part_size: Option<u64>,
+    /// Print a signed URL valid this long for a `gs://` or `az://` URL
+    /// instead of sending the request.
+    pub presign: Option<Duration>,
     pub compressed: bool,
+    /// Read until the connection closes instead of trusting Content-Length.
+    pub ignore_content_length: bool,
+    /// Return a truncated body as success instead of `PartialBody`.
+    pub accept_partial: bool,
     pub show_timing: bool,
+    /// Fail with `SlaExceeded` when the whole transfer takes longer.
+    pub assert_time_total_max: Option<Duration>,
+    /// Fail with `SlaExceeded` when the first byte arrives later.
+    pub assert_ttfb_max: Option<Duration>,
+    /// Prometheus textfile to replace with this probe's metrics.
+    pub prom_out: Option<String>,
+    /// Named session whose request and byte totals this request adds to
+    /// (`--session`).
+    pub session: Option<String>,
+    /// Print the effective settings instead of sending (`--print-config`,
+    /// `rustcurl config show`).
+    pub print_config: bool,
+    /// Answer yes to confirmation questions (`--yes`); see `confirm`.
+    pub assume_yes: bool,
+    /// Repeat the request on this interval (`--watch`).
+    pub watch: Option<Duration>,
+    /// Follow the remote file as it grows, polling on this interval
+    /// (`--tail`).
+    pub tail: Option<Duration>,
+    /// JSONL request specs to run instead of `url` (`--batch`, `-` for stdin).
+    pub batch: Option<String>,
+    /// YAML workflow of request steps to run instead of `url`
+    /// (`--collection`).
+    pub collection: Option<String>,
+    /// Address to serve as a forward proxy on instead of sending `url`
+    /// (`rustcurl relay --listen`).
+    pub relay: Option<String>,
+    /// Let the relay serve on an address other than loopback
+    /// (`--listen-remote`).
+    pub relay_remote: bool,
+    /// In watch mode, flag attempts whose body differs from the last one.
+    pub watch_diff: bool,
+    /// State file holding the last body's hash (`--if-changed`).
+    pub if_changed: Option<String>,
+    /// Values to extract from the response into `capture_file`.
+    pub captures: Vec<Capture>,
+    pub capture_file: Option<String>,
+    /// In watch mode, shell command run when the status class changes.
+    pub notify_cmd: Option<String>,
+    /// In watch mode, show a desktop notification when the status class changes.
+    pub notify_desktop: bool,
+    /// Consecutive attempts a new status class must hold before notifying.
+    pub notify_debounce: u32,
     pub user_agent: Option<String>,
+    /// Browser to pass for: its User-Agent (unless `user_agent` is set),
+    /// its navigation headers and, where the backend allows, its ALPN.
+    pub impersonate: Option<Preset>,
+    /// HTTP/2 stream weight (1-256) to ask the server to schedule the
+    /// request by; not sent to servers whose SETTINGS turn RFC 7540
+    /// priorities off. Also logs the connection's HTTP/2 frames under `-v`.
+    pub h2_priority: Option<u32>,
+    /// Ask for HTTP/2 beyond the backend's default (`--http2`,
+    /// `--http2-prior-knowledge`).
+    pub http2: Option<Http2>,
     pub silent: bool,
+    /// Follow redirects (on by default); the relay passes them back instead.
+    pub follow_location: bool,
     pub max_redirs: Option<u32>,
     pub resolve: Vec<String>,
     pub proxy_negotiate: bool,
     pub proxy_ntlm: bool,
+    /// Answer the proxy's 407 with Digest (`--proxy-digest`).
+    pub proxy_digest: bool,
+    /// Send proxy credentials with Basic only (`--proxy-basic`).
+    pub proxy_basic: bool,
+    /// Use the strongest method the proxy's 407 offers (`--proxy-anyauth`).
+    pub proxy_anyauth: bool,
     pub proxy_insecure: bool,
+    /// Tunnel through the proxy with CONNECT even for `http://` URLs
+    /// (`-p`), rather than have it forward the request.
+    pub proxy_tunnel: bool,
     pub proxy_cacert: Option<String>,
     pub ssl_no_revoke: bool,
+    /// File TLS sessions are loaded from and saved to between runs.
+    pub tls_session_cache: Option<String>,
+    /// Send replay-safe requests as TLS 1.3 early (0-RTT) data when
+    /// resuming a session that allows it.
+    pub tls_early_data: bool,
+    /// Oldest TLS version to accept (`--tlsv1.2` and friends).
+    pub tls_min: Option<TlsVersion>,
+    /// Newest TLS version to offer (`--tls-max`).
+    pub tls_max: Option<TlsVersion>,
+    /// Cipher list for TLS 1.2 and older, in the TLS library's syntax
+    /// (`--ciphers`).
+    pub ciphers: Option<String>,
+    /// TLS 1.3 cipher suites, colon-separated (`--tls13-ciphers`).
+    pub tls13_ciphers: Option<String>,
+    /// File TLS secrets are appended to in NSS key log format (`--keylog`).
+    pub keylog: Option<String>,
+    /// Collect the server's certificate chain in
+    /// `Response::peer_certificates` for the CLI to print (`--show-certs`).
+    pub show_certs: bool,
+    /// Trust a server's key on first use and require it after (`--tofu`).
+    pub tofu: bool,
+    /// Public key the server must present, as libcurl takes it
+    /// (`sha256//BASE64`); set from the `--tofu` store.
+    pub pinned_pubkey: Option<String>,
+    /// Encrypted Client Hello mode as libcurl takes it: `false`, `grease`,
+    /// `true`, `hard`, `ecl:<base64 ECHConfigList>` or `pn:<public name>`.
+    pub ech: Option<String>,
+    pub retry: u32,
+    pub retry_delay: Option<Duration>,
+    pub retry_report: bool,
+    pub message_format: MessageFormat,
+    /// Seed for all randomized behavior; random when `None`.
+    pub seed: Option<u64>,
+    /// Environment for credential/proxy fallbacks; the process
+    /// environment when `None`.
+    pub env: Option<EnvSource>,
 }
 
 impl RequestConfig {
     pub fn new(url: &str) -> Self {
         Self {
             url: url.to_string(),
+            more_urls: Vec::new(),
             method: Method::Get,
             negotiate: false,
             insecure: false,
             cacert: None,
+            cert: None,
+            cert_type: None,
+            key: None,
+            key_type: None,
+            key_password: None,
             username: None,
             password: None,
             proxy: None,
+            preproxy: None,
+            proxy_pac: None,
+            ssh_tunnel: None,
+            tor: false,
             verbose: false,
+            verbose_sink: VerboseSink::Stderr,
+            diagnose: false,
             headers: Vec::new(),
             data: None,
+            form_fields: Vec::new(),
+            query_data: false,
+            crlf: false,
+            upload_file: None,
+            content_digest: Vec::new(),
+            content_md5: false,
+            encrypt_jwe: None,
+            decrypt_jwe: None,
             connect_timeout: None,
             max_time: None,
+            read_timeout: None,
+            dns_timeout: None,
+            dns_cache: true,
+            dns_cache_timeout: DEFAULT_DNS_CACHE_TIMEOUT,
             output: None,
+            remote_name: false,
+            remote_header_name: false,
+            output_dir: None,
+            accept_push: None,
+            extract: false,
+            extract_overwrite: false,
+            verify_sig: None,
+            verify_key: None,
+            provenance: false,
+            range: None,
+            mime_sniff: true,
+            output_charset: None,
+            normalize_eol: None,
             head_only: false,
+            include: false,
+            write_out: None,
             ntlm: false,
             proxy_user: None,
             proxy_password: None,
+            proxy_headers: Vec::new(),
             noproxy: None,
-            cookie: None,
+            cookies: Vec::new(),
             cookie_jar: None,
             bearer: None,
+            oauth2_token_url: None,
+            oauth2_client_id: None,
+            oauth2_client_secret: None,
+            oauth2_refresh_token: None,
+            auth_on_challenge: false,
+            auth_chain: Vec::new(),
+            anyauth: false,
+            registry_auth: false,
+            netrc: false,
+            netrc_file: None,
+            s3_region: None,
+            s3_endpoint: None,
+            s3_path_style: false,
+            s3_part_size: None,
+            presign: None,
             compressed: false,
+            ignore_content_length: false,
+            accept_partial: false,
             show_timing: false,
+            assert_time_total_max: None,
+            assert_ttfb_max: None,
+            prom_out: None,
+            session: None,
+            print_config: false,
+            assume_yes: false,
+            watch: None,
+            tail: None,
+            batch: None,
+            collection: None,
+            relay: None,
+            relay_remote: false,
+            watch_diff: false,
+            if_changed: None,
+            captures: Vec::new(),
+            capture_file: None,
+            notify_cmd: None,
+            notify_desktop: false,
+            notify_debounce: 1,
             user_agent: None,
+            impersonate: None,
+            h2_priority: None,
+            http2: None,
             silent: false,
+            follow_location: true,
             max_redirs: None,
             resolve: Vec::new(),
             proxy_negotiate: false,
             proxy_ntlm: false,
+            proxy_digest: false,
+            proxy_basic: false,
+            proxy_anyauth: false,
             proxy_insecure: false,
+            proxy_tunnel: false,
             proxy_cacert: None,
             ssl_no_revoke: false,
+            tls_session_cache: None,
+            tls_early_data: false,
+            tls_min: None,
+            tls_max: None,
+            ciphers: None,
+            tls13_ciphers: None,
+            keylog: None,
+            show_certs: false,
+            tofu: false,
+            pinned_pubkey: None,
+            ech: None,
+            retry: 0,
+            retry_delay: None,
+            retry_report: false,
+            message_format: MessageFormat::Long,
+            seed: None,
+            env: None,
         }
     }
 
@@ -131,6 +534,41 @@ impl RequestConfig {
         self
     }
 
+    pub fn cert(mut self, path: &str) -> Self {
+        self.cert = Some(path.to_string());
+        self
+    }
+
+    pub fn cert_type(mut self, cert_type: CertType) -> Self {
+        self.cert_type = Some(cert_type);
+        self
+    }
+
+    pub fn key(mut self, path: &str) -> Self {
+        self.key = Some(path.to_string());
+        self
+    }
+
+    pub fn key_type(mut self, key_type: CertType) -> Self {
+        self.key_type = Some(key_type);
+        self
+    }
+
+    pub fn key_password(mut self, password: &str) -> Self {
+        self.key_password = Some(password.to_string());
+        self
+    }
+
+    /// Format of `cert`: `--cert-type`, else guessed from the file name.
+    pub fn cert_format(&self) -> CertType {
+        self.cert_type.unwrap_or_else(|| CertType::guess(self.cert.as_deref().unwrap_or("")))
+    }
+
+    /// Format of `key`: `--key-type`, else guessed from the file name.
+    pub fn key_format(&self) -> CertType {
+        self.key_type.unwrap_or_else(|| CertType::guess(self.key.as_deref().unwrap_or("")))
+    }
+
     pub fn username(mut self, user: &str) -> Self {
         self.username = Some(user.to_string());
         self
@@ -146,19 +584,117 @@ impl RequestConfig {
         self
     }
 
+    /// Reach `proxy` through the SOCKS proxy at `proxy_url`, for networks
+    /// where the HTTP proxy is only reachable from a jump host.
+    pub fn preproxy(mut self, proxy_url: &str) -> Self {
+        self.preproxy = Some(proxy_url.to_string());
+        self
+    }
+
+    /// Pick the proxy with the PAC script at `location` (an http(s) URL
+    /// or a file); see `pac::route`.
+    pub fn proxy_pac(mut self, location: &str) -> Self {
+        self.proxy_pac = Some(location.to_string());
+        self
+    }
+
+    /// Route the request through an SSH dynamic forward to `dest`; see
+    /// `ssh::Tunnel`.
+    pub fn ssh_tunnel(mut self, dest: &str) -> Self {
+        self.ssh_tunnel = Some(dest.to_string());
+        self
+    }
+
+    pub fn tor(mut self, enable: bool) -> Self {
+        self.tor = enable;
+        self
+    }
+
     pub fn verbose(mut self, enable: bool) -> Self {
         self.verbose = enable;
         self
     }
 
+    pub fn verbose_sink(mut self, sink: VerboseSink) -> Self {
+        self.verbose_sink = sink;
+        self
+    }
+
+    /// Pass `message` to `verbose_sink` when `verbose` is on.
+    pub fn note(&self, message: impl std::fmt::Display) {
+        if self.verbose {
+            self.verbose_sink.write(&message.to_string());
+        }
+    }
+
+    pub fn diagnose(mut self, enable: bool) -> Self {
+        self.diagnose = enable;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn header(mut self, h: &str) -> Self {
         self.headers.push(h.to_string());
         self
     }
 
-    pub fn data(mut self, d: &str) -> Self {
-        self.data = Some(d.to_string());
+    /// Append a body part; repeated calls are joined with `&` like `-d`.
+    pub fn data(self, d: &str) -> Self {
+        self.data_bytes(d.as_bytes())
+    }
+
+    /// Append a binary body part, joined with `&` like `data()`.
+    pub fn data_bytes(mut self, d: &[u8]) -> Self {
+        match self.data {
+            Some(ref mut existing) => {
+                existing.push(b'&');
+                existing.extend_from_slice(d);
+            }
+            None => self.data = Some(d.to_vec()),
+        }
+        self
+    }
+
+    pub fn query_data(mut self, enable: bool) -> Self {
+        self.query_data = enable;
+        self
+    }
+
+    pub fn crlf(mut self, enable: bool) -> Self {
+        self.crlf = enable;
+        self
+    }
+
+    pub fn upload_file(mut self, path: &str) -> Self {
+        self.upload_file = Some(path.to_string());
+        self
+    }
+
+    /// Add `algorithm` to the body's `Content-Digest`; see `content_digest`.
+    pub fn content_digest(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.content_digest.push(algorithm);
+        self
+    }
+
+    pub fn content_md5(mut self, enable: bool) -> Self {
+        self.content_md5 = enable;
+        self
+    }
+
+    pub fn encrypt_jwe(mut self, key_file: &str) -> Self {
+        self.encrypt_jwe = Some(key_file.to_string());
+        self
+    }
+
+    pub fn decrypt_jwe(mut self, key_file: &str) -> Self {
+        self.decrypt_jwe = Some(key_file.to_string());
+        self
+    }
+
+    /// Add a field to an application/x-www-form-urlencoded body.
+    /// Fields are encoded and joined with `&` at send time.
+    pub fn form_field(mut self, key: &str, value: &str) -> Self {
+        self.form_fields.push((key.to_string(), value.to_string()));
         self
     }
 
@@ -172,16 +708,100 @@ impl RequestConfig {
         self
     }
 
+    /// Abort when no data arrives for this long, independent of `max_time`.
+    pub fn read_timeout(mut self, d: Duration) -> Self {
+        self.read_timeout = Some(d);
+        self
+    }
+
+    /// Limit how long host name resolution may take.
+    pub fn dns_timeout(mut self, d: Duration) -> Self {
+        self.dns_timeout = Some(d);
+        self
+    }
+
+    /// Enable or disable the in-process DNS cache.
+    pub fn dns_cache(mut self, enable: bool) -> Self {
+        self.dns_cache = enable;
+        self
+    }
+
+    /// How long cached lookups are reused.
+    pub fn dns_cache_timeout(mut self, d: Duration) -> Self {
+        self.dns_cache_timeout = d;
+        self
+    }
+
     pub fn output(mut self, path: &str) -> Self {
         self.output = Some(path.to_string());
         self
     }
 
+    pub fn more_url(mut self, url: &str, output: Option<&str>) -> Self {
+        self.more_urls.push((url.to_string(), output.map(str::to_string)));
+        self
+    }
+
+    pub fn remote_name(mut self, enable: bool) -> Self {
+        self.remote_name = enable;
+        self
+    }
+
+    pub fn remote_header_name(mut self, enable: bool) -> Self {
+        self.remote_header_name = enable;
+        self
+    }
+
+    pub fn output_dir(mut self, dir: &str) -> Self {
+        self.output_dir = Some(dir.to_string());
+        self
+    }
+
+    pub fn accept_push(mut self, dir: &str) -> Self {
+        self.accept_push = Some(dir.to_string());
+        self
+    }
+
+    pub fn extract(mut self, enable: bool) -> Self {
+        self.extract = enable;
+        self
+    }
+
+    pub fn extract_overwrite(mut self, enable: bool) -> Self {
+        self.extract_overwrite = enable;
+        self
+    }
+
+    pub fn verify_sig(mut self, sig: &str) -> Self {
+        self.verify_sig = Some(sig.to_string());
+        self
+    }
+
+    pub fn verify_key(mut self, key: &str) -> Self {
+        self.verify_key = Some(key.to_string());
+        self
+    }
+
+    pub fn provenance(mut self, enable: bool) -> Self {
+        self.provenance = enable;
+        self
+    }
+
     pub fn head_only(mut self, enable: bool) -> Self {
         self.head_only = enable;
         self
     }
 
+    pub fn include(mut self, enable: bool) -> Self {
+        self.include = enable;
+        self
+    }
+
+    pub fn write_out(mut self, template: &str) -> Self {
+        self.write_out = Some(template.to_string());
+        self
+    }
+
     pub fn ntlm(mut self, enable: bool) -> Self {
         self.ntlm = enable;
         self
@@ -197,13 +817,20 @@ impl RequestConfig {
         self
     }
 
+    pub fn proxy_header(mut self, h: &str) -> Self {
+        self.proxy_headers.push(h.to_string());
+        self
+    }
+
     pub fn noproxy(mut self, hosts: &str) -> Self {
         self.noproxy = Some(hosts.to_string());
         self
     }
 
-    pub fn cookie(mut self, path: &str) -> Self {
-        self.cookie = Some(path.to_string());
+    /// Add a cookie source: a file path, or an inline `name=value` string.
+    /// Repeatable; sources are merged in order.
+    pub fn cookie(mut self, source: &str) -> Self {
+        self.cookies.push(CookieSource::parse(source));
         self
     }
 
@@ -217,26 +844,262 @@ impl RequestConfig {
         self
     }
 
+    /// On a 401 to a `bearer` request, fetch a new access token from
+    /// `url` and retry once; see `oauth::refresh_request`.
+    pub fn oauth2_token_url(mut self, url: &str) -> Self {
+        self.oauth2_token_url = Some(url.to_string());
+        self
+    }
+
+    pub fn oauth2_client_id(mut self, id: &str) -> Self {
+        self.oauth2_client_id = Some(id.to_string());
+        self
+    }
+
+    pub fn oauth2_client_secret(mut self, secret: &str) -> Self {
+        self.oauth2_client_secret = Some(secret.to_string());
+        self
+    }
+
+    pub fn oauth2_refresh_token(mut self, token: &str) -> Self {
+        self.oauth2_refresh_token = Some(token.to_string());
+        self
+    }
+
+    pub fn auth_on_challenge(mut self, enable: bool) -> Self {
+        self.auth_on_challenge = enable;
+        self
+    }
+
+    /// Try these mechanisms in order instead of committing to one up
+    /// front; see `auth::perform`.
+    pub fn auth_chain(mut self, chain: Vec<Mechanism>) -> Self {
+        self.auth_chain = chain;
+        self
+    }
+
+    pub fn anyauth(mut self, enable: bool) -> Self {
+        self.anyauth = enable;
+        self
+    }
+
+    pub fn registry_auth(mut self, enable: bool) -> Self {
+        self.registry_auth = enable;
+        self
+    }
+
+    pub fn netrc(mut self, enable: bool) -> Self {
+        self.netrc = enable;
+        self
+    }
+
+    /// Read credentials from `path` rather than `~/.netrc`; implies `netrc`.
+    pub fn netrc_file(mut self, path: &str) -> Self {
+        self.netrc = true;
+        self.netrc_file = Some(path.to_string());
+        self
+    }
+
+    pub fn s3_region(mut self, region: &str) -> Self {
+        self.s3_region = Some(region.to_string());
+        self
+    }
+
+    pub fn s3_endpoint(mut self, url: &str) -> Self {
+        self.s3_endpoint = Some(url.to_string());
+        self
+    }
+
+    pub fn s3_path_style(mut self, enable: bool) -> Self {
+        self.s3_path_style = enable;
+        self
+    }
+
+    pub fn s3_part_size(mut self, bytes: u64) -> Self {
+        self.s3_part_size = Some(bytes);
+        self
+    }
+
+    pub fn presign(mut self, expires: Duration) -> Self {
+        self.presign = Some(expires);
+        self
+    }
+
     pub fn compressed(mut self, enable: bool) -> Self {
         self.compressed = enable;
         self
     }
 
+    pub fn range(mut self, spec: &str) -> Self {
+        self.range = Some(spec.to_string());
+        self
+    }
+
+    pub fn mime_sniff(mut self, enable: bool) -> Self {
+        self.mime_sniff = enable;
+        self
+    }
+
+    pub fn output_charset(mut self, label: &str) -> Self {
+        self.output_charset = Some(label.to_string());
+        self
+    }
+
+    pub fn normalize_eol(mut self, eol: Eol) -> Self {
+        self.normalize_eol = Some(eol);
+        self
+    }
+
+    pub fn ignore_content_length(mut self, enable: bool) -> Self {
+        self.ignore_content_length = enable;
+        self
+    }
+
+    pub fn accept_partial(mut self, enable: bool) -> Self {
+        self.accept_partial = enable;
+        self
+    }
+
     pub fn show_timing(mut self, enable: bool) -> Self {
         self.show_timing = enable;
         self
     }
 
+    pub fn assert_time_total_max(mut self, limit: Duration) -> Self {
+        self.assert_time_total_max = Some(limit);
+        self
+    }
+
+    pub fn assert_ttfb_max(mut self, limit: Duration) -> Self {
+        self.assert_ttfb_max = Some(limit);
+        self
+    }
+
+    pub fn prom_out(mut self, path: &str) -> Self {
+        self.prom_out = Some(path.to_string());
+        self
+    }
+
+    pub fn session(mut self, name: &str) -> Self {
+        self.session = Some(name.to_string());
+        self
+    }
+
+    pub fn print_config(mut self, enable: bool) -> Self {
+        self.print_config = enable;
+        self
+    }
+
+    pub fn assume_yes(mut self, enable: bool) -> Self {
+        self.assume_yes = enable;
+        self
+    }
+
+    pub fn watch(mut self, interval: Duration) -> Self {
+        self.watch = Some(interval);
+        self
+    }
+
+    pub fn tail(mut self, interval: Duration) -> Self {
+        self.tail = Some(interval);
+        self
+    }
+
+    pub fn batch(mut self, source: &str) -> Self {
+        self.batch = Some(source.to_string());
+        self
+    }
+
+    pub fn collection(mut self, path: &str) -> Self {
+        self.collection = Some(path.to_string());
+        self
+    }
+
+    pub fn relay(mut self, listen: &str) -> Self {
+        self.relay = Some(listen.to_string());
+        self
+    }
+
+    pub fn relay_remote(mut self, enable: bool) -> Self {
+        self.relay_remote = enable;
+        self
+    }
+
+    pub fn watch_diff(mut self, enable: bool) -> Self {
+        self.watch_diff = enable;
+        self
+    }
+
+    pub fn if_changed(mut self, state: &str) -> Self {
+        self.if_changed = Some(state.to_string());
+        self
+    }
+
+    pub fn capture(mut self, capture: Capture) -> Self {
+        self.captures.push(capture);
+        self
+    }
+
+    pub fn capture_file(mut self, path: &str) -> Self {
+        self.capture_file = Some(path.to_string());
+        self
+    }
+
+    pub fn notify_cmd(mut self, cmd: &str) -> Self {
+        self.notify_cmd = Some(cmd.to_string());
+        self
+    }
+
+    pub fn notify_desktop(mut self, enable: bool) -> Self {
+        self.notify_desktop = enable;
+        self
+    }
+
+    pub fn notify_debounce(mut self, attempts: u32) -> Self {
+        self.notify_debounce = attempts;
+        self
+    }
+
+    /// Whether the backend should collect phase timings: shown with
+    /// `--timing`, needed by a timing assertion or exported as metrics.
+    pub fn wants_timing(&self) -> bool {
+        self.show_timing
+            || self.assert_time_total_max.is_some()
+            || self.assert_ttfb_max.is_some()
+            || self.prom_out.is_some()
+            || self.write_out.is_some()
+    }
+
     pub fn user_agent(mut self, ua: &str) -> Self {
         self.user_agent = Some(ua.to_string());
         self
     }
 
+    pub fn impersonate(mut self, browser: Preset) -> Self {
+        self.impersonate = Some(browser);
+        self
+    }
+
+    pub fn h2_priority(mut self, weight: u32) -> Self {
+        self.h2_priority = Some(weight);
+        self
+    }
+
+    pub fn http2(mut self, mode: Http2) -> Self {
+        self.http2 = Some(mode);
+        self
+    }
+
     pub fn silent(mut self, enable: bool) -> Self {
         self.silent = enable;
         self
     }
 
+    pub fn follow_location(mut self, enable: bool) -> Self {
+        self.follow_location = enable;
+        self
+    }
+
     pub fn max_redirs(mut self, n: u32) -> Self {
         self.max_redirs = Some(n);
         self
@@ -258,11 +1121,33 @@ impl RequestConfig {
         self
     }
 
+    pub fn proxy_digest(mut self, enable: bool) -> Self {
+        self.proxy_digest = enable;
+        self
+    }
+
+    pub fn proxy_basic(mut self, enable: bool) -> Self {
+        self.proxy_basic = enable;
+        self
+    }
+
+    /// Let the proxy's challenge pick among negotiate, NTLM, Digest and
+    /// Basic, strongest first.
+    pub fn proxy_anyauth(mut self, enable: bool) -> Self {
+        self.proxy_anyauth = enable;
+        self
+    }
+
     pub fn proxy_insecure(mut self, enable: bool) -> Self {
         self.proxy_insecure = enable;
         self
     }
 
+    pub fn proxy_tunnel(mut self, enable: bool) -> Self {
+        self.proxy_tunnel = enable;
+        self
+    }
+
     pub fn proxy_cacert(mut self, path: &str) -> Self {
         self.proxy_cacert = Some(path.to_string());
         self
@@ -272,6 +1157,256 @@ impl RequestConfig {
         self.ssl_no_revoke = enable;
         self
     }
+
+    pub fn tls_session_cache(mut self, path: &str) -> Self {
+        self.tls_session_cache = Some(path.to_string());
+        self
+    }
+
+    pub fn tls_early_data(mut self, enable: bool) -> Self {
+        self.tls_early_data = enable;
+        self
+    }
+
+    pub fn tls_min(mut self, version: TlsVersion) -> Self {
+        self.tls_min = Some(version);
+        self
+    }
+
+    pub fn tls_max(mut self, version: TlsVersion) -> Self {
+        self.tls_max = Some(version);
+        self
+    }
+
+    pub fn ciphers(mut self, list: &str) -> Self {
+        self.ciphers = Some(list.to_string());
+        self
+    }
+
+    pub fn tls13_ciphers(mut self, list: &str) -> Self {
+        self.tls13_ciphers = Some(list.to_string());
+        self
+    }
+
+    pub fn keylog(mut self, path: &str) -> Self {
+        self.keylog = Some(path.to_string());
+        self
+    }
+
+    pub fn show_certs(mut self, enable: bool) -> Self {
+        self.show_certs = enable;
+        self
+    }
+
+    pub fn tofu(mut self, enable: bool) -> Self {
+        self.tofu = enable;
+        self
+    }
+
+    pub fn pinned_pubkey(mut self, pin: &str) -> Self {
+        self.pinned_pubkey = Some(pin.to_string());
+        self
+    }
+
+    pub fn ech(mut self, mode: &str) -> Self {
+        self.ech = Some(mode.to_string());
+        self
+    }
+
+    /// Retry transient failures (timeouts, 408/429/5xx) up to `n` times.
+    pub fn retry(mut self, n: u32) -> Self {
+        self.retry = n;
+        self
+    }
+
+    /// Fixed delay between retries instead of exponential backoff.
+    pub fn retry_delay(mut self, d: Duration) -> Self {
+        self.retry_delay = Some(d);
+        self
+    }
+
+    /// Print a summary of all attempts to stderr after the transfer.
+    pub fn retry_report(mut self, enable: bool) -> Self {
+        self.retry_report = enable;
+        self
+    }
+
+    pub fn message_format(mut self, format: MessageFormat) -> Self {
+        self.message_format = format;
+        self
+    }
+
+    /// Make jitter and generated IDs reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Look up environment fallbacks in `env` instead of the process
+    /// environment.
+    pub fn env(mut self, env: EnvSource) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Value of an environment fallback variable for this request.
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        match self.env {
+            Some(ref env) => env.get(name),
+            None => std::env::var(name).ok(),
+        }
+    }
+
+    /// True when the request must not see the process environment.
+    pub fn env_isolated(&self) -> bool {
+        self.env.as_ref().is_some_and(EnvSource::is_isolated)
+    }
+
+    /// Request body to send: raw `data` followed by any encoded form
+    /// fields, joined with `&` like curl does for repeated `-d`. With
+    /// `query_data` they go in the URL instead and there is no body.
+    pub fn body(&self) -> Option<Vec<u8>> {
+        if self.query_data {
+            return None;
+        }
+        let payload = self.payload()?;
+        Some(if self.crlf { eol::normalize(&payload, Eol::Crlf) } else { payload })
+    }
+
+    /// URL to request: `url` with the payload appended under `query_data`.
+    pub fn request_url(&self) -> Cow<'_, str> {
+        match self.payload() {
+            Some(query) if self.query_data => Cow::Owned(url::append_query(&self.url, &String::from_utf8_lossy(&query))),
+            _ => Cow::Borrowed(&self.url),
+        }
+    }
+
+    fn payload(&self) -> Option<Vec<u8>> {
+        let form = self
+            .form_fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", urlencode(k), urlencode(v)))
+            .collect::<Vec<_>>();
+        let parts: Vec<&[u8]> = self
+            .data
+            .iter()
+            .map(|d| d.as_slice())
+            .chain(form.iter().map(|f| f.as_bytes()))
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(&b'&'))
+        }
+    }
+
+    /// One config per URL on the command line: this one, then each of
+    /// `more_urls` with its own output.
+    pub fn each_url(&self) -> Vec<RequestConfig> {
+        let first = RequestConfig {
+            more_urls: Vec::new(),
+            ..self.clone()
+        };
+        let more = self.more_urls.iter().map(|(url, output)| RequestConfig {
+            url: url.clone(),
+            output: output.clone(),
+            ..first.clone()
+        });
+        std::iter::once(first.clone()).chain(more).collect()
+    }
+
+    /// Where `-o` writes: the output path, under `output_dir` when relative.
+    /// `None` for `-o -`, which writes to stdout.
+    pub fn output_path(&self) -> Option<PathBuf> {
+        let path = Path::new(self.output.as_ref().filter(|o| *o != "-")?);
+        Some(match self.output_dir {
+            Some(ref dir) if path.is_relative() => Path::new(dir).join(path),
+            _ => path.to_path_buf(),
+        })
+    }
+
+    /// Whether the body goes to stdout as it arrives (`-o -`).
+    pub fn writes_stdout(&self) -> bool {
+        self.output.as_deref() == Some("-")
+    }
+
+    /// Directory `--extract` unpacks into: `output_dir`, else the directory
+    /// of the saved file.
+    pub fn extract_dir(&self) -> Option<PathBuf> {
+        match self.output_dir {
+            Some(ref dir) => Some(PathBuf::from(dir)),
+            None => self
+                .output_path()?
+                .parent()
+                .map(|p| if p.as_os_str().is_empty() { PathBuf::from(".") } else { p.to_path_buf() }),
+        }
+    }
+
+    /// Whether a header with the given name was set with `header()`/`-H`.
+    pub fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|h| split_header(h).is_some_and(|(k, _)| k.eq_ignore_ascii_case(name)))
+    }
+
+    /// User-Agent to send: `-A`, else the impersonated browser's, else
+    /// `DEFAULT_USER_AGENT`.
+    pub fn effective_user_agent(&self) -> &str {
+        self.user_agent
+            .as_deref()
+            .or(self.impersonate.map(|p| p.user_agent()))
+            .unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// The impersonated browser's headers that `-H` does not override.
+    pub fn impersonated_headers(&self) -> Vec<(&'static str, &'static str)> {
+        self.impersonate
+            .map_or(&[][..], |p| p.headers())
+            .iter()
+            .filter(|(name, _)| !self.has_header(name))
+            .copied()
+            .collect()
+    }
+
+    /// Content-Type implied by the body, unless the user set one explicitly.
+    /// Like curl, any `-d` body is sent as a form unless told otherwise.
+    pub fn implied_content_type(&self) -> Option<&'static str> {
+        if self.body().is_some() && !self.has_header("Content-Type") {
+            Some("application/x-www-form-urlencoded")
+        } else {
+            None
+        }
+    }
+}
+
+/// Split a `Name: value` header line. Returns `None` for lines without a
+/// colon or whose name is empty or contains whitespace/control characters.
+pub fn split_header(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once(':')?;
+    let name = name.trim();
+    if name.is_empty() || name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return None;
+    }
+    Some((name, value.trim()))
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+pub fn urlencode(s: &str) -> String {
+    urlencode_bytes(s.as_bytes())
+}
+
+/// `urlencode` for arbitrary bytes, e.g. a `--data-urlencode @file`.
+pub fn urlencode_bytes(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
 }
 
 #[cfg(test)]
@@ -292,28 +1427,44 @@ mod tests {
         assert!(!cfg.verbose);
         assert!(cfg.headers.is_empty());
         assert!(cfg.data.is_none());
+        assert!(cfg.form_fields.is_empty());
         assert!(cfg.connect_timeout.is_none());
         assert!(cfg.max_time.is_none());
+        assert!(cfg.read_timeout.is_none());
+        assert!(cfg.dns_timeout.is_none());
+        assert!(cfg.dns_cache);
+        assert_eq!(cfg.dns_cache_timeout, Duration::from_secs(60));
         assert!(cfg.output.is_none());
         assert!(!cfg.head_only);
+        assert!(!cfg.include);
         assert!(!cfg.ntlm);
         assert!(cfg.proxy_user.is_none());
         assert!(cfg.proxy_password.is_none());
         assert!(cfg.noproxy.is_none());
-        assert!(cfg.cookie.is_none());
+        assert!(cfg.cookies.is_empty());
         assert!(cfg.cookie_jar.is_none());
         assert!(cfg.bearer.is_none());
         assert!(!cfg.compressed);
+        assert!(!cfg.ignore_content_length);
+        assert!(!cfg.accept_partial);
+        assert!(cfg.mime_sniff);
         assert!(!cfg.show_timing);
         assert!(cfg.user_agent.is_none());
         assert!(!cfg.silent);
+        assert!(cfg.follow_location);
         assert!(cfg.max_redirs.is_none());
         assert!(cfg.resolve.is_empty());
         assert!(!cfg.proxy_negotiate);
         assert!(!cfg.proxy_ntlm);
+        assert!(!cfg.proxy_digest && !cfg.proxy_basic && !cfg.proxy_anyauth);
         assert!(!cfg.proxy_insecure);
         assert!(cfg.proxy_cacert.is_none());
         assert!(!cfg.ssl_no_revoke);
+        assert_eq!(cfg.retry, 0);
+        assert!(cfg.retry_delay.is_none());
+        assert!(!cfg.retry_report);
+        assert_eq!(cfg.message_format, MessageFormat::Long);
+        assert!(cfg.seed.is_none());
     }
 
     #[test]
@@ -331,11 +1482,15 @@ mod tests {
             .data("{\"key\":\"val\"}")
             .connect_timeout(Duration::from_secs(10))
             .max_time(Duration::from_secs(30))
+            .read_timeout(Duration::from_secs(15))
+            .dns_timeout(Duration::from_secs(2))
             .output("/tmp/out.html")
             .head_only(true)
+            .include(true)
             .ntlm(true)
             .proxy_user("puser")
             .proxy_password("ppass")
+            .proxy_header("X-Corp-Token: abc")
             .noproxy("localhost,127.0.0.1")
             .cookie("/tmp/cookies")
             .cookie_jar("/tmp/jar")
@@ -349,8 +1504,13 @@ mod tests {
             .proxy_negotiate(true)
             .proxy_ntlm(true)
             .proxy_insecure(true)
+            .proxy_tunnel(true)
+            .preproxy("socks5://jump:1080")
             .proxy_cacert("/proxy-ca.pem")
-            .ssl_no_revoke(true);
+            .ssl_no_revoke(true)
+            .retry(3)
+            .retry_delay(Duration::from_secs(2))
+            .retry_report(true);
 
         assert_eq!(cfg.method, Method::Post);
         assert!(cfg.negotiate);
@@ -361,16 +1521,20 @@ mod tests {
         assert_eq!(cfg.proxy.as_deref(), Some("http://proxy:8080"));
         assert!(cfg.verbose);
         assert_eq!(cfg.headers, vec!["Content-Type: application/json"]);
-        assert_eq!(cfg.data.as_deref(), Some("{\"key\":\"val\"}"));
+        assert_eq!(cfg.data.as_deref(), Some(&b"{\"key\":\"val\"}"[..]));
         assert_eq!(cfg.connect_timeout, Some(Duration::from_secs(10)));
         assert_eq!(cfg.max_time, Some(Duration::from_secs(30)));
+        assert_eq!(cfg.read_timeout, Some(Duration::from_secs(15)));
+        assert_eq!(cfg.dns_timeout, Some(Duration::from_secs(2)));
         assert_eq!(cfg.output.as_deref(), Some("/tmp/out.html"));
         assert!(cfg.head_only);
+        assert!(cfg.include);
         assert!(cfg.ntlm);
         assert_eq!(cfg.proxy_user.as_deref(), Some("puser"));
         assert_eq!(cfg.proxy_password.as_deref(), Some("ppass"));
+        assert_eq!(cfg.proxy_headers, ["X-Corp-Token: abc"]);
         assert_eq!(cfg.noproxy.as_deref(), Some("localhost,127.0.0.1"));
-        assert_eq!(cfg.cookie.as_deref(), Some("/tmp/cookies"));
+        assert_eq!(cfg.cookies, vec![CookieSource::File("/tmp/cookies".into())]);
         assert_eq!(cfg.cookie_jar.as_deref(), Some("/tmp/jar"));
         assert_eq!(cfg.bearer.as_deref(), Some("tok123"));
         assert!(cfg.compressed);
@@ -382,8 +1546,13 @@ mod tests {
         assert!(cfg.proxy_negotiate);
         assert!(cfg.proxy_ntlm);
         assert!(cfg.proxy_insecure);
+        assert!(cfg.proxy_tunnel);
+        assert_eq!(cfg.preproxy.as_deref(), Some("socks5://jump:1080"));
         assert_eq!(cfg.proxy_cacert.as_deref(), Some("/proxy-ca.pem"));
         assert!(cfg.ssl_no_revoke);
+        assert_eq!(cfg.retry, 3);
+        assert_eq!(cfg.retry_delay, Some(Duration::from_secs(2)));
+        assert!(cfg.retry_report);
     }
 
     #[test]
@@ -420,6 +1589,113 @@ mod tests {
         assert_eq!(cfg.headers.len(), 2);
     }
 
+    #[test]
+    fn urlencode_reserved_chars() {
+        assert_eq!(urlencode("abc-._~123"), "abc-._~123");
+        assert_eq!(urlencode("a b&c=d"), "a%20b%26c%3Dd");
+        assert_eq!(urlencode("caf\u{e9}"), "caf%C3%A9");
+    }
+
+    #[test]
+    fn impersonation_defers_to_explicit_values() {
+        let cfg = RequestConfig::new("https://x.com");
+        assert_eq!(cfg.effective_user_agent(), DEFAULT_USER_AGENT);
+        assert!(cfg.impersonated_headers().is_empty());
+
+        let cfg = cfg.impersonate(Preset::Firefox).header("accept-language: de");
+        assert_eq!(cfg.effective_user_agent(), Preset::Firefox.user_agent());
+        let names: Vec<_> = cfg.impersonated_headers().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["Accept", "Upgrade-Insecure-Requests"]);
+        assert_eq!(cfg.user_agent("me/1").effective_user_agent(), "me/1");
+    }
+
+    #[test]
+    fn split_header_lines() {
+        assert_eq!(split_header("Accept: text/html"), Some(("Accept", "text/html")));
+        assert_eq!(split_header("X-Empty:"), Some(("X-Empty", "")));
+        assert_eq!(split_header("Host: a:b:c"), Some(("Host", "a:b:c")));
+        assert_eq!(split_header("HTTP/1.1 200 OK"), None);
+        assert_eq!(split_header("Bad Name: x"), None);
+        assert_eq!(split_header(": x"), None);
+        assert_eq!(split_header("no colon"), None);
+    }
+
+    #[test]
+    fn form_fields_build_encoded_body() {
+        let cfg = RequestConfig::new("https://x.com")
+            .form_field("name", "John Doe")
+            .form_field("q", "a&b");
+        assert_eq!(cfg.body().as_deref(), Some(&b"name=John%20Doe&q=a%26b"[..]));
+        assert_eq!(
+            cfg.implied_content_type(),
+            Some("application/x-www-form-urlencoded")
+        );
+    }
+
+    #[test]
+    fn form_fields_appended_to_data() {
+        let cfg = RequestConfig::new("https://x.com")
+            .data("raw=1")
+            .form_field("k", "v");
+        assert_eq!(cfg.body().as_deref(), Some(&b"raw=1&k=v"[..]));
+    }
+
+    #[test]
+    fn explicit_content_type_wins_over_form() {
+        let cfg = RequestConfig::new("https://x.com")
+            .header("content-type: text/plain")
+            .form_field("k", "v");
+        assert!(cfg.implied_content_type().is_none());
+    }
+
+    #[test]
+    fn repeated_data_concatenates() {
+        let cfg = RequestConfig::new("https://x.com").data("a=1").data("b=2");
+        assert_eq!(cfg.data.as_deref(), Some(&b"a=1&b=2"[..]));
+    }
+
+    #[test]
+    fn binary_data_survives() {
+        let cfg = RequestConfig::new("https://x.com").data_bytes(&[0x00, 0xff]).data("a=1");
+        assert_eq!(cfg.body().as_deref(), Some(&[0x00, 0xff, b'&', b'a', b'=', b'1'][..]));
+    }
+
+    #[test]
+    fn query_data_moves_payload_to_url() {
+        let cfg = RequestConfig::new("https://x.com/s?lang=en")
+            .data("q=rust")
+            .form_field("page", "2")
+            .query_data(true);
+        assert!(cfg.body().is_none());
+        assert!(cfg.implied_content_type().is_none());
+        assert_eq!(cfg.request_url(), "https://x.com/s?lang=en&q=rust&page=2");
+        let plain = RequestConfig::new("https://x.com/s").data("q=rust");
+        assert_eq!(plain.request_url(), "https://x.com/s");
+    }
+
+    #[test]
+    fn crlf_applies_to_body_only() {
+        let cfg = RequestConfig::new("https://x.com").data("a\nb").crlf(true);
+        assert_eq!(cfg.body().as_deref(), Some(&b"a\r\nb"[..]));
+        assert_eq!(cfg.data.as_deref(), Some(&b"a\nb"[..]));
+    }
+
+    #[test]
+    fn body_none_without_data() {
+        let cfg = RequestConfig::new("https://x.com");
+        assert!(cfg.body().is_none());
+        assert!(cfg.implied_content_type().is_none());
+    }
+
+    #[test]
+    fn raw_data_implies_form_content_type() {
+        let cfg = RequestConfig::new("https://x.com").data("{}");
+        assert_eq!(
+            cfg.implied_content_type(),
+            Some("application/x-www-form-urlencoded")
+        );
+    }
+
     #[test]
     fn multiple_resolve_entries() {
         let cfg = RequestConfig::new("https://x.com")
@@ -427,4 +1703,52 @@ mod tests {
             .add_resolve("b.com:80:2.2.2.2");
         assert_eq!(cfg.resolve.len(), 2);
     }
+
+    #[test]
+    fn output_path_and_extract_dir() {
+        let cfg = RequestConfig::new("https://x.com");
+        assert_eq!(cfg.output_path(), None);
+        assert_eq!(cfg.extract_dir(), None);
+
+        let cfg = cfg.output("pkg.tgz");
+        assert_eq!(cfg.output_path(), Some(PathBuf::from("pkg.tgz")));
+        assert_eq!(cfg.extract_dir(), Some(PathBuf::from(".")));
+
+        let cfg = cfg.output_dir("/dl");
+        assert_eq!(cfg.output_path(), Some(PathBuf::from("/dl/pkg.tgz")));
+        assert_eq!(cfg.extract_dir(), Some(PathBuf::from("/dl")));
+
+        let cfg = cfg.output("/tmp/abs.zip");
+        assert_eq!(cfg.output_path(), Some(PathBuf::from("/tmp/abs.zip")));
+        assert_eq!(cfg.extract_dir(), Some(PathBuf::from("/dl")));
+    }
+
+    #[test]
+    fn each_url_keeps_shared_options() {
+        let cfg = RequestConfig::new("https://x.com/a")
+            .output("a.html")
+            .insecure(true)
+            .more_url("https://x.com/b", Some("b.html"))
+            .more_url("https://y.com/c", None);
+        let each = cfg.each_url();
+        let urls: Vec<_> = each.iter().map(|c| (c.url.as_str(), c.output.as_deref())).collect();
+        assert_eq!(
+            urls,
+            [("https://x.com/a", Some("a.html")), ("https://x.com/b", Some("b.html")), ("https://y.com/c", None)]
+        );
+        assert!(each.iter().all(|c| c.insecure && c.more_urls.is_empty()));
+        assert_eq!(RequestConfig::new("https://x.com").each_url().len(), 1);
+    }
+
+    #[test]
+    fn notes_reach_the_sink_only_when_verbose() {
+        use std::sync::{Arc, Mutex};
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&seen);
+        let cfg = RequestConfig::new("https://x.com")
+            .verbose_sink(VerboseSink::custom(move |m| collected.lock().unwrap().push(m.to_string())));
+        cfg.note("quiet");
+        cfg.clone().verbose(true).note(format_args!("DNS cache miss for {}:443", "x.com"));
+        assert_eq!(*seen.lock().unwrap(), ["DNS cache miss for x.com:443"]);
+    }
 }
@@ -0,0 +1,106 @@
+// src/curl/confirm.rs
+
+//! Asking before `-k`/`--insecure` or `--proxy-insecure` is used from a
+//! terminal against a host other than this machine. The server's
+//! certificate chain is shown first, so that pasting `-k` from a snippet
+//! is a decision rather than a habit. `--yes` skips the question, and so
+//! does a run without a terminal (scripts, pipes, CI).
+
+use std::io::{self, BufRead, Write};
+use std::net::IpAddr;
+
+use super::config::RequestConfig;
+use super::request::resolve_proxy;
+use super::url;
+
+/// A check `config` turns off for a remote host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Risk {
+    pub flag: &'static str,
+    pub host: String,
+}
+
+impl Risk {
+    pub fn question(&self) -> String {
+        format!("{} turns off certificate checks for {}. Continue? [y/N] ", self.flag, self.host)
+    }
+}
+
+/// What `config` would send without certificate checks, if that is
+/// anything but this machine: an `https://` URL with `-k`, an `https://`
+/// proxy with `--proxy-insecure`.
+pub fn risks(config: &RequestConfig) -> Vec<Risk> {
+    let mut risks = Vec::new();
+    let remote_https = |target: &str| {
+        let parts = url::parse(target).ok()?;
+        let https = parts.scheme.is_some_and(|s| s.eq_ignore_ascii_case("https"));
+        (https && !is_local(parts.host)).then(|| parts.host.to_string())
+    };
+    if config.insecure
+        && let Some(host) = remote_https(&config.url)
+    {
+        risks.push(Risk { flag: "-k", host });
+    }
+    if config.proxy_insecure
+        && let Some(host) = resolve_proxy(config).and_then(|proxy| remote_https(&proxy))
+    {
+        risks.push(Risk {
+            flag: "--proxy-insecure",
+            host,
+        });
+    }
+    risks
+}
+
+/// `localhost`, a `.localhost` name or a loopback address.
+pub fn is_local(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Write `question` and read the answer: `y` or `yes` in any case goes on,
+/// anything else (including end of input) does not.
+pub fn ask(question: &str, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<bool> {
+    output.write_all(question.as_bytes())?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(matches!(line.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::env::EnvSource;
+
+    #[test]
+    fn remote_https_only() {
+        let config = |url: &str| RequestConfig::new(url).env(EnvSource::Disabled).insecure(true);
+        assert_eq!(risks(&config("https://api.example.com/x"))[0].host, "api.example.com");
+        assert!(risks(&config("https://localhost:8443/")).is_empty());
+        assert!(risks(&config("https://127.0.0.2/")).is_empty());
+        assert!(risks(&config("https://[::1]:8443/")).is_empty());
+        assert!(risks(&config("http://api.example.com/")).is_empty());
+        assert!(risks(&RequestConfig::new("https://api.example.com/").env(EnvSource::Disabled)).is_empty());
+
+        let proxied = RequestConfig::new("http://origin.test/").proxy("https://proxy.corp:3129").proxy_insecure(true);
+        let risk = &risks(&proxied)[0];
+        assert_eq!((risk.flag, risk.host.as_str()), ("--proxy-insecure", "proxy.corp"));
+        assert!(risk.question().starts_with("--proxy-insecure turns off certificate checks for proxy.corp."));
+    }
+
+    #[test]
+    fn only_yes_goes_on() {
+        let answer = |text: &str| {
+            let mut out = Vec::new();
+            let yes = ask("Continue? ", &mut text.as_bytes(), &mut out).unwrap();
+            assert_eq!(out, b"Continue? ");
+            yes
+        };
+        assert!(answer("y\n"));
+        assert!(answer(" YES \r\n"));
+        assert!(!answer("\n"));
+        assert!(!answer("no\n"));
+        assert!(!answer(""));
+    }
+}
@@ -0,0 +1,169 @@
+// src/curl/content_digest.rs
+
+//! Digests of the outgoing body for APIs that check them:
+//! `--content-digest` sends RFC 9530's `Content-Digest`, `--content-md5`
+//! the older `Content-MD5` (RFC 1864) some storage APIs still require.
+//! They cover the bytes as sent: `-d`/form data, or a `-T` file read once
+//! ahead of the upload, after `--crlf`. A header set with `-H` is kept.
+//! A `-T -` upload from stdin cannot be read twice, so it is refused.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+
+use super::config::RequestConfig;
+use super::encoding::base64;
+use super::eol::CrlfReader;
+use super::error::RequestError;
+
+/// Algorithms from the IANA Hash Algorithms for HTTP Digest Fields
+/// registry that are not deprecated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "sha-256" => Some(DigestAlgorithm::Sha256),
+            "sha-512" => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DigestAlgorithm::Sha256 => "sha-256",
+            DigestAlgorithm::Sha512 => "sha-512",
+        })
+    }
+}
+
+/// `config` with its digest headers added and the options that asked for
+/// them cleared, or `None` when none were asked for.
+pub fn apply(config: &RequestConfig) -> Result<Option<RequestConfig>, RequestError> {
+    if config.content_digest.is_empty() && !config.content_md5 {
+        return Ok(None);
+    }
+    let headers = match config.upload_file {
+        Some(ref path) if path == "-" => {
+            let option = if config.content_digest.is_empty() { "--content-md5" } else { "--content-digest" };
+            return Err(RequestError::Config {
+                option: option.to_string(),
+                message: "the body is hashed before it is sent, so it cannot be read from stdin with -T -; \
+                          save it to a file first"
+                    .to_string(),
+            });
+        }
+        Some(ref path) => {
+            let file = File::open(path)?;
+            if config.crlf {
+                headers(&config.content_digest, config.content_md5, CrlfReader::new(file))?
+            } else {
+                headers(&config.content_digest, config.content_md5, file)?
+            }
+        }
+        None => headers(&config.content_digest, config.content_md5, config.body().unwrap_or_default().as_slice())?,
+    };
+    let mut digested = config.clone();
+    digested.content_digest.clear();
+    digested.content_md5 = false;
+    for (name, value) in headers {
+        if !config.has_header(name) {
+            digested = digested.header(&format!("{name}: {value}"));
+        }
+    }
+    Ok(Some(digested))
+}
+
+/// The `Content-Digest` (one member per algorithm) and `Content-MD5`
+/// headers for `body`, reading it once.
+pub fn headers(
+    algorithms: &[DigestAlgorithm],
+    md5: bool,
+    mut body: impl Read,
+) -> io::Result<Vec<(&'static str, String)>> {
+    let mut sha256 = Sha256::new();
+    let mut sha512 = Sha512::new();
+    let mut md5_state = Md5::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = body.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for algorithm in algorithms {
+            match algorithm {
+                DigestAlgorithm::Sha256 => sha256.update(&buf[..n]),
+                DigestAlgorithm::Sha512 => sha512.update(&buf[..n]),
+            }
+        }
+        if md5 {
+            md5_state.update(&buf[..n]);
+        }
+    }
+    let mut headers = Vec::new();
+    if !algorithms.is_empty() {
+        let members: Vec<String> = algorithms
+            .iter()
+            .map(|algorithm| {
+                let digest = match algorithm {
+                    DigestAlgorithm::Sha256 => base64(&sha256.clone().finalize()),
+                    DigestAlgorithm::Sha512 => base64(&sha512.clone().finalize()),
+                };
+                format!("{algorithm}=:{digest}:")
+            })
+            .collect();
+        headers.push(("Content-Digest", members.join(", ")));
+    }
+    if md5 {
+        headers.push(("Content-MD5", base64(&md5_state.finalize())));
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_9530_example() {
+        // RFC 9530 section 2, the body {"hello": "world"}
+        let body: &[u8] = b"{\"hello\": \"world\"}";
+        let headers = headers(&[DigestAlgorithm::Sha256, DigestAlgorithm::Sha512], true, body).unwrap();
+        assert_eq!(headers[0].0, "Content-Digest");
+        assert!(headers[0].1.starts_with("sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:, sha-512=:"));
+        assert_eq!(headers[1], ("Content-MD5", "Sd/dVLAcvNLSq16eXua5uQ==".to_string()));
+        assert_eq!(DigestAlgorithm::parse("SHA-512"), Some(DigestAlgorithm::Sha512));
+        assert_eq!(DigestAlgorithm::parse("md5"), None);
+    }
+
+    #[test]
+    fn applied_once_and_user_header_kept() {
+        let config = RequestConfig::new("https://x.com")
+            .data("{\"hello\": \"world\"}")
+            .content_digest(DigestAlgorithm::Sha256)
+            .content_md5(true)
+            .header("Content-MD5: given");
+        let digested = apply(&config).unwrap().unwrap();
+        let expected = "Content-Digest: sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:";
+        assert!(digested.headers.iter().any(|h| h == expected), "{:?}", digested.headers);
+        assert_eq!(digested.headers.iter().filter(|h| h.starts_with("Content-MD5")).count(), 1);
+        assert!(apply(&digested).unwrap().is_none());
+    }
+
+    #[test]
+    fn stdin_upload_refused() {
+        let config = RequestConfig::new("https://x.com").upload_file("-").content_md5(true);
+        let err = apply(&config).unwrap_err();
+        assert_eq!(err.code(), "config");
+        assert!(err.to_string().contains("--content-md5") && err.to_string().contains("-T -"), "{err}");
+    }
+}
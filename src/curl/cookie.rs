@@ -0,0 +1,385 @@
+// src/curl/cookie.rs
+
+//! Cookie sources given with `-b`: either a cookie file or an inline
+//! `name=value; name2=value2` string, merged in argument order.
+
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One `-b` argument. Like curl, a value containing `=` is an inline
+/// cookie string; anything else is a path to a cookie file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CookieSource {
+    File(String),
+    Inline(String),
+}
+
+impl CookieSource {
+    pub fn parse(arg: &str) -> Self {
+        if arg.contains('=') {
+            CookieSource::Inline(arg.to_string())
+        } else {
+            CookieSource::File(arg.to_string())
+        }
+    }
+}
+
+/// Parse an inline cookie string (`a=1; b=2`) into name/value pairs.
+pub fn parse_inline(s: &str) -> Vec<(String, String)> {
+    s.split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some((name.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Parse a Netscape-format cookie file, keeping the cookies a request to
+/// `host` and `path` carries: the domain matches (exactly, unless the
+/// line's tailmatch flag is TRUE), the cookie path is a prefix of `path`,
+/// the cookie has not expired, and Secure ones only when `https`. Lines
+/// that don't have the seven tab-separated fields are skipped.
+pub fn parse_cookie_file(content: &str, host: &str, path: &str, https: bool) -> Vec<(String, String)> {
+    let host = host.to_ascii_lowercase();
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields = live_fields(line)?;
+            let tailmatch = fields[1].eq_ignore_ascii_case("TRUE");
+            if !domain_matches(&host, fields[0], tailmatch)
+                || !path_matches(path, fields[2])
+                || (fields[3].eq_ignore_ascii_case("TRUE") && !https)
+            {
+                return None;
+            }
+            Some((fields[5].to_string(), fields[6].trim_end().to_string()))
+        })
+        .collect()
+}
+
+/// The seven fields of a cookie line that has not expired.
+fn live_fields(line: &str) -> Option<Vec<&str>> {
+    let fields: Vec<&str> = cookie_line(line)?.split('\t').collect();
+    let expires = fields.get(4)?.parse::<u64>().unwrap_or(0);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    (fields.len() == 7 && (expires == 0 || expires > now)).then_some(fields)
+}
+
+/// `line` without a `#HttpOnly_` prefix; `None` for comments and blank
+/// lines.
+fn cookie_line(line: &str) -> Option<&str> {
+    let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+    (!line.starts_with('#') && !line.trim().is_empty()).then_some(line)
+}
+
+/// 1-based numbers of the lines `parse_cookie_file` skips for not having
+/// seven fields.
+pub fn malformed_lines(content: &str) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| cookie_line(line).is_some_and(|line| line.split('\t').count() != 7))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+fn domain_matches(host: &str, domain: &str, tailmatch: bool) -> bool {
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    host == domain || (tailmatch && host.ends_with(&format!(".{domain}")))
+}
+
+/// RFC 6265 section 5.1.4: `cookie_path` is `path` or a prefix of it
+/// that ends at a `/`.
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    let path = if path.is_empty() { "/" } else { path };
+    match path.strip_prefix(cookie_path) {
+        Some(rest) => cookie_path.ends_with('/') || rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Host, path and whether a request to `url` is over https.
+fn target(url: &str) -> (&str, &str, bool) {
+    let parts = super::url::parse(url).ok();
+    let https = parts.as_ref().and_then(|p| p.scheme).is_some_and(|s| s.eq_ignore_ascii_case("https"));
+    parts.map_or(("", "/", https), |p| (p.host, p.path, https))
+}
+
+/// Merge all sources for a request to `url` into one list. Sources are
+/// applied in order, so a later `-b` overrides an earlier cookie with the
+/// same name. A cookie file that does not exist yet, as with `-b jar -c
+/// jar` on the first run, adds nothing, as in curl.
+pub fn merge_cookies(
+    sources: &[CookieSource],
+    url: &str,
+) -> std::io::Result<Vec<(String, String)>> {
+    let (host, path, https) = target(url);
+    let mut merged: Vec<(String, String)> = Vec::new();
+    for source in sources {
+        let cookies = match source {
+            CookieSource::File(file) => match read_cookie_file(file)? {
+                Some(content) => parse_cookie_file(&content, host, path, https),
+                None => Vec::new(),
+            },
+            CookieSource::Inline(s) => parse_inline(s),
+        };
+        merge_into(&mut merged, cookies);
+    }
+    Ok(merged)
+}
+
+fn merge_into(merged: &mut Vec<(String, String)>, cookies: Vec<(String, String)>) {
+    for (name, value) in cookies {
+        match merged.iter_mut().find(|(n, _)| *n == name) {
+            Some(existing) => existing.1 = value,
+            None => merged.push((name, value)),
+        }
+    }
+}
+
+/// The `-b` sources split the way the curl backend sends them.
+#[derive(Debug, Default, PartialEq)]
+pub struct EngineCookies {
+    /// Cookie file lines for libcurl's cookie engine, which applies each
+    /// one's domain, path and Secure flag again on every redirect hop.
+    pub lines: Vec<String>,
+    /// Inline pairs for `CURLOPT_COOKIE`.
+    pub inline: Vec<(String, String)>,
+}
+
+/// Split `sources` for a request to `url`. A later source still wins:
+/// file lines whose name a later inline source sets are left out, and so
+/// are inline pairs a later file sets for `url`.
+pub fn engine_split(sources: &[CookieSource], url: &str) -> std::io::Result<EngineCookies> {
+    let (host, path, https) = target(url);
+    let contents = sources
+        .iter()
+        .map(|source| match source {
+            CookieSource::File(file) => read_cookie_file(file),
+            CookieSource::Inline(_) => Ok(None),
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut lines = Vec::new();
+    let mut inline = Vec::new();
+    for (i, source) in sources.iter().enumerate() {
+        let later = i + 1..sources.len();
+        match source {
+            CookieSource::File(_) => {
+                let set_later = |name: &str| {
+                    later.clone().any(|j| match &sources[j] {
+                        CookieSource::Inline(s) => parse_inline(s).iter().any(|(n, _)| n == name),
+                        CookieSource::File(_) => false,
+                    })
+                };
+                for line in contents[i].as_deref().unwrap_or_default().lines() {
+                    if live_fields(line).is_some_and(|fields| !set_later(fields[5])) {
+                        lines.push(line.to_string());
+                    }
+                }
+            }
+            CookieSource::Inline(s) => {
+                let set_later = |name: &str| {
+                    later.clone().any(|j| {
+                        let content = contents[j].as_deref().unwrap_or_default();
+                        parse_cookie_file(content, host, path, https).iter().any(|(n, _)| n == name)
+                    })
+                };
+                merge_into(&mut inline, parse_inline(s).into_iter().filter(|(n, _)| !set_later(n)).collect());
+            }
+        }
+    }
+    Ok(EngineCookies { lines, inline })
+}
+
+fn read_cookie_file(path: &str) -> std::io::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Lines for a `-c` jar: `saved`, the cookies the transfer ended with,
+/// then those of the `-b` files that `saved` does not replace (same
+/// domain, path and name). `engine_split` keeps file lines a later inline
+/// `-b` overrides out of the cookie engine `saved` comes from, so without
+/// this `-b jar -b name=v -c jar` would drop them from the jar.
+pub fn carry_over(saved: Vec<String>, sources: &[CookieSource]) -> std::io::Result<Vec<String>> {
+    let key = |fields: &[&str]| {
+        let domain = fields[0].trim_start_matches('.').to_ascii_lowercase();
+        (domain, fields[2].to_string(), fields[5].to_string())
+    };
+    let mut seen: HashSet<_> = saved
+        .iter()
+        .filter_map(|line| cookie_line(line).map(|line| line.split('\t').collect::<Vec<_>>()))
+        .filter(|fields| fields.len() == 7)
+        .map(|fields| key(&fields))
+        .collect();
+    let mut lines = saved;
+    for source in sources {
+        let CookieSource::File(path) = source else {
+            continue;
+        };
+        for line in read_cookie_file(path)?.unwrap_or_default().lines() {
+            if let Some(fields) = live_fields(line)
+                && seen.insert(key(&fields))
+            {
+                lines.push(line.to_string());
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Format cookies as a `Cookie` header value.
+pub fn cookie_header(cookies: &[(String, String)]) -> String {
+    cookies
+        .iter()
+        .map(|(n, v)| format!("{n}={v}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, removed when dropped.
+    struct Scratch(std::path::PathBuf);
+
+    impl Scratch {
+        fn new(tag: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rustcurl-cookie-{tag}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Scratch(dir)
+        }
+
+        fn jar(&self, content: &str) -> String {
+            let path = self.0.join("cookies.txt");
+            std::fs::write(&path, content).unwrap();
+            path.display().to_string()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parse_source_kind() {
+        assert_eq!(CookieSource::parse("a=1"), CookieSource::Inline("a=1".into()));
+        assert_eq!(
+            CookieSource::parse("/tmp/cookies.txt"),
+            CookieSource::File("/tmp/cookies.txt".into())
+        );
+    }
+
+    #[test]
+    fn inline_pairs() {
+        let c = parse_inline("a=1; b = 2 ;; =x");
+        assert_eq!(c, vec![("a".into(), "1".into()), ("b".into(), "2".into())]);
+    }
+
+    #[test]
+    fn cookie_file_domain_filtering() {
+        let content = "# Netscape HTTP Cookie File\n\
+            .example.com\tTRUE\t/\tFALSE\t0\tsid\tabc\n\
+            #HttpOnly_api.example.com\tFALSE\t/\tTRUE\t0\ttok\txyz\n\
+            other.org\tFALSE\t/\tFALSE\t0\tnope\t1\n\
+            malformed line\n";
+        let c = parse_cookie_file(content, "api.example.com", "/", true);
+        assert_eq!(c, vec![("sid".into(), "abc".into()), ("tok".into(), "xyz".into())]);
+        assert_eq!(malformed_lines(content), [5]);
+        // Secure cookies stay off plain http
+        assert_eq!(parse_cookie_file(content, "api.example.com", "/", false), vec![("sid".into(), "abc".into())]);
+    }
+
+    #[test]
+    fn cookie_file_tailmatch_and_path() {
+        let content = "example.com\tFALSE\t/\tFALSE\t0\thost\t1\n\
+            .example.com\tTRUE\t/admin\tFALSE\t0\tadmin\t1\n";
+        assert_eq!(parse_cookie_file(content, "example.com", "/", false), vec![("host".into(), "1".into())]);
+        // Without tailmatch a cookie stays on its own host
+        assert!(parse_cookie_file(content, "api.example.com", "/", false).is_empty());
+        for path in ["/admin", "/admin/", "/admin/users"] {
+            assert_eq!(parse_cookie_file(content, "api.example.com", path, false), vec![("admin".into(), "1".into())]);
+        }
+        assert!(parse_cookie_file(content, "api.example.com", "/administrator", false).is_empty());
+        assert!(path_matches("", "/"));
+        assert!(path_matches("/a/b", "/a/"));
+    }
+
+    #[test]
+    fn expired_cookies_dropped() {
+        let content = "x.com\tFALSE\t/\tFALSE\t1\told\t1\nx.com\tFALSE\t/\tFALSE\t99999999999\tnew\t2\n";
+        assert_eq!(parse_cookie_file(content, "x.com", "/", false), vec![("new".into(), "2".into())]);
+    }
+
+    #[test]
+    fn later_source_overrides_earlier() {
+        let scratch = Scratch::new("merge");
+        let jar = scratch.jar("x.com\tFALSE\t/\tFALSE\t0\tsid\tfromfile\nx.com\tFALSE\t/\tFALSE\t0\tkeep\t1\n");
+        let sources = vec![
+            CookieSource::Inline("sid=first; lang=en".into()),
+            CookieSource::File(jar),
+            CookieSource::Inline("lang=fr".into()),
+        ];
+        let merged = merge_cookies(&sources, "https://x.com/").unwrap();
+        assert_eq!(cookie_header(&merged), "sid=fromfile; lang=fr; keep=1");
+    }
+
+    #[test]
+    fn engine_split_keeps_later_wins() {
+        let scratch = Scratch::new("split");
+        let jar = scratch.jar("x.com\tFALSE\t/\tFALSE\t0\tsid\tfromfile\nx.com\tFALSE\t/\tFALSE\t0\tkeep\t1\n");
+        let sources = vec![
+            CookieSource::Inline("sid=first; lang=en".into()),
+            CookieSource::File(jar.clone()),
+            CookieSource::Inline("lang=fr".into()),
+        ];
+        let split = engine_split(&sources, "https://x.com/").unwrap();
+        assert_eq!(split.lines, ["x.com\tFALSE\t/\tFALSE\t0\tsid\tfromfile", "x.com\tFALSE\t/\tFALSE\t0\tkeep\t1"]);
+        assert_eq!(cookie_header(&split.inline), "lang=fr");
+
+        let sources = vec![CookieSource::File(jar.clone()), CookieSource::Inline("sid=inline".into())];
+        let split = engine_split(&sources, "https://x.com/").unwrap();
+        assert_eq!(split.lines, ["x.com\tFALSE\t/\tFALSE\t0\tkeep\t1"]);
+        assert_eq!(cookie_header(&split.inline), "sid=inline");
+
+        // A later file only overrides where its cookie would be sent
+        let sources = vec![CookieSource::Inline("sid=inline".into()), CookieSource::File(jar)];
+        let split = engine_split(&sources, "https://other.org/").unwrap();
+        assert_eq!(cookie_header(&split.inline), "sid=inline");
+    }
+
+    #[test]
+    fn missing_file_adds_nothing() {
+        let sources = vec![CookieSource::File("/nonexistent/rustcurl-jar".into()), CookieSource::Inline("a=1".into())];
+        assert_eq!(cookie_header(&merge_cookies(&sources, "https://x.com/").unwrap()), "a=1");
+        let scratch = Scratch::new("unreadable");
+        let sources = vec![CookieSource::File(scratch.0.display().to_string())];
+        assert!(merge_cookies(&sources, "https://x.com/").is_err());
+    }
+
+    #[test]
+    fn jar_keeps_file_cookies_not_replaced() {
+        let scratch = Scratch::new("carry");
+        let jar = scratch.jar(concat!(
+            "# Netscape HTTP Cookie File\n",
+            ".x.com\tTRUE\t/\tFALSE\t0\tsid\told\n",
+            "x.com\tFALSE\t/\tFALSE\t0\tkeep\t1\n",
+            "x.com\tFALSE\t/\tFALSE\t1\tgone\t1\n",
+        ));
+        let saved = vec!["x.com\tFALSE\t/\tFALSE\t0\tsid\tnew".to_string()];
+        let lines = carry_over(saved, &[CookieSource::File(jar), CookieSource::Inline("a=1".into())]).unwrap();
+        assert_eq!(lines, ["x.com\tFALSE\t/\tFALSE\t0\tsid\tnew", "x.com\tFALSE\t/\tFALSE\t0\tkeep\t1"]);
+    }
+}
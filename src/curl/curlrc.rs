@@ -0,0 +1,181 @@
+// src/curl/curlrc.rs
+
+//! The user's `.curlrc`, read at startup like curl does so proxy and CA
+//! settings kept there apply to rustcurl too. Its options go before the
+//! command line's, which can override them; `-q`/`--no-curlrc` as the
+//! first argument skips the file.
+
+use std::path::{Path, PathBuf};
+
+use super::env::EnvSource;
+
+/// First-argument flags that skip the file.
+pub const OPT_OUT: [&str; 2] = ["-q", "--no-curlrc"];
+
+/// The file to read, as curl looks for it: `.curlrc` in `$CURL_HOME`,
+/// `curlrc` in `$XDG_CONFIG_HOME`, then `.curlrc` in `$HOME` (on Windows
+/// also `_curlrc`, and `%USERPROFILE%` and `%APPDATA%`).
+pub fn find(env: &EnvSource) -> Option<PathBuf> {
+    let names: &[&str] = if cfg!(windows) { &[".curlrc", "_curlrc"] } else { &[".curlrc"] };
+    let mut candidates = Vec::new();
+    let mut add = |var: &str, names: &[&str]| {
+        if let Some(dir) = env.get(var).filter(|d| !d.is_empty()) {
+            candidates.extend(names.iter().map(|name| Path::new(&dir).join(name)));
+        }
+    };
+    add("CURL_HOME", names);
+    add("XDG_CONFIG_HOME", &["curlrc"]);
+    add("HOME", names);
+    if cfg!(windows) {
+        add("USERPROFILE", names);
+        add("APPDATA", names);
+    }
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+/// `args` with the options from `rc` (a curlrc's text) put in front,
+/// after the `relay` command word if there is one.
+pub fn prepend(rc: &str, args: &[String]) -> Result<Vec<String>, String> {
+    let options = parse(rc)?;
+    let at = usize::from(args.first().is_some_and(|a| a == "relay"));
+    let mut combined = args[..at].to_vec();
+    combined.extend(options);
+    combined.extend_from_slice(&args[at..]);
+    Ok(combined)
+}
+
+/// Whether the command line asks for the file to be skipped.
+pub fn skipped(args: &[String]) -> bool {
+    let first = match args.first() {
+        Some(command) if command == "relay" => args.get(1),
+        first => first,
+    };
+    first.is_some_and(|a| OPT_OUT.contains(&a.as_str()))
+}
+
+/// The arguments a curlrc's lines stand for. Each line holds one option,
+/// its leading `--` optional, and maybe a value after whitespace, `=` or
+/// `:`; a value with spaces is double-quoted, with `\"`, `\\`, `\t`, `\n`,
+/// `\r` and `\v` escapes. Blank lines and `#` comments are skipped.
+pub fn parse(text: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let end = line.find(|c: char| c.is_whitespace() || c == '=' || c == ':').unwrap_or(line.len());
+        let (option, rest) = line.split_at(end);
+        args.push(if option.starts_with('-') { option.to_string() } else { format!("--{option}") });
+
+        let rest = rest.trim_start();
+        let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest).trim_start();
+        if rest.is_empty() {
+            continue;
+        }
+        let value = match rest.strip_prefix('"') {
+            Some(quoted) => unquote(quoted).map_err(|e| format!("line {}: {e}", n + 1))?,
+            None => rest.split_whitespace().next().unwrap_or_default().to_string(),
+        };
+        args.push(value);
+    }
+    Ok(args)
+}
+
+/// A quoted value up to its closing quote, escapes resolved.
+fn unquote(quoted: &str) -> Result<String, String> {
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(value),
+            '\\' => match chars.next() {
+                Some('t') => value.push('\t'),
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('v') => value.push('\x0b'),
+                Some(other) => value.push(other),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+    Err("unterminated quote".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(strs: &[&str]) -> Vec<String> {
+        strs.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_curl_syntax() {
+        let rc = concat!(
+            "# corporate defaults\n",
+            "\n",
+            "proxy = http://proxy.corp:3128\n",
+            "  --cacert: /etc/ssl/corp.pem\n",
+            "noproxy localhost,.corp\n",
+            "-k\n",
+            "proxy-negotiate\n",
+            "user-agent = \"corp \\\"tool\\\"\\t1\"\n",
+            "url=http://x.com:8080/a trailing\n",
+        );
+        assert_eq!(
+            parse(rc).unwrap(),
+            strings(&[
+                "--proxy",
+                "http://proxy.corp:3128",
+                "--cacert",
+                "/etc/ssl/corp.pem",
+                "--noproxy",
+                "localhost,.corp",
+                "-k",
+                "--proxy-negotiate",
+                "--user-agent",
+                "corp \"tool\"\t1",
+                "--url",
+                "http://x.com:8080/a",
+            ])
+        );
+        assert_eq!(parse("header = \"X-A: 1").unwrap_err(), "line 1: unterminated quote");
+    }
+
+    #[test]
+    fn prepends_before_command_line() {
+        let rc = "proxy = http://p:1\n";
+        assert_eq!(
+            prepend(rc, &strings(&["-x", "http://q:2", "https://x.com"])).unwrap(),
+            strings(&["--proxy", "http://p:1", "-x", "http://q:2", "https://x.com"])
+        );
+        assert_eq!(
+            prepend(rc, &strings(&["relay", "--listen", "127.0.0.1:0"])).unwrap(),
+            strings(&["relay", "--proxy", "http://p:1", "--listen", "127.0.0.1:0"])
+        );
+        assert!(skipped(&strings(&["-q", "https://x.com"])));
+        assert!(skipped(&strings(&["relay", "--no-curlrc"])));
+        assert!(!skipped(&strings(&["https://x.com", "-q"])));
+    }
+
+    #[test]
+    fn found_in_curl_home_before_home() {
+        let root = std::env::temp_dir().join(format!("rustcurl-curlrc-{}", std::process::id()));
+        let (curl_home, home) = (root.join("curl"), root.join("home"));
+        std::fs::create_dir_all(&curl_home).unwrap();
+        std::fs::create_dir_all(&home).unwrap();
+        std::fs::write(home.join(".curlrc"), "").unwrap();
+        let env = EnvSource::from_pairs([
+            ("CURL_HOME", curl_home.to_str().unwrap()),
+            ("HOME", home.to_str().unwrap()),
+        ]);
+        assert_eq!(find(&env), Some(home.join(".curlrc")));
+
+        std::fs::write(curl_home.join(".curlrc"), "").unwrap();
+        assert_eq!(find(&env), Some(curl_home.join(".curlrc")));
+        assert_eq!(find(&EnvSource::Disabled), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,329 @@
+// src/curl/diagnose.rs
+
+//! `--diagnose`: when a request cannot connect, retrace the way there one
+//! step at a time (proxy, DNS, TCP to each address, TLS) and report what
+//! worked, so a vague "connection failed" becomes something a helpdesk
+//! ticket can act on. Through a proxy, the time to reach the proxy is
+//! split from the time its `CONNECT` to the origin takes, to show which
+//! leg is slow.
+
+use std::fmt;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use super::config::{Method, RequestConfig};
+use super::error::RequestError;
+use super::relay::Tunnel;
+use super::request::resolve_proxy;
+use super::response::TransferSummary;
+use super::url;
+
+/// Time allowed for each connect when `--connect-timeout` is not given.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One step of the way and how it went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// `url`, `route`, `proxy`, `dns`, `tcp`, `tunnel`, `tls` or `http`.
+    pub step: &'static str,
+    pub target: String,
+    /// What was found, or why the step failed.
+    pub result: Result<String, String>,
+    /// `None` for steps that were not timed, e.g. skipped ones.
+    pub elapsed: Option<Duration>,
+}
+
+impl Check {
+    fn timed(step: &'static str, target: String, clock: Instant, result: Result<String, String>) -> Check {
+        Check {
+            step,
+            target,
+            result,
+            elapsed: Some(clock.elapsed()),
+        }
+    }
+
+    pub fn failed(step: &'static str, target: &str, message: String) -> Check {
+        Check {
+            step,
+            target: target.to_string(),
+            result: Err(message),
+            elapsed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub url: String,
+    pub checks: Vec<Check>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Diagnosis for {}:", self.url)?;
+        for check in &self.checks {
+            let outcome = match check.result {
+                Ok(ref found) => found.clone(),
+                Err(ref message) => format!("FAILED: {message}"),
+            };
+            write!(f, "  {:<7}{:<32}{outcome}", check.step, check.target)?;
+            match check.elapsed {
+                Some(elapsed) => writeln!(f, " ({} ms)", elapsed.as_millis())?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The proxy, DNS and TCP checks for `config.url`. Through a proxy only
+/// the proxy itself is checked, since it resolves and connects for us.
+pub fn network(config: &RequestConfig) -> Vec<Check> {
+    let parts = match url::parse(&config.url) {
+        Ok(parts) => parts,
+        Err(message) => return vec![Check::failed("url", &config.url, message)],
+    };
+    let timeout = config.connect_timeout.unwrap_or(DEFAULT_TIMEOUT);
+    if let Some(proxy) = resolve_proxy(config).filter(|p| !p.is_empty()) {
+        return vec![
+            check_proxy(&proxy, timeout),
+            Check {
+                step: "dns",
+                target: parts.host.to_string(),
+                result: Ok("left to the proxy".to_string()),
+                elapsed: None,
+            },
+        ];
+    }
+    let port = parts.port.unwrap_or_else(|| default_port(parts.scheme));
+    let (dns, addrs) = resolve(parts.host, port);
+    let mut checks = vec![dns];
+    checks.extend(addrs.iter().map(|addr| connect(*addr, timeout)));
+    checks
+}
+
+fn default_port(scheme: Option<&str>) -> u16 {
+    match scheme.map(str::to_ascii_lowercase).as_deref() {
+        Some("https") | Some("wss") => 443,
+        _ => 80,
+    }
+}
+
+/// `host:port` to `CONNECT` to for `url` when it goes through a proxy
+/// that way (https, or anything with `-p`), or `None`.
+pub fn tunnel_authority(url: &str, proxy_tunnel: bool) -> Option<String> {
+    let parts = url::parse(url).ok()?;
+    if !proxy_tunnel && !parts.scheme.is_some_and(|s| s.eq_ignore_ascii_case("https")) {
+        return None;
+    }
+    let port = parts.port.unwrap_or_else(|| default_port(parts.scheme));
+    Some(if parts.host.contains(':') {
+        format!("[{}]:{port}", parts.host)
+    } else {
+        format!("{}:{port}", parts.host)
+    })
+}
+
+/// The `CONNECT` through the proxy, split into the time to reach the
+/// proxy (`to_proxy`, from its own check) and the rest, which the proxy
+/// spent reaching the origin.
+pub fn tunnel_check(
+    authority: &str,
+    to_proxy: Option<Duration>,
+    result: Result<Tunnel, RequestError>,
+    elapsed: Duration,
+) -> Check {
+    let result = match (result, to_proxy) {
+        (Ok(_), Some(to_proxy)) => Ok(format!(
+            "CONNECT answered: {} ms to the proxy, {} ms beyond it",
+            to_proxy.as_millis(),
+            elapsed.saturating_sub(to_proxy).as_millis()
+        )),
+        (Ok(_), None) => Ok("CONNECT answered".to_string()),
+        (Err(e), _) => Err(e.to_string()),
+    };
+    Check {
+        step: "tunnel",
+        target: authority.to_string(),
+        result,
+        elapsed: Some(elapsed),
+    }
+}
+
+/// Whether the proxy (`scheme://host:port`) accepts a TCP connection.
+pub fn check_proxy(proxy: &str, timeout: Duration) -> Check {
+    let clock = Instant::now();
+    let target = proxy.to_string();
+    let parts = match url::parse(proxy) {
+        Ok(parts) => parts,
+        Err(message) => return Check::timed("proxy", target, clock, Err(message)),
+    };
+    // libcurl's default proxy port, except for HTTPS proxies
+    let port = parts.port.unwrap_or(if parts.scheme == Some("https") { 443 } else { 1080 });
+    let addrs = match (parts.host, port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect::<Vec<_>>(),
+        Err(e) => return Check::timed("proxy", target, clock, Err(format!("cannot resolve {}: {e}", parts.host))),
+    };
+    let mut last = format!("{} resolved to no addresses", parts.host);
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => return Check::timed("proxy", target, clock, Ok(format!("reachable at {addr}"))),
+            Err(e) => last = format!("{addr}: {e}"),
+        }
+    }
+    Check::timed("proxy", target, clock, Err(last))
+}
+
+/// Look `host` up with the system resolver.
+pub fn resolve(host: &str, port: u16) -> (Check, Vec<SocketAddr>) {
+    let clock = Instant::now();
+    match (host, port).to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs: Vec<SocketAddr> = addrs.collect();
+            let found = if addrs.is_empty() {
+                Err("no addresses".to_string())
+            } else {
+                Ok(addrs.iter().map(|a| a.ip().to_string()).collect::<Vec<_>>().join(", "))
+            };
+            (Check::timed("dns", host.to_string(), clock, found), addrs)
+        }
+        Err(e) => (Check::timed("dns", host.to_string(), clock, Err(e.to_string())), Vec::new()),
+    }
+}
+
+pub fn connect(addr: SocketAddr, timeout: Duration) -> Check {
+    let clock = Instant::now();
+    let result = TcpStream::connect_timeout(&addr, timeout)
+        .map(|_| "connected".to_string())
+        .map_err(|e| e.to_string());
+    Check::timed("tcp", addr.to_string(), clock, result)
+}
+
+/// `config` reduced to a bodiless HEAD that is tried once, for checking
+/// TLS and the first HTTP exchange with the same proxy, auth and TLS
+/// options.
+pub fn probe_config(config: &RequestConfig) -> RequestConfig {
+    let mut probe = config.clone().method(Method::Head).retry(0);
+    probe.data = None;
+    probe.form_fields.clear();
+    probe.output = None;
+    probe.remote_name = false;
+    if probe.max_time.is_none() {
+        probe = probe.max_time(config.connect_timeout.unwrap_or(DEFAULT_TIMEOUT) * 3);
+    }
+    probe
+}
+
+/// The check for the probe's outcome: `tls` for https URLs when the
+/// handshake is what succeeded or failed, `http` otherwise.
+pub fn request_check(
+    config: &RequestConfig,
+    result: Result<TransferSummary, RequestError>,
+    elapsed: Duration,
+) -> Check {
+    let https = url::parse(&config.url).is_ok_and(|p| p.scheme.is_some_and(|s| s.eq_ignore_ascii_case("https")));
+    let (step, result) = match result {
+        Ok(summary) if https => ("tls", Ok(format!("handshake ok, HEAD answered {}", summary.status_code))),
+        Ok(summary) => ("http", Ok(format!("HEAD answered {}", summary.status_code))),
+        Err(e @ (RequestError::TlsHandshake { .. } | RequestError::TlsVerify { .. })) => ("tls", Err(e.to_string())),
+        Err(e) => ("http", Err(e.to_string())),
+    };
+    Check {
+        step,
+        target: config.url.clone(),
+        result,
+        elapsed: Some(elapsed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn checks_each_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config = RequestConfig::new(&format!("http://127.0.0.1:{port}/")).noproxy("*");
+        let checks = network(&config);
+        assert_eq!(checks.iter().map(|c| c.step).collect::<Vec<_>>(), ["dns", "tcp"]);
+        assert_eq!(checks[1].result, Ok("connected".to_string()));
+
+        drop(listener);
+        let checks = network(&config);
+        assert!(checks[1].result.is_err());
+    }
+
+    #[test]
+    fn proxy_checked_instead_of_origin() {
+        let config = RequestConfig::new("https://intranet.invalid/").proxy("http://127.0.0.1:1");
+        let checks = network(&config);
+        assert_eq!(checks[0].step, "proxy");
+        assert!(checks[0].result.is_err());
+        assert_eq!(checks[1].result, Ok("left to the proxy".to_string()));
+    }
+
+    #[test]
+    fn tunnel_split_between_legs() {
+        assert_eq!(tunnel_authority("https://x.com/a", false).as_deref(), Some("x.com:443"));
+        assert_eq!(tunnel_authority("https://[::1]:8443/", false).as_deref(), Some("[::1]:8443"));
+        assert_eq!(tunnel_authority("http://x.com/", false), None);
+        assert_eq!(tunnel_authority("http://x.com/", true).as_deref(), Some("x.com:80"));
+
+        let err = RequestError::ProxyAuth { message: "407".into() };
+        let check = tunnel_check("x.com:443", Some(Duration::from_millis(5)), Err(err), Duration::from_millis(9));
+        assert_eq!(check.step, "tunnel");
+        assert!(check.result.is_err());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let check = tunnel_check(
+            "x.com:443",
+            Some(Duration::from_millis(12)),
+            Ok(Tunnel::new(stream, ())),
+            Duration::from_millis(340),
+        );
+        assert_eq!(check.result, Ok("CONNECT answered: 12 ms to the proxy, 328 ms beyond it".to_string()));
+    }
+
+    #[test]
+    fn unparsable_url_reported() {
+        let checks = network(&RequestConfig::new("http://:80/"));
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].step, "url");
+    }
+
+    #[test]
+    fn report_lists_checks() {
+        let report = Report {
+            url: "https://x.com/".into(),
+            checks: vec![
+                Check {
+                    step: "dns",
+                    target: "x.com".into(),
+                    result: Ok("1.2.3.4".into()),
+                    elapsed: Some(Duration::from_millis(8)),
+                },
+                Check::failed("tcp", "1.2.3.4:443", "Connection refused".into()),
+            ],
+        };
+        let text = report.to_string();
+        assert!(text.starts_with("Diagnosis for https://x.com/:\n"));
+        assert!(text.contains("  dns    x.com"));
+        assert!(text.contains("1.2.3.4 (8 ms)\n"));
+        assert!(text.contains("FAILED: Connection refused\n"));
+    }
+
+    #[test]
+    fn probe_is_a_single_head() {
+        let config = RequestConfig::new("https://x.com/").data("a=1").retry(3).output("out.bin");
+        let probe = probe_config(&config);
+        assert_eq!(probe.method, Method::Head);
+        assert!(probe.body().is_none());
+        assert_eq!(probe.retry, 0);
+        assert!(probe.output.is_none());
+        assert!(probe.max_time.is_some());
+    }
+}
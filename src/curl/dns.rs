@@ -0,0 +1,295 @@
+// src/curl/dns.rs
+
+//! In-process DNS cache shared by every request in the process. Lookups
+//! go through the system resolver once per host and port, and the result
+//! is pinned into the backend (`CURLOPT_RESOLVE` / reqwest's
+//! `resolve_to_addrs`) so repeated requests skip resolution. The system
+//! resolver does not report record TTLs, so entries live for
+//! `--dns-cache-timeout` (60s by default, like curl). `--dns-timeout`
+//! is enforced on that same lookup, so the backend never resolves the
+//! host a second time.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use super::config::RequestConfig;
+use super::error::{RequestError, TimeoutPhase};
+
+/// How long entries are reused when `--dns-cache-timeout` is not given.
+pub const DEFAULT_DNS_CACHE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Addresses for a host and port, with the time they were resolved.
+type Entries = HashMap<(String, u16), (Vec<SocketAddr>, Instant)>;
+
+#[derive(Default)]
+pub struct DnsCache {
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl DnsCache {
+    /// Cached addresses younger than `ttl`, counting the hit or miss.
+    pub fn get(&self, host: &str, port: u16, ttl: Duration) -> Option<Vec<SocketAddr>> {
+        let found = self.entries.lock().ok().and_then(|entries| {
+            entries
+                .get(&(host.to_string(), port))
+                .filter(|(_, at)| at.elapsed() < ttl)
+                .map(|(addrs, _)| addrs.clone())
+        });
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    pub fn insert(&self, host: &str, port: u16, addrs: Vec<SocketAddr>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert((host.to_string(), port), (addrs, Instant::now()));
+        }
+    }
+
+    /// `(hits, misses)` since the process started.
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+
+    #[allow(dead_code)]
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+static CACHE: LazyLock<DnsCache> = LazyLock::new(DnsCache::default);
+
+/// The process-wide cache.
+pub fn cache() -> &'static DnsCache {
+    &CACHE
+}
+
+/// Addresses rustcurl resolved for the request's host, to be pinned into
+/// the backend so it does not resolve again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pinned {
+    pub host: String,
+    pub port: u16,
+    pub addrs: Vec<SocketAddr>,
+}
+
+impl Pinned {
+    /// `host:port:addr,addr` in `--resolve` syntax.
+    pub fn resolve_entry(&self) -> String {
+        let addrs = self
+            .addrs
+            .iter()
+            .map(|a| match a.ip() {
+                IpAddr::V6(ip) => format!("[{ip}]"),
+                IpAddr::V4(ip) => ip.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}:{}:{addrs}", self.host, self.port)
+    }
+}
+
+fn default_port(scheme: Option<&str>) -> Option<u16> {
+    match scheme.map(|s| s.to_ascii_lowercase()).as_deref() {
+        Some("http") | Some("ws") | None => Some(80),
+        Some("https") | Some("wss") => Some(443),
+        _ => None,
+    }
+}
+
+/// A system resolver call running on its own thread, shared by every
+/// request waiting on the same host and port.
+#[derive(Default)]
+struct InFlight {
+    /// The addresses, empty if the lookup failed; `None` until it ends.
+    result: Mutex<Option<Vec<SocketAddr>>>,
+    done: Condvar,
+}
+
+/// Lookups under `--dns-timeout`. The system resolver cannot be
+/// cancelled, so a lookup that times out runs on; later requests for the
+/// same host wait on it instead of starting another.
+static IN_FLIGHT: LazyLock<Mutex<Lookups>> = LazyLock::new(Default::default);
+
+type Lookups = HashMap<(String, u16), Arc<InFlight>>;
+
+fn resolve(host: &str, port: u16) -> Vec<SocketAddr> {
+    (host, port).to_socket_addrs().map(|a| a.collect()).unwrap_or_default()
+}
+
+/// Resolve `host`, giving up after `timeout` when one is set.
+fn lookup(host: &str, port: u16, timeout: Option<Duration>) -> Result<Vec<SocketAddr>, RequestError> {
+    let addrs = match timeout {
+        None => resolve(host, port),
+        Some(timeout) => {
+            let lookup = start(host, port);
+            let result = lookup.result.lock().unwrap_or_else(|e| e.into_inner());
+            let (result, _) = lookup
+                .done
+                .wait_timeout_while(result, timeout, |result| result.is_none())
+                .unwrap_or_else(|e| e.into_inner());
+            result.clone().ok_or_else(|| RequestError::Timeout {
+                phase: TimeoutPhase::Dns,
+                message: format!("lookup of {host} took longer than {timeout:?}"),
+            })?
+        }
+    };
+    if addrs.is_empty() {
+        return Err(RequestError::Dns {
+            host: host.to_string(),
+            proxy: false,
+        });
+    }
+    Ok(addrs)
+}
+
+/// The running lookup of `host:port`, started if there is none.
+fn start(host: &str, port: u16) -> Arc<InFlight> {
+    let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+    let key = (host.to_string(), port);
+    if let Some(lookup) = in_flight.get(&key) {
+        return Arc::clone(lookup);
+    }
+    let lookup = Arc::new(InFlight::default());
+    in_flight.insert(key.clone(), Arc::clone(&lookup));
+    let shared = Arc::clone(&lookup);
+    std::thread::spawn(move || {
+        let addrs = resolve(&key.0, key.1);
+        IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        *shared.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(addrs);
+        shared.done.notify_all();
+    });
+    lookup
+}
+
+/// Resolve the request's host through the cache, enforcing
+/// `--dns-timeout`. Returns `None` when there is nothing to pin: a proxy
+/// resolves names for us, `--resolve` already covers the host, the host is
+/// an IP literal, the scheme's port is unknown, or caching is off and no
+/// timeout needs enforcing.
+pub fn pre_resolve(config: &RequestConfig) -> Result<Option<Pinned>, RequestError> {
+    if super::request::resolve_proxy(config).is_some() {
+        return Ok(None);
+    }
+    let Ok(parts) = super::url::parse(&config.url) else {
+        return Ok(None);
+    };
+    let host = parts.host;
+    if host.parse::<IpAddr>().is_ok() || config.resolve.iter().any(|r| r.starts_with(&format!("{host}:"))) {
+        return Ok(None);
+    }
+    let Some(port) = parts.port.or_else(|| default_port(parts.scheme)) else {
+        return Ok(None);
+    };
+    if !config.dns_cache {
+        // Nothing to cache, but a lookup bounded by the timeout is pinned
+        // all the same so the backend does not repeat it unbounded
+        if config.dns_timeout.is_none() {
+            return Ok(None);
+        }
+        return Ok(Some(Pinned {
+            host: host.to_string(),
+            port,
+            addrs: lookup(host, port, config.dns_timeout)?,
+        }));
+    }
+
+    if let Some(addrs) = cache().get(host, port, config.dns_cache_timeout) {
+        let (hits, misses) = cache().stats();
+        config.note(format_args!("DNS cache hit for {host}:{port} ({hits} hits, {misses} misses)"));
+        return Ok(Some(Pinned {
+            host: host.to_string(),
+            port,
+            addrs,
+        }));
+    }
+    let addrs = lookup(host, port, config.dns_timeout)?;
+    cache().insert(host, port, addrs.clone());
+    let (hits, misses) = cache().stats();
+    config.note(format_args!("DNS cache miss for {host}:{port} ({hits} hits, {misses} misses)"));
+    Ok(Some(Pinned {
+        host: host.to_string(),
+        port,
+        addrs,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::env::EnvSource;
+
+    #[test]
+    fn skipped_for_resolve_entries() {
+        let cfg = RequestConfig::new("https://unresolvable.invalid/")
+            .dns_timeout(Duration::from_millis(1))
+            .add_resolve("unresolvable.invalid:443:127.0.0.1");
+        assert_eq!(pre_resolve(&cfg).unwrap(), None);
+    }
+
+    #[test]
+    fn skipped_for_ip_literal() {
+        let cfg = RequestConfig::new("http://127.0.0.1:1/").dns_timeout(Duration::from_secs(5));
+        assert_eq!(pre_resolve(&cfg).unwrap(), None);
+    }
+
+    #[test]
+    fn localhost_is_cached_and_pinned() {
+        let cfg = RequestConfig::new("http://localhost:8080/").env(EnvSource::Disabled);
+        let first = pre_resolve(&cfg).unwrap().expect("localhost resolves");
+        assert_eq!(first.port, 8080);
+        assert!(first.resolve_entry().starts_with("localhost:8080:"));
+        let (hits, _) = cache().stats();
+        let second = pre_resolve(&cfg).unwrap().unwrap();
+        assert_eq!(first.addrs, second.addrs);
+        assert!(cache().stats().0 > hits);
+    }
+
+    #[test]
+    fn no_dns_cache_pins_nothing() {
+        let cfg = RequestConfig::new("http://localhost/")
+            .env(EnvSource::Disabled)
+            .dns_cache(false);
+        assert_eq!(pre_resolve(&cfg).unwrap(), None);
+        // A --dns-timeout lookup is still pinned, not repeated by the backend
+        let (hits, misses) = cache().stats();
+        let pinned = pre_resolve(&cfg.dns_timeout(Duration::from_secs(5))).unwrap().expect("localhost resolves");
+        assert_eq!(pinned.port, 80);
+        assert_eq!(cache().stats(), (hits, misses));
+    }
+
+    #[test]
+    fn timed_lookups_share_one_resolver_call() {
+        let first = start("localhost", 1);
+        let second = start("localhost", 1);
+        assert!(Arc::ptr_eq(&first, &second) || first.result.lock().unwrap().is_some());
+        assert!(!lookup("localhost", 1, Some(Duration::from_secs(5))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn entries_expire() {
+        let cache = DnsCache::default();
+        let addr: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        cache.insert("h", 80, vec![addr]);
+        assert_eq!(cache.get("h", 80, Duration::from_secs(60)), Some(vec![addr]));
+        assert_eq!(cache.get("h", 80, Duration::ZERO), None);
+        assert_eq!(cache.stats(), (1, 1));
+    }
+
+    #[test]
+    fn ipv6_resolve_entry() {
+        let pinned = Pinned {
+            host: "x.com".into(),
+            port: 443,
+            addrs: vec!["[::1]:443".parse().unwrap(), "10.0.0.1:443".parse().unwrap()],
+        };
+        assert_eq!(pinned.resolve_entry(), "x.com:443:[::1],10.0.0.1");
+    }
+}
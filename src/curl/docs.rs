@@ -0,0 +1,332 @@
+// src/curl/docs.rs
+
+//! The option reference: one table behind `--help` and
+//! `rustcurl generate-docs man|markdown`, so the usage text, the man page
+//! and the markdown reference cannot drift apart. Add an option here when
+//! adding it to `args.rs`.
+
+use super::presets;
+
+/// Command lines after `rustcurl`, the first being the default form.
+pub const COMMANDS: &[&str] = &[
+    "[OPTIONS] <URL>...",
+    "relay --listen <ADDR> [OPTIONS]",
+    "session stats <NAME>",
+    "trust list|remove <HOST:PORT>",
+    "config show [OPTIONS] <URL>",
+    "self-update [--channel stable|nightly] [--verify-key KEY | --allow-unsigned] [--force] [--check]",
+    "generate-docs man|markdown",
+    "capabilities [--json]",
+];
+
+/// Options and their help text. A `\n` in the help breaks the line in
+/// `--help`; elsewhere it is a space. `{presets}` is the list of
+/// `--user-agent-preset` names.
+pub const OPTIONS: &[(&str, &str)] = &[
+    ("-X, --request <METHOD>", "HTTP method (GET, POST, PUT, DELETE, HEAD, PATCH, OPTIONS)"),
+    ("-H, --header <HEADER>", "Add header (repeatable), e.g. -H \"Content-Type: application/json\""),
+    ("-H @FILE, --header-file <FILE>", "Add the headers in FILE, one per line (- for stdin)"),
+    (
+        "--secret <NAME=SRC>",
+        "Fetch a value for {{secret.NAME}} in the URL, headers, body and credentials;\n\
+         SRC is env:VAR, file:PATH, vault:PATH#FIELD or aws-sm:ID[#FIELD]",
+    ),
+    ("-d, --data <DATA>", "Request body data, @file to read a file (repeatable, joined with &)"),
+    ("-T, --upload-file <FILE>", "PUT FILE as the body, streamed from disk"),
+    ("--data-binary <DATA>", "Like -d, but @file is sent byte-for-byte"),
+    ("--data-raw <DATA>", "Like -d, but a leading @ is sent literally"),
+    ("-G, --get", "Send -d/--data-urlencode data as a GET query string"),
+    ("--data-urlencode <DATA>", "Like -d, percent-encoding content, name=content or name@file"),
+    ("-o, --output <FILE>", "Write response body to file (repeat for each URL; #1 for a glob value)"),
+    ("-O, --remote-name", "Write response body to a file named after the URL"),
+    ("-g, --globoff", "Take [] and {} in URLs literally instead of as globs"),
+    ("-J, --remote-header-name", "With -O, use the Content-Disposition file name"),
+    ("--output-dir <DIR>", "Directory for -o files and --extract"),
+    ("--extract", "Unpack a saved tar, tar.gz or zip archive"),
+    ("--extract-overwrite", "Let --extract replace existing files"),
+    ("--accept-push <DIR|->", "Save HTTP/2 server pushes under DIR (- to only list them)"),
+    ("--verify-sig <FILE|URL>", "Detached minisign or OpenPGP signature to check the download against"),
+    ("--verify-key <KEY>", "Minisign public key (or .pub file) or OpenPGP keyring for --verify-sig"),
+    ("--provenance", "Write <file>.provenance.json (source, redirects, checksum, TLS cert)"),
+    ("-r, --range <RANGE>", "Request byte ranges, e.g. 0-99 or 0-99,200-299"),
+    ("--no-mime-sniff", "Save files under the URL's name without inferring the type"),
+    ("--crlf", "Convert LF to CRLF in the request body"),
+    ("--content-digest <ALG>", "Send an RFC 9530 Content-Digest of the body: sha-256, sha-512 or both"),
+    ("--content-md5", "Send a Content-MD5 of the body"),
+    ("--encrypt-jwe <KEY>", "Send the -d body as a JWE for a PEM RSA key or certificate, or an oct JWK"),
+    ("--decrypt-jwe <KEY>", "Decrypt a JWE response body with a PEM RSA key or an oct JWK"),
+    ("--normalize-eol <EOL>", "Rewrite text bodies to lf or crlf line endings"),
+    ("--output-charset <CS>", "Transcode text bodies to CS (utf-8, latin1, utf-16le, ...)"),
+    ("-I, --head", "Send HEAD request (show headers only)"),
+    ("-i, --include", "Print the status line and headers before the body"),
+    (
+        "-w, --write-out <FMT>",
+        "Print FMT after the transfer, e.g. '%{http_code} %{time_total}\\n' (@file reads it)",
+    ),
+    ("-s, --silent", "Silent mode (no notes on stderr)"),
+    ("-A, --user-agent <STR>", "Set User-Agent header"),
+    ("--accept <MIME>", "Set Accept, e.g. 'application/json' or 'text/html,*/*;q=0.8'"),
+    ("--accept-language <TAGS>", "Set Accept-Language, e.g. 'de-CH,de;q=0.9,en;q=0.5'"),
+    ("--user-agent-preset <NAME>", "Send a known User-Agent: {presets}"),
+    ("--http2", "Ask for HTTP/2: h2 over TLS, or an upgrade for http:// URLs"),
+    ("--http2-prior-knowledge", "Speak HTTP/2 from the start, also in clear text (h2c)"),
+    ("--h2-priority <WEIGHT>", "HTTP/2 stream weight 1-256 (default 16); -v also logs HTTP/2 frames"),
+    ("--impersonate <BROWSER>", "Look like chrome, firefox or safari (User-Agent, headers, ALPN)"),
+    ("-b, --cookie <FILE|DATA>", "Read cookies from file or \"name=value\" string (repeatable)"),
+    ("-c, --cookie-jar <FILE>", "Write cookies to file after request"),
+    ("--negotiate", "Enable Kerberos/SPNEGO authentication"),
+    ("--ntlm", "Enable NTLM authentication"),
+    ("-k, --insecure", "Ignore SSL certificate verification (asks first from a terminal; see --yes)"),
+    ("--cacert <PATH>", "Path to CA certificate bundle"),
+    ("-E, --cert <PATH>", "Client certificate for mutual TLS (PEM, or .p12/.pfx)"),
+    ("--cert-type <TYPE>", "PEM, DER or P12 (default: from the file name)"),
+    ("--key <PATH>", "Private key for --cert, if not in the same file"),
+    ("--key-type <TYPE>", "PEM or DER (default: from the file name)"),
+    ("--pass <PHRASE>", "Passphrase for the key or .p12 file"),
+    ("-u, --user <USER:PASS>", "Credentials (user:password)"),
+    ("-n, --netrc", "Take credentials -u leaves out from ~/.netrc"),
+    ("--netrc-optional", "Like -n, but a missing ~/.netrc is not an error"),
+    ("--netrc-file <FILE>", "Like -n, reading FILE instead of ~/.netrc"),
+    ("--bearer <TOKEN>", "Bearer token authentication"),
+    ("--oauth2-token-url <URL>", "On 401, refresh the --bearer token at URL and retry once"),
+    ("--oauth2-refresh-token <TOKEN>", "Refresh token (default: $RUSTCURL_REFRESH_TOKEN)"),
+    ("--oauth2-client-id <ID>", "Client ID sent with the refresh"),
+    ("--oauth2-client-secret <SECRET>", "Client secret sent with the refresh"),
+    ("--auth-preemptive", "Send Basic credentials with the first request (default)"),
+    ("--auth-on-challenge", "Send Basic credentials only after a 401 asks for them"),
+    ("--auth-chain <LIST>", "On 401, escalate through e.g. negotiate,ntlm,basic,bearer"),
+    ("--anyauth", "Use the strongest of negotiate, ntlm, digest, basic the server offers"),
+    ("--registry-auth", "On 401, fetch a container registry token (with -u if given) and retry"),
+    ("-x, --proxy <URL>", "Proxy URL: http://, https://, socks4://, socks4a://, socks5:// or socks5h://"),
+    ("--s3-region <REGION>", "Region for s3://BUCKET/KEY URLs (default: $AWS_REGION, us-east-1)"),
+    ("--s3-endpoint <URL>", "S3-compatible endpoint (default: $AWS_ENDPOINT_URL_S3, AWS)"),
+    ("--s3-path-style", "Put the bucket in the path, not the host name"),
+    ("--s3-part-size <SIZE>", "Multipart upload part size for -T to s3:// (default 8m, at least 5m)"),
+    (
+        "--presign <DUR>",
+        "Instead of sending, print a URL signed for DUR for the method and\n\
+         a gs://BUCKET/OBJECT or az://CONTAINER/BLOB URL",
+    ),
+    ("--preproxy <URL>", "SOCKS proxy to reach the -x proxy through, e.g. socks5://jump:1080"),
+    ("--proxy-pac <URL|FILE>", "Choose the proxy per URL with a PAC script's FindProxyForURL"),
+    ("--tor", "Route through local Tor (port 9050 or 9150) with a random browser User-Agent"),
+    ("--ssh-tunnel <DEST>", "Send the request through SSH jump host [user@]host[:port]"),
+    ("--proxy-user <USER:PASS>", "Proxy credentials"),
+    ("--proxy-header <HEADER|@FILE>", "Send a header to the proxy only, not to the server"),
+    ("--proxy-negotiate", "Enable Kerberos/SPNEGO proxy authentication"),
+    ("--proxy-ntlm", "Enable NTLM proxy authentication"),
+    ("--proxy-digest", "Enable Digest proxy authentication"),
+    ("--proxy-basic", "Send proxy credentials with Basic authentication only"),
+    ("--proxy-anyauth", "Use the strongest of negotiate, ntlm, digest, basic the proxy offers"),
+    ("-p, --proxytunnel", "Tunnel http:// URLs through the proxy with CONNECT too"),
+    ("--proxy-insecure", "Skip SSL verification for proxy connection"),
+    ("--proxy-cacert <PATH>", "CA certificate for proxy SSL verification"),
+    ("--noproxy <HOSTS>", "Comma-separated list of hosts to bypass proxy"),
+    ("--connect-timeout <DUR>", "Connection timeout (seconds, or 500ms, 2m, 1h)"),
+    ("--max-time <DUR>", "Maximum total time (seconds, or 500ms, 2m, 1h)"),
+    ("--read-timeout <DUR>", "Abort if no data is received for this long"),
+    ("--dns-timeout <DUR>", "Maximum time for host name resolution"),
+    ("--dns-cache-timeout <DUR>", "Reuse DNS lookups for this long (default: 60s)"),
+    ("--no-dns-cache", "Resolve host names on every request"),
+    ("--max-redirs <N>", "Maximum number of redirects"),
+    ("--retry <N>", "Retry transient failures up to N times (exit 75 if the last still fails)"),
+    ("--retry-delay <DUR>", "Wait this long between retries (default: backoff)"),
+    ("--retry-report", "Print a summary of all attempts to stderr"),
+    ("-L, --location", "Follow redirects (always enabled)"),
+    ("--ssl-no-revoke", "Disable certificate revocation checks"),
+    ("--tls-session-cache <FILE>", "Keep TLS sessions in FILE so later runs resume them"),
+    (
+        "--ech <MODE>",
+        "Encrypted Client Hello: grease, true (use HTTPS RR), hard (require it),\n\
+         ecl:<BASE64> or pn:<NAME> for an explicit config, false",
+    ),
+    ("--tls-early-data", "With --tls-session-cache, send GET/HEAD/OPTIONS as 0-RTT data"),
+    ("-1, --tlsv1", "Use TLS 1.0 or later (also --tlsv1.0, --tlsv1.1, --tlsv1.2, --tlsv1.3)"),
+    ("--tls-max <VERSION>", "Newest TLS version to offer: 1.0, 1.1, 1.2 or 1.3"),
+    ("--ciphers <LIST>", "Cipher list for TLS 1.2 and older (e.g. ECDHE-RSA-AES128-GCM-SHA256)"),
+    ("--tls13-ciphers <LIST>", "TLS 1.3 cipher suites (e.g. TLS_AES_256_GCM_SHA384)"),
+    ("--show-certs", "Print the server's certificate chain: subject, issuer, SANs, validity, SHA-256"),
+    ("--tofu", "Trust a server's key on first use and fail if it changes (see 'trust list')"),
+    ("--keylog <FILE>", "Append TLS secrets to FILE in NSS key log format, for Wireshark"),
+    ("--compressed", "Request compressed response"),
+    ("--ignore-content-length", "Ignore the Content-Length header; read until close"),
+    ("--accept-partial", "Treat a truncated body as success"),
+    ("--timing", "Show timing information"),
+    ("--assert-time-total-max <DUR>", "Fail (exit 3) if the transfer takes longer"),
+    ("--assert-ttfb-max <DUR>", "Fail (exit 3) if the first byte arrives later"),
+    ("--prom-out <FILE>", "Write probe metrics in Prometheus textfile format"),
+    ("--yes", "Skip the question -k and --proxy-insecure ask for remote hosts from a terminal"),
+    ("--print-config", "Print each effective setting and where it came from, then exit"),
+    ("--session <NAME>", "Add requests and bytes to NAME's totals (see 'session stats')"),
+    ("--watch <DUR>", "Repeat the request every DUR, one status line per attempt"),
+    ("--batch <FILE>", "Run one JSON request spec per line of FILE (- for stdin), one JSON result per line"),
+    (
+        "--collection <FILE>",
+        "Run the YAML request steps in FILE in depends_on order, independent ones in\n\
+         parallel, one JSON result per step (exit 1 if any step does not pass)",
+    ),
+    (
+        "--listen <ADDR>",
+        "With relay, serve as a forward proxy on ADDR (e.g. 127.0.0.1:9999),\n\
+         sending requests on with these options (-x, --proxy-negotiate, TLS)",
+    ),
+    (
+        "--listen-remote",
+        "With relay, allow a --listen address other than loopback; anyone who\n\
+         can reach it can send requests with your proxy credentials",
+    ),
+    ("--watch-diff", "With --watch, flag attempts whose body changed"),
+    ("--tail <DUR>", "Follow a growing file, fetching new bytes by range every DUR"),
+    ("--if-changed <FILE>", "Only print when the body's hash differs from FILE (exit 100 when no URL changed)"),
+    ("--capture <NAME=EXPR>", "Capture $.json.path or header:Name into --capture-file (repeatable)"),
+    ("--capture-file <FILE>", "Write captures as shell exports to FILE (- for stdout)"),
+    ("--notify-cmd <CMD>", "With --watch, run CMD when the status class changes"),
+    ("--notify-desktop", "With --watch, show a desktop notification on changes"),
+    ("--notify-debounce <N>", "Notify once a new status class holds for N attempts"),
+    ("--resolve <H:P:A>", "Resolve host:port to address (repeatable)"),
+    ("--message-format <FMT>", "Error output format: short, long (default), json"),
+    ("--seed <N>", "Seed randomized behavior (retry jitter) for reproducible runs"),
+    ("-v, --verbose", "Verbose output"),
+    ("--diagnose", "On connection failure, check proxy, DNS, TCP and TLS and print a report"),
+    ("--url <URL>", "URL to fetch, as an option (e.g. in .curlrc)"),
+    ("-:, --next", "Start options for the following URLs afresh (-v, -s and the like stay)"),
+    ("-q, --no-curlrc", "As the first option, do not read ~/.curlrc"),
+    ("-h, --help", "Show this help"),
+];
+
+/// Environment variables rustcurl reads.
+pub const ENVIRONMENT: &[(&str, &str)] = &[
+    ("RUSTCURL_USER", "Username fallback"),
+    ("RUSTCURL_PASSWORD", "Password fallback"),
+    ("HTTPS_PROXY", "HTTPS proxy URL"),
+    ("HTTP_PROXY", "HTTP proxy URL"),
+    ("ALL_PROXY", "Proxy for all protocols"),
+    ("NO_PROXY", "Hosts to bypass proxy"),
+    ("RUSTCURL_HOME", "Directory for all of rustcurl's own config, cache, data and state files"),
+    ("CURL_HOME", "Directory of the .curlrc to read (else XDG_CONFIG_HOME, HOME)"),
+    ("RUSTCURL_LANG", "Message language (en, es); falls back to LANG"),
+    ("SSLKEYLOGFILE", "Key log file for TLS secrets, as with --keylog (read by libcurl)"),
+];
+
+/// Column the help text starts at in `--help`.
+const HELP_COLUMN: usize = 27;
+
+fn help_text(help: &str) -> String {
+    help.replace("{presets}", &presets::names())
+}
+
+/// The `--help` text.
+pub fn usage() -> String {
+    let mut out = String::new();
+    for (i, command) in COMMANDS.iter().enumerate() {
+        let lead = if i == 0 { "Usage:" } else { "" };
+        out.push_str(&format!("{lead:6} rustcurl {command}\n"));
+    }
+    out.push_str("\nOptions:\n");
+    for (synopsis, help) in OPTIONS {
+        let help = help_text(help);
+        let mut lines = help.split('\n');
+        out.push_str(&format!("  {synopsis:width$} {}\n", lines.next().unwrap_or_default(), width = HELP_COLUMN - 3));
+        for line in lines {
+            out.push_str(&format!("{:HELP_COLUMN$}{line}\n", ""));
+        }
+    }
+    out.push_str("\nEnvironment variables:\n");
+    for (name, help) in ENVIRONMENT {
+        out.push_str(&format!("  {name:width$} {help}\n", width = HELP_COLUMN - 3));
+    }
+    out
+}
+
+/// A roff man page, section 1.
+pub fn man() -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let mut out = format!(".TH RUSTCURL 1 \"\" \"rustcurl {version}\" \"User Commands\"\n");
+    out.push_str(".SH NAME\nrustcurl \\- transfer data from or to a server\n.SH SYNOPSIS\n");
+    for (i, command) in COMMANDS.iter().enumerate() {
+        if i > 0 {
+            out.push_str(".br\n");
+        }
+        out.push_str(&format!(".B rustcurl\n{}\n", roff(command)));
+    }
+    out.push_str(".SH OPTIONS\n");
+    for (synopsis, help) in OPTIONS {
+        out.push_str(&format!(".TP\n.B {}\n{}\n", roff(synopsis), roff(&help_text(help).replace('\n', " "))));
+    }
+    out.push_str(".SH ENVIRONMENT\n");
+    for (name, help) in ENVIRONMENT {
+        out.push_str(&format!(".TP\n.B {name}\n{}\n", roff(help)));
+    }
+    out
+}
+
+/// Escape text for roff: backslashes and hyphens, and a leading `.` or
+/// `'` that would read as a request.
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with(['.', '\'']) { format!("\\&{escaped}") } else { escaped }
+}
+
+/// A markdown reference with a table per section.
+pub fn markdown() -> String {
+    let mut out = String::from("# rustcurl\n\n## Synopsis\n\n```\n");
+    for command in COMMANDS {
+        out.push_str(&format!("rustcurl {command}\n"));
+    }
+    out.push_str("```\n\n## Options\n\n| Option | Description |\n| --- | --- |\n");
+    for (synopsis, help) in OPTIONS {
+        let help = help_text(help).replace('\n', " ");
+        out.push_str(&format!("| `{}` | {} |\n", synopsis.replace('|', "\\|"), cell(&help)));
+    }
+    out.push_str("\n## Environment\n\n| Variable | Description |\n| --- | --- |\n");
+    for (name, help) in ENVIRONMENT {
+        out.push_str(&format!("| `{name}` | {} |\n", cell(help)));
+    }
+    out
+}
+
+/// Escape text for a table cell, where `|` ends the cell and `*`, `_`
+/// and `<` would be read as markup.
+fn cell(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '|' | '*' | '_' | '<' | '\\' | '`') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_aligns_help() {
+        let text = usage();
+        assert!(text.starts_with("Usage: rustcurl [OPTIONS] <URL>...\n       rustcurl relay --listen"));
+        assert!(text.contains("\n  -X, --request <METHOD>   HTTP method"));
+        assert!(text.contains("\n  --oauth2-token-url <URL> On 401"));
+        assert!(text.contains("credentials;\n                           SRC is env:VAR"));
+        assert!(text.contains(&format!("Send a known User-Agent: {}\n", presets::names())));
+        assert!(!text.contains("{presets}"));
+    }
+
+    #[test]
+    fn man_escapes_roff() {
+        let page = man();
+        assert!(page.starts_with(".TH RUSTCURL 1"));
+        assert!(page.contains(".TP\n.B \\-X, \\-\\-request <METHOD>\n"));
+        assert_eq!(roff(".hidden 'x' a\\b"), "\\&.hidden 'x' a\\eb");
+        assert_eq!(page.matches(".TP\n").count(), OPTIONS.len() + ENVIRONMENT.len());
+    }
+
+    #[test]
+    fn markdown_escapes_cells() {
+        let reference = markdown();
+        assert!(reference.contains("| `-b, --cookie <FILE\\|DATA>` | Read cookies"));
+        assert_eq!(cell("a|b *c* <d>"), "a\\|b \\*c\\* \\<d>");
+        assert_eq!(reference.lines().filter(|l| l.starts_with("| `")).count(), OPTIONS.len() + ENVIRONMENT.len());
+    }
+}
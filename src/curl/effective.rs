@@ -0,0 +1,231 @@
+// src/curl/effective.rs
+
+//! `rustcurl config show` and `--print-config`: every setting of the
+//! merged configuration with where its value came from. Layers, lowest
+//! first: built-in defaults, the `.curlrc`, environment variables the
+//! request falls back on (proxy, credentials), then the command line.
+
+use std::path::Path;
+
+use super::config::RequestConfig;
+use super::request::{resolve_noproxy, resolve_proxy};
+
+/// Settings filled from the environment when left unset, and the
+/// variables they read, in order.
+const ENV_FALLBACKS: [(&str, &[&str]); 6] = [
+    ("proxy", &["HTTPS_PROXY", "HTTP_PROXY", "ALL_PROXY", "https_proxy", "http_proxy", "all_proxy"]),
+    ("noproxy", &["NO_PROXY", "no_proxy"]),
+    ("username", &["RUSTCURL_USER"]),
+    ("password", &["RUSTCURL_PASSWORD"]),
+    ("oauth2_refresh_token", &["RUSTCURL_REFRESH_TOKEN"]),
+    ("keylog", &["SSLKEYLOGFILE"]),
+];
+
+/// Settings whose values are not printed.
+const SECRETS: [&str; 6] = [
+    "password",
+    "proxy_password",
+    "key_password",
+    "bearer",
+    "oauth2_client_secret",
+    "oauth2_refresh_token",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    Curlrc,
+    Env(String),
+    CommandLine,
+}
+
+impl Source {
+    fn label(&self) -> String {
+        match self {
+            Source::Default => "default".to_string(),
+            Source::Curlrc => "curlrc".to_string(),
+            Source::Env(var) => format!("env {var}"),
+            Source::CommandLine => "command line".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    pub name: String,
+    pub value: String,
+    pub source: Source,
+}
+
+/// The settings of `config`, parsed from the curlrc's options followed by
+/// the command line. `from_curlrc` is the same curlrc options parsed on
+/// their own (with `config`'s URL); values it shares with `config` are
+/// attributed to the curlrc.
+pub fn settings(config: &RequestConfig, from_curlrc: Option<&RequestConfig>) -> Vec<Setting> {
+    let defaults = fields(&RequestConfig::new(&config.url).env(config.env.clone().unwrap_or_default()));
+    let curlrc = from_curlrc.map(fields);
+    let mut settings = Vec::new();
+    for (name, value) in fields(config) {
+        let default = defaults.iter().find(|(n, _)| *n == name).map(|(_, v)| v);
+        let mut source = match default {
+            // The URL is always given, and only on the command line
+            _ if name == "url" => Source::CommandLine,
+            Some(default) if *default == value => Source::Default,
+            _ if curlrc.as_ref().is_some_and(|rc| rc.iter().any(|(n, v)| *n == name && *v == value)) => {
+                Source::Curlrc
+            }
+            _ => Source::CommandLine,
+        };
+        let mut value = value;
+        if source == Source::Default
+            && let Some((var, from_env)) = env_fallback(config, &name)
+        {
+            source = Source::Env(var);
+            value = from_env;
+        }
+        if SECRETS.contains(&name.as_str()) && value != "-" {
+            value = "***".to_string();
+        }
+        settings.push(Setting { name, value, source });
+    }
+    settings
+}
+
+/// The variable and value an unset setting takes from the environment.
+fn env_fallback(config: &RequestConfig, name: &str) -> Option<(String, String)> {
+    let (_, vars) = ENV_FALLBACKS.iter().find(|(n, _)| *n == name)?;
+    let var = vars.iter().find(|var| config.env_var(var).is_some())?;
+    // The same lookups the request makes, so the value shown is the one used
+    let value = match name {
+        "proxy" => resolve_proxy(config)?,
+        "noproxy" => resolve_noproxy(config)?,
+        _ => config.env_var(var)?,
+    };
+    Some((var.to_string(), value))
+}
+
+/// `name: value` pairs from `config`'s `Debug` output, each value on one
+/// line: `None` as `-`, without `Some(...)` or string quotes. The
+/// environment source is left out, as it may hold the whole process
+/// environment.
+fn fields(config: &RequestConfig) -> Vec<(String, String)> {
+    let debug = format!("{config:?}");
+    let inner = debug
+        .strip_prefix("RequestConfig { ")
+        .and_then(|d| d.strip_suffix(" }"))
+        .unwrap_or_default();
+    let mut fields: Vec<(String, String)> = split_top_level(inner)
+        .into_iter()
+        .filter_map(|field| {
+            let (name, value) = field.split_once(": ")?;
+            Some((name.to_string(), tidy(value)))
+        })
+        .filter(|(name, _)| name != "env")
+        .collect();
+    // A body is shown as text rather than a list of bytes
+    if let Some(field) = fields.iter_mut().find(|(name, _)| name == "data")
+        && let Some(ref data) = config.data
+    {
+        field.1 = String::from_utf8_lossy(data).into_owned();
+    }
+    fields
+}
+
+/// Split on `, ` outside of brackets and string literals.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut in_string, mut escaped) = (0i32, false, false);
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 && bytes.get(i + 1) == Some(&b' ') => {
+                parts.push(&text[start..i]);
+                start = i + 2;
+            }
+            _ => {}
+        }
+    }
+    if start < text.len() {
+        parts.push(&text[start..]);
+    }
+    parts
+}
+
+fn tidy(value: &str) -> String {
+    if value == "None" {
+        return "-".to_string();
+    }
+    let value = value.strip_prefix("Some(").and_then(|v| v.strip_suffix(')')).unwrap_or(value);
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(text) => text.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    }
+}
+
+/// One line per setting: source, name and value, after a header naming
+/// the curlrc read, if any.
+pub fn render(settings: &[Setting], curlrc: Option<&Path>) -> String {
+    let mut out = String::new();
+    if let Some(path) = curlrc {
+        out.push_str(&format!("# curlrc: {}\n", path.display()));
+    }
+    let width = settings.iter().map(|s| s.source.label().len()).max().unwrap_or(0);
+    for setting in settings {
+        out.push_str(&format!("{:width$}  {} = {}\n", setting.source.label(), setting.name, setting.value));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::config::Method;
+    use crate::curl::env::EnvSource;
+    use std::time::Duration;
+
+    fn find<'a>(settings: &'a [Setting], name: &str) -> &'a Setting {
+        settings.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn attributes_each_layer() {
+        let env = EnvSource::from_pairs([("HTTPS_PROXY", "http://p:3128"), ("RUSTCURL_PASSWORD", "pw")]);
+        let curlrc = RequestConfig::new("https://x.com/a").env(env.clone()).connect_timeout(Duration::from_secs(5));
+        let config = curlrc.clone().method(Method::Post).header("X-A: \"q\", b").username("u");
+        let settings = settings(&config, Some(&curlrc));
+
+        assert_eq!(find(&settings, "url").source, Source::CommandLine);
+        assert_eq!(find(&settings, "method").value, "Post");
+        assert_eq!(find(&settings, "method").source, Source::CommandLine);
+        assert_eq!(find(&settings, "connect_timeout").source, Source::Curlrc);
+        assert_eq!(find(&settings, "connect_timeout").value, "5s");
+        assert_eq!(find(&settings, "headers").value, r#"["X-A: \"q\", b"]"#);
+        assert_eq!(find(&settings, "username").value, "u");
+        let proxy = find(&settings, "proxy");
+        assert_eq!((proxy.value.as_str(), &proxy.source), ("http://p:3128", &Source::Env("HTTPS_PROXY".into())));
+        assert_eq!(find(&settings, "password").value, "***");
+        assert_eq!(find(&settings, "bearer").value, "-");
+        assert!(settings.iter().all(|s| s.name != "env"));
+    }
+
+    #[test]
+    fn renders_aligned_lines() {
+        let config = RequestConfig::new("https://x.com").env(EnvSource::Disabled).data("a=1");
+        let text = render(&settings(&config, None), Some(Path::new("/home/u/.curlrc")));
+        assert!(text.starts_with("# curlrc: /home/u/.curlrc\ncommand line  url = https://x.com\n"));
+        assert!(text.contains("\ncommand line  data = a=1\n"));
+    }
+}
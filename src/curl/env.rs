@@ -0,0 +1,62 @@
+// src/curl/env.rs
+
+//! Where credential and proxy fallbacks (`RUSTCURL_USER`, `HTTPS_PROXY`,
+//! ...) are looked up. Embedders serving several tenants can swap the
+//! process environment for a fixed map or turn lookups off entirely.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EnvSource {
+    /// The process environment, as the CLI uses.
+    #[default]
+    Process,
+    /// Only these variables exist.
+    Static(HashMap<String, String>),
+    /// No variables exist.
+    Disabled,
+}
+
+impl EnvSource {
+    pub fn from_pairs<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        EnvSource::Static(pairs.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        match self {
+            EnvSource::Process => std::env::var(name).ok(),
+            EnvSource::Static(vars) => vars.get(name).cloned(),
+            EnvSource::Disabled => None,
+        }
+    }
+
+    /// True when lookups bypass the process environment, in which case
+    /// backends must also stop their own implicit proxy detection.
+    pub fn is_isolated(&self) -> bool {
+        *self != EnvSource::Process
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_map_lookup() {
+        let env = EnvSource::from_pairs([("HTTPS_PROXY", "http://p:1")]);
+        assert_eq!(env.get("HTTPS_PROXY").as_deref(), Some("http://p:1"));
+        assert_eq!(env.get("PATH"), None);
+        assert!(env.is_isolated());
+    }
+
+    #[test]
+    fn disabled_has_nothing() {
+        assert_eq!(EnvSource::Disabled.get("PATH"), None);
+        assert!(EnvSource::Disabled.is_isolated());
+        assert!(!EnvSource::Process.is_isolated());
+    }
+}
@@ -0,0 +1,184 @@
+// src/curl/eol.rs
+
+//! Line endings: `--crlf` turns LF into CRLF in request bodies, and
+//! `--normalize-eol` rewrites text responses to LF or CRLF for the
+//! pipeline on the other end.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+
+use super::error::RequestError;
+use super::mime;
+use super::response::Response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eol {
+    Lf,
+    Crlf,
+}
+
+impl Eol {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "lf" => Some(Eol::Lf),
+            "crlf" => Some(Eol::Crlf),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Eol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Eol::Lf => "LF",
+            Eol::Crlf => "CRLF",
+        })
+    }
+}
+
+/// Rewrite every line ending in `data` to `eol`. Existing CRLF pairs are
+/// never doubled; a lone CR is left alone.
+pub fn normalize(data: &[u8], eol: Eol) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut prev = 0u8;
+    for &b in data {
+        match (eol, b) {
+            (Eol::Lf, b'\n') if prev == b'\r' => {
+                out.pop();
+                out.push(b'\n');
+            }
+            (Eol::Crlf, b'\n') if prev != b'\r' => out.extend_from_slice(b"\r\n"),
+            _ => out.push(b),
+        }
+        prev = b;
+    }
+    out
+}
+
+/// Reader adapter for `--crlf` on streamed bodies.
+pub struct CrlfReader<R> {
+    inner: R,
+    prev: u8,
+    /// LF held back when the caller's buffer filled right after its CR.
+    pending_lf: bool,
+}
+
+impl<R: Read> CrlfReader<R> {
+    pub fn new(inner: R) -> Self {
+        CrlfReader {
+            inner,
+            prev: 0,
+            pending_lf: false,
+        }
+    }
+}
+
+impl<R: Read> Read for CrlfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pending_lf {
+            self.pending_lf = false;
+            self.prev = b'\n';
+            buf[0] = b'\n';
+            return Ok(1);
+        }
+        // Read at most half the buffer so every LF can grow into CRLF
+        let mut chunk = vec![0; buf.len().div_ceil(2)];
+        let n = self.inner.read(&mut chunk)?;
+        let mut written = 0;
+        for &b in &chunk[..n] {
+            if b == b'\n' && self.prev != b'\r' {
+                buf[written] = b'\r';
+                written += 1;
+                if written == buf.len() {
+                    self.pending_lf = true;
+                    self.prev = b'\r';
+                    return Ok(written);
+                }
+            }
+            buf[written] = b;
+            written += 1;
+            self.prev = b;
+        }
+        Ok(written)
+    }
+}
+
+/// Rewrite a text response's line endings, in memory or in the file it
+/// was saved to. Returns whether the body was rewritten.
+pub fn apply(eol: Eol, response: &mut Response) -> Result<bool, RequestError> {
+    if !mime::is_text(response.get_header("content-type").as_deref()) {
+        return Ok(false);
+    }
+    let converted = normalize(&response.received_body(), eol);
+    match response.saved_as {
+        Some(ref path) if response.body.is_empty() => {
+            fs::write(path, converted).map_err(|source| RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            })?;
+        }
+        _ => response.body = converted,
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_crlf_without_doubling() {
+        assert_eq!(normalize(b"a\nb\r\nc\n", Eol::Crlf), b"a\r\nb\r\nc\r\n");
+        assert_eq!(normalize(b"\n\n", Eol::Crlf), b"\r\n\r\n");
+    }
+
+    #[test]
+    fn to_lf() {
+        assert_eq!(normalize(b"a\r\nb\nc\rd\r\n", Eol::Lf), b"a\nb\nc\rd\n");
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(Eol::parse("CRLF"), Some(Eol::Crlf));
+        assert_eq!(Eol::parse("lf"), Some(Eol::Lf));
+        assert_eq!(Eol::parse("cr"), None);
+    }
+
+    #[test]
+    fn reader_matches_normalize() {
+        let input = b"one\ntwo\r\n\nthree\n".repeat(50);
+        for buf_len in [1, 2, 3, 7, 64] {
+            let mut reader = CrlfReader::new(&input[..]);
+            let mut out = Vec::new();
+            let mut buf = vec![0; buf_len];
+            loop {
+                let n = reader.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                out.extend_from_slice(&buf[..n]);
+            }
+            assert_eq!(out, normalize(&input, Eol::Crlf), "buffer of {buf_len}");
+        }
+    }
+
+    #[test]
+    fn binary_response_untouched() {
+        let mut response = Response {
+            status_code: 200,
+            headers: vec!["Content-Type: application/octet-stream".into()],
+            body: b"a\nb".to_vec(),
+            attempts: vec![200],
+            ..Default::default()
+        };
+        assert!(!apply(Eol::Crlf, &mut response).unwrap());
+        assert_eq!(response.body, b"a\nb");
+        response.headers = vec!["Content-Type: text/plain".into()];
+        assert!(apply(Eol::Crlf, &mut response).unwrap());
+        assert_eq!(response.body, b"a\r\nb");
+    }
+}
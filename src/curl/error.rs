@@ -2,36 +2,102 @@
 
 use std::fmt;
 
+use super::messages::{self, Hint, Locale};
+
+/// Which part of the transfer ran out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    Dns,
+    Connect,
+    Read,
+    Total,
+}
+
+/// Failure categories shared by all backends, so callers can match on
+/// what went wrong rather than on backend-specific error strings.
 #[derive(Debug)]
 pub enum RequestError {
-    #[cfg(feature = "curl")]
-    Curl(curl::Error),
+    /// Name resolution failed for the target host, or the proxy host.
+    Dns { host: String, proxy: bool },
+    /// TCP connection could not be established.
+    Connect { message: String },
+    /// TLS negotiation failed before certificates could be checked.
+    TlsHandshake { message: String },
+    /// The peer certificate was rejected (untrusted, expired, revoked...).
+    TlsVerify { message: String },
+    Timeout { phase: TimeoutPhase, message: String },
+    /// The proxy rejected our credentials (HTTP 407).
+    ProxyAuth { message: String },
+    TooManyRedirects { message: String },
+    /// The connection closed before the whole body arrived. `expected` is
+    /// the declared Content-Length, when there was one.
+    PartialBody { expected: Option<u64>, received: u64 },
+    /// The server answered with an error status the caller asked to fail on.
+    HttpStatus { code: u32 },
+    /// The response body could not be written to the output file.
+    WriteOutput { path: String, source: std::io::Error },
+    /// A downloaded archive could not be unpacked (`--extract`).
+    Extract { path: String, message: String },
+    /// The detached signature named by `signature` did not verify
+    /// (`--verify-sig`).
+    Signature { signature: String, message: String },
+    /// A rejected bearer token could not be refreshed at the token
+    /// endpoint `url` (`--oauth2-token-url`), or a registry token could not
+    /// be fetched from its realm (`--registry-auth`).
+    TokenRefresh { url: String, message: String },
+    /// A `--capture` expression found nothing in the response.
+    Capture { name: String, message: String },
+    /// The transfer succeeded but broke a timing assertion such as
+    /// `--assert-time-total-max`.
+    SlaExceeded { option: String, limit: std::time::Duration, actual: std::time::Duration },
     Io(std::io::Error),
-    #[allow(dead_code)]
-    Config(String),
-    #[allow(dead_code)]
-    Http(String), // Generic HTTP error for non-curl backends
+    /// An option value the backend cannot use.
+    Config { option: String, message: String },
+    /// `rustcurl self-update` found no usable release, or its download
+    /// did not match the manifest.
+    Update { message: String },
+    /// Any other transfer failure reported by the backend.
+    Transfer { message: String },
+    /// `--retry` sent the request `attempts.len()` times (each one's status
+    /// recorded, 0 for a failed transfer) and `last` still failed.
+    Retried { attempts: Vec<u32>, last: Box<RequestError> },
 }
 
 impl fmt::Display for RequestError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) => write!(f, "curl error: {e}"),
-            RequestError::Io(e) => write!(f, "io error: {e}"),
-            RequestError::Config(msg) => write!(f, "config error: {msg}"),
-            RequestError::Http(msg) => write!(f, "http error: {msg}"),
-        }
+        f.write_str(&messages::describe(self, Locale::En))
     }
 }
 
 impl std::error::Error for RequestError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) => Some(e),
             RequestError::Io(e) => Some(e),
-            RequestError::Config(_) | RequestError::Http(_) => None,
+            RequestError::WriteOutput { source, .. } => Some(source),
+            RequestError::Retried { last, .. } => Some(&**last),
+            _ => None,
+        }
+    }
+}
+
+/// Host named in libcurl's "Could not resolve host: X" detail message.
+#[cfg(feature = "curl")]
+fn resolved_name(e: &curl::Error) -> String {
+    e.extra_description()
+        .and_then(|s| s.rsplit_once(": "))
+        .map(|(_, host)| host.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// `--ech hard` could not use ECH; the curl crate has no predicate for it.
+#[cfg(feature = "curl")]
+const CURLE_ECH_REQUIRED: curl_sys::CURLcode = 101;
+
+#[cfg(feature = "curl")]
+impl From<curl::MultiError> for RequestError {
+    fn from(e: curl::MultiError) -> Self {
+        RequestError::Transfer {
+            message: e.to_string(),
         }
     }
 }
@@ -39,42 +105,186 @@ impl std::error::Error for RequestError {
 #[cfg(feature = "curl")]
 impl From<curl::Error> for RequestError {
     fn from(e: curl::Error) -> Self {
-        RequestError::Curl(e)
+        let message = e.extra_description().unwrap_or(e.description()).to_string();
+        if e.is_couldnt_resolve_host() {
+            RequestError::Dns { host: resolved_name(&e), proxy: false }
+        } else if e.is_couldnt_resolve_proxy() {
+            RequestError::Dns { host: resolved_name(&e), proxy: true }
+        } else if e.is_operation_timedout() {
+            let phase = if message.starts_with("Resolving") {
+                TimeoutPhase::Dns
+            } else if message.starts_with("Connection") || message.starts_with("Failed to connect") {
+                TimeoutPhase::Connect
+            } else if message.contains("too slow") {
+                TimeoutPhase::Read
+            } else {
+                TimeoutPhase::Total
+            };
+            RequestError::Timeout { phase, message }
+        } else if message.contains("407") {
+            RequestError::ProxyAuth { message }
+        } else if e.is_peer_failed_verification()
+            || e.is_ssl_cacert()
+            || e.is_ssl_cacert_badfile()
+            || e.is_ssl_issuer_error()
+            || e.code() == curl_sys::CURLE_SSL_PINNEDPUBKEYNOTMATCH
+        {
+            RequestError::TlsVerify { message }
+        } else if e.is_ssl_certproblem() {
+            RequestError::Config {
+                option: "--cert".to_string(),
+                message,
+            }
+        } else if e.is_ssl_cipher() {
+            RequestError::Config {
+                option: "--ciphers".to_string(),
+                message,
+            }
+        } else if e.is_ssl_connect_error() || e.code() == CURLE_ECH_REQUIRED {
+            RequestError::TlsHandshake { message }
+        } else if e.is_too_many_redirects() {
+            RequestError::TooManyRedirects { message }
+        } else if e.is_couldnt_connect() {
+            RequestError::Connect { message }
+        } else {
+            RequestError::Transfer { message }
+        }
     }
 }
 
 impl From<std::io::Error> for RequestError {
     fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::TimedOut {
+            return RequestError::Timeout {
+                phase: TimeoutPhase::Total,
+                message: e.to_string(),
+            };
+        }
         RequestError::Io(e)
     }
 }
 
 impl RequestError {
-    pub fn hint(&self) -> Option<&'static str> {
+    /// Whether the failure is worth retrying under `--retry` (timeouts,
+    /// like curl; connection refusals and DNS errors are not).
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RequestError::Retried { last, .. } => last.is_transient(),
+            _ => matches!(self, RequestError::Timeout { .. }),
+        }
+    }
+
+    /// Process exit status for this failure: 1, except for failed timing
+    /// assertions so pipelines can tell slow from broken, and for retries
+    /// that ran out on a failure still worth retrying.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RequestError::SlaExceeded { .. } => super::sla::EXIT_SLA_EXCEEDED,
+            RequestError::Retried { last, .. } if last.is_transient() => super::retry::EXIT_RETRIES_EXHAUSTED,
+            RequestError::Retried { last, .. } => last.exit_code(),
+            _ => 1,
+        }
+    }
+
+    /// Status of each attempt when `--retry` sent the request more than
+    /// once, 0 for a failed transfer.
+    pub fn attempts(&self) -> Option<&[u32]> {
         match self {
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) if e.is_couldnt_resolve_host() => Some(
-                "Hint: DNS resolution failed. If behind a corporate proxy, set HTTPS_PROXY or use -x <proxy-url>",
-            ),
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) if e.is_couldnt_resolve_proxy() => Some(
-                "Hint: Could not resolve proxy hostname. Check your proxy URL",
-            ),
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) if e.is_ssl_connect_error() || e.is_peer_failed_verification() => Some(
-                "Hint: SSL error. Try --insecure (-k), --cacert <path>, or --ssl-no-revoke for revocation issues",
-            ),
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) if format!("{e}").contains("revocation") => Some(
-                "Hint: Certificate revocation check failed. Try --ssl-no-revoke to disable revocation checks",
-            ),
-            #[cfg(feature = "curl")]
-            RequestError::Curl(e) if format!("{e}").contains("407") => Some(
-                "Hint: Proxy requires authentication (407). Try --proxy-negotiate for Kerberos/SPNEGO or --proxy-user <user:pass>",
-            ),
+            RequestError::Retried { attempts, .. } => Some(attempts),
             _ => None,
         }
     }
+
+    /// Whether the request never got as far as an HTTP exchange: name
+    /// resolution, connecting, the TLS handshake or the proxy failed.
+    pub fn is_connection_failure(&self) -> bool {
+        if let RequestError::Retried { last, .. } = self {
+            return last.is_connection_failure();
+        }
+        matches!(
+            self,
+            RequestError::Dns { .. }
+                | RequestError::Connect { .. }
+                | RequestError::TlsHandshake { .. }
+                | RequestError::TlsVerify { .. }
+                | RequestError::ProxyAuth { .. }
+                | RequestError::Timeout {
+                    phase: TimeoutPhase::Dns | TimeoutPhase::Connect,
+                    ..
+                }
+        )
+    }
+
+    /// Stable machine-readable name of the failure category.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RequestError::Dns { proxy: false, .. } => "dns",
+            RequestError::Dns { proxy: true, .. } => "proxy_dns",
+            RequestError::Connect { .. } => "connect",
+            RequestError::TlsHandshake { .. } => "tls_handshake",
+            RequestError::TlsVerify { .. } => "tls_verify",
+            RequestError::Timeout { .. } => "timeout",
+            RequestError::ProxyAuth { .. } => "proxy_auth",
+            RequestError::TooManyRedirects { .. } => "too_many_redirects",
+            RequestError::PartialBody { .. } => "partial_body",
+            RequestError::HttpStatus { .. } => "http_status",
+            RequestError::WriteOutput { .. } => "write_output",
+            RequestError::Extract { .. } => "extract",
+            RequestError::Signature { .. } => "signature",
+            RequestError::TokenRefresh { .. } => "token_refresh",
+            RequestError::Capture { .. } => "capture",
+            RequestError::SlaExceeded { .. } => "sla_exceeded",
+            RequestError::Io(_) => "io",
+            RequestError::Config { .. } => "config",
+            RequestError::Update { .. } => "update",
+            RequestError::Transfer { .. } => "transfer",
+            RequestError::Retried { last, .. } => last.code(),
+        }
+    }
+
+    /// Actionable suggestion for the failure, derived from the error
+    /// category so every backend gets the same advice.
+    pub fn hint_kind(&self) -> Option<Hint> {
+        match self {
+            RequestError::Dns { proxy: false, .. } => Some(Hint::Dns),
+            RequestError::Dns { proxy: true, .. } => Some(Hint::ProxyDns),
+            RequestError::TlsHandshake { message } | RequestError::TlsVerify { message }
+                if is_revocation_failure(message) =>
+            {
+                Some(Hint::Revocation)
+            }
+            RequestError::TlsVerify { message } if is_self_signed(message) => Some(Hint::SelfSigned),
+            // A `--tofu` key change already says what to do, and -k is not it
+            RequestError::TlsVerify { message } if message.contains("rustcurl trust remove") => None,
+            RequestError::TlsHandshake { .. } | RequestError::TlsVerify { .. } => Some(Hint::Tls),
+            RequestError::ProxyAuth { .. } => Some(Hint::ProxyAuth),
+            RequestError::PartialBody { .. } => Some(Hint::PartialBody),
+            RequestError::Retried { last, .. } => last.hint_kind(),
+            _ => None,
+        }
+    }
+
+    /// English hint text; see `messages::render_error` for other locales.
+    pub fn hint(&self) -> Option<&'static str> {
+        self.hint_kind().map(|h| messages::hint_text(h, Locale::En))
+    }
+}
+
+/// Revocation failures as worded by schannel, OpenSSL and rustls.
+fn is_revocation_failure(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("revocation") || lower.contains("revoked") || lower.contains("crl")
+}
+
+/// Untrusted-issuer failures as worded by OpenSSL, schannel and rustls.
+fn is_self_signed(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("self-signed")
+        || lower.contains("self signed")
+        || lower.contains("unknownissuer")
+        || lower.contains("unknown issuer")
+        || lower.contains("unable to get local issuer")
+        || lower.contains("untrusted root")
 }
 
 #[cfg(test)]
@@ -84,8 +294,11 @@ mod tests {
 
     #[test]
     fn display_config_error() {
-        let err = RequestError::Config("bad url".to_string());
-        assert_eq!(format!("{err}"), "config error: bad url");
+        let err = RequestError::Config {
+            option: "--url".into(),
+            message: "bad url".into(),
+        };
+        assert_eq!(format!("{err}"), "config error: --url: bad url");
     }
 
     #[test]
@@ -97,32 +310,147 @@ mod tests {
         assert!(msg.contains("file missing"));
     }
 
+    #[test]
+    fn display_structured_variants() {
+        let err = RequestError::Dns { host: "example.com".into(), proxy: false };
+        assert_eq!(err.to_string(), "could not resolve host: example.com");
+        let err = RequestError::Timeout {
+            phase: TimeoutPhase::Connect,
+            message: "after 5000 ms".into(),
+        };
+        assert_eq!(err.to_string(), "connect timeout: after 5000 ms");
+        let err = RequestError::HttpStatus { code: 503 };
+        assert_eq!(err.to_string(), "server returned HTTP 503");
+    }
+
     #[test]
     fn from_io_error() {
-        let io_err = std::io::Error::new(std::io::ErrorKind::Other, "test");
+        let io_err = std::io::Error::other("test");
         let err: RequestError = io_err.into();
         assert!(matches!(err, RequestError::Io(_)));
     }
 
+    #[test]
+    fn from_io_timeout_is_timeout() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::TimedOut, "slow");
+        let err: RequestError = io_err.into();
+        assert!(matches!(err, RequestError::Timeout { phase: TimeoutPhase::Total, .. }));
+        assert!(err.is_transient());
+    }
+
     #[test]
     fn error_trait_source() {
-        let err = RequestError::Config("x".into());
+        let err = RequestError::Config { option: "x".into(), message: "y".into() };
         assert!(err.source().is_none());
 
-        let io_err = std::io::Error::new(std::io::ErrorKind::Other, "y");
+        let io_err = std::io::Error::other("y");
         let err = RequestError::Io(io_err);
         assert!(err.source().is_some());
+
+        let err = RequestError::WriteOutput {
+            path: "/out".into(),
+            source: std::io::Error::other("disk full"),
+        };
+        assert!(err.source().is_some());
+    }
+
+    // Raw libcurl codes: 5/6 resolve proxy/host, 7 connect, 28 timeout,
+    // 35 SSL connect, 47 redirects, 52 got nothing, 60 peer verification.
+    #[cfg(feature = "curl")]
+    fn curl_error(code: i32, extra: &str) -> RequestError {
+        let mut e = curl::Error::new(code as _);
+        e.set_extra(extra.to_string());
+        e.into()
+    }
+
+    #[cfg(feature = "curl")]
+    #[test]
+    fn curl_errors_map_to_categories() {
+        assert!(matches!(
+            curl_error(6, "Could not resolve host: example.com"),
+            RequestError::Dns { ref host, proxy: false } if host == "example.com"
+        ));
+        assert!(matches!(
+            curl_error(5, "Could not resolve proxy: proxy.corp"),
+            RequestError::Dns { ref host, proxy: true } if host == "proxy.corp"
+        ));
+        assert!(matches!(
+            curl_error(28, "Connection timed out after 5001 milliseconds"),
+            RequestError::Timeout { phase: TimeoutPhase::Connect, .. }
+        ));
+        assert!(matches!(
+            curl_error(28, "Operation too slow. Less than 1 bytes/sec"),
+            RequestError::Timeout { phase: TimeoutPhase::Read, .. }
+        ));
+        assert!(matches!(
+            curl_error(7, "CONNECT tunnel failed, response 407"),
+            RequestError::ProxyAuth { .. }
+        ));
+        assert!(matches!(
+            curl_error(60, "self-signed certificate"),
+            RequestError::TlsVerify { .. }
+        ));
+        assert!(matches!(
+            curl_error(35, "handshake failure"),
+            RequestError::TlsHandshake { .. }
+        ));
+        assert!(matches!(
+            curl_error(47, "Maximum (3) redirects followed"),
+            RequestError::TooManyRedirects { .. }
+        ));
+        assert!(matches!(
+            curl_error(7, "Connection refused"),
+            RequestError::Connect { .. }
+        ));
+        assert!(matches!(
+            curl_error(52, "Empty reply from server"),
+            RequestError::Transfer { .. }
+        ));
+    }
+
+    #[test]
+    fn hints_from_variants_regardless_of_backend() {
+        let dns = RequestError::Dns { host: "x.corp".into(), proxy: false };
+        assert!(dns.hint().unwrap().contains("HTTPS_PROXY"));
+
+        let proxy_dns = RequestError::Dns { host: "proxy".into(), proxy: true };
+        assert!(proxy_dns.hint().unwrap().contains("proxy URL"));
+
+        let proxy_auth = RequestError::ProxyAuth { message: "407".into() };
+        assert!(proxy_auth.hint().unwrap().contains("--proxy-negotiate"));
+
+        // schannel wording (curl backend) and rustls wording (reqwest backend)
+        for message in [
+            "schannel: next InitializeSecurityContext failed: CRYPT_E_NO_REVOCATION_CHECK - The revocation function was unable to check revocation",
+            "invalid peer certificate: Revoked",
+        ] {
+            let err = RequestError::TlsVerify { message: message.into() };
+            assert!(err.hint().unwrap().contains("--ssl-no-revoke"), "{message}");
+        }
+
+        for message in [
+            "SSL certificate problem: self-signed certificate in certificate chain",
+            "invalid peer certificate: UnknownIssuer",
+        ] {
+            let err = RequestError::TlsVerify { message: message.into() };
+            assert!(err.hint().unwrap().contains("self-signed"), "{message}");
+        }
+
+        let handshake = RequestError::TlsHandshake { message: "alert".into() };
+        assert!(handshake.hint().unwrap().contains("--insecure"));
+        let key_changed = RequestError::TlsVerify { message: "run 'rustcurl trust remove a:443'".into() };
+        assert!(key_changed.hint().is_none());
     }
 
     #[test]
     fn hint_none_for_config_error() {
-        let err = RequestError::Config("bad".into());
+        let err = RequestError::Config { option: "x".into(), message: "bad".into() };
         assert!(err.hint().is_none());
     }
 
     #[test]
     fn hint_none_for_io_error() {
-        let err = RequestError::Io(std::io::Error::new(std::io::ErrorKind::Other, "x"));
+        let err = RequestError::Io(std::io::Error::other("x"));
         assert!(err.hint().is_none());
     }
 }
@@ -0,0 +1,535 @@
+// src/curl/extract.rs
+
+//! `--extract`: unpack a downloaded `.tar`, `.tar.gz`/`.tgz` or `.zip`
+//! after it has been saved. Entries that would land outside the target
+//! directory (absolute paths, `..`) are rejected, links are skipped,
+//! existing files are kept unless overwriting is asked for, and archives
+//! that expand past `MAX_EXTRACTED` bytes are refused.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use miniz_oxide::inflate::TINFLStatus;
+
+use super::error::RequestError;
+use super::mime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    /// Recognize an archive by its content, falling back to the file name.
+    pub fn detect(name: &str, data: &[u8]) -> Option<Self> {
+        match mime::sniff(data) {
+            Some("application/zip") => return Some(ArchiveKind::Zip),
+            Some("application/x-tar") => return Some(ArchiveKind::Tar),
+            Some("application/gzip") => return Some(ArchiveKind::TarGz),
+            _ => {}
+        }
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if lower.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if lower.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Most bytes an archive may expand to, over all of its entries.
+pub const MAX_EXTRACTED: usize = 4 << 30;
+
+/// A file or directory read from an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub is_dir: bool,
+    pub data: Vec<u8>,
+}
+
+/// Unpack the archive at `archive` into `dest`, returning the paths
+/// written. Nothing is written if any entry is unsafe, or, unless
+/// `overwrite`, would replace an existing file.
+pub fn extract(archive: &Path, dest: &Path, overwrite: bool) -> Result<Vec<PathBuf>, RequestError> {
+    extract_with_limit(archive, dest, overwrite, MAX_EXTRACTED)
+}
+
+fn extract_with_limit(
+    archive: &Path,
+    dest: &Path,
+    overwrite: bool,
+    limit: usize,
+) -> Result<Vec<PathBuf>, RequestError> {
+    let label = archive.display().to_string();
+    let err = |message: String| RequestError::Extract {
+        path: label.clone(),
+        message,
+    };
+    let data = fs::read(archive).map_err(|e| err(e.to_string()))?;
+    let name = archive.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let entries = match ArchiveKind::detect(name, &data) {
+        Some(ArchiveKind::Tar) => read_tar(&data),
+        Some(ArchiveKind::TarGz) => gunzip(&data, limit).and_then(|tar| read_tar(&tar)),
+        Some(ArchiveKind::Zip) => read_zip(&data, limit),
+        None => Err("not a recognized archive (tar, tar.gz or zip)".to_string()),
+    }
+    .map_err(&err)?;
+    let total = entries.iter().map(|e| e.data.len()).fold(0usize, usize::saturating_add);
+    if total > limit {
+        return Err(err(too_large(limit)));
+    }
+
+    let mut targets = Vec::new();
+    for entry in &entries {
+        let target = safe_join(dest, &entry.path).ok_or_else(|| err(format!("unsafe entry path: {}", entry.path)))?;
+        if target == dest && !entry.is_dir {
+            return Err(err(format!("unsafe entry path: {}", entry.path)));
+        }
+        if !overwrite && !entry.is_dir && fs::symlink_metadata(&target).is_ok() {
+            return Err(err(format!("{} already exists (--extract-overwrite replaces it)", target.display())));
+        }
+        targets.push(target);
+    }
+
+    let mut written = Vec::new();
+    for (entry, target) in entries.into_iter().zip(targets) {
+        if target == dest {
+            continue;
+        }
+        let result = if entry.is_dir {
+            fs::create_dir_all(&target)
+        } else {
+            target.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| {
+                let mut options = OpenOptions::new();
+                options.write(true);
+                if overwrite {
+                    options.create(true).truncate(true);
+                } else {
+                    options.create_new(true);
+                }
+                options.open(&target)?.write_all(&entry.data)
+            })
+        };
+        result.map_err(|e| err(format!("{}: {e}", target.display())))?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+fn too_large(limit: usize) -> String {
+    format!("archive expands to more than {limit} bytes")
+}
+
+/// `dest` joined with an archive entry path, or `None` if the entry is
+/// absolute or climbs out of `dest`. `.` components change nothing, so
+/// `./` is `dest` itself.
+pub fn safe_join(dest: &Path, entry: &str) -> Option<PathBuf> {
+    let entry = entry.replace('\\', "/");
+    let mut out = dest.to_path_buf();
+    let mut depth = 0usize;
+    for component in Path::new(&entry).components() {
+        match component {
+            Component::Normal(part) => {
+                out.push(part);
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => {
+                out.pop();
+                depth -= 1;
+            }
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Decompress a gzip member (RFC 1952) of at most `limit` bytes.
+fn gunzip(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+    let bad = || "truncated gzip header".to_string();
+    if data.len() < 18 || data[..3] != [0x1f, 0x8b, 8] {
+        return Err("not a gzip stream".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = u16::from_le_bytes([*data.get(pos).ok_or_else(bad)?, *data.get(pos + 1).ok_or_else(bad)?]);
+        pos += 2 + len as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            pos += data.get(pos..).ok_or_else(bad)?.iter().position(|&b| b == 0).ok_or_else(bad)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    let deflated = data.get(pos..data.len().saturating_sub(8)).ok_or_else(bad)?;
+    inflate(deflated, limit, limit)
+}
+
+/// Inflate a raw deflate stream that may come to at most `max` bytes;
+/// `limit` is the cap on the whole archive, for the error.
+fn inflate(data: &[u8], max: usize, limit: usize) -> Result<Vec<u8>, String> {
+    miniz_oxide::inflate::decompress_to_vec_with_limit(data, max).map_err(|e| match e.status {
+        TINFLStatus::HasMoreOutput => too_large(limit),
+        status => format!("corrupt deflate data: {status:?}"),
+    })
+}
+
+/// Regular files and directories of a ustar/GNU tar stream. PAX and GNU
+/// long names are honored; links and special files are skipped.
+fn read_tar(data: &[u8]) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut long_name: Option<String> = None;
+    let mut pos = 0;
+    while let Some(header) = data.get(pos..pos + 512) {
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size = octal(&header[124..136]).ok_or("bad tar entry size")? as usize;
+        let body = data
+            .get(pos + 512..pos + 512 + size)
+            .ok_or("truncated tar entry")?;
+        pos += 512 + size.div_ceil(512) * 512;
+
+        let mut name = cstr(&header[..100]);
+        if &header[257..262] == b"ustar" {
+            let prefix = cstr(&header[345..500]);
+            if !prefix.is_empty() {
+                name = format!("{prefix}/{name}");
+            }
+        }
+        match header[156] {
+            b'L' => long_name = Some(cstr(body)),
+            b'x' => long_name = pax_path(body).or(long_name),
+            kind @ (b'0' | 0 | b'5') => {
+                let path = long_name.take().unwrap_or(name);
+                entries.push(Entry {
+                    is_dir: kind == b'5' || path.ends_with('/'),
+                    path,
+                    data: if kind == b'5' { Vec::new() } else { body.to_vec() },
+                });
+            }
+            _ => long_name = None,
+        }
+    }
+    Ok(entries)
+}
+
+fn octal(field: &[u8]) -> Option<u64> {
+    let text = cstr(field);
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+fn cstr(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// `path` record of a PAX extended header (`"<len> path=<value>\n"`).
+fn pax_path(body: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(body)
+        .lines()
+        .find_map(|record| record.split_once(' ')?.1.strip_prefix("path=").map(str::to_string))
+}
+
+/// Entries of a zip file, located through its central directory. Stored
+/// and deflated entries are supported; zip64 and encryption are not.
+/// Inflating stops once the entries would take more than `limit` bytes.
+fn read_zip(data: &[u8], limit: usize) -> Result<Vec<Entry>, String> {
+    let mut budget = limit;
+    const EOCD: &[u8] = b"PK\x05\x06";
+    const CENTRAL: &[u8] = b"PK\x01\x02";
+    const LOCAL: &[u8] = b"PK\x03\x04";
+    let u16_at = |at: usize| data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let u32_at = |at: usize| data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+    let bad = || "malformed zip".to_string();
+
+    let eocd = (0..=data.len().saturating_sub(22))
+        .rev()
+        .find(|&i| data[i..].starts_with(EOCD))
+        .ok_or("zip end of central directory not found")?;
+    let count = u16_at(eocd + 10).ok_or_else(bad)?;
+    let mut pos = u32_at(eocd + 16).ok_or_else(bad)?;
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        if !data.get(pos..).is_some_and(|d| d.starts_with(CENTRAL)) {
+            return Err(bad());
+        }
+        let flags = u16_at(pos + 8).ok_or_else(bad)?;
+        let method = u16_at(pos + 10).ok_or_else(bad)?;
+        let compressed = u32_at(pos + 20).ok_or_else(bad)?;
+        let name_len = u16_at(pos + 28).ok_or_else(bad)?;
+        let extra_len = u16_at(pos + 30).ok_or_else(bad)?;
+        let comment_len = u16_at(pos + 32).ok_or_else(bad)?;
+        let local = u32_at(pos + 42).ok_or_else(bad)?;
+        let name = data.get(pos + 46..pos + 46 + name_len).ok_or_else(bad)?;
+        let path = String::from_utf8_lossy(name).to_string();
+        pos += 46 + name_len + extra_len + comment_len;
+
+        if flags & 1 != 0 {
+            return Err(format!("{path}: encrypted entries are not supported"));
+        }
+        if compressed == 0xffff_ffff || local == 0xffff_ffff {
+            return Err(format!("{path}: zip64 entries are not supported"));
+        }
+        if !data.get(local..).is_some_and(|d| d.starts_with(LOCAL)) {
+            return Err(bad());
+        }
+        let start = local + 30 + u16_at(local + 26).ok_or_else(bad)? + u16_at(local + 28).ok_or_else(bad)?;
+        let raw = data.get(start..start + compressed).ok_or_else(bad)?;
+        let is_dir = path.ends_with('/');
+        let data = match method {
+            _ if is_dir => Vec::new(),
+            0 => raw.to_vec(),
+            8 => inflate(raw, budget, limit).map_err(|e| format!("{path}: {e}"))?,
+            m => return Err(format!("{path}: unsupported compression method {m}")),
+        };
+        budget = budget.checked_sub(data.len()).ok_or_else(|| too_large(limit))?;
+        entries.push(Entry { path, is_dir, data });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_header(name: &str, kind: u8, size: usize) -> Vec<u8> {
+        let mut h = vec![0u8; 512];
+        h[..name.len()].copy_from_slice(name.as_bytes());
+        h[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        h[156] = kind;
+        h[257..262].copy_from_slice(b"ustar");
+        h
+    }
+
+    fn tar(entries: &[(&str, u8, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, kind, data) in entries {
+            out.extend(tar_header(name, *kind, data.len()));
+            out.extend_from_slice(data);
+            out.resize(out.len().div_ceil(512) * 512, 0);
+        }
+        out.extend([0u8; 1024]);
+        out
+    }
+
+    /// Gzip member with stored (uncompressed) deflate blocks.
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+        out.extend(deflate_stored(data));
+        out.extend([0u8; 8]);
+        out
+    }
+
+    fn deflate_stored(data: &[u8]) -> Vec<u8> {
+        miniz_oxide::deflate::compress_to_vec(data, 0)
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, data) in entries {
+            let packed = deflate_stored(data);
+            let offset = out.len() as u32;
+            let common = |sig: &[u8]| {
+                let mut h = sig.to_vec();
+                h.extend([20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+                h.extend((packed.len() as u32).to_le_bytes());
+                h.extend((data.len() as u32).to_le_bytes());
+                h.extend((name.len() as u16).to_le_bytes());
+                h.extend([0, 0]);
+                h
+            };
+            out.extend(common(b"PK\x03\x04"));
+            out.extend_from_slice(name.as_bytes());
+            out.extend(&packed);
+            central.extend(&b"PK\x01\x02"[..]);
+            central.extend([20, 0]);
+            central.extend(&common(b"")[..]);
+            central.extend([0u8; 10]);
+            central.extend(offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let cd_offset = out.len() as u32;
+        let cd_len = central.len() as u32;
+        out.extend(central);
+        out.extend(&b"PK\x05\x06"[..]);
+        out.extend([0u8; 4]);
+        out.extend((entries.len() as u16).to_le_bytes());
+        out.extend((entries.len() as u16).to_le_bytes());
+        out.extend(cd_len.to_le_bytes());
+        out.extend(cd_offset.to_le_bytes());
+        out.extend([0u8; 2]);
+        out
+    }
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustcurl-extract-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn safe_join_rejects_escapes() {
+        let dest = Path::new("/out");
+        assert_eq!(safe_join(dest, "a/b.txt"), Some(PathBuf::from("/out/a/b.txt")));
+        assert_eq!(safe_join(dest, "./a/../b"), Some(PathBuf::from("/out/b")));
+        assert_eq!(safe_join(dest, "../etc/passwd"), None);
+        assert_eq!(safe_join(dest, "a/../../x"), None);
+        assert_eq!(safe_join(dest, "/etc/passwd"), None);
+        assert_eq!(safe_join(dest, "..\\x"), None);
+        // `.` changes nothing: `./` is the directory itself
+        assert_eq!(safe_join(dest, "."), Some(PathBuf::from("/out")));
+        assert_eq!(safe_join(dest, "./"), Some(PathBuf::from("/out")));
+        assert_eq!(safe_join(dest, "a/./b"), Some(PathBuf::from("/out/a/b")));
+        assert_eq!(safe_join(dest, "./.."), None);
+    }
+
+    #[test]
+    fn extracts_dot_slash_tarballs() {
+        let dir = temp_dir("dot");
+        let archive = dir.join("pkg.tar");
+        fs::write(&archive, tar(&[("./", b'5', b""), ("./readme", b'0', b"hi")])).unwrap();
+        let written = extract(&archive, &dir.join("out"), false).unwrap();
+        assert_eq!(written, vec![dir.join("out/readme")]);
+        let archive = dir.join("bad.tar");
+        fs::write(&archive, tar(&[(".", b'0', b"file over the directory")])).unwrap();
+        assert!(extract(&archive, &dir.join("out"), true).unwrap_err().to_string().contains("unsafe entry path"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn existing_files_kept_unless_overwriting() {
+        let dir = temp_dir("exists");
+        let archive = dir.join("pkg.zip");
+        fs::write(&archive, zip(&[("new.txt", b"new"), ("keep.txt", b"new")])).unwrap();
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        fs::write(out.join("keep.txt"), b"old").unwrap();
+        let err = extract(&archive, &out, false).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+        assert!(!out.join("new.txt").exists());
+        assert_eq!(fs::read(out.join("keep.txt")).unwrap(), b"old");
+
+        extract(&archive, &out, true).unwrap();
+        assert_eq!(fs::read(out.join("keep.txt")).unwrap(), b"new");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn expansion_is_capped() {
+        let dir = temp_dir("bomb");
+        let zeros = vec![0u8; 64 * 1024];
+        let packed = miniz_oxide::deflate::compress_to_vec(&zeros, 9);
+        assert!(packed.len() < 1024);
+        assert!(inflate(&packed, 1024, 1024).unwrap_err().contains("more than 1024 bytes"));
+
+        let archive = dir.join("bomb.tgz");
+        let mut gz = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+        gz.extend(miniz_oxide::deflate::compress_to_vec(&tar(&[("big", b'0', &zeros)]), 9));
+        gz.extend([0u8; 8]);
+        fs::write(&archive, gz).unwrap();
+        let err = extract_with_limit(&archive, &dir.join("out"), false, 4096).unwrap_err();
+        assert!(err.to_string().contains("archive expands to more than 4096 bytes"), "{err}");
+
+        // The cap is on the whole archive, not each entry
+        let archive = dir.join("many.zip");
+        fs::write(&archive, zip(&[("a", &zeros[..3000]), ("b", &zeros[..3000])])).unwrap();
+        let err = extract_with_limit(&archive, &dir.join("out"), false, 4096).unwrap_err();
+        assert!(err.to_string().contains("more than 4096 bytes"), "{err}");
+        assert!(!dir.join("out").exists());
+        extract_with_limit(&archive, &dir.join("out"), false, 6000).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn detects_kind() {
+        assert_eq!(ArchiveKind::detect("x", &gzip(b"")), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::detect("x", &zip(&[])), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::detect("x", &tar(&[("a", b'0', b"")])), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::detect("pkg.TGZ", b""), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::detect("notes.txt", b"hello"), None);
+    }
+
+    #[test]
+    fn reads_tar_entries() {
+        let long = "d/".to_string() + &"n".repeat(120);
+        let data = tar(&[
+            ("d/", b'5', b""),
+            ("d/a.txt", b'0', b"alpha"),
+            ("d/link", b'2', b""),
+            ("././@LongLink", b'L', long.as_bytes()),
+            ("d/truncated-name", b'0', b"long"),
+        ]);
+        let entries = read_tar(&data).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].data, b"alpha");
+        assert_eq!(entries[2].path, long);
+        assert!(read_tar(&data[..1026]).is_err());
+    }
+
+    #[test]
+    fn reads_zip_entries() {
+        let entries = read_zip(&zip(&[("dir/", b""), ("dir/b.txt", b"bravo")]), MAX_EXTRACTED).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].path, "dir/b.txt");
+        assert_eq!(entries[1].data, b"bravo");
+        assert!(read_zip(b"PK\x03\x04 not really", MAX_EXTRACTED).is_err());
+    }
+
+    #[test]
+    fn extracts_tar_gz() {
+        let dir = temp_dir("tgz");
+        let archive = dir.join("pkg.tar.gz");
+        fs::write(&archive, gzip(&tar(&[("pkg/readme", b'0', b"hi")]))).unwrap();
+        let written = extract(&archive, &dir.join("out"), false).unwrap();
+        assert_eq!(written, vec![dir.join("out/pkg/readme")]);
+        assert_eq!(fs::read(&written[0]).unwrap(), b"hi");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn traversal_writes_nothing() {
+        let dir = temp_dir("evil");
+        let archive = dir.join("evil.zip");
+        fs::write(&archive, zip(&[("ok.txt", b"fine"), ("../escape.txt", b"bad")])).unwrap();
+        let err = extract(&archive, &dir.join("out"), false).unwrap_err();
+        assert_eq!(err.code(), "extract");
+        assert!(err.to_string().contains("../escape.txt"));
+        assert!(!dir.join("out/ok.txt").exists());
+        assert!(!dir.join("escape.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn not_an_archive() {
+        let dir = temp_dir("plain");
+        let file = dir.join("page");
+        fs::write(&file, b"<html></html>").unwrap();
+        assert!(extract(&file, &dir, false).unwrap_err().to_string().contains("not a recognized archive"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
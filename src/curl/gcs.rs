@@ -0,0 +1,119 @@
+// src/curl/gcs.rs
+
+//! `gs://BUCKET/OBJECT` URLs: sent to Cloud Storage as a V4 signed URL,
+//! and with `--presign` printed as one to hand out. Signing uses an HMAC
+//! key, from `-u ACCESS_ID:SECRET` or `GOOGLE_HMAC_ACCESS_ID` and
+//! `GOOGLE_HMAC_SECRET`; service account (RSA) keys are not supported.
+
+use std::time::{Duration, SystemTime};
+
+use super::config::{RequestConfig, urlencode_bytes};
+use super::encoding::{hex, hmac_sha256 as hmac, sha256_hex};
+use super::error::RequestError;
+use super::provenance::rfc3339;
+use super::s3::{self, Object, canonical_query};
+
+pub const HOST: &str = "storage.googleapis.com";
+/// Longest validity Cloud Storage accepts for a signed URL.
+pub const MAX_EXPIRES: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Validity of the URLs rustcurl signs to send a request itself.
+const REQUEST_EXPIRES: Duration = Duration::from_secs(15 * 60);
+
+pub fn is_gcs(url: &str) -> bool {
+    s3::has_scheme(url, "gs")
+}
+
+/// The HMAC access ID and secret for signing.
+fn credentials(config: &RequestConfig) -> Option<(String, String)> {
+    if let (Some(id), Some(secret)) = (&config.username, &config.password) {
+        return Some((id.clone(), secret.clone()));
+    }
+    Some((config.env_var("GOOGLE_HMAC_ACCESS_ID")?, config.env_var("GOOGLE_HMAC_SECRET")?))
+}
+
+/// A signed `https://storage.googleapis.com/...` URL for `config`'s
+/// method and `gs://` URL, valid for `expires` from `time`.
+pub fn signed_url(config: &RequestConfig, expires: Duration, time: SystemTime) -> Result<String, String> {
+    let object = s3::parse_as("gs", &config.url)?;
+    if expires > MAX_EXPIRES {
+        return Err("Cloud Storage signed URLs last at most 7 days".to_string());
+    }
+    let (access_id, secret) =
+        credentials(config).ok_or("no HMAC key; use -u ACCESS_ID:SECRET or set GOOGLE_HMAC_ACCESS_ID")?;
+    let date_time: String = rfc3339(time).chars().filter(|c| *c != '-' && *c != ':').collect();
+    let date = &date_time[..8];
+    let scope = format!("{date}/auto/storage/goog4_request");
+    let path = object_path(&object);
+    let query = canonical_query(&format!(
+        "{}&X-Goog-Algorithm=GOOG4-HMAC-SHA256&X-Goog-Credential={}&X-Goog-Date={date_time}\
+         &X-Goog-Expires={}&X-Goog-SignedHeaders=host",
+        object.query,
+        urlencode_bytes(format!("{access_id}/{scope}").as_bytes()),
+        expires.as_secs()
+    ));
+    let canonical_request = format!(
+        "{}\n{path}\n{query}\nhost:{HOST}\n\nhost\nUNSIGNED-PAYLOAD",
+        config.method.as_str()
+    );
+    let string_to_sign = format!(
+        "GOOG4-HMAC-SHA256\n{date_time}\n{scope}\n{}",
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let key = ["auto", "storage", "goog4_request"].iter().fold(
+        hmac(format!("GOOG4{secret}").as_bytes(), date.as_bytes()),
+        |key, part| hmac(&key, part.as_bytes()),
+    );
+    let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+    Ok(format!("https://{HOST}{path}?{query}&X-Goog-Signature={signature}"))
+}
+
+fn object_path(object: &Object) -> String {
+    let key: Vec<String> = object.key.split('/').map(|s| urlencode_bytes(s.as_bytes())).collect();
+    format!("/{}/{}", urlencode_bytes(object.bucket.as_bytes()), key.join("/"))
+}
+
+/// `config` sent to its signed URL instead. The key is in the signature,
+/// so it does not go out as Basic auth as well.
+pub fn request(config: &RequestConfig) -> Result<RequestConfig, RequestError> {
+    let url = signed_url(config, REQUEST_EXPIRES, SystemTime::now()).map_err(|message| RequestError::Config {
+        option: "gs://".to_string(),
+        message,
+    })?;
+    let mut request = config.clone();
+    request.url = url;
+    request.username = None;
+    request.password = None;
+    request.netrc = false;
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::config::Method;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn signs_url() {
+        let config = RequestConfig::new("gs://bkt/dir/a b.txt").username("GOOG1EXAMPLE").password("secret");
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let url = signed_url(&config, Duration::from_secs(600), time).unwrap();
+        assert!(url.starts_with("https://storage.googleapis.com/bkt/dir/a%20b.txt?X-Goog-Algorithm=GOOG4-HMAC-SHA256"));
+        assert!(url.contains("&X-Goog-Credential=GOOG1EXAMPLE%2F20231114%2Fauto%2Fstorage%2Fgoog4_request&"));
+        assert!(url.contains("&X-Goog-Date=20231114T221320Z&X-Goog-Expires=600&"));
+        // The signature covers the method
+        let put = signed_url(&config.clone().method(Method::Put), Duration::from_secs(600), time).unwrap();
+        assert_ne!(url, put);
+        assert!(signed_url(&config, MAX_EXPIRES + Duration::from_secs(1), time).is_err());
+    }
+
+    #[test]
+    fn request_goes_to_signed_url() {
+        let config = RequestConfig::new("gs://bkt/o").username("id").password("s").method(Method::Put);
+        let request = request(&config).unwrap();
+        assert!(request.url.starts_with("https://storage.googleapis.com/bkt/o?"));
+        assert!(request.username.is_none());
+        let missing = RequestConfig::new("gs://bkt/o").env(crate::curl::env::EnvSource::Disabled);
+        assert_eq!(super::request(&missing).unwrap_err().code(), "config");
+    }
+}
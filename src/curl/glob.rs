@@ -0,0 +1,223 @@
+// src/curl/glob.rs
+
+//! curl-style URL globbing: `{a,b,c}` sets and `[1-10]`, `[001-100:5]` or
+//! `[a-z]` ranges expand one URL into many, the leftmost glob varying
+//! slowest. In an `-o` name, `#1` stands for the first glob's value in
+//! each URL, `#2` for the second and so on. `\[`, `\]`, `\{` and `\}` are
+//! literal, as is a bracketed IPv6 host. `--globoff` turns all of it off.
+
+use std::net::Ipv6Addr;
+
+/// Patterns expanding to more URLs than this are refused.
+pub const MAX_URLS: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Values(Vec<String>),
+}
+
+/// One URL of an expansion and the value each glob took in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expanded {
+    pub url: String,
+    pub values: Vec<String>,
+}
+
+/// All URLs `pattern` stands for, in order. A pattern without globs
+/// expands to itself.
+pub fn expand(pattern: &str) -> Result<Vec<Expanded>, String> {
+    let parts = parse(pattern)?;
+    let sets: Vec<&Vec<String>> = parts
+        .iter()
+        .filter_map(|p| match p {
+            Part::Values(values) => Some(values),
+            Part::Text(_) => None,
+        })
+        .collect();
+    let total = sets
+        .iter()
+        .try_fold(1usize, |n, set| n.checked_mul(set.len()).filter(|n| *n <= MAX_URLS))
+        .ok_or_else(|| format!("expands to more than {MAX_URLS} URLs"))?;
+
+    let mut expanded = Vec::with_capacity(total);
+    for mut index in 0..total {
+        // Mixed-radix digits of `index`, the last glob varying fastest
+        let mut picks = vec![0; sets.len()];
+        for (pick, set) in picks.iter_mut().zip(&sets).rev() {
+            *pick = index % set.len();
+            index /= set.len();
+        }
+        let mut url = String::new();
+        let mut values = Vec::with_capacity(sets.len());
+        let mut picks = picks.into_iter();
+        for part in &parts {
+            match part {
+                Part::Text(text) => url.push_str(text),
+                Part::Values(set) => {
+                    let value = &set[picks.next().unwrap_or(0)];
+                    url.push_str(value);
+                    values.push(value.clone());
+                }
+            }
+        }
+        expanded.push(Expanded { url, values });
+    }
+    Ok(expanded)
+}
+
+/// `template` with each `#N` replaced by the N-th glob value. A `#` not
+/// followed by the number of a glob is kept as is.
+pub fn output_name(template: &str, values: &[String]) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(at) = rest.find('#') {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let digits = after.find(|c: char| !c.is_ascii_digit()).unwrap_or(after.len());
+        match after[..digits].parse::<usize>().ok().and_then(|n| values.get(n.checked_sub(1)?)) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[at..at + 1 + digits]),
+        }
+        rest = &after[digits..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse(pattern: &str) -> Result<Vec<Part>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\'
+            && let Some(next) = rest[1..].chars().next().filter(|n| "[]{}".contains(*n))
+        {
+            text.push(next);
+            rest = &rest[2..];
+            continue;
+        }
+        let values = match c {
+            '{' => {
+                let end = rest.find('}').ok_or("unmatched '{'")?;
+                let inner = &rest[1..end];
+                if inner.contains(['{', '[']) {
+                    return Err("nested globs are not supported".to_string());
+                }
+                rest = &rest[end + 1..];
+                inner.split(',').map(str::to_string).collect()
+            }
+            '[' => {
+                let end = rest.find(']').ok_or("unmatched '['")?;
+                let inner = &rest[1..end];
+                if is_ipv6(inner) {
+                    text.push_str(&rest[..=end]);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                rest = &rest[end + 1..];
+                range(inner)?
+            }
+            '}' | ']' => return Err(format!("unmatched '{c}'")),
+            _ => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+        };
+        if !text.is_empty() {
+            parts.push(Part::Text(std::mem::take(&mut text)));
+        }
+        parts.push(Part::Values(values));
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+/// `[::1]` or `[fe80::1%25eth0]` in a host, rather than a range.
+fn is_ipv6(inner: &str) -> bool {
+    inner.split('%').next().is_some_and(|addr| addr.parse::<Ipv6Addr>().is_ok())
+}
+
+/// The values of a `[from-to]` or `[from-to:step]` range, numeric or a
+/// single letter at each end. A leading zero on `from` pads every number
+/// to its width.
+fn range(spec: &str) -> Result<Vec<String>, String> {
+    let bad = || format!("bad range [{spec}]");
+    let (span, step) = match spec.split_once(':') {
+        Some((span, step)) => (span, step.parse::<usize>().ok().filter(|s| *s > 0).ok_or_else(bad)?),
+        None => (spec, 1),
+    };
+    let (from, to) = span.split_once('-').ok_or_else(bad)?;
+    let letters = |s: &str| {
+        let mut chars = s.chars();
+        chars.next().filter(|c| c.is_ascii_alphabetic() && chars.next().is_none())
+    };
+    if let (Some(from), Some(to)) = (letters(from), letters(to)) {
+        if from > to || from.is_ascii_lowercase() != to.is_ascii_lowercase() {
+            return Err(bad());
+        }
+        return Ok((from..=to).step_by(step).map(String::from).collect());
+    }
+    let (first, last) = match (from.parse::<u64>(), to.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => (first, last),
+        _ => return Err(bad()),
+    };
+    if (last - first) / step as u64 >= MAX_URLS as u64 {
+        return Err(format!("range [{spec}] has more than {MAX_URLS} values"));
+    }
+    let width = if from.len() > 1 && from.starts_with('0') { from.len() } else { 0 };
+    Ok((first..=last).step_by(step).map(|n| format!("{n:0width$}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(pattern: &str) -> Vec<String> {
+        expand(pattern).unwrap().into_iter().map(|e| e.url).collect()
+    }
+
+    #[test]
+    fn expands_sets_and_ranges() {
+        assert_eq!(urls("https://x.com/a"), ["https://x.com/a"]);
+        assert_eq!(
+            urls("https://{a,b}.x.com/p[1-2]"),
+            ["https://a.x.com/p1", "https://a.x.com/p2", "https://b.x.com/p1", "https://b.x.com/p2"]
+        );
+        assert_eq!(urls("x/[08-11]"), ["x/08", "x/09", "x/10", "x/11"]);
+        assert_eq!(urls("x/[0-10:5]"), ["x/0", "x/5", "x/10"]);
+        assert_eq!(urls("x/[a-e:2]"), ["x/a", "x/c", "x/e"]);
+        assert_eq!(urls("x/{,.bak}"), ["x/", "x/.bak"]);
+
+        let second = &expand("x/{a,b}/[1-3]").unwrap()[4];
+        assert_eq!(second.url, "x/b/2");
+        assert_eq!(second.values, ["b", "2"]);
+    }
+
+    #[test]
+    fn literal_brackets() {
+        assert_eq!(urls("http://[::1]:8080/a"), ["http://[::1]:8080/a"]);
+        assert_eq!(urls("http://[fe80::1%25eth0]/"), ["http://[fe80::1%25eth0]/"]);
+        assert_eq!(urls(r"x/?a\[\]=1&b=\{\}"), ["x/?a[]=1&b={}"]);
+    }
+
+    #[test]
+    fn rejects_bad_globs() {
+        for bad in ["x/[1-", "x/{a,b", "x/a]", "x/[5-1]", "x/[1-x]", "x/[a-Z]", "x/[1-3:0]", "x/{a,[1-2]}"] {
+            assert!(expand(bad).is_err(), "{bad}");
+        }
+        assert!(expand("x/[1-1000000]").is_err());
+        assert!(expand("x/[1-1000][1-1000]").is_err());
+    }
+
+    #[test]
+    fn numbered_output_names() {
+        let values = ["a".to_string(), "07".to_string()];
+        assert_eq!(output_name("page_#2_#1.html", &values), "page_07_a.html");
+        assert_eq!(output_name("#3 #0 # ##1", &values), "#3 #0 # #a");
+        assert_eq!(output_name("plain.html", &[]), "plain.html");
+    }
+}
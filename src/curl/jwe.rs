@@ -0,0 +1,473 @@
+// src/curl/jwe.rs
+
+//! `--encrypt-jwe KEY` and `--decrypt-jwe KEY`: JSON Web Encryption (RFC
+//! 7516) in compact serialization, for payment and identity APIs that
+//! require payloads encrypted end to end. The `-d` body is sent as a JWE;
+//! a response body that is one is decrypted for display and anything else
+//! is left as it is. Content is encrypted in-process (RustCrypto's `aes`,
+//! `aes-gcm`, `cbc` and `hmac`) with A128GCM to A256GCM or A128CBC-HS256
+//! to A256CBC-HS512. With a PEM key the content key is wrapped with
+//! RSA-OAEP or RSA-OAEP-256 by `openssl pkeyutl`; with an `oct` JWK, the
+//! key itself is the content key (`dir`). ECDH-ES and AES key wrap are
+//! not supported.
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::{BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::generic_array::typenum::Unsigned;
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{Aes128Gcm, Aes256Gcm, AesGcm};
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::config::RequestConfig;
+use super::encoding::{base64url, unbase64url};
+use super::error::RequestError;
+use super::response::Response;
+
+/// A key from a `--encrypt-jwe` or `--decrypt-jwe` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key {
+    /// A PEM RSA key, public key or certificate, used through `openssl`.
+    Pem { path: String, text: String },
+    /// A symmetric JWK (`"kty": "oct"`).
+    Oct { k: Vec<u8>, kid: Option<String> },
+}
+
+impl Key {
+    pub fn load(path: &str) -> Result<Key, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        if text.contains("-----BEGIN ") {
+            return Ok(Key::Pem {
+                path: path.to_string(),
+                text,
+            });
+        }
+        let jwk: Value = serde_json::from_str(&text).map_err(|_| format!("{path} is neither a PEM key nor a JWK"))?;
+        match jwk["kty"].as_str() {
+            Some("oct") => Ok(Key::Oct {
+                k: jwk["k"].as_str().and_then(unbase64url).ok_or(format!("{path}: the JWK has no valid k"))?,
+                kid: jwk["kid"].as_str().map(str::to_string),
+            }),
+            Some(kty) => Err(format!("{path}: {kty} JWKs are not supported; give the key as PEM")),
+            None => Err(format!("{path}: the JWK has no kty")),
+        }
+    }
+}
+
+/// Content encryption algorithms (RFC 7518 section 5).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enc {
+    A128Gcm,
+    A192Gcm,
+    A256Gcm,
+    A128CbcHs256,
+    A192CbcHs384,
+    A256CbcHs512,
+}
+
+impl Enc {
+    const ALL: [Enc; 6] = [
+        Enc::A128Gcm,
+        Enc::A192Gcm,
+        Enc::A256Gcm,
+        Enc::A128CbcHs256,
+        Enc::A192CbcHs384,
+        Enc::A256CbcHs512,
+    ];
+
+    pub fn parse(name: &str) -> Option<Enc> {
+        Enc::ALL.into_iter().find(|enc| enc.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Enc::A128Gcm => "A128GCM",
+            Enc::A192Gcm => "A192GCM",
+            Enc::A256Gcm => "A256GCM",
+            Enc::A128CbcHs256 => "A128CBC-HS256",
+            Enc::A192CbcHs384 => "A192CBC-HS384",
+            Enc::A256CbcHs512 => "A256CBC-HS512",
+        }
+    }
+
+    fn key_len(self) -> usize {
+        match self {
+            Enc::A128Gcm => 16,
+            Enc::A192Gcm => 24,
+            Enc::A256Gcm | Enc::A128CbcHs256 => 32,
+            Enc::A192CbcHs384 => 48,
+            Enc::A256CbcHs512 => 64,
+        }
+    }
+
+    fn gcm(self) -> bool {
+        matches!(self, Enc::A128Gcm | Enc::A192Gcm | Enc::A256Gcm)
+    }
+
+    /// The algorithm a `dir` key of `len` bytes is used with; GCM where
+    /// the length allows either.
+    fn for_key(len: usize) -> Option<Enc> {
+        Enc::ALL.into_iter().find(|enc| enc.key_len() == len)
+    }
+
+    /// Ciphertext and tag for `plaintext`.
+    fn seal(self, key: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            Enc::A128Gcm => gcm_seal::<Aes128Gcm>(key, iv, aad, plaintext),
+            Enc::A192Gcm => gcm_seal::<AesGcm<Aes192, U12>>(key, iv, aad, plaintext),
+            Enc::A256Gcm => gcm_seal::<Aes256Gcm>(key, iv, aad, plaintext),
+            Enc::A128CbcHs256 => cbc_seal::<Aes128, Hmac<Sha256>>(key, iv, aad, plaintext),
+            Enc::A192CbcHs384 => cbc_seal::<Aes192, Hmac<Sha384>>(key, iv, aad, plaintext),
+            Enc::A256CbcHs512 => cbc_seal::<Aes256, Hmac<Sha512>>(key, iv, aad, plaintext),
+        }
+    }
+
+    /// The plaintext, if `tag` authenticates `ciphertext` and `aad`.
+    fn open(self, key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Enc::A128Gcm => gcm_open::<Aes128Gcm>(key, iv, aad, ciphertext, tag),
+            Enc::A192Gcm => gcm_open::<AesGcm<Aes192, U12>>(key, iv, aad, ciphertext, tag),
+            Enc::A256Gcm => gcm_open::<Aes256Gcm>(key, iv, aad, ciphertext, tag),
+            Enc::A128CbcHs256 => cbc_open::<Aes128, Hmac<Sha256>>(key, iv, aad, ciphertext, tag),
+            Enc::A192CbcHs384 => cbc_open::<Aes192, Hmac<Sha384>>(key, iv, aad, ciphertext, tag),
+            Enc::A256CbcHs512 => cbc_open::<Aes256, Hmac<Sha512>>(key, iv, aad, ciphertext, tag),
+        }
+        .ok_or_else(|| "the authentication tag does not match; wrong key or tampered token".to_string())
+    }
+}
+
+/// `config` with its body replaced by a JWE for `encrypt_jwe`, or `None`
+/// when it is unset. `Content-Type` becomes `application/jose` unless
+/// `-H` sets one.
+pub fn seal(config: &RequestConfig) -> Result<Option<RequestConfig>, RequestError> {
+    let Some(ref path) = config.encrypt_jwe else {
+        return Ok(None);
+    };
+    let err = |message: String| RequestError::Config {
+        option: "--encrypt-jwe".to_string(),
+        message,
+    };
+    if config.upload_file.is_some() {
+        return Err(err("a -T upload is streamed and cannot be encrypted; send the file with -d @FILE".to_string()));
+    }
+    let body = config.body().ok_or_else(|| err("there is no -d body to encrypt".to_string()))?;
+    let token = encrypt(&body, &Key::load(path).map_err(err)?).map_err(err)?;
+    let mut sealed = config.clone();
+    sealed.encrypt_jwe = None;
+    sealed.form_fields.clear();
+    sealed.crlf = false;
+    sealed.data = Some(token.into_bytes());
+    if !config.has_header("Content-Type") {
+        sealed = sealed.header("Content-Type: application/jose");
+    }
+    Ok(Some(sealed))
+}
+
+/// Decrypt `response` with the `decrypt_jwe` key if its body, in memory
+/// or in the file it was saved to, is a JWE. Returns the token's `enc`
+/// when it was.
+pub fn open(key_path: &str, response: &mut Response) -> Result<Option<Enc>, RequestError> {
+    let err = |message: String| RequestError::Config {
+        option: "--decrypt-jwe".to_string(),
+        message,
+    };
+    let body = response.received_body().into_owned();
+    let Some(token) = Compact::parse(&body) else {
+        return Ok(None);
+    };
+    let (enc, plaintext) = token.decrypt(&Key::load(key_path).map_err(err)?).map_err(err)?;
+    match response.saved_as {
+        Some(ref path) if response.body.is_empty() => {
+            fs::write(path, plaintext).map_err(|source| RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            })?;
+        }
+        _ => response.body = plaintext,
+    }
+    Ok(Some(enc))
+}
+
+/// `plaintext` as a compact JWE for `key`: RSA-OAEP-256 with A256GCM for
+/// a PEM key, `dir` with the algorithm its length calls for otherwise.
+pub fn encrypt(plaintext: &[u8], key: &Key) -> Result<String, String> {
+    let (mut header, enc, cek) = match key {
+        Key::Pem { .. } => (json!({"alg": "RSA-OAEP-256"}), Enc::A256Gcm, random(32)?),
+        Key::Oct { k, kid } => {
+            let enc = Enc::for_key(k.len()).ok_or(format!("a {}-byte key fits no JWE algorithm", k.len()))?;
+            let mut header = json!({"alg": "dir"});
+            if let Some(kid) = kid {
+                header["kid"] = json!(kid);
+            }
+            (header, enc, k.clone())
+        }
+    };
+    header["enc"] = json!(enc.name());
+    let protected = base64url(header.to_string().as_bytes());
+    let encrypted_key = match key {
+        Key::Pem { path, text } => {
+            let form = if text.contains("-----BEGIN CERTIFICATE") {
+                &["-certin"][..]
+            } else if text.contains("PUBLIC KEY-----") {
+                &["-pubin"][..]
+            } else {
+                &[][..]
+            };
+            pkeyutl("-encrypt", path, form, "RSA-OAEP-256", &cek)?
+        }
+        Key::Oct { .. } => Vec::new(),
+    };
+    let iv = random(if enc.gcm() { 12 } else { 16 })?;
+    let (ciphertext, tag) = enc.seal(&cek, &iv, protected.as_bytes(), plaintext);
+    Ok([protected, base64url(&encrypted_key), base64url(&iv), base64url(&ciphertext), base64url(&tag)].join("."))
+}
+
+/// A compact JWE, split into its parts.
+#[derive(Debug)]
+pub struct Compact {
+    protected: String,
+    header: Value,
+    encrypted_key: Vec<u8>,
+    iv: Vec<u8>,
+    ciphertext: Vec<u8>,
+    tag: Vec<u8>,
+}
+
+impl Compact {
+    /// The JWE `body` is, optionally as a JSON string: five base64url
+    /// parts whose protected header names an `enc`.
+    pub fn parse(body: &[u8]) -> Option<Compact> {
+        let text = std::str::from_utf8(body).ok()?.trim();
+        let text = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text);
+        let parts: Vec<&str> = text.split('.').collect();
+        let [protected, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+            return None;
+        };
+        let header: Value = serde_json::from_slice(&unbase64url(protected)?).ok()?;
+        header["enc"].as_str()?;
+        Some(Compact {
+            protected: protected.to_string(),
+            header,
+            encrypted_key: unbase64url(encrypted_key)?,
+            iv: unbase64url(iv)?,
+            ciphertext: unbase64url(ciphertext)?,
+            tag: unbase64url(tag)?,
+        })
+    }
+
+    /// The content encryption algorithm and the plaintext.
+    pub fn decrypt(&self, key: &Key) -> Result<(Enc, Vec<u8>), String> {
+        let alg = self.header["alg"].as_str().unwrap_or_default();
+        let enc_name = self.header["enc"].as_str().unwrap_or_default();
+        let enc = Enc::parse(enc_name).ok_or(format!("unsupported enc {enc_name}"))?;
+        let cek = match (alg, key) {
+            ("dir", Key::Oct { k, .. }) => k.clone(),
+            ("RSA-OAEP" | "RSA-OAEP-256", Key::Pem { path, .. }) => {
+                pkeyutl("-decrypt", path, &[], alg, &self.encrypted_key)?
+            }
+            ("dir", Key::Pem { .. }) => return Err("alg dir needs an oct JWK, not a PEM key".to_string()),
+            ("RSA-OAEP" | "RSA-OAEP-256", Key::Oct { .. }) => return Err(format!("alg {alg} needs a PEM RSA key")),
+            _ => return Err(format!("unsupported alg {alg}; RSA-OAEP, RSA-OAEP-256 and dir are")),
+        };
+        if cek.len() != enc.key_len() {
+            return Err(format!("{enc_name} needs a {}-byte key, got {}", enc.key_len(), cek.len()));
+        }
+        let plaintext = enc.open(&cek, &self.iv, self.protected.as_bytes(), &self.ciphertext, &self.tag)?;
+        match self.header["zip"].as_str() {
+            None => Ok((enc, plaintext)),
+            Some("DEF") => miniz_oxide::inflate::decompress_to_vec(&plaintext)
+                .map(|inflated| (enc, inflated))
+                .map_err(|e| format!("the payload does not inflate: {e:?}")),
+            Some(zip) => Err(format!("unsupported zip {zip}")),
+        }
+    }
+}
+
+/// Wrap (`-encrypt`) or unwrap (`-decrypt`) a content key with RSA-OAEP
+/// through `openssl pkeyutl`; `form` says how `path` holds the key.
+fn pkeyutl(op: &str, path: &str, form: &[&str], alg: &str, input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut command = Command::new("openssl");
+    command.args(["pkeyutl", op, "-inkey", path]).args(form);
+    command.args(["-pkeyopt", "rsa_padding_mode:oaep"]);
+    if alg == "RSA-OAEP-256" {
+        command.args(["-pkeyopt", "rsa_oaep_md:sha256", "-pkeyopt", "rsa_mgf1_md:sha256"]);
+    }
+    let output = run(&mut command, input)?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("openssl could not {} the content key: {}", &op[1..], stderr.trim()))
+    }
+}
+
+fn run(command: &mut Command, input: &[u8]) -> Result<std::process::Output, String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run openssl: {e}"))?;
+    // A content key fits in the pipe buffer, so this cannot block
+    let written = child.stdin.take().map(|mut stdin| stdin.write_all(input));
+    let output = child.wait_with_output().map_err(|e| format!("could not run openssl: {e}"))?;
+    written.transpose().map_err(|e| format!("could not run openssl: {e}"))?;
+    Ok(output)
+}
+
+/// `n` bytes from the operating system's secure generator (`Rng` is
+/// not one).
+fn random(n: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0; n];
+    getrandom::fill(&mut bytes).map_err(|e| format!("no secure random source: {e}"))?;
+    Ok(bytes)
+}
+
+fn gcm_seal<C: AeadInPlace + KeyInit>(key: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let cipher = C::new_from_slice(key).expect("the key length matches the algorithm");
+    let mut buffer = plaintext.to_vec();
+    let tag = cipher
+        .encrypt_in_place_detached(GenericArray::from_slice(iv), aad, &mut buffer)
+        .expect("the plaintext is within GCM's limit");
+    (buffer, tag.to_vec())
+}
+
+fn gcm_open<C>(key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>>
+where
+    C: AeadInPlace + KeyInit,
+{
+    if iv.len() != C::NonceSize::USIZE || tag.len() != C::TagSize::USIZE {
+        return None;
+    }
+    let cipher = C::new_from_slice(key).ok()?;
+    let mut buffer = ciphertext.to_vec();
+    cipher
+        .decrypt_in_place_detached(GenericArray::from_slice(iv), aad, &mut buffer, GenericArray::from_slice(tag))
+        .ok()?;
+    Some(buffer)
+}
+
+/// RFC 7518 section 5.2.2.1: AES-CBC, then an HMAC over the AAD, IV,
+/// ciphertext and AAD length, cut to the MAC key's length.
+fn cbc_seal<C, M>(key: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, Vec<u8>)
+where
+    C: BlockEncryptMut + BlockCipher + KeyInit,
+    M: Mac + KeyInit,
+{
+    let (mac_key, enc_key) = key.split_at(key.len() / 2);
+    let ciphertext = cbc::Encryptor::<C>::new_from_slices(enc_key, iv)
+        .expect("the key and IV lengths match the algorithm")
+        .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+    let tag = cbc_mac::<M>(mac_key, iv, aad, &ciphertext).finalize().into_bytes();
+    (ciphertext, tag[..mac_key.len()].to_vec())
+}
+
+fn cbc_open<C, M>(key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockDecryptMut + BlockCipher + KeyInit,
+    M: Mac + KeyInit,
+{
+    let (mac_key, enc_key) = key.split_at(key.len() / 2);
+    // verify_truncated_left accepts any shorter tag, so the length is checked first
+    if tag.len() != mac_key.len() || cbc_mac::<M>(mac_key, iv, aad, ciphertext).verify_truncated_left(tag).is_err() {
+        return None;
+    }
+    cbc::Decryptor::<C>::new_from_slices(enc_key, iv).ok()?.decrypt_padded_vec_mut::<Pkcs7>(ciphertext).ok()
+}
+
+fn cbc_mac<M: Mac + KeyInit>(mac_key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8]) -> M {
+    let mut mac = <M as KeyInit>::new_from_slice(mac_key).expect("HMAC takes keys of any length");
+    for part in [aad, iv, ciphertext, &(aad.len() as u64 * 8).to_be_bytes()] {
+        mac.update(part);
+    }
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::encoding;
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        encoding::unhex(hex).unwrap()
+    }
+
+    #[test]
+    fn gcm_known_answer() {
+        // The GCM specification's test case 4
+        let key = unhex("feffe9928665731c6d6a8f9467308308");
+        let iv = unhex("cafebabefacedbaddecaf888");
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = unhex(concat!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+            "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        ));
+        let (ciphertext, tag) = Enc::A128Gcm.seal(&key, &iv, &aad, &plaintext);
+        let expected = concat!(
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+            "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+        );
+        assert_eq!(ciphertext, unhex(expected));
+        assert_eq!(tag, unhex("5bc94fbc3221a5db94fae95ae7121a47"));
+        assert_eq!(Enc::A128Gcm.open(&key, &iv, &aad, &ciphertext, &tag).unwrap(), plaintext);
+        assert!(Enc::A128Gcm.open(&key, &iv, b"other", &ciphertext, &tag).is_err());
+    }
+
+    #[test]
+    fn cbc_hmac_known_answer() {
+        // RFC 7518 appendix B.1, AES_128_CBC_HMAC_SHA_256
+        let key: Vec<u8> = (0..32).collect();
+        let plaintext = b"A cipher system must not be required to be secret, and it must be able to fall into \
+                          the hands of the enemy without inconvenience";
+        let iv = unhex("1af38c2dc2b96ffdd86694092341bc04");
+        let aad = b"The second principle of Auguste Kerckhoffs";
+        let (ciphertext, tag) = Enc::A128CbcHs256.seal(&key, &iv, aad, plaintext);
+        assert_eq!(ciphertext[..16], unhex("c80edfa32ddf39d5ef00c0b468834279"));
+        assert_eq!(tag, unhex("652c3fa36b0a7c5b3219fab3a30bc1c4"));
+        assert_eq!(Enc::A128CbcHs256.open(&key, &iv, aad, &ciphertext, &tag).unwrap(), plaintext);
+        let mut tampered = ciphertext.clone();
+        tampered[0] ^= 1;
+        assert!(Enc::A128CbcHs256.open(&key, &iv, aad, &tampered, &tag).is_err());
+    }
+
+    #[test]
+    fn dir_round_trip() {
+        for len in [16, 32, 48, 64] {
+            let key = Key::Oct {
+                k: vec![7; len],
+                kid: Some("k1".to_string()),
+            };
+            let token = encrypt(b"{\"pan\":\"4111111111111111\"}", &key).unwrap();
+            let parsed = Compact::parse(format!("\"{token}\"\n").as_bytes()).unwrap();
+            assert_eq!(parsed.header["alg"], "dir");
+            assert_eq!(parsed.header["kid"], "k1");
+            let (enc, plaintext) = parsed.decrypt(&key).unwrap();
+            assert_eq!(enc, Enc::for_key(len).unwrap());
+            assert_eq!(plaintext, b"{\"pan\":\"4111111111111111\"}");
+        }
+        let wrong = Key::Oct { k: vec![8; 16], kid: None };
+        let token = encrypt(b"x", &Key::Oct { k: vec![7; 16], kid: None }).unwrap();
+        let err = Compact::parse(token.as_bytes()).unwrap().decrypt(&wrong).unwrap_err();
+        assert!(err.contains("authentication tag"), "{err}");
+    }
+
+    #[test]
+    fn only_jwes_are_parsed() {
+        assert!(Compact::parse(b"{\"status\":\"ok\"}").is_none());
+        // A JWS has three parts, and a five-part token needs an enc
+        assert!(Compact::parse(b"eyJhbGciOiJIUzI1NiJ9.e30.c2ln").is_none());
+        let no_enc = format!("{}.a.b.c.d", base64url(b"{\"alg\":\"dir\"}"));
+        assert!(Compact::parse(no_enc.as_bytes()).is_none());
+        let ecdh = format!("{}..AAAA.AAAA.AAAA", base64url(b"{\"alg\":\"ECDH-ES\",\"enc\":\"A256GCM\"}"));
+        let err = Compact::parse(ecdh.as_bytes()).unwrap().decrypt(&Key::Oct { k: vec![0; 32], kid: None });
+        assert!(err.unwrap_err().contains("unsupported alg ECDH-ES"));
+    }
+}
@@ -0,0 +1,61 @@
+// src/curl/keylog.rs
+
+//! `--keylog <FILE>`: TLS secrets appended to a file in NSS key log
+//! format, so a capture of rustcurl's traffic can be decrypted in
+//! Wireshark. libcurl honors `SSLKEYLOGFILE` itself, but reads it once,
+//! before `main`; `--keylog` lines come from OpenSSL's key log callback
+//! (see `request.rs`) and are written here.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// The key log in use and its path. OpenSSL's callback carries no
+/// context, so there is one per process.
+static LOG: Mutex<Option<(String, File)>> = Mutex::new(None);
+
+/// Send key log lines to `path`, creating it readable by its owner only
+/// (it holds everything needed to read the traffic).
+pub fn open(path: &str) -> io::Result<()> {
+    let mut log = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if log.as_ref().is_some_and(|(open, _)| open == path) {
+        return Ok(());
+    }
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    *log = Some((path.to_string(), options.open(path)?));
+    Ok(())
+}
+
+/// Append one `LABEL CLIENT_RANDOM SECRET` line. Failures are dropped:
+/// the key log must never fail the handshake.
+pub fn write(line: &[u8]) {
+    let mut log = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, ref mut file)) = *log {
+        let _ = file.write_all(&[line, b"\n"].concat());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_lines_owner_only() {
+        let file = std::env::temp_dir().join(format!("rustcurl-keylog-{}", std::process::id()));
+        let path = file.to_str().unwrap();
+        open(path).unwrap();
+        write(b"CLIENT_RANDOM 00 11");
+        open(path).unwrap();
+        write(b"CLIENT_RANDOM 22 33");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "CLIENT_RANDOM 00 11\nCLIENT_RANDOM 22 33\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        std::fs::remove_file(file).unwrap();
+    }
+}
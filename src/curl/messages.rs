@@ -0,0 +1,320 @@
+// src/curl/messages.rs
+
+//! User-facing error and hint strings, kept in one catalog so they can be
+//! translated and rendered in the format chosen with `--message-format`.
+
+use super::error::{RequestError, TimeoutPhase};
+use super::warning::Warning;
+
+/// Catalog language, picked from `RUSTCURL_LANG` or the usual locale
+/// variables (`LC_ALL`, `LC_MESSAGES`, `LANG`). Defaults to English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Es,
+}
+
+impl Locale {
+    pub fn parse(tag: &str) -> Option<Self> {
+        let lang = tag.split(['_', '-', '.']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        ["RUSTCURL_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| Locale::parse(&v))
+            .unwrap_or(Locale::En)
+    }
+}
+
+/// How errors and warnings are written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// One line, no hint.
+    Short,
+    /// Message followed by a hint line when one applies.
+    #[default]
+    Long,
+    /// One JSON object per error or warning, for tools parsing stderr.
+    Json,
+}
+
+impl MessageFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "short" => Some(MessageFormat::Short),
+            "long" => Some(MessageFormat::Long),
+            "json" => Some(MessageFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Hints the catalog knows how to phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    Dns,
+    ProxyDns,
+    Revocation,
+    SelfSigned,
+    Tls,
+    ProxyAuth,
+    PartialBody,
+}
+
+pub fn hint_text(hint: Hint, locale: Locale) -> &'static str {
+    match (locale, hint) {
+        (Locale::En, Hint::Dns) => "Hint: DNS resolution failed. If behind a corporate proxy, set HTTPS_PROXY or use -x <proxy-url>",
+        (Locale::En, Hint::ProxyDns) => "Hint: Could not resolve proxy hostname. Check your proxy URL",
+        (Locale::En, Hint::Revocation) => "Hint: Certificate revocation check failed. Try --ssl-no-revoke to disable revocation checks",
+        (Locale::En, Hint::SelfSigned) => "Hint: The server certificate is self-signed or issued by an unknown CA. Pass the CA with --cacert <path>, or use --insecure (-k) to skip verification",
+        (Locale::En, Hint::Tls) => "Hint: SSL error. Try --insecure (-k), --cacert <path>, or --ssl-no-revoke for revocation issues",
+        (Locale::En, Hint::ProxyAuth) => "Hint: Proxy requires authentication (407). Try --proxy-negotiate for Kerberos/SPNEGO or --proxy-user <user:pass>",
+        (Locale::En, Hint::PartialBody) => "Hint: The server closed the connection before the full body arrived. Use --accept-partial to keep what was received, or --ignore-content-length if the server sends a wrong Content-Length",
+        (Locale::Es, Hint::Dns) => "Sugerencia: falló la resolución DNS. Si está detrás de un proxy corporativo, defina HTTPS_PROXY o use -x <url-proxy>",
+        (Locale::Es, Hint::ProxyDns) => "Sugerencia: no se pudo resolver el nombre del proxy. Revise la URL del proxy",
+        (Locale::Es, Hint::Revocation) => "Sugerencia: falló la comprobación de revocación del certificado. Pruebe --ssl-no-revoke para desactivarla",
+        (Locale::Es, Hint::SelfSigned) => "Sugerencia: el certificado del servidor es autofirmado o de una CA desconocida. Indique la CA con --cacert <ruta>, o use --insecure (-k) para omitir la verificación",
+        (Locale::Es, Hint::Tls) => "Sugerencia: error SSL. Pruebe --insecure (-k), --cacert <ruta>, o --ssl-no-revoke para problemas de revocación",
+        (Locale::Es, Hint::ProxyAuth) => "Sugerencia: el proxy requiere autenticación (407). Pruebe --proxy-negotiate para Kerberos/SPNEGO o --proxy-user <usuario:clave>",
+        (Locale::Es, Hint::PartialBody) => "Sugerencia: el servidor cerró la conexión antes de enviar todo el cuerpo. Use --accept-partial para conservar lo recibido, o --ignore-content-length si el servidor envía un Content-Length incorrecto",
+    }
+}
+
+fn phase_name(phase: TimeoutPhase, locale: Locale) -> &'static str {
+    match (locale, phase) {
+        (Locale::En, TimeoutPhase::Dns) => "DNS",
+        (Locale::En, TimeoutPhase::Connect) => "connect",
+        (Locale::En, TimeoutPhase::Read) => "read",
+        (Locale::En, TimeoutPhase::Total) => "total",
+        (Locale::Es, TimeoutPhase::Dns) => "DNS",
+        (Locale::Es, TimeoutPhase::Connect) => "de conexión",
+        (Locale::Es, TimeoutPhase::Read) => "de lectura",
+        (Locale::Es, TimeoutPhase::Total) => "total",
+    }
+}
+
+/// Human-readable description of an error in the given language.
+pub fn describe(err: &RequestError, locale: Locale) -> String {
+    match locale {
+        Locale::En => match err {
+            RequestError::Dns { host, proxy: false } => format!("could not resolve host: {host}"),
+            RequestError::Dns { host, proxy: true } => format!("could not resolve proxy: {host}"),
+            RequestError::Connect { message } => format!("connection failed: {message}"),
+            RequestError::TlsHandshake { message } => format!("TLS handshake failed: {message}"),
+            RequestError::TlsVerify { message } => {
+                format!("TLS certificate verification failed: {message}")
+            }
+            RequestError::Timeout { phase, message } => {
+                format!("{} timeout: {message}", phase_name(*phase, locale))
+            }
+            RequestError::ProxyAuth { message } => format!("proxy authentication failed: {message}"),
+            RequestError::TooManyRedirects { message } => format!("too many redirects: {message}"),
+            RequestError::PartialBody { expected: Some(n), received } => {
+                format!("partial body: received {received} of {n} bytes")
+            }
+            RequestError::PartialBody { expected: None, received } => {
+                format!("partial body: connection closed after {received} bytes")
+            }
+            RequestError::HttpStatus { code } => format!("server returned HTTP {code}"),
+            RequestError::WriteOutput { path, source } => format!("failed to write {path}: {source}"),
+            RequestError::Extract { path, message } => format!("failed to extract {path}: {message}"),
+            RequestError::Signature { signature, message } => {
+                format!("signature check against {signature} failed: {message}")
+            }
+            RequestError::TokenRefresh { url, message } => format!("token refresh at {url} failed: {message}"),
+            RequestError::Capture { name, message } => format!("cannot capture {name}: {message}"),
+            RequestError::SlaExceeded { option, limit, actual } => {
+                format!("took {} ms, over {option} {} ms", millis(*actual), millis(*limit))
+            }
+            RequestError::Io(e) => format!("io error: {e}"),
+            RequestError::Config { option, message } => format!("config error: {option}: {message}"),
+            RequestError::Update { message } => format!("self-update failed: {message}"),
+            RequestError::Transfer { message } => format!("transfer failed: {message}"),
+            RequestError::Retried { attempts, last } => match attempts.len().saturating_sub(1) {
+                1 => format!("{} (after 1 retry)", describe(last, locale)),
+                n => format!("{} (after {n} retries)", describe(last, locale)),
+            },
+        },
+        Locale::Es => match err {
+            RequestError::Dns { host, proxy: false } => format!("no se pudo resolver el host: {host}"),
+            RequestError::Dns { host, proxy: true } => format!("no se pudo resolver el proxy: {host}"),
+            RequestError::Connect { message } => format!("falló la conexión: {message}"),
+            RequestError::TlsHandshake { message } => format!("falló la negociación TLS: {message}"),
+            RequestError::TlsVerify { message } => {
+                format!("falló la verificación del certificado TLS: {message}")
+            }
+            RequestError::Timeout { phase, message } => {
+                format!("tiempo de espera {} agotado: {message}", phase_name(*phase, locale))
+            }
+            RequestError::ProxyAuth { message } => {
+                format!("falló la autenticación con el proxy: {message}")
+            }
+            RequestError::TooManyRedirects { message } => {
+                format!("demasiadas redirecciones: {message}")
+            }
+            RequestError::PartialBody { expected: Some(n), received } => {
+                format!("cuerpo incompleto: se recibieron {received} de {n} bytes")
+            }
+            RequestError::PartialBody { expected: None, received } => {
+                format!("cuerpo incompleto: la conexión se cerró tras {received} bytes")
+            }
+            RequestError::HttpStatus { code } => format!("el servidor respondió HTTP {code}"),
+            RequestError::WriteOutput { path, source } => {
+                format!("no se pudo escribir {path}: {source}")
+            }
+            RequestError::Extract { path, message } => {
+                format!("no se pudo extraer {path}: {message}")
+            }
+            RequestError::Signature { signature, message } => {
+                format!("falló la verificación de la firma {signature}: {message}")
+            }
+            RequestError::TokenRefresh { url, message } => {
+                format!("no se pudo renovar el token en {url}: {message}")
+            }
+            RequestError::Capture { name, message } => format!("no se pudo capturar {name}: {message}"),
+            RequestError::SlaExceeded { option, limit, actual } => {
+                format!("tardó {} ms, más que {option} {} ms", millis(*actual), millis(*limit))
+            }
+            RequestError::Io(e) => format!("error de E/S: {e}"),
+            RequestError::Config { option, message } => {
+                format!("error de configuración: {option}: {message}")
+            }
+            RequestError::Update { message } => format!("falló la autoactualización: {message}"),
+            RequestError::Transfer { message } => format!("falló la transferencia: {message}"),
+            RequestError::Retried { attempts, last } => match attempts.len().saturating_sub(1) {
+                1 => format!("{} (tras 1 reintento)", describe(last, locale)),
+                n => format!("{} (tras {n} reintentos)", describe(last, locale)),
+            },
+        },
+    }
+}
+
+fn millis(d: std::time::Duration) -> String {
+    format!("{:.0}", d.as_secs_f64() * 1000.0)
+}
+
+/// Render a failed request for stderr in the chosen format.
+pub fn render_error(err: &RequestError, format: MessageFormat, locale: Locale) -> String {
+    let message = describe(err, locale);
+    let hint = err.hint_kind().map(|h| hint_text(h, locale));
+    match format {
+        MessageFormat::Short => message,
+        MessageFormat::Long => {
+            let failed = match locale {
+                Locale::En => "Request failed",
+                Locale::Es => "La solicitud falló",
+            };
+            match hint {
+                Some(hint) => format!("{failed}: {message}\n{hint}"),
+                None => format!("{failed}: {message}"),
+            }
+        }
+        MessageFormat::Json => serde_json::json!({
+            "error": err.code(),
+            "message": message,
+            "hint": hint,
+        })
+        .to_string(),
+    }
+}
+
+/// Render a warning for stderr in the chosen format. Warning messages
+/// are not translated; only the label is.
+pub fn render_warning(warning: &Warning, format: MessageFormat, locale: Locale) -> String {
+    match format {
+        MessageFormat::Short | MessageFormat::Long => {
+            let label = match locale {
+                Locale::En => "Warning",
+                Locale::Es => "Aviso",
+            };
+            format!("{label}: {warning}")
+        }
+        MessageFormat::Json => serde_json::json!({
+            "warning": warning.code(),
+            "message": warning.message,
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_parse_tags() {
+        assert_eq!(Locale::parse("es_ES.UTF-8"), Some(Locale::Es));
+        assert_eq!(Locale::parse("en-US"), Some(Locale::En));
+        assert_eq!(Locale::parse("C"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr_FR"), None);
+    }
+
+    #[test]
+    fn message_format_parse() {
+        assert_eq!(MessageFormat::parse("short"), Some(MessageFormat::Short));
+        assert_eq!(MessageFormat::parse("json"), Some(MessageFormat::Json));
+        assert_eq!(MessageFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn english_matches_display() {
+        let err = RequestError::Dns { host: "x.com".into(), proxy: false };
+        assert_eq!(describe(&err, Locale::En), err.to_string());
+    }
+
+    #[test]
+    fn spanish_description() {
+        let err = RequestError::Timeout {
+            phase: TimeoutPhase::Connect,
+            message: "5s".into(),
+        };
+        assert_eq!(describe(&err, Locale::Es), "tiempo de espera de conexión agotado: 5s");
+    }
+
+    #[test]
+    fn render_short_and_long() {
+        let err = RequestError::ProxyAuth { message: "407".into() };
+        let short = render_error(&err, MessageFormat::Short, Locale::En);
+        assert_eq!(short, "proxy authentication failed: 407");
+        let long = render_error(&err, MessageFormat::Long, Locale::En);
+        assert!(long.starts_with("Request failed: proxy authentication failed: 407\nHint:"));
+    }
+
+    #[test]
+    fn render_json() {
+        let err = RequestError::Dns { host: "x.com".into(), proxy: false };
+        let json: serde_json::Value =
+            serde_json::from_str(&render_error(&err, MessageFormat::Json, Locale::Es)).unwrap();
+        assert_eq!(json["error"], "dns");
+        assert_eq!(json["message"], "no se pudo resolver el host: x.com");
+        assert!(json["hint"].as_str().unwrap().starts_with("Sugerencia"));
+    }
+
+    #[test]
+    fn render_warnings() {
+        use crate::curl::warning::WarningKind;
+        let warning = Warning::new(WarningKind::InsecureTls, "verification is off");
+        assert_eq!(render_warning(&warning, MessageFormat::Long, Locale::Es), "Aviso: verification is off");
+        let json: serde_json::Value =
+            serde_json::from_str(&render_warning(&warning, MessageFormat::Json, Locale::En)).unwrap();
+        assert_eq!(json["warning"], "insecure_tls");
+        assert_eq!(json["message"], "verification is off");
+    }
+
+    #[test]
+    fn render_json_without_hint() {
+        let err = RequestError::Transfer { message: "boom".into() };
+        let json: serde_json::Value =
+            serde_json::from_str(&render_error(&err, MessageFormat::Json, Locale::En)).unwrap();
+        assert_eq!(json["error"], "transfer");
+        assert!(json["hint"].is_null());
+    }
+}
@@ -0,0 +1,335 @@
+// src/curl/mime.rs
+
+//! File names for `-O` downloads: infer an extension from Content-Type or
+//! the body's magic bytes when the URL gives none, and warn when the
+//! extension the URL promises does not match what actually arrived (an
+//! HTML error page saved as `.tar.gz`). `--no-mime-sniff` turns it off.
+//! With `-J` the name comes from Content-Disposition instead of the URL.
+
+/// Media types and the extension used when saving them.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("text/html", "html"),
+    ("application/xhtml+xml", "html"),
+    ("text/plain", "txt"),
+    ("text/css", "css"),
+    ("text/csv", "csv"),
+    ("text/javascript", "js"),
+    ("application/javascript", "js"),
+    ("application/json", "json"),
+    ("application/xml", "xml"),
+    ("text/xml", "xml"),
+    ("application/pdf", "pdf"),
+    ("application/zip", "zip"),
+    ("application/gzip", "gz"),
+    ("application/x-gzip", "gz"),
+    ("application/x-tar", "tar"),
+    ("application/x-bzip2", "bz2"),
+    ("application/x-xz", "xz"),
+    ("application/zstd", "zst"),
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/svg+xml", "svg"),
+];
+
+/// Extensions (lowercase, last component) and the types they imply.
+const EXPECTED: &[(&str, &[&str])] = &[
+    ("html", &["text/html", "application/xhtml+xml"]),
+    ("htm", &["text/html", "application/xhtml+xml"]),
+    ("json", &["application/json"]),
+    ("xml", &["application/xml", "text/xml"]),
+    ("pdf", &["application/pdf"]),
+    ("zip", &["application/zip"]),
+    ("jar", &["application/zip"]),
+    ("whl", &["application/zip"]),
+    ("gz", &["application/gzip", "application/x-gzip"]),
+    ("tgz", &["application/gzip", "application/x-gzip"]),
+    ("tar", &["application/x-tar"]),
+    ("bz2", &["application/x-bzip2"]),
+    ("xz", &["application/x-xz"]),
+    ("zst", &["application/zstd"]),
+    ("png", &["image/png"]),
+    ("jpg", &["image/jpeg"]),
+    ("jpeg", &["image/jpeg"]),
+    ("gif", &["image/gif"]),
+    ("webp", &["image/webp"]),
+];
+
+/// Extension for a Content-Type value, ignoring parameters.
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(mime, _)| *mime == essence)
+        .map(|(_, ext)| *ext)
+}
+
+/// Whether a body of this type is text, safe to transcode or rewrite line
+/// endings in. An absent Content-Type is taken as text.
+pub fn is_text(content_type: Option<&str>) -> bool {
+    let Some(ct) = content_type else {
+        return true;
+    };
+    let essence = ct.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    essence.starts_with("text/") || ["json", "xml", "javascript", "csv"].iter().any(|t| essence.contains(t))
+}
+
+/// Media type recognized from the first bytes of a body.
+pub fn sniff(body: &[u8]) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x1f\x8b", "application/gzip"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"PK\x05\x06", "application/zip"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"BZh", "application/x-bzip2"),
+        (b"\xfd7zXZ\x00", "application/x-xz"),
+        (b"\x28\xb5\x2f\xfd", "application/zstd"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| body.starts_with(magic)) {
+        return Some(mime);
+    }
+    if body.starts_with(b"RIFF") && body.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+    if body.get(257..262) == Some(b"ustar") {
+        return Some("application/x-tar");
+    }
+    let start = body.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(body.len());
+    let head = String::from_utf8_lossy(&body[start..body.len().min(start + 64)]).to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        Some("text/html")
+    } else if head.starts_with("<?xml") {
+        Some("application/xml")
+    } else {
+        None
+    }
+}
+
+/// Name chosen for a download, with a warning when the content does not
+/// look like what the name promises.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inferred {
+    pub name: String,
+    pub warning: Option<String>,
+}
+
+/// Name for saving a body fetched from a URL whose last path segment is
+/// `base` (`None` when the URL has no usable file name). With `enabled`
+/// false the base name is used unchanged.
+pub fn infer_name(base: Option<&str>, content_type: Option<&str>, body: &[u8], enabled: bool) -> Inferred {
+    let stem = base.filter(|b| !b.is_empty()).unwrap_or("index");
+    if !enabled {
+        return Inferred {
+            name: stem.to_string(),
+            warning: None,
+        };
+    }
+    let sniffed = sniff(body);
+    match extension(stem) {
+        Some(ext) => {
+            let warning = EXPECTED
+                .iter()
+                .find(|(e, _)| *e == ext)
+                .zip(sniffed)
+                .filter(|((_, expected), found)| !expected.contains(found))
+                .map(|(_, found)| format!("{stem} looks like {found}, not .{ext}; the server may have sent an error page"));
+            Inferred {
+                name: stem.to_string(),
+                warning,
+            }
+        }
+        None => {
+            let ext = content_type.and_then(extension_for).or_else(|| sniffed.and_then(extension_for));
+            Inferred {
+                name: ext.map_or(stem.to_string(), |ext| format!("{stem}.{ext}")),
+                warning: None,
+            }
+        }
+    }
+}
+
+/// File name from a Content-Disposition value (RFC 6266). `filename*`
+/// (RFC 8187, UTF-8 or ISO-8859-1) wins over `filename`. Directory parts
+/// are dropped; `None` when nothing usable is left.
+pub fn disposition_filename(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for (name, val) in disposition_params(value) {
+        if name.eq_ignore_ascii_case("filename*") {
+            extended = extended.or_else(|| decode_ext_value(&val));
+        } else if name.eq_ignore_ascii_case("filename") {
+            plain = plain.or(Some(val));
+        }
+    }
+    safe_file_name(&extended.or(plain)?)
+}
+
+/// `name=value` parameters after the disposition type, with quoted
+/// strings unescaped.
+fn disposition_params(value: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let Some((_, mut rest)) = value.split_once(';') else {
+        return params;
+    };
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        let Some((name, after)) = rest.split_once('=') else {
+            return params;
+        };
+        let after = after.trim_start();
+        let (val, remainder) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let mut val = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => val.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = i + 1;
+                            break;
+                        }
+                        c => val.push(c),
+                    }
+                }
+                (val, &quoted[end..])
+            }
+            None => {
+                let end = after.find(';').unwrap_or(after.len());
+                (after[..end].trim_end().to_string(), &after[end..])
+            }
+        };
+        params.push((name.trim().to_string(), val));
+        rest = remainder;
+    }
+}
+
+/// Decode an RFC 8187 `charset'language'percent-encoded` value.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let encoded = parts.nth(1)?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+/// Last path component of a server-supplied name, rejecting empty, `.`,
+/// `..` and names with control characters.
+fn safe_file_name(name: &str) -> Option<String> {
+    let base = name.rsplit(['/', '\\']).next()?.trim();
+    (!base.is_empty() && base != "." && base != ".." && !base.chars().any(char::is_control)).then(|| base.to_string())
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    (!stem.is_empty() && !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| ext.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &[u8] = b"\n  <!DOCTYPE html><html><body>404</body></html>";
+
+    #[test]
+    fn disposition_names() {
+        assert_eq!(disposition_filename("attachment; filename=report.csv").as_deref(), Some("report.csv"));
+        assert_eq!(
+            disposition_filename("attachment; filename=\"Q3 \\\"final\\\".pdf\"; size=10").as_deref(),
+            Some("Q3 \"final\".pdf")
+        );
+        assert_eq!(
+            disposition_filename("attachment; filename=\"EURO rates.pdf\"; filename*=UTF-8''%e2%82%ac%20rates.pdf").as_deref(),
+            Some("\u{20ac} rates.pdf")
+        );
+        assert_eq!(disposition_filename("inline; FILENAME*=iso-8859-1'en'caf%E9.txt").as_deref(), Some("caf\u{e9}.txt"));
+        assert_eq!(disposition_filename("attachment").as_deref(), None);
+        assert_eq!(disposition_filename("attachment; name=field").as_deref(), None);
+    }
+
+    #[test]
+    fn disposition_names_are_sanitized() {
+        assert_eq!(disposition_filename("attachment; filename=\"../../etc/passwd\"").as_deref(), Some("passwd"));
+        assert_eq!(disposition_filename("attachment; filename=\"C:\\\\temp\\\\x.exe\"").as_deref(), Some("x.exe"));
+        assert_eq!(disposition_filename("attachment; filename=\"..\"").as_deref(), None);
+        assert_eq!(disposition_filename("attachment; filename=\"dir/\"").as_deref(), None);
+        assert_eq!(disposition_filename("attachment; filename=\"a\nb\"").as_deref(), None);
+    }
+
+    #[test]
+    fn text_types() {
+        assert!(is_text(Some("text/csv; charset=utf-8")));
+        assert!(is_text(Some("application/problem+json")));
+        assert!(is_text(None));
+        assert!(!is_text(Some("application/octet-stream")));
+        assert!(!is_text(Some("image/svg")));
+    }
+
+    #[test]
+    fn extension_from_content_type() {
+        assert_eq!(extension_for("text/html; charset=utf-8"), Some("html"));
+        assert_eq!(extension_for("Application/JSON"), Some("json"));
+        assert_eq!(extension_for("application/octet-stream"), None);
+    }
+
+    #[test]
+    fn sniffs_magic_bytes() {
+        assert_eq!(sniff(b"\x1f\x8b\x08\x00"), Some("application/gzip"));
+        assert_eq!(sniff(b"PK\x03\x04rest"), Some("application/zip"));
+        assert_eq!(sniff(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(sniff(HTML), Some("text/html"));
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?>"), Some("application/xml"));
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(sniff(&tar), Some("application/x-tar"));
+        assert_eq!(sniff(b"plain words"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn infers_missing_extension() {
+        let by_header = infer_name(Some("report"), Some("application/pdf"), b"", true);
+        assert_eq!(by_header.name, "report.pdf");
+        assert_eq!(infer_name(None, None, HTML, true).name, "index.html");
+        assert_eq!(infer_name(Some("blob"), None, b"??", true).name, "blob");
+    }
+
+    #[test]
+    fn warns_on_mismatch() {
+        let html_as_tarball = infer_name(Some("release.tar.gz"), Some("text/html"), HTML, true);
+        assert_eq!(html_as_tarball.name, "release.tar.gz");
+        assert!(html_as_tarball.warning.unwrap().contains("text/html"));
+        assert_eq!(infer_name(Some("release.tar.gz"), None, b"\x1f\x8b\x08", true).warning, None);
+        // Unknown content or extension: nothing to compare
+        assert_eq!(infer_name(Some("notes.md"), None, HTML, true).warning, None);
+        assert_eq!(infer_name(Some("data.gz"), None, b"??", true).warning, None);
+    }
+
+    #[test]
+    fn disabled_keeps_base_name() {
+        let inferred = infer_name(Some("release.tar.gz"), Some("text/html"), HTML, false);
+        assert_eq!(inferred, Inferred { name: "release.tar.gz".into(), warning: None });
+        assert_eq!(infer_name(Some("report"), Some("application/pdf"), b"", false).name, "report");
+    }
+}
@@ -1,9 +1,73 @@
 // src/curl/mod.rs
 
 pub mod args;
+pub mod atomic;
+pub mod auth;
+pub mod azure;
+pub mod batch;
+pub mod body;
+pub mod capabilities;
+pub mod capture;
+pub mod changed;
+pub mod charset;
+pub mod collection;
 pub mod config;
+pub mod confirm;
+pub mod content_digest;
+pub mod cookie;
+pub mod curlrc;
+pub mod diagnose;
+pub mod docs;
+pub mod dns;
+pub mod env;
+pub mod effective;
+pub mod encoding;
+pub mod eol;
 pub mod error;
+pub mod extract;
+pub mod gcs;
+pub mod identity;
+pub mod jwe;
+pub mod keylog;
+pub mod glob;
+pub mod messages;
+pub mod mime;
+pub mod netrc;
+pub mod notify;
+pub mod oauth;
+pub mod output;
+pub mod pac;
+pub mod paths;
+pub mod presets;
+pub mod prom;
+pub mod provenance;
+pub mod proxy;
+pub mod push;
+pub mod ranges;
+pub mod recorder;
+pub mod registry;
+pub mod relay;
 pub mod request;
 pub mod response;
+pub mod retry;
+pub mod rng;
+pub mod s3;
+pub mod secret;
+pub mod session;
+pub mod signature;
+pub mod sink;
+pub mod sla;
+pub mod tail;
+pub mod ssh;
+pub mod tls_session;
+pub mod tofu;
+pub mod tor;
+pub mod update;
+pub mod url;
+pub mod verbose;
+pub mod warning;
+pub mod watch;
+pub mod writeout;
+pub mod x509;
 
-pub use args::{parse_args, print_usage};
+pub use args::{parse_args, parse_groups, print_usage};
@@ -0,0 +1,193 @@
+// src/curl/netrc.rs
+
+//! `-n`/`--netrc`: credentials from `~/.netrc` (`_netrc` on Windows) or
+//! `--netrc-file`, for hosts `-u` does not cover. Entries are `machine
+//! HOST login USER password PASS`, with a final `default` entry for any
+//! other host; `account` and `macdef` are skipped.
+
+use std::path::PathBuf;
+
+use super::config::RequestConfig;
+use super::env::EnvSource;
+use super::url;
+
+/// One `machine` entry, or the `default` one when `host` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub host: Option<String>,
+    pub login: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Where the file is when `--netrc-file` is not given.
+pub fn default_path(env: &EnvSource) -> Option<PathBuf> {
+    let (var, name) = if cfg!(windows) { ("USERPROFILE", "_netrc") } else { ("HOME", ".netrc") };
+    env.get(var).filter(|d| !d.is_empty()).map(|dir| PathBuf::from(dir).join(name))
+}
+
+/// The entries of a netrc file's text.
+pub fn parse(text: &str) -> Result<Vec<Machine>, String> {
+    let tokens = tokenize(text)?;
+    let mut machines: Vec<Machine> = Vec::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        let mut value = |key: &str| tokens.next().ok_or_else(|| format!("'{key}' without a value"));
+        match token.as_str() {
+            "machine" => machines.push(Machine {
+                host: Some(value("machine")?),
+                login: None,
+                password: None,
+            }),
+            "default" => machines.push(Machine {
+                host: None,
+                login: None,
+                password: None,
+            }),
+            "login" | "password" => {
+                let v = value(&token)?;
+                let machine = machines.last_mut().ok_or_else(|| format!("'{token}' before any machine"))?;
+                if token == "login" {
+                    machine.login = Some(v);
+                } else {
+                    machine.password = Some(v);
+                }
+            }
+            "account" => {
+                value("account")?;
+            }
+            other => return Err(format!("unexpected '{other}'")),
+        }
+    }
+    Ok(machines)
+}
+
+/// Split on whitespace, honouring `"quoted strings"` with `\"`, `\\`,
+/// `\n`, `\r` and `\t` escapes, and dropping `macdef` bodies, which run to
+/// the next blank line.
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let start = tokens.len();
+        let mut chars = line.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            if c == '#' {
+                break;
+            }
+            let mut token = String::new();
+            if c == '"' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => token.push('\n'),
+                            Some('r') => token.push('\r'),
+                            Some('t') => token.push('\t'),
+                            Some(other) => token.push(other),
+                            None => return Err("unterminated quote".to_string()),
+                        },
+                        Some(other) => token.push(other),
+                        None => return Err("unterminated quote".to_string()),
+                    }
+                }
+            } else {
+                while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                    token.push(c);
+                    chars.next();
+                }
+            }
+            tokens.push(token);
+        }
+        if let Some(at) = tokens[start..].iter().position(|t| t == "macdef") {
+            tokens.truncate(start + at);
+            for body in lines.by_ref() {
+                if body.trim().is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// The entry for `host`: its `machine` (with login `user` if one is
+/// given), else `default`.
+pub fn lookup<'a>(machines: &'a [Machine], host: &str, user: Option<&str>) -> Option<&'a Machine> {
+    let fits = |m: &&Machine| user.is_none_or(|u| m.login.as_deref().is_none_or(|l| l == u));
+    machines
+        .iter()
+        .filter(|m| m.host.as_deref().is_some_and(|h| h.eq_ignore_ascii_case(host)))
+        .find(fits)
+        .or_else(|| machines.iter().filter(|m| m.host.is_none()).find(fits))
+}
+
+/// The entry for `config`'s host, if `--netrc` is on and the file has
+/// one. The file was checked when the options were parsed, so one that
+/// has gone or broken since is treated as empty.
+pub fn for_config(config: &RequestConfig, user: Option<&str>) -> Option<Machine> {
+    if !config.netrc {
+        return None;
+    }
+    let path = match config.netrc_file {
+        Some(ref path) => PathBuf::from(path),
+        None => default_path(&config.env.clone().unwrap_or_default())?,
+    };
+    let machines = parse(&std::fs::read_to_string(path).ok()?).ok()?;
+    let host = url::parse(&config.url).ok()?.host;
+    lookup(&machines, host, user).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETRC: &str = concat!(
+        "# build credentials\n",
+        "machine api.x.com login ci password \"p w\\\"d\"\n",
+        "machine git.x.com\n",
+        "  login alice\n",
+        "  password a1\n",
+        "machine git.x.com login bob password b2 account ops\n",
+        "macdef init\n",
+        "cd /pub\n",
+        "\n",
+        "default login anonymous password guest@\n",
+    );
+
+    #[test]
+    fn parses_entries() {
+        let machines = parse(NETRC).unwrap();
+        assert_eq!(machines.len(), 4);
+        assert_eq!(machines[0].password.as_deref(), Some("p w\"d"));
+        assert_eq!(machines[3].host, None);
+        assert!(parse("login x").unwrap_err().contains("before any machine"));
+        assert!(parse("machine").is_err());
+        assert!(parse("machine h login \"x").is_err());
+    }
+
+    #[test]
+    fn looks_up_host_then_default() {
+        let machines = parse(NETRC).unwrap();
+        assert_eq!(lookup(&machines, "API.x.com", None).unwrap().login.as_deref(), Some("ci"));
+        assert_eq!(lookup(&machines, "git.x.com", None).unwrap().login.as_deref(), Some("alice"));
+        assert_eq!(lookup(&machines, "git.x.com", Some("bob")).unwrap().password.as_deref(), Some("b2"));
+        assert_eq!(lookup(&machines, "other.com", None).unwrap().login.as_deref(), Some("anonymous"));
+        assert_eq!(lookup(&machines[..3], "other.com", None), None);
+    }
+
+    #[test]
+    fn credentials_for_request_host() {
+        let path = std::env::temp_dir().join(format!("rustcurl-netrc-{}", std::process::id()));
+        std::fs::write(&path, NETRC).unwrap();
+        let config = RequestConfig::new("https://api.x.com/v1").netrc_file(path.to_str().unwrap());
+        let machine = for_config(&config, None).unwrap();
+        assert_eq!((machine.login.as_deref(), machine.password.as_deref()), (Some("ci"), Some("p w\"d")));
+        assert_eq!(for_config(&config.clone().netrc(false), None), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
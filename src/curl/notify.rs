@@ -0,0 +1,212 @@
+// src/curl/notify.rs
+
+//! Watch-mode alerts: `--notify-cmd` and `--notify-desktop` fire when the
+//! status class changes (e.g. 2xx to 5xx, or back), once the new class has
+//! held for `--notify-debounce` consecutive attempts.
+
+use std::fmt;
+use std::process::Command;
+
+use super::error::RequestError;
+use super::response::Response;
+
+/// Outcome of one attempt, coarse enough that flapping within a class
+/// (200 vs 204) is not an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusClass {
+    /// 1xx-5xx by leading digit.
+    Http(u32),
+    /// The request failed without a usable response.
+    Failed,
+}
+
+impl StatusClass {
+    pub fn of(result: &Result<Response, RequestError>) -> Self {
+        match result {
+            Ok(response) => StatusClass::Http(response.status_code / 100),
+            Err(_) => StatusClass::Failed,
+        }
+    }
+}
+
+impl fmt::Display for StatusClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusClass::Http(digit) => write!(f, "{digit}xx"),
+            StatusClass::Failed => f.write_str("error"),
+        }
+    }
+}
+
+/// A confirmed change of status class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: StatusClass,
+    pub to: StatusClass,
+}
+
+impl Transition {
+    /// Back to 2xx from anything else.
+    pub fn is_recovery(&self) -> bool {
+        self.to == StatusClass::Http(2)
+    }
+}
+
+/// Tracks the confirmed class and reports a transition once a different
+/// class has been seen `debounce` times in a row.
+pub struct Debouncer {
+    debounce: u32,
+    confirmed: Option<StatusClass>,
+    pending: Option<(StatusClass, u32)>,
+}
+
+impl Debouncer {
+    pub fn new(debounce: u32) -> Self {
+        Debouncer {
+            debounce: debounce.max(1),
+            confirmed: None,
+            pending: None,
+        }
+    }
+
+    /// Record one attempt. The first attempt sets the baseline silently.
+    pub fn observe(&mut self, class: StatusClass) -> Option<Transition> {
+        let Some(from) = self.confirmed else {
+            self.confirmed = Some(class);
+            return None;
+        };
+        if class == from {
+            self.pending = None;
+            return None;
+        }
+        let seen = match self.pending {
+            Some((pending, n)) if pending == class => n + 1,
+            _ => 1,
+        };
+        if seen < self.debounce {
+            self.pending = Some((class, seen));
+            return None;
+        }
+        self.confirmed = Some(class);
+        self.pending = None;
+        Some(Transition { from, to: class })
+    }
+}
+
+/// Human-readable summary, e.g. `https://x.com/: 2xx -> 5xx (503)`.
+pub fn summary(url: &str, transition: &Transition, detail: &str) -> String {
+    format!("{url}: {} -> {} ({detail})", transition.from, transition.to)
+}
+
+/// Run `cmd` through the shell with the transition in the environment:
+/// `RUSTCURL_URL`, `RUSTCURL_FROM`, `RUSTCURL_TO`, `RUSTCURL_DETAIL` and
+/// `RUSTCURL_RECOVERED` (`1` or `0`).
+pub fn run_command(cmd: &str, url: &str, transition: &Transition, detail: &str) -> Result<(), String> {
+    let mut command = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(cmd);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(cmd);
+        c
+    };
+    let status = command
+        .env("RUSTCURL_URL", url)
+        .env("RUSTCURL_FROM", transition.from.to_string())
+        .env("RUSTCURL_TO", transition.to.to_string())
+        .env("RUSTCURL_DETAIL", detail)
+        .env("RUSTCURL_RECOVERED", if transition.is_recovery() { "1" } else { "0" })
+        .status()
+        .map_err(|e| format!("could not run --notify-cmd: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("--notify-cmd exited with {status}"))
+    }
+}
+
+/// Show a desktop notification: `notify-send` on Linux and BSD,
+/// `osascript` on macOS.
+pub fn desktop(url: &str, transition: &Transition, detail: &str) -> Result<(), String> {
+    let title = if transition.is_recovery() {
+        "rustcurl: recovered"
+    } else {
+        "rustcurl: status changed"
+    };
+    let body = summary(url, transition, detail);
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {} with title {}", applescript_quote(&body), applescript_quote(title));
+        let mut c = Command::new("osascript");
+        c.arg("-e").arg(script);
+        c
+    } else {
+        let mut c = Command::new("notify-send");
+        c.arg(title).arg(body);
+        c
+    };
+    let status = command
+        .status()
+        .map_err(|e| format!("could not show desktop notification: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("desktop notification exited with {status}"))
+    }
+}
+
+fn applescript_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK: StatusClass = StatusClass::Http(2);
+    const DOWN: StatusClass = StatusClass::Http(5);
+
+    #[test]
+    fn first_attempt_is_baseline() {
+        let mut d = Debouncer::new(1);
+        assert_eq!(d.observe(DOWN), None);
+        assert_eq!(d.observe(DOWN), None);
+        assert_eq!(d.observe(OK), Some(Transition { from: DOWN, to: OK }));
+    }
+
+    #[test]
+    fn debounce_needs_consecutive_attempts() {
+        let mut d = Debouncer::new(3);
+        d.observe(OK);
+        assert_eq!(d.observe(DOWN), None);
+        assert_eq!(d.observe(DOWN), None);
+        // A blip back to 2xx resets the count
+        assert_eq!(d.observe(OK), None);
+        assert_eq!(d.observe(DOWN), None);
+        assert_eq!(d.observe(DOWN), None);
+        assert_eq!(d.observe(DOWN), Some(Transition { from: OK, to: DOWN }));
+        assert_eq!(d.observe(DOWN), None);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(StatusClass::Http(4).to_string(), "4xx");
+        assert_eq!(StatusClass::Failed.to_string(), "error");
+        let t = Transition { from: StatusClass::Failed, to: OK };
+        assert!(t.is_recovery());
+        assert_eq!(summary("https://x.com/", &t, "200"), "https://x.com/: error -> 2xx (200)");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_gets_transition_env() {
+        let path = std::env::temp_dir().join(format!("rustcurl-notify-{}.txt", std::process::id()));
+        let cmd = format!("echo \"$RUSTCURL_FROM $RUSTCURL_TO $RUSTCURL_RECOVERED $RUSTCURL_DETAIL\" > {}", path.display());
+        let t = Transition { from: OK, to: DOWN };
+        run_command(&cmd, "https://x.com/", &t, "503").unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written, "2xx 5xx 0 503\n");
+        assert!(run_command("exit 3", "u", &t, "").unwrap_err().contains("exited"));
+    }
+}
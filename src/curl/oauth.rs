@@ -0,0 +1,139 @@
+// src/curl/oauth.rs
+
+//! OAuth2 token refresh: when a `--bearer` request comes back 401 and
+//! `--oauth2-token-url` is set, trade the refresh token for a new access
+//! token (RFC 6749 section 6) and retry once.
+
+use super::config::{Method, RequestConfig};
+use super::error::RequestError;
+use super::response::Response;
+
+/// The refresh token: `--oauth2-refresh-token`, else `RUSTCURL_REFRESH_TOKEN`.
+pub fn resolve_refresh_token(config: &RequestConfig) -> Option<String> {
+    config
+        .oauth2_refresh_token
+        .clone()
+        .or_else(|| config.env_var("RUSTCURL_REFRESH_TOKEN"))
+}
+
+/// A request to a token endpoint at `url`: the same transport settings
+/// (proxy, TLS, timeouts) as `config` but none of its payload, headers
+/// or credentials.
+pub fn endpoint_request(config: &RequestConfig, url: &str) -> RequestConfig {
+    let mut fetch = config.clone();
+    fetch.url = url.to_string();
+    fetch.method = Method::Get;
+    fetch.headers = vec!["Accept: application/json".to_string()];
+    fetch.data = None;
+    fetch.form_fields.clear();
+    fetch.query_data = false;
+    fetch.crlf = false;
+    fetch.head_only = false;
+    fetch.output = None;
+    fetch.remote_name = false;
+    fetch.range = None;
+    fetch.extract = false;
+    fetch.provenance = false;
+    fetch.verify_sig = None;
+    fetch.verify_key = None;
+    fetch.output_charset = None;
+    fetch.normalize_eol = None;
+    fetch.upload_file = None;
+    fetch.bearer = None;
+    fetch.username = None;
+    fetch.password = None;
+    fetch.negotiate = false;
+    fetch.ntlm = false;
+    fetch.anyauth = false;
+    fetch.auth_chain.clear();
+    fetch.oauth2_token_url = None;
+    fetch.registry_auth = false;
+    fetch
+}
+
+/// The refresh grant: a form POST to `token_url`.
+pub fn refresh_request(config: &RequestConfig, token_url: &str) -> Result<RequestConfig, RequestError> {
+    let refresh_token = resolve_refresh_token(config).ok_or_else(|| RequestError::TokenRefresh {
+        url: token_url.to_string(),
+        message: "no refresh token (--oauth2-refresh-token or RUSTCURL_REFRESH_TOKEN)".to_string(),
+    })?;
+    let mut fetch = endpoint_request(config, token_url).method(Method::Post);
+    fetch = fetch
+        .form_field("grant_type", "refresh_token")
+        .form_field("refresh_token", &refresh_token);
+    if let Some(ref id) = config.oauth2_client_id {
+        fetch = fetch.form_field("client_id", id);
+    }
+    if let Some(ref secret) = config.oauth2_client_secret {
+        fetch = fetch.form_field("client_secret", secret);
+    }
+    Ok(fetch)
+}
+
+/// `access_token` from the token endpoint's JSON reply.
+pub fn access_token(token_url: &str, response: &Response) -> Result<String, RequestError> {
+    let err = |message: String| RequestError::TokenRefresh {
+        url: token_url.to_string(),
+        message,
+    };
+    let json: serde_json::Value = serde_json::from_slice(&response.body)
+        .map_err(|e| err(format!("HTTP {}, reply is not JSON: {e}", response.status_code)))?;
+    if !(200..300).contains(&response.status_code) {
+        let reason = json["error_description"].as_str().or(json["error"].as_str()).unwrap_or("no error given");
+        return Err(err(format!("HTTP {}: {reason}", response.status_code)));
+    }
+    json["access_token"]
+        .as_str()
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| err("reply has no access_token".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(status: u32, body: &str) -> Response {
+        Response {
+            status_code: status,
+            body: body.as_bytes().to_vec(),
+            attempts: vec![status],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn request_is_a_clean_form_post() {
+        let config = RequestConfig::new("https://api.x.com/items")
+            .header("X-Api: 1")
+            .data("q=1")
+            .bearer("old")
+            .oauth2_refresh_token("r&t")
+            .oauth2_client_id("cli");
+        let fetch = refresh_request(&config, "https://auth.x.com/token").unwrap();
+        assert_eq!(fetch.url, "https://auth.x.com/token");
+        assert_eq!(fetch.method, Method::Post);
+        assert_eq!(fetch.headers, vec!["Accept: application/json"]);
+        assert!(fetch.bearer.is_none());
+        assert_eq!(
+            fetch.body().unwrap(),
+            b"grant_type=refresh_token&refresh_token=r%26t&client_id=cli"
+        );
+    }
+
+    #[test]
+    fn missing_refresh_token() {
+        let config = RequestConfig::new("https://x.com").env(crate::curl::env::EnvSource::Disabled);
+        assert_eq!(refresh_request(&config, "https://x.com/token").unwrap_err().code(), "token_refresh");
+    }
+
+    #[test]
+    fn parses_access_token() {
+        let ok = reply(200, r#"{"access_token":"new","token_type":"Bearer","expires_in":3600}"#);
+        assert_eq!(access_token("u", &ok).unwrap(), "new");
+        let denied = reply(400, r#"{"error":"invalid_grant"}"#);
+        assert!(access_token("u", &denied).unwrap_err().to_string().contains("HTTP 400: invalid_grant"));
+        assert!(access_token("u", &reply(200, "{}")).is_err());
+        assert!(access_token("u", &reply(502, "<html>")).is_err());
+    }
+}
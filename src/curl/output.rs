@@ -0,0 +1,166 @@
+// src/curl/output.rs
+
+//! What the CLI writes to stdout for a finished request, as curl does: the
+//! body alone, with `-i` the status line and headers ahead of it, and with
+//! `-I` the headers alone. `--timing` and the `-w` template follow.
+//! When nothing needs the whole body first, the CLI streams it instead
+//! (see `can_stream`) and only the rest is written here.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use super::config::RequestConfig;
+use super::ranges;
+use super::response::Response;
+use super::writeout;
+
+/// Write `response` to `out` per the output flags in `config`.
+pub fn write(out: &mut impl Write, config: &RequestConfig, response: &Response) -> io::Result<()> {
+    let mut ends_with_newline = true;
+    if config.include || config.head_only {
+        write_headers(out, response)?;
+    }
+    if !config.head_only && response.saved_as.is_none() && !config.writes_stdout() {
+        let body = body(response);
+        out.write_all(&body)?;
+        ends_with_newline = body.is_empty() || body.ends_with(b"\n");
+    }
+    if config.show_timing
+        && let Some(ref timing) = response.timing
+    {
+        if !ends_with_newline {
+            writeln!(out)?;
+        }
+        writeln!(out)?;
+        write!(out, "{timing}")?;
+    }
+    if let Some(ref template) = config.write_out {
+        let template = writeout::parse(template).map_err(io::Error::other)?;
+        out.write_all(writeout::render(&template, config, Ok(response)).as_bytes())?;
+    }
+    out.flush()
+}
+
+/// Whether the body can go to stdout as it arrives (`-o -`) instead of
+/// being buffered: nothing is printed ahead of it, nothing rewrites or
+/// rereads it afterwards, and no retry or authentication round trip can
+/// send a second body after the first.
+pub fn can_stream(config: &RequestConfig) -> bool {
+    config.output.is_none()
+        && !config.remote_name
+        && !config.include
+        && !config.head_only
+        && config.range.is_none()
+        && !config.has_header("Range")
+        && config.output_charset.is_none()
+        && config.normalize_eol.is_none()
+        && config.decrypt_jwe.is_none()
+        && config.captures.is_empty()
+        && config.if_changed.is_none()
+        && config.verify_sig.is_none()
+        && config.retry == 0
+        && config.auth_chain.is_empty()
+        && config.oauth2_token_url.is_none()
+        && !config.registry_auth
+        && config.watch.is_none()
+        && config.batch.is_none()
+        && config.accept_push.is_none()
+        && config.relay.is_none()
+}
+
+/// Header lines, a blank line between the blocks of interim, redirect
+/// and final responses, and a blank line before the body.
+fn write_headers(out: &mut impl Write, response: &Response) -> io::Result<()> {
+    // Backends that report no status line still get one
+    if !response.headers.first().is_some_and(|h| is_status_line(h)) {
+        writeln!(out, "HTTP/1.1 {}", response.status_code)?;
+    }
+    for (i, header) in response.headers.iter().enumerate() {
+        if i > 0 && is_status_line(header) {
+            writeln!(out)?;
+        }
+        writeln!(out, "{header}")?;
+    }
+    writeln!(out)
+}
+
+fn is_status_line(line: &str) -> bool {
+    line.starts_with("HTTP/")
+}
+
+/// The body as received, or the reassembled whole for a multipart 206
+/// whose ranges leave no gap.
+fn body(response: &Response) -> Cow<'_, [u8]> {
+    match response.byte_ranges().and_then(|parts| ranges::reassemble(&parts)) {
+        Some(whole) => Cow::Owned(whole),
+        None => Cow::Borrowed(&response.body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn render(config: &RequestConfig, response: &Response) -> String {
+        let mut out = Vec::new();
+        write(&mut out, config, response).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn body_only_by_default() {
+        let r = Response::fixture(200, &["HTTP/1.1 200 OK", "Content-Type: text/plain"], b"hello");
+        assert_eq!(render(&RequestConfig::new("https://x.com"), &r), "hello");
+    }
+
+    #[test]
+    fn streams_only_when_nothing_needs_the_body() {
+        let config = RequestConfig::new("https://x.com/a");
+        assert!(can_stream(&config));
+        assert!(!can_stream(&config.clone().include(true)));
+        assert!(!can_stream(&config.clone().output("a.bin")));
+        assert!(!can_stream(&config.clone().retry(2)));
+        assert!(!can_stream(&config.clone().range("0-9")));
+        assert!(!can_stream(&config.clone().header("Range: bytes=0-9")));
+
+        // The streamed body is not written again
+        let r = Response::fixture(200, &["HTTP/1.1 200 OK"], b"");
+        assert_eq!(render(&config.output("-").write_out("%{http_code}"), &r), "200");
+    }
+
+    #[test]
+    fn include_prints_every_block() {
+        let r = Response::fixture(
+            200,
+            &[
+                "HTTP/1.1 301 Moved",
+                "Location: /b",
+                "HTTP/1.1 200 OK",
+                "Content-Type: text/plain",
+            ],
+            b"hello",
+        );
+        let config = RequestConfig::new("https://x.com").include(true);
+        assert_eq!(
+            render(&config, &r),
+            "HTTP/1.1 301 Moved\nLocation: /b\n\nHTTP/1.1 200 OK\nContent-Type: text/plain\n\nhello"
+        );
+    }
+
+    #[test]
+    fn status_line_added_when_missing() {
+        let r = Response::fixture(200, &["content-type: text/plain"], b"");
+        let config = RequestConfig::new("https://x.com").head_only(true);
+        assert_eq!(render(&config, &r), "HTTP/1.1 200\ncontent-type: text/plain\n\n");
+    }
+
+    #[test]
+    fn saved_body_not_printed() {
+        let mut r = Response::fixture(200, &["HTTP/1.1 200 OK"], b"");
+        r.saved_as = Some(PathBuf::from("/tmp/out"));
+        assert_eq!(render(&RequestConfig::new("https://x.com"), &r), "");
+        let config = RequestConfig::new("https://x.com").include(true);
+        assert_eq!(render(&config, &r), "HTTP/1.1 200 OK\n\n");
+    }
+}
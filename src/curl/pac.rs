@@ -0,0 +1,865 @@
+// src/curl/pac.rs
+
+//! `--proxy-pac <URL|FILE>`: the proxy for each request is chosen by the
+//! proxy auto-config script's `FindProxyForURL(url, host)`. PAC files are
+//! JavaScript; this runs the subset they are written in: functions, `var`,
+//! `if`/`else`, `return`, the usual operators, string methods and the PAC
+//! helpers (`shExpMatch`, `dnsDomainIs`, `isInNet`, ...). Loops, arrays,
+//! regular expressions and the date/time helpers are reported as
+//! unsupported. The first entry of the answer is used.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+
+use super::error::RequestError;
+use super::url;
+
+/// Nested calls allowed before a script is taken to recurse forever.
+const MAX_DEPTH: usize = 64;
+
+/// Nested expressions and statements the parser follows before refusing
+/// the script, so hostile input cannot exhaust the stack.
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+/// Longest first, so `===` is not read as `==` then `=`.
+const PUNCTUATION: &[&str] = &[
+    "===", "!==", "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", "[", "]", ",", ";", "=", "!", "+", "-",
+    "*", "/", "%", "<", ">", ".", "?", ":",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = src;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or("unterminated comment")?;
+            rest = &comment[end + 2..];
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            loop {
+                match chars.next() {
+                    Some((i, q)) if q == c => {
+                        rest = &rest[1 + i + 1..];
+                        break;
+                    }
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err("unterminated string".to_string()),
+                    },
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else if c.is_ascii_digit() {
+            let end = rest.find(|ch: char| !ch.is_ascii_digit() && ch != '.').unwrap_or(rest.len());
+            tokens.push(Token::Num(rest[..end].parse().map_err(|_| format!("bad number '{}'", &rest[..end]))?));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let end = rest.find(|ch: char| !ch.is_alphanumeric() && ch != '_' && ch != '$').unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let punct = PUNCTUATION.iter().find(|p| rest.starts_with(**p)).ok_or(format!("unexpected '{c}'"))?;
+            tokens.push(Token::Punct(punct));
+            rest = &rest[punct.len()..];
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Undefined,
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Num(n) => *n != 0.0 && !n.is_nan(),
+            Value::Bool(b) => *b,
+            Value::Undefined => false,
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Str(s) => s.clone(),
+            Value::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Value::Num(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Undefined => "undefined".to_string(),
+        }
+    }
+
+    fn number(&self) -> f64 {
+        match self {
+            Value::Str(s) if s.trim().is_empty() => 0.0,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::Num(n) => *n,
+            Value::Bool(b) => f64::from(u8::from(*b)),
+            Value::Undefined => f64::NAN,
+        }
+    }
+
+    /// `==`: strings compare as strings, `undefined` only to itself,
+    /// anything else as numbers.
+    fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Undefined, b) | (b, Value::Undefined) => *b == Value::Undefined,
+            (a, b) => a.number() == b.number(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Lit(Value),
+    Var(String),
+    Assign(String, Box<Expr>),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Prop(Box<Expr>, String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Var(Vec<(String, Option<Expr>)>),
+    Expr(Expr),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    Return(Option<Expr>),
+    Block(Vec<Stmt>),
+    Function(String, Function),
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn at(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.at(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) { Ok(()) } else { Err(format!("expected '{punct}' {}", self.near())) }
+    }
+
+    fn near(&self) -> String {
+        match self.peek() {
+            Some(Token::Ident(s)) => format!("near '{s}'"),
+            Some(Token::Str(s)) => format!("near \"{s}\""),
+            Some(Token::Num(n)) => format!("near {n}"),
+            Some(Token::Punct(p)) => format!("near '{p}'"),
+            None => "at end of script".to_string(),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(format!("expected a name {}", self.near())),
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(w)) if w == word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Runs `parse` one nesting level deeper.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth == MAX_NESTING {
+            return Err(format!("the script nests more than {MAX_NESTING} levels deep {}", self.near()));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        self.nested(Self::statement_inner)
+    }
+
+    fn statement_inner(&mut self) -> Result<Stmt, String> {
+        if self.keyword("function") {
+            let name = self.ident()?;
+            self.expect("(")?;
+            let mut params = Vec::new();
+            while !self.eat(")") {
+                params.push(self.ident()?);
+                if !self.at(")") {
+                    self.expect(",")?;
+                }
+            }
+            self.expect("{")?;
+            return Ok(Stmt::Function(name, Function { params, body: self.block()? }));
+        }
+        if self.keyword("var") || self.keyword("let") || self.keyword("const") {
+            let mut vars = Vec::new();
+            loop {
+                let name = self.ident()?;
+                let init = if self.eat("=") { Some(self.expr()?) } else { None };
+                vars.push((name, init));
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.eat(";");
+            return Ok(Stmt::Var(vars));
+        }
+        if self.keyword("if") {
+            self.expect("(")?;
+            let cond = self.expr()?;
+            self.expect(")")?;
+            let then = Box::new(self.statement()?);
+            let otherwise = if self.keyword("else") { Some(Box::new(self.statement()?)) } else { None };
+            return Ok(Stmt::If(cond, then, otherwise));
+        }
+        if self.keyword("return") {
+            let value = if self.at(";") || self.at("}") { None } else { Some(self.expr()?) };
+            self.eat(";");
+            return Ok(Stmt::Return(value));
+        }
+        if self.eat("{") {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        if self.eat(";") {
+            return Ok(Stmt::Block(Vec::new()));
+        }
+        if let Some(Token::Ident(word)) = self.peek()
+            && ["for", "while", "do", "switch", "try", "new"].contains(&word.as_str())
+        {
+            return Err(format!("'{word}' is not supported"));
+        }
+        let expr = self.expr()?;
+        self.eat(";");
+        Ok(Stmt::Expr(expr))
+    }
+
+    /// Statements up to and including the closing `}`.
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut body = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err("missing '}'".to_string());
+            }
+            body.push(self.statement()?);
+        }
+        Ok(body)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, String> {
+        let target = self.conditional()?;
+        if self.eat("=") {
+            let Expr::Var(name) = target else {
+                return Err("only variables can be assigned".to_string());
+            };
+            return Ok(Expr::Assign(name, Box::new(self.expr()?)));
+        }
+        Ok(target)
+    }
+
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let cond = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.expr()?;
+        self.expect(":")?;
+        Ok(Expr::Cond(Box::new(cond), Box::new(then), Box::new(self.expr()?)))
+    }
+
+    /// Binary operators by precedence, loosest first.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: &[&[&str]] = &[
+            &["||"],
+            &["&&"],
+            &["===", "!==", "==", "!="],
+            &["<=", ">=", "<", ">"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        let Some(ops) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Punct(op)) = self.peek()
+            && ops.contains(op)
+        {
+            let op = *op;
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.binary(level + 1)?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for op in ["!", "-"] {
+            if self.eat(op) {
+                return Ok(Expr::Unary(op, Box::new(self.nested(Self::unary)?)));
+            }
+        }
+        let mut expr = self.primary()?;
+        while self.eat(".") {
+            let name = self.ident()?;
+            expr = if self.eat("(") {
+                Expr::Method(Box::new(expr), name, self.args()?)
+            } else {
+                Expr::Prop(Box::new(expr), name)
+            };
+        }
+        Ok(expr)
+    }
+
+    /// Call arguments after the `(`, through the `)`.
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        while !self.eat(")") {
+            args.push(self.expr()?);
+            if !self.at(")") {
+                self.expect(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self.peek().cloned().ok_or("unexpected end of script")?;
+        self.pos += 1;
+        match token {
+            Token::Str(s) => Ok(Expr::Lit(Value::Str(s))),
+            Token::Num(n) => Ok(Expr::Lit(Value::Num(n))),
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) => Ok(match name.as_str() {
+                "true" => Expr::Lit(Value::Bool(true)),
+                "false" => Expr::Lit(Value::Bool(false)),
+                "null" | "undefined" => Expr::Lit(Value::Undefined),
+                _ if self.eat("(") => Expr::Call(name, self.args()?),
+                _ => Expr::Var(name),
+            }),
+            Token::Punct(p) => {
+                self.pos -= 1;
+                Err(format!("unexpected '{p}' {}", self.near()))
+            }
+        }
+    }
+}
+
+/// Host lookups for the DNS helpers, swappable in tests.
+pub type Resolver = dyn Fn(&str) -> Option<IpAddr> + Send + Sync;
+
+fn system_resolve(host: &str) -> Option<IpAddr> {
+    if let Ok(ip) = host.parse() {
+        return Some(ip);
+    }
+    let addrs: Vec<IpAddr> = (host, 0).to_socket_addrs().ok()?.map(|a| a.ip()).collect();
+    addrs.iter().find(|ip| ip.is_ipv4()).or(addrs.first()).copied()
+}
+
+/// The address of the interface with the default route; no packet is sent.
+fn my_ip_address() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| socket.connect("198.51.100.1:53").and_then(|()| socket.local_addr()))
+        .map_or(IpAddr::V4(Ipv4Addr::LOCALHOST), |addr| addr.ip())
+}
+
+/// `*` and `?` wildcards, the rest literal.
+fn sh_exp_match(text: &str, pattern: &str) -> bool {
+    let (text, pattern): (Vec<char>, Vec<char>) = (text.chars().collect(), pattern.chars().collect());
+    let (mut t, mut p, mut star, mut mark) = (0, 0, None, 0);
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            mark = t;
+            p += 1;
+        } else if let Some(s) = star {
+            p = s + 1;
+            mark += 1;
+            t = mark;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// A parsed PAC script.
+#[derive(Debug, Clone)]
+pub struct Script {
+    program: Vec<Stmt>,
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+}
+
+struct Interpreter<'a> {
+    functions: HashMap<String, Function>,
+    /// Globals first, then one frame per active call.
+    frames: Vec<HashMap<String, Value>>,
+    resolve: &'a Resolver,
+}
+
+impl Interpreter<'_> {
+    fn lookup(&self, name: &str) -> Result<Value, String> {
+        let local = self.frames.last().and_then(|frame| frame.get(name));
+        local
+            .or_else(|| self.frames[0].get(name))
+            .cloned()
+            .ok_or_else(|| format!("'{name}' is not defined"))
+    }
+
+    fn assign(&mut self, name: &str, value: Value) {
+        let frame = match self.frames.last() {
+            Some(frame) if frame.contains_key(name) => self.frames.len() - 1,
+            _ => 0,
+        };
+        self.frames[frame].insert(name.to_string(), value);
+    }
+
+    fn run(&mut self, body: &[Stmt]) -> Result<Flow, String> {
+        for stmt in body {
+            if let Flow::Return(value) = self.exec(stmt)? {
+                return Ok(Flow::Return(value));
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec(&mut self, stmt: &Stmt) -> Result<Flow, String> {
+        match stmt {
+            Stmt::Var(vars) => {
+                for (name, init) in vars {
+                    let value = match init {
+                        Some(expr) => self.eval(expr)?,
+                        None => Value::Undefined,
+                    };
+                    self.frames.last_mut().expect("global frame").insert(name.clone(), value);
+                }
+            }
+            Stmt::Expr(expr) => {
+                self.eval(expr)?;
+            }
+            Stmt::If(cond, then, otherwise) => {
+                if self.eval(cond)?.truthy() {
+                    return self.exec(then);
+                } else if let Some(otherwise) = otherwise {
+                    return self.exec(otherwise);
+                }
+            }
+            Stmt::Return(value) => {
+                let value = value.as_ref().map_or(Ok(Value::Undefined), |expr| self.eval(expr))?;
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Block(body) => return self.run(body),
+            Stmt::Function(name, function) => {
+                self.functions.insert(name.clone(), function.clone());
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, String> {
+        Ok(match expr {
+            Expr::Lit(value) => value.clone(),
+            Expr::Var(name) => self.lookup(name)?,
+            Expr::Assign(name, value) => {
+                let value = self.eval(value)?;
+                self.assign(name, value.clone());
+                value
+            }
+            Expr::Call(name, args) => {
+                let args = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                self.call(name, args)?
+            }
+            Expr::Method(target, name, args) => {
+                let target = self.eval(target)?.text();
+                let args = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                string_method(&target, name, &args)?
+            }
+            Expr::Prop(target, name) => match name.as_str() {
+                "length" => Value::Num(self.eval(target)?.text().chars().count() as f64),
+                _ => return Err(format!("property '{name}' is not supported")),
+            },
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand)?;
+                match *op {
+                    "!" => Value::Bool(!value.truthy()),
+                    _ => Value::Num(-value.number()),
+                }
+            }
+            Expr::Binary("&&", left, right) => {
+                let left = self.eval(left)?;
+                if left.truthy() { self.eval(right)? } else { left }
+            }
+            Expr::Binary("||", left, right) => {
+                let left = self.eval(left)?;
+                if left.truthy() { left } else { self.eval(right)? }
+            }
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                binary(op, &left, &right)
+            }
+            Expr::Cond(cond, then, otherwise) => {
+                if self.eval(cond)?.truthy() {
+                    self.eval(then)?
+                } else {
+                    self.eval(otherwise)?
+                }
+            }
+        })
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        if let Some(function) = self.functions.get(name).cloned() {
+            if self.frames.len() > MAX_DEPTH {
+                return Err(format!("calls nested too deeply in '{name}'"));
+            }
+            let mut frame = HashMap::new();
+            for (i, param) in function.params.iter().enumerate() {
+                frame.insert(param.clone(), args.get(i).cloned().unwrap_or(Value::Undefined));
+            }
+            self.frames.push(frame);
+            let flow = self.run(&function.body);
+            self.frames.pop();
+            return Ok(match flow? {
+                Flow::Return(value) => value,
+                Flow::Next => Value::Undefined,
+            });
+        }
+        let arg = |i: usize| args.get(i).map_or(String::new(), Value::text);
+        let resolve = |host: &str| (self.resolve)(host);
+        Ok(match name {
+            "isPlainHostName" => Value::Bool(!arg(0).contains('.')),
+            "dnsDomainIs" => Value::Bool(arg(0).to_ascii_lowercase().ends_with(&arg(1).to_ascii_lowercase())),
+            "localHostOrDomainIs" => {
+                let (host, full) = (arg(0).to_ascii_lowercase(), arg(1).to_ascii_lowercase());
+                Value::Bool(host == full || (!host.contains('.') && full.starts_with(&format!("{host}."))))
+            }
+            "dnsDomainLevels" => Value::Num(arg(0).matches('.').count() as f64),
+            "shExpMatch" => Value::Bool(sh_exp_match(&arg(0), &arg(1))),
+            "isResolvable" => Value::Bool(resolve(&arg(0)).is_some()),
+            "dnsResolve" => resolve(&arg(0)).map_or(Value::Undefined, |ip| Value::Str(ip.to_string())),
+            "myIpAddress" => Value::Str(my_ip_address().to_string()),
+            "isInNet" => {
+                let ip = match resolve(&arg(0)) {
+                    Some(IpAddr::V4(ip)) => u32::from(ip),
+                    _ => return Ok(Value::Bool(false)),
+                };
+                let (Ok(net), Ok(mask)) = (arg(1).parse::<Ipv4Addr>(), arg(2).parse::<Ipv4Addr>()) else {
+                    return Ok(Value::Bool(false));
+                };
+                Value::Bool(ip & u32::from(mask) == u32::from(net) & u32::from(mask))
+            }
+            "convert_addr" => Value::Num(arg(0).parse::<Ipv4Addr>().map_or(0.0, |ip| f64::from(u32::from(ip)))),
+            "alert" => Value::Undefined,
+            _ => return Err(format!("function '{name}' is not supported")),
+        })
+    }
+}
+
+fn binary(op: &str, left: &Value, right: &Value) -> Value {
+    let strings = matches!(left, Value::Str(_)) && matches!(right, Value::Str(_));
+    match op {
+        "+" if matches!(left, Value::Str(_)) || matches!(right, Value::Str(_)) => {
+            Value::Str(left.text() + &right.text())
+        }
+        "+" => Value::Num(left.number() + right.number()),
+        "-" => Value::Num(left.number() - right.number()),
+        "*" => Value::Num(left.number() * right.number()),
+        "/" => Value::Num(left.number() / right.number()),
+        "%" => Value::Num(left.number() % right.number()),
+        "===" => Value::Bool(left == right),
+        "!==" => Value::Bool(left != right),
+        "==" => Value::Bool(left.loose_eq(right)),
+        "!=" => Value::Bool(!left.loose_eq(right)),
+        "<" if strings => Value::Bool(left.text() < right.text()),
+        ">" if strings => Value::Bool(left.text() > right.text()),
+        "<=" if strings => Value::Bool(left.text() <= right.text()),
+        ">=" if strings => Value::Bool(left.text() >= right.text()),
+        "<" => Value::Bool(left.number() < right.number()),
+        ">" => Value::Bool(left.number() > right.number()),
+        "<=" => Value::Bool(left.number() <= right.number()),
+        _ => Value::Bool(left.number() >= right.number()),
+    }
+}
+
+fn string_method(text: &str, name: &str, args: &[Value]) -> Result<Value, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let len = chars.len() as f64;
+    let index = |i: usize, default: f64| {
+        let n = args.get(i).map_or(default, Value::number);
+        if n.is_nan() { 0 } else { n.clamp(0.0, len) as usize }
+    };
+    let slice = |from: usize, to: usize| chars[from..to.max(from)].iter().collect::<String>();
+    let needle = args.first().map_or("undefined".to_string(), Value::text);
+    let position = |found: Option<usize>| Value::Num(found.map_or(-1.0, |byte| text[..byte].chars().count() as f64));
+    Ok(match name {
+        "toLowerCase" => Value::Str(text.to_lowercase()),
+        "toUpperCase" => Value::Str(text.to_uppercase()),
+        "trim" => Value::Str(text.trim().to_string()),
+        "indexOf" => position(text.find(&needle)),
+        "lastIndexOf" => position(text.rfind(&needle)),
+        "startsWith" => Value::Bool(text.starts_with(&needle)),
+        "endsWith" => Value::Bool(text.ends_with(&needle)),
+        "includes" => Value::Bool(text.contains(&needle)),
+        "charAt" => Value::Str(chars.get(index(0, 0.0)).map(char::to_string).unwrap_or_default()),
+        "substring" => {
+            let (a, b) = (index(0, 0.0), index(1, len));
+            Value::Str(slice(a.min(b), a.max(b)))
+        }
+        "substr" => {
+            let from = index(0, 0.0);
+            let count = args.get(1).map_or(chars.len(), |n| n.number().max(0.0) as usize);
+            Value::Str(slice(from, from.saturating_add(count).min(chars.len())))
+        }
+        _ => return Err(format!("method '{name}' is not supported")),
+    })
+}
+
+impl Script {
+    pub fn parse(src: &str) -> Result<Script, String> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+            depth: 0,
+        };
+        let mut program = Vec::new();
+        while parser.peek().is_some() {
+            program.push(parser.statement()?);
+        }
+        Ok(Script { program })
+    }
+
+    /// `FindProxyForURL(url, host)`'s answer, with host names looked up by
+    /// `resolve`.
+    pub fn find_proxy(&self, url: &str, host: &str, resolve: &Resolver) -> Result<String, String> {
+        let mut interpreter = Interpreter {
+            functions: HashMap::new(),
+            frames: vec![HashMap::new()],
+            resolve,
+        };
+        interpreter.run(&self.program)?;
+        if !interpreter.functions.contains_key("FindProxyForURL") {
+            return Err("the script defines no FindProxyForURL".to_string());
+        }
+        let args = vec![Value::Str(url.to_string()), Value::Str(host.to_string())];
+        interpreter.call("FindProxyForURL", args).map(|value| value.text())
+    }
+}
+
+/// Where a PAC answer sends a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    Direct,
+    /// A proxy URL for `-x`.
+    Proxy(String),
+}
+
+/// The first usable entry of a `PROXY host:port; SOCKS5 host:port; DIRECT`
+/// answer. `SOCKS` is SOCKS4, as browsers read it.
+pub fn first_route(answer: &str) -> Option<Route> {
+    answer.split(';').find_map(|entry| {
+        let mut words = entry.split_whitespace();
+        let kind = words.next()?.to_ascii_uppercase();
+        if kind == "DIRECT" {
+            return Some(Route::Direct);
+        }
+        let scheme = match kind.as_str() {
+            "PROXY" | "HTTP" => "http",
+            "HTTPS" => "https",
+            "SOCKS" | "SOCKS4" => "socks4",
+            "SOCKS5" => "socks5",
+            _ => return None,
+        };
+        Some(Route::Proxy(format!("{scheme}://{}", words.next()?)))
+    })
+}
+
+/// Scripts already fetched, by location, so each is loaded once per run.
+static SCRIPTS: Mutex<Vec<(String, Arc<Script>)>> = Mutex::new(Vec::new());
+
+/// The script at `location`, fetched with `fetch` (given an http(s) URL)
+/// or read from a file, parsed once and kept for the rest of the process.
+pub fn load(
+    location: &str,
+    fetch: impl FnOnce(&str) -> Result<Vec<u8>, RequestError>,
+) -> Result<Arc<Script>, RequestError> {
+    if let Some((_, script)) = SCRIPTS.lock().unwrap_or_else(|e| e.into_inner()).iter().find(|(l, _)| l == location) {
+        return Ok(Arc::clone(script));
+    }
+    let source = if location.starts_with("http://") || location.starts_with("https://") {
+        fetch(location)?
+    } else {
+        std::fs::read(location.strip_prefix("file://").unwrap_or(location))?
+    };
+    let script = Script::parse(&String::from_utf8_lossy(&source)).map_err(|e| pac_error(location, &e))?;
+    let script = Arc::new(script);
+    SCRIPTS.lock().unwrap_or_else(|e| e.into_inner()).push((location.to_string(), Arc::clone(&script)));
+    Ok(script)
+}
+
+/// The route `script` picks for `request_url`.
+pub fn route(script: &Script, location: &str, request_url: &str) -> Result<Route, RequestError> {
+    let parts = url::parse(request_url).map_err(|e| pac_error(location, &e))?;
+    let answer = script.find_proxy(request_url, parts.host, &system_resolve).map_err(|e| pac_error(location, &e))?;
+    first_route(&answer).ok_or_else(|| pac_error(location, &format!("FindProxyForURL returned '{answer}'")))
+}
+
+fn pac_error(location: &str, message: &str) -> RequestError {
+    RequestError::Config {
+        option: "--proxy-pac".to_string(),
+        message: format!("{location}: {message}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAC: &str = r#"
+        // Corporate PAC
+        var proxy = "PROXY proxy.corp:8080";
+        function isInternal(host) {
+            return dnsDomainIs(host, ".corp.example") || isInNet(host, "10.0.0.0", "255.0.0.0");
+        }
+        function FindProxyForURL(url, host) {
+            host = host.toLowerCase();
+            if (isPlainHostName(host) || isInternal(host))
+                return "DIRECT";
+            else if (shExpMatch(url, "https://*.cdn.example/*") && url.substring(0, 5) === "https") {
+                return "SOCKS5 socks.corp:1080; DIRECT";
+            }
+            /* everything else */
+            return host.indexOf("test") != -1 ? "PROXY test.corp:" + (8000 + 81) : proxy + "; DIRECT";
+        }
+    "#;
+
+    fn resolve(host: &str) -> Option<IpAddr> {
+        match host {
+            "db.lan" => Some(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))),
+            _ => host.parse().ok(),
+        }
+    }
+
+    fn answer(url: &str, host: &str) -> String {
+        Script::parse(PAC).unwrap().find_proxy(url, host, &resolve).unwrap()
+    }
+
+    #[test]
+    fn runs_find_proxy_for_url() {
+        assert_eq!(answer("http://intranet/", "intranet"), "DIRECT");
+        assert_eq!(answer("http://WIKI.corp.example/", "WIKI.corp.example"), "DIRECT");
+        assert_eq!(answer("http://db.lan/", "db.lan"), "DIRECT");
+        assert_eq!(answer("https://a.cdn.example/x", "a.cdn.example"), "SOCKS5 socks.corp:1080; DIRECT");
+        assert_eq!(answer("http://test.example.com/", "test.example.com"), "PROXY test.corp:8081");
+        assert_eq!(answer("https://example.com/", "example.com"), "PROXY proxy.corp:8080; DIRECT");
+    }
+
+    #[test]
+    fn reports_what_it_cannot_run() {
+        let unsupported = Script::parse("function FindProxyForURL(u, h) { for (;;) {} }").unwrap_err();
+        assert_eq!(unsupported, "'for' is not supported");
+        let script = Script::parse("function FindProxyForURL(u, h) { return weekdayRange('MON', 'FRI') }").unwrap();
+        assert_eq!(script.find_proxy("u", "h", &resolve).unwrap_err(), "function 'weekdayRange' is not supported");
+        let missing = Script::parse("var x = 1;").unwrap().find_proxy("u", "h", &resolve).unwrap_err();
+        assert_eq!(missing, "the script defines no FindProxyForURL");
+        let recursive = Script::parse("function FindProxyForURL(u, h) { return FindProxyForURL(u, h) }").unwrap();
+        assert!(recursive.find_proxy("u", "h", &resolve).unwrap_err().contains("nested too deeply"));
+        assert!(Script::parse("function FindProxyForURL(u, h) { return 'DIRECT'").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_refused() {
+        let script = |body: String| Script::parse(&format!("function FindProxyForURL(u, h) {{ return {body} }}"));
+        let nots = "!".repeat(200_000) + "true";
+        assert!(script(nots).unwrap_err().contains("nests more than 64 levels"));
+        let parens = "(".repeat(200_000) + "1" + &")".repeat(200_000);
+        assert!(script(parens).unwrap_err().contains("nests more than 64 levels"));
+        let blocks = format!("function FindProxyForURL(u, h) {{ {}{} }}", "{".repeat(200_000), "}".repeat(200_000));
+        assert!(Script::parse(&blocks).unwrap_err().contains("nests more than 64 levels"));
+        let fine = "(".repeat(40) + "'DIRECT'" + &")".repeat(40);
+        assert_eq!(script(fine).unwrap().find_proxy("u", "h", &resolve).unwrap(), "DIRECT");
+    }
+
+    #[test]
+    fn picks_first_route() {
+        assert_eq!(first_route("PROXY p:3128; DIRECT"), Some(Route::Proxy("http://p:3128".into())));
+        assert_eq!(first_route(" SOCKS s:1080"), Some(Route::Proxy("socks4://s:1080".into())));
+        assert_eq!(first_route("QUIC q:443; direct"), Some(Route::Direct));
+        assert_eq!(first_route(""), None);
+    }
+
+    #[test]
+    fn wildcard_match() {
+        assert!(sh_exp_match("http://home.netscape.com/people/ari/index.html", "*/ari/*"));
+        assert!(sh_exp_match("abc", "a?c"));
+        assert!(!sh_exp_match("http://home.netscape.com/people/montulli/index.html", "*/ari/*"));
+        assert!(sh_exp_match("", "*"));
+    }
+}
@@ -0,0 +1,150 @@
+// src/curl/paths.rs
+
+//! Where rustcurl keeps its own files, per platform. `RUSTCURL_HOME`, when
+//! set, holds them all (`config`, `cache`, `data` and `state` under it).
+//! Otherwise Linux and other Unix systems follow the XDG base directory
+//! spec; macOS uses `~/Library` unless an `XDG_*` variable is set; Windows
+//! uses the Roaming and Local AppData known folders, as `%APPDATA%` and
+//! `%LOCALAPPDATA%` name them.
+
+use std::path::PathBuf;
+
+use super::env::EnvSource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Settings the user writes.
+    Config,
+    /// Files that can be deleted at any time.
+    Cache,
+    /// Files rustcurl writes that are worth keeping, such as stored tokens.
+    Data,
+    /// History and totals (`--session`).
+    State,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Config => "config",
+            Kind::Cache => "cache",
+            Kind::Data => "data",
+            Kind::State => "state",
+        }
+    }
+
+    fn xdg(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Kind::Config => ("XDG_CONFIG_HOME", &[".config"]),
+            Kind::Cache => ("XDG_CACHE_HOME", &[".cache"]),
+            Kind::Data => ("XDG_DATA_HOME", &[".local", "share"]),
+            Kind::State => ("XDG_STATE_HOME", &[".local", "state"]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    Unix,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    fn current() -> Platform {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Unix
+        }
+    }
+}
+
+/// rustcurl's directory of `kind`, or `None` when the variables it is
+/// derived from are unset.
+pub fn dir(env: &EnvSource, kind: Kind) -> Option<PathBuf> {
+    dir_for(env, kind, Platform::current())
+}
+
+/// What to tell the user when `dir` comes back empty.
+pub fn missing(kind: Kind) -> String {
+    let vars = match Platform::current() {
+        Platform::Windows if kind == Kind::Config || kind == Kind::Data => "APPDATA",
+        Platform::Windows => "LOCALAPPDATA",
+        _ => "HOME",
+    };
+    format!("no {} directory; set RUSTCURL_HOME or {vars}", kind.name())
+}
+
+fn dir_for(env: &EnvSource, kind: Kind, platform: Platform) -> Option<PathBuf> {
+    let var = |name: &str| env.get(name).filter(|d| !d.is_empty()).map(PathBuf::from);
+    if let Some(home) = var("RUSTCURL_HOME") {
+        return Some(home.join(kind.name()));
+    }
+    let (xdg, fallback) = kind.xdg();
+    let home = || var("HOME");
+    match platform {
+        Platform::Windows => match kind {
+            Kind::Config | Kind::Data => var("APPDATA").map(|d| d.join("rustcurl")),
+            Kind::Cache => var("LOCALAPPDATA").map(|d| d.join("rustcurl").join("cache")),
+            Kind::State => var("LOCALAPPDATA").map(|d| d.join("rustcurl")),
+        },
+        Platform::MacOs if var(xdg).is_none() => {
+            let library = home()?.join("Library");
+            Some(match kind {
+                Kind::Cache => library.join("Caches").join("rustcurl"),
+                Kind::Config | Kind::Data | Kind::State => library.join("Application Support").join("rustcurl"),
+            })
+        }
+        Platform::MacOs | Platform::Unix => {
+            let base = var(xdg).or_else(|| home().map(|home| fallback.iter().fold(home, |dir, part| dir.join(part))))?;
+            Some(base.join("rustcurl"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(pairs: &[(&str, &str)], kind: Kind, platform: Platform) -> Option<String> {
+        let env = EnvSource::from_pairs(pairs.iter().copied());
+        dir_for(&env, kind, platform).map(|dir| dir.to_string_lossy().replace('\\', "/"))
+    }
+
+    #[test]
+    fn xdg_with_home_fallback() {
+        let home = [("HOME", "/home/u")];
+        assert_eq!(at(&home, Kind::State, Platform::Unix).as_deref(), Some("/home/u/.local/state/rustcurl"));
+        assert_eq!(at(&home, Kind::Data, Platform::Unix).as_deref(), Some("/home/u/.local/share/rustcurl"));
+        let xdg = [("HOME", "/home/u"), ("XDG_CACHE_HOME", "/var/cache/u")];
+        assert_eq!(at(&xdg, Kind::Cache, Platform::Unix).as_deref(), Some("/var/cache/u/rustcurl"));
+        assert_eq!(at(&[("XDG_CONFIG_HOME", "")], Kind::Config, Platform::Unix), None);
+    }
+
+    #[test]
+    fn macos_and_windows() {
+        let home = [("HOME", "/Users/u")];
+        assert_eq!(at(&home, Kind::Cache, Platform::MacOs).as_deref(), Some("/Users/u/Library/Caches/rustcurl"));
+        assert_eq!(
+            at(&home, Kind::State, Platform::MacOs).as_deref(),
+            Some("/Users/u/Library/Application Support/rustcurl")
+        );
+        let xdg = [("HOME", "/Users/u"), ("XDG_STATE_HOME", "/tmp/s")];
+        assert_eq!(at(&xdg, Kind::State, Platform::MacOs).as_deref(), Some("/tmp/s/rustcurl"));
+        let win = [("APPDATA", "C:/Users/u/AppData/Roaming"), ("LOCALAPPDATA", "C:/Users/u/AppData/Local")];
+        assert_eq!(at(&win, Kind::Config, Platform::Windows).as_deref(), Some("C:/Users/u/AppData/Roaming/rustcurl"));
+        assert_eq!(at(&win, Kind::Cache, Platform::Windows).as_deref(), Some("C:/Users/u/AppData/Local/rustcurl/cache"));
+        assert_eq!(at(&[("HOME", "/h")], Kind::State, Platform::Windows), None);
+    }
+
+    #[test]
+    fn rustcurl_home_overrides() {
+        let env = [("RUSTCURL_HOME", "/opt/rc"), ("XDG_STATE_HOME", "/s"), ("LOCALAPPDATA", "C:/L")];
+        for platform in [Platform::Unix, Platform::MacOs, Platform::Windows] {
+            assert_eq!(at(&env, Kind::State, platform).as_deref(), Some("/opt/rc/state"));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// src/curl/presets.rs
+
+//! Named clients for `--user-agent-preset` and `--impersonate`: the
+//! User-Agent each one sends and, for browsers, the headers that go with
+//! it, so servers that vary on the device can be tested from the CLI.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Chrome,
+    Firefox,
+    Safari,
+    Curl,
+    Googlebot,
+}
+
+/// Every preset, in the order listed in `--help`.
+pub const ALL: [Preset; 5] = [Preset::Chrome, Preset::Firefox, Preset::Safari, Preset::Curl, Preset::Googlebot];
+
+impl Preset {
+    pub fn parse(s: &str) -> Option<Self> {
+        ALL.into_iter().find(|p| p.name().eq_ignore_ascii_case(s.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Chrome => "chrome",
+            Preset::Firefox => "firefox",
+            Preset::Safari => "safari",
+            Preset::Curl => "curl",
+            Preset::Googlebot => "googlebot",
+        }
+    }
+
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            Preset::Chrome => {
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+            }
+            Preset::Firefox => "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:133.0) Gecko/20100101 Firefox/133.0",
+            Preset::Safari => {
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.1 Safari/605.1.15"
+            }
+            Preset::Curl => "curl/8.11.0",
+            Preset::Googlebot => {
+                "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)"
+            }
+        }
+    }
+
+    /// Only browsers can be impersonated; the others are just a User-Agent.
+    pub fn is_browser(&self) -> bool {
+        matches!(self, Preset::Chrome | Preset::Firefox | Preset::Safari)
+    }
+
+    /// Headers the browser sends on a top-level navigation, besides the
+    /// User-Agent.
+    pub fn headers(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Preset::Chrome => &[
+                (
+                    "Accept",
+                    "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7",
+                ),
+                ("Accept-Language", "en-US,en;q=0.9"),
+                ("sec-ch-ua", "\"Google Chrome\";v=\"131\", \"Chromium\";v=\"131\", \"Not_A Brand\";v=\"24\""),
+                ("sec-ch-ua-mobile", "?0"),
+                ("sec-ch-ua-platform", "\"Windows\""),
+                ("Upgrade-Insecure-Requests", "1"),
+            ],
+            Preset::Firefox => &[
+                ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+                ("Accept-Language", "en-US,en;q=0.5"),
+                ("Upgrade-Insecure-Requests", "1"),
+            ],
+            Preset::Safari => &[
+                ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+                ("Accept-Language", "en-US,en;q=0.9"),
+            ],
+            Preset::Curl | Preset::Googlebot => &[],
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// `chrome|firefox|...` for usage and error messages.
+pub fn names() -> String {
+    ALL.map(|p| p.name()).join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names() {
+        for preset in ALL {
+            assert_eq!(Preset::parse(preset.name()), Some(preset));
+        }
+        assert_eq!(Preset::parse("Chrome"), Some(Preset::Chrome));
+        assert_eq!(Preset::parse("edge"), None);
+        assert_eq!(names(), "chrome|firefox|safari|curl|googlebot");
+    }
+
+    #[test]
+    fn only_browsers_send_extra_headers() {
+        for preset in ALL {
+            assert_eq!(preset.is_browser(), !preset.headers().is_empty(), "{preset}");
+        }
+        assert!(Preset::Chrome.headers().iter().any(|(name, _)| *name == "sec-ch-ua"));
+        assert!(!Preset::Firefox.headers().iter().any(|(name, _)| name.starts_with("sec-ch-")));
+    }
+}
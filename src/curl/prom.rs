@@ -0,0 +1,163 @@
+// src/curl/prom.rs
+
+//! `--prom-out`: the latest probe result in Prometheus text exposition
+//! format, for node_exporter's textfile collector. The file is replaced
+//! atomically so the collector never reads a half-written probe.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::atomic;
+use super::error::RequestError;
+use super::response::Response;
+
+/// Render the metrics for one probe of `url`. `elapsed` is the wall-clock
+/// duration, used as the total when the backend reports no timing.
+pub fn render(url: &str, result: &Result<Response, RequestError>, bytes: u64, elapsed: Duration) -> String {
+    let url = escape(url);
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{name}{{url=\"{url}\"{labels}}} {value}");
+        }
+    };
+
+    gauge(
+        "rustcurl_probe_up",
+        "Whether the last probe succeeded (1) or failed (0).",
+        &[(String::new(), if result.is_ok() { 1.0 } else { 0.0 })],
+    );
+    match result {
+        Ok(response) => {
+            let mut phases = vec![("total", response.timing.as_ref().map_or(elapsed, |t| t.total))];
+            if let Some(ref t) = response.timing {
+                phases.extend([
+                    ("dns", t.dns),
+                    ("connect", t.connect),
+                    ("tls", t.tls),
+                    ("first_byte", t.starttransfer),
+                ]);
+            }
+            gauge(
+                "rustcurl_probe_status_code",
+                "HTTP status code of the last probe.",
+                &[(String::new(), response.status_code as f64)],
+            );
+            gauge(
+                "rustcurl_probe_duration_seconds",
+                "Time from start until the end of each phase of the last probe.",
+                &phases
+                    .iter()
+                    .map(|(phase, d)| (format!(",phase=\"{phase}\""), d.as_secs_f64()))
+                    .collect::<Vec<_>>(),
+            );
+            gauge(
+                "rustcurl_probe_response_bytes",
+                "Body size of the last probe.",
+                &[(String::new(), bytes as f64)],
+            );
+        }
+        Err(e) => gauge(
+            "rustcurl_probe_failure",
+            "Failure category of the last probe.",
+            &[(format!(",code=\"{}\"", e.code()), 1.0)],
+        ),
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    gauge(
+        "rustcurl_probe_timestamp_seconds",
+        "Unix time the last probe finished.",
+        &[(String::new(), now.as_secs_f64().floor())],
+    );
+    out
+}
+
+/// Replace `path` with `text`.
+pub fn write(path: &str, text: &str) -> Result<(), RequestError> {
+    atomic::write(Path::new(path), text.as_bytes()).map_err(|source| RequestError::WriteOutput {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Label value escaping: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::error::TimeoutPhase;
+    use crate::curl::response::Timing;
+    use std::fs;
+
+    fn ok_response(timing: Option<Timing>) -> Result<Response, RequestError> {
+        Ok(Response {
+            status_code: 200,
+            body: b"hello".to_vec(),
+            timing,
+            attempts: vec![200],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn success_with_timing() {
+        let timing = Timing {
+            dns: Duration::from_millis(1),
+            connect: Duration::from_millis(2),
+            tls: Duration::from_millis(3),
+            starttransfer: Duration::from_millis(40),
+            total: Duration::from_millis(50),
+            redirect: Duration::ZERO,
+            tls_resumed: None,
+            early_data: None,
+            hops: Vec::new(),
+        };
+        let text = render("https://x.com/", &ok_response(Some(timing)), 5, Duration::from_secs(9));
+        assert!(text.contains("# TYPE rustcurl_probe_up gauge\nrustcurl_probe_up{url=\"https://x.com/\"} 1\n"));
+        assert!(text.contains("rustcurl_probe_status_code{url=\"https://x.com/\"} 200\n"));
+        assert!(text.contains("rustcurl_probe_duration_seconds{url=\"https://x.com/\",phase=\"total\"} 0.05\n"));
+        assert!(text.contains("rustcurl_probe_duration_seconds{url=\"https://x.com/\",phase=\"first_byte\"} 0.04\n"));
+        assert!(text.contains("rustcurl_probe_response_bytes{url=\"https://x.com/\"} 5\n"));
+        assert!(!text.contains("rustcurl_probe_failure"));
+    }
+
+    #[test]
+    fn success_without_timing_uses_elapsed() {
+        let text = render("u", &ok_response(None), 0, Duration::from_millis(250));
+        assert!(text.contains("phase=\"total\"} 0.25\n"));
+        assert!(!text.contains("phase=\"dns\""));
+    }
+
+    #[test]
+    fn failure() {
+        let err = Err(RequestError::Timeout {
+            phase: TimeoutPhase::Connect,
+            message: "slow".into(),
+        });
+        let text = render("u", &err, 0, Duration::ZERO);
+        assert!(text.contains("rustcurl_probe_up{url=\"u\"} 0\n"));
+        assert!(text.contains("rustcurl_probe_failure{url=\"u\",code=\"timeout\"} 1\n"));
+        assert!(!text.contains("rustcurl_probe_status_code"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn write_replaces_file() {
+        let path = std::env::temp_dir().join(format!("rustcurl-prom-{}.prom", std::process::id()));
+        let path = path.to_str().unwrap();
+        write(path, "first\n").unwrap();
+        write(path, "second\n").unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "second\n");
+        let _ = fs::remove_file(path);
+    }
+}
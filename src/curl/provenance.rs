@@ -0,0 +1,204 @@
+// src/curl/provenance.rs
+
+//! `--provenance`: a JSON record written next to a downloaded file
+//! (`<file>.provenance.json`) with where it came from and what arrived,
+//! for supply-chain audits.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+
+use super::config::{RequestConfig, split_header};
+use super::encoding::sha256_hex;
+use super::error::RequestError;
+use super::response::Response;
+use super::url;
+
+/// One redirect followed on the way to the final response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+    pub status: u32,
+    /// Absolute URL the redirect pointed to.
+    pub location: String,
+}
+
+/// Redirects recorded in a response's header blocks, with each
+/// `Location` resolved against the URL it came from.
+pub fn redirect_chain(start: &str, headers: &[String]) -> Vec<Hop> {
+    let mut hops = Vec::new();
+    let mut current = start.to_string();
+    let mut status = None;
+    for line in headers {
+        if let Some(code) = line
+            .strip_prefix("HTTP/")
+            .and_then(|rest| rest.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u32>().ok())
+        {
+            status = Some(code);
+        } else if let Some((name, value)) = split_header(line)
+            && name.eq_ignore_ascii_case("location")
+            && let Some(code) = status.filter(|c| (300..400).contains(c))
+        {
+            current = url::join(&current, value.trim());
+            hops.push(Hop {
+                status: code,
+                location: current.clone(),
+            });
+        }
+    }
+    hops
+}
+
+/// Where the record for `file` is written.
+pub fn record_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".provenance.json");
+    PathBuf::from(name)
+}
+
+/// Build the provenance record for a download saved at `file`.
+pub fn record(
+    config: &RequestConfig,
+    response: &Response,
+    file: &Path,
+    data: &[u8],
+    backend: &str,
+    started: SystemTime,
+    finished: SystemTime,
+) -> Value {
+    let redirects = redirect_chain(&config.url, &response.headers);
+    let final_url = redirects.last().map_or(config.url.as_str(), |h| h.location.as_str());
+    json!({
+        "tool": "rustcurl",
+        "version": env!("CARGO_PKG_VERSION"),
+        "backend": backend,
+        "url": config.url,
+        "final_url": final_url,
+        "redirects": redirects
+            .iter()
+            .map(|h| json!({ "status": h.status, "location": h.location }))
+            .collect::<Vec<_>>(),
+        "status": response.status_code,
+        "started": rfc3339(started),
+        "finished": rfc3339(finished),
+        "file": file.display().to_string(),
+        "size": data.len(),
+        "sha256": sha256_hex(data),
+        "tls_certificate_sha256": response.peer_certificates.first().map(|der| sha256_hex(der)),
+        "signature": config.verify_sig,
+    })
+}
+
+/// Write the record for the file saved by `-o`, returning its path.
+pub fn write(
+    config: &RequestConfig,
+    response: &Response,
+    file: &Path,
+    backend: &str,
+    started: SystemTime,
+) -> Result<PathBuf, RequestError> {
+    let data = fs::read(file).map_err(|source| RequestError::WriteOutput {
+        path: file.display().to_string(),
+        source,
+    })?;
+    let record = record(config, response, file, &data, backend, started, SystemTime::now());
+    let path = record_path(file);
+    let text = serde_json::to_string_pretty(&record).unwrap_or_default();
+    fs::write(&path, text + "\n").map_err(|source| RequestError::WriteOutput {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// UTC timestamp like `2024-05-01T12:00:00Z`.
+pub fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn chain_from_header_blocks() {
+        let headers = lines(&[
+            "HTTP/1.1 301 Moved Permanently",
+            "Location: /v2/pkg.tgz",
+            "HTTP/1.1 302 Found",
+            "location: https://cdn.example.net/blob/abc",
+            "HTTP/1.1 200 OK",
+            "Content-Type: application/gzip",
+        ]);
+        let hops = redirect_chain("https://example.com/pkg.tgz", &headers);
+        assert_eq!(
+            hops,
+            vec![
+                Hop { status: 301, location: "https://example.com/v2/pkg.tgz".into() },
+                Hop { status: 302, location: "https://cdn.example.net/blob/abc".into() },
+            ]
+        );
+        // Location on a non-redirect status is not a hop
+        let created = lines(&["HTTP/1.1 201 Created", "Location: /items/1"]);
+        assert!(redirect_chain("https://x.com/", &created).is_empty());
+    }
+
+    #[test]
+    fn sha256_known_vector() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn record_fields() {
+        let config = RequestConfig::new("http://x.com/a.bin");
+        let response = Response {
+            status_code: 200,
+            headers: lines(&["HTTP/1.1 200 OK"]),
+            attempts: vec![200],
+            peer_certificates: vec![b"abc".to_vec()],
+            ..Default::default()
+        };
+        let record = record(&config, &response, Path::new("a.bin"), b"abc", "curl", UNIX_EPOCH, UNIX_EPOCH);
+        assert_eq!(record["final_url"], "http://x.com/a.bin");
+        assert_eq!(record["size"], 3);
+        assert_eq!(record["sha256"], sha256_hex(b"abc"));
+        assert_eq!(record["tls_certificate_sha256"], sha256_hex(b"abc"));
+        assert_eq!(record["redirects"], json!([]));
+        assert_eq!(record["signature"], Value::Null);
+        assert_eq!(record_path(Path::new("/d/a.bin")), PathBuf::from("/d/a.bin.provenance.json"));
+    }
+}
@@ -0,0 +1,140 @@
+// src/curl/proxy.rs
+
+//! The kind of proxy named by `-x` or the `*_proxy` variables, from its
+//! scheme as curl reads it: `http://` (also the default without one),
+//! `https://`, `socks4://`, `socks4a://`, `socks5://` and `socks5h://`.
+//! The `a` and `h` variants have the proxy resolve the host name.
+//! `--preproxy` puts a SOCKS proxy in front of an HTTP(S) one.
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::request::resolve_proxy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    Http,
+    Https,
+    Socks4,
+    Socks4a,
+    Socks5,
+    Socks5h,
+}
+
+impl ProxyKind {
+    /// The kind of `proxy`, or `None` for a scheme curl does not know.
+    pub fn of(proxy: &str) -> Option<ProxyKind> {
+        let Some((scheme, _)) = proxy.split_once("://") else {
+            return Some(ProxyKind::Http);
+        };
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" => Some(ProxyKind::Http),
+            "https" => Some(ProxyKind::Https),
+            "socks4" => Some(ProxyKind::Socks4),
+            "socks4a" => Some(ProxyKind::Socks4a),
+            "socks5" => Some(ProxyKind::Socks5),
+            "socks5h" => Some(ProxyKind::Socks5h),
+            _ => None,
+        }
+    }
+
+    pub fn is_socks(self) -> bool {
+        !matches!(self, ProxyKind::Http | ProxyKind::Https)
+    }
+}
+
+/// `config`'s proxy, if any, with its kind. An unknown scheme is an
+/// error, and so is HTTP proxy authentication (`--proxy-negotiate`,
+/// `--proxy-ntlm`, `--proxy-digest`, `--proxy-anyauth`) for a SOCKS proxy;
+/// `--proxy-user` is SOCKS5's username/password or SOCKS4's user ID.
+pub fn resolve(config: &RequestConfig) -> Result<Option<(String, ProxyKind)>, RequestError> {
+    let Some(proxy) = resolve_proxy(config) else {
+        return Ok(None);
+    };
+    let kind = ProxyKind::of(&proxy).ok_or_else(|| RequestError::Config {
+        option: "-x".to_string(),
+        message: format!("unsupported proxy scheme in '{proxy}' (http, https, socks4, socks4a, socks5, socks5h)"),
+    })?;
+    let http_auth = [
+        (config.proxy_negotiate, "--proxy-negotiate"),
+        (config.proxy_ntlm, "--proxy-ntlm"),
+        (config.proxy_digest, "--proxy-digest"),
+        (config.proxy_anyauth, "--proxy-anyauth"),
+    ];
+    if kind.is_socks()
+        && let Some((_, option)) = http_auth.iter().find(|(on, _)| *on)
+    {
+        return Err(RequestError::Config {
+            option: option.to_string(),
+            message: "SOCKS proxies take --proxy-user credentials, not HTTP proxy authentication".to_string(),
+        });
+    }
+    Ok(Some((proxy, kind)))
+}
+
+/// `--preproxy` for a request going through a proxy of kind `proxy`: a
+/// SOCKS proxy, SOCKS4 when the scheme is left out as in curl, leading to
+/// an HTTP or HTTPS proxy.
+pub fn resolve_preproxy(config: &RequestConfig, proxy: Option<ProxyKind>) -> Result<Option<String>, RequestError> {
+    let Some(ref preproxy) = config.preproxy else {
+        return Ok(None);
+    };
+    let err = |message: String| RequestError::Config {
+        option: "--preproxy".to_string(),
+        message,
+    };
+    let preproxy = if preproxy.contains("://") {
+        preproxy.clone()
+    } else {
+        format!("socks4://{preproxy}")
+    };
+    if !ProxyKind::of(&preproxy).is_some_and(ProxyKind::is_socks) {
+        return Err(err(format!("'{preproxy}' is not a SOCKS proxy (socks4, socks4a, socks5, socks5h)")));
+    }
+    match proxy {
+        Some(ProxyKind::Http | ProxyKind::Https) => Ok(Some(preproxy)),
+        Some(_) => Err(err("the proxy it leads to must be http:// or https://, not SOCKS".to_string())),
+        None => Err(err("there is no proxy to lead to; set one with -x or *_proxy".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::env::EnvSource;
+
+    #[test]
+    fn kind_from_scheme() {
+        assert_eq!(ProxyKind::of("proxy:3128"), Some(ProxyKind::Http));
+        assert_eq!(ProxyKind::of("HTTPS://p"), Some(ProxyKind::Https));
+        assert_eq!(ProxyKind::of("socks4a://p:1080"), Some(ProxyKind::Socks4a));
+        assert_eq!(ProxyKind::of("socks5h://u:p@p:1080"), Some(ProxyKind::Socks5h));
+        assert_eq!(ProxyKind::of("ftp://p"), None);
+        assert!(ProxyKind::Socks5.is_socks() && !ProxyKind::Https.is_socks());
+    }
+
+    #[test]
+    fn resolves_and_rejects() {
+        let config = RequestConfig::new("https://x.com").env(EnvSource::from_pairs([("ALL_PROXY", "socks5://p:1080")]));
+        assert_eq!(resolve(&config).unwrap(), Some(("socks5://p:1080".to_string(), ProxyKind::Socks5)));
+        let err = resolve(&config.clone().proxy_ntlm(true)).unwrap_err();
+        assert!(matches!(err, RequestError::Config { ref option, .. } if option == "--proxy-ntlm"));
+        let err = resolve(&config.proxy_digest(true)).unwrap_err();
+        assert!(matches!(err, RequestError::Config { ref option, .. } if option == "--proxy-digest"));
+        let bad = RequestConfig::new("https://x.com").proxy("gopher://p");
+        assert!(matches!(resolve(&bad), Err(RequestError::Config { .. })));
+        assert_eq!(resolve(&RequestConfig::new("https://x.com").env(EnvSource::Disabled)).unwrap(), None);
+    }
+
+    #[test]
+    fn preproxy_is_socks_before_http() {
+        let config = |pre: &str| RequestConfig::new("https://x.com").preproxy(pre);
+        let http = Some(ProxyKind::Http);
+        let chained = resolve_preproxy(&config("socks5h://jump:1080"), http).unwrap();
+        assert_eq!(chained.as_deref(), Some("socks5h://jump:1080"));
+        assert_eq!(resolve_preproxy(&config("jump:1080"), http).unwrap().as_deref(), Some("socks4://jump:1080"));
+        assert!(resolve_preproxy(&config("http://jump:3128"), http).is_err());
+        assert!(resolve_preproxy(&config("socks5://jump:1080"), Some(ProxyKind::Socks5)).is_err());
+        assert!(resolve_preproxy(&config("socks5://jump:1080"), None).is_err());
+        assert_eq!(resolve_preproxy(&RequestConfig::new("https://x.com"), None).unwrap(), None);
+    }
+}
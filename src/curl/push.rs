@@ -0,0 +1,84 @@
+// src/curl/push.rs
+
+//! `--accept-push <DIR|->`: HTTP/2 server push. libcurl only lets a server
+//! push when a push callback is installed, so without this option pushes
+//! are refused in the connection's settings. With it, each pushed
+//! response is saved under DIR, or for `-` only counted, and listed
+//! after the transfer.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// `--accept-push` value that lists pushes without saving them.
+pub const LIST_ONLY: &str = "-";
+
+/// A response the server pushed alongside the requested one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pushed {
+    pub url: String,
+    pub status_code: u32,
+    pub saved_as: Option<PathBuf>,
+    pub bytes: u64,
+    /// Why the pushed transfer failed, if it did.
+    pub error: Option<String>,
+}
+
+impl fmt::Display for Pushed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status_code, self.url)?;
+        if let Some(ref path) = self.saved_as {
+            write!(f, " -> {}", path.display())?;
+        }
+        match self.error {
+            Some(ref e) => write!(f, " (failed: {e})"),
+            None => write!(f, " ({} bytes)", self.bytes),
+        }
+    }
+}
+
+/// Where the push of URL path `path` (e.g. `/static/app.css`) is
+/// saved under `dir`: its segments without the query, `.` or `..`, and
+/// `index.html` for a directory. `None` for names no file can have.
+pub fn save_path(dir: &Path, path: &str) -> Option<PathBuf> {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty() && *s != "." && *s != "..").collect();
+    if segments.iter().any(|s| s.contains(['\\', '\0'])) {
+        return None;
+    }
+    let mut target = dir.to_path_buf();
+    target.extend(&segments);
+    if segments.is_empty() || path.ends_with('/') {
+        target.push("index.html");
+    }
+    Some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_paths_stay_under_dir() {
+        let dir = Path::new("/tmp/push");
+        assert_eq!(save_path(dir, "/static/app.css?v=2"), Some(dir.join("static/app.css")));
+        assert_eq!(save_path(dir, "/../../etc/passwd"), Some(dir.join("etc/passwd")));
+        assert_eq!(save_path(dir, "/"), Some(dir.join("index.html")));
+        assert_eq!(save_path(dir, "/docs/"), Some(dir.join("docs/index.html")));
+        assert_eq!(save_path(dir, "/a\\b"), None);
+    }
+
+    #[test]
+    fn lists_pushes() {
+        let mut pushed = Pushed {
+            url: "https://x.com/app.css".into(),
+            status_code: 200,
+            saved_as: Some(PathBuf::from("out/app.css")),
+            bytes: 120,
+            error: None,
+        };
+        assert_eq!(pushed.to_string(), "200 https://x.com/app.css -> out/app.css (120 bytes)");
+        pushed.saved_as = None;
+        pushed.error = Some("stream reset".into());
+        assert_eq!(pushed.to_string(), "200 https://x.com/app.css (failed: stream reset)");
+    }
+}
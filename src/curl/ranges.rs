@@ -0,0 +1,226 @@
+// src/curl/ranges.rs
+
+//! `--range` specs and `206 Partial Content` replies carrying several
+//! ranges as `multipart/byteranges` (RFC 9110 §14.6).
+
+/// One part of a `multipart/byteranges` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteRange {
+    /// First byte offset, inclusive.
+    pub start: u64,
+    /// Last byte offset, inclusive.
+    pub end: u64,
+    /// Complete length of the representation, when the server knows it.
+    pub total: Option<u64>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl ByteRange {
+    /// The part's `Content-Range` value, e.g. `bytes 0-99/1000`.
+    pub fn content_range(&self) -> String {
+        let total = self.total.map_or("*".to_string(), |t| t.to_string());
+        format!("bytes {}-{}/{total}", self.start, self.end)
+    }
+}
+
+/// Check a `--range` value: comma-separated `N-M`, `N-` or `-N` items.
+pub fn validate_spec(spec: &str) -> Result<(), String> {
+    let err = |item: &str| format!("--range: invalid range '{item}', expected N-M, N- or -N");
+    for item in spec.split(',') {
+        let item = item.trim();
+        let (first, last) = item.split_once('-').ok_or_else(|| err(item))?;
+        let first = (!first.is_empty())
+            .then(|| first.parse::<u64>())
+            .transpose()
+            .map_err(|_| err(item))?;
+        let last = (!last.is_empty())
+            .then(|| last.parse::<u64>())
+            .transpose()
+            .map_err(|_| err(item))?;
+        match (first, last) {
+            (None, None) => return Err(err(item)),
+            (Some(a), Some(b)) if a > b => return Err(err(item)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// `boundary` parameter of a `multipart/byteranges` content type.
+pub fn boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';').map(str::trim);
+    if !params.next()?.eq_ignore_ascii_case("multipart/byteranges") {
+        return None;
+    }
+    params.find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        k.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| v.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Parse a `Content-Range` value such as `bytes 0-99/1000` or `bytes 0-99/*`.
+pub fn parse_content_range(value: &str) -> Option<(u64, u64, Option<u64>)> {
+    let rest = value.trim().strip_prefix("bytes")?.trim_start();
+    let (span, total) = rest.split_once('/')?;
+    let (start, end) = span.split_once('-')?;
+    let total = match total.trim() {
+        "*" => None,
+        t => Some(t.parse().ok()?),
+    };
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?, total))
+}
+
+/// Split a `multipart/byteranges` body into its parts. `None` when the
+/// body is not well-formed or a part lacks a usable `Content-Range`.
+pub fn parse_multipart(boundary: &str, body: &[u8]) -> Option<Vec<ByteRange>> {
+    let delimiter = format!("--{boundary}").into_bytes();
+    let mut parts = Vec::new();
+    let mut pos = find(body, &delimiter, 0)?;
+    loop {
+        pos += delimiter.len();
+        if body[pos..].starts_with(b"--") {
+            return Some(parts);
+        }
+        // Rest of the delimiter line, then the part's headers
+        pos = find(body, b"\n", pos)? + 1;
+        let mut range = None;
+        let mut content_type = None;
+        loop {
+            let eol = find(body, b"\n", pos)?;
+            let line = std::str::from_utf8(&body[pos..eol]).ok()?.trim_end_matches('\r');
+            pos = eol + 1;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-range") {
+                    range = parse_content_range(value);
+                } else if name.trim().eq_ignore_ascii_case("content-type") {
+                    content_type = Some(value.trim().to_string());
+                }
+            }
+        }
+        let next = find(body, &delimiter, pos)?;
+        let mut content = &body[pos..next];
+        content = content.strip_suffix(b"\n").unwrap_or(content);
+        content = content.strip_suffix(b"\r").unwrap_or(content);
+        let (start, end, total) = range?;
+        parts.push(ByteRange {
+            start,
+            end,
+            total,
+            content_type,
+            body: content.to_vec(),
+        });
+        pos = next;
+    }
+}
+
+/// Join the parts into one buffer starting at the lowest offset, merging
+/// overlaps. `None` when the ranges leave a gap.
+pub fn reassemble(parts: &[ByteRange]) -> Option<Vec<u8>> {
+    let mut sorted: Vec<&ByteRange> = parts.iter().collect();
+    sorted.sort_by_key(|p| p.start);
+    let mut next = sorted.first()?.start;
+    let mut out = Vec::new();
+    for part in sorted {
+        if part.start > next {
+            return None;
+        }
+        let skip = (next - part.start) as usize;
+        if let Some(fresh) = part.body.get(skip..) {
+            out.extend_from_slice(fresh);
+            next += fresh.len() as u64;
+        }
+    }
+    Some(out)
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"--THIS\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Range: bytes 0-4/20\r\n\
+        \r\n\
+        hello\r\n\
+        --THIS\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Range: bytes 10-14/20\r\n\
+        \r\n\
+        world\r\n\
+        --THIS--\r\n";
+
+    #[test]
+    fn spec_validation() {
+        assert!(validate_spec("0-99").is_ok());
+        assert!(validate_spec("0-99,200-299").is_ok());
+        assert!(validate_spec("500-").is_ok());
+        assert!(validate_spec("-500").is_ok());
+        assert!(validate_spec("-").is_err());
+        assert!(validate_spec("99-0").is_err());
+        assert!(validate_spec("abc").is_err());
+        assert!(validate_spec("0-99,").is_err());
+    }
+
+    #[test]
+    fn boundary_from_content_type() {
+        assert_eq!(boundary("multipart/byteranges; boundary=THIS").as_deref(), Some("THIS"));
+        assert_eq!(boundary("Multipart/Byteranges; boundary=\"a b\"").as_deref(), Some("a b"));
+        assert_eq!(boundary("multipart/form-data; boundary=x"), None);
+        assert_eq!(boundary("multipart/byteranges"), None);
+    }
+
+    #[test]
+    fn content_range_values() {
+        assert_eq!(parse_content_range("bytes 0-99/1000"), Some((0, 99, Some(1000))));
+        assert_eq!(parse_content_range("bytes 5-9/*"), Some((5, 9, None)));
+        assert_eq!(parse_content_range("bytes */1000"), None);
+    }
+
+    #[test]
+    fn parses_parts() {
+        let parts = parse_multipart("THIS", BODY).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].body, b"hello");
+        assert_eq!(parts[0].content_range(), "bytes 0-4/20");
+        assert_eq!(parts[1].start, 10);
+        assert_eq!(parts[1].body, b"world");
+        assert_eq!(parts[1].content_type.as_deref(), Some("text/plain"));
+    }
+
+    #[test]
+    fn malformed_body_is_none() {
+        assert_eq!(parse_multipart("THIS", b"no delimiters here"), None);
+        assert_eq!(parse_multipart("THIS", b"--THIS\r\n\r\nunterminated"), None);
+        assert_eq!(parse_multipart("THIS", b"--THIS\r\n\r\nno range\r\n--THIS--"), None);
+    }
+
+    #[test]
+    fn reassembles_contiguous_and_overlapping() {
+        let part = |start, body: &[u8]| ByteRange {
+            start,
+            end: start + body.len() as u64 - 1,
+            total: None,
+            content_type: None,
+            body: body.to_vec(),
+        };
+        assert_eq!(reassemble(&[part(5, b"world"), part(0, b"hello")]).unwrap(), b"helloworld");
+        assert_eq!(reassemble(&[part(0, b"hello"), part(3, b"lo!")]).unwrap(), b"hello!");
+        assert_eq!(reassemble(&[part(0, b"ab"), part(0, b"a")]).unwrap(), b"ab");
+        assert_eq!(reassemble(&parse_multipart("THIS", BODY).unwrap()), None);
+        assert_eq!(reassemble(&[]), None);
+    }
+}
@@ -0,0 +1,111 @@
+// src/curl/recorder.rs
+
+//! What a request put on the wire, byte for byte, for debugging and for
+//! tests of header order (`--impersonate`, request signing). Recording is
+//! per thread: `record` captures every request its closure sends, which
+//! the blocking backends do on the calling thread. The curl backend
+//! records the header block libcurl wrote; reqwest, which hides its
+//! connection, records the head it was handed, which hyper may still
+//! extend.
+
+use std::cell::RefCell;
+
+/// One request as sent: its head, up to and including the blank line,
+/// and any body bytes that followed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outbound {
+    pub head: Vec<u8>,
+    pub body: Vec<u8>,
+}
+
+impl Outbound {
+    pub fn head_text(&self) -> String {
+        String::from_utf8_lossy(&self.head).into_owned()
+    }
+
+    /// The request line, e.g. `GET /a HTTP/1.1`.
+    pub fn request_line(&self) -> String {
+        self.head_text().lines().next().unwrap_or_default().to_string()
+    }
+
+    /// Header names in the order sent, as written.
+    pub fn header_names(&self) -> Vec<String> {
+        self.head_text()
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':').map(|(name, _)| name.to_string()))
+            .collect()
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Vec<Outbound>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, returning its result and every request sent meanwhile on this
+/// thread, in order (redirects and retries each add one). Calls nest: an
+/// inner `record` takes what is sent inside it.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, Vec<Outbound>) {
+    let outer = ACTIVE.with(|active| active.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let sent = ACTIVE.with(|active| std::mem::replace(&mut *active.borrow_mut(), outer));
+    (result, sent.unwrap_or_default())
+}
+
+/// Whether a `record` is running on this thread.
+pub fn active() -> bool {
+    ACTIVE.with(|active| active.borrow().is_some())
+}
+
+/// Note outgoing head bytes. A new request starts whenever the last one
+/// has body bytes or a complete head.
+pub fn head(data: &[u8]) {
+    ACTIVE.with(|active| {
+        if let Some(sent) = active.borrow_mut().as_mut() {
+            match sent.last_mut() {
+                Some(last) if last.body.is_empty() && !last.head.ends_with(b"\r\n\r\n") => {
+                    last.head.extend_from_slice(data)
+                }
+                _ => sent.push(Outbound {
+                    head: data.to_vec(),
+                    body: Vec::new(),
+                }),
+            }
+        }
+    });
+}
+
+/// Note outgoing body bytes, which belong to the last head.
+pub fn body(data: &[u8]) {
+    ACTIVE.with(|active| {
+        if let Some(last) = active.borrow_mut().as_mut().and_then(|sent| sent.last_mut()) {
+            last.body.extend_from_slice(data);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_requests_and_nests() {
+        let ((), sent) = record(|| {
+            head(b"POST /a HTTP/1.1\r\nHost: x\r\n");
+            head(b"X-B: 1\r\n\r\n");
+            body(b"q=1");
+            let ((), inner) = record(|| head(b"GET /b HTTP/1.1\r\n\r\n"));
+            assert_eq!(inner.len(), 1);
+            head(b"GET /c HTTP/1.1\r\nAccept: */*\r\n\r\n");
+        });
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].request_line(), "POST /a HTTP/1.1");
+        assert_eq!(sent[0].header_names(), ["Host", "X-B"]);
+        assert_eq!(sent[0].body, b"q=1");
+        assert_eq!(sent[1].header_names(), ["Accept"]);
+        // Nothing is kept outside `record`
+        assert!(!active());
+        head(b"GET / HTTP/1.1\r\n\r\n");
+        assert!(record(|| ()).1.is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+// src/curl/registry.rs
+
+//! `--registry-auth`: the Docker Registry v2 token handshake. A registry
+//! answers an anonymous request with 401 and `WWW-Authenticate: Bearer
+//! realm=...,service=...,scope=...`; the token fetched from the realm
+//! (with `-u` as Basic auth, or anonymously) is sent as a bearer token
+//! on a single retry. Manifest requests get an `Accept` header listing
+//! the OCI and Docker manifest types unless one was given.
+
+use super::config::{RequestConfig, urlencode_bytes};
+use super::error::RequestError;
+use super::oauth;
+use super::response::Response;
+
+/// Manifest media types a registry should pick from, newest first.
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+                              application/vnd.oci.image.manifest.v1+json, \
+                              application/vnd.docker.distribution.manifest.list.v2+json, \
+                              application/vnd.docker.distribution.manifest.v2+json";
+
+/// The parameters of a `Bearer` challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub realm: String,
+    pub service: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// The `Bearer` challenge in the final response's `WWW-Authenticate`
+/// headers, if it names a realm.
+pub fn challenge(headers: &[String]) -> Option<Challenge> {
+    let last = headers.iter().rposition(|h| h.starts_with("HTTP/")).map_or(0, |i| i + 1);
+    headers[last..].iter().find_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("www-authenticate") {
+            return None;
+        }
+        let value = value.trim();
+        let (scheme, rest) = value.split_once(' ').unwrap_or((value, ""));
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return None;
+        }
+        let params = params(rest);
+        let get = |key: &str| params.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.clone());
+        Some(Challenge {
+            realm: get("realm")?,
+            service: get("service"),
+            scope: get("scope"),
+        })
+    })
+}
+
+/// `key=value` and `key="quoted, value"` pairs separated by commas.
+fn params(text: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ',' || c.is_whitespace()).is_some() {}
+        let key: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=' && *c != ',')).collect();
+        if key.is_empty() {
+            return params;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(c) = chars.next().filter(|c| *c != '"') {
+                    value.push(if c == '\\' { chars.next().unwrap_or(c) } else { c });
+                }
+            } else {
+                value.extend(std::iter::from_fn(|| chars.next_if(|c| *c != ',')));
+            }
+        }
+        params.push((key.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+/// The token request: a GET to the realm with `service` and each scope
+/// as query parameters, carrying `-u` if given.
+pub fn token_request(config: &RequestConfig, challenge: &Challenge) -> RequestConfig {
+    let mut query: Vec<String> = Vec::new();
+    if let Some(ref service) = challenge.service {
+        query.push(format!("service={}", urlencode_bytes(service.as_bytes())));
+    }
+    for scope in challenge.scope.iter().flat_map(|s| s.split_whitespace()) {
+        query.push(format!("scope={}", urlencode_bytes(scope.as_bytes())));
+    }
+    let mut url = challenge.realm.clone();
+    if !query.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&query.join("&"));
+    }
+    let mut fetch = oauth::endpoint_request(config, &url);
+    fetch.username = config.username.clone();
+    fetch.password = config.password.clone();
+    fetch.netrc = config.netrc;
+    fetch.netrc_file = config.netrc_file.clone();
+    fetch
+}
+
+/// The token from the realm's JSON reply: `token`, or the OAuth2-style
+/// `access_token` some registries send instead.
+pub fn token(realm: &str, response: &Response) -> Result<String, RequestError> {
+    let err = |message: String| RequestError::TokenRefresh {
+        url: realm.to_string(),
+        message,
+    };
+    if !(200..300).contains(&response.status_code) {
+        return Err(err(format!("HTTP {}", response.status_code)));
+    }
+    let json: serde_json::Value =
+        serde_json::from_slice(&response.body).map_err(|e| err(format!("reply is not JSON: {e}")))?;
+    json["token"]
+        .as_str()
+        .or(json["access_token"].as_str())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| err("reply has no token".to_string()))
+}
+
+/// Send `config` without credentials, and on a 401 with a `Bearer`
+/// challenge fetch a token and retry once with it. Any other reply is
+/// returned as is.
+pub fn perform(
+    config: &RequestConfig,
+    mut send: impl FnMut(&RequestConfig) -> Result<Response, RequestError>,
+) -> Result<Response, RequestError> {
+    let mut request = config.clone();
+    request.username = None;
+    request.password = None;
+    request.netrc = false;
+    if request.url.contains("/manifests/") && !request.has_header("accept") {
+        request.headers.push(format!("Accept: {MANIFEST_TYPES}"));
+    }
+    let response = send(&request)?;
+    if response.status_code != 401 {
+        return Ok(response);
+    }
+    let Some(challenge) = challenge(&response.headers) else {
+        return Ok(response);
+    };
+    let token = token(&challenge.realm, &send(&token_request(config, &challenge))?)?;
+    config.note(format_args!("Registry token fetched from {}; retrying", challenge.realm));
+    request.bearer = Some(token);
+    send(&request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(status: u32, headers: &[&str], body: &str) -> Response {
+        Response {
+            status_code: status,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            body: body.as_bytes().to_vec(),
+            attempts: vec![status],
+            ..Default::default()
+        }
+    }
+
+    const CHALLENGE: &str = "WWW-Authenticate: Bearer realm=\"https://auth.docker.io/token\",\
+                             service=\"registry.docker.io\",scope=\"repository:library/alpine:pull,push\"";
+
+    #[test]
+    fn parses_bearer_challenge() {
+        let headers = vec!["HTTP/1.1 401 Unauthorized".to_string(), CHALLENGE.to_string()];
+        assert_eq!(
+            challenge(&headers).unwrap(),
+            Challenge {
+                realm: "https://auth.docker.io/token".into(),
+                service: Some("registry.docker.io".into()),
+                scope: Some("repository:library/alpine:pull,push".into()),
+            }
+        );
+        assert_eq!(challenge(&["WWW-Authenticate: Basic realm=\"r\"".to_string()]), None);
+        assert_eq!(challenge(&["WWW-Authenticate: Bearer error=\"x\"".to_string()]), None);
+    }
+
+    #[test]
+    fn token_request_carries_scope_and_credentials() {
+        let config = RequestConfig::new("https://ghcr.io/v2/o/r/manifests/1").username("u").password("p");
+        let challenge = Challenge {
+            realm: "https://ghcr.io/token".into(),
+            service: Some("ghcr.io".into()),
+            scope: Some("repository:o/r:pull repository:o/base:pull".into()),
+        };
+        let fetch = token_request(&config, &challenge);
+        assert_eq!(
+            fetch.url,
+            "https://ghcr.io/token?service=ghcr.io&scope=repository%3Ao%2Fr%3Apull&scope=repository%3Ao%2Fbase%3Apull"
+        );
+        assert_eq!(fetch.username.as_deref(), Some("u"));
+        assert!(!fetch.registry_auth);
+        assert_eq!(token("r", &reply(200, &[], r#"{"access_token":"a"}"#)).unwrap(), "a");
+        assert_eq!(token("r", &reply(401, &[], "")).unwrap_err().code(), "token_refresh");
+    }
+
+    #[test]
+    fn retries_with_fetched_token() {
+        let config = RequestConfig::new("https://registry-1.docker.io/v2/library/alpine/manifests/latest")
+            .registry_auth(true)
+            .username("u")
+            .password("p");
+        let mut sent = Vec::new();
+        let response = perform(&config, |c| {
+            sent.push((c.url.clone(), c.username.clone(), c.bearer.clone()));
+            Ok(match sent.len() {
+                1 => reply(401, &[CHALLENGE], ""),
+                2 => reply(200, &[], r#"{"token":"t1","expires_in":300}"#),
+                _ => {
+                    assert!(c.has_header("accept"));
+                    reply(200, &[], "{}")
+                }
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(sent[0].1, None);
+        assert!(sent[1].0.starts_with("https://auth.docker.io/token?service=registry.docker.io&scope="));
+        assert_eq!(sent[1].1.as_deref(), Some("u"));
+        assert_eq!((sent[2].1.as_deref(), sent[2].2.as_deref()), (None, Some("t1")));
+    }
+}
@@ -0,0 +1,360 @@
+// src/curl/relay.rs
+
+//! `rustcurl relay --listen 127.0.0.1:9999`: a local forward proxy that
+//! sends its clients' requests on with rustcurl's proxy, Negotiate and TLS
+//! options, so tools that cannot answer a SPNEGO proxy challenge (pip,
+//! git) can point `HTTPS_PROXY` at it. Plain requests are forwarded one
+//! per connection; `CONNECT` gets a tunnel through the upstream proxy.
+//! Only loopback clients are served unless `--listen-remote` is given.
+
+use std::io::{self, BufRead, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Condvar, Mutex};
+
+use super::config::{Method, RequestConfig, split_header};
+
+/// Request heads larger than this are refused.
+pub const MAX_HEAD: u64 = 64 * 1024;
+
+/// Request bodies larger than this are refused with `413`, since a body
+/// is held in memory until it is forwarded.
+pub const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// Connections served at once; more clients wait in the listen queue.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// Headers that describe one connection rather than the message, so they
+/// are not passed along in either direction.
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "expect",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// A client's request line and headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Head {
+    pub method: String,
+    /// An absolute URL, or `host:port` for `CONNECT`.
+    pub target: String,
+    pub headers: Vec<String>,
+}
+
+/// A connection to a `CONNECT` target, through the upstream proxy when
+/// there is one.
+pub struct Tunnel {
+    pub stream: TcpStream,
+    /// Whatever holds the connection on the backend's side.
+    _owner: Box<dyn Send>,
+}
+
+impl Tunnel {
+    pub fn new(stream: TcpStream, owner: impl Send + 'static) -> Self {
+        Self {
+            stream,
+            _owner: Box::new(owner),
+        }
+    }
+}
+
+/// Refuse to serve on `listener` unless it is bound to loopback or
+/// `remote` allows other addresses: a relay carries the user's proxy
+/// credentials to whoever can reach it.
+pub fn check_listener(listener: &TcpListener, remote: bool) -> io::Result<()> {
+    let local = listener.local_addr()?;
+    if remote || local.ip().to_canonical().is_loopback() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("{local} is not a loopback address; add --listen-remote to serve other hosts"),
+    ))
+}
+
+/// Connections being served, so that accepting waits while `limit` are
+/// busy.
+pub struct Slots {
+    busy: Mutex<usize>,
+    freed: Condvar,
+    limit: usize,
+}
+
+/// One connection's place in `Slots`, given back when dropped.
+pub struct Slot<'a>(&'a Slots);
+
+impl Slots {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            busy: Mutex::new(0),
+            freed: Condvar::new(),
+            limit,
+        }
+    }
+
+    /// Wait until fewer than `limit` connections are busy and take a place.
+    pub fn acquire(&self) -> Slot<'_> {
+        let mut busy = self.busy.lock().unwrap_or_else(|e| e.into_inner());
+        while *busy >= self.limit {
+            busy = self.freed.wait(busy).unwrap_or_else(|e| e.into_inner());
+        }
+        *busy += 1;
+        Slot(self)
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        *self.0.busy.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+impl Head {
+    /// Read a request head; `None` when the client closed the connection
+    /// without sending one.
+    pub fn read(reader: &mut impl BufRead) -> Result<Option<Head>, String> {
+        let mut limited = reader.take(MAX_HEAD);
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            match limited.read_line(&mut line) {
+                Ok(0) if lines.is_empty() && line.is_empty() => return Ok(None),
+                Ok(0) => return Err("request head is incomplete or too large".to_string()),
+                Ok(_) => {}
+                Err(e) => return Err(format!("bad request head: {e}")),
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line.to_string());
+        }
+        let request_line = lines.remove(0);
+        let mut parts = request_line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version), None) if version.starts_with("HTTP/1.") => Ok(Some(Head {
+                method: method.to_string(),
+                target: target.to_string(),
+                headers: lines,
+            })),
+            _ => Err(format!("bad request line '{request_line}'")),
+        }
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .filter_map(|h| split_header(h))
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+
+    /// Length of the request body. Chunked bodies are refused, since the
+    /// body is read whole before it is forwarded.
+    pub fn content_length(&self) -> Result<usize, String> {
+        if self.get_header("Transfer-Encoding").is_some() {
+            return Err("chunked request bodies are not supported; send Content-Length".to_string());
+        }
+        match self.get_header("Content-Length") {
+            None => Ok(0),
+            Some(n) => n.parse().map_err(|_| format!("bad Content-Length '{n}'")),
+        }
+    }
+
+    /// `base` (the relay's options) aimed at this request. Redirects are
+    /// passed back to the client rather than followed.
+    pub fn forward_config(&self, base: &RequestConfig, body: Vec<u8>) -> Result<RequestConfig, String> {
+        let lower = self.target.to_ascii_lowercase();
+        if !lower.starts_with("http://") && !lower.starts_with("https://") {
+            return Err(format!("'{}' is not an absolute http(s) URL", self.target));
+        }
+        let mut config = base.clone().follow_location(false);
+        config.url = self.target.clone();
+        config.method = Method::parse(&self.method);
+        let dropped = dropped_headers(&self.headers);
+        // The backend sets Host and Content-Length itself
+        let forwarded = self.headers.iter().filter(|h| {
+            split_header(h).is_some_and(|(name, _)| {
+                let name = name.to_ascii_lowercase();
+                name != "host" && name != "content-length" && !dropped.contains(&name)
+            })
+        });
+        config.headers.extend(forwarded.cloned());
+        config.data = (!body.is_empty()).then_some(body);
+        Ok(config)
+    }
+}
+
+/// Hop-by-hop headers, plus any that `Connection` names.
+fn dropped_headers(headers: &[String]) -> Vec<String> {
+    let mut dropped: Vec<String> = HOP_BY_HOP.iter().map(|h| h.to_string()).collect();
+    for (name, value) in headers.iter().filter_map(|h| split_header(h)) {
+        if name.eq_ignore_ascii_case("connection") {
+            dropped.extend(value.split(',').map(|v| v.trim().to_ascii_lowercase()));
+        }
+    }
+    dropped
+}
+
+/// Status line and headers to send the client for a response whose
+/// header lines (1xx and proxy replies included) are `headers`. Only the
+/// final block is kept, and the connection is closed after the body.
+pub fn response_head(status_code: u32, headers: &[String]) -> String {
+    let start = headers.iter().rposition(|h| h.starts_with("HTTP/")).map_or(0, |i| i + 1);
+    let reason = start
+        .checked_sub(1)
+        .and_then(|i| headers.get(i))
+        .and_then(|line| line.splitn(3, ' ').nth(2))
+        .unwrap_or("");
+    let final_block = &headers[start..];
+    let dropped = dropped_headers(final_block);
+    let mut head = format!("HTTP/1.1 {status_code} {reason}\r\n");
+    for line in final_block {
+        if split_header(line).is_some_and(|(name, _)| !dropped.contains(&name.to_ascii_lowercase())) {
+            head.push_str(line);
+            head.push_str("\r\n");
+        }
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    head
+}
+
+/// A complete reply from the relay itself, e.g. `502` when the upstream
+/// request failed.
+pub fn error_reply(status: &str, message: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}\n",
+        message.len() + 1
+    )
+}
+
+/// Copy bytes both ways between the client and the tunnel until both
+/// sides are done. `client_in` reads the client's side, including anything
+/// it sent after the `CONNECT` head.
+pub fn splice(client_in: &mut impl Read, client_out: TcpStream, tunnel: &Tunnel) -> io::Result<()> {
+    let mut upstream = tunnel.stream.try_clone()?;
+    let mut downstream_in = tunnel.stream.try_clone()?;
+    let mut client_out = client_out;
+    let down = std::thread::spawn(move || {
+        let _ = io::copy(&mut downstream_in, &mut client_out);
+        let _ = client_out.shutdown(Shutdown::Write);
+    });
+    let _ = io::copy(client_in, &mut upstream);
+    let _ = upstream.shutdown(Shutdown::Write);
+    let _ = down.join();
+    Ok(())
+}
+
+/// Write `head` and then `body` to the client.
+pub fn reply(mut out: impl Write, head: &str, body: &mut impl Read) -> io::Result<()> {
+    out.write_all(head.as_bytes())?;
+    io::copy(body, &mut out)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(raw: &str) -> Result<Option<Head>, String> {
+        Head::read(&mut io::Cursor::new(raw.as_bytes()))
+    }
+
+    #[test]
+    fn reads_request_heads() {
+        let h = head("GET http://x.com/a HTTP/1.1\r\nHost: x.com\r\nProxy-Connection: keep-alive\r\n\r\nrest")
+            .unwrap()
+            .unwrap();
+        assert_eq!(h.method, "GET");
+        assert_eq!(h.target, "http://x.com/a");
+        assert_eq!(h.headers, vec!["Host: x.com", "Proxy-Connection: keep-alive"]);
+        assert_eq!(head("CONNECT pypi.org:443 HTTP/1.1\n\n").unwrap().unwrap().target, "pypi.org:443");
+
+        assert_eq!(head("").unwrap(), None);
+        for bad in ["GET http://x.com/ HTTP/1.1\r\nHost: x", "GET /\r\n\r\n", "GET / SPDY/3\r\n\r\n"] {
+            assert!(head(bad).is_err(), "{bad}");
+        }
+        let huge = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_HEAD as usize));
+        assert!(head(&huge).is_err());
+    }
+
+    #[test]
+    fn forwards_end_to_end_headers() {
+        let h = head(concat!(
+            "POST http://x.com/up HTTP/1.1\r\n",
+            "Host: x.com\r\n",
+            "Content-Type: application/json\r\n",
+            "Content-Length: 2\r\n",
+            "Proxy-Authorization: Basic eDp5\r\n",
+            "Connection: close, X-Trace\r\n",
+            "X-Trace: 1\r\n\r\n",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(h.content_length(), Ok(2));
+        let base = RequestConfig::new("").header("X-Relay: 1");
+        let config = h.forward_config(&base, b"{}".to_vec()).unwrap();
+        assert_eq!(config.url, "http://x.com/up");
+        assert_eq!(config.method, Method::Post);
+        assert_eq!(config.headers, vec!["X-Relay: 1", "Content-Type: application/json"]);
+        assert_eq!(config.data.as_deref(), Some(&b"{}"[..]));
+        assert!(!config.follow_location);
+
+        let origin_form = head("GET /a HTTP/1.1\r\n\r\n").unwrap().unwrap();
+        assert!(origin_form.forward_config(&base, Vec::new()).is_err());
+        let chunked = head("POST http://x.com/ HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap().unwrap();
+        assert!(chunked.content_length().is_err());
+    }
+
+    #[test]
+    fn response_head_keeps_final_block() {
+        let headers: Vec<String> = [
+            "HTTP/1.1 200 Connection established",
+            "HTTP/1.1 100 Continue",
+            "HTTP/2 404",
+            "content-type: text/plain",
+            "transfer-encoding: chunked",
+            "content-length: 9",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(
+            response_head(404, &headers),
+            "HTTP/1.1 404 \r\ncontent-type: text/plain\r\ncontent-length: 9\r\nConnection: close\r\n\r\n"
+        );
+        let ok = ["HTTP/1.1 302 Found".to_string(), "Location: /b".to_string()];
+        assert!(response_head(302, &ok).starts_with("HTTP/1.1 302 Found\r\nLocation: /b\r\n"));
+    }
+
+    #[test]
+    fn only_loopback_unless_remote() {
+        let local = TcpListener::bind("127.0.0.1:0").unwrap();
+        assert!(check_listener(&local, false).is_ok());
+        let any = TcpListener::bind("0.0.0.0:0").unwrap();
+        let err = check_listener(&any, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(err.to_string().contains("--listen-remote"), "{err}");
+        assert!(check_listener(&any, true).is_ok());
+    }
+
+    #[test]
+    fn slots_wait_for_a_free_place() {
+        let slots = Slots::new(2);
+        let first = slots.acquire();
+        let _second = slots.acquire();
+        std::thread::scope(|scope| {
+            let third = scope.spawn(|| drop(slots.acquire()));
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert!(!third.is_finished());
+            drop(first);
+            third.join().unwrap();
+        });
+    }
+}
@@ -1,67 +1,111 @@
 // src/curl/request.rs
 
 #[cfg(feature = "curl")]
-use curl::easy::{Auth, Easy, List, SslOpt};
-use std::env;
+use curl::easy::{Auth, Easy, HttpVersion, InfoType, List, ProxyType, ReadError, SslOpt, SslVersion};
+#[cfg(feature = "curl")]
+use curl::multi::{EasyHandle, Multi};
+#[cfg(feature = "curl")]
+use std::borrow::BorrowMut;
+#[cfg(feature = "curl")]
+use std::cell::{Cell, RefCell};
 #[cfg(feature = "curl")]
 use std::fs;
 #[cfg(feature = "curl")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "curl")]
+use std::collections::HashSet;
+#[cfg(feature = "curl")]
+use std::ffi::{CStr, c_char, c_int, c_long, c_void};
+#[cfg(feature = "curl")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "curl")]
+use std::sync::{Arc, Mutex, mpsc};
+#[cfg(feature = "curl")]
 use std::time::Duration;
 
 #[cfg(feature = "curl")]
-use super::config::Method;
-use super::config::RequestConfig;
+use super::atomic;
+#[cfg(feature = "curl")]
+use super::body::RequestBody;
+#[cfg(feature = "curl")]
+use super::config::{Http2, Method, TlsVersion};
+#[cfg(feature = "curl")]
+use super::dns::Pinned;
+#[cfg(feature = "curl")]
+use super::keylog;
+#[cfg(feature = "curl")]
+use super::proxy::{self, ProxyKind};
 #[cfg(feature = "curl")]
+use super::push::{self, Pushed};
+#[cfg(feature = "curl")]
+use super::relay::Tunnel;
+#[cfg(feature = "curl")]
+use super::recorder;
+#[cfg(feature = "curl")]
+use super::sink::{BodySink, saved_body};
+#[cfg(feature = "curl")]
+use super::warning::{Warning, WarningKind};
+#[cfg(feature = "curl")]
+use super::verbose::VerboseSink;
+#[cfg(feature = "curl")]
+use super::cookie::{CookieSource, carry_over, cookie_header, engine_split};
+use super::config::RequestConfig;
 use super::error::RequestError;
+use super::netrc;
 #[cfg(feature = "curl")]
-use super::response::{Response, Timing};
+use super::response::{
+    HopTiming, ParallelOutcome, Response, StreamingResponse, Timing, TransferInfo, TransferSummary, hop_timings,
+    informational_responses,
+};
 
-#[cfg(feature = "curl")]
 pub fn resolve_username(config: &RequestConfig) -> Option<String> {
     config
         .username
         .clone()
-        .or_else(|| env::var("RUSTCURL_USER").ok())
+        .or_else(|| netrc::for_config(config, None)?.login)
+        .or_else(|| config.env_var("RUSTCURL_USER"))
 }
 
-#[cfg(feature = "curl")]
+/// With `-u user` but no password, the netrc entry for that user fills it in.
 pub fn resolve_password(config: &RequestConfig) -> Option<String> {
     config
         .password
         .clone()
-        .or_else(|| env::var("RUSTCURL_PASSWORD").ok())
+        .or_else(|| netrc::for_config(config, config.username.as_deref())?.password)
+        .or_else(|| config.env_var("RUSTCURL_PASSWORD"))
 }
 
 pub fn resolve_proxy(config: &RequestConfig) -> Option<String> {
     config
         .proxy
         .clone()
-        .or_else(|| env::var("HTTPS_PROXY").ok())
-        .or_else(|| env::var("HTTP_PROXY").ok())
-        .or_else(|| env::var("ALL_PROXY").ok())
-        .or_else(|| env::var("https_proxy").ok())
-        .or_else(|| env::var("http_proxy").ok())
-        .or_else(|| env::var("all_proxy").ok())
+        .or_else(|| config.env_var("HTTPS_PROXY"))
+        .or_else(|| config.env_var("HTTP_PROXY"))
+        .or_else(|| config.env_var("ALL_PROXY"))
+        .or_else(|| config.env_var("https_proxy"))
+        .or_else(|| config.env_var("http_proxy"))
+        .or_else(|| config.env_var("all_proxy"))
 }
 
 pub fn resolve_noproxy(config: &RequestConfig) -> Option<String> {
     config
         .noproxy
         .clone()
-        .or_else(|| env::var("NO_PROXY").ok())
-        .or_else(|| env::var("no_proxy").ok())
+        .or_else(|| config.env_var("NO_PROXY"))
+        .or_else(|| config.env_var("no_proxy"))
 }
 
 #[cfg(feature = "curl")]
 fn apply_method(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
     match &config.method {
+        // A body would otherwise turn the request into a POST
+        Method::Get if config.body().is_some() => {
+            easy.custom_request("GET")?;
+        }
         Method::Get => {}
         Method::Post => {
             easy.post(true)?;
         }
-        Method::Put => {
-            easy.put(true)?;
-        }
         Method::Head => {
             easy.nobody(true)?;
         }
@@ -77,7 +121,15 @@ fn apply_method(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestEr
 
 #[cfg(feature = "curl")]
 fn apply_auth(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
-    if config.negotiate {
+    if config.anyauth {
+        // With more than one method allowed, libcurl first sends without
+        // credentials and picks the strongest one the 401 offers
+        let mut auth = Auth::new();
+        auth.gssnegotiate(true).ntlm(true).digest(true).basic(true);
+        easy.http_auth(&auth)?;
+        easy.username(&resolve_username(config).unwrap_or_default())?;
+        easy.password(&resolve_password(config).unwrap_or_default())?;
+    } else if config.negotiate {
         let mut auth = Auth::new();
         auth.gssnegotiate(true);
         easy.http_auth(&auth)?;
@@ -94,6 +146,193 @@ fn apply_auth(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestErro
         if let Some(ref pass) = resolve_password(config) {
             easy.password(pass)?;
         }
+        if config.auth_on_challenge {
+            basic_on_challenge(easy)?;
+        }
+    }
+    Ok(())
+}
+
+/// Hold Basic credentials back until a 401 asks for them. libcurl sends a
+/// lone Basic preemptively; adding `CURLAUTH_ONLY`, which the curl crate
+/// does not expose, makes it wait for the challenge.
+#[cfg(feature = "curl")]
+fn basic_on_challenge(easy: &mut Easy) -> Result<(), RequestError> {
+    const CURLAUTH_ONLY: std::os::raw::c_ulong = 1 << 31;
+    // SAFETY: CURLOPT_HTTPAUTH takes a long bitmask on a live handle
+    let code = unsafe {
+        curl_sys::curl_easy_setopt(easy.raw(), curl_sys::CURLOPT_HTTPAUTH, curl_sys::CURLAUTH_BASIC | CURLAUTH_ONLY)
+    };
+    if code != curl_sys::CURLE_OK {
+        return Err(curl::Error::new(code).into());
+    }
+    Ok(())
+}
+
+/// Ask for HTTP/2 and give the stream `weight`. The curl crate and
+/// curl-sys have neither `CURLOPT_STREAM_WEIGHT` nor `curl_global_trace`;
+/// the latter makes `-v` also show the connection's HTTP/2 frames
+/// (SETTINGS, WINDOW_UPDATE, ...). It is process-wide, so it is turned on
+/// once and stays on.
+#[cfg(feature = "curl")]
+fn h2_priority(easy: &mut Easy, weight: u32, verbose: bool) -> Result<(), RequestError> {
+    const CURLOPT_STREAM_WEIGHT: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_LONG + 239;
+    unsafe extern "C" {
+        fn curl_global_trace(config: *const c_char) -> curl_sys::CURLcode;
+    }
+    static FRAME_TRACE: std::sync::Once = std::sync::Once::new();
+
+    if !curl::Version::get().feature_http2() {
+        return Err(RequestError::Config {
+            option: "--h2-priority".to_string(),
+            message: "libcurl was built without HTTP/2".to_string(),
+        });
+    }
+    easy.http_version(HttpVersion::V2TLS)?;
+    // SAFETY: CURLOPT_STREAM_WEIGHT takes a long on a live handle
+    let code = unsafe { curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_STREAM_WEIGHT, weight as c_long) };
+    if code != curl_sys::CURLE_OK {
+        return Err(curl::Error::new(code).into());
+    }
+    if verbose {
+        // SAFETY: a NUL-terminated trace config; an unknown name is ignored
+        FRAME_TRACE.call_once(|| unsafe {
+            curl_global_trace(c"http/2".as_ptr());
+        });
+    }
+    Ok(())
+}
+
+/// Set `--tlsv1.x` and `--tls-max`. libcurl refuses versions its TLS
+/// library cannot limit to.
+#[cfg(feature = "curl")]
+fn tls_versions(easy: &mut Easy, min: Option<TlsVersion>, max: Option<TlsVersion>) -> Result<(), RequestError> {
+    let ssl = |version| match version {
+        None => SslVersion::Default,
+        Some(TlsVersion::V1_0) => SslVersion::Tlsv10,
+        Some(TlsVersion::V1_1) => SslVersion::Tlsv11,
+        Some(TlsVersion::V1_2) => SslVersion::Tlsv12,
+        Some(TlsVersion::V1_3) => SslVersion::Tlsv13,
+    };
+    easy.ssl_min_max_version(ssl(min), ssl(max)).map_err(|e| RequestError::Config {
+        option: match min {
+            Some(min) => format!("--tlsv{}", min.as_str()),
+            None => "--tls-max".to_string(),
+        },
+        message: format!("libcurl's TLS library cannot use this version: {}", e.description()),
+    })
+}
+
+/// Set `--tls13-ciphers`; the curl crate has no `CURLOPT_TLS13_CIPHERS`.
+/// libcurl refuses it when its TLS library cannot pick TLS 1.3 suites.
+#[cfg(feature = "curl")]
+fn tls13_ciphers(easy: &mut Easy, list: &str) -> Result<(), RequestError> {
+    const CURLOPT_TLS13_CIPHERS: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_OBJECTPOINT + 276;
+    const CURLE_NOT_BUILT_IN: curl_sys::CURLcode = 4;
+    let err = |message: &str| RequestError::Config {
+        option: "--tls13-ciphers".to_string(),
+        message: message.to_string(),
+    };
+    let list = std::ffi::CString::new(list).map_err(|_| err("list contains a NUL byte"))?;
+    // SAFETY: libcurl copies the string option
+    let code = unsafe { curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_TLS13_CIPHERS, list.as_ptr()) };
+    match code {
+        curl_sys::CURLE_OK => Ok(()),
+        curl_sys::CURLE_UNKNOWN_OPTION | CURLE_NOT_BUILT_IN => {
+            Err(err("libcurl's TLS library cannot select TLS 1.3 cipher suites"))
+        }
+        code => Err(curl::Error::new(code).into()),
+    }
+}
+
+/// Log TLS secrets to `--keylog`. libcurl only reads `SSLKEYLOGFILE`, at
+/// startup, so OpenSSL's key log callback is set on each connection's
+/// `SSL_CTX` instead; it replaces libcurl's own when both are set.
+#[cfg(feature = "curl")]
+fn keylog(easy: &mut Easy, path: &str, config: &RequestConfig) -> Result<(), RequestError> {
+    let err = |message: String| RequestError::Config {
+        option: "--keylog".to_string(),
+        message,
+    };
+    let tls = curl::Version::get().ssl_version().unwrap_or("no TLS").to_string();
+    if cfg!(windows) || !tls.starts_with("OpenSSL") {
+        return Err(err(format!("needs libcurl built with OpenSSL, not {tls}; set SSLKEYLOGFILE instead")));
+    }
+    keylog::open(path).map_err(|e| err(format!("{path}: {e}")))?;
+    #[cfg(not(windows))]
+    easy.ssl_ctx_function(|ctx| {
+        // SAFETY: with OpenSSL, libcurl passes the connection's SSL_CTX
+        unsafe { SSL_CTX_set_keylog_callback(ctx, Some(on_keylog)) };
+        Ok(())
+    })?;
+    config.note(format_args!("TLS secrets logged to {path}"));
+    Ok(())
+}
+
+#[cfg(all(feature = "curl", not(windows)))]
+unsafe extern "C" {
+    fn SSL_CTX_set_keylog_callback(ctx: *mut c_void, callback: Option<extern "C" fn(*const c_void, *const c_char)>);
+}
+
+#[cfg(all(feature = "curl", not(windows)))]
+extern "C" fn on_keylog(_ssl: *const c_void, line: *const c_char) {
+    // SAFETY: OpenSSL passes a NUL-terminated line without its newline
+    keylog::write(unsafe { CStr::from_ptr(line) }.to_bytes());
+}
+
+/// Set `--preproxy`; the curl crate has no `CURLOPT_PRE_PROXY`. libcurl
+/// takes the SOCKS version from the scheme.
+#[cfg(feature = "curl")]
+fn pre_proxy(easy: &mut Easy, proxy_url: &str) -> Result<(), RequestError> {
+    const CURLOPT_PRE_PROXY: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_OBJECTPOINT + 262;
+    let proxy_url = std::ffi::CString::new(proxy_url).map_err(|_| RequestError::Config {
+        option: "--preproxy".to_string(),
+        message: "URL contains a NUL byte".to_string(),
+    })?;
+    // SAFETY: libcurl copies the string option
+    let code = unsafe { curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_PRE_PROXY, proxy_url.as_ptr()) };
+    match code {
+        curl_sys::CURLE_OK => Ok(()),
+        code => Err(curl::Error::new(code).into()),
+    }
+}
+
+/// Set `--ech`; the curl crate has no `CURLOPT_ECH`. libcurl refuses it
+/// unless built with an ECH-capable TLS library.
+#[cfg(feature = "curl")]
+fn ech(easy: &mut Easy, mode: &str) -> Result<(), RequestError> {
+    const CURLOPT_ECH: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_OBJECTPOINT + 325;
+    const CURLE_NOT_BUILT_IN: curl_sys::CURLcode = 4;
+    let mode = std::ffi::CString::new(mode).map_err(|_| RequestError::Config {
+        option: "--ech".to_string(),
+        message: "mode contains a NUL byte".to_string(),
+    })?;
+    // SAFETY: libcurl copies the string option
+    let code = unsafe { curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_ECH, mode.as_ptr()) };
+    match code {
+        curl_sys::CURLE_OK => Ok(()),
+        CURLE_NOT_BUILT_IN => Err(RequestError::Config {
+            option: "--ech".to_string(),
+            message: "libcurl was built without ECH support".to_string(),
+        }),
+        code => Err(curl::Error::new(code).into()),
+    }
+}
+
+/// Allow TLS 1.3 early data when a resumed session permits it. The curl
+/// crate's `SslOpt` has no `CURLSSLOPT_EARLYDATA`, so the options are set
+/// whole, keeping `--ssl-no-revoke`.
+#[cfg(feature = "curl")]
+fn early_data(easy: &mut Easy, no_revoke: bool) -> Result<(), RequestError> {
+    const CURLSSLOPT_EARLYDATA: c_long = 1 << 6;
+    let mut bits = CURLSSLOPT_EARLYDATA;
+    if no_revoke {
+        bits |= curl_sys::CURLSSLOPT_NO_REVOKE;
+    }
+    // SAFETY: CURLOPT_SSL_OPTIONS takes a long bitmask on a live handle
+    let code = unsafe { curl_sys::curl_easy_setopt(easy.raw(), curl_sys::CURLOPT_SSL_OPTIONS, bits) };
+    if code != curl_sys::CURLE_OK {
+        return Err(curl::Error::new(code).into());
     }
     Ok(())
 }
@@ -104,9 +343,15 @@ fn build_headers(config: &RequestConfig) -> Result<List, RequestError> {
     for h in &config.headers {
         list.append(h)?;
     }
+    for (name, value) in config.impersonated_headers() {
+        list.append(&format!("{name}: {value}"))?;
+    }
     if let Some(ref token) = config.bearer {
         list.append(&format!("Authorization: Bearer {token}"))?;
     }
+    if let Some(content_type) = config.implied_content_type() {
+        list.append(&format!("Content-Type: {content_type}"))?;
+    }
     Ok(list)
 }
 
@@ -119,8 +364,47 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
     if let Some(ref path) = config.cacert {
         easy.cainfo(path)?;
     }
-    if let Some(ref proxy_url) = resolve_proxy(config) {
+    if let Some(ref path) = config.cert {
+        easy.ssl_cert(path)?;
+        easy.ssl_cert_type(config.cert_format().as_str())?;
+    }
+    if let Some(ref path) = config.key {
+        easy.ssl_key(path)?;
+        easy.ssl_key_type(config.key_format().as_str())?;
+    }
+    if let Some(ref pass) = config.key_password {
+        easy.key_password(pass)?;
+    }
+    let resolved = proxy::resolve(config)?;
+    if let Some(ref preproxy) = proxy::resolve_preproxy(config, resolved.as_ref().map(|(_, kind)| *kind))? {
+        pre_proxy(easy, preproxy)?;
+    }
+    if let Some((ref proxy_url, kind)) = resolved {
         easy.proxy(proxy_url)?;
+        // An https:// proxy has no curl crate type; libcurl takes it from the scheme
+        let proxy_type = match kind {
+            ProxyKind::Http => Some(ProxyType::Http),
+            ProxyKind::Https => None,
+            ProxyKind::Socks4 => Some(ProxyType::Socks4),
+            ProxyKind::Socks4a => Some(ProxyType::Socks4a),
+            ProxyKind::Socks5 => Some(ProxyType::Socks5),
+            ProxyKind::Socks5h => Some(ProxyType::Socks5Hostname),
+        };
+        if let Some(proxy_type) = proxy_type {
+            easy.proxy_type(proxy_type)?;
+        }
+    } else if config.env_isolated() {
+        // An empty proxy stops libcurl reading *_proxy from the process
+        easy.proxy("")?;
+    }
+    // libcurl's default CURLHEADER_SEPARATE keeps -H off the CONNECT
+    // request and these off the tunneled one
+    if !config.proxy_headers.is_empty() {
+        let mut list = List::new();
+        for h in &config.proxy_headers {
+            list.append(h)?;
+        }
+        easy.proxy_headers(list)?;
     }
     if let Some(ref noproxy_hosts) = resolve_noproxy(config) {
         easy.noproxy(noproxy_hosts)?;
@@ -138,6 +422,16 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
         let mut auth = Auth::new();
         auth.ntlm(true);
         easy.proxy_auth(&auth)?;
+    } else if config.proxy_anyauth {
+        // As with --anyauth, the first request goes without credentials
+        // and the 407's challenge picks the method
+        let mut auth = Auth::new();
+        auth.gssnegotiate(true).ntlm(true).digest(true).basic(true);
+        easy.proxy_auth(&auth)?;
+    } else if config.proxy_digest || config.proxy_basic {
+        let mut auth = Auth::new();
+        auth.digest(config.proxy_digest).basic(config.proxy_basic);
+        easy.proxy_auth(&auth)?;
     }
     if let Some(ref pu) = config.proxy_user {
         easy.proxy_username(pu)?;
@@ -145,6 +439,9 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
     if let Some(ref pp) = config.proxy_password {
         easy.proxy_password(pp)?;
     }
+    if config.proxy_tunnel {
+        easy.http_proxy_tunnel(true)?;
+    }
     if config.proxy_insecure {
         easy.proxy_ssl_verify_peer(false)?;
         easy.proxy_ssl_verify_host(false)?;
@@ -153,13 +450,35 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
         easy.proxy_cainfo(path)?;
     }
     if let Some(d) = config.connect_timeout {
-        easy.connect_timeout(d)?;
+        easy.connect_timeout(whole_millis(d))?;
     }
     if let Some(d) = config.max_time {
-        easy.timeout(d)?;
+        easy.timeout(whole_millis(d))?;
+    }
+    if let Some(d) = config.read_timeout {
+        // Idle timeout: abort when under 1 byte/s for the whole window.
+        // libcurl only tracks this with one-second granularity.
+        easy.low_speed_limit(1)?;
+        easy.low_speed_time(d.max(Duration::from_secs(1)))?;
+    }
+    // Cookie file lines go into libcurl's cookie engine, so each is sent
+    // only where its domain and path match, on every redirect hop too;
+    // inline -b pairs go out as CURLOPT_COOKIE. `engine_split` keeps the
+    // later-wins order between the two. The engine is emptied first:
+    // cookies a pooled handle kept from earlier requests would otherwise
+    // be sent twice.
+    if !config.cookies.is_empty() {
+        easy.cookie_list("ALL")?;
+    }
+    if config.cookies.iter().any(|source| matches!(source, CookieSource::File(_))) {
+        easy.cookie_file("")?;
+    }
+    let split = engine_split(&config.cookies, &config.url)?;
+    for line in &split.lines {
+        easy.cookie_list(line)?;
     }
-    if let Some(ref path) = config.cookie {
-        easy.cookie_file(path)?;
+    if !split.inline.is_empty() {
+        easy.cookie(&cookie_header(&split.inline))?;
     }
     if let Some(ref path) = config.cookie_jar {
         easy.cookie_jar(path)?;
@@ -167,10 +486,40 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
     if config.compressed {
         easy.accept_encoding("")?;
     }
-    let user_agent = config.user_agent.as_deref().unwrap_or(
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36 Edg/131.0.0.0"
-    );
-    easy.useragent(user_agent)?;
+    if config.ignore_content_length {
+        easy.ignore_content_length(true)?;
+    }
+    if let Some(ref spec) = config.range {
+        easy.range(spec)?;
+    }
+    if config.provenance || config.show_certs || config.tofu {
+        easy.certinfo(true)?;
+    }
+    if let Some(ref pin) = config.pinned_pubkey {
+        easy.pinned_public_key(pin)?;
+    }
+    easy.useragent(config.effective_user_agent())?;
+    // Browsers offer h2 ahead of http/1.1 in ALPN; only possible when
+    // libcurl was built with HTTP/2
+    if config.impersonate.is_some() && curl::Version::get().feature_http2() {
+        easy.http_version(HttpVersion::V2TLS)?;
+    }
+    if let Some(weight) = config.h2_priority {
+        h2_priority(easy, weight, config.verbose)?;
+    }
+    if let Some(mode) = config.http2 {
+        if !curl::Version::get().feature_http2() {
+            return Err(RequestError::Config {
+                option: mode.flag().to_string(),
+                message: "libcurl was built without HTTP/2".to_string(),
+            });
+        }
+        easy.http_version(match mode {
+            // h2 through ALPN, or an h2c upgrade for http:// URLs
+            Http2::Negotiate => HttpVersion::V2,
+            Http2::PriorKnowledge => HttpVersion::V2PriorKnowledge,
+        })?;
+    }
     if let Some(n) = config.max_redirs {
         easy.max_redirections(n)?;
     }
@@ -180,6 +529,24 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
         easy.ssl_options(&ssl_opts)?;
         easy.proxy_ssl_options(&ssl_opts)?;
     }
+    if config.tls_min.is_some() || config.tls_max.is_some() {
+        tls_versions(easy, config.tls_min, config.tls_max)?;
+    }
+    if let Some(ref list) = config.ciphers {
+        easy.ssl_cipher_list(list)?;
+    }
+    if let Some(ref list) = config.tls13_ciphers {
+        tls13_ciphers(easy, list)?;
+    }
+    if let Some(ref path) = config.keylog {
+        keylog(easy, path, config)?;
+    }
+    if let Some(ref mode) = config.ech {
+        ech(easy, mode)?;
+    }
+    if config.tls_early_data && super::tls_session::early_data_safe(config) {
+        early_data(easy, config.ssl_no_revoke)?;
+    }
     if config.verbose {
         easy.verbose(true)?;
     }
@@ -187,19 +554,28 @@ fn apply_options(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestE
 }
 
 #[cfg(feature = "curl")]
-fn apply_resolve(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
-    if !config.resolve.is_empty() {
+fn apply_resolve(easy: &mut Easy, config: &RequestConfig, pinned: Option<&Pinned>) -> Result<(), RequestError> {
+    // rustcurl's own cache stands in for libcurl's when enabled
+    easy.dns_cache_timeout(if config.dns_cache {
+        config.dns_cache_timeout
+    } else {
+        Duration::ZERO
+    })?;
+    if !config.resolve.is_empty() || pinned.is_some() {
         let mut list = List::new();
         for entry in &config.resolve {
             list.append(entry)?;
         }
+        if let Some(pinned) = pinned {
+            list.append(&pinned.resolve_entry())?;
+        }
         easy.resolve(list)?;
     }
     Ok(())
 }
 
 #[cfg(feature = "curl")]
-fn collect_timing(easy: &mut Easy) -> Timing {
+fn collect_timing(easy: &mut Easy, hops: Vec<HopTiming>, handshake: &Handshake) -> Timing {
     Timing {
         dns: easy.namelookup_time().unwrap_or(Duration::ZERO),
         connect: easy.connect_time().unwrap_or(Duration::ZERO),
@@ -207,82 +583,1228 @@ fn collect_timing(easy: &mut Easy) -> Timing {
         starttransfer: easy.starttransfer_time().unwrap_or(Duration::ZERO),
         total: easy.total_time().unwrap_or(Duration::ZERO),
         redirect: easy.redirect_time().unwrap_or(Duration::ZERO),
+        // Only new TLS connections log their handshake
+        tls_resumed: handshake.tls_version.is_some().then_some(handshake.resumed),
+        early_data: handshake.early_data,
+        hops,
     }
 }
 
 #[cfg(feature = "curl")]
-pub fn perform_request(config: &RequestConfig) -> Result<Response, RequestError> {
-    let mut easy = Easy::new();
-    easy.url(&config.url)?;
-    easy.follow_location(true)?;
+fn collect_transfer(easy: &mut Easy, handshake: Handshake) -> TransferInfo {
+    TransferInfo {
+        url_effective: easy.effective_url().ok().flatten().unwrap_or_default().to_string(),
+        size_download: easy.download_size().unwrap_or(0.0) as u64,
+        size_upload: easy.upload_size().unwrap_or(0.0) as u64,
+        size_header: easy.header_size().unwrap_or(0),
+        num_redirects: easy.redirect_count().unwrap_or(0),
+        remote_ip: easy.primary_ip().ok().flatten().filter(|ip| !ip.is_empty()).map(str::to_string),
+        remote_port: easy.primary_port().ok().filter(|&port| port != 0),
+        local_ip: easy.local_ip().ok().flatten().filter(|ip| !ip.is_empty()).map(str::to_string),
+        local_port: easy.local_port().ok().filter(|&port| port != 0),
+        num_connects: info_long(easy, curl_sys::CURLINFO_NUM_CONNECTS).map(|n| n as u32),
+        tls_version: handshake.tls_version,
+        tls_cipher: handshake.tls_cipher,
+        alpn: handshake.alpn,
+        ssl_verify_result: info_long(easy, curl_sys::CURLINFO_SSL_VERIFYRESULT),
+        ech: handshake.ech,
+        pushed: Vec::new(),
+    }
+}
+
+/// A `long` getinfo value the curl crate has no getter for.
+#[cfg(feature = "curl")]
+fn info_long(easy: &Easy, info: curl_sys::CURLINFO) -> Option<i64> {
+    let mut value: std::os::raw::c_long = 0;
+    // SAFETY: callers pass CURLINFO_LONG values, which write a long
+    let code = unsafe { curl_sys::curl_easy_getinfo(easy.raw(), info, &mut value) };
+    (code == curl_sys::CURLE_OK).then_some(value as i64)
+}
+
+/// `CURLINFO_HTTP_VERSION` of the last response as `1.1`, `2` and so on;
+/// the curl crate has no getter for it.
+#[cfg(feature = "curl")]
+fn http_version_used(easy: &Easy) -> Option<String> {
+    const CURLINFO_HTTP_VERSION: curl_sys::CURLINFO = curl_sys::CURLINFO_LONG + 46;
+    let version = match info_long(easy, CURLINFO_HTTP_VERSION)? {
+        1 => "1.0",
+        2 => "1.1",
+        3 => "2",
+        30 => "3",
+        _ => return None,
+    };
+    Some(version.to_string())
+}
+
+/// The method the proxy accepted credentials with, from
+/// `CURLINFO_PROXYAUTH_USED` (libcurl 8.12), which the curl crate lacks.
+#[cfg(feature = "curl")]
+fn proxy_auth_used(easy: &Easy) -> Option<&'static str> {
+    const CURLINFO_PROXYAUTH_USED: curl_sys::CURLINFO = curl_sys::CURLINFO_LONG + 70;
+    let used = info_long(easy, CURLINFO_PROXYAUTH_USED)? as std::os::raw::c_ulong;
+    [
+        (curl_sys::CURLAUTH_GSSNEGOTIATE, "Negotiate"),
+        (curl_sys::CURLAUTH_NTLM, "NTLM"),
+        (curl_sys::CURLAUTH_DIGEST, "Digest"),
+        (curl_sys::CURLAUTH_BASIC, "Basic"),
+    ]
+    .into_iter()
+    .find(|(bit, _)| used & bit != 0)
+    .map(|(_, name)| name)
+}
+
+/// What libcurl's informational messages said about the TLS handshake,
+/// which it has no getinfo for, e.g.
+/// `SSL connection using TLSv1.3 / TLS_AES_256_GCM_SHA384 / X25519` and
+/// `ALPN: server accepted h2`. Wording differs between TLS libraries and
+/// libcurl versions, so anything unrecognised is left unset.
+#[cfg(feature = "curl")]
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Handshake {
+    tls_version: Option<String>,
+    tls_cipher: Option<String>,
+    alpn: Option<String>,
+    /// A cached session was offered for resumption.
+    resumed: bool,
+    /// Early data the server accepted, 0 if it rejected it.
+    early_data: Option<u64>,
+    ech: Option<String>,
+}
 
-    apply_method(&mut easy, config)?;
-    apply_auth(&mut easy, config)?;
+#[cfg(feature = "curl")]
+impl Handshake {
+    fn note(&mut self, text: &str) {
+        let text = text.trim();
+        if let Some(rest) = text.strip_prefix("SSL connection using ") {
+            let mut parts = rest.split(" / ").map(str::trim);
+            self.tls_version = parts.next().filter(|v| !v.is_empty()).map(str::to_string);
+            self.tls_cipher = parts.next().filter(|c| !c.is_empty()).map(str::to_string);
+        } else if let Some(protocol) = text
+            .strip_prefix("ALPN: server accepted ")
+            .or_else(|| text.strip_prefix("ALPN, server accepted to use "))
+        {
+            self.alpn = Some(protocol.trim().to_string());
+        } else if text.starts_with("SSL reusing session") {
+            self.resumed = true;
+        } else if let Some(rest) = text.strip_prefix("Server accepted ") {
+            self.early_data = rest.split(' ').next().and_then(|n| n.parse().ok());
+        } else if text.starts_with("Server rejected TLS early data") {
+            self.early_data = Some(0);
+        } else if let Some(rest) = text.strip_prefix("ECH: result: status is ") {
+            self.ech = Some(rest.split(", inner is ").next().unwrap_or(rest).to_string());
+        }
+    }
+}
+
+/// What `run_transfer` received besides the body.
+#[cfg(feature = "curl")]
+#[derive(Default)]
+struct Received {
+    headers: Vec<String>,
+    /// When each status line in `headers` arrived.
+    arrivals: Vec<Duration>,
+    bytes: u64,
+    handshake: Handshake,
+    /// Header lines left out for not being valid text.
+    dropped_headers: usize,
+}
 
-    let header_list = build_headers(config)?;
-    easy.http_headers(header_list)?;
+/// Reset `easy` and configure it for `config`, ready for `transfer()`.
+/// Resetting keeps the handle's connection and DNS caches, so reused
+/// handles skip reconnecting to hosts they have already talked to.
+#[cfg(feature = "curl")]
+fn prepare(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
+    let pinned = super::dns::pre_resolve(config)?;
+
+    easy.reset();
+    easy.url(&config.request_url())?;
+    easy.follow_location(config.follow_location)?;
 
-    if let Some(ref data) = config.data {
+    apply_method(easy, config)?;
+    apply_auth(easy, config)?;
+
+    apply_options(easy, config)?;
+    apply_resolve(easy, config, pinned.as_ref())?;
+    Ok(())
+}
+
+/// Send `-d`/form data from the config as the request body.
+#[cfg(feature = "curl")]
+fn apply_data(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
+    easy.http_headers(build_headers(config)?)?;
+    if let Some(ref data) = config.body() {
         easy.post_field_size(data.len() as u64)?;
-        easy.post_fields_copy(data.as_bytes())?;
+        easy.post_fields_copy(data)?;
+    } else if config.method == Method::Post {
+        // Without this libcurl streams an empty chunked body from the
+        // read callback instead of sending Content-Length: 0.
+        easy.post_field_size(0)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "curl")]
+pub fn perform_request(easy: &mut Easy, config: &RequestConfig) -> Result<Response, RequestError> {
+    prepare(easy, config)?;
+    apply_data(easy, config)?;
+    finish(easy, config, None)
+}
+
+/// Like `perform_request`, but the body is read from `body` through
+/// libcurl's read callback instead of coming from `-d` data.
+#[cfg(feature = "curl")]
+pub fn perform_upload(
+    easy: &mut Easy,
+    config: &RequestConfig,
+    body: RequestBody,
+) -> Result<Response, RequestError> {
+    prepare(easy, config)?;
+    let mut list = build_headers(config)?;
+    match body.len {
+        Some(len) => easy.post_field_size(len)?,
+        None => list.append("Transfer-Encoding: chunked")?,
+    }
+    easy.http_headers(list)?;
+    // POST makes libcurl pull the body from the read callback; any other
+    // method keeps its name through CUSTOMREQUEST.
+    easy.post(true)?;
+    if config.method != Method::Post {
+        easy.custom_request(config.method.as_str())?;
+    }
+    finish(easy, config, Some(body.reader))
+}
+
+#[cfg(feature = "curl")]
+fn finish(
+    easy: &mut Easy,
+    config: &RequestConfig,
+    upload: Option<Box<dyn Read + Send>>,
+) -> Result<Response, RequestError> {
+    let sessions = match config.tls_session_cache {
+        Some(ref path) => Some(SessionCache::attach(easy, path)?),
+        None => None,
+    };
+    let mut sink = BodySink::open(config);
+    let received = run_transfer(easy, config, upload, &mut sink);
+    let jar = save_cookie_jar(easy, config);
+    let received = match received {
+        Ok(received) => received,
+        Err(RequestError::Io(e)) => return Err(sink.write_error(e)),
+        Err(e) => return Err(e),
+    };
+    jar?;
+    if let Some(sessions) = sessions {
+        sessions.save()?;
+    }
+    let body = sink.finish()?;
+    build_response(easy, config, received, body, BodySink::streams(config))
+}
+
+/// The header libcurl writes at the top of a cookie jar.
+#[cfg(feature = "curl")]
+const JAR_HEADER: &str = "# Netscape HTTP Cookie File\n# https://curl.se/docs/http-cookies.html\n\
+    # This file was generated by libcurl! Edit at your own risk.\n\n";
+
+/// `-c FILE`: save the jar as soon as the transfer ends, replacing it
+/// atomically, instead of leaving it to libcurl when the handle closes,
+/// by which time another run may have saved a newer one. `-c -` (stdout)
+/// is still libcurl's.
+#[cfg(feature = "curl")]
+fn save_cookie_jar(easy: &mut Easy, config: &RequestConfig) -> Result<(), RequestError> {
+    let Some(path) = config.cookie_jar.as_deref().filter(|path| *path != "-") else {
+        return Ok(());
+    };
+    let saved = easy.cookies()?.iter().map(|line| String::from_utf8_lossy(line).into_owned()).collect();
+    let mut text = JAR_HEADER.to_string();
+    for line in carry_over(saved, &config.cookies)? {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    atomic::write(Path::new(path), text.as_bytes()).map_err(|source| RequestError::WriteOutput {
+        path: path.to_string(),
+        source,
+    })?;
+    // SAFETY: a NULL string clears CURLOPT_COOKIEJAR on a live handle
+    let code =
+        unsafe { curl_sys::curl_easy_setopt(easy.raw(), curl_sys::CURLOPT_COOKIEJAR, std::ptr::null::<c_char>()) };
+    if code != curl_sys::CURLE_OK {
+        return Err(curl::Error::new(code).into());
+    }
+    Ok(())
+}
+
+/// `--tls-session-cache` for one transfer: a share handle holding the
+/// file's sessions, attached to the easy handle. libcurl keeps sessions
+/// in the multi or share handle a transfer runs on, and the easy
+/// interface's own multi is gone after `perform()`, hence the share.
+#[cfg(feature = "curl")]
+struct SessionCache<'a> {
+    easy: *mut curl_sys::CURL,
+    share: *mut curl_sys::CURLSH,
+    path: &'a str,
+}
+
+#[cfg(feature = "curl")]
+type SslsExportCallback = extern "C" fn(
+    *mut curl_sys::CURL,
+    *mut c_void,
+    *const c_char,
+    *const u8,
+    usize,
+    *const u8,
+    usize,
+    curl_sys::curl_off_t,
+    c_int,
+    *const c_char,
+    usize,
+) -> curl_sys::CURLcode;
+
+#[cfg(feature = "curl")]
+unsafe extern "C" {
+    fn curl_easy_ssls_import(
+        easy: *mut curl_sys::CURL,
+        session_key: *const c_char,
+        shmac: *const u8,
+        shmac_len: usize,
+        sdata: *const u8,
+        sdata_len: usize,
+    ) -> curl_sys::CURLcode;
+    fn curl_easy_ssls_export(
+        easy: *mut curl_sys::CURL,
+        export_fn: SslsExportCallback,
+        userptr: *mut c_void,
+    ) -> curl_sys::CURLcode;
+}
+
+#[cfg(feature = "curl")]
+impl<'a> SessionCache<'a> {
+    fn attach(easy: &mut Easy, path: &'a str) -> Result<SessionCache<'a>, RequestError> {
+        let tickets = super::tls_session::load(Path::new(path)).map_err(|e| session_cache_error(path, e))?;
+        // SAFETY: a new share handle, owned (and cleaned up) by the guard
+        let share = unsafe { curl_sys::curl_share_init() };
+        if share.is_null() {
+            return Err(curl::Error::new(curl_sys::CURLE_OUT_OF_MEMORY).into());
+        }
+        let cache = SessionCache {
+            easy: easy.raw(),
+            share,
+            path,
+        };
+        // SAFETY: both handles are live; the guard detaches the share
+        // before cleaning it up
+        let code = unsafe {
+            curl_sys::curl_share_setopt(share, curl_sys::CURLSHOPT_SHARE, curl_sys::CURL_LOCK_DATA_SSL_SESSION);
+            curl_sys::curl_easy_setopt(cache.easy, curl_sys::CURLOPT_SHARE, share)
+        };
+        if code != curl_sys::CURLE_OK {
+            return Err(curl::Error::new(code).into());
+        }
+        // Fails up front when libcurl was built without session export
+        cache.export()?;
+        for ticket in tickets {
+            let Ok(key) = std::ffi::CString::new(ticket.key) else {
+                continue;
+            };
+            // SAFETY: pointers and lengths of live buffers. A session
+            // libcurl cannot use, e.g. from another TLS library, is
+            // refused and skipped.
+            unsafe {
+                curl_easy_ssls_import(
+                    cache.easy,
+                    key.as_ptr(),
+                    ticket.shmac.as_ptr(),
+                    ticket.shmac.len(),
+                    ticket.data.as_ptr(),
+                    ticket.data.len(),
+                )
+            };
+        }
+        Ok(cache)
+    }
+
+    fn export(&self) -> Result<Vec<super::tls_session::Ticket>, RequestError> {
+        const CURLE_NOT_BUILT_IN: curl_sys::CURLcode = 4;
+        let mut tickets = Vec::new();
+        // SAFETY: `tickets` outlives the call, which is all the callback
+        // uses it for
+        let code = unsafe {
+            curl_easy_ssls_export(self.easy, on_session_export, &mut tickets as *mut Vec<_> as *mut c_void)
+        };
+        match code {
+            curl_sys::CURLE_OK => Ok(tickets),
+            CURLE_NOT_BUILT_IN => Err(RequestError::Config {
+                option: "--tls-session-cache".to_string(),
+                message: "libcurl was built without TLS session export (--enable-ssls-export)".to_string(),
+            }),
+            code => Err(curl::Error::new(code).into()),
+        }
+    }
+
+    /// Write every session the cache now holds back to the file.
+    fn save(self) -> Result<(), RequestError> {
+        let tickets = self.export()?;
+        super::tls_session::save(Path::new(self.path), &tickets).map_err(|e| session_cache_error(self.path, e))
     }
+}
 
-    apply_options(&mut easy, config)?;
-    apply_resolve(&mut easy, config)?;
+#[cfg(feature = "curl")]
+impl Drop for SessionCache<'_> {
+    fn drop(&mut self) {
+        // SAFETY: detach first, as libcurl refuses to clean up a share
+        // still in use
+        unsafe {
+            curl_sys::curl_easy_setopt(self.easy, curl_sys::CURLOPT_SHARE, std::ptr::null_mut::<c_void>());
+            curl_sys::curl_share_cleanup(self.share);
+        }
+    }
+}
 
+/// Collects each session `curl_easy_ssls_export` hands over.
+#[cfg(feature = "curl")]
+#[allow(clippy::too_many_arguments)]
+extern "C" fn on_session_export(
+    _easy: *mut curl_sys::CURL,
+    tickets: *mut c_void,
+    session_key: *const c_char,
+    shmac: *const u8,
+    shmac_len: usize,
+    sdata: *const u8,
+    sdata_len: usize,
+    valid_until: curl_sys::curl_off_t,
+    _ietf_tls_id: c_int,
+    _alpn: *const c_char,
+    _earlydata_max: usize,
+) -> curl_sys::CURLcode {
+    // SAFETY: libcurl passes the export's user pointer and buffers valid
+    // for the duration of the call
+    unsafe {
+        let tickets = &mut *(tickets as *mut Vec<super::tls_session::Ticket>);
+        if session_key.is_null() || sdata.is_null() {
+            return curl_sys::CURLE_OK;
+        }
+        tickets.push(super::tls_session::Ticket {
+            key: CStr::from_ptr(session_key).to_string_lossy().into_owned(),
+            shmac: if shmac.is_null() { Vec::new() } else { std::slice::from_raw_parts(shmac, shmac_len).to_vec() },
+            data: std::slice::from_raw_parts(sdata, sdata_len).to_vec(),
+            valid_until,
+        });
+    }
+    curl_sys::CURLE_OK
+}
+
+#[cfg(feature = "curl")]
+fn session_cache_error(path: &str, e: io::Error) -> RequestError {
+    RequestError::Config {
+        option: "--tls-session-cache".to_string(),
+        message: format!("{path}: {e}"),
+    }
+}
+
+/// Response for a finished transfer, writing a buffered body to `-o` if
+/// set and the sink has not already `written` it.
+#[cfg(feature = "curl")]
+fn build_response(
+    easy: &mut Easy,
+    config: &RequestConfig,
+    received: Received,
+    body: Vec<u8>,
+    written: bool,
+) -> Result<Response, RequestError> {
+    let Received {
+        headers,
+        arrivals,
+        handshake,
+        dropped_headers,
+        ..
+    } = received;
+    let mut warnings = Vec::new();
+    if dropped_headers > 0 {
+        warnings.push(Warning::new(
+            WarningKind::Header,
+            format!("{dropped_headers} response header line(s) could not be read as text and were left out"),
+        ));
+    }
+    let status_code = easy.response_code()?;
+    if let Some(scheme) = proxy_auth_used(easy) {
+        config.note(format_args!("Authenticated to the proxy with {scheme}"));
+    }
+    let informational = informational_responses(&headers);
+
+    let timing = if config.wants_timing() {
+        Some(collect_timing(easy, hop_timings(&config.request_url(), &headers, &arrivals), &handshake))
+    } else {
+        None
+    };
+
+    let peer_certificates = if config.provenance || config.show_certs || config.tofu {
+        peer_certificates(easy)
+    } else {
+        Vec::new()
+    };
+    let transfer = Some(collect_transfer(easy, handshake));
+
+    let body = match config.output_path() {
+        Some(path) if !written => {
+            let body = saved_body(status_code, &headers, body, &mut warnings);
+            fs::write(&path, &body).map_err(|source| RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            })?;
+            Vec::new()
+        }
+        _ => body,
+    };
+
+    Ok(Response {
+        status_code,
+        headers,
+        body,
+        timing,
+        attempts: Vec::new(),
+        informational,
+        peer_certificates,
+        saved_as: None,
+        http_version: http_version_used(easy),
+        transfer,
+        warnings,
+    })
+}
+
+/// DER encoding of the server's certificate chain, leaf first, from
+/// libcurl's certificate info (requires `certinfo(true)` before the
+/// transfer). The curl crate has no getter for `CURLINFO_CERTINFO`, so
+/// read it through curl-sys.
+#[cfg(feature = "curl")]
+fn peer_certificates(easy: &Easy) -> Vec<Vec<u8>> {
+    let mut chain = Vec::new();
+    let mut info: *mut curl_sys::curl_certinfo = std::ptr::null_mut();
+    // SAFETY: CURLINFO_CERTINFO writes a pointer owned by the handle,
+    // valid until the next transfer; the lists are only read here.
+    unsafe {
+        if curl_sys::curl_easy_getinfo(easy.raw(), curl_sys::CURLINFO_CERTINFO, &mut info) != curl_sys::CURLE_OK
+            || info.is_null()
+        {
+            return chain;
+        }
+        for i in 0..(*info).num_of_certs.max(0) as usize {
+            let mut node = *(*info).certinfo.add(i);
+            while !node.is_null() {
+                let line = CStr::from_ptr((*node).data).to_string_lossy();
+                if let Some(der) = line.strip_prefix("Cert:").and_then(pem_to_der) {
+                    chain.push(der);
+                    break;
+                }
+                node = (*node).next;
+            }
+        }
+    }
+    chain
+}
+
+/// Body of the first PEM block, base64-decoded.
+#[cfg(feature = "curl")]
+fn pem_to_der(pem: &str) -> Option<Vec<u8>> {
+    let encoded: String = pem
+        .lines()
+        .filter(|l| !l.starts_with("-----"))
+        .flat_map(|l| l.trim().chars())
+        .collect();
+    let mut out = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes().take_while(|&c| c != b'=') {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Run the transfer, feeding `upload` to libcurl and the response body to
+/// `sink`. libcurl's informational messages are always on, to learn the
+/// handshake details; they reach stderr only with `--verbose`, in
+/// libcurl's own format.
+#[cfg(feature = "curl")]
+fn run_transfer(
+    easy: &mut Easy,
+    config: &RequestConfig,
+    mut upload: Option<Box<dyn Read + Send>>,
+    sink: &mut dyn Write,
+) -> Result<Received, RequestError> {
     let mut headers: Vec<String> = Vec::new();
-    let mut body: Vec<u8> = Vec::new();
+    let mut arrivals = Vec::new();
+    let mut handshake = Handshake::default();
+    let started = std::time::Instant::now();
+    let mut bytes = 0u64;
+    let mut sink_error = None;
+    let mut dropped_headers = 0;
 
-    {
+    easy.verbose(true)?;
+    let result = {
         let mut transfer = easy.transfer();
 
+        transfer.debug_function(|kind, data| {
+            let prefix = match kind {
+                InfoType::Text => {
+                    handshake.note(&String::from_utf8_lossy(data));
+                    "* "
+                }
+                InfoType::HeaderIn => "< ",
+                InfoType::HeaderOut => {
+                    recorder::head(data);
+                    "> "
+                }
+                InfoType::DataOut => return recorder::body(data),
+                _ => return,
+            };
+            if config.verbose {
+                let mut err = io::stderr().lock();
+                let _ = err.write_all(prefix.as_bytes()).and_then(|()| err.write_all(data));
+            }
+        })?;
+
+        if let Some(reader) = upload.as_mut() {
+            transfer.read_function(|buf| reader.read(buf).map_err(|_| ReadError::Abort))?;
+        }
+
         transfer.header_function(|data| {
             if let Ok(header) = std::str::from_utf8(data) {
                 let trimmed = header.trim();
+                if status_line_code(trimmed).is_some() {
+                    arrivals.push(started.elapsed());
+                }
                 if !trimmed.is_empty() {
                     headers.push(trimmed.to_string());
                 }
+            } else {
+                dropped_headers += 1;
             }
             true
         })?;
 
-        transfer.write_function(|data| {
-            body.extend_from_slice(data);
-            Ok(data.len())
+        transfer.write_function(|data| match sink.write_all(data) {
+            Ok(()) => {
+                bytes += data.len() as u64;
+                Ok(data.len())
+            }
+            Err(e) => {
+                // Short write aborts the transfer; the cause is reported below
+                sink_error = Some(e);
+                Ok(0)
+            }
         })?;
 
-        transfer.perform()?;
+        transfer.perform()
+    };
+
+    if let Some(e) = sink_error {
+        return Err(RequestError::Io(e));
+    }
+    match result {
+        Err(e) if e.is_partial_file() => {
+            let expected = easy
+                .content_length_download()
+                .ok()
+                .filter(|n| *n >= 0.0)
+                .map(|n| n as u64);
+            if !config.accept_partial {
+                return Err(RequestError::PartialBody { expected, received: bytes });
+            }
+            let of = expected.map(|n| format!(" of {n}")).unwrap_or_default();
+            config.note(format_args!("Accepting partial body: received {bytes}{of} bytes"));
+        }
+        other => other?,
+    }
+    Ok(Received {
+        headers,
+        arrivals,
+        bytes,
+        handshake,
+        dropped_headers,
+    })
+}
+
+/// Send the request and write the body to `sink` as it arrives.
+#[cfg(feature = "curl")]
+pub fn perform_to(
+    easy: &mut Easy,
+    config: &RequestConfig,
+    sink: &mut dyn Write,
+) -> Result<TransferSummary, RequestError> {
+    prepare(easy, config)?;
+    apply_data(easy, config)?;
+    let received = run_transfer(easy, config, None, sink);
+    let jar = save_cookie_jar(easy, config);
+    let received = received?;
+    jar?;
+    let hops = hop_timings(&config.request_url(), &received.headers, &received.arrivals);
+    Ok(TransferSummary {
+        status_code: easy.response_code()?,
+        headers: received.headers,
+        bytes: received.bytes,
+        timing: Some(collect_timing(easy, hops, &received.handshake)),
+    })
+}
+
+/// Header lines and body of a transfer run on a multi handle.
+#[cfg(feature = "curl")]
+type Collected = Arc<Mutex<(Vec<String>, Vec<u8>)>>;
+
+/// Point `easy`'s header and body callbacks at `collected`.
+#[cfg(feature = "curl")]
+fn collect_into(easy: &mut Easy, collected: &Collected) -> Result<(), RequestError> {
+    let sink = Arc::clone(collected);
+    easy.header_function(move |data| {
+        if let Ok(header) = std::str::from_utf8(data) {
+            let trimmed = header.trim();
+            if !trimmed.is_empty() {
+                sink.lock().unwrap().0.push(trimmed.to_string());
+            }
+        }
+        true
+    })?;
+    let sink = Arc::clone(collected);
+    easy.write_function(move |data| {
+        sink.lock().unwrap().1.extend_from_slice(data);
+        Ok(data.len())
+    })?;
+    Ok(())
+}
+
+/// Run all requests concurrently on one libcurl multi handle. Multiplexing
+/// is enabled and each transfer waits for a usable HTTP/2 connection
+/// (`PIPEWAIT`) instead of opening its own, so requests to the same h2
+/// origin share one connection. Bodies are buffered, then saved to `-o`.
+#[cfg(feature = "curl")]
+pub fn perform_parallel(configs: &[RequestConfig]) -> ParallelOutcome {
+    let mut responses: Vec<Option<Result<Response, RequestError>>> = configs.iter().map(|_| None).collect();
+    let mut multi = Multi::new();
+    if let Err(e) = multi.pipelining(false, true) {
+        return ParallelOutcome::failed(configs.len(), e.into());
     }
 
-    let status_code = easy.response_code()?;
+    let mut handles: Vec<Option<(EasyHandle, Collected)>> = configs.iter().map(|_| None).collect();
+    for (i, config) in configs.iter().enumerate() {
+        let collected: Collected = Arc::default();
+        let mut easy = Easy::new();
+        let setup = prepare(&mut easy, config)
+            .and_then(|_| apply_data(&mut easy, config))
+            .and_then(|_| Ok(easy.pipewait(true)?))
+            .and_then(|_| collect_into(&mut easy, &collected))
+            .and_then(|_| {
+                let mut handle = multi.add(easy)?;
+                handle.set_token(i)?;
+                Ok(handle)
+            });
+        match setup {
+            Ok(handle) => handles[i] = Some((handle, collected)),
+            Err(e) => responses[i] = Some(Err(e)),
+        }
+    }
 
-    let timing = if config.show_timing {
-        Some(collect_timing(&mut easy))
-    } else {
-        None
+    let mut endpoints = HashSet::new();
+    loop {
+        let running = match multi.perform() {
+            Ok(n) => n,
+            Err(e) => {
+                let message = e.to_string();
+                for slot in responses.iter_mut().filter(|r| r.is_none()) {
+                    *slot = Some(Err(RequestError::Transfer { message: message.clone() }));
+                }
+                break;
+            }
+        };
+
+        let mut done = Vec::new();
+        multi.messages(|msg| {
+            if let (Ok(token), Some(result)) = (msg.token(), msg.result()) {
+                done.push((token, result));
+            }
+        });
+        for (i, result) in done {
+            let Some((handle, collected)) = handles[i].take() else {
+                continue;
+            };
+            let outcome = multi.remove(handle).map_err(RequestError::from).and_then(|mut easy| {
+                result?;
+                if let (Ok(Some(ip)), Ok(port)) = (easy.primary_ip(), easy.local_port()) {
+                    endpoints.insert((ip.to_string(), port));
+                }
+                let (headers, body) = std::mem::take(&mut *collected.lock().unwrap());
+                let received = Received {
+                    headers,
+                    ..Received::default()
+                };
+                build_response(&mut easy, &configs[i], received, body, false)
+            });
+            responses[i] = Some(outcome);
+        }
+
+        if running == 0 {
+            break;
+        }
+        if let Err(e) = multi.wait(&mut [], Duration::from_secs(1)) {
+            let message = e.to_string();
+            for slot in responses.iter_mut().filter(|r| r.is_none()) {
+                *slot = Some(Err(RequestError::Transfer { message: message.clone() }));
+            }
+            break;
+        }
+    }
+
+    ParallelOutcome {
+        responses: responses
+            .into_iter()
+            .map(|r| {
+                r.unwrap_or_else(|| {
+                    Err(RequestError::Transfer {
+                        message: "transfer did not complete".to_string(),
+                    })
+                })
+            })
+            .collect(),
+        connections: Some(endpoints.len()),
+    }
+}
+
+/// Tokens of pushed handles start here, clear of the request's own.
+#[cfg(feature = "curl")]
+const PUSH_TOKEN: usize = usize::MAX / 2;
+#[cfg(feature = "curl")]
+const CURL_PUSH_OK: c_int = 0;
+#[cfg(feature = "curl")]
+const CURL_PUSH_DENY: c_int = 1;
+
+#[cfg(feature = "curl")]
+type PushCallback = extern "C" fn(*mut curl_sys::CURL, *mut curl_sys::CURL, usize, *mut c_void, *mut c_void) -> c_int;
+
+/// What the push callback works on, owned by `perform_accepting_push`.
+#[cfg(feature = "curl")]
+struct PushState {
+    /// `None` to count pushes without saving them.
+    dir: Option<PathBuf>,
+    /// The sink for `verbose` notes, `None` when they are off.
+    notes: Option<VerboseSink>,
+    /// Boxed so each write callback's pointer stays put as more arrive.
+    #[allow(clippy::vec_box)]
+    sinks: Vec<Box<PushSink>>,
+}
+
+#[cfg(feature = "curl")]
+impl PushState {
+    fn note(&self, message: std::fmt::Arguments<'_>) {
+        if let Some(ref sink) = self.notes {
+            sink.write(&message.to_string());
+        }
+    }
+}
+
+#[cfg(feature = "curl")]
+struct PushSink {
+    pushed: Pushed,
+    file: Option<fs::File>,
+    handle: *mut curl_sys::CURL,
+}
+
+/// Send the request on a multi handle with a push callback installed;
+/// without one, libcurl tells the server not to push. Each push is saved
+/// under `--accept-push` (or only counted for `-`) and listed in
+/// `TransferInfo::pushed`. The body is buffered, then saved to `-o`.
+#[cfg(feature = "curl")]
+pub fn perform_accepting_push(config: &RequestConfig) -> Result<Response, RequestError> {
+    let mut multi = Multi::new();
+    multi.pipelining(false, true)?;
+    let collected: Collected = Arc::default();
+    let mut easy = Easy::new();
+    prepare(&mut easy, config)?;
+    apply_data(&mut easy, config)?;
+    collect_into(&mut easy, &collected)?;
+    let handle = multi.add(easy)?;
+
+    let state = Box::into_raw(Box::new(PushState {
+        dir: config.accept_push.as_deref().filter(|d| *d != push::LIST_ONLY).map(PathBuf::from),
+        notes: config.verbose.then(|| config.verbose_sink.clone()),
+        sinks: Vec::new(),
+    }));
+    let result = install_push_callback(&multi, state).and_then(|()| run_pushable(&multi, &handle, state));
+    // SAFETY: `state` came from Box::into_raw above, and no callback can
+    // run once `multi` stops performing. The pushed handles are freed
+    // before the request's own handle, whose callbacks they copied.
+    let sinks = unsafe { Box::from_raw(state) }.sinks;
+    let pushed: Vec<Pushed> = sinks.into_iter().map(|sink| unsafe { release_push(&multi, *sink) }).collect();
+    let mut easy = multi.remove(handle)?;
+    result?;
+
+    let (headers, body) = std::mem::take(&mut *collected.lock().unwrap());
+    let received = Received {
+        headers,
+        ..Received::default()
     };
+    let mut response = build_response(&mut easy, config, received, body, false)?;
+    if let Some(transfer) = response.transfer.as_mut() {
+        transfer.pushed = pushed;
+    }
+    Ok(response)
+}
+
+#[cfg(feature = "curl")]
+fn install_push_callback(multi: &Multi, state: *mut PushState) -> Result<(), RequestError> {
+    // SAFETY: the callback matches curl_push_callback and `state` outlives
+    // every transfer on `multi`
+    unsafe {
+        for code in [
+            curl_sys::curl_multi_setopt(multi.raw(), curl_sys::CURLMOPT_PUSHFUNCTION, on_push as PushCallback),
+            curl_sys::curl_multi_setopt(multi.raw(), curl_sys::CURLMOPT_PUSHDATA, state as *mut c_void),
+        ] {
+            if code != curl_sys::CURLM_OK {
+                return Err(curl::MultiError::new(code).into());
+            }
+        }
+    }
+    Ok(())
+}
 
-    if let Some(ref path) = config.output {
-        fs::write(path, &body)?;
-        return Ok(Response {
-            status_code,
-            headers,
-            body: Vec::new(),
-            timing,
+/// Drive `multi` until the request and every push it brought are done,
+/// returning the request's own result.
+#[cfg(feature = "curl")]
+fn run_pushable(multi: &Multi, handle: &EasyHandle, state: *mut PushState) -> Result<(), RequestError> {
+    let mut result = None;
+    loop {
+        let running = multi.perform()?;
+        multi.messages(|msg| {
+            if let Some(done) = msg.result_for(handle) {
+                result = Some(done);
+            } else if let (Ok(token), Some(Err(e))) = (msg.token(), msg.result()) {
+                // SAFETY: callbacks only run inside `perform`, so nothing
+                // else holds the state now
+                let sinks = unsafe { &mut (*state).sinks };
+                if let Some(sink) = token.checked_sub(PUSH_TOKEN).and_then(|i| sinks.get_mut(i)) {
+                    sink.pushed.error.get_or_insert_with(|| e.to_string());
+                }
+            }
         });
+        if running == 0 {
+            break;
+        }
+        multi.wait(&mut [], Duration::from_secs(1))?;
     }
+    match result {
+        Some(done) => Ok(done?),
+        None => Err(RequestError::Transfer {
+            message: "transfer did not complete".to_string(),
+        }),
+    }
+}
 
-    Ok(Response {
+/// CURLMOPT_PUSHFUNCTION: take the push if it can be saved, pointing the
+/// new handle's output at a sink of its own.
+#[cfg(feature = "curl")]
+extern "C" fn on_push(
+    _parent: *mut curl_sys::CURL,
+    easy: *mut curl_sys::CURL,
+    _num_headers: usize,
+    _headers: *mut c_void,
+    userp: *mut c_void,
+) -> c_int {
+    // SAFETY: `userp` is the PushState installed as CURLMOPT_PUSHDATA
+    let state = unsafe { &mut *(userp as *mut PushState) };
+    // libcurl has already set the pushed handle's URL from the promise's
+    // :scheme, :authority and :path; reading it back keeps this working
+    // with libcurl builds that lack the curl_pushheader_* functions
+    let mut url_ptr: *const c_char = std::ptr::null();
+    // SAFETY: CURLINFO_EFFECTIVE_URL writes a string owned by the handle
+    let url = unsafe {
+        if curl_sys::curl_easy_getinfo(easy, curl_sys::CURLINFO_EFFECTIVE_URL, &mut url_ptr) != curl_sys::CURLE_OK
+            || url_ptr.is_null()
+        {
+            return CURL_PUSH_DENY;
+        }
+        CStr::from_ptr(url_ptr).to_string_lossy().into_owned()
+    };
+    let (saved_as, file) = match state.dir {
+        None => (None, None),
+        Some(ref dir) => match super::url::parse(&url).map_err(io::Error::other).and_then(|u| open_push_file(dir, u.path)) {
+            Ok((saved_as, file)) => (Some(saved_as), Some(file)),
+            Err(e) => {
+                state.note(format_args!("Refusing push of {url}: {e}"));
+                return CURL_PUSH_DENY;
+            }
+        },
+    };
+    state.note(format_args!("Accepting push of {url}"));
+    let mut sink = Box::new(PushSink {
+        pushed: Pushed {
+            url,
+            status_code: 0,
+            saved_as,
+            bytes: 0,
+            error: None,
+        },
+        file,
+        handle: easy,
+    });
+    let token = PUSH_TOKEN + state.sinks.len();
+    let null = std::ptr::null_mut::<c_void>();
+    // SAFETY: `easy` is the pushed handle, a copy of the request's. Its
+    // body goes to this push's sink, which lives until the handle is
+    // freed; its headers and error text are dropped rather than written
+    // through the request's callbacks.
+    unsafe {
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_WRITEFUNCTION, push_write as curl_sys::curl_write_callback);
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_WRITEDATA, &mut *sink as *mut PushSink as *mut c_void);
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_HEADERFUNCTION, null);
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_HEADERDATA, null);
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_ERRORBUFFER, null);
+        curl_sys::curl_easy_setopt(easy, curl_sys::CURLOPT_PRIVATE, token as *mut c_void);
+    }
+    state.sinks.push(sink);
+    CURL_PUSH_OK
+}
+
+#[cfg(feature = "curl")]
+fn open_push_file(dir: &Path, path: &str) -> io::Result<(PathBuf, fs::File)> {
+    let target = push::save_path(dir, path).ok_or_else(|| io::Error::other("no file name for it"))?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = fs::File::create(&target)?;
+    Ok((target, file))
+}
+
+/// CURLOPT_WRITEFUNCTION of a pushed handle.
+#[cfg(feature = "curl")]
+extern "C" fn push_write(data: *mut c_char, size: usize, count: usize, userdata: *mut c_void) -> usize {
+    // SAFETY: `userdata` is this push's PushSink and `data` holds
+    // `size * count` bytes
+    let sink = unsafe { &mut *(userdata as *mut PushSink) };
+    let len = size * count;
+    let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, len) };
+    if let Some(ref mut file) = sink.file
+        && let Err(e) = file.write_all(bytes)
+    {
+        sink.pushed.error = Some(e.to_string());
+        return 0;
+    }
+    sink.pushed.bytes += len as u64;
+    len
+}
+
+/// The finished push, with its status; its handle is removed and freed.
+///
+/// # Safety
+/// `sink.handle` must be a pushed handle on `multi`, not yet freed.
+#[cfg(feature = "curl")]
+unsafe fn release_push(multi: &Multi, sink: PushSink) -> Pushed {
+    let mut pushed = sink.pushed;
+    let mut code: c_long = 0;
+    unsafe {
+        if curl_sys::curl_easy_getinfo(sink.handle, curl_sys::CURLINFO_RESPONSE_CODE, &mut code) == curl_sys::CURLE_OK {
+            pushed.status_code = code as u32;
+        }
+        curl_sys::curl_multi_remove_handle(multi.raw(), sink.handle);
+        curl_sys::curl_easy_cleanup(sink.handle);
+    }
+    pushed
+}
+
+/// libcurl takes timeouts in whole milliseconds and reads 0 as none, so a
+/// nonzero duration under 1ms is rounded up rather than truncated away.
+#[cfg(feature = "curl")]
+fn whole_millis(d: Duration) -> Duration {
+    if d.is_zero() { d } else { d.max(Duration::from_millis(1)) }
+}
+
+/// Chunks buffered between the transfer thread and the reader before
+/// libcurl is made to wait.
+#[cfg(feature = "curl")]
+const STREAM_QUEUE: usize = 16;
+
+/// Run the transfer on a worker thread and return as soon as the body
+/// starts (or the transfer ends), so callers can read the body
+/// incrementally. Dropping the body aborts the transfer. The handle moves
+/// to the transfer thread and is dropped with it, so a pooled handle goes
+/// back when the transfer ends.
+#[cfg(feature = "curl")]
+pub fn perform_streaming<H>(mut handle: H, config: &RequestConfig) -> Result<StreamingResponse, RequestError>
+where
+    H: BorrowMut<Easy> + Send + 'static,
+{
+    prepare(handle.borrow_mut(), config)?;
+    apply_data(handle.borrow_mut(), config)?;
+    let (head_tx, head_rx) = mpsc::channel::<Result<(u32, Vec<String>), RequestError>>();
+    let (body_tx, body_rx) = mpsc::sync_channel::<io::Result<Vec<u8>>>(STREAM_QUEUE);
+
+    std::thread::spawn(move || {
+        let easy: &mut Easy = handle.borrow_mut();
+        // Both callbacks need the header state, so it lives in cells
+        let headers = RefCell::new(Vec::<String>::new());
+        let status = Cell::new(0);
+        let head_sent = Cell::new(false);
+        let result = {
+            let mut transfer = easy.transfer();
+            let setup = transfer
+                .header_function(|data| {
+                    if let Ok(header) = std::str::from_utf8(data) {
+                        let trimmed = header.trim();
+                        if let Some(code) = status_line_code(trimmed) {
+                            status.set(code);
+                        }
+                        if !trimmed.is_empty() {
+                            headers.borrow_mut().push(trimmed.to_string());
+                        }
+                    }
+                    true
+                })
+                .and_then(|_| {
+                    transfer.write_function(|data| {
+                        if !head_sent.replace(true) {
+                            let _ = head_tx.send(Ok((status.get(), headers.take())));
+                        }
+                        // A closed receiver means the caller dropped the
+                        // body; a short write makes libcurl abort.
+                        match body_tx.send(Ok(data.to_vec())) {
+                            Ok(()) => Ok(data.len()),
+                            Err(_) => Ok(0),
+                        }
+                    })
+                });
+            setup.and_then(|_| transfer.perform())
+        };
+        match result {
+            Ok(()) if !head_sent.get() => {
+                let code = easy.response_code().unwrap_or(status.get());
+                let _ = head_tx.send(Ok((code, headers.take())));
+            }
+            Ok(()) => {}
+            Err(e) if !head_sent.get() => {
+                let _ = head_tx.send(Err(e.into()));
+            }
+            Err(e) => {
+                let _ = body_tx.send(Err(io::Error::other(RequestError::from(e).to_string())));
+            }
+        }
+    });
+
+    let (status_code, headers) = head_rx.recv().map_err(|_| RequestError::Transfer {
+        message: "transfer thread exited before the response started".to_string(),
+    })??;
+    Ok(StreamingResponse {
         status_code,
         headers,
-        body,
-        timing,
+        body: Box::new(ChannelReader {
+            rx: body_rx,
+            chunk: Vec::new(),
+            pos: 0,
+        }),
     })
 }
 
+/// Connect to `authority` (`host:port`) for a `CONNECT` tunnel: libcurl
+/// does the proxy handshake, authentication included, and the connected
+/// socket is handed over. Only a plain-TCP hop to the proxy can be handed
+/// over, so `https://` proxies are refused. The tunnel owns the handle
+/// until it is dropped.
+#[cfg(feature = "curl")]
+pub fn open_tunnel<H>(mut handle: H, config: &RequestConfig, authority: &str) -> Result<Tunnel, RequestError>
+where
+    H: BorrowMut<Easy> + Send + 'static,
+{
+    if resolve_proxy(config).is_some_and(|proxy| proxy.to_ascii_lowercase().starts_with("https://")) {
+        return Err(RequestError::Config {
+            option: "relay".to_string(),
+            message: "CONNECT tunnels through an https:// proxy are not supported".to_string(),
+        });
+    }
+    let mut target = config.clone();
+    target.url = format!("http://{authority}/");
+    let easy = handle.borrow_mut();
+    prepare(easy, &target)?;
+    easy.http_proxy_tunnel(true)?;
+    easy.connect_only(true)?;
+    easy.perform()?;
+    let stream = active_socket(easy)?;
+    Ok(Tunnel::new(stream, handle))
+}
+
+/// The connected socket of a `connect_only` handle, duplicated into a
+/// blocking `TcpStream`. The curl crate has no getter for
+/// `CURLINFO_ACTIVESOCKET`, nor curl-sys a constant for it.
+#[cfg(feature = "curl")]
+fn active_socket(easy: &Easy) -> Result<std::net::TcpStream, RequestError> {
+    const CURLINFO_ACTIVESOCKET: curl_sys::CURLINFO = 0x500000 + 44;
+    let mut socket = curl_sys::CURL_SOCKET_BAD;
+    // SAFETY: CURLINFO_ACTIVESOCKET writes a curl_socket_t
+    let code = unsafe { curl_sys::curl_easy_getinfo(easy.raw(), CURLINFO_ACTIVESOCKET, &mut socket) };
+    if code != curl_sys::CURLE_OK {
+        return Err(curl::Error::new(code).into());
+    }
+    if socket == curl_sys::CURL_SOCKET_BAD {
+        return Err(RequestError::Connect {
+            message: "the tunnel has no connected socket".to_string(),
+        });
+    }
+    // SAFETY: the socket stays open while `easy` holds the connection
+    #[cfg(unix)]
+    let owned = unsafe { std::os::fd::BorrowedFd::borrow_raw(socket) }.try_clone_to_owned()?;
+    #[cfg(windows)]
+    let owned = unsafe { std::os::windows::io::BorrowedSocket::borrow_raw(socket as _) }.try_clone_to_owned()?;
+    let stream = std::net::TcpStream::from(owned);
+    // libcurl left it non-blocking
+    stream.set_nonblocking(false)?;
+    Ok(stream)
+}
+
+/// Status code from an `HTTP/x.y NNN ...` line.
+#[cfg(feature = "curl")]
+fn status_line_code(line: &str) -> Option<u32> {
+    let rest = line.strip_prefix("HTTP/")?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// `Read` over body chunks sent by the transfer thread.
+#[cfg(feature = "curl")]
+struct ChannelReader {
+    rx: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+#[cfg(feature = "curl")]
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.chunk.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                // Sender dropped: transfer finished
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::curl::env::EnvSource;
+    use std::env;
     use std::sync::Mutex;
 
     static ENV_MUTEX: Mutex<()> = Mutex::new(());
@@ -321,6 +1843,14 @@ mod tests {
         assert!(resolve_username(&cfg).is_none());
     }
 
+    #[cfg(feature = "curl")]
+    #[test]
+    fn sub_millisecond_timeouts_round_up() {
+        assert_eq!(whole_millis(Duration::ZERO), Duration::ZERO);
+        assert_eq!(whole_millis(Duration::from_micros(300)), Duration::from_millis(1));
+        assert_eq!(whole_millis(Duration::from_secs(2)), Duration::from_secs(2));
+    }
+
     #[test]
     fn password_from_config() {
         let cfg = RequestConfig::new("https://x.com").password("cfgpass");
@@ -411,11 +1941,82 @@ mod tests {
         assert_eq!(result.unwrap(), "cfghost");
     }
 
+    #[test]
+    fn static_env_replaces_process_env() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        unsafe { env::set_var("RUSTCURL_USER", "envuser") };
+        let cfg = RequestConfig::new("https://x.com").env(EnvSource::from_pairs([
+            ("RUSTCURL_USER", "tenant"),
+            ("HTTPS_PROXY", "http://tenant-proxy:3128"),
+        ]));
+        assert_eq!(resolve_username(&cfg).as_deref(), Some("tenant"));
+        assert_eq!(resolve_proxy(&cfg).as_deref(), Some("http://tenant-proxy:3128"));
+        assert_eq!(resolve_password(&cfg), None);
+        unsafe { env::remove_var("RUSTCURL_USER") };
+    }
+
+    #[test]
+    fn disabled_env_sees_nothing() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        unsafe { env::set_var("HTTPS_PROXY", "http://envproxy:3128") };
+        let cfg = RequestConfig::new("https://x.com").env(EnvSource::Disabled);
+        assert_eq!(resolve_proxy(&cfg), None);
+        assert_eq!(resolve_noproxy(&cfg), None);
+        unsafe { env::remove_var("HTTPS_PROXY") };
+    }
+
+    #[cfg(feature = "curl")]
+    #[test]
+    fn handshake_from_info_messages() {
+        let mut handshake = Handshake::default();
+        for text in [
+            "  Trying 93.184.215.14:443...\n",
+            "SSL connection using TLSv1.3 / TLS_AES_256_GCM_SHA384 / X25519 / RSASSA-PSS\n",
+            "ALPN: server accepted h2\n",
+        ] {
+            handshake.note(text);
+        }
+        assert_eq!(
+            handshake,
+            Handshake {
+                tls_version: Some("TLSv1.3".into()),
+                tls_cipher: Some("TLS_AES_256_GCM_SHA384".into()),
+                alpn: Some("h2".into()),
+                ..Handshake::default()
+            }
+        );
+
+        // Older libcurl wording
+        let mut handshake = Handshake::default();
+        handshake.note("SSL connection using TLSv1.2 / ECDHE-RSA-AES128-GCM-SHA256");
+        handshake.note("ALPN, server accepted to use http/1.1");
+        assert_eq!(handshake.tls_cipher.as_deref(), Some("ECDHE-RSA-AES128-GCM-SHA256"));
+        assert_eq!(handshake.alpn.as_deref(), Some("http/1.1"));
+
+        let mut handshake = Handshake::default();
+        handshake.note("SSL reusing session with ALPN 'h2'\n");
+        handshake.note("Server accepted 78 bytes of TLS early data.\n");
+        assert!(handshake.resumed);
+        assert_eq!(handshake.early_data, Some(78));
+        handshake.note("Server rejected TLS early data.");
+        assert_eq!(handshake.early_data, Some(0));
+        handshake.note("ECH: result: status is succeeded, inner is x.com, outer is public.example");
+        assert_eq!(handshake.ech.as_deref(), Some("succeeded"));
+    }
+
+    #[cfg(feature = "curl")]
+    #[test]
+    fn status_line_code_parsing() {
+        assert_eq!(status_line_code("HTTP/1.1 200 OK"), Some(200));
+        assert_eq!(status_line_code("HTTP/2 404"), Some(404));
+        assert_eq!(status_line_code("Content-Type: text/html"), None);
+    }
+
     #[test]
     #[ignore = "requires network access"]
     fn integration_get_request() {
         let cfg = RequestConfig::new("https://httpbin.org/get");
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
         assert!(!resp.headers.is_empty());
         assert!(resp.body_string().contains("httpbin.org"));
@@ -425,7 +2026,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_insecure_request() {
         let cfg = RequestConfig::new("https://httpbin.org/get").insecure(true);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
     }
 
@@ -433,7 +2034,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_headers_captured() {
         let cfg = RequestConfig::new("https://httpbin.org/get");
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert!(resp.get_header("content-type").is_some());
     }
 
@@ -441,7 +2042,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_follow_redirect() {
         let cfg = RequestConfig::new("http://httpbin.org/redirect/1");
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
     }
 
@@ -452,7 +2053,7 @@ mod tests {
             .method(Method::Post)
             .data("{\"key\":\"value\"}")
             .header("Content-Type: application/json");
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
         assert!(resp.body_string().contains("key"));
     }
@@ -461,7 +2062,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_head_request() {
         let cfg = RequestConfig::new("https://httpbin.org/get").head_only(true);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
         assert!(resp.body.is_empty());
     }
@@ -470,7 +2071,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_timing() {
         let cfg = RequestConfig::new("https://httpbin.org/get").show_timing(true);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert!(resp.timing.is_some());
         let t = resp.timing.unwrap();
         assert!(t.total > Duration::ZERO);
@@ -480,7 +2081,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_compressed() {
         let cfg = RequestConfig::new("https://httpbin.org/gzip").compressed(true);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
         assert!(resp.body_string().contains("gzipped"));
     }
@@ -490,7 +2091,7 @@ mod tests {
     fn integration_custom_user_agent() {
         let cfg = RequestConfig::new("https://httpbin.org/user-agent")
             .user_agent("rustcurl-test/0.1");
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert!(resp.body_string().contains("rustcurl-test/0.1"));
     }
 
@@ -499,7 +2100,7 @@ mod tests {
     fn integration_output_to_file() {
         let path = "/tmp/rustcurl_test_output.html";
         let cfg = RequestConfig::new("https://httpbin.org/html").output(path);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert!(resp.body.is_empty());
         let content = fs::read_to_string(path).unwrap();
         assert!(content.contains("html"));
@@ -510,7 +2111,7 @@ mod tests {
     #[ignore = "requires network access"]
     fn integration_max_redirs() {
         let cfg = RequestConfig::new("https://httpbin.org/redirect/3").max_redirs(5);
-        let resp = perform_request(&cfg).unwrap();
+        let resp = perform_request(&mut Easy::new(), &cfg).unwrap();
         assert_eq!(resp.status_code, 200);
     }
 }
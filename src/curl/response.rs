@@ -1,8 +1,18 @@
 // src/curl/response.rs
 
+use std::borrow::Cow;
 use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
 use std::time::Duration;
 
+use super::config::split_header;
+use super::error::RequestError;
+use super::push::Pushed;
+use super::ranges::{self, ByteRange};
+use super::warning::Warning;
+
 #[derive(Debug, Clone)]
 pub struct Timing {
     pub dns: Duration,
@@ -11,6 +21,25 @@ pub struct Timing {
     pub starttransfer: Duration,
     pub total: Duration,
     pub redirect: Duration,
+    /// Whether the TLS handshake resumed a cached session; `None` when no
+    /// handshake was made or the backend cannot tell.
+    pub tls_resumed: Option<bool>,
+    /// Bytes of TLS early (0-RTT) data the server accepted, `Some(0)` when
+    /// it rejected them; `None` when none were sent.
+    pub early_data: Option<u64>,
+    /// Each redirect followed and then the final response, with how long
+    /// it took to arrive; empty when the backend cannot tell.
+    pub hops: Vec<HopTiming>,
+}
+
+/// One response on the way to the final one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HopTiming {
+    pub status: u32,
+    /// URL requested for this hop.
+    pub url: String,
+    /// From the previous hop's status line (or the start) to this one's.
+    pub elapsed: Duration,
 }
 
 impl fmt::Display for Timing {
@@ -18,19 +47,192 @@ impl fmt::Display for Timing {
         writeln!(f, "Timing:")?;
         writeln!(f, "  DNS lookup:    {:>8.3}ms", self.dns.as_secs_f64() * 1000.0)?;
         writeln!(f, "  Connect:       {:>8.3}ms", self.connect.as_secs_f64() * 1000.0)?;
-        writeln!(f, "  TLS handshake: {:>8.3}ms", self.tls.as_secs_f64() * 1000.0)?;
+        write!(f, "  TLS handshake: {:>8.3}ms", self.tls.as_secs_f64() * 1000.0)?;
+        match (self.tls_resumed, self.early_data) {
+            (Some(true), Some(0)) => writeln!(f, " (resumed, early data rejected)")?,
+            (Some(true), Some(bytes)) => writeln!(f, " (resumed, {bytes} bytes of early data)")?,
+            (Some(true), None) => writeln!(f, " (resumed)")?,
+            (Some(false), _) => writeln!(f, " (full)")?,
+            (None, _) => writeln!(f)?,
+        }
         writeln!(f, "  First byte:    {:>8.3}ms", self.starttransfer.as_secs_f64() * 1000.0)?;
         writeln!(f, "  Redirect:      {:>8.3}ms", self.redirect.as_secs_f64() * 1000.0)?;
+        if self.hops.len() > 1 {
+            for hop in &self.hops {
+                writeln!(f, "    {} {:>8.3}ms  {}", hop.status, hop.elapsed.as_secs_f64() * 1000.0, hop.url)?;
+            }
+        }
         write!(f, "  Total:         {:>8.3}ms", self.total.as_secs_f64() * 1000.0)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Response {
     pub status_code: u32,
     pub headers: Vec<String>,
     pub body: Vec<u8>,
     pub timing: Option<Timing>,
+    /// Status of every attempt made under `--retry`, in order; 0 marks a
+    /// transfer that failed without a response.
+    pub attempts: Vec<u32>,
+    /// 1xx responses received before the final one (100 Continue,
+    /// 103 Early Hints, ...). Empty for backends that hide them (reqwest).
+    pub informational: Vec<Interim>,
+    /// DER of the server's certificate chain, leaf first; only collected
+    /// for `--provenance` and `--show-certs`, and only by backends that can
+    /// report it (reqwest reports the leaf alone).
+    pub peer_certificates: Vec<Vec<u8>>,
+    /// File the body was written to by `-o` or `-O`, set by `Client::send`.
+    pub saved_as: Option<PathBuf>,
+    /// HTTP version the response came over: `1.0`, `1.1`, `2` or `3`, as
+    /// `--http2` and ALPN settled it; `None` if the backend cannot tell.
+    pub http_version: Option<String>,
+    /// Transfer details for `-w`, from backends that report them.
+    pub transfer: Option<TransferInfo>,
+    /// Problems that did not fail the request, in the order noticed.
+    pub warnings: Vec<Warning>,
+}
+
+/// What the backend knows about a finished transfer beyond its timing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferInfo {
+    /// URL of the last request, after redirects.
+    pub url_effective: String,
+    pub size_download: u64,
+    pub size_upload: u64,
+    /// Bytes of all received header blocks.
+    pub size_header: u64,
+    pub num_redirects: u32,
+    pub remote_ip: Option<String>,
+    pub remote_port: Option<u16>,
+    pub local_ip: Option<String>,
+    pub local_port: Option<u16>,
+    /// New connections opened; 0 when an earlier one was reused.
+    pub num_connects: Option<u32>,
+    /// e.g. `TLSv1.3`; `None` without TLS or when the backend cannot tell.
+    pub tls_version: Option<String>,
+    pub tls_cipher: Option<String>,
+    /// Protocol agreed through ALPN, e.g. `h2`.
+    pub alpn: Option<String>,
+    /// The TLS library's certificate verification result; 0 is success.
+    pub ssl_verify_result: Option<i64>,
+    /// How Encrypted Client Hello went (`succeeded`, `failed`, `GREASE`,
+    /// `not attempted`, ...), as the TLS library reports it.
+    pub ech: Option<String>,
+    /// HTTP/2 pushes accepted with `--accept-push`.
+    pub pushed: Vec<Pushed>,
+}
+
+impl TransferInfo {
+    /// Whether the request went over a connection left open by an earlier
+    /// one, when the backend can tell.
+    pub fn connection_reused(&self) -> Option<bool> {
+        self.num_connects.map(|n| n == 0)
+    }
+}
+
+/// A 1xx informational response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interim {
+    pub status_code: u32,
+    pub headers: Vec<String>,
+}
+
+impl Interim {
+    /// Targets of `Link` headers with `rel=preload`, as sent in
+    /// 103 Early Hints, e.g. `</style.css>; rel=preload; as=style`.
+    pub fn preload_links(&self) -> Vec<String> {
+        header_map(&self.headers)
+            .into_iter()
+            .filter(|(name, _)| name == "link")
+            .flat_map(|(_, value)| {
+                value
+                    .split(',')
+                    .filter_map(|link| {
+                        let mut parts = link.split(';').map(str::trim);
+                        let target = parts.next()?.strip_prefix('<')?.strip_suffix('>')?;
+                        parts
+                            .any(|p| {
+                                p.split_once('=').is_some_and(|(k, v)| {
+                                    k.trim().eq_ignore_ascii_case("rel")
+                                        && v.trim_matches('"')
+                                            .split_whitespace()
+                                            .any(|r| r.eq_ignore_ascii_case("preload"))
+                                })
+                            })
+                            .then(|| target.to_string())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Per-hop timing for an exchange that started at `url`, given when each
+/// status line in `headers` arrived (`arrivals`, in the same order). The
+/// hops are the redirects followed and the final response; 1xx and proxy
+/// `CONNECT` replies count toward the hop they precede.
+pub fn hop_timings(url: &str, headers: &[String], arrivals: &[Duration]) -> Vec<HopTiming> {
+    let mut blocks: Vec<(u32, Option<String>)> = Vec::new();
+    for line in headers {
+        if let Some(code) = line.strip_prefix("HTTP/").and_then(|l| l.split_whitespace().nth(1)?.parse().ok()) {
+            blocks.push((code, None));
+        } else if let Some((name, value)) = split_header(line)
+            && name.eq_ignore_ascii_case("location")
+            && let Some(block) = blocks.last_mut()
+        {
+            block.1 = Some(value.trim().to_string());
+        }
+    }
+    if blocks.len() != arrivals.len() {
+        return Vec::new();
+    }
+    let last = blocks.len().saturating_sub(1);
+    let mut hops = Vec::new();
+    let mut current = url.to_string();
+    let mut previous = Duration::ZERO;
+    for (i, ((status, location), arrived)) in blocks.into_iter().zip(arrivals).enumerate() {
+        let redirect = (300..400).contains(&status) && location.is_some();
+        if !redirect && i != last {
+            continue;
+        }
+        hops.push(HopTiming {
+            status,
+            url: current.clone(),
+            elapsed: arrived.saturating_sub(previous),
+        });
+        previous = *arrived;
+        if let Some(location) = location.filter(|_| redirect) {
+            current = super::url::join(&current, &location);
+        }
+    }
+    hops
+}
+
+/// Split the header lines of a whole exchange into its 1xx responses.
+/// Lines belong to the most recent `HTTP/x NNN` status line.
+pub fn informational_responses(headers: &[String]) -> Vec<Interim> {
+    let mut interim = Vec::new();
+    let mut current: Option<Interim> = None;
+    for line in headers {
+        if let Some(code) = line
+            .strip_prefix("HTTP/")
+            .and_then(|rest| rest.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u32>().ok())
+        {
+            interim.extend(current.take());
+            if (100..200).contains(&code) {
+                current = Some(Interim {
+                    status_code: code,
+                    headers: Vec::new(),
+                });
+            }
+        } else if let Some(ref mut block) = current {
+            block.headers.push(line.clone());
+        }
+    }
+    interim.extend(current);
+    interim
 }
 
 impl Response {
@@ -38,24 +240,166 @@ impl Response {
         String::from_utf8_lossy(&self.body).to_string()
     }
 
+    /// The body as received: the buffered body, or the contents of the
+    /// file it was saved to.
+    pub fn received_body(&self) -> Cow<'_, [u8]> {
+        match self.saved_as {
+            Some(ref path) if self.body.is_empty() => Cow::Owned(fs::read(path).unwrap_or_default()),
+            _ => Cow::Borrowed(&self.body),
+        }
+    }
+
+    /// Number of retries it took to get this response.
+    pub fn num_retries(&self) -> usize {
+        self.attempts.len().saturating_sub(1)
+    }
+
+    /// Parts of a `206` reply sent as `multipart/byteranges`; `None` for
+    /// any other response or a malformed multipart body.
+    pub fn byte_ranges(&self) -> Option<Vec<ByteRange>> {
+        if self.status_code != 206 {
+            return None;
+        }
+        let boundary = ranges::boundary(&self.get_header("content-type")?)?;
+        ranges::parse_multipart(&boundary, &self.body)
+    }
+
     #[allow(dead_code)]
     pub fn header_map(&self) -> Vec<(String, String)> {
-        self.headers
-            .iter()
-            .filter_map(|h| {
-                let (key, val) = h.split_once(':')?;
-                Some((key.trim().to_lowercase(), val.trim().to_string()))
-            })
-            .collect()
+        header_map(&self.headers)
     }
 
     #[allow(dead_code)]
     pub fn get_header(&self, name: &str) -> Option<String> {
-        let name_lower = name.to_lowercase();
-        self.header_map()
-            .into_iter()
-            .find(|(k, _)| *k == name_lower)
-            .map(|(_, v)| v)
+        get_header(&self.headers, name)
+    }
+}
+
+fn header_map(headers: &[String]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|h| {
+            let (key, val) = split_header(h)?;
+            Some((key.to_lowercase(), val.to_string()))
+        })
+        .collect()
+}
+
+fn get_header(headers: &[String], name: &str) -> Option<String> {
+    let name_lower = name.to_lowercase();
+    header_map(headers)
+        .into_iter()
+        .find(|(k, _)| *k == name_lower)
+        .map(|(_, v)| v)
+}
+
+/// Outcome of a transfer whose body went to a caller-supplied writer.
+#[derive(Debug, Clone)]
+pub struct TransferSummary {
+    pub status_code: u32,
+    pub headers: Vec<String>,
+    /// Body bytes written to the sink.
+    pub bytes: u64,
+    /// Phase timings, when the backend can report them.
+    pub timing: Option<Timing>,
+}
+
+impl TransferSummary {
+    pub fn get_header(&self, name: &str) -> Option<String> {
+        get_header(&self.headers, name)
+    }
+}
+
+/// Results of a batch of concurrent transfers, in request order.
+#[derive(Debug)]
+pub struct ParallelOutcome {
+    pub responses: Vec<Result<Response, RequestError>>,
+    /// Distinct connections the batch used, when the backend can tell.
+    pub connections: Option<usize>,
+}
+
+impl ParallelOutcome {
+    /// Every request failed with the same error before any transfer ran.
+    pub fn failed(count: usize, err: RequestError) -> Self {
+        let message = err.to_string();
+        Self {
+            responses: (0..count)
+                .map(|_| Err(RequestError::Transfer { message: message.clone() }))
+                .collect(),
+            connections: None,
+        }
+    }
+
+    /// Average completed transfers per connection; above 1 means
+    /// connections were reused or multiplexed.
+    pub fn streams_per_connection(&self) -> Option<f64> {
+        let ok = self.responses.iter().filter(|r| r.is_ok()).count();
+        match self.connections {
+            Some(n) if n > 0 => Some(ok as f64 / n as f64),
+            _ => None,
+        }
+    }
+
+    /// One-line summary, e.g. `3 transfers, 3 ok, 1 connection (3.0 streams per connection)`.
+    pub fn summary(&self) -> String {
+        let total = self.responses.len();
+        let ok = self.responses.iter().filter(|r| r.is_ok()).count();
+        let transfers = if total == 1 { "transfer" } else { "transfers" };
+        match (self.connections, self.streams_per_connection()) {
+            (Some(n), Some(spc)) => {
+                let conns = if n == 1 { "connection" } else { "connections" };
+                format!("{total} {transfers}, {ok} ok, {n} {conns} ({spc:.1} streams per connection)")
+            }
+            _ => format!("{total} {transfers}, {ok} ok"),
+        }
+    }
+}
+
+/// Response whose status and headers are available as soon as they are
+/// received; the body is read incrementally through `Read`.
+pub struct StreamingResponse {
+    pub status_code: u32,
+    pub headers: Vec<String>,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl StreamingResponse {
+    pub fn get_header(&self, name: &str) -> Option<String> {
+        get_header(&self.headers, name)
+    }
+
+    /// Read the rest of the body into a buffered `Response`.
+    pub fn into_response(mut self) -> io::Result<Response> {
+        let mut body = Vec::new();
+        self.body.read_to_end(&mut body)?;
+        Ok(Response {
+            status_code: self.status_code,
+            headers: self.headers,
+            body,
+            timing: None,
+            attempts: Vec::new(),
+            informational: Vec::new(),
+            peer_certificates: Vec::new(),
+            saved_as: None,
+            http_version: None,
+            transfer: None,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl Read for StreamingResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+impl fmt::Debug for StreamingResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingResponse")
+            .field("status_code", &self.status_code)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
     }
 }
 
@@ -67,7 +411,18 @@ impl fmt::Display for Response {
             writeln!(f, "{header}")?;
         }
         writeln!(f)?;
-        write!(f, "{}", self.body_string())?;
+        match self.byte_ranges() {
+            Some(parts) => match ranges::reassemble(&parts) {
+                Some(body) => write!(f, "{}", String::from_utf8_lossy(&body))?,
+                None => {
+                    for part in &parts {
+                        writeln!(f, "--- {} ({} bytes)", part.content_range(), part.body.len())?;
+                        writeln!(f, "{}", String::from_utf8_lossy(&part.body))?;
+                    }
+                }
+            },
+            None => write!(f, "{}", self.body_string())?,
+        }
         if let Some(ref timing) = self.timing {
             writeln!(f)?;
             writeln!(f)?;
@@ -77,17 +432,26 @@ impl fmt::Display for Response {
     }
 }
 
+#[cfg(test)]
+impl Response {
+    /// A response for unit tests; set anything else with
+    /// `Response { field, ..Response::fixture(..) }`.
+    pub(crate) fn fixture(status_code: u32, headers: &[&str], body: &[u8]) -> Response {
+        Response {
+            status_code,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            body: body.to_vec(),
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn make_response(headers: Vec<&str>, body: &[u8]) -> Response {
-        Response {
-            status_code: 200,
-            headers: headers.into_iter().map(String::from).collect(),
-            body: body.to_vec(),
-            timing: None,
-        }
+        Response::fixture(200, &headers, body)
     }
 
     #[test]
@@ -100,9 +464,8 @@ mod tests {
     fn body_invalid_utf8() {
         let resp = Response {
             status_code: 200,
-            headers: vec![],
             body: vec![0xFF, 0xFE, 0x48, 0x65, 0x6C, 0x6C, 0x6F],
-            timing: None,
+            ..Default::default()
         };
         assert!(resp.body_string().contains("Hello"));
     }
@@ -111,9 +474,7 @@ mod tests {
     fn body_empty() {
         let resp = Response {
             status_code: 204,
-            headers: vec![],
-            body: vec![],
-            timing: None,
+            ..Default::default()
         };
         assert_eq!(resp.body_string(), "");
     }
@@ -172,19 +533,64 @@ mod tests {
             starttransfer: Duration::from_millis(50),
             total: Duration::from_millis(100),
             redirect: Duration::from_millis(0),
+            tls_resumed: Some(true),
+            early_data: Some(78),
+            hops: Vec::new(),
         };
         let output = format!("{timing}");
+        assert!(output.contains("20.000ms (resumed, 78 bytes of early data)\n"));
         assert!(output.contains("DNS lookup:"));
         assert!(output.contains("5.000ms"));
         assert!(output.contains("Total:"));
         assert!(output.contains("100.000ms"));
     }
 
+    #[test]
+    fn hop_timings_follow_redirects() {
+        let headers: Vec<String> = [
+            "HTTP/1.1 200 Connection established",
+            "HTTP/1.1 301 Moved Permanently",
+            "Location: https://x.com/b",
+            "HTTP/1.1 302 Found",
+            "Location: /login",
+            "HTTP/1.1 100 Continue",
+            "HTTP/1.1 200 OK",
+            "Content-Type: text/html",
+        ]
+        .map(String::from)
+        .to_vec();
+        let ms = Duration::from_millis;
+        let hops = hop_timings("http://x.com/a", &headers, &[ms(10), ms(40), ms(100), ms(110), ms(130)]);
+        let summary: Vec<_> = hops.iter().map(|h| (h.status, h.url.as_str(), h.elapsed)).collect();
+        assert_eq!(
+            summary,
+            [
+                (301, "http://x.com/a", ms(40)),
+                (302, "https://x.com/b", ms(60)),
+                (200, "https://x.com/login", ms(30)),
+            ]
+        );
+        // Arrival times that do not line up with the headers are ignored
+        assert!(hop_timings("http://x.com/a", &headers, &[ms(1)]).is_empty());
+
+        let timing = Timing {
+            dns: ms(0),
+            connect: ms(0),
+            tls: ms(0),
+            starttransfer: ms(0),
+            total: ms(130),
+            redirect: ms(100),
+            tls_resumed: None,
+            early_data: None,
+            hops,
+        };
+        assert!(timing.to_string().contains("    302   60.000ms  https://x.com/b\n"));
+    }
+
     #[test]
     fn display_with_timing() {
         let resp = Response {
             status_code: 200,
-            headers: vec![],
             body: b"ok".to_vec(),
             timing: Some(Timing {
                 dns: Duration::from_millis(1),
@@ -193,7 +599,11 @@ mod tests {
                 starttransfer: Duration::from_millis(4),
                 total: Duration::from_millis(5),
                 redirect: Duration::from_millis(0),
+                tls_resumed: None,
+                early_data: None,
+                hops: Vec::new(),
             }),
+            ..Default::default()
         };
         let output = format!("{resp}");
         assert!(output.contains("Timing:"));
@@ -206,4 +616,61 @@ mod tests {
         let output = format!("{resp}");
         assert!(!output.contains("Timing:"));
     }
+
+    #[test]
+    fn streaming_response_reads_and_buffers() {
+        let mut resp = StreamingResponse {
+            status_code: 200,
+            headers: vec!["Content-Type: text/plain".into()],
+            body: Box::new(io::Cursor::new(b"hello world".to_vec())),
+        };
+        assert_eq!(resp.get_header("content-type").as_deref(), Some("text/plain"));
+        let mut first = [0u8; 5];
+        resp.read_exact(&mut first).unwrap();
+        assert_eq!(&first, b"hello");
+        let rest = resp.into_response().unwrap();
+        assert_eq!(rest.body, b" world");
+    }
+
+    #[test]
+    fn parallel_summary() {
+        let outcome = ParallelOutcome {
+            responses: vec![Ok(make_response(vec![], b"")), Ok(make_response(vec![], b""))],
+            connections: Some(1),
+        };
+        assert_eq!(outcome.streams_per_connection(), Some(2.0));
+        assert_eq!(outcome.summary(), "2 transfers, 2 ok, 1 connection (2.0 streams per connection)");
+
+        let failed = ParallelOutcome::failed(1, RequestError::Transfer { message: "x".into() });
+        assert_eq!(failed.summary(), "1 transfer, 0 ok");
+    }
+
+    #[test]
+    fn informational_blocks_are_split_out() {
+        let headers: Vec<String> = [
+            "HTTP/1.1 100 Continue",
+            "HTTP/1.1 103 Early Hints",
+            "Link: </style.css>; rel=preload; as=style, </app.js>; rel=\"preload modulepreload\"",
+            "Link: </other>; rel=next",
+            "HTTP/1.1 200 OK",
+            "Content-Type: text/html",
+        ]
+        .map(String::from)
+        .to_vec();
+        let interim = informational_responses(&headers);
+        assert_eq!(interim.len(), 2);
+        assert_eq!(interim[0].status_code, 100);
+        assert!(interim[0].headers.is_empty());
+        assert_eq!(interim[1].status_code, 103);
+        assert_eq!(interim[1].headers.len(), 2);
+        assert_eq!(interim[1].preload_links(), vec!["/style.css", "/app.js"]);
+    }
+
+    #[test]
+    fn no_informational_for_plain_or_redirected_exchanges() {
+        let headers: Vec<String> = ["HTTP/1.1 301 Moved", "Location: /x", "HTTP/2 200", "A: b"]
+            .map(String::from)
+            .to_vec();
+        assert!(informational_responses(&headers).is_empty());
+    }
 }
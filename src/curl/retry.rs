@@ -0,0 +1,261 @@
+// src/curl/retry.rs
+
+//! `--retry` handling shared by all backends.
+
+use std::time::Duration;
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::response::Response;
+use super::rng::Rng;
+
+/// Longest backoff between attempts when no `--retry-delay` is given.
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Extra random share added to each backoff step.
+const JITTER: f64 = 0.25;
+
+/// Exit status when `--retry` ran out with the last attempt still failing
+/// transiently, so scripts can tell a flaky server from a broken request
+/// (`EX_TEMPFAIL`).
+pub const EXIT_RETRIES_EXHAUSTED: i32 = 75;
+
+/// HTTP statuses curl treats as transient for `--retry`.
+pub fn is_retryable_status(status: u32) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Delay before retry number `retry` (0-based): `--retry-delay` if set,
+/// otherwise the server's `Retry-After` seconds, otherwise exponential
+/// backoff starting at one second with up to 25% jitter.
+fn delay_for(config: &RequestConfig, retry: u32, response: Option<&Response>, rng: &mut Rng) -> Duration {
+    if let Some(d) = config.retry_delay {
+        return d;
+    }
+    if let Some(secs) = response
+        .and_then(|r| r.get_header("retry-after"))
+        .and_then(|v| v.parse::<u64>().ok())
+    {
+        return Duration::from_secs(secs).min(MAX_BACKOFF);
+    }
+    let base = Duration::from_secs(1u64 << retry.min(10));
+    rng.jitter(base, JITTER).min(MAX_BACKOFF)
+}
+
+/// Run `perform` up to `1 + config.retry` times, retrying transient
+/// failures. Every attempt's status (0 for a failed transfer) is recorded
+/// in `Response::attempts`, or in `RequestError::Retried` when the last
+/// attempt failed after at least one retry.
+pub fn perform_with_retry<F>(config: &RequestConfig, mut perform: F) -> Result<Response, RequestError>
+where
+    F: FnMut(&RequestConfig) -> Result<Response, RequestError>,
+{
+    let mut rng = Rng::new(config.seed);
+    let mut attempts = Vec::new();
+    let mut retry = 0;
+    loop {
+        let result = perform(config);
+        let retryable = match &result {
+            Ok(resp) => is_retryable_status(resp.status_code),
+            Err(e) => e.is_transient(),
+        };
+        attempts.push(result.as_ref().map_or(0, |r| r.status_code));
+
+        if !retryable || retry >= config.retry {
+            return match result {
+                Ok(resp) => Ok(Response { attempts, ..resp }),
+                Err(last) if attempts.len() > 1 => Err(RequestError::Retried {
+                    attempts,
+                    last: Box::new(last),
+                }),
+                Err(last) => Err(last),
+            };
+        }
+
+        let delay = delay_for(config, retry, result.as_ref().ok(), &mut rng);
+        if config.verbose {
+            let what = match &result {
+                Ok(resp) => format!("HTTP {}", resp.status_code),
+                Err(e) => e.to_string(),
+            };
+            config.note(format_args!(
+                "Transient problem: {what}. Will retry in {:.1}s ({} retries left)",
+                delay.as_secs_f64(),
+                config.retry - retry
+            ));
+        }
+        std::thread::sleep(delay);
+        retry += 1;
+    }
+}
+
+/// Whether `response` still has a retryable status after `config.retry`
+/// retries were spent on it.
+pub fn exhausted(config: &RequestConfig, response: &Response) -> bool {
+    config.retry > 0 && response.num_retries() >= config.retry as usize && is_retryable_status(response.status_code)
+}
+
+/// One-line summary for `--retry-report`, e.g.
+/// `Retry report: succeeded after 2 retries (attempts: 503 503 200)`.
+pub fn retry_report(attempts: &[u32]) -> String {
+    let retries = attempts.len().saturating_sub(1);
+    let codes = attempts
+        .iter()
+        .map(|c| if *c == 0 { "error".to_string() } else { c.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let outcome = match attempts.last() {
+        Some(&c) if c != 0 && !is_retryable_status(c) => "succeeded",
+        _ => "failed",
+    };
+    match retries {
+        0 => format!("Retry report: {outcome} on first attempt (attempts: {codes})"),
+        1 => format!("Retry report: {outcome} after 1 retry (attempts: {codes})"),
+        n => format!("Retry report: {outcome} after {n} retries (attempts: {codes})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::error::TimeoutPhase;
+
+    fn response(status: u32) -> Response {
+        Response {
+            status_code: status,
+            ..Default::default()
+        }
+    }
+
+    fn config(retries: u32) -> RequestConfig {
+        RequestConfig::new("https://x.com")
+            .retry(retries)
+            .retry_delay(Duration::ZERO)
+    }
+
+    #[test]
+    fn no_retry_on_success() {
+        let mut calls = 0;
+        let resp = perform_with_retry(&config(3), |_| {
+            calls += 1;
+            Ok(response(200))
+        })
+        .unwrap();
+        assert_eq!(calls, 1);
+        assert_eq!(resp.attempts, vec![200]);
+        assert_eq!(resp.num_retries(), 0);
+    }
+
+    #[test]
+    fn retries_transient_status_until_success() {
+        let mut statuses = vec![503, 429, 200].into_iter();
+        let resp = perform_with_retry(&config(5), |_| Ok(response(statuses.next().unwrap()))).unwrap();
+        assert_eq!(resp.attempts, vec![503, 429, 200]);
+        assert_eq!(resp.num_retries(), 2);
+    }
+
+    #[test]
+    fn stops_when_budget_exhausted() {
+        let mut calls = 0;
+        let resp = perform_with_retry(&config(2), |_| {
+            calls += 1;
+            Ok(response(503))
+        })
+        .unwrap();
+        assert_eq!(calls, 3);
+        assert_eq!(resp.attempts, vec![503, 503, 503]);
+        assert!(exhausted(&config(2), &resp));
+        assert!(!exhausted(&config(2), &response(503)));
+        assert!(!exhausted(&config(0), &response(503)));
+    }
+
+    #[test]
+    fn failures_after_retries_keep_the_attempts() {
+        let timeout = || RequestError::Timeout {
+            phase: TimeoutPhase::Total,
+            message: "timed out".into(),
+        };
+        let mut statuses = vec![Ok(response(503)), Err(timeout()), Err(timeout())].into_iter();
+        let err = perform_with_retry(&config(2), |_| statuses.next().unwrap()).unwrap_err();
+        assert_eq!(err.attempts(), Some(&[503, 0, 0][..]));
+        assert_eq!((err.code(), err.exit_code()), ("timeout", EXIT_RETRIES_EXHAUSTED));
+        assert!(err.to_string().ends_with("(after 2 retries)"), "{err}");
+
+        let refused = perform_with_retry(&config(2), |_| Err(RequestError::Connect { message: "refused".into() }));
+        assert_eq!(refused.unwrap_err().attempts(), None);
+    }
+
+    #[test]
+    fn connection_refused_not_retried() {
+        let mut calls = 0;
+        let result = perform_with_retry(&config(3), |_| {
+            calls += 1;
+            Err(RequestError::Connect { message: "refused".into() })
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn transient_error_then_success() {
+        let mut calls = 0;
+        let resp = perform_with_retry(&config(3), |_| {
+            calls += 1;
+            if calls == 1 {
+                Err(RequestError::Timeout {
+                    phase: TimeoutPhase::Total,
+                    message: "timed out".into(),
+                })
+            } else {
+                Ok(response(200))
+            }
+        })
+        .unwrap();
+        assert_eq!(resp.attempts, vec![0, 200]);
+    }
+
+    #[test]
+    fn report_wording() {
+        assert_eq!(
+            retry_report(&[200]),
+            "Retry report: succeeded on first attempt (attempts: 200)"
+        );
+        assert_eq!(
+            retry_report(&[0, 503, 200]),
+            "Retry report: succeeded after 2 retries (attempts: error 503 200)"
+        );
+        assert_eq!(
+            retry_report(&[503, 503]),
+            "Retry report: failed after 1 retry (attempts: 503 503)"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_without_delay() {
+        let cfg = RequestConfig::new("https://x.com");
+        let mut rng = Rng::new(None);
+        let first = delay_for(&cfg, 0, None, &mut rng);
+        assert!(first >= Duration::from_secs(1) && first <= Duration::from_millis(1250));
+        let fourth = delay_for(&cfg, 3, None, &mut rng);
+        assert!(fourth >= Duration::from_secs(8) && fourth <= Duration::from_secs(10));
+        assert_eq!(delay_for(&cfg, 30, None, &mut rng), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn seeded_backoff_is_reproducible() {
+        let cfg = RequestConfig::new("https://x.com").seed(99);
+        let run = || {
+            let mut rng = Rng::new(cfg.seed);
+            (0..5).map(|n| delay_for(&cfg, n, None, &mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn backoff_honors_retry_after() {
+        let cfg = RequestConfig::new("https://x.com");
+        let mut resp = response(503);
+        resp.headers.push("Retry-After: 7".into());
+        assert_eq!(delay_for(&cfg, 0, Some(&resp), &mut Rng::new(None)), Duration::from_secs(7));
+    }
+}
@@ -0,0 +1,217 @@
+// src/curl/secret.rs
+
+//! `--secret NAME=PROVIDER:REF`: values fetched when the command runs and
+//! referenced as `{{secret.NAME}}` in the URL, headers, body, form fields
+//! and credentials, so tokens stay out of shell history. Providers are
+//! `env:VAR`, `file:PATH`, `vault:PATH#FIELD` (the `vault` CLI, with its
+//! usual `VAULT_ADDR` and `VAULT_TOKEN`) and `aws-sm:ID[#FIELD]` (the
+//! `aws` CLI; FIELD picks a key from a JSON secret).
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use serde_json::Value;
+
+use super::config::RequestConfig;
+use super::env::EnvSource;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provider {
+    Env(String),
+    File(String),
+    Vault { path: String, field: String },
+    AwsSm { id: String, field: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Secret {
+    pub name: String,
+    pub provider: Provider,
+}
+
+impl Secret {
+    /// Parse `NAME=PROVIDER:REF`.
+    pub fn parse(spec: &str) -> Result<Secret, String> {
+        let (name, source) = spec.split_once('=').ok_or("expected NAME=PROVIDER:REF")?;
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(format!("bad secret name '{name}'"));
+        }
+        let (provider, reference) = source.split_once(':').ok_or("expected NAME=PROVIDER:REF")?;
+        if reference.is_empty() {
+            return Err(format!("{provider}: empty reference"));
+        }
+        let (path, field) = match reference.split_once('#') {
+            Some((path, field)) => (path.to_string(), Some(field.to_string())),
+            None => (reference.to_string(), None),
+        };
+        let provider = match provider {
+            "env" => Provider::Env(reference.to_string()),
+            "file" => Provider::File(reference.to_string()),
+            "vault" => Provider::Vault {
+                path,
+                field: field.ok_or("vault: expected PATH#FIELD")?,
+            },
+            "aws-sm" => Provider::AwsSm { id: path, field },
+            other => return Err(format!("unknown provider '{other}' (env, file, vault, aws-sm)")),
+        };
+        Ok(Secret {
+            name: name.to_string(),
+            provider,
+        })
+    }
+
+    /// Fetch the value. File values lose a trailing newline.
+    pub fn resolve(&self, env: &EnvSource) -> Result<String, String> {
+        let err = |message: String| format!("secret '{}': {message}", self.name);
+        match self.provider {
+            Provider::Env(ref var) => env.get(var).ok_or_else(|| err(format!("${var} is not set"))),
+            Provider::File(ref path) => std::fs::read_to_string(path)
+                .map(|text| text.trim_end_matches(['\r', '\n']).to_string())
+                .map_err(|e| err(format!("{path}: {e}"))),
+            Provider::Vault { ref path, ref field } => {
+                run("vault", &["kv", "get", &format!("-field={field}"), path]).map_err(err)
+            }
+            Provider::AwsSm { ref id, ref field } => {
+                let args = ["secretsmanager", "get-secret-value", "--secret-id", id, "--query", "SecretString"];
+                let text = run("aws", &[&args[..], &["--output", "text"]].concat()).map_err(err)?;
+                match field {
+                    Some(field) => json_field(&text, field).map_err(err),
+                    None => Ok(text),
+                }
+            }
+        }
+    }
+}
+
+/// Stdout of a provider's CLI, without its trailing newline.
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("could not run {program}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{program} failed: {}", stderr.trim()));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|_| format!("{program} printed non-UTF-8 output"))?;
+    Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn json_field(text: &str, field: &str) -> Result<String, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("not a JSON secret: {e}"))?;
+    match value.get(field) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(format!("no field '{field}'")),
+    }
+}
+
+/// `text` with each `{{secret.NAME}}` replaced by its value. Naming a
+/// secret that was not given is an error, so a typo is not sent as is.
+pub fn interpolate(text: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    const OPEN: &str = "{{secret.";
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(at) = rest.find(OPEN) {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + OPEN.len()..];
+        let end = after.find("}}").ok_or("unterminated {{secret.")?;
+        let name = &after[..end];
+        out.push_str(values.get(name).ok_or_else(|| format!("unknown secret '{name}' (add --secret {name}=...)"))?);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `config` with `secrets` resolved and substituted. A body that is not
+/// UTF-8 is left alone.
+pub fn apply(mut config: RequestConfig, secrets: &[Secret]) -> Result<RequestConfig, String> {
+    let env = config.env.clone().unwrap_or_default();
+    let values = secrets
+        .iter()
+        .map(|s| Ok((s.name.clone(), s.resolve(&env)?)))
+        .collect::<Result<HashMap<_, _>, String>>()?;
+    let sub = |text: &mut String| -> Result<(), String> {
+        *text = interpolate(text, &values)?;
+        Ok(())
+    };
+
+    sub(&mut config.url)?;
+    config.headers.iter_mut().try_for_each(sub)?;
+    config.proxy_headers.iter_mut().try_for_each(sub)?;
+    for (_, value) in config.form_fields.iter_mut() {
+        sub(value)?;
+    }
+    for field in [
+        &mut config.username,
+        &mut config.password,
+        &mut config.bearer,
+        &mut config.proxy_user,
+        &mut config.proxy_password,
+    ] {
+        field.as_mut().map(sub).transpose()?;
+    }
+    if let Some(ref mut data) = config.data
+        && let Ok(text) = std::str::from_utf8(data)
+    {
+        *data = interpolate(text, &values)?.into_bytes();
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_providers() {
+        assert_eq!(
+            Secret::parse("tok=vault:kv/api#token").unwrap().provider,
+            Provider::Vault {
+                path: "kv/api".into(),
+                field: "token".into()
+            }
+        );
+        assert_eq!(
+            Secret::parse("db=aws-sm:prod/db").unwrap().provider,
+            Provider::AwsSm {
+                id: "prod/db".into(),
+                field: None
+            }
+        );
+        assert_eq!(Secret::parse("k=env:API_KEY").unwrap().provider, Provider::Env("API_KEY".into()));
+        assert!(Secret::parse("tok=vault:kv/api").is_err());
+        assert!(Secret::parse("tok=gcp:x").unwrap_err().contains("unknown provider"));
+        assert!(Secret::parse("a b=env:X").is_err());
+        assert!(Secret::parse("tok").is_err());
+    }
+
+    #[test]
+    fn interpolates_known_names() {
+        let values = HashMap::from([("tok".to_string(), "s3cr3t".to_string())]);
+        assert_eq!(interpolate("Bearer {{secret.tok}}!", &values).unwrap(), "Bearer s3cr3t!");
+        assert_eq!(interpolate("{{other}}", &values).unwrap(), "{{other}}");
+        assert!(interpolate("{{secret.nope}}", &values).unwrap_err().contains("nope"));
+        assert!(interpolate("{{secret.tok", &values).is_err());
+        assert_eq!(json_field(r#"{"user":"u","port":5432}"#, "port").unwrap(), "5432");
+    }
+
+    #[test]
+    fn applies_to_request() {
+        let env = EnvSource::from_pairs([("API_KEY", "k1")]);
+        let config = RequestConfig::new("https://x.com/?key={{secret.key}}")
+            .env(env)
+            .header("X-Key: {{secret.key}}")
+            .data("key={{secret.key}}")
+            .bearer("{{secret.key}}");
+        let config = apply(config, &[Secret::parse("key=env:API_KEY").unwrap()]).unwrap();
+        assert_eq!(config.url, "https://x.com/?key=k1");
+        assert_eq!(config.headers, ["X-Key: k1"]);
+        assert_eq!(config.data.as_deref(), Some(&b"key=k1"[..]));
+        assert_eq!(config.bearer.as_deref(), Some("k1"));
+
+        let missing = apply(RequestConfig::new("https://x.com/"), &[Secret::parse("key=env:NOT_SET_X").unwrap()]);
+        assert!(missing.unwrap_err().contains("$NOT_SET_X"));
+    }
+}
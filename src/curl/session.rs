@@ -0,0 +1,218 @@
+// src/curl/session.rs
+
+//! `--session NAME`: running totals of requests and bytes for a named
+//! session, so a scripted pipeline can account for its network use, and
+//! `rustcurl session stats NAME` to print them. Totals are kept in
+//! `NAME.json` under `sessions` in the state directory (`paths`), e.g.
+//! `~/.local/state/rustcurl/sessions`; updates hold the file's lock and
+//! replace it whole, so concurrent runs add up.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde_json::{Value, json};
+
+use super::atomic;
+use super::env::EnvSource;
+use super::paths::{self, Kind};
+use super::error::RequestError;
+use super::provenance::rfc3339;
+use super::response::Response;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub requests: u64,
+    /// Requests that ended in an error rather than a response.
+    pub failed: u64,
+    /// Body and header bytes received.
+    pub bytes_received: u64,
+    /// Request body bytes sent.
+    pub bytes_sent: u64,
+    pub first: Option<String>,
+    pub last: Option<String>,
+}
+
+impl Stats {
+    fn from_json(value: &Value) -> Stats {
+        let count = |key: &str| value[key].as_u64().unwrap_or(0);
+        let time = |key: &str| value[key].as_str().map(str::to_string);
+        Stats {
+            requests: count("requests"),
+            failed: count("failed"),
+            bytes_received: count("bytes_received"),
+            bytes_sent: count("bytes_sent"),
+            first: time("first"),
+            last: time("last"),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "requests": self.requests,
+            "failed": self.failed,
+            "bytes_received": self.bytes_received,
+            "bytes_sent": self.bytes_sent,
+            "first": self.first,
+            "last": self.last,
+        })
+    }
+
+    /// Count one request finished at `at`.
+    pub fn add(&mut self, result: &Result<Response, RequestError>, at: SystemTime) {
+        self.requests += 1;
+        match result {
+            Ok(response) => {
+                let (received, sent) = match response.transfer {
+                    Some(ref t) => (t.size_download + t.size_header, t.size_upload),
+                    None => (response.body.len() as u64, 0),
+                };
+                self.bytes_received += received;
+                self.bytes_sent += sent;
+            }
+            Err(_) => self.failed += 1,
+        }
+        let at = rfc3339(at);
+        self.first.get_or_insert_with(|| at.clone());
+        self.last = Some(at);
+    }
+}
+
+/// Session names become file names, so they are kept to letters, digits,
+/// `.`, `_` and `-`.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if ok { Ok(()) } else { Err(format!("bad session name '{name}' (letters, digits, '.', '_', '-')")) }
+}
+
+/// Directory the session files live in.
+pub fn dir(env: &EnvSource) -> Option<PathBuf> {
+    paths::dir(env, Kind::State).map(|state| state.join("sessions"))
+}
+
+fn path(env: &EnvSource, name: &str) -> io::Result<PathBuf> {
+    let dir = dir(env).ok_or_else(|| io::Error::other(paths::missing(Kind::State)))?;
+    Ok(dir.join(format!("{name}.json")))
+}
+
+/// Add one request to session `name`'s totals.
+pub fn record(env: &EnvSource, name: &str, result: &Result<Response, RequestError>) -> io::Result<()> {
+    let path = path(env, name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let _lock = atomic::lock(&path)?;
+    let mut stats = match File::open(&path) {
+        Ok(mut file) => read_stats(&mut file)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Stats::default(),
+        Err(e) => return Err(e),
+    };
+    stats.add(result, SystemTime::now());
+    atomic::write(&path, format!("{}\n", stats.to_json()).as_bytes())
+}
+
+fn read_stats(file: &mut File) -> io::Result<Stats> {
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    if text.trim().is_empty() {
+        return Ok(Stats::default());
+    }
+    let value: Value = serde_json::from_str(&text).map_err(io::Error::other)?;
+    Ok(Stats::from_json(&value))
+}
+
+/// Session `name`'s totals; an error if nothing was recorded under it.
+pub fn load(env: &EnvSource, name: &str) -> io::Result<Stats> {
+    let path = path(env, name)?;
+    let mut file = File::open(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => io::Error::new(e.kind(), format!("no session '{name}' ({})", path.display())),
+        _ => e,
+    })?;
+    read_stats(&mut file)
+}
+
+/// The `session stats` report.
+pub fn render(name: &str, stats: &Stats) -> String {
+    format!(
+        "session   {name}\nrequests  {} ({} failed)\nreceived  {}\nsent      {}\nfirst     {}\nlast      {}\n",
+        stats.requests,
+        stats.failed,
+        size(stats.bytes_received),
+        size(stats.bytes_sent),
+        stats.first.as_deref().unwrap_or("-"),
+        stats.last.as_deref().unwrap_or("-"),
+    )
+}
+
+/// `n` bytes, with a binary-prefixed size alongside from 1 KiB up.
+fn size(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    let mut value = n as f64;
+    let mut unit = None;
+    for u in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = Some(u);
+    }
+    match unit {
+        Some(unit) => format!("{n} bytes ({value:.1} {unit})"),
+        None => format!("{n} bytes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::response::TransferInfo;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn ok(download: u64, upload: u64) -> Result<Response, RequestError> {
+        Ok(Response {
+            status_code: 200,
+            attempts: vec![200],
+            transfer: Some(TransferInfo {
+                size_download: download,
+                size_upload: upload,
+                size_header: 100,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn adds_up_requests() {
+        let mut stats = Stats::default();
+        let at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        stats.add(&ok(2048, 10), at);
+        stats.add(&Err(RequestError::HttpStatus { code: 500 }), at + Duration::from_secs(60));
+        assert_eq!((stats.requests, stats.failed), (2, 1));
+        assert_eq!((stats.bytes_received, stats.bytes_sent), (2148, 10));
+        assert_eq!(stats.first.as_deref(), Some("2023-11-14T22:13:20Z"));
+        assert_eq!(stats.last.as_deref(), Some("2023-11-14T22:14:20Z"));
+        assert_eq!(Stats::from_json(&stats.to_json()), stats);
+        let report = render("nightly", &stats);
+        assert!(report.contains("requests  2 (1 failed)\nreceived  2148 bytes (2.1 KiB)\nsent      10 bytes\n"));
+        assert!(check_name("etl-2026.10").is_ok());
+        assert!(check_name("../x").is_err());
+        assert!(check_name(".hidden").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_to_state_dir() {
+        let state = std::env::temp_dir().join(format!("rustcurl-session-{}", std::process::id()));
+        let env = EnvSource::from_pairs([("XDG_STATE_HOME", state.to_str().unwrap())]);
+        record(&env, "etl", &ok(5, 1)).unwrap();
+        record(&env, "etl", &ok(5, 1)).unwrap();
+        let stats = load(&env, "etl").unwrap();
+        assert_eq!((stats.requests, stats.bytes_received, stats.bytes_sent), (2, 210, 2));
+        assert_eq!(load(&env, "other").unwrap_err().kind(), io::ErrorKind::NotFound);
+        fs::remove_dir_all(&state).unwrap();
+    }
+}
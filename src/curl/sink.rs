@@ -0,0 +1,168 @@
+// src/curl/sink.rs
+
+//! Where a response body goes as it arrives: straight into the `-o` file
+//! or to stdout for `-o -`, so large downloads never sit in memory, and
+//! into a buffer otherwise.
+
+use std::fs::File;
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::ranges;
+use super::response::Response;
+use super::warning::{Warning, WarningKind};
+
+pub enum BodySink {
+    Memory(Vec<u8>),
+    /// The file is created on the first write, so a failed connection
+    /// leaves no empty file behind.
+    File { path: PathBuf, out: Option<BufWriter<File>> },
+    Stdout(StdoutLock<'static>),
+}
+
+impl BodySink {
+    /// The sink for `config`'s body. Byte range replies stay in memory,
+    /// since a multipart reply is reassembled before it is saved.
+    pub fn open(config: &RequestConfig) -> BodySink {
+        if !BodySink::streams(config) {
+            return BodySink::Memory(Vec::new());
+        }
+        match config.output_path() {
+            Some(path) => BodySink::File { path, out: None },
+            None => BodySink::Stdout(io::stdout().lock()),
+        }
+    }
+
+    /// Whether `open` would write the body out rather than buffer it.
+    pub fn streams(config: &RequestConfig) -> bool {
+        config.range.is_none()
+            && !config.has_header("Range")
+            && (config.writes_stdout() || config.output_path().is_some())
+    }
+
+    /// Flush what was written and return the buffered body, which is
+    /// empty when it was streamed. An empty reply still creates the file.
+    pub fn finish(self) -> Result<Vec<u8>, RequestError> {
+        match self {
+            BodySink::Memory(body) => Ok(body),
+            BodySink::File { path, out } => {
+                let flushed = match out {
+                    Some(mut out) => out.flush(),
+                    None => File::create(&path).map(drop),
+                };
+                flushed.map_err(|source| RequestError::WriteOutput {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+                Ok(Vec::new())
+            }
+            BodySink::Stdout(mut out) => {
+                out.flush()?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// The error to report for a failed write into this sink.
+    pub fn write_error(&self, source: io::Error) -> RequestError {
+        match self {
+            BodySink::File { path, .. } => RequestError::WriteOutput {
+                path: path.display().to_string(),
+                source,
+            },
+            _ => RequestError::Io(source),
+        }
+    }
+}
+
+/// Body to store for `-o`: multiple ranges are reassembled when they are
+/// contiguous; otherwise the multipart body is kept as received, with a
+/// warning.
+pub fn saved_body(status_code: u32, headers: &[String], body: Vec<u8>, warnings: &mut Vec<Warning>) -> Vec<u8> {
+    let response = Response {
+        status_code,
+        headers: headers.to_vec(),
+        body,
+        ..Default::default()
+    };
+    match response.byte_ranges() {
+        Some(parts) => match ranges::reassemble(&parts) {
+            Some(joined) => joined,
+            None => {
+                warnings.push(Warning::new(
+                    WarningKind::Ranges,
+                    "requested ranges are not contiguous; writing the multipart body as received",
+                ));
+                response.body
+            }
+        },
+        None => response.body,
+    }
+}
+
+impl Write for BodySink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            BodySink::Memory(body) => body.write(data),
+            BodySink::File { path, out } => {
+                let out = match out {
+                    Some(out) => out,
+                    None => out.insert(BufWriter::new(File::create(&*path)?)),
+                };
+                out.write(data)
+            }
+            BodySink::Stdout(out) => out.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            BodySink::Memory(_) => Ok(()),
+            BodySink::File { out, .. } => out.as_mut().map_or(Ok(()), |out| out.flush()),
+            BodySink::Stdout(out) => out.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chooses_sink_from_config() {
+        let config = RequestConfig::new("https://x.com/a.bin");
+        assert!(!BodySink::streams(&config));
+        assert!(BodySink::streams(&config.clone().output("a.bin")));
+        assert!(BodySink::streams(&config.clone().output("-")));
+        assert!(!BodySink::streams(&config.clone().output("a.bin").range("0-9")));
+        assert!(!BodySink::streams(&config.clone().output("a.bin").header("Range: bytes=0-9")));
+    }
+
+    #[test]
+    fn file_written_as_chunks_arrive() {
+        let path = std::env::temp_dir().join(format!("rustcurl-sink-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = RequestConfig::new("https://x.com/").output(path.to_str().unwrap());
+        let mut sink = BodySink::open(&config);
+        assert!(!path.exists());
+        sink.write_all(b"hello ").unwrap();
+        sink.write_all(b"world").unwrap();
+        assert!(sink.finish().unwrap().is_empty());
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello world");
+
+        // An empty body still leaves an (empty) file
+        BodySink::open(&config).finish().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn write_errors_name_the_file() {
+        let config = RequestConfig::new("https://x.com/").output("/nonexistent-dir/out.bin");
+        let mut sink = BodySink::open(&config);
+        let err = sink.write_all(b"x").unwrap_err();
+        assert_eq!(sink.write_error(err).code(), "write_output");
+    }
+}
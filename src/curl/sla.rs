@@ -0,0 +1,91 @@
+// src/curl/sla.rs
+
+//! `--assert-time-total-max` / `--assert-ttfb-max`: fail the run when a
+//! transfer is slower than allowed, so rustcurl can gate a pipeline.
+
+use std::time::Duration;
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::response::Timing;
+
+/// Process exit status for a run that failed a timing assertion.
+pub const EXIT_SLA_EXCEEDED: i32 = 3;
+
+/// Check the configured limits against the transfer's timing. `elapsed`
+/// stands in for the total time when the backend reports no timing;
+/// time to first byte cannot be asserted without backend timing.
+pub fn check(config: &RequestConfig, timing: Option<&Timing>, elapsed: Duration) -> Result<(), RequestError> {
+    if let Some(limit) = config.assert_time_total_max {
+        let actual = timing.map_or(elapsed, |t| t.total);
+        if actual > limit {
+            return Err(RequestError::SlaExceeded {
+                option: "--assert-time-total-max".to_string(),
+                limit,
+                actual,
+            });
+        }
+    }
+    if let Some(limit) = config.assert_ttfb_max {
+        let Some(timing) = timing else {
+            return Err(RequestError::Config {
+                option: "--assert-ttfb-max".to_string(),
+                message: "this backend does not report time to first byte".to_string(),
+            });
+        };
+        if timing.starttransfer > limit {
+            return Err(RequestError::SlaExceeded {
+                option: "--assert-ttfb-max".to_string(),
+                limit,
+                actual: timing.starttransfer,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(ttfb_ms: u64, total_ms: u64) -> Timing {
+        Timing {
+            dns: Duration::ZERO,
+            connect: Duration::ZERO,
+            tls: Duration::ZERO,
+            starttransfer: Duration::from_millis(ttfb_ms),
+            total: Duration::from_millis(total_ms),
+            redirect: Duration::ZERO,
+            tls_resumed: None,
+            early_data: None,
+            hops: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn no_limits_always_pass() {
+        let cfg = RequestConfig::new("https://x.com");
+        assert!(check(&cfg, None, Duration::from_secs(60)).is_ok());
+    }
+
+    #[test]
+    fn total_limit() {
+        let cfg = RequestConfig::new("https://x.com").assert_time_total_max(Duration::from_millis(800));
+        assert!(check(&cfg, Some(&timing(100, 700)), Duration::ZERO).is_ok());
+        let err = check(&cfg, Some(&timing(100, 900)), Duration::ZERO).unwrap_err();
+        assert_eq!(err.code(), "sla_exceeded");
+        assert_eq!(err.exit_code(), EXIT_SLA_EXCEEDED);
+        assert!(err.to_string().contains("--assert-time-total-max"));
+        // Falls back to wall-clock time without backend timing
+        assert!(check(&cfg, None, Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn ttfb_limit() {
+        let cfg = RequestConfig::new("https://x.com").assert_ttfb_max(Duration::from_millis(200));
+        assert!(check(&cfg, Some(&timing(150, 5000)), Duration::ZERO).is_ok());
+        let err = check(&cfg, Some(&timing(250, 300)), Duration::ZERO).unwrap_err();
+        assert!(matches!(err, RequestError::SlaExceeded { .. }));
+        assert_eq!(check(&cfg, None, Duration::ZERO).unwrap_err().code(), "config");
+    }
+}
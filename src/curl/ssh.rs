@@ -0,0 +1,160 @@
+// src/curl/ssh.rs
+
+//! `--ssh-tunnel [user@]host[:port]`: reach internal services through an
+//! SSH jump host. The system `ssh` client opens a dynamic forward (`-D`)
+//! and the request goes through it as a `socks5h://` proxy, so names are
+//! resolved on the bastion's side and TLS still sees the real host.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+
+/// How long to wait for the forward when `--connect-timeout` is not set.
+const DEFAULT_SETUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Trailing `ssh` stderr lines kept for the error message.
+const STDERR_LINES: usize = 8;
+
+/// A running `ssh -D`; the process is stopped when this is dropped.
+pub struct Tunnel {
+    child: Child,
+    port: u16,
+    stderr: Option<JoinHandle<String>>,
+}
+
+impl Tunnel {
+    /// Start `ssh` towards `dest` and wait until its SOCKS port accepts
+    /// connections. Authentication must not prompt (keys or an agent).
+    pub fn open(dest: &str, timeout: Option<Duration>) -> Result<Tunnel, RequestError> {
+        let err = |message: String| RequestError::Connect {
+            message: format!("ssh tunnel to {dest}: {message}"),
+        };
+        let port = free_port().map_err(|e| err(format!("no local port: {e}")))?;
+        let timeout = timeout.unwrap_or(DEFAULT_SETUP_TIMEOUT);
+        let mut child = Command::new("ssh")
+            .args(ssh_args(dest, port, timeout).map_err(err)?)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| err(format!("could not run ssh: {e}")))?;
+        // Drained for the whole session so a chatty ssh never blocks on a
+        // full pipe
+        let stderr = child.stderr.take().map(|pipe| thread::spawn(move || drain(pipe)));
+        let mut tunnel = Tunnel { child, port, stderr };
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = tunnel.child.try_wait().map_err(|e| err(e.to_string()))? {
+                let stderr = tunnel.stderr.take().and_then(|t| t.join().ok()).unwrap_or_default();
+                let detail = stderr.trim();
+                return Err(err(if detail.is_empty() { format!("ssh exited with {status}") } else { detail.to_string() }));
+            }
+            if TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_ok() {
+                return Ok(tunnel);
+            }
+            if Instant::now() >= deadline {
+                return Err(err(format!("forward not ready after {}s", timeout.as_secs())));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    pub fn proxy_url(&self) -> String {
+        format!("socks5h://127.0.0.1:{}", self.port)
+    }
+
+    /// `config` sent through this tunnel; an empty no-proxy list keeps
+    /// `NO_PROXY` from sending some hosts around it.
+    pub fn route(&self, config: &RequestConfig) -> RequestConfig {
+        config.clone().proxy(&self.proxy_url()).noproxy("")
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Read `pipe` to the end, keeping its last few lines.
+fn drain(pipe: impl Read) -> String {
+    let mut tail = VecDeque::with_capacity(STDERR_LINES);
+    for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+        if tail.len() == STDERR_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    Vec::from(tail).join("\n")
+}
+
+fn free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()?.port())
+}
+
+/// Arguments for `ssh`: no remote command, a SOCKS forward on `port`, and
+/// failure instead of a prompt or a tunnel-less session.
+fn ssh_args(dest: &str, port: u16, timeout: Duration) -> Result<Vec<String>, String> {
+    let (host, ssh_port) = match dest.rsplit_once(':') {
+        Some((host, p)) if !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) => (host, Some(p)),
+        _ => (dest, None),
+    };
+    if host.is_empty() || host.starts_with('-') || host.ends_with('@') {
+        return Err(format!("invalid destination '{dest}', expected [user@]host[:port]"));
+    }
+    let mut args: Vec<String> = vec![
+        "-N".into(),
+        "-D".into(),
+        format!("127.0.0.1:{port}"),
+        "-o".into(),
+        "ExitOnForwardFailure=yes".into(),
+        "-o".into(),
+        "BatchMode=yes".into(),
+        "-o".into(),
+        format!("ConnectTimeout={}", timeout.as_secs().max(1)),
+    ];
+    if let Some(p) = ssh_port {
+        args.extend(["-p".into(), p.to_string()]);
+    }
+    args.extend(["--".into(), host.to_string()]);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_ssh_arguments() {
+        let args = ssh_args("ops@bastion.corp:2222", 40000, Duration::from_secs(10)).unwrap();
+        assert_eq!(&args[..3], ["-N", "-D", "127.0.0.1:40000"]);
+        assert!(args.contains(&"ConnectTimeout=10".to_string()));
+        assert_eq!(&args[args.len() - 4..], ["-p", "2222", "--", "ops@bastion.corp"]);
+
+        let args = ssh_args("bastion", 1, Duration::ZERO).unwrap();
+        assert!(args.contains(&"ConnectTimeout=1".to_string()));
+        assert_eq!(&args[args.len() - 2..], ["--", "bastion"]);
+    }
+
+    #[test]
+    fn drain_keeps_the_last_lines() {
+        let output: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let tail = drain(output.as_bytes());
+        assert_eq!(tail.lines().count(), STDERR_LINES);
+        assert!(tail.starts_with("line 13\n") && tail.ends_with("line 20"));
+    }
+
+    #[test]
+    fn rejects_option_like_destinations() {
+        assert!(ssh_args("-oProxyCommand=evil", 1, Duration::ZERO).is_err());
+        assert!(ssh_args("user@", 1, Duration::ZERO).is_err());
+        assert!(ssh_args(":22", 1, Duration::ZERO).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+// src/curl/tail.rs
+
+//! `--tail <DUR>`: follow a growing remote file, like `tail -f`. The
+//! first request fetches the whole file; each later one asks for
+//! `Range: bytes=N-` from the length seen so far, and only the new bytes
+//! are printed. Polls that find nothing new back off, up to eight times
+//! the interval. A file that shrank is followed again from the start.
+
+use std::time::Duration;
+
+use super::response::Response;
+
+/// Longest wait between polls, as a multiple of the interval.
+const MAX_BACKOFF: u32 = 8;
+
+/// What one poll found.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Poll<'a> {
+    /// Bytes appended since the last poll.
+    New(&'a [u8]),
+    Unchanged,
+    /// The file is shorter than before; these bytes are its new content.
+    Truncated(&'a [u8]),
+    /// The server answered with neither the file nor a range of it.
+    Failed(u32),
+}
+
+/// How far into the file rustcurl has read, and how long since it last
+/// grew.
+#[derive(Debug, Default)]
+pub struct Follower {
+    offset: u64,
+    idle: u32,
+}
+
+impl Follower {
+    pub fn new() -> Self {
+        Follower::default()
+    }
+
+    /// The `Range` to ask for next, once something has been read.
+    pub fn range(&self) -> Option<String> {
+        (self.offset > 0).then(|| format!("{}-", self.offset))
+    }
+
+    /// Take in one poll's response.
+    pub fn observe<'a>(&mut self, response: &'a Response) -> Poll<'a> {
+        let body = &response.body[..];
+        let len = body.len() as u64;
+        let poll = match response.status_code {
+            206 => {
+                let start = response
+                    .get_header("content-range")
+                    .and_then(|range| content_range_start(&range))
+                    .unwrap_or(self.offset);
+                self.offset = start + len;
+                Poll::New(body)
+            }
+            416 => match response.get_header("content-range").and_then(|range| complete_length(&range)) {
+                Some(total) if total < self.offset => {
+                    self.offset = 0;
+                    Poll::Truncated(&[])
+                }
+                _ => Poll::Unchanged,
+            },
+            // The server ignored the range and sent the whole file
+            200 if len < self.offset => {
+                self.offset = len;
+                Poll::Truncated(body)
+            }
+            200 => {
+                let new = &body[self.offset as usize..];
+                self.offset = len;
+                Poll::New(new)
+            }
+            status => Poll::Failed(status),
+        };
+        let poll = match poll {
+            Poll::New([]) => Poll::Unchanged,
+            poll => poll,
+        };
+        self.idle = match poll {
+            Poll::New(_) | Poll::Truncated(_) => 0,
+            _ => self.idle.saturating_add(1),
+        };
+        poll
+    }
+
+    /// A poll that got no response at all.
+    pub fn failed(&mut self) {
+        self.idle = self.idle.saturating_add(1);
+    }
+
+    /// How long to wait before the next poll: `interval`, doubled for each
+    /// poll in a row that found nothing new.
+    pub fn delay(&self, interval: Duration) -> Duration {
+        interval * (1 << self.idle.min(MAX_BACKOFF.ilog2()))
+    }
+}
+
+/// `START` of `bytes START-END/TOTAL`.
+fn content_range_start(value: &str) -> Option<u64> {
+    value.trim().strip_prefix("bytes ")?.split('-').next()?.trim().parse().ok()
+}
+
+/// `TOTAL` of `bytes */TOTAL` or `bytes START-END/TOTAL`.
+fn complete_length(value: &str) -> Option<u64> {
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(status: u32, headers: &[&str], body: &str) -> Response {
+        Response {
+            status_code: status,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            body: body.as_bytes().to_vec(),
+            attempts: vec![status],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn follows_appended_bytes() {
+        let mut follower = Follower::new();
+        assert_eq!(follower.range(), None);
+        assert_eq!(follower.observe(&reply(200, &[], "abc")), Poll::New(b"abc"));
+        assert_eq!(follower.range().as_deref(), Some("3-"));
+        let more = reply(206, &["Content-Range: bytes 3-4/5"], "de");
+        assert_eq!(follower.observe(&more), Poll::New(b"de"));
+        assert_eq!(follower.range().as_deref(), Some("5-"));
+        assert_eq!(follower.observe(&reply(416, &["Content-Range: bytes */5"], "")), Poll::Unchanged);
+        // A server without range support resends everything
+        assert_eq!(follower.observe(&reply(200, &[], "abcdefg")), Poll::New(b"fg"));
+        assert_eq!(follower.observe(&reply(503, &[], "")), Poll::Failed(503));
+    }
+
+    #[test]
+    fn restarts_after_truncation() {
+        let mut follower = Follower::new();
+        follower.observe(&reply(200, &[], "0123456789"));
+        assert_eq!(follower.observe(&reply(416, &["Content-Range: bytes */4"], "")), Poll::Truncated(b""));
+        assert_eq!(follower.range(), None);
+        follower.observe(&reply(200, &[], "0123456789"));
+        assert_eq!(follower.observe(&reply(200, &[], "new")), Poll::Truncated(b"new"));
+    }
+
+    #[test]
+    fn backs_off_while_unchanged() {
+        let second = Duration::from_secs(1);
+        let mut follower = Follower::new();
+        follower.observe(&reply(200, &[], "x"));
+        assert_eq!(follower.delay(second), second);
+        let unchanged = reply(416, &["Content-Range: bytes */1"], "");
+        follower.observe(&unchanged);
+        assert_eq!(follower.delay(second), 2 * second);
+        for _ in 0..5 {
+            follower.failed();
+        }
+        assert_eq!(follower.delay(second), 8 * second);
+        follower.observe(&reply(206, &["Content-Range: bytes 1-1/2"], "y"));
+        assert_eq!(follower.delay(second), second);
+    }
+}
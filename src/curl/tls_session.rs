@@ -0,0 +1,122 @@
+// src/curl/tls_session.rs
+
+//! `--tls-session-cache <FILE>`: TLS sessions (tickets) kept between runs,
+//! so the next invocation can resume instead of doing a full handshake,
+//! and with `--tls-early-data` send its request as 0-RTT data. Each line
+//! of FILE holds one session as libcurl exports it. A ticket lets anyone
+//! holding it resume the session, so the file is only readable by its
+//! owner.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+
+use super::atomic;
+use super::config::{Method, RequestConfig};
+use super::encoding::{hex, unhex};
+
+/// One exported session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    /// libcurl's key for the peer (host, port and TLS settings).
+    pub key: String,
+    /// libcurl's salted hash identifying the peer.
+    pub shmac: Vec<u8>,
+    pub data: Vec<u8>,
+    /// Unix time after which the server no longer accepts it.
+    pub valid_until: i64,
+}
+
+impl Ticket {
+    fn to_json(&self) -> Value {
+        json!({
+            "key": self.key,
+            "shmac": hex(&self.shmac),
+            "data": hex(&self.data),
+            "valid_until": self.valid_until,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Ticket> {
+        Some(Ticket {
+            key: value.get("key")?.as_str()?.to_string(),
+            shmac: unhex(value.get("shmac")?.as_str()?)?,
+            data: unhex(value.get("data")?.as_str()?)?,
+            valid_until: value.get("valid_until")?.as_i64()?,
+        })
+    }
+}
+
+/// Sessions in `path` that have not expired. A missing file has none;
+/// lines that cannot be read are skipped, as the file is only a cache.
+pub fn load(path: &Path) -> io::Result<Vec<Ticket>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let now = now();
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter_map(|value| Ticket::from_json(&value))
+        .filter(|ticket| ticket.valid_until > now)
+        .collect())
+}
+
+/// Replace `path` with `tickets`.
+pub fn save(path: &Path, tickets: &[Ticket]) -> io::Result<()> {
+    let text: String = tickets.iter().map(|ticket| format!("{}\n", ticket.to_json())).collect();
+    atomic::write_private(path, text.as_bytes())
+}
+
+/// Whether `config`'s request may go out as 0-RTT data, which an attacker
+/// can replay: only bodiless GET, HEAD and OPTIONS requests qualify.
+pub fn early_data_safe(config: &RequestConfig) -> bool {
+    matches!(config.method, Method::Get | Method::Head | Method::Options) && config.body().is_none()
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_live_tickets() {
+        let path = std::env::temp_dir().join(format!("rustcurl-tls-sessions-{}", std::process::id()));
+        let live = Ticket {
+            key: "x.com:443:CA".into(),
+            shmac: vec![0, 1, 0xfe],
+            data: b"ticket".to_vec(),
+            valid_until: now() + 3600,
+        };
+        let expired = Ticket {
+            valid_until: now() - 1,
+            ..live.clone()
+        };
+        save(&path, &[live.clone(), expired]).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "not json\n").unwrap();
+        assert_eq!(load(&path).unwrap(), vec![live]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+        assert_eq!(load(&path).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn early_data_only_for_replayable_requests() {
+        assert!(early_data_safe(&RequestConfig::new("https://x.com/")));
+        assert!(early_data_safe(&RequestConfig::new("https://x.com/").method(Method::Head)));
+        assert!(!early_data_safe(&RequestConfig::new("https://x.com/").data("a=1")));
+        assert!(!early_data_safe(&RequestConfig::new("https://x.com/").method(Method::Delete)));
+    }
+}
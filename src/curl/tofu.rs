@@ -0,0 +1,201 @@
+// src/curl/tofu.rs
+
+//! `--tofu`: trust on first use, as SSH does with `known_hosts`, for
+//! internal services with self-signed certificates. The first https
+//! request to a `host:port` accepts whatever certificate it is shown and
+//! records a pin of the server's public key; later requests must present
+//! the same key, which libcurl checks during the handshake, so nothing is
+//! sent to an impostor. The pin covers the whole transfer, so a redirect
+//! to another host fails. Pins live in `known_hosts` in the data directory
+//! (`paths`); `rustcurl trust list` shows them and `rustcurl trust remove
+//! HOST:PORT` forgets one after a legitimate key change.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use super::atomic;
+use super::encoding::base64;
+use super::env::EnvSource;
+use super::error::RequestError;
+use super::paths::{self, Kind};
+use super::provenance::rfc3339;
+use super::{url, x509};
+
+/// One trusted server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// `host:port`.
+    pub authority: String,
+    /// The public key pin, as libcurl takes it: `sha256//BASE64`.
+    pub pin: String,
+    /// Fingerprint of the certificate first seen, for people to compare.
+    pub sha256: String,
+    /// When it was first seen.
+    pub added: String,
+}
+
+impl Entry {
+    fn line(&self) -> String {
+        format!("{} {} {} {}\n", self.authority, self.pin, self.sha256, self.added)
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let mut fields = line.split_whitespace();
+        let entry = Entry {
+            authority: fields.next()?.to_string(),
+            pin: fields.next()?.to_string(),
+            sha256: fields.next()?.to_string(),
+            added: fields.next()?.to_string(),
+        };
+        fields.next().is_none().then_some(entry)
+    }
+}
+
+/// The store's path.
+pub fn store(env: &EnvSource) -> io::Result<PathBuf> {
+    let dir = paths::dir(env, Kind::Data).ok_or_else(|| io::Error::other(paths::missing(Kind::Data)))?;
+    Ok(dir.join("known_hosts"))
+}
+
+/// `host:port` for an https URL; other schemes have nothing to pin.
+pub fn authority(request_url: &str) -> Option<String> {
+    let parts = url::parse(request_url).ok()?;
+    if !parts.scheme.is_some_and(|s| s.eq_ignore_ascii_case("https")) {
+        return None;
+    }
+    let port = parts.port.unwrap_or(443);
+    Some(if parts.host.contains(':') {
+        format!("[{}]:{port}", parts.host.to_ascii_lowercase())
+    } else {
+        format!("{}:{port}", parts.host.to_ascii_lowercase())
+    })
+}
+
+/// Every entry in `path`; none if it does not exist yet. Lines that do
+/// not parse are skipped.
+pub fn load(path: &Path) -> io::Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text.lines().filter_map(Entry::parse).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The entry for `authority`, if it was trusted before.
+pub fn find(path: &Path, authority: &str) -> io::Result<Option<Entry>> {
+    Ok(load(path)?.into_iter().find(|e| e.authority == authority))
+}
+
+/// Trust `leaf`, the certificate `authority` presented, unless another run
+/// got there first. Returns the stored entry and whether it is new.
+pub fn remember(path: &Path, authority: &str, leaf: &[u8]) -> io::Result<(Entry, bool)> {
+    let key = x509::public_key_info(leaf).ok_or_else(|| io::Error::other("the server certificate could not be read"))?;
+    let entry = Entry {
+        authority: authority.to_string(),
+        pin: format!("sha256//{}", base64(&Sha256::digest(key))),
+        sha256: x509::summarize(leaf).map_or_else(|| "-".to_string(), |cert| cert.sha256),
+        added: rfc3339(SystemTime::now()),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let _lock = atomic::lock(path)?;
+    let mut entries = load(path)?;
+    if let Some(existing) = entries.iter().find(|e| e.authority == authority) {
+        return Ok((existing.clone(), false));
+    }
+    entries.push(entry.clone());
+    write(path, &entries)?;
+    Ok((entry, true))
+}
+
+/// Forget `authority`; returns whether it was there.
+pub fn remove(path: &Path, authority: &str) -> io::Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let _lock = atomic::lock(path)?;
+    let mut entries = load(path)?;
+    let before = entries.len();
+    entries.retain(|e| e.authority != authority);
+    if entries.len() == before {
+        return Ok(false);
+    }
+    write(path, &entries)?;
+    Ok(true)
+}
+
+fn write(path: &Path, entries: &[Entry]) -> io::Result<()> {
+    let text: String = entries.iter().map(Entry::line).collect();
+    atomic::write(path, text.as_bytes())
+}
+
+/// Whether `leaf` carries the key `entry` pins.
+pub fn matches(entry: &Entry, leaf: &[u8]) -> bool {
+    x509::public_key_info(leaf).is_some_and(|key| entry.pin == format!("sha256//{}", base64(&Sha256::digest(key))))
+}
+
+/// What a request pinned to `entry` failed with: a key mismatch is
+/// spelled out, anything else is passed on.
+pub fn check_failure(entry: &Entry, e: RequestError) -> RequestError {
+    match e {
+        RequestError::TlsVerify { .. } => RequestError::TlsVerify {
+            message: format!(
+                "the server at {} presented a different key than the one trusted on {} \
+                 (certificate sha256 {}); if the change is expected, run 'rustcurl trust remove {}'",
+                entry.authority, entry.added, entry.sha256, entry.authority
+            ),
+        },
+        e => e,
+    }
+}
+
+/// The `trust list` report.
+pub fn render(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{}  added {}\n  sha256 {}\n", e.authority, e.added, e.sha256))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authority_for_https_only() {
+        assert_eq!(authority("https://Svc.Internal/a").as_deref(), Some("svc.internal:443"));
+        assert_eq!(authority("https://[::1]:8443/").as_deref(), Some("[::1]:8443"));
+        assert_eq!(authority("http://svc.internal/"), None);
+    }
+
+    #[test]
+    fn remembers_once_and_removes() {
+        let dir = std::env::temp_dir().join(format!("rustcurl-tofu-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("known_hosts");
+        // The smallest certificate shape `public_key_info` walks
+        let field = |tag: u8| [tag, 0];
+        let tbs: Vec<u8> = [0x02, 0x30, 0x30, 0x30, 0x30, 0x30].into_iter().flat_map(field).collect();
+        let leaf = [&[0x30, tbs.len() as u8 + 2, 0x30, tbs.len() as u8][..], &tbs].concat();
+
+        let (added, new) = remember(&path, "svc.internal:443", &leaf).unwrap();
+        assert!(new);
+        assert_eq!(added.pin, format!("sha256//{}", base64(&Sha256::digest([0x30, 0]))));
+        assert_eq!(remember(&path, "svc.internal:443", &leaf).unwrap(), (added.clone(), false));
+        assert_eq!(find(&path, "svc.internal:443").unwrap(), Some(added.clone()));
+        assert!(matches(&added, &leaf) && !matches(&added, b"junk"));
+        assert!(render(&load(&path).unwrap()).starts_with("svc.internal:443  added "));
+
+        let failure = check_failure(&added, RequestError::TlsVerify { message: "pin".into() });
+        assert!(failure.to_string().contains("rustcurl trust remove svc.internal:443"), "{failure}");
+        assert!(remove(&path, "svc.internal:443").unwrap());
+        assert!(!remove(&path, "svc.internal:443").unwrap());
+        assert!(load(&path).unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+// src/curl/tor.rs
+
+//! `--tor`: send the request through a local Tor SOCKS port, resolving
+//! names through Tor, with a common browser User-Agent instead of the
+//! default one and without headers that name the client or its network.
+
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+use super::config::{RequestConfig, split_header};
+use super::rng::Rng;
+
+/// SOCKS ports tried in order: the tor daemon, then Tor Browser.
+pub const PORTS: [u16; 2] = [9050, 9150];
+
+/// Picked at random per request when neither `-A` nor `--impersonate` is given.
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; rv:128.0) Gecko/20100101 Firefox/128.0",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:133.0) Gecko/20100101 Firefox/133.0",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.1 Safari/605.1.15",
+    "Mozilla/5.0 (X11; Linux x86_64; rv:133.0) Gecko/20100101 Firefox/133.0",
+];
+
+/// `-H` headers dropped in Tor mode.
+const IDENTIFYING_HEADERS: &[&str] = &["from", "forwarded", "referer", "via", "x-forwarded-for", "x-real-ip"];
+
+/// First of `PORTS` accepting connections on localhost.
+pub fn detect() -> Option<u16> {
+    PORTS.into_iter().find(|&port| {
+        TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, port)), Duration::from_millis(500)).is_ok()
+    })
+}
+
+/// `config` routed through Tor on `port`. `socks5h` hands names to Tor
+/// to resolve, and an empty no-proxy list keeps `NO_PROXY` from sending
+/// any host around it.
+pub fn apply(config: &RequestConfig, port: u16, rng: &mut Rng) -> RequestConfig {
+    let mut routed = config
+        .clone()
+        .proxy(&format!("socks5h://127.0.0.1:{port}"))
+        .noproxy("");
+    if routed.user_agent.is_none() && routed.impersonate.is_none() {
+        let pick = USER_AGENTS[rng.below(USER_AGENTS.len() as u64) as usize];
+        routed = routed.user_agent(pick);
+    }
+    routed.headers.retain(|h| {
+        split_header(h).is_none_or(|(name, _)| !IDENTIFYING_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+    });
+    routed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_names_through_tor() {
+        let config = RequestConfig::new("http://example.onion/").noproxy("example.onion");
+        let routed = apply(&config, 9150, &mut Rng::new(Some(1)));
+        assert_eq!(routed.proxy.as_deref(), Some("socks5h://127.0.0.1:9150"));
+        assert_eq!(routed.noproxy.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn user_agent_randomized_unless_given() {
+        let config = RequestConfig::new("https://x.com/");
+        let ua = apply(&config, 9050, &mut Rng::new(Some(3))).user_agent.unwrap();
+        assert!(USER_AGENTS.contains(&ua.as_str()));
+        let seen: std::collections::HashSet<_> = (0..20)
+            .map(|seed| apply(&config, 9050, &mut Rng::new(Some(seed))).user_agent.unwrap())
+            .collect();
+        assert!(seen.len() > 1);
+
+        let explicit = config.user_agent("mine/1.0");
+        assert_eq!(apply(&explicit, 9050, &mut Rng::new(None)).user_agent.as_deref(), Some("mine/1.0"));
+    }
+
+    #[test]
+    fn identifying_headers_stripped() {
+        let config = RequestConfig::new("https://x.com/")
+            .header("X-Forwarded-For: 10.1.2.3")
+            .header("Referer: https://intranet/")
+            .header("Accept: text/html");
+        let routed = apply(&config, 9050, &mut Rng::new(None));
+        assert_eq!(routed.headers, vec!["Accept: text/html"]);
+    }
+}
@@ -0,0 +1,434 @@
+// src/curl/update.rs
+
+//! `rustcurl self-update`: replace the running binary with the latest
+//! release on a channel. The channel's manifest,
+//! `<endpoint>/<channel>/manifest.json`, names the version and, for each
+//! platform, the binary's URL, its SHA-256 and optionally a detached
+//! signature. The download goes through the same `--verify-sig` check as
+//! any other, and `--verify-key` is required unless `--allow-unsigned`
+//! settles for the SHA-256. Versions are compared as semver and an older
+//! release is only installed with `--force`. The new binary is written
+//! next to the old one and renamed over it, so an interrupted update
+//! leaves the old binary in place.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config::RequestConfig;
+use super::encoding::sha256_hex;
+use super::error::RequestError;
+use super::response::Response;
+use super::url;
+use super::verbose::VerboseSink;
+use super::warning::{Warning, WarningKind};
+
+pub const DEFAULT_ENDPOINT: &str = "https://github.com/sganis/rustcurl/releases/download";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Stable,
+    Nightly,
+}
+
+impl Channel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "stable" => Some(Channel::Stable),
+            "nightly" => Some(Channel::Nightly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Nightly => "nightly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub channel: Channel,
+    pub endpoint: String,
+    /// Minisign public key or OpenPGP keyring the release's signature
+    /// must verify against.
+    pub verify_key: Option<String>,
+    /// Install a release that `verify_key` cannot vouch for, on its
+    /// SHA-256 alone.
+    pub allow_unsigned: bool,
+    /// Install the channel's release even when it is older.
+    pub force: bool,
+    /// Only report whether an update is available.
+    pub check: bool,
+    pub verbose: bool,
+    /// Where `verbose` notes go; see `RequestConfig::verbose_sink`.
+    pub verbose_sink: VerboseSink,
+}
+
+impl Options {
+    /// `[--channel stable|nightly] [--endpoint URL] [--verify-key KEY]
+    /// [--allow-unsigned] [--force] [--check] [-v]`.
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            channel: Channel::Stable,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            verify_key: None,
+            allow_unsigned: false,
+            force: false,
+            check: false,
+            verbose: false,
+            verbose_sink: VerboseSink::Stderr,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} requires a value"));
+            match arg.as_str() {
+                "--channel" => {
+                    let val = value()?;
+                    options.channel = Channel::parse(val)
+                        .ok_or_else(|| format!("--channel: unknown channel '{val}' (stable, nightly)"))?;
+                }
+                "--endpoint" => options.endpoint = value()?.trim_end_matches('/').to_string(),
+                "--verify-key" => options.verify_key = Some(value()?.to_string()),
+                "--allow-unsigned" => options.allow_unsigned = true,
+                "--force" => options.force = true,
+                "--check" => options.check = true,
+                "-v" | "--verbose" => options.verbose = true,
+                _ => return Err(format!("unknown self-update option '{arg}'")),
+            }
+        }
+        Ok(options)
+    }
+
+    pub fn manifest_url(&self) -> String {
+        format!("{}/{}/manifest.json", self.endpoint, self.channel.as_str())
+    }
+
+    /// Warnings to show before updating.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.allow_unsigned && self.verify_key.is_none() && !self.check {
+            warnings.push(Warning::new(
+                WarningKind::UnsignedUpdate,
+                "--allow-unsigned: the new binary is checked against the manifest's SHA-256 only",
+            ));
+        }
+        warnings
+    }
+}
+
+/// A semver version (`MAJOR.MINOR.PATCH[-PRE][+BUILD]`), ordered by
+/// semver precedence; build metadata is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub core: [u64; 3],
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Option<Version> {
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(str::to_string).collect::<Vec<_>>()),
+            None => (text, Vec::new()),
+        };
+        if pre.iter().any(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) {
+            return None;
+        }
+        let mut parts = core.split('.').map(|n| n.parse::<u64>().ok());
+        let core = [parts.next()??, parts.next()??, parts.next()??];
+        parts.next().is_none().then_some(Version { core, pre })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                for (a, b) in self.pre.iter().zip(&other.pre) {
+                    let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                        (Ok(a), Ok(b)) => a.cmp(&b),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => a.cmp(b),
+                    };
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+                self.pre.len().cmp(&other.pre.len())
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The build a release must provide, e.g. `x86_64-linux`.
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+/// One platform's binary in a channel's manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+    pub signature: Option<String>,
+}
+
+/// `platform`'s entry in the manifest fetched from `manifest_url`:
+/// `{"version": V, "artifacts": {PLATFORM: {"url", "sha256", "signature"}}}`.
+/// Relative URLs are resolved against the manifest's.
+pub fn release(manifest_url: &str, body: &[u8], platform: &str) -> Result<Release, RequestError> {
+    let err = |message: String| RequestError::Update { message };
+    let json: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| err(format!("{manifest_url}: not a release manifest: {e}")))?;
+    let field = |value: &serde_json::Value, key: &str| {
+        value[key].as_str().filter(|s| !s.is_empty()).map(str::to_string)
+    };
+    let version = field(&json, "version").ok_or_else(|| err(format!("{manifest_url}: no version")))?;
+    let artifact = &json["artifacts"][platform];
+    let (Some(binary), Some(sha256)) = (field(artifact, "url"), field(artifact, "sha256")) else {
+        return Err(err(format!("release {version} has no build for {platform}")));
+    };
+    Ok(Release {
+        version,
+        url: url::join(manifest_url, &binary),
+        sha256: sha256.to_ascii_lowercase(),
+        signature: field(artifact, "signature").map(|sig| url::join(manifest_url, &sig)),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    UpToDate(String),
+    /// A newer release was found but `--check` left it alone.
+    Available(String),
+    Updated { from: String, to: String },
+}
+
+/// Fetch the channel's manifest and, unless already on its version,
+/// download, verify and install the release over `exe`. `current` is the
+/// running version.
+pub fn run(
+    options: &Options,
+    current: &str,
+    exe: &Path,
+    mut send: impl FnMut(&RequestConfig) -> Result<Response, RequestError>,
+) -> Result<Outcome, RequestError> {
+    let err = |message: String| RequestError::Update { message };
+    let manifest_url = options.manifest_url();
+    let manifest = send(&get(&manifest_url, options))?;
+    if !(200..300).contains(&manifest.status_code) {
+        return Err(err(format!("{manifest_url}: server returned HTTP {}", manifest.status_code)));
+    }
+    let release = release(&manifest_url, &manifest.body, &platform())?;
+    let version = |text: &str| Version::parse(text).ok_or_else(|| err(format!("'{text}' is not a semver version")));
+    match version(&release.version)?.cmp(&version(current)?) {
+        Ordering::Equal => return Ok(Outcome::UpToDate(release.version)),
+        Ordering::Less if options.check => return Ok(Outcome::UpToDate(current.to_string())),
+        Ordering::Less if !options.force => {
+            return Err(err(format!(
+                "release {} is older than the running {current} (--force installs it anyway)",
+                release.version
+            )));
+        }
+        _ if options.check => return Ok(Outcome::Available(release.version)),
+        _ => {}
+    }
+    let mut download = get(&release.url, options);
+    match (&options.verify_key, &release.signature) {
+        (Some(key), Some(sig)) => download = download.verify_sig(sig).verify_key(key),
+        (Some(_), None) => return Err(err(format!("release {} is not signed", release.version))),
+        (None, _) if options.allow_unsigned => download.note("--allow-unsigned: checking the SHA-256 only"),
+        (None, _) => {
+            return Err(err(format!(
+                "no --verify-key to check release {} with (--allow-unsigned trusts its SHA-256 alone)",
+                release.version
+            )));
+        }
+    }
+    let staged = staging_path(exe);
+    download = download.output(&staged.to_string_lossy());
+    let installed = fetch_and_install(&release, &download, &staged, exe, &mut send);
+    if installed.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    installed?;
+    Ok(Outcome::Updated {
+        from: current.to_string(),
+        to: release.version,
+    })
+}
+
+fn get(url: &str, options: &Options) -> RequestConfig {
+    RequestConfig::new(url)
+        .follow_location(true)
+        .verbose(options.verbose)
+        .verbose_sink(options.verbose_sink.clone())
+}
+
+/// A file beside `exe`, so the final rename stays on one filesystem.
+fn staging_path(exe: &Path) -> PathBuf {
+    let name = exe.file_name().map_or("rustcurl".into(), |n| n.to_string_lossy());
+    exe.with_file_name(format!(".{name}.update-{}", std::process::id()))
+}
+
+fn fetch_and_install(
+    release: &Release,
+    download: &RequestConfig,
+    staged: &Path,
+    exe: &Path,
+    send: &mut impl FnMut(&RequestConfig) -> Result<Response, RequestError>,
+) -> Result<(), RequestError> {
+    let err = |message: String| RequestError::Update { message };
+    let response = send(download)?;
+    if !(200..300).contains(&response.status_code) {
+        return Err(err(format!("{}: server returned HTTP {}", release.url, response.status_code)));
+    }
+    let digest = sha256_hex(&fs::read(staged)?);
+    if digest != release.sha256 {
+        return Err(err(format!("{}: SHA-256 is {digest}, the manifest says {}", release.url, release.sha256)));
+    }
+    fs::set_permissions(staged, fs::metadata(exe)?.permissions())?;
+    install(staged, exe)?;
+    Ok(())
+}
+
+/// Rename `staged` over `exe`. Windows will not replace a running
+/// executable, but lets it be renamed out of the way first.
+fn install(staged: &Path, exe: &Path) -> std::io::Result<()> {
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)?;
+        if let Err(e) = fs::rename(staged, exe) {
+            let _ = fs::rename(&old, exe);
+            return Err(e);
+        }
+        Ok(())
+    } else {
+        fs::rename(staged, exe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(status: u32, body: &[u8]) -> Response {
+        Response {
+            status_code: status,
+            body: body.to_vec(),
+            attempts: vec![status],
+            ..Default::default()
+        }
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn manifest(sha256: &str) -> String {
+        let artifact = format!(r#"{{"url":"rustcurl-0.2.0","sha256":"{sha256}"}}"#);
+        format!(r#"{{"version":"0.2.0","artifacts":{{"{}":{artifact}}}}}"#, platform())
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = Options::parse(&args(&["--channel", "nightly", "--endpoint", "https://r.example/dl/"])).unwrap();
+        assert_eq!(options.manifest_url(), "https://r.example/dl/nightly/manifest.json");
+        assert_eq!(Options::parse(&[]).unwrap().channel, Channel::Stable);
+        assert!(Options::parse(&args(&["--channel", "beta"])).is_err());
+        assert!(Options::parse(&args(&["--verify-key"])).is_err());
+        assert!(Options::parse(&[]).unwrap().warnings().is_empty());
+        let unsigned = Options::parse(&args(&["--allow-unsigned", "--force"])).unwrap();
+        assert!(unsigned.force);
+        assert_eq!(unsigned.warnings()[0].code(), "unsigned_update");
+    }
+
+    #[test]
+    fn orders_semver() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        assert_eq!(v("v1.2.3+build.5"), Version { core: [1, 2, 3], pre: vec![] });
+        assert!(v("0.10.0") > v("0.9.9"));
+        assert!(v("1.0.0") > v("1.0.0-rc.1"));
+        assert!(v("1.0.0-rc.10") > v("1.0.0-rc.2"));
+        assert!(v("1.0.0-beta") > v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") > v("1.0.0-alpha"));
+        assert!(v("1.0.0-alpha.beta") > v("1.0.0-alpha.1"));
+        assert_eq!(v("1.0.0+a").cmp(&v("1.0.0+b")), Ordering::Equal);
+        for bad in ["1.2", "1.2.3.4", "x.1.2", "1.2.3-", "1.2.3-a..b", ""] {
+            assert_eq!(Version::parse(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn reads_platform_release() {
+        let body = br#"{"version":"1.4.0","artifacts":{"x86_64-linux":{"url":"v1.4.0/rustcurl",
+            "sha256":"AB12","signature":"https://sig.example/rustcurl.minisig"}}}"#;
+        let release = release("https://r.example/dl/stable/manifest.json", body, "x86_64-linux").unwrap();
+        assert_eq!(release.url, "https://r.example/dl/stable/v1.4.0/rustcurl");
+        assert_eq!(release.sha256, "ab12");
+        assert_eq!(release.signature.as_deref(), Some("https://sig.example/rustcurl.minisig"));
+        let missing = super::release("https://r.example/m.json", body, "aarch64-macos").unwrap_err();
+        assert_eq!(missing.code(), "update");
+    }
+
+    #[test]
+    fn replaces_binary_after_checksum() {
+        let dir = std::env::temp_dir().join(format!("rustcurl-update-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("rustcurl");
+        fs::write(&exe, b"old").unwrap();
+        let unsigned = Options::parse(&args(&["--endpoint", "https://r.example"])).unwrap();
+        let options = Options::parse(&args(&["--endpoint", "https://r.example", "--allow-unsigned"])).unwrap();
+        let serve = |sha256: String| {
+            move |c: &RequestConfig| {
+                if c.url.ends_with("manifest.json") {
+                    return Ok(reply(200, manifest(&sha256).as_bytes()));
+                }
+                assert_eq!(c.url, "https://r.example/stable/rustcurl-0.2.0");
+                fs::write(c.output_path().unwrap(), b"new").unwrap();
+                Ok(reply(200, b""))
+            }
+        };
+
+        // Without a key or --allow-unsigned nothing is downloaded
+        let refused = run(&unsigned, "0.1.0", &exe, serve(sha256_hex(b"new"))).unwrap_err();
+        assert!(refused.to_string().contains("--allow-unsigned"), "{refused}");
+        assert_eq!(fs::read(&exe).unwrap(), b"old");
+
+        let bad = run(&options, "0.1.0", &exe, serve("00".repeat(32))).unwrap_err();
+        assert!(bad.to_string().contains("SHA-256"), "{bad}");
+        assert_eq!(fs::read(&exe).unwrap(), b"old");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let outcome = run(&options, "0.1.0", &exe, serve(sha256_hex(b"new"))).unwrap();
+        assert_eq!(outcome, Outcome::Updated { from: "0.1.0".into(), to: "0.2.0".into() });
+        assert_eq!(fs::read(&exe).unwrap(), b"new");
+        assert_eq!(run(&options, "0.2.0", &exe, serve(sha256_hex(b"new"))).unwrap(), Outcome::UpToDate("0.2.0".into()));
+
+        // 0.2.0 is older than 0.10.0, though not as a string
+        let older = run(&options, "0.10.0", &exe, serve(sha256_hex(b"new"))).unwrap_err();
+        assert!(older.to_string().contains("older than the running 0.10.0"), "{older}");
+        let check = Options { check: true, ..options.clone() };
+        assert_eq!(run(&check, "0.10.0", &exe, serve(sha256_hex(b"new"))).unwrap(), Outcome::UpToDate("0.10.0".into()));
+        let force = Options { force: true, ..options };
+        let outcome = run(&force, "0.10.0", &exe, serve(sha256_hex(b"new"))).unwrap();
+        assert_eq!(outcome, Outcome::Updated { from: "0.10.0".into(), to: "0.2.0".into() });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+// src/curl/url.rs
+
+//! Minimal URL splitting. Backends do the real parsing; this is for the
+//! places rustcurl itself needs a host, port or path (cookies, DNS checks,
+//! output file names) and must not panic on arbitrary input.
+
+/// Borrowed components of `scheme://userinfo@host:port/path?query#fragment`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlParts<'a> {
+    pub scheme: Option<&'a str>,
+    pub userinfo: Option<&'a str>,
+    pub host: &'a str,
+    pub port: Option<u16>,
+    /// Path including the leading `/`, empty when absent.
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    pub fragment: Option<&'a str>,
+}
+
+/// Split a URL into its parts. A missing scheme is accepted, like curl
+/// does for `example.com/path`.
+pub fn parse(url: &str) -> Result<UrlParts<'_>, String> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let valid = scheme.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if !valid {
+                return Err(format!("invalid URL scheme: {scheme:?}"));
+            }
+            (Some(scheme), rest)
+        }
+        None => (None, url),
+    };
+
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((r, f)) => (r, Some(f)),
+        None => (rest, None),
+    };
+    let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(authority_end);
+    let (path, query) = match tail.split_once('?') {
+        Some((p, q)) => (p, Some(q)),
+        None => (tail, None),
+    };
+
+    let (userinfo, hostport) = match authority.rsplit_once('@') {
+        Some((u, h)) => (Some(u), h),
+        None => (None, authority),
+    };
+
+    let (host, port_str) = if let Some(bracketed) = hostport.strip_prefix('[') {
+        let (host, after) = bracketed
+            .split_once(']')
+            .ok_or_else(|| format!("unterminated IPv6 address in URL: {url}"))?;
+        let port = match after {
+            "" => None,
+            p => Some(
+                p.strip_prefix(':')
+                    .ok_or_else(|| format!("unexpected characters after IPv6 address: {after}"))?,
+            ),
+        };
+        (host, port)
+    } else {
+        match hostport.split_once(':') {
+            Some((h, p)) => (h, Some(p)),
+            None => (hostport, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(format!("URL has no host: {url}"));
+    }
+    let port = match port_str {
+        None | Some("") => None,
+        Some(p) => Some(p.parse::<u16>().map_err(|_| format!("invalid port in URL: {p}"))?),
+    };
+
+    Ok(UrlParts {
+        scheme,
+        userinfo,
+        host,
+        port,
+        path,
+        query,
+        fragment,
+    })
+}
+
+/// Host part of a URL, or an empty string when it cannot be parsed.
+pub fn host(url: &str) -> &str {
+    parse(url).map(|p| p.host).unwrap_or_default()
+}
+
+/// Last path segment, for naming `-O` downloads; `None` when the path is
+/// empty or ends in `/`, or the segment is `.` or `..`.
+pub fn file_name(url: &str) -> Option<&str> {
+    let path = parse(url).ok()?.path;
+    let name = &path[path.rfind('/')? + 1..];
+    (!name.is_empty() && name != "." && name != "..").then_some(name)
+}
+
+/// Resolve a `Location` value against the URL it was received from
+/// (RFC 3986 §5.2, without percent-encoding normalization).
+pub fn join(base: &str, reference: &str) -> String {
+    let has_scheme = reference
+        .split_once(':')
+        .is_some_and(|(s, _)| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')));
+    let Ok(parts) = parse(base) else {
+        return reference.to_string();
+    };
+    if has_scheme {
+        return reference.to_string();
+    }
+    if reference.starts_with('#') {
+        let without_fragment = base.split_once('#').map_or(base, |(b, _)| b);
+        return format!("{without_fragment}{reference}");
+    }
+    let scheme = parts.scheme.unwrap_or("http");
+    if let Some(rest) = reference.strip_prefix("//") {
+        return format!("{scheme}://{rest}");
+    }
+    let after_scheme = base.find("://").map_or(0, |i| i + 3);
+    let origin_end = base[after_scheme..].find(['/', '?', '#']).map_or(base.len(), |i| i + after_scheme);
+    let origin = &base[..origin_end];
+    let base_path = if parts.path.is_empty() { "/" } else { parts.path };
+
+    let (path, suffix) = match reference.find(['?', '#']) {
+        Some(i) => reference.split_at(i),
+        None => (reference, ""),
+    };
+    let path = if path.is_empty() {
+        base_path.to_string()
+    } else if path.starts_with('/') {
+        remove_dot_segments(path)
+    } else {
+        let dir = &base_path[..base_path.rfind('/').map_or(0, |i| i + 1)];
+        remove_dot_segments(&format!("{dir}{path}"))
+    };
+    format!("{origin}{path}{suffix}")
+}
+
+/// Add `query` to the URL's query string, ahead of any fragment.
+pub fn append_query(url: &str, query: &str) -> String {
+    if query.is_empty() {
+        return url.to_string();
+    }
+    let (head, fragment) = match url.find('#') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let sep = if !head.contains('?') {
+        "?"
+    } else if head.ends_with(['?', '&']) {
+        ""
+    } else {
+        "&"
+    };
+    format!("{head}{sep}{query}{fragment}")
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    let last = segments.len() - 1;
+    let mut out: Vec<&str> = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            "." | ".." => {
+                if *segment == ".." && out.len() > 1 {
+                    out.pop();
+                }
+                if i == last {
+                    out.push("");
+                }
+            }
+            s => out.push(s),
+        }
+    }
+    let joined = out.join("/");
+    if joined.starts_with('/') { joined } else { format!("/{joined}") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_url() {
+        let p = parse("https://user:pw@Example.com:8443/a/b?x=1#frag").unwrap();
+        assert_eq!(p.scheme, Some("https"));
+        assert_eq!(p.userinfo, Some("user:pw"));
+        assert_eq!(p.host, "Example.com");
+        assert_eq!(p.port, Some(8443));
+        assert_eq!(p.path, "/a/b");
+        assert_eq!(p.query, Some("x=1"));
+        assert_eq!(p.fragment, Some("frag"));
+    }
+
+    #[test]
+    fn ipv6_and_schemeless() {
+        let p = parse("http://[::1]:80/").unwrap();
+        assert_eq!(p.host, "::1");
+        assert_eq!(p.port, Some(80));
+        let p = parse("example.com/path").unwrap();
+        assert_eq!(p.scheme, None);
+        assert_eq!(p.host, "example.com");
+        assert_eq!(p.path, "/path");
+    }
+
+    #[test]
+    fn query_without_path() {
+        let p = parse("https://x.com?q=1").unwrap();
+        assert_eq!(p.host, "x.com");
+        assert_eq!(p.path, "");
+        assert_eq!(p.query, Some("q=1"));
+    }
+
+    #[test]
+    fn malformed_urls_are_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("https://").is_err());
+        assert!(parse("https://x.com:99999/").is_err());
+        assert!(parse("https://x.com:port/").is_err());
+        assert!(parse("http://[::1/").is_err());
+        assert!(parse("http://[::1]junk/").is_err());
+        assert!(parse("1ht!tp://x.com").is_err());
+    }
+
+    #[test]
+    fn join_references() {
+        let base = "http://a.com/b/c/d?q=1";
+        assert_eq!(join(base, "https://x.org/y"), "https://x.org/y");
+        assert_eq!(join(base, "//cdn.net/f"), "http://cdn.net/f");
+        assert_eq!(join(base, "/g"), "http://a.com/g");
+        assert_eq!(join(base, "g"), "http://a.com/b/c/g");
+        assert_eq!(join(base, "../g?x=2"), "http://a.com/b/g?x=2");
+        assert_eq!(join(base, "./"), "http://a.com/b/c/");
+        assert_eq!(join(base, "../../../../g"), "http://a.com/g");
+        assert_eq!(join(base, "?y"), "http://a.com/b/c/d?y");
+        assert_eq!(join(base, "#f"), "http://a.com/b/c/d?q=1#f");
+        assert_eq!(join("https://a.com", "x"), "https://a.com/x");
+        assert_eq!(join("https://a.com:8443?q", "/p"), "https://a.com:8443/p");
+    }
+
+    #[test]
+    fn append_query_strings() {
+        assert_eq!(append_query("http://a.com/p", "x=1"), "http://a.com/p?x=1");
+        assert_eq!(append_query("http://a.com/p?a=1", "x=1"), "http://a.com/p?a=1&x=1");
+        assert_eq!(append_query("http://a.com/p?", "x=1"), "http://a.com/p?x=1");
+        assert_eq!(append_query("http://a.com/p#top", "x=1"), "http://a.com/p?x=1#top");
+        assert_eq!(append_query("http://a.com/p", ""), "http://a.com/p");
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(file_name("https://x.com/dl/pkg-1.2.tar.gz?sig=abc#top"), Some("pkg-1.2.tar.gz"));
+        assert_eq!(file_name("https://x.com/dl/"), None);
+        assert_eq!(file_name("https://x.com"), None);
+        assert_eq!(file_name("https://x.com/a/.."), None);
+        assert_eq!(file_name("x.com/report"), Some("report"));
+    }
+
+    #[test]
+    fn host_helper_never_panics() {
+        assert_eq!(host("https://x.com:1/"), "x.com");
+        assert_eq!(host("http://[::1"), "");
+        assert_eq!(host("\u{0}@:"), "");
+    }
+}
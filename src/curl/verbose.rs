@@ -0,0 +1,70 @@
+// src/curl/verbose.rs
+
+//! Where the `* ...` notes `-v` adds go. The CLI leaves them on stderr;
+//! an embedder can collect them, log them elsewhere or drop them with
+//! `RequestConfig::verbose_sink`. libcurl's own `-v` trace is not
+//! included.
+
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Clone, Default)]
+pub enum VerboseSink {
+    /// `* message` lines on stderr, as the CLI prints them.
+    #[default]
+    Stderr,
+    /// Each message, without the `* ` prefix.
+    Custom(Arc<dyn Fn(&str) + Send + Sync>),
+}
+
+impl VerboseSink {
+    pub fn custom(write: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        VerboseSink::Custom(Arc::new(write))
+    }
+
+    pub fn write(&self, message: &str) {
+        match self {
+            VerboseSink::Stderr => eprintln!("* {message}"),
+            VerboseSink::Custom(write) => write(message),
+        }
+    }
+}
+
+impl fmt::Debug for VerboseSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerboseSink::Stderr => f.write_str("Stderr"),
+            VerboseSink::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Two sinks are equal when they print to stderr or call the same closure.
+impl PartialEq for VerboseSink {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (VerboseSink::Stderr, VerboseSink::Stderr) => true,
+            (VerboseSink::Custom(a), VerboseSink::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for VerboseSink {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn custom_sink_gets_each_message() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&seen);
+        let sink = VerboseSink::custom(move |m| collected.lock().unwrap().push(m.to_string()));
+        sink.write("DNS cache hit");
+        assert_eq!(*seen.lock().unwrap(), ["DNS cache hit"]);
+        assert_eq!(sink.clone(), sink);
+        assert_ne!(sink, VerboseSink::Stderr);
+    }
+}
@@ -0,0 +1,125 @@
+// src/curl/warning.rs
+
+//! Problems that do not fail a request but that the user should hear
+//! about. They are collected on the `Response`, printed by the CLI in the
+//! `--message-format` chosen and listed in `--batch` results.
+
+use std::fmt;
+
+use super::config::RequestConfig;
+use super::cookie::{self, CookieSource};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An option the backend cannot honor was left out.
+    IgnoredOption,
+    /// Certificate verification is turned off.
+    InsecureTls,
+    /// A `-b` cookie file line that is not a Netscape cookie line.
+    CookieParse,
+    /// A response header that could not be kept as received.
+    Header,
+    /// Multiple byte ranges saved as the multipart body they came in.
+    Ranges,
+    /// A `-O`/`-J` file name that had to be changed.
+    FileName,
+    /// `--session` totals that could not be updated.
+    Session,
+    /// A server's key was trusted on first use (`--tofu`).
+    Tofu,
+    /// A `--tail` poll that failed or found the resource truncated.
+    Tail,
+    /// A `--notify-cmd` or `--notify-desktop` hook that failed.
+    Notify,
+    /// `self-update --allow-unsigned`: a release checked by SHA-256 only.
+    UnsignedUpdate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Stable identifier for `--message-format json` and `--batch`.
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            WarningKind::IgnoredOption => "ignored_option",
+            WarningKind::InsecureTls => "insecure_tls",
+            WarningKind::CookieParse => "cookie_parse",
+            WarningKind::Header => "header",
+            WarningKind::Ranges => "ranges",
+            WarningKind::FileName => "file_name",
+            WarningKind::Session => "session",
+            WarningKind::Tofu => "tofu",
+            WarningKind::Tail => "tail",
+            WarningKind::Notify => "notify",
+            WarningKind::UnsignedUpdate => "unsigned_update",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Warnings that follow from `config` alone, whichever backend sends it.
+pub fn for_config(config: &RequestConfig) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if config.insecure {
+        warnings.push(Warning::new(
+            WarningKind::InsecureTls,
+            "certificate verification is disabled (-k); the server's identity was not checked",
+        ));
+    }
+    if config.proxy_insecure {
+        warnings.push(Warning::new(
+            WarningKind::InsecureTls,
+            "proxy certificate verification is disabled (--proxy-insecure)",
+        ));
+    }
+    for source in &config.cookies {
+        let CookieSource::File(path) = source else {
+            continue;
+        };
+        // An unreadable file is the request's business, not a warning
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        for line in cookie::malformed_lines(&content) {
+            warnings.push(Warning::new(
+                WarningKind::CookieParse,
+                format!("cookie file {path}, line {line}: not a Netscape cookie line; skipped"),
+            ));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insecure_and_cookie_file() {
+        let file = std::env::temp_dir().join(format!("rustcurl-warning-cookies-{}", std::process::id()));
+        std::fs::write(&file, "# Netscape HTTP Cookie File\n.x.com\tTRUE\t/\tFALSE\t0\ta\t1\nbroken line\n").unwrap();
+        let config = RequestConfig::new("https://x.com").insecure(true).cookie(file.to_str().unwrap());
+        let warnings = for_config(&config);
+        std::fs::remove_file(&file).unwrap();
+        let codes: Vec<&str> = warnings.iter().map(Warning::code).collect();
+        assert_eq!(codes, ["insecure_tls", "cookie_parse"]);
+        assert!(warnings[1].message.ends_with(", line 3: not a Netscape cookie line; skipped"));
+        assert!(for_config(&RequestConfig::new("https://x.com").cookie("a=1")).is_empty());
+    }
+}
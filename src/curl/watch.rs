@@ -0,0 +1,100 @@
+// src/curl/watch.rs
+
+//! `--watch`: one compact line per attempt (timestamp, status, total time,
+//! size), with `--watch-diff` flagging attempts whose body changed.
+
+use std::time::{Duration, SystemTime};
+
+use super::encoding::sha256_hex;
+use super::error::RequestError;
+use super::provenance::rfc3339;
+use super::response::Response;
+
+/// Formats attempt lines, remembering the last body for `--watch-diff`.
+pub struct Watcher {
+    diff: bool,
+    color: bool,
+    last_digest: Option<String>,
+}
+
+impl Watcher {
+    /// `color` highlights changed bodies with ANSI escapes.
+    pub fn new(diff: bool, color: bool) -> Self {
+        Watcher {
+            diff,
+            color,
+            last_digest: None,
+        }
+    }
+
+    /// The line for one attempt finished at `at`. `body` is what arrived,
+    /// from memory or the `-o` file; failed attempts leave the last body
+    /// in place so a transient error does not count as a change.
+    pub fn line(
+        &mut self,
+        at: SystemTime,
+        result: &Result<Response, RequestError>,
+        total: Duration,
+        body: &[u8],
+    ) -> String {
+        let (status, size) = match result {
+            Ok(response) => (response.status_code.to_string(), format!("{} B", body.len())),
+            Err(e) => (format!("ERR {}", e.code()), "-".to_string()),
+        };
+        let mut line = format!("{}  {status}  {:.3}s  {size}", rfc3339(at), total.as_secs_f64());
+        if self.diff && result.is_ok() {
+            let digest = sha256_hex(body);
+            if self.last_digest.as_ref().is_some_and(|last| *last != digest) {
+                line.push_str(if self.color { "  \x1b[1;33mchanged\x1b[0m" } else { "  changed" });
+            }
+            self.last_digest = Some(digest);
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::error::TimeoutPhase;
+    use std::time::UNIX_EPOCH;
+
+    fn ok(status: u32) -> Result<Response, RequestError> {
+        Ok(Response {
+            status_code: status,
+            attempts: vec![status],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn success_line() {
+        let mut w = Watcher::new(false, false);
+        let line = w.line(UNIX_EPOCH, &ok(200), Duration::from_millis(123), b"hello");
+        assert_eq!(line, "1970-01-01T00:00:00Z  200  0.123s  5 B");
+    }
+
+    #[test]
+    fn failure_line() {
+        let mut w = Watcher::new(true, false);
+        let err = Err(RequestError::Timeout {
+            phase: TimeoutPhase::Connect,
+            message: "slow".into(),
+        });
+        let line = w.line(UNIX_EPOCH, &err, Duration::from_secs(2), b"");
+        assert_eq!(line, "1970-01-01T00:00:00Z  ERR timeout  2.000s  -");
+    }
+
+    #[test]
+    fn diff_flags_changes_only() {
+        let mut w = Watcher::new(true, false);
+        assert!(!w.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"a").contains("changed"));
+        assert!(!w.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"a").contains("changed"));
+        assert!(w.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"b").ends_with("  changed"));
+        assert!(!w.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"b").contains("changed"));
+
+        let mut colored = Watcher::new(true, true);
+        colored.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"a");
+        assert!(colored.line(UNIX_EPOCH, &ok(200), Duration::ZERO, b"b").contains("\x1b[1;33m"));
+    }
+}
@@ -0,0 +1,280 @@
+// src/curl/writeout.rs
+
+//! `-w`/`--write-out`: a curl-style template such as
+//! `'%{http_code} %{time_total}\n'`, filled in after the transfer.
+//! `%header{name}` expands to a response header, `%%` to a percent sign,
+//! and `\n`, `\r`, `\t` and `\\` are unescaped.
+
+use std::time::Duration;
+
+use super::config::RequestConfig;
+use super::error::RequestError;
+use super::response::Response;
+
+/// Names accepted inside `%{...}`.
+pub const VARIABLES: &[&str] = &[
+    "alpn",
+    "content_type",
+    "errormsg",
+    "http_code",
+    "http_version",
+    "local_ip",
+    "local_port",
+    "num_connects",
+    "num_redirects",
+    "num_retries",
+    "remote_ip",
+    "remote_port",
+    "response_code",
+    "size_download",
+    "size_header",
+    "size_upload",
+    "speed_download",
+    "ssl_verify_result",
+    "time_appconnect",
+    "time_connect",
+    "time_namelookup",
+    "time_redirect",
+    "time_starttransfer",
+    "time_total",
+    "tls_cipher",
+    "tls_version",
+    "url",
+    "url_effective",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece {
+    Text(String),
+    Var(&'static str),
+    Header(String),
+}
+
+/// Split a template into text and variables; unknown variables and
+/// unterminated `%{` are errors.
+pub fn parse(template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("%{") {
+            let end = after.find('}').ok_or("unterminated %{ in --write-out")?;
+            let name = &after[..end];
+            let var = VARIABLES
+                .iter()
+                .find(|v| **v == name)
+                .ok_or_else(|| format!("unknown --write-out variable %{{{name}}}"))?;
+            pieces.extend((!text.is_empty()).then(|| Piece::Text(std::mem::take(&mut text))));
+            pieces.push(Piece::Var(var));
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix("%header{") {
+            let end = after.find('}').ok_or("unterminated %header{ in --write-out")?;
+            pieces.extend((!text.is_empty()).then(|| Piece::Text(std::mem::take(&mut text))));
+            pieces.push(Piece::Header(after[..end].to_string()));
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix("%%") {
+            text.push('%');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('\\')
+            && let Some(unescaped) = after.chars().next().and_then(unescape)
+        {
+            text.push(unescaped);
+            rest = &after[1..];
+        } else {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    pieces.extend((!text.is_empty()).then_some(Piece::Text(text)));
+    Ok(pieces)
+}
+
+fn unescape(c: char) -> Option<char> {
+    match c {
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        '\\' => Some('\\'),
+        _ => None,
+    }
+}
+
+/// Fill in `template` for a finished request. A failed request renders
+/// as curl does: `http_code` is `000` and sizes and times are zero.
+pub fn render(template: &[Piece], config: &RequestConfig, outcome: Result<&Response, &RequestError>) -> String {
+    let response = outcome.ok();
+    let transfer = response.and_then(|r| r.transfer.as_ref());
+    let timing = response.and_then(|r| r.timing.as_ref());
+    let secs = |pick: fn(&super::response::Timing) -> Duration| {
+        format!("{:.6}", timing.map_or(Duration::ZERO, pick).as_secs_f64())
+    };
+    let mut out = String::new();
+    for piece in template {
+        match piece {
+            Piece::Text(text) => out.push_str(text),
+            Piece::Header(name) => out.push_str(&response.and_then(|r| r.get_header(name)).unwrap_or_default()),
+            Piece::Var(name) => out.push_str(&match *name {
+                "alpn" => transfer.and_then(|t| t.alpn.clone()).unwrap_or_default(),
+                "content_type" => response.and_then(|r| r.get_header("content-type")).unwrap_or_default(),
+                "errormsg" => outcome.err().map(|e| e.to_string()).unwrap_or_default(),
+                "http_code" | "response_code" => format!("{:03}", response.map_or(0, |r| r.status_code)),
+                "http_version" => response.map_or("0".to_string(), http_version),
+                "local_ip" => transfer.and_then(|t| t.local_ip.clone()).unwrap_or_default(),
+                "local_port" => transfer.and_then(|t| t.local_port).map(|p| p.to_string()).unwrap_or_default(),
+                "num_connects" => transfer.and_then(|t| t.num_connects).unwrap_or(0).to_string(),
+                "num_redirects" => transfer.map_or(0, |t| t.num_redirects).to_string(),
+                "num_retries" => match outcome {
+                    Ok(r) => r.num_retries(),
+                    Err(e) => e.attempts().map_or(0, |a| a.len() - 1),
+                }
+                .to_string(),
+                "remote_ip" => transfer.and_then(|t| t.remote_ip.clone()).unwrap_or_default(),
+                "remote_port" => transfer.and_then(|t| t.remote_port).map(|p| p.to_string()).unwrap_or_default(),
+                "size_download" => transfer.map_or(0, |t| t.size_download).to_string(),
+                "size_header" => transfer.map_or(0, |t| t.size_header).to_string(),
+                "size_upload" => transfer.map_or(0, |t| t.size_upload).to_string(),
+                "speed_download" => {
+                    let total = timing.map_or(0.0, |t| t.total.as_secs_f64());
+                    let size = transfer.map_or(0, |t| t.size_download) as f64;
+                    format!("{:.0}", if total > 0.0 { size / total } else { 0.0 })
+                }
+                "ssl_verify_result" => transfer.and_then(|t| t.ssl_verify_result).unwrap_or(0).to_string(),
+                "time_appconnect" => secs(|t| t.tls),
+                "time_connect" => secs(|t| t.connect),
+                "time_namelookup" => secs(|t| t.dns),
+                "time_redirect" => secs(|t| t.redirect),
+                "time_starttransfer" => secs(|t| t.starttransfer),
+                "time_total" => secs(|t| t.total),
+                "tls_cipher" => transfer.and_then(|t| t.tls_cipher.clone()).unwrap_or_default(),
+                "tls_version" => transfer.and_then(|t| t.tls_version.clone()).unwrap_or_default(),
+                "url" => config.url.clone(),
+                "url_effective" => transfer
+                    .map(|t| t.url_effective.clone())
+                    .filter(|u| !u.is_empty())
+                    .unwrap_or_else(|| config.request_url().into_owned()),
+                _ => String::new(),
+            }),
+        }
+    }
+    out
+}
+
+/// `1.1`, `2` or `3`, as the backend reported it or else from the final
+/// status line.
+fn http_version(response: &Response) -> String {
+    if let Some(ref version) = response.http_version {
+        return version.clone();
+    }
+    response
+        .headers
+        .iter()
+        .rev()
+        .find_map(|h| h.strip_prefix("HTTP/"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .map_or("0".to_string(), |v| match v {
+            "2.0" | "3.0" => v[..1].to_string(),
+            _ => v.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curl::error::TimeoutPhase;
+    use crate::curl::response::{Timing, TransferInfo};
+
+    fn response() -> Response {
+        Response {
+            status_code: 200,
+            headers: vec![
+                "HTTP/1.1 301 Moved".into(),
+                "HTTP/2 200".into(),
+                "content-type: text/html".into(),
+                "x-id: 42".into(),
+            ],
+            body: b"hello".to_vec(),
+            timing: Some(Timing {
+                dns: Duration::from_millis(5),
+                connect: Duration::from_millis(10),
+                tls: Duration::from_millis(30),
+                starttransfer: Duration::from_millis(250),
+                total: Duration::from_millis(500),
+                redirect: Duration::ZERO,
+                tls_resumed: None,
+                early_data: None,
+                hops: Vec::new(),
+            }),
+            attempts: vec![200],
+            transfer: Some(TransferInfo {
+                url_effective: "https://x.com/b".into(),
+                size_download: 1000,
+                num_redirects: 1,
+                remote_ip: Some("10.0.0.1".into()),
+                remote_port: Some(443),
+                num_connects: Some(0),
+                tls_version: Some("TLSv1.3".into()),
+                alpn: Some("h2".into()),
+                ssl_verify_result: Some(0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn fill(template: &str, outcome: Result<&Response, &RequestError>) -> String {
+        render(&parse(template).unwrap(), &RequestConfig::new("https://x.com/a"), outcome)
+    }
+
+    #[test]
+    fn parses_text_vars_and_escapes() {
+        assert_eq!(
+            parse("%{http_code}\\t100%% %header{ETag}\\n").unwrap(),
+            vec![
+                Piece::Var("http_code"),
+                Piece::Text("\t100% ".into()),
+                Piece::Header("ETag".into()),
+                Piece::Text("\n".into()),
+            ]
+        );
+        assert_eq!(parse("50% \\d").unwrap(), vec![Piece::Text("50% \\d".into())]);
+        assert!(parse("%{bogus}").unwrap_err().contains("bogus"));
+        assert!(parse("%{http_code").is_err());
+    }
+
+    #[test]
+    fn renders_a_response() {
+        let r = response();
+        assert_eq!(fill("%{http_code} %{http_version} %{content_type}", Ok(&r)), "200 2 text/html");
+        assert_eq!(fill("%{time_total} %{time_namelookup}", Ok(&r)), "0.500000 0.005000");
+        assert_eq!(fill("%{size_download} %{speed_download} %{num_redirects}", Ok(&r)), "1000 2000 1");
+        assert_eq!(fill("%{url} -> %{url_effective}", Ok(&r)), "https://x.com/a -> https://x.com/b");
+        assert_eq!(fill("%{remote_ip}:%{remote_port} %header{X-Id}", Ok(&r)), "10.0.0.1:443 42");
+        assert_eq!(
+            fill("%{tls_version} %{alpn} %{ssl_verify_result} %{num_connects} [%{local_ip}]", Ok(&r)),
+            "TLSv1.3 h2 0 0 []"
+        );
+    }
+
+    #[test]
+    fn renders_a_failure() {
+        let err = RequestError::Timeout {
+            phase: TimeoutPhase::Connect,
+            message: "slow".into(),
+        };
+        assert_eq!(fill("%{http_code} %{time_total} %{size_download}", Err(&err)), "000 0.000000 0");
+        assert!(fill("%{errormsg}", Err(&err)).contains("slow"));
+    }
+
+    #[test]
+    fn counts_retries() {
+        let mut r = response();
+        assert_eq!(fill("%{num_retries}", Ok(&r)), "0");
+        r.attempts = vec![503, 0, 200];
+        assert_eq!(fill("%{num_retries}", Ok(&r)), "2");
+        let err = RequestError::Retried {
+            attempts: vec![503, 503],
+            last: Box::new(RequestError::Connect { message: "refused".into() }),
+        };
+        assert_eq!(fill("%{num_retries} %{http_code}", Err(&err)), "1 000");
+    }
+}
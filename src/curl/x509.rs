@@ -0,0 +1,314 @@
+// src/curl/x509.rs
+
+//! `--show-certs`: subject, issuer, subject alternative names, validity
+//! and SHA-256 fingerprint of each certificate the server presented,
+//! read from DER. Only the fields printed are decoded; signatures are
+//! libcurl's or native-tls's business.
+
+use sha2::{Digest, Sha256};
+
+/// The printed fields of one certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub subject: String,
+    pub issuer: String,
+    /// `DNS:`, `IP:`, `email:` and `URI:` entries of the SAN extension.
+    pub sans: Vec<String>,
+    pub not_before: String,
+    pub not_after: String,
+    pub serial: String,
+    /// Colon-separated uppercase hex, as browsers show it.
+    pub sha256: String,
+}
+
+/// One DER element: its tag, its contents and what follows it.
+fn element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let len = rest[..count].iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+        (len, &rest[count..])
+    };
+    (rest.len() >= len).then(|| (tag, &rest[..len], &rest[len..]))
+}
+
+/// The elements of a constructed value's contents.
+fn elements(mut input: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut out = Vec::new();
+    while let Some((tag, content, rest)) = element(input) {
+        out.push((tag, content));
+        input = rest;
+    }
+    out
+}
+
+fn oid(content: &[u8]) -> String {
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut value = 0u64;
+    for &b in content {
+        value = value << 7 | u64::from(b & 0x7f);
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter().map(u64::to_string).collect::<Vec<_>>().join(".")
+}
+
+fn attribute_name(oid: &str) -> &str {
+    match oid {
+        "2.5.4.3" => "CN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "1.2.840.113549.1.9.1" => "emailAddress",
+        other => other,
+    }
+}
+
+/// A directory string; BMPString is UTF-16BE, the rest are read as UTF-8.
+fn text(tag: u8, content: &[u8]) -> String {
+    if tag == 0x1e {
+        let units: Vec<u16> = content.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(content).into_owned()
+    }
+}
+
+/// A Name as `C=US, O=Example, CN=example.com`, in encoded order.
+fn name(content: &[u8]) -> String {
+    let mut parts = Vec::new();
+    for (_, set) in elements(content) {
+        for (_, pair) in elements(set) {
+            if let [(0x06, id), (tag, value), ..] = elements(pair)[..] {
+                parts.push(format!("{}={}", attribute_name(&oid(id)), text(tag, value)));
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+/// UTCTime or GeneralizedTime as RFC 3339.
+fn time(tag: u8, content: &[u8]) -> Option<String> {
+    let digits = std::str::from_utf8(content).ok()?.strip_suffix('Z')?;
+    let full = match tag {
+        0x17 if digits.len() == 12 => {
+            let year: u32 = digits[..2].parse().ok()?;
+            format!("{}{digits}", if year >= 50 { "19" } else { "20" })
+        }
+        0x18 if digits.len() == 14 => digits.to_string(),
+        _ => return None,
+    };
+    if !full.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let f = |range: std::ops::Range<usize>| &full[range];
+    Some(format!("{}-{}-{}T{}:{}:{}Z", f(0..4), f(4..6), f(6..8), f(8..10), f(10..12), f(12..14)))
+}
+
+fn general_names(content: &[u8]) -> Vec<String> {
+    let Some((0x30, names, _)) = element(content) else {
+        return Vec::new();
+    };
+    elements(names)
+        .into_iter()
+        .filter_map(|(tag, value)| match tag {
+            0x81 => Some(format!("email:{}", String::from_utf8_lossy(value))),
+            0x82 => Some(format!("DNS:{}", String::from_utf8_lossy(value))),
+            0x86 => Some(format!("URI:{}", String::from_utf8_lossy(value))),
+            0x87 => ip(value).map(|ip| format!("IP:{ip}")),
+            _ => None,
+        })
+        .collect()
+}
+
+fn ip(bytes: &[u8]) -> Option<std::net::IpAddr> {
+    match bytes.len() {
+        4 => Some(<[u8; 4]>::try_from(bytes).ok()?.into()),
+        16 => Some(<[u8; 16]>::try_from(bytes).ok()?.into()),
+        _ => None,
+    }
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>().join(":")
+}
+
+/// The printed fields of a DER certificate; `None` if it is not one.
+pub fn summarize(der: &[u8]) -> Option<Summary> {
+    let (0x30, certificate, _) = element(der)? else {
+        return None;
+    };
+    let (0x30, tbs, _) = element(certificate)? else {
+        return None;
+    };
+    let mut fields = elements(tbs);
+    // The version is optional and explicitly tagged [0]
+    if fields.first().is_some_and(|(tag, _)| *tag == 0xa0) {
+        fields.remove(0);
+    }
+    let [(0x02, serial), (0x30, _), (0x30, issuer), (0x30, validity), (0x30, subject), (0x30, _), ref extra @ ..] =
+        fields[..]
+    else {
+        return None;
+    };
+    let [(before_tag, before), (after_tag, after)] = elements(validity)[..] else {
+        return None;
+    };
+    let mut sans = Vec::new();
+    if let Some((_, extensions)) = extra.iter().find(|(tag, _)| *tag == 0xa3)
+        && let Some((0x30, list, _)) = element(extensions)
+    {
+        for (_, extension) in elements(list) {
+            let parts = elements(extension);
+            if let (Some((0x06, id)), Some((0x04, value))) = (parts.first(), parts.last())
+                && oid(id) == "2.5.29.17"
+            {
+                sans = general_names(value);
+            }
+        }
+    }
+    Some(Summary {
+        subject: name(subject),
+        issuer: name(issuer),
+        sans,
+        not_before: time(before_tag, before)?,
+        not_after: time(after_tag, after)?,
+        serial: colon_hex(serial.strip_prefix(&[0]).filter(|s| !s.is_empty()).unwrap_or(serial)),
+        sha256: colon_hex(&Sha256::digest(der)),
+    })
+}
+
+/// The DER of a certificate's SubjectPublicKeyInfo, which key pins
+/// (`--tofu`, libcurl's `sha256//` pins) are a hash of.
+pub fn public_key_info(der: &[u8]) -> Option<&[u8]> {
+    let (0x30, certificate, _) = element(der)? else {
+        return None;
+    };
+    let (0x30, mut tbs, _) = element(certificate)? else {
+        return None;
+    };
+    // After the optional version: serial, signature, issuer, validity,
+    // subject, then the key
+    let mut field = 0;
+    while let Some((tag, _, rest)) = element(tbs) {
+        if !(field == 0 && tag == 0xa0) {
+            field += 1;
+        }
+        if field == 6 {
+            return (tag == 0x30).then(|| &tbs[..tbs.len() - rest.len()]);
+        }
+        tbs = rest;
+    }
+    None
+}
+
+/// The `--show-certs` report for a chain, leaf first, as `*` lines.
+pub fn render(chain: &[Vec<u8>]) -> String {
+    if chain.is_empty() {
+        return "* No server certificate (not TLS, or not reported by the backend)\n".to_string();
+    }
+    let mut out = String::new();
+    for (i, der) in chain.iter().enumerate() {
+        let Some(cert) = summarize(der) else {
+            out.push_str(&format!("* Certificate {i}: could not be decoded ({} bytes)\n", der.len()));
+            continue;
+        };
+        out.push_str(&format!("* Certificate {i}: {}\n", cert.subject));
+        out.push_str(&format!("*   issuer:  {}\n", cert.issuer));
+        if !cert.sans.is_empty() {
+            out.push_str(&format!("*   SANs:    {}\n", cert.sans.join(", ")));
+        }
+        out.push_str(&format!("*   valid:   {} to {}\n", cert.not_before, cert.not_after));
+        out.push_str(&format!("*   serial:  {}\n", cert.serial));
+        out.push_str(&format!("*   sha256:  {}\n", cert.sha256));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match content.len() {
+            n if n < 0x80 => out.push(n as u8),
+            n => out.extend([0x82, (n >> 8) as u8, n as u8]),
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn rdn(oid: &[u8], value: &str) -> Vec<u8> {
+        tlv(0x31, &tlv(0x30, &[tlv(0x06, oid), tlv(0x0c, value.as_bytes())].concat()))
+    }
+
+    /// A certificate with only the fields `summarize` reads filled in.
+    fn certificate() -> Vec<u8> {
+        let cn = [0x55, 0x04, 0x03];
+        let org = [0x55, 0x04, 0x0a];
+        let subject = tlv(0x30, &rdn(&cn, "example.com"));
+        let issuer = tlv(0x30, &[rdn(&org, "Test CA"), rdn(&cn, "Test Root")].concat());
+        let validity = tlv(0x30, &[tlv(0x17, b"260101000000Z"), tlv(0x18, b"20270630235959Z")].concat());
+        let names = [tlv(0x82, b"example.com"), tlv(0x82, b"*.example.com"), tlv(0x87, &[127, 0, 0, 1])].concat();
+        let san = tlv(0x30, &[tlv(0x06, &[0x55, 0x1d, 0x11]), tlv(0x04, &tlv(0x30, &names))].concat());
+        let extensions = tlv(0xa3, &tlv(0x30, &san));
+        let algorithm = tlv(0x30, &tlv(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02]));
+        let tbs = [
+            tlv(0xa0, &tlv(0x02, &[2])),
+            tlv(0x02, &[0x00, 0x9f, 0x01]),
+            algorithm.clone(),
+            issuer,
+            validity,
+            subject,
+            tlv(0x30, &[]),
+            extensions,
+        ]
+        .concat();
+        tlv(0x30, &[tlv(0x30, &tbs), algorithm, tlv(0x03, &[0])].concat())
+    }
+
+    #[test]
+    fn summarizes_fields() {
+        let der = certificate();
+        let cert = summarize(&der).unwrap();
+        assert_eq!(cert.subject, "CN=example.com");
+        assert_eq!(cert.issuer, "O=Test CA, CN=Test Root");
+        assert_eq!(cert.sans, ["DNS:example.com", "DNS:*.example.com", "IP:127.0.0.1"]);
+        assert_eq!(cert.not_before, "2026-01-01T00:00:00Z");
+        assert_eq!(cert.not_after, "2027-06-30T23:59:59Z");
+        assert_eq!(cert.serial, "9F:01");
+        assert_eq!(cert.sha256.len(), 32 * 3 - 1);
+        assert!(summarize(&der[..40]).is_none());
+        assert!(summarize(b"not a certificate").is_none());
+        assert_eq!(public_key_info(&der), Some(&[0x30, 0][..]));
+        assert_eq!(public_key_info(&der[..40]), None);
+    }
+
+    #[test]
+    fn decodes_oids_and_times() {
+        assert_eq!(oid(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01]), "1.2.840.113549.1.9.1");
+        assert_eq!(time(0x17, b"991231235959Z").as_deref(), Some("1999-12-31T23:59:59Z"));
+        assert_eq!(time(0x17, b"99123123595Z"), None);
+        let report = render(&[certificate(), b"junk".to_vec()]);
+        assert!(report.starts_with("* Certificate 0: CN=example.com\n*   issuer:  O=Test CA, CN=Test Root\n"));
+        assert!(report.ends_with("* Certificate 1: could not be decoded (4 bytes)\n"));
+    }
+}
@@ -0,0 +1,10 @@
+// src/lib.rs
+
+//! rustcurl as a library: request configuration, backends and responses.
+//! The `rustcurl` binary is a thin CLI on top of these modules.
+
+pub mod backend;
+pub mod client;
+pub mod curl;
+
+pub use client::Client;
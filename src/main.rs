@@ -1,61 +1,415 @@
 // src/main.rs
 
-mod curl;
-mod backend;
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use rustcurl::{Client, curl};
 
 fn main() {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let config = match curl::parse_args(&args) {
-        Ok(config) => config,
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "session") {
+        std::process::exit(session_command(&args[1..]));
+    }
+    if args.first().is_some_and(|a| a == "trust") {
+        std::process::exit(trust_command(&args[1..]));
+    }
+    if args.first().is_some_and(|a| a == "generate-docs") {
+        match args.get(1).map(String::as_str) {
+            Some("man") if args.len() == 2 => print!("{}", curl::docs::man()),
+            Some("markdown") if args.len() == 2 => print!("{}", curl::docs::markdown()),
+            _ => {
+                eprintln!("Usage: rustcurl generate-docs man|markdown");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if args.first().is_some_and(|a| a == "capabilities") {
+        let json = match &args[1..] {
+            [] => false,
+            [flag] if flag == "--json" => true,
+            _ => {
+                eprintln!("Usage: rustcurl capabilities [--json]");
+                std::process::exit(1);
+            }
+        };
+        let caps = Client::new().backend().capabilities();
+        let subsystems = curl::capabilities::subsystems(&curl::env::EnvSource::Process);
+        if json {
+            println!("{}", curl::capabilities::to_json(&caps, &subsystems));
+        } else {
+            print!("{}", curl::capabilities::render(&caps, &subsystems));
+        }
+        return;
+    }
+    if args.first().is_some_and(|a| a == "self-update") {
+        std::process::exit(self_update_command(&args[1..]));
+    }
+    // `rustcurl config show [OPTIONS] <URL>` is `--print-config`
+    if args.first().is_some_and(|a| a == "config") {
+        if args.get(1).is_none_or(|a| a != "show") {
+            eprintln!("Usage: rustcurl config show [OPTIONS] <URL>");
+            std::process::exit(1);
+        }
+        args.drain(..2);
+        args.push("--print-config".to_string());
+    }
+    // Options from ~/.curlrc go first, so the command line's override them
+    let curlrc = if args.is_empty() || curl::curlrc::skipped(&args) {
+        None
+    } else {
+        curl::curlrc::find(&curl::env::EnvSource::Process)
+    };
+    let rc_text = curlrc.as_ref().map(|path| match std::fs::read_to_string(path) {
+        Ok(text) => text,
         Err(e) => {
-            eprintln!("Error: {e}");
+            eprintln!("Error: {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    });
+    let args = match rc_text {
+        Some(ref text) => match curl::curlrc::prepend(text, &args) {
+            Ok(combined) => combined,
+            Err(e) => {
+                eprintln!("Error: {}: {e}", curlrc.as_ref().map_or(String::new(), |p| p.display().to_string()));
+                std::process::exit(1);
+            }
+        },
+        None => args,
+    };
+    let groups = match curl::parse_groups(&args) {
+        Ok(groups) => groups,
+        Err(e) => {
+            match curlrc {
+                Some(ref path) => eprintln!("Error: {e} (with the options in {}; -q skips them)", path.display()),
+                None => eprintln!("Error: {e}"),
+            }
             curl::print_usage();
             std::process::exit(1);
         }
     };
 
-    let backend = backend::get_backend();
+    // Relay, --batch, --watch and --tail never come with --next groups
+    let config = &groups[0];
+    let client = Client::new();
 
-    match backend.perform_request(&config) {
-        Ok(response) => {
-            if config.silent {
-                if config.output.is_none() {
-                    print!("{}", response.body_string());
-                }
-            } else if config.head_only {
-                println!("Status: {}", response.status_code);
-                println!();
-                for header in &response.headers {
-                    println!("{header}");
-                }
-                if let Some(ref timing) = response.timing {
-                    println!();
-                    print!("{timing}");
+    if config.print_config {
+        // The curlrc's options on their own, to tell its values apart
+        let from_curlrc = rc_text.as_deref().and_then(|text| {
+            let mut options = curl::curlrc::parse(text).ok()?;
+            options.push(config.url.clone());
+            curl::parse_args(&options).ok()
+        });
+        let settings = curl::effective::settings(config, from_curlrc.as_ref());
+        print!("{}", curl::effective::render(&settings, curlrc.as_deref()));
+        return;
+    }
+
+    if let Some(ref source) = config.batch {
+        let done = if source == "-" {
+            client.batch(config, std::io::stdin().lock(), std::io::stdout().lock())
+        } else {
+            std::fs::File::open(source)
+                .and_then(|file| client.batch(config, std::io::BufReader::new(file), std::io::stdout().lock()))
+        };
+        if let Err(e) = done {
+            eprintln!("Error: --batch {source}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(ref path) = config.collection {
+        let passed = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| curl::collection::Collection::parse(&text))
+            .and_then(|steps| client.collection(config, &steps, std::io::stdout().lock()).map_err(|e| e.to_string()));
+        match passed {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Error: --collection {path}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(ref addr) = config.relay {
+        let served = std::net::TcpListener::bind(addr.as_str()).and_then(|listener| {
+            curl::relay::check_listener(&listener, config.relay_remote)?;
+            if !config.silent {
+                eprintln!("Relaying on {}", listener.local_addr()?);
+            }
+            client.relay(config, listener)
+        });
+        if let Err(e) = served {
+            eprintln!("Error: relay on {addr}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if config.watch.is_some() {
+        let color = std::io::stdout().is_terminal();
+        if let Err(e) = client.watch(config, None, color, std::io::stdout()) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if config.tail.is_some() {
+        if let Err(e) = client.tail(config, None, std::io::stdout().lock()) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // One client for every URL, so later requests reuse its connections
+    // and TLS sessions; see `changed::run_status` for the exit status
+    let mut statuses = Vec::new();
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let mut confirmed_hosts = HashSet::new();
+    for config in groups.iter().flat_map(curl::config::RequestConfig::each_url) {
+        if interactive && !config.assume_yes && !confirmed(&client, &config, &mut confirmed_hosts) {
+            eprintln!("Skipped {}: not confirmed", config.url);
+            statuses.push(1);
+            continue;
+        }
+        if let Some(expires) = config.presign {
+            statuses.push(match client.presign(&config, expires) {
+                Ok(url) => {
+                    println!("{url}");
+                    0
                 }
-            } else if config.output.is_some() {
-                println!("Status: {}", response.status_code);
-                println!();
-                for header in &response.headers {
-                    println!("{header}");
+                Err(e) => report(&config, &e),
+            });
+            continue;
+        }
+        // Large bodies go straight to stdout rather than through memory
+        let config = if curl::output::can_stream(&config) {
+            config.output("-")
+        } else {
+            config
+        };
+        statuses.push(fetch(&client, &config));
+    }
+    let status = curl::changed::run_status(&statuses);
+    if status != 0 {
+        std::process::exit(status);
+    }
+}
+
+/// Ask before `config` goes out with certificate checks off for a remote
+/// host, showing the server's certificates first. Each host is asked
+/// about once per run.
+fn confirmed(client: &Client, config: &curl::config::RequestConfig, asked: &mut HashSet<String>) -> bool {
+    let risks: Vec<_> = curl::confirm::risks(config).into_iter().filter(|r| !asked.contains(&r.host)).collect();
+    if risks.is_empty() {
+        return true;
+    }
+    // A HEAD with the same options, straight to the backend so nothing is
+    // saved or recorded, fetches the chain
+    let mut probe = curl::diagnose::probe_config(config).show_certs(true);
+    probe.cookie_jar = None;
+    let cap = Duration::from_secs(5);
+    probe.max_time = Some(probe.max_time.map_or(cap, |t| t.min(cap)));
+    match client.backend().perform_request(&probe) {
+        Ok(response) => eprint!("{}", curl::x509::render(&response.peer_certificates)),
+        Err(e) => eprintln!("* Could not fetch the certificate chain: {e}"),
+    }
+    for risk in risks {
+        let yes = curl::confirm::ask(&risk.question(), &mut std::io::stdin().lock(), &mut std::io::stderr());
+        if !yes.unwrap_or(false) {
+            return false;
+        }
+        asked.insert(risk.host);
+    }
+    true
+}
+
+/// `rustcurl session stats NAME`: print the totals `--session NAME` kept;
+/// returns the exit status.
+fn session_command(args: &[String]) -> i32 {
+    let [command, name] = args else {
+        eprintln!("Usage: rustcurl session stats <NAME>");
+        return 1;
+    };
+    if command != "stats" {
+        eprintln!("Error: unknown session command '{command}' (stats)");
+        return 1;
+    }
+    let loaded = curl::session::check_name(name)
+        .map_err(std::io::Error::other)
+        .and_then(|()| curl::session::load(&curl::env::EnvSource::Process, name));
+    match loaded {
+        Ok(stats) => {
+            print!("{}", curl::session::render(name, &stats));
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    }
+}
+
+fn trust_command(args: &[String]) -> i32 {
+    use curl::tofu;
+    let usage = "Usage: rustcurl trust list | rustcurl trust remove <HOST:PORT>";
+    let store = match tofu::store(&curl::env::EnvSource::Process) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return 1;
+        }
+    };
+    let result = match args {
+        [command] if command == "list" => tofu::load(&store).map(|entries| {
+            if entries.is_empty() {
+                eprintln!("No trusted hosts in {}", store.display());
+            }
+            print!("{}", tofu::render(&entries));
+            true
+        }),
+        [command, authority] if command == "remove" => tofu::remove(&store, authority).inspect(|&removed| {
+            if removed {
+                println!("Removed {authority}");
+            } else {
+                eprintln!("{authority} is not trusted");
+            }
+        }),
+        _ => {
+            eprintln!("{usage}");
+            return 1;
+        }
+    };
+    match result {
+        Ok(found) => i32::from(!found),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    }
+}
+
+fn self_update_command(args: &[String]) -> i32 {
+    let options = match curl::update::Options::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            eprintln!("Usage: rustcurl self-update [--channel stable|nightly] [--endpoint URL]");
+            eprintln!("                           [--verify-key KEY | --allow-unsigned] [--force] [--check] [-v]");
+            return 1;
+        }
+    };
+    let locale = curl::messages::Locale::from_env();
+    for warning in options.warnings() {
+        let format = curl::messages::MessageFormat::Short;
+        eprintln!("{}", curl::messages::render_warning(&warning, format, locale));
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Error: cannot locate the running binary: {e}");
+            return 1;
+        }
+    };
+    let client = Client::new();
+    let current = env!("CARGO_PKG_VERSION");
+    match curl::update::run(&options, current, &exe, |c| client.send(c)) {
+        Ok(curl::update::Outcome::UpToDate(version)) => {
+            eprintln!("rustcurl {version} is the latest {} release", options.channel.as_str());
+            0
+        }
+        Ok(curl::update::Outcome::Available(version)) => {
+            eprintln!("rustcurl {version} is available on {} (running {current})", options.channel.as_str());
+            0
+        }
+        Ok(curl::update::Outcome::Updated { from, to }) => {
+            eprintln!("Updated {} from {from} to {to}", exe.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    }
+}
+
+/// Send one request and write out its response; returns the exit status,
+/// `changed::EXIT_UNCHANGED` when `--if-changed` found nothing new.
+fn fetch(client: &Client, config: &curl::config::RequestConfig) -> i32 {
+    match client.send(config) {
+        Ok(response) => {
+            if config.show_certs {
+                eprint!("{}", curl::x509::render(&response.peer_certificates));
+            }
+            if config.retry_report && !response.attempts.is_empty() {
+                eprintln!("{}", curl::retry::retry_report(&response.attempts));
+            }
+            if !config.silent {
+                let locale = curl::messages::Locale::from_env();
+                for warning in &response.warnings {
+                    eprintln!("{}", curl::messages::render_warning(warning, config.message_format, locale));
                 }
-                if let Some(ref path) = config.output {
-                    println!();
-                    println!("Body written to {path}");
+            }
+            if let Some(ref state) = config.if_changed {
+                let body = response.received_body();
+                match curl::changed::update(state, &body) {
+                    Ok(true) => {}
+                    Ok(false) => return curl::changed::EXIT_UNCHANGED,
+                    Err(e) => return report(config, &e),
                 }
-                if let Some(ref timing) = response.timing {
-                    println!();
-                    print!("{timing}");
+            }
+            if let Some(ref path) = response.saved_as
+                && !config.silent
+            {
+                eprintln!("Body written to {}", path.display());
+            }
+            if let Some(ref transfer) = response.transfer
+                && !config.silent
+            {
+                for pushed in &transfer.pushed {
+                    eprintln!("Pushed {pushed}");
                 }
-            } else {
-                print!("{response}");
             }
+            // A closed pipe (e.g. `| head`) is not an error worth reporting
+            let _ = curl::output::write(&mut std::io::stdout().lock(), config, &response);
+            if let Some(ref path) = config.capture_file
+                && let Err(e) = curl::capture::write(path, &config.captures, &response)
+            {
+                return report(config, &e);
+            }
+            if curl::retry::exhausted(config, &response) {
+                return curl::retry::EXIT_RETRIES_EXHAUSTED;
+            }
+            0
         }
+        // A closed pipe (e.g. `| head`) while streaming, as above
+        Err(curl::error::RequestError::Io(ref e)) if e.kind() == std::io::ErrorKind::BrokenPipe => 0,
         Err(e) => {
-            eprintln!("Request failed: {e}");
-            if let Some(hint) = e.hint() {
-                eprintln!("{hint}");
+            if config.retry_report {
+                eprintln!("{}", curl::retry::retry_report(e.attempts().unwrap_or(&[0])));
             }
-            std::process::exit(1);
+            if config.diagnose && e.is_connection_failure() {
+                eprint!("{}", client.diagnose(config));
+            }
+            report(config, &e)
         }
     }
 }
+
+/// Print `e` (and any `-w` output) and return the exit status for it.
+fn report(config: &curl::config::RequestConfig, e: &curl::error::RequestError) -> i32 {
+    if let Some(ref template) = config.write_out
+        && let Ok(template) = curl::writeout::parse(template)
+    {
+        print!("{}", curl::writeout::render(&template, config, Err(e)));
+    }
+    let locale = curl::messages::Locale::from_env();
+    eprintln!("{}", curl::messages::render_error(e, config.message_format, locale));
+    e.exit_code()
+}
@@ -0,0 +1,65 @@
+// tests/auth_chain.rs
+
+//! `--auth-chain`: a 401 moves on to the next mechanism the server
+//! offers, for every compiled-in backend.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::auth::Mechanism;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn escalates_from_bearer_to_basic() {
+    for client in clients() {
+        let server = TestServer::with_responses(vec![
+            CannedResponse {
+                status: 401,
+                headers: vec!["WWW-Authenticate: Basic realm=\"test\"".into()],
+                ..Default::default()
+            },
+            CannedResponse::default(),
+        ]);
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/"))
+            .noproxy("*")
+            .bearer("tok")
+            .username("user")
+            .password("pass")
+            .auth_chain(vec![Mechanism::Bearer, Mechanism::Basic]);
+        let response = client.send(&config).unwrap();
+        assert_eq!(response.status_code, 200, "{name}");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2, "{name}");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer tok"), "{name}");
+        assert_eq!(requests[1].header("authorization"), Some("Basic dXNlcjpwYXNz"), "{name}");
+    }
+}
+
+#[test]
+fn stops_when_nothing_offered_is_left() {
+    let server = TestServer::with_response(CannedResponse {
+        status: 401,
+        headers: vec!["WWW-Authenticate: Digest realm=\"test\", nonce=\"n\"".into()],
+        ..Default::default()
+    });
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .username("user")
+        .auth_chain(vec![Mechanism::Basic, Mechanism::Ntlm]);
+    let response = Client::new().send(&config).unwrap();
+    assert_eq!(response.status_code, 401);
+    assert_eq!(server.requests().len(), 1);
+}
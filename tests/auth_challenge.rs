@@ -0,0 +1,81 @@
+// tests/auth_challenge.rs
+
+//! `--auth-preemptive` vs `--auth-on-challenge`: when Basic credentials
+//! go out, for every compiled-in backend.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+fn challenge_then_ok() -> TestServer {
+    TestServer::with_responses(vec![
+        CannedResponse {
+            status: 401,
+            headers: vec!["WWW-Authenticate: Basic realm=\"test\"".into()],
+            ..Default::default()
+        },
+        CannedResponse::default(),
+    ])
+}
+
+#[test]
+fn preemptive_by_default() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/")).noproxy("*").username("user").password("pass");
+        client.send(&config).unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1, "{name}");
+        assert_eq!(requests[0].header("authorization"), Some("Basic dXNlcjpwYXNz"), "{name}");
+    }
+}
+
+#[test]
+fn on_challenge_waits_for_401() {
+    for client in clients() {
+        let server = challenge_then_ok();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/"))
+            .noproxy("*")
+            .username("user")
+            .password("pass")
+            .auth_on_challenge(true);
+        let response = client.send(&config).unwrap();
+        assert_eq!(response.status_code, 200, "{name}");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2, "{name}");
+        assert_eq!(requests[0].header("authorization"), None, "{name}");
+        assert_eq!(requests[1].header("authorization"), Some("Basic dXNlcjpwYXNz"), "{name}");
+    }
+}
+
+#[test]
+fn on_challenge_without_challenge_sends_nothing() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/"))
+            .noproxy("*")
+            .username("user")
+            .password("pass")
+            .auth_on_challenge(true);
+        assert_eq!(client.send(&config).unwrap().status_code, 200, "{name}");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1, "{name}");
+        assert_eq!(requests[0].header("authorization"), None, "{name}");
+    }
+}
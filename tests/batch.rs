@@ -0,0 +1,55 @@
+// tests/batch.rs
+
+//! `--batch`: JSONL request specs in, one JSON summary line out per
+//! request, for every compiled-in backend.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn one_summary_per_spec() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let input = format!(
+            "{{\"id\":1,\"url\":\"{}\"}}\n\nnot json\n{{\"id\":2,\"url\":\"{}\",\"data\":\"a=1\",\"headers\":[\"X-Step: 2\"]}}\n",
+            server.url("/one"),
+            server.url("/two"),
+        );
+        let base = RequestConfig::new("").noproxy("*").header("X-Run: r1");
+        let mut out = Vec::new();
+        client.batch(&base, input.as_bytes(), &mut out).unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3, "{name}");
+        assert_eq!((lines[0]["id"].clone(), lines[0]["status"].clone()), (1.into(), 200.into()), "{name}");
+        assert_eq!(lines[0]["body"], "ok", "{name}");
+        assert_eq!(lines[1]["line"], 3, "{name}");
+        assert_eq!(lines[1]["error"]["code"], "invalid_spec", "{name}");
+        assert_eq!(lines[2]["id"], 2, "{name}");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2, "{name}");
+        assert!(requests.iter().all(|r| r.header("x-run") == Some("r1")), "{name}");
+        assert_eq!(requests[1].method, "POST", "{name}");
+        assert_eq!(requests[1].header("x-step"), Some("2"), "{name}");
+    }
+}
@@ -0,0 +1,69 @@
+// tests/byte_ranges.rs
+
+//! `206` replies with `multipart/byteranges` bodies.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+fn multipart_server(second_start: u64) -> TestServer {
+    let body = format!(
+        "--SEP\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-4/20\r\n\r\nhello\r\n\
+         --SEP\r\nContent-Type: text/plain\r\nContent-Range: bytes {second_start}-{}/20\r\n\r\nworld\r\n\
+         --SEP--\r\n",
+        second_start + 4
+    );
+    TestServer::with_response(CannedResponse {
+        status: 206,
+        headers: vec!["Content-Type: multipart/byteranges; boundary=SEP".into()],
+        body: body.into_bytes(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn range_header_and_parts() {
+    let server = multipart_server(10);
+    let config = RequestConfig::new(&server.url("/file"))
+        .noproxy("*")
+        .range("0-4,10-14");
+    let resp = Client::new().send(&config).unwrap();
+
+    assert_eq!(server.last_request().header("range"), Some("bytes=0-4,10-14"));
+    let parts = resp.byte_ranges().unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[1].content_range(), "bytes 10-14/20");
+    assert_eq!(parts[1].body, b"world");
+
+    // Gapped ranges are listed rather than joined
+    let shown = resp.to_string();
+    assert!(shown.contains("--- bytes 0-4/20 (5 bytes)\nhello\n"));
+    assert!(!shown.contains("--SEP"));
+}
+
+#[test]
+fn contiguous_parts_are_reassembled() {
+    let server = multipart_server(5);
+    let config = RequestConfig::new(&server.url("/file"))
+        .noproxy("*")
+        .range("0-4,5-9");
+    let resp = Client::new().send(&config).unwrap();
+    assert!(resp.to_string().ends_with("helloworld"));
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn output_file_gets_reassembled_body() {
+    let server = multipart_server(5);
+    let path = std::env::temp_dir().join(format!("rustcurl-ranges-{}.bin", std::process::id()));
+    let config = RequestConfig::new(&server.url("/file"))
+        .noproxy("*")
+        .range("0-9")
+        .output(path.to_str().unwrap());
+    Client::new().send(&config).unwrap();
+    let written = std::fs::read(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(written, b"helloworld");
+}
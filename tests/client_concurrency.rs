@@ -0,0 +1,36 @@
+// tests/client_concurrency.rs
+
+//! One shared `Client` issuing requests from many threads.
+
+mod common;
+
+use std::sync::Arc;
+use std::thread;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[test]
+fn shared_client_serves_concurrent_requests() {
+    let server = TestServer::start();
+    let client = Arc::new(Client::new());
+    let handles: Vec<_> = (0..8)
+        .map(|t| {
+            let client = Arc::clone(&client);
+            let base = server.url("");
+            thread::spawn(move || {
+                for i in 0..5 {
+                    let config = RequestConfig::new(&format!("{base}/t{t}/{i}")).noproxy("*");
+                    let resp = client.send(&config).unwrap();
+                    assert_eq!(resp.status_code, 200);
+                    assert_eq!(resp.body, b"ok");
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(server.requests().len(), 40);
+}
@@ -0,0 +1,42 @@
+// tests/client_parallel.rs
+
+//! `Client::send_parallel` against the embedded server.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[test]
+fn parallel_results_in_request_order() {
+    let server = TestServer::start();
+    let configs: Vec<RequestConfig> = (0..6)
+        .map(|i| RequestConfig::new(&server.url(&format!("/item/{i}"))).noproxy("*"))
+        .collect();
+    let outcome = Client::new().send_parallel(&configs);
+
+    assert_eq!(outcome.responses.len(), 6);
+    for resp in &outcome.responses {
+        assert_eq!(resp.as_ref().unwrap().status_code, 200);
+    }
+    let mut targets: Vec<String> = server.requests().into_iter().map(|r| r.target).collect();
+    targets.sort();
+    assert_eq!(targets, (0..6).map(|i| format!("/item/{i}")).collect::<Vec<_>>());
+    if let Some(connections) = outcome.connections {
+        assert!((1..=6).contains(&connections));
+        assert!(outcome.summary().starts_with("6 transfers, 6 ok, "));
+    }
+}
+
+#[test]
+fn failures_stay_in_their_slot() {
+    let server = TestServer::start();
+    let configs = vec![
+        RequestConfig::new(&server.url("/ok")).noproxy("*"),
+        RequestConfig::new("http://127.0.0.1:1/").noproxy("*"),
+    ];
+    let outcome = Client::new().send_parallel(&configs);
+    assert!(outcome.responses[0].is_ok());
+    assert!(outcome.responses[1].is_err());
+}
@@ -0,0 +1,86 @@
+// tests/client_send_to.rs
+
+//! `Client::send_to` writing bodies into caller-supplied sinks.
+
+mod common;
+
+use std::io::{self, Write};
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+/// Sink that only counts and checksums what it is given.
+#[derive(Default)]
+struct Digest {
+    len: u64,
+    sum: u64,
+}
+
+impl Write for Digest {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.len += buf.len() as u64;
+        self.sum = buf.iter().fold(self.sum, |acc, b| acc.wrapping_mul(31).wrapping_add(*b as u64));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct FailingSink;
+
+impl Write for FailingSink {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("sink full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn body_goes_to_writer_with_summary() {
+    let body: Vec<u8> = (0..300_000).map(|i| (i % 199) as u8).collect();
+    let mut expected = Digest::default();
+    expected.write_all(&body).unwrap();
+
+    let server = TestServer::with_response(CannedResponse {
+        status: 201,
+        headers: vec!["X-Kind: blob".into()],
+        body,
+        ..Default::default()
+    });
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/blob")).noproxy("*");
+        let mut digest = Digest::default();
+        let summary = client.send_to(&config, &mut digest).unwrap();
+        let name = client.backend().name();
+        assert_eq!(summary.status_code, 201, "{name}");
+        assert_eq!(summary.bytes, 300_000, "{name}");
+        assert_eq!(summary.get_header("x-kind").as_deref(), Some("blob"), "{name}");
+        assert_eq!((digest.len, digest.sum), (expected.len, expected.sum), "{name}");
+    }
+}
+
+#[test]
+fn writer_errors_abort_the_transfer() {
+    let server = TestServer::start();
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/")).noproxy("*");
+        assert!(client.send_to(&config, FailingSink).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+// tests/client_streaming.rs
+
+//! `Client::send_streaming` against the embedded server, for every
+//! compiled-in backend.
+
+mod common;
+
+use std::io::Read;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn streams_large_body_incrementally() {
+    let body: Vec<u8> = (0..512 * 1024).map(|i| (i % 251) as u8).collect();
+    let server = TestServer::with_response(CannedResponse {
+        status: 200,
+        headers: vec!["X-Stream: yes".into()],
+        body: body.clone(),
+        ..Default::default()
+    });
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/big")).noproxy("*");
+        let mut resp = client.send_streaming(&config).unwrap();
+        assert_eq!(resp.status_code, 200, "{}", client.backend().name());
+        assert_eq!(resp.get_header("x-stream").as_deref(), Some("yes"));
+
+        let mut received = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = resp.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            received.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(received, body, "{}", client.backend().name());
+    }
+}
+
+#[test]
+fn empty_body_and_error_status() {
+    let server = TestServer::with_response(CannedResponse {
+        status: 404,
+        headers: vec![],
+        body: Vec::new(),
+        ..Default::default()
+    });
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/missing")).noproxy("*");
+        let resp = client.send_streaming(&config).unwrap();
+        assert_eq!(resp.status_code, 404);
+        assert!(resp.into_response().unwrap().body.is_empty());
+    }
+}
+
+#[test]
+fn dropping_body_early_is_fine() {
+    let server = TestServer::with_response(CannedResponse {
+        status: 200,
+        headers: vec![],
+        body: vec![b'x'; 4 * 1024 * 1024],
+        ..Default::default()
+    });
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/drop")).noproxy("*");
+        let mut resp = client.send_streaming(&config).unwrap();
+        let mut first = [0u8; 16];
+        resp.read_exact(&mut first).unwrap();
+        drop(resp);
+    }
+}
+
+#[test]
+fn connection_failure_is_reported_before_body() {
+    // Bind then drop to get a port nothing listens on
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    for client in clients() {
+        let config = RequestConfig::new(&format!("http://127.0.0.1:{port}/")).noproxy("*");
+        assert!(client.send_streaming(&config).is_err());
+    }
+}
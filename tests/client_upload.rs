@@ -0,0 +1,129 @@
+// tests/client_upload.rs
+
+//! `Client::send_body` with reader-supplied bodies and binary `data`, and
+//! body digest headers, for every compiled-in backend.
+
+mod common;
+
+use std::io::Cursor;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::body::RequestBody;
+use rustcurl::curl::config::{Method, RequestConfig};
+use rustcurl::curl::content_digest::DigestAlgorithm;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+fn payload() -> Vec<u8> {
+    (0..200_000).map(|i| (i % 256) as u8).collect()
+}
+
+#[test]
+fn sized_body_sends_content_length() {
+    let server = TestServer::start();
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/upload"))
+            .method(Method::Put)
+            .noproxy("*");
+        let body = RequestBody::sized(Cursor::new(payload()), payload().len() as u64);
+        let resp = client.send_body(&config, body).unwrap();
+        assert_eq!(resp.status_code, 200);
+
+        let recorded = server.last_request();
+        let name = client.backend().name();
+        assert_eq!(recorded.method, "PUT", "{name}");
+        assert_eq!(recorded.header("content-length"), Some("200000"), "{name}");
+        assert_eq!(recorded.body, payload(), "{name}");
+    }
+}
+
+#[test]
+fn unsized_body_is_chunked() {
+    let server = TestServer::start();
+    for client in clients() {
+        let config = RequestConfig::new(&server.url("/upload"))
+            .method(Method::Post)
+            .noproxy("*");
+        client.send_body(&config, RequestBody::new(Cursor::new(payload()))).unwrap();
+
+        let recorded = server.last_request();
+        let name = client.backend().name();
+        assert_eq!(recorded.method, "POST", "{name}");
+        assert_eq!(recorded.header("transfer-encoding"), Some("chunked"), "{name}");
+        assert_eq!(recorded.body, payload(), "{name}");
+    }
+}
+
+#[test]
+fn binary_data_sent_intact() {
+    let body: Vec<u8> = (0..=255).collect();
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/bin")).noproxy("*").data_bytes(&body);
+        let resp = client.send(&config).unwrap();
+        assert_eq!(resp.status_code, 200, "{name}");
+        assert_eq!(server.last_request().body, body, "{name}");
+    }
+}
+
+#[test]
+fn body_and_data_are_exclusive() {
+    let config = RequestConfig::new("http://127.0.0.1:1/").data("a=1");
+    let err = Client::new()
+        .send_body(&config, RequestBody::new(Cursor::new(Vec::new())))
+        .unwrap_err();
+    assert_eq!(err.code(), "config");
+}
+
+#[test]
+fn crlf_converts_streamed_and_data_bodies() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/eol")).noproxy("*").crlf(true);
+        client
+            .send_body(&config.clone().method(Method::Put), RequestBody::sized(Cursor::new(b"a\nb\n".to_vec()), 4))
+            .unwrap();
+        assert_eq!(server.last_request().body, b"a\r\nb\r\n", "{name}");
+        client.send(&config.data("x\ny")).unwrap();
+        assert_eq!(server.last_request().body, b"x\r\ny", "{name}");
+    }
+}
+
+#[test]
+fn digest_headers_cover_the_body_as_sent() {
+    let file = std::env::temp_dir().join(format!("rustcurl-digest-{}.txt", std::process::id()));
+    std::fs::write(&file, "a\nb\n").unwrap();
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/digest"))
+            .noproxy("*")
+            .content_digest(DigestAlgorithm::Sha256)
+            .content_md5(true);
+        client.send(&config.clone().upload_file(file.to_str().unwrap()).crlf(true)).unwrap();
+        let recorded = server.last_request();
+        assert_eq!(recorded.body, b"a\r\nb\r\n", "{name}");
+        let digest = "sha-256=:WAVb3Mc3h+uIx4028LSTnpxdwcOtF+JcyFpoM88aDKs=:";
+        assert_eq!(recorded.header("content-digest"), Some(digest), "{name}");
+        assert_eq!(recorded.header("content-md5"), Some("WbDXdy8FYe+5VRjzy4q8YA=="), "{name}");
+
+        client.send(&config.data("x=1")).unwrap();
+        let recorded = server.last_request();
+        let digest = "sha-256=:HyBrEcI+KMwlDe1/wAmNOCOoRnpUNA8axOU1y4VEST8=:";
+        assert_eq!(recorded.header("content-digest"), Some(digest), "{name}");
+        assert_eq!(recorded.header("content-md5"), Some("olVRL51hpnd71aMEI1vSbQ=="), "{name}");
+    }
+    std::fs::remove_file(file).unwrap();
+}
@@ -0,0 +1,83 @@
+// tests/collection.rs
+
+//! `--collection`: YAML steps run in dependency order with per-step
+//! retries, for every compiled-in backend.
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::collection::Collection;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+fn status(code: u16) -> CannedResponse {
+    CannedResponse { status: code, ..Default::default() }
+}
+
+#[test]
+fn steps_run_after_their_dependencies() {
+    for client in clients() {
+        let name = client.backend().name();
+        let api = TestServer::start();
+        let flaky = TestServer::with_responses(vec![status(503), status(200)]);
+        let down = TestServer::with_response(status(500));
+        let suite = format!(
+            "steps:
+  - name: login
+    url: {login}
+    data: user=ops
+  - name: orders
+    url: {orders}
+    depends_on: login
+    retry: 1
+    headers: {{ X-Step: orders }}
+  - name: audit
+    url: {audit}
+    depends_on: [login]
+  - name: report
+    url: {report}
+    depends_on: [orders, audit]
+",
+            login = api.url("/login"),
+            orders = flaky.url("/orders"),
+            audit = down.url("/audit"),
+            report = api.url("/report"),
+        );
+        let collection = Collection::parse(&suite).unwrap();
+        let base = RequestConfig::new("").noproxy("*").retry_delay(Duration::ZERO);
+        let mut out = Vec::new();
+        let passed = client.collection(&base, &collection, &mut out).unwrap();
+        assert!(!passed, "{name}");
+
+        let lines: HashMap<String, serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .map(|line| (line["step"].as_str().unwrap().to_string(), line))
+            .collect();
+        assert_eq!(lines.len(), 4, "{name}");
+        assert_eq!(lines["login"]["passed"], true, "{name}");
+        assert_eq!((lines["orders"]["status"].clone(), lines["orders"]["attempts"].clone()), (200.into(), 2.into()));
+        assert_eq!((lines["audit"]["status"].clone(), lines["audit"]["passed"].clone()), (500.into(), false.into()));
+        assert_eq!(lines["report"]["skipped"], "dependency 'audit' did not pass", "{name}");
+
+        let requests = api.requests();
+        assert_eq!(requests.len(), 1, "{name}: report must not run");
+        assert_eq!(requests[0].method, "POST", "{name}");
+        assert_eq!(flaky.last_request().header("x-step"), Some("orders"), "{name}");
+    }
+}
@@ -0,0 +1,205 @@
+// tests/common/mod.rs
+
+//! Embedded HTTP/1.1 test server: records every request it receives and
+//! answers with canned responses, so tests can inspect exactly what a
+//! backend put on the wire.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub target: String,
+    /// Header names are lowercased; order is preserved.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Canned reply the server sends for a request.
+#[derive(Debug, Clone)]
+pub struct CannedResponse {
+    pub status: u16,
+    pub headers: Vec<String>,
+    pub body: Vec<u8>,
+    /// 1xx responses sent ahead of the final one, as (status, header lines).
+    pub informational: Vec<(u16, Vec<String>)>,
+    /// Content-Length to announce instead of the body's real length; the
+    /// connection is closed after the body so a short body is truncated.
+    pub content_length: Option<usize>,
+}
+
+impl Default for CannedResponse {
+    fn default() -> Self {
+        Self {
+            status: 200,
+            headers: vec!["Content-Type: text/plain".into()],
+            body: b"ok".to_vec(),
+            informational: Vec::new(),
+            content_length: None,
+        }
+    }
+}
+
+pub struct TestServer {
+    pub port: u16,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+    pub fn start() -> Self {
+        Self::with_response(CannedResponse::default())
+    }
+
+    pub fn with_response(response: CannedResponse) -> Self {
+        Self::with_responses(vec![response])
+    }
+
+    /// Answer the n-th request with `responses[n]`, repeating the last one
+    /// once they run out.
+    pub fn with_responses(responses: Vec<CannedResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            let responses = Arc::new(responses);
+            for stream in listener.incoming().flatten() {
+                let recorded = Arc::clone(&recorded);
+                let responses = Arc::clone(&responses);
+                thread::spawn(move || {
+                    let _ = handle(stream, &recorded, &responses);
+                });
+            }
+        });
+        Self { port, requests }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{path}", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn last_request(&self) -> RecordedRequest {
+        self.requests().pop().expect("server received no request")
+    }
+}
+
+fn handle(
+    stream: TcpStream,
+    recorded: &Mutex<Vec<RecordedRequest>>,
+    responses: &[CannedResponse],
+) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.push((k.trim().to_lowercase(), v.trim().to_string()));
+            }
+        }
+        let find = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+        };
+
+        if find("expect").is_some_and(|v| v.eq_ignore_ascii_case("100-continue")) {
+            writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        }
+
+        let body = if let Some(len) = find("content-length").and_then(|v| v.parse::<usize>().ok()) {
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body)?;
+            body
+        } else if find("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+            read_chunked(&mut reader)?
+        } else {
+            Vec::new()
+        };
+
+        let seen = {
+            let mut recorded = recorded.lock().unwrap();
+            recorded.push(RecordedRequest {
+                method: method.clone(),
+                target,
+                headers,
+                body,
+            });
+            recorded.len()
+        };
+        let response = &responses[seen.min(responses.len()) - 1];
+
+        let mut reply = String::new();
+        for (status, headers) in &response.informational {
+            reply.push_str(&format!("HTTP/1.1 {status} Info\r\n"));
+            for h in headers {
+                reply.push_str(h);
+                reply.push_str("\r\n");
+            }
+            reply.push_str("\r\n");
+        }
+        reply.push_str(&format!("HTTP/1.1 {} Test\r\n", response.status));
+        for h in &response.headers {
+            reply.push_str(h);
+            reply.push_str("\r\n");
+        }
+        let body: &[u8] = if method == "HEAD" { &[] } else { &response.body };
+        let length = response.content_length.unwrap_or(response.body.len());
+        reply.push_str(&format!("Content-Length: {length}\r\n\r\n"));
+        writer.write_all(reply.as_bytes())?;
+        writer.write_all(body)?;
+        writer.flush()?;
+        if response.content_length.is_some() {
+            return writer.shutdown(std::net::Shutdown::Both);
+        }
+    }
+}
+
+fn read_chunked(reader: &mut impl BufRead) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        reader.read_line(&mut size_line)?;
+        let size = usize::from_str_radix(size_line.trim(), 16).unwrap_or(0);
+        let mut chunk = vec![0; size + 2];
+        reader.read_exact(&mut chunk)?;
+        if size == 0 {
+            return Ok(body);
+        }
+        body.extend_from_slice(&chunk[..size]);
+    }
+}
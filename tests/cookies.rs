@@ -0,0 +1,139 @@
+// tests/cookies.rs
+
+//! Repeated `-b`: file and inline sources are merged in argument order, so
+//! a later source's cookie replaces an earlier one of the same name, with
+//! every backend; `-b jar -c jar` keeps the file's cookies; and a file's
+//! cookies only go where their domain and path match.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(|b| Client::with_backend(b).env(EnvSource::Disabled)).collect()
+}
+
+fn scratch(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustcurl-cookies-{tag}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The pairs of a `Cookie` header, sorted: the curl backend sends its
+/// cookie engine's cookies ahead of inline ones.
+fn pairs(header: Option<&str>) -> Vec<&str> {
+    let mut pairs: Vec<_> = header.unwrap_or_default().split("; ").filter(|p| !p.is_empty()).collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+const FILE: &str = "127.0.0.1\tFALSE\t/\tFALSE\t0\tsid\tfromfile\n127.0.0.1\tFALSE\t/\tFALSE\t0\tkeep\t1\n";
+
+#[test]
+fn later_source_wins() {
+    let dir = scratch("order");
+    let file = dir.join("cookies.txt");
+    fs::write(&file, FILE).unwrap();
+    let file = file.to_str().unwrap();
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::start();
+        let config = RequestConfig::new(&server.url("/"));
+        client.send(&config.clone().cookie(file).cookie("sid=inline")).unwrap();
+        let request = server.last_request();
+        let cookies: Vec<_> = request.headers.iter().filter(|(k, _)| k == "cookie").collect();
+        assert_eq!(cookies.len(), 1, "{name}: {cookies:?}");
+        assert_eq!(pairs(request.header("cookie")), ["keep=1", "sid=inline"], "{name}");
+
+        client.send(&config.cookie("sid=inline; lang=en").cookie(file)).unwrap();
+        assert_eq!(pairs(server.last_request().header("cookie")), ["keep=1", "lang=en", "sid=fromfile"], "{name}");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn jar_read_and_written_keeps_its_cookies() {
+    let server = TestServer::with_response(CannedResponse {
+        headers: vec!["Set-Cookie: sid=fresh; Path=/".into()],
+        ..Default::default()
+    });
+    let dir = scratch("jar");
+    let jar = dir.join("cookies.txt");
+    let jar = jar.to_str().unwrap();
+    let config = RequestConfig::new(&server.url("/")).cookie(jar).cookie_jar(jar);
+    let client = Client::new().env(EnvSource::Disabled);
+
+    // The jar does not exist on the first run
+    client.send(&config).unwrap();
+    assert_eq!(server.last_request().header("cookie"), None);
+    let keep = FILE.lines().nth(1).unwrap();
+    fs::write(jar, format!("{}{keep}\n", fs::read_to_string(jar).unwrap())).unwrap();
+
+    client.send(&config).unwrap();
+    assert_eq!(pairs(server.last_request().header("cookie")), ["keep=1", "sid=fresh"]);
+    let text = fs::read_to_string(jar).unwrap();
+    let cookies: Vec<_> = text.lines().filter(|l| !l.starts_with('#') && !l.is_empty()).collect();
+    assert_eq!(cookies.len(), 2, "{text}");
+    assert!(cookies.iter().any(|l| l.ends_with("\tsid\tfresh")), "{text}");
+    assert!(cookies.iter().any(|l| l.ends_with("\tkeep\t1")), "{text}");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn file_cookie_stays_on_its_host() {
+    let dir = scratch("redirect");
+    let file = dir.join("cookies.txt");
+    fs::write(&file, FILE).unwrap();
+    let file = file.to_str().unwrap();
+    for client in clients() {
+        let name = client.backend().name();
+        let other = TestServer::start();
+        let server = TestServer::with_response(CannedResponse {
+            status: 302,
+            headers: vec![format!("Location: http://localhost:{}/landing", other.port)],
+            ..Default::default()
+        });
+        let config = RequestConfig::new(&server.url("/")).follow_location(true).cookie(file).cookie("lang=en");
+        client.send(&config).unwrap();
+        assert_eq!(pairs(server.last_request().header("cookie")), ["keep=1", "lang=en", "sid=fromfile"], "{name}");
+        // The file's cookies are for 127.0.0.1 only, and neither backend
+        // passes an inline Cookie on to another host
+        let landed = other.last_request();
+        assert_eq!(landed.target, "/landing", "{name}");
+        assert_eq!(landed.header("cookie"), None, "{name}");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn file_cookie_stays_on_its_path() {
+    let dir = scratch("path");
+    let file = dir.join("cookies.txt");
+    fs::write(&file, "127.0.0.1\tFALSE\t/admin\tFALSE\t0\tadmin\t1\n").unwrap();
+    let file = file.to_str().unwrap();
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::start();
+        client.send(&RequestConfig::new(&server.url("/")).cookie(file)).unwrap();
+        assert_eq!(server.last_request().header("cookie"), None, "{name}");
+        client.send(&RequestConfig::new(&server.url("/administrator")).cookie(file)).unwrap();
+        assert_eq!(server.last_request().header("cookie"), None, "{name}");
+        client.send(&RequestConfig::new(&server.url("/admin/users")).cookie(file)).unwrap();
+        assert_eq!(server.last_request().header("cookie"), Some("admin=1"), "{name}");
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
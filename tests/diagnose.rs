@@ -0,0 +1,72 @@
+// tests/diagnose.rs
+
+//! `--diagnose`: each step toward the server is checked on its own, and
+//! the final probe is a single HEAD, for every compiled-in backend.
+//! Through a proxy, the CONNECT is timed on its own.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn reachable_server_passes_every_check() {
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::start();
+        let config = RequestConfig::new(&server.url("/health")).noproxy("*").data("a=1").retry(2);
+        let report = client.diagnose(&config);
+        let steps: Vec<_> = report.checks.iter().map(|c| c.step).collect();
+        assert_eq!(steps, ["dns", "tcp", "http"], "{name}");
+        assert!(report.checks.iter().all(|c| c.result.is_ok()), "{name}: {report}");
+        assert_eq!(report.checks[2].result, Ok("HEAD answered 200".to_string()), "{name}");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1, "{name}");
+        assert_eq!(requests[0].method, "HEAD", "{name}");
+    }
+}
+
+#[test]
+fn refused_connection_pinpointed() {
+    for client in clients() {
+        let name = client.backend().name();
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let report = client.diagnose(&RequestConfig::new(&format!("http://127.0.0.1:{port}/")).noproxy("*"));
+        assert!(report.checks[0].result.is_ok(), "{name}: {report}");
+        assert_eq!(report.checks[1].step, "tcp", "{name}");
+        assert!(report.checks[1].result.is_err(), "{name}: {report}");
+        assert!(report.to_string().contains("FAILED"), "{name}");
+    }
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn connect_timed_through_proxy() {
+    let server = TestServer::start();
+    // A relay stands in for the corporate proxy
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || Client::new().relay(&RequestConfig::new("").noproxy("*"), listener));
+
+    let url = format!("https://127.0.0.1:{}/", server.port);
+    let report = Client::new().diagnose(&RequestConfig::new(&url).proxy(&proxy));
+    let steps: Vec<_> = report.checks.iter().map(|c| c.step).collect();
+    assert_eq!(steps, ["proxy", "dns", "tunnel", "tls"], "{report}");
+    let tunnel = report.checks[2].result.as_ref().unwrap();
+    assert!(tunnel.contains("ms to the proxy") && tunnel.contains("ms beyond it"), "{report}");
+    // The test server speaks plain HTTP, so the handshake itself fails
+    assert!(report.checks[3].result.is_err(), "{report}");
+}
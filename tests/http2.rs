@@ -0,0 +1,51 @@
+// tests/http2.rs
+
+//! `--http2` and `--http2-prior-knowledge` on clear-text URLs, against a
+//! server that only speaks HTTP/1.1, and the version `Response` reports.
+//! The HTTP/2 cases need a libcurl built with it.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::{Http2, RequestConfig};
+use rustcurl::curl::env::EnvSource;
+
+fn has_http2(client: &Client) -> bool {
+    client.backend().capabilities().features.contains(&"http2")
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn http2_asks_to_upgrade_and_falls_back() {
+    let server = TestServer::start();
+    let client = Client::new();
+    let config = RequestConfig::new(&server.url("/h2")).env(EnvSource::Disabled);
+
+    let plain = client.send(&config).unwrap();
+    assert_eq!(plain.http_version.as_deref(), Some("1.1"));
+    assert_eq!(server.last_request().header("upgrade"), None);
+
+    let asked = client.send(&config.http2(Http2::Negotiate));
+    if !has_http2(&client) {
+        assert_eq!(asked.unwrap_err().code(), "config");
+        return;
+    }
+    assert_eq!(server.last_request().header("upgrade"), Some("h2c"));
+    assert_eq!(asked.unwrap().http_version.as_deref(), Some("1.1"));
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn prior_knowledge_starts_with_the_preface() {
+    let server = TestServer::start();
+    let client = Client::new();
+    let config = RequestConfig::new(&server.url("/h2c")).env(EnvSource::Disabled).http2(Http2::PriorKnowledge);
+    // Without HTTP/2 it is refused up front; with it, the server answers
+    // the preface in HTTP/1.1, which is not h2
+    assert!(client.send(&config).is_err());
+    if has_http2(&client) {
+        let preface = server.last_request();
+        assert_eq!((preface.method.as_str(), preface.target.as_str()), ("PRI", "*"));
+    }
+}
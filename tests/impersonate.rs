@@ -0,0 +1,54 @@
+// tests/impersonate.rs
+
+//! `--impersonate`: the browser's User-Agent and navigation headers reach
+//! the server, with `-A` and `-H` still taking precedence.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::presets::Preset;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn sends_browser_headers() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/")).noproxy("*").impersonate(Preset::Chrome);
+        client.send(&config).unwrap();
+        let request = server.last_request();
+        assert_eq!(request.header("user-agent"), Some(Preset::Chrome.user_agent()), "{name}");
+        assert_eq!(request.header("sec-ch-ua-mobile"), Some("?0"), "{name}");
+        assert_eq!(request.header("accept-language"), Some("en-US,en;q=0.9"), "{name}");
+    }
+}
+
+#[test]
+fn explicit_values_win() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/"))
+            .noproxy("*")
+            .impersonate(Preset::Safari)
+            .user_agent("probe/1.0")
+            .header("Accept: application/json");
+        client.send(&config).unwrap();
+        let request = server.last_request();
+        assert_eq!(request.header("user-agent"), Some("probe/1.0"), "{name}");
+        assert_eq!(request.header("accept"), Some("application/json"), "{name}");
+        assert_eq!(request.header("accept-language"), Some("en-US,en;q=0.9"), "{name}");
+    }
+}
@@ -0,0 +1,27 @@
+// tests/informational.rs
+
+//! 1xx responses surfaced in `Response::informational`.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[cfg(feature = "curl")]
+#[test]
+fn early_hints_are_captured() {
+    let server = TestServer::with_response(CannedResponse {
+        informational: vec![(103, vec!["Link: </main.css>; rel=preload; as=style".into()])],
+        ..Default::default()
+    });
+    let client = Client::with_backend(Box::new(rustcurl::backend::curl_backend::CurlBackend::new()));
+    let config = RequestConfig::new(&server.url("/page")).noproxy("*");
+    let resp = client.send(&config).unwrap();
+
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.body, b"ok");
+    assert_eq!(resp.informational.len(), 1);
+    assert_eq!(resp.informational[0].status_code, 103);
+    assert_eq!(resp.informational[0].preload_links(), vec!["/main.css"]);
+}
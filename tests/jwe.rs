@@ -0,0 +1,99 @@
+// tests/jwe.rs
+
+//! `--encrypt-jwe` and `--decrypt-jwe` with a shared `oct` key (`dir`):
+//! the body on the wire is a JWE the key opens, a JWE response is shown
+//! decrypted, in memory or in the `-o` file, and other bodies are left
+//! alone.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::{Method, RequestConfig};
+use rustcurl::curl::env::EnvSource;
+use rustcurl::curl::jwe::{self, Compact, Key};
+
+fn key_file(tag: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rustcurl-jwe-{tag}-{}.jwk", std::process::id()));
+    // 32 bytes of 0x2a, so A256GCM
+    fs::write(&path, r#"{"kty":"oct","kid":"psp-1","k":"KioqKioqKioqKioqKioqKioqKioqKioqKioqKioqKio"}"#).unwrap();
+    path
+}
+
+#[test]
+fn request_body_is_sent_encrypted() {
+    let server = TestServer::start();
+    let path = key_file("seal");
+    let key = path.to_str().unwrap();
+    let config = RequestConfig::new(&server.url("/pay"))
+        .env(EnvSource::Disabled)
+        .method(Method::Post)
+        .data("{\"pan\":\"4111\"}");
+    Client::new().send(&config.clone().encrypt_jwe(key)).unwrap();
+
+    let request = server.last_request();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.header("content-type"), Some("application/jose"));
+    let token = Compact::parse(&request.body).expect("a compact JWE");
+    let (enc, plaintext) = token.decrypt(&Key::load(key).unwrap()).unwrap();
+    assert_eq!((enc.name(), plaintext.as_slice()), ("A256GCM", &b"{\"pan\":\"4111\"}"[..]));
+
+    // A Content-Type from -H is kept
+    Client::new().send(&config.header("Content-Type: application/json").encrypt_jwe(key)).unwrap();
+    assert_eq!(server.last_request().header("content-type"), Some("application/json"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn jwe_response_is_decrypted() {
+    let path = key_file("open");
+    let key = path.to_str().unwrap();
+    let token = jwe::encrypt(b"{\"status\":\"approved\"}", &Key::load(key).unwrap()).unwrap();
+    let server = TestServer::with_responses(vec![
+        CannedResponse {
+            headers: vec!["Content-Type: application/jose".into()],
+            body: token.clone().into_bytes(),
+            ..Default::default()
+        },
+        CannedResponse {
+            body: token.into_bytes(),
+            ..Default::default()
+        },
+        CannedResponse {
+            body: b"{\"error\":\"plain\"}".to_vec(),
+            ..Default::default()
+        },
+    ]);
+    let client = Client::new().env(EnvSource::Disabled);
+    let config = RequestConfig::new(&server.url("/status")).decrypt_jwe(key);
+    assert_eq!(client.send(&config).unwrap().body, b"{\"status\":\"approved\"}");
+
+    let saved = std::env::temp_dir().join(format!("rustcurl-jwe-out-{}.json", std::process::id()));
+    client.send(&config.clone().output(saved.to_str().unwrap())).unwrap();
+    assert_eq!(fs::read(&saved).unwrap(), b"{\"status\":\"approved\"}");
+
+    assert_eq!(client.send(&config).unwrap().body, b"{\"error\":\"plain\"}");
+    fs::remove_file(saved).unwrap();
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn wrong_key_fails_the_request() {
+    let path = key_file("wrong");
+    let other = std::env::temp_dir().join(format!("rustcurl-jwe-other-{}.jwk", std::process::id()));
+    fs::write(&other, r#"{"kty":"oct","k":"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"}"#).unwrap();
+    let token = jwe::encrypt(b"secret", &Key::load(path.to_str().unwrap()).unwrap()).unwrap();
+    let server = TestServer::with_response(CannedResponse {
+        body: token.into_bytes(),
+        ..Default::default()
+    });
+    let config = RequestConfig::new(&server.url("/")).env(EnvSource::Disabled).decrypt_jwe(other.to_str().unwrap());
+    let err = Client::new().send(&config).unwrap_err();
+    assert_eq!(err.code(), "config");
+    assert!(err.to_string().contains("authentication tag"), "{err}");
+    fs::remove_file(path).unwrap();
+    fs::remove_file(other).unwrap();
+}
@@ -0,0 +1,46 @@
+// tests/partial_body.rs
+
+//! Bodies cut short of their Content-Length: an error by default, kept with
+//! `--accept-partial`.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::error::RequestError;
+
+fn truncated_server() -> TestServer {
+    TestServer::with_response(CannedResponse {
+        body: b"only half".to_vec(),
+        content_length: Some(100),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn truncated_body_is_partial_body_error() {
+    let server = truncated_server();
+    let config = RequestConfig::new(&server.url("/short")).noproxy("*");
+    match Client::new().send(&config) {
+        Err(e @ RequestError::PartialBody { .. }) => {
+            assert_eq!(e.code(), "partial_body");
+            if let RequestError::PartialBody { expected, received } = e {
+                assert_eq!(expected, Some(100));
+                assert_eq!(received, 9);
+            }
+        }
+        other => panic!("expected PartialBody, got {other:?}"),
+    }
+}
+
+#[test]
+fn accept_partial_keeps_truncated_body() {
+    let server = truncated_server();
+    let config = RequestConfig::new(&server.url("/short"))
+        .noproxy("*")
+        .accept_partial(true);
+    let resp = Client::new().send(&config).unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.body, b"only half");
+}
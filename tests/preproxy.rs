@@ -0,0 +1,92 @@
+// tests/preproxy.rs
+
+//! `--preproxy`: the connection to the HTTP proxy is made through a SOCKS
+//! proxy, and the request is then sent to the HTTP proxy as usual.
+
+mod common;
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+/// A SOCKS5 proxy without authentication, recording each CONNECT target.
+fn socks5_proxy() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = Arc::clone(&seen);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Ok((target, upstream)) = connect(&stream) {
+                record.lock().unwrap().push(target);
+                let (mut c_in, mut u_out) = (stream.try_clone().unwrap(), upstream.try_clone().unwrap());
+                thread::spawn(move || io::copy(&mut c_in, &mut u_out));
+                let (mut u_in, mut c_out) = (upstream, stream);
+                let _ = io::copy(&mut u_in, &mut c_out);
+            }
+        }
+    });
+    (port, seen)
+}
+
+fn connect(stream: &TcpStream) -> io::Result<(String, TcpStream)> {
+    let mut stream = stream.try_clone()?;
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head)?;
+    stream.read_exact(&mut vec![0u8; head[1] as usize])?;
+    stream.write_all(&[5, 0])?;
+    let mut request = [0u8; 4];
+    stream.read_exact(&mut request)?;
+    let host = match request[3] {
+        1 => {
+            let mut ip = [0u8; 4];
+            stream.read_exact(&mut ip)?;
+            std::net::Ipv4Addr::from(ip).to_string()
+        }
+        3 => {
+            let mut len = [0u8];
+            stream.read_exact(&mut len)?;
+            let mut name = vec![0u8; len[0] as usize];
+            stream.read_exact(&mut name)?;
+            String::from_utf8_lossy(&name).into_owned()
+        }
+        _ => return Err(io::Error::other("unexpected address type")),
+    };
+    let mut port = [0u8; 2];
+    stream.read_exact(&mut port)?;
+    let target = format!("{host}:{}", u16::from_be_bytes(port));
+    let upstream = TcpStream::connect(target.as_str())?;
+    stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])?;
+    Ok((target, upstream))
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn http_proxy_reached_through_socks() {
+    let proxy = TestServer::with_response(CannedResponse {
+        status: 403,
+        ..Default::default()
+    });
+    let (socks, seen) = socks5_proxy();
+    let config = RequestConfig::new("http://origin.invalid/a")
+        .env(EnvSource::Disabled)
+        .proxy(&format!("http://127.0.0.1:{}", proxy.port))
+        .preproxy(&format!("socks5://127.0.0.1:{socks}"));
+
+    let response = Client::new().send(&config).unwrap();
+    assert_eq!(response.status_code, 403);
+    assert_eq!(proxy.last_request().target, "http://origin.invalid/a");
+    assert_eq!(*seen.lock().unwrap(), [format!("127.0.0.1:{}", proxy.port)]);
+}
+
+#[test]
+fn preproxy_needs_an_http_proxy() {
+    let config = RequestConfig::new("http://localhost:9/").env(EnvSource::Disabled).preproxy("socks5://127.0.0.1:9");
+    assert_eq!(Client::new().send(&config).unwrap_err().code(), "config");
+}
@@ -0,0 +1,60 @@
+// tests/prom_out.rs
+
+//! `--prom-out` records every probe, failed or not.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+fn prom_path(tag: &str) -> String {
+    let path = std::env::temp_dir().join(format!("rustcurl-{tag}-{}.prom", std::process::id()));
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn successful_probe() {
+    let server = TestServer::start();
+    let path = prom_path("up");
+    let config = RequestConfig::new(&server.url("/health")).noproxy("*").prom_out(&path);
+    let resp = Client::new().send(&config).unwrap();
+    assert!(resp.timing.is_none());
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let url = server.url("/health");
+    assert!(text.contains(&format!("rustcurl_probe_up{{url=\"{url}\"}} 1\n")));
+    assert!(text.contains(&format!("rustcurl_probe_status_code{{url=\"{url}\"}} 200\n")));
+    assert!(text.contains(&format!("rustcurl_probe_response_bytes{{url=\"{url}\"}} 2\n")));
+}
+
+#[test]
+fn streamed_body_is_counted() {
+    let server = TestServer::start();
+    let path = prom_path("stream");
+    // -o -: the body goes to stdout as it arrives and is not kept
+    let config = RequestConfig::new(&server.url("/health")).noproxy("*").output("-").prom_out(&path);
+    let resp = Client::new().send(&config).unwrap();
+    assert!(resp.body.is_empty());
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let url = server.url("/health");
+    assert!(text.contains(&format!("rustcurl_probe_response_bytes{{url=\"{url}\"}} 2\n")), "{text}");
+}
+
+#[test]
+fn failed_probe_is_recorded() {
+    // Bind then drop a listener so the port refuses connections
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let path = prom_path("down");
+    let url = format!("http://127.0.0.1:{port}/");
+    let config = RequestConfig::new(&url).noproxy("*").prom_out(&path);
+    assert!(Client::new().send(&config).is_err());
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(text.contains(&format!("rustcurl_probe_up{{url=\"{url}\"}} 0\n")));
+    assert!(text.contains("rustcurl_probe_failure{"));
+}
@@ -0,0 +1,41 @@
+// tests/provenance.rs
+
+//! `--provenance` writes a JSON record next to the `-o` file.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[cfg(feature = "curl")]
+#[test]
+fn record_written_next_to_download() {
+    let server = TestServer::with_response(CannedResponse {
+        body: b"abc".to_vec(),
+        ..Default::default()
+    });
+    let dir = std::env::temp_dir().join(format!("rustcurl-provenance-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = RequestConfig::new(&server.url("/artifact.bin"))
+        .noproxy("*")
+        .output_dir(dir.to_str().unwrap())
+        .output("artifact.bin")
+        .provenance(true);
+    Client::new().send(&config).unwrap();
+
+    let text = std::fs::read_to_string(dir.join("artifact.bin.provenance.json")).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    let record: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(record["url"], server.url("/artifact.bin"));
+    assert_eq!(record["final_url"], server.url("/artifact.bin"));
+    assert_eq!(record["status"], 200);
+    assert_eq!(record["size"], 3);
+    assert_eq!(
+        record["sha256"],
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Plain HTTP: no certificate to fingerprint
+    assert!(record["tls_certificate_sha256"].is_null());
+    assert!(record["started"].as_str().unwrap().ends_with('Z'));
+}
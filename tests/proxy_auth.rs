@@ -0,0 +1,61 @@
+// tests/proxy_auth.rs
+
+//! `--proxy-digest` and `--proxy-anyauth`: credentials are held back until
+//! the proxy's 407 names a method, then sent with the one it asked for.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+fn challenging_proxy(challenges: &[&str]) -> TestServer {
+    TestServer::with_responses(vec![
+        CannedResponse {
+            status: 407,
+            headers: challenges.iter().map(|c| format!("Proxy-Authenticate: {c}")).collect(),
+            body: Vec::new(),
+            ..Default::default()
+        },
+        CannedResponse::default(),
+    ])
+}
+
+fn through(proxy: &TestServer) -> RequestConfig {
+    RequestConfig::new("http://origin.invalid/a")
+        .env(EnvSource::Disabled)
+        .proxy(&format!("http://127.0.0.1:{}", proxy.port))
+        .proxy_user("u")
+        .proxy_password("p")
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn digest_answers_the_challenge() {
+    let proxy = challenging_proxy(&[r#"Digest realm="corp", nonce="n0", qop="auth""#]);
+    let response = Client::new().send(&through(&proxy).proxy_digest(true)).unwrap();
+    assert_eq!(response.status_code, 200);
+    let requests = proxy.requests();
+    assert_eq!(requests[0].header("proxy-authorization"), None);
+    let answer = requests[1].header("proxy-authorization").unwrap();
+    assert!(answer.starts_with(r#"Digest username="u", realm="corp", nonce="n0""#), "{answer}");
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn anyauth_picks_the_strongest_offered() {
+    let proxy = challenging_proxy(&[r#"Basic realm="corp""#, r#"Digest realm="corp", nonce="n1""#]);
+    Client::new().send(&through(&proxy).proxy_anyauth(true)).unwrap();
+    let requests = proxy.requests();
+    assert_eq!(requests[0].header("proxy-authorization"), None);
+    assert!(requests[1].header("proxy-authorization").unwrap().starts_with("Digest "));
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn basic_is_sent_up_front() {
+    let proxy = TestServer::start();
+    Client::new().send(&through(&proxy).proxy_basic(true)).unwrap();
+    assert_eq!(proxy.last_request().header("proxy-authorization"), Some("Basic dTpw"));
+}
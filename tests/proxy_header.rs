@@ -0,0 +1,43 @@
+// tests/proxy_header.rs
+
+//! `--proxy-header` goes on the CONNECT request only, and `-H` stays off
+//! it, for every compiled-in backend.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(|backend| Client::with_backend(backend).env(EnvSource::Disabled)).collect()
+}
+
+#[test]
+fn proxy_headers_only_on_connect() {
+    for client in clients() {
+        // Refusing the tunnel ends the transfer once the CONNECT is seen
+        let proxy = TestServer::with_response(CannedResponse {
+            status: 403,
+            ..Default::default()
+        });
+        let config = RequestConfig::new("https://origin.invalid/secret")
+            .proxy(&format!("http://127.0.0.1:{}", proxy.port))
+            .proxy_header("X-Corp-Token: abc")
+            .header("X-Origin-Only: 1");
+        assert!(client.send(&config).is_err());
+        let connect = proxy.last_request();
+        assert_eq!(connect.method, "CONNECT");
+        assert_eq!(connect.target, "origin.invalid:443");
+        assert_eq!(connect.header("X-Corp-Token"), Some("abc"));
+        assert_eq!(connect.header("X-Origin-Only"), None);
+    }
+}
@@ -0,0 +1,60 @@
+// tests/proxy_pac.rs
+
+//! `--proxy-pac`: the script, read from a file or fetched over HTTP,
+//! sends matching hosts through the proxy it names and the rest direct.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+fn script(proxy_port: u16) -> String {
+    format!(
+        r#"function FindProxyForURL(url, host) {{
+            if (dnsDomainIs(host, ".pac.invalid")) return "PROXY 127.0.0.1:{proxy_port}; DIRECT";
+            return "DIRECT";
+        }}"#
+    )
+}
+
+#[test]
+fn file_script_picks_proxy_or_direct() {
+    let proxy = TestServer::start();
+    let origin = TestServer::start();
+    let file = std::env::temp_dir().join(format!("rustcurl-{}-{}.pac", std::process::id(), proxy.port));
+    std::fs::write(&file, script(proxy.port)).unwrap();
+    let pac = file.to_str().unwrap();
+    let client = Client::new().env(EnvSource::Disabled);
+
+    client.send(&RequestConfig::new("http://www.pac.invalid/a").proxy_pac(pac)).unwrap();
+    assert_eq!(proxy.last_request().target, "http://www.pac.invalid/a");
+
+    client.send(&RequestConfig::new(&origin.url("/direct")).proxy_pac(pac)).unwrap();
+    assert_eq!(origin.last_request().target, "/direct");
+    assert_eq!(proxy.requests().len(), 1);
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn fetched_script_and_errors() {
+    let proxy = TestServer::start();
+    let wpad = TestServer::with_response(CannedResponse {
+        headers: vec!["Content-Type: application/x-ns-proxy-autoconfig".into()],
+        body: script(proxy.port).into_bytes(),
+        ..Default::default()
+    });
+    let client = Client::new().env(EnvSource::Disabled);
+    let config = RequestConfig::new("http://api.pac.invalid/v1").proxy_pac(&wpad.url("/wpad.dat"));
+    client.send(&config).unwrap();
+    assert_eq!(proxy.last_request().target, "http://api.pac.invalid/v1");
+
+    let broken = TestServer::with_response(CannedResponse {
+        body: b"function FindProxyForURL(url, host) { while (true) {} }".to_vec(),
+        ..Default::default()
+    });
+    let err = client.send(&RequestConfig::new("http://x.invalid/").proxy_pac(&broken.url("/p.pac"))).unwrap_err();
+    assert_eq!(err.code(), "config");
+    assert!(err.to_string().contains("'while' is not supported"), "{err}");
+}
@@ -0,0 +1,31 @@
+// tests/proxytunnel.rs
+
+//! `-p`: an `http://` URL goes through the proxy as a CONNECT tunnel
+//! instead of a forwarded request.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+#[cfg(feature = "curl")]
+#[test]
+fn plain_http_tunneled_only_with_flag() {
+    // Refusing the tunnel ends the transfer once the CONNECT is seen
+    let proxy = TestServer::with_response(CannedResponse {
+        status: 403,
+        ..Default::default()
+    });
+    let client = Client::new().env(EnvSource::Disabled);
+    let config = RequestConfig::new("http://origin.invalid/a").proxy(&format!("http://127.0.0.1:{}", proxy.port));
+
+    client.send(&config).unwrap();
+    let forwarded = proxy.last_request();
+    assert_eq!((forwarded.method.as_str(), forwarded.target.as_str()), ("GET", "http://origin.invalid/a"));
+
+    assert!(client.send(&config.proxy_tunnel(true)).is_err());
+    let connect = proxy.last_request();
+    assert_eq!((connect.method.as_str(), connect.target.as_str()), ("CONNECT", "origin.invalid:80"));
+}
@@ -0,0 +1,40 @@
+// tests/query_data.rs
+
+//! `-G`: data goes out as the query string of a GET, for every
+//! compiled-in backend.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn data_sent_as_query_string() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/search?lang=en#results"))
+            .noproxy("*")
+            .data("q=rust%20curl")
+            .form_field("page", "2")
+            .query_data(true);
+        client.send(&config).unwrap();
+        let recorded = server.last_request();
+        assert_eq!(recorded.method, "GET", "{name}");
+        assert_eq!(recorded.target, "/search?lang=en&q=rust%20curl&page=2", "{name}");
+        assert!(recorded.body.is_empty(), "{name}");
+        assert_eq!(recorded.header("content-type"), None, "{name}");
+    }
+}
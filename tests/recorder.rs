@@ -0,0 +1,68 @@
+// tests/recorder.rs
+
+//! `recorder::record` sees each request as the backend sent it: headers
+//! in the order given and the body after them, one entry per request.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::{Method, RequestConfig};
+use rustcurl::curl::recorder;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn records_header_order_and_body() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/form?a=1"))
+            .noproxy("*")
+            .method(Method::Post)
+            .header("X-Zeta: 1")
+            .header("X-Alpha: 2")
+            .header("X-Mid: 3")
+            .data("q=1");
+        let (result, sent) = recorder::record(|| client.send(&config));
+        result.unwrap();
+        assert_eq!(sent.len(), 1, "{name}");
+        assert_eq!(sent[0].request_line(), "POST /form?a=1 HTTP/1.1", "{name}");
+        let custom: Vec<String> = sent[0]
+            .header_names()
+            .into_iter()
+            .map(|h| h.to_ascii_lowercase())
+            .filter(|h| h.starts_with("x-"))
+            .collect();
+        assert_eq!(custom, ["x-zeta", "x-alpha", "x-mid"], "{name}");
+        assert_eq!(sent[0].body, b"q=1", "{name}");
+    }
+}
+
+#[test]
+fn each_redirect_is_a_request() {
+    for client in clients() {
+        let server = TestServer::with_responses(vec![
+            CannedResponse {
+                status: 302,
+                headers: vec!["Location: /next".into()],
+                ..Default::default()
+            },
+            CannedResponse::default(),
+        ]);
+        let config = RequestConfig::new(&server.url("/")).noproxy("*").follow_location(true);
+        let (result, sent) = recorder::record(|| client.send(&config));
+        assert_eq!(result.unwrap().status_code, 200);
+        let lines: Vec<String> = sent.iter().map(|s| s.request_line()).collect();
+        assert_eq!(lines, ["GET / HTTP/1.1", "GET /next HTTP/1.1"], "{}", client.backend().name());
+    }
+}
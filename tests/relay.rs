@@ -0,0 +1,198 @@
+// tests/relay.rs
+
+//! `rustcurl relay`: requests sent to the local proxy reach the origin
+//! without hop-by-hop headers, redirects come back unfollowed, and
+//! `CONNECT` tunnels pass through an authenticating upstream proxy, for
+//! every compiled-in backend.
+
+mod common;
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+/// Start a relay with `client` and return its port. It serves until the
+/// test process exits.
+fn start_relay(client: Client, config: RequestConfig) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || client.relay(&config, listener));
+    port
+}
+
+fn exchange(port: u16, request: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    reply
+}
+
+#[test]
+fn forwards_plain_requests() {
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::start();
+        let port = start_relay(client, RequestConfig::new("").noproxy("*").header("X-Relay: 1"));
+        let reply = exchange(
+            port,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: x\r\nContent-Type: text/plain\r\nContent-Length: 5\r\nProxy-Authorization: Basic eDp5\r\n\r\nhello",
+                server.url("/up")
+            ),
+        );
+        assert!(reply.starts_with("HTTP/1.1 200 "), "{name}: {reply}");
+        assert!(reply.ends_with("\r\n\r\nok"), "{name}: {reply}");
+
+        let request = server.last_request();
+        assert_eq!(request.method, "POST", "{name}");
+        assert_eq!(request.target, "/up", "{name}");
+        assert_eq!(request.body, b"hello", "{name}");
+        assert_eq!(request.header("x-relay"), Some("1"), "{name}");
+        assert_eq!(request.header("content-type"), Some("text/plain"), "{name}");
+        assert_eq!(request.header("proxy-authorization"), None, "{name}");
+    }
+}
+
+#[test]
+fn redirects_passed_back() {
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::with_response(CannedResponse {
+            status: 302,
+            headers: vec!["Location: /elsewhere".into()],
+            body: Vec::new(),
+            ..CannedResponse::default()
+        });
+        let port = start_relay(client, RequestConfig::new("").noproxy("*"));
+        let reply = exchange(port, &format!("GET {} HTTP/1.1\r\n\r\n", server.url("/old")));
+        assert!(reply.starts_with("HTTP/1.1 302 "), "{name}: {reply}");
+        assert!(reply.contains("Location: /elsewhere\r\n"), "{name}: {reply}");
+        assert_eq!(server.requests().len(), 1, "{name}");
+
+        let reply = exchange(port, "GET /origin-form HTTP/1.1\r\n\r\n");
+        assert!(reply.starts_with("HTTP/1.1 400 "), "{name}: {reply}");
+    }
+}
+
+#[test]
+fn oversized_bodies_refused() {
+    let server = TestServer::start();
+    let port = start_relay(Client::new(), RequestConfig::new("").noproxy("*"));
+    let reply = exchange(
+        port,
+        &format!("POST {} HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n", server.url("/up")),
+    );
+    assert!(reply.starts_with("HTTP/1.1 413 "), "{reply}");
+    assert!(server.requests().is_empty());
+}
+
+/// An upstream proxy that wants Basic `u:p` and tunnels `CONNECT`s to
+/// their target. Returns its port and the credentials each CONNECT carried.
+fn authenticating_proxy() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&seen);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let recorded = Arc::clone(&recorded);
+            thread::spawn(move || proxy_connect(stream, &recorded));
+        }
+    });
+    (port, seen)
+}
+
+fn proxy_connect(stream: TcpStream, seen: &Mutex<Vec<String>>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+    let mut auth = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("proxy-authorization")
+        {
+            auth = value.trim().to_string();
+        }
+    }
+    seen.lock().unwrap().push(auth.clone());
+    let mut client = stream;
+    if auth != "Basic dTpw" {
+        return client.write_all(
+            b"HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"corp\"\r\nContent-Length: 0\r\n\r\n",
+        );
+    }
+    let mut origin = TcpStream::connect(target)?;
+    client.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")?;
+    let (mut from_origin, mut to_client) = (origin.try_clone()?, client.try_clone()?);
+    thread::spawn(move || {
+        let _ = io::copy(&mut from_origin, &mut to_client);
+        let _ = to_client.shutdown(Shutdown::Write);
+    });
+    io::copy(&mut reader, &mut origin)?;
+    origin.shutdown(Shutdown::Write)
+}
+
+#[test]
+fn connect_tunnels_through_authenticating_proxy() {
+    for client in clients() {
+        let name = client.backend().name();
+        let server = TestServer::start();
+        let (proxy_port, seen) = authenticating_proxy();
+        let config = RequestConfig::new("").proxy(&format!("http://127.0.0.1:{proxy_port}"));
+        let port = start_relay(client, config.clone().proxy_user("u").proxy_password("p"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(stream, "CONNECT 127.0.0.1:{} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", server.port).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+        if name != "curl" {
+            assert!(status.starts_with("HTTP/1.1 501 "), "{name}: {status}");
+            continue;
+        }
+        assert!(status.starts_with("HTTP/1.1 200 "), "{name}: {status}");
+        let mut blank = String::new();
+        reader.read_line(&mut blank).unwrap();
+        assert_eq!(blank, "\r\n", "{name}");
+
+        // Bytes sent after the 200 reach the origin through both proxies
+        stream.write_all(b"GET /tunneled HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut reply = String::new();
+        reader.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 200"), "{name}: {reply}");
+        assert!(reply.ends_with("ok"), "{name}: {reply}");
+        assert_eq!(server.last_request().target, "/tunneled", "{name}");
+        assert!(seen.lock().unwrap().contains(&"Basic dTpw".to_string()), "{name}");
+
+        // Rejected credentials are reported by the relay, not passed on
+        let port = start_relay(Client::new(), config.proxy_user("u").proxy_password("wrong"));
+        let reply = exchange(port, &format!("CONNECT 127.0.0.1:{} HTTP/1.1\r\n\r\n", server.port));
+        assert!(reply.starts_with("HTTP/1.1 502 "), "{name}: {reply}");
+        assert!(!reply.contains("Proxy-Authenticate"), "{name}: {reply}");
+    }
+}
@@ -0,0 +1,85 @@
+// tests/remote_name.rs
+
+//! `-O`: the body is saved under the URL's file name, or with `-J` the
+//! Content-Disposition name, with an extension inferred when it has none.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+fn scratch_dir(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustcurl-remote-{tag}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn html_server() -> TestServer {
+    TestServer::with_response(CannedResponse {
+        headers: vec!["Content-Type: text/html; charset=utf-8".into()],
+        body: b"<html>hi</html>".to_vec(),
+        ..Default::default()
+    })
+}
+
+fn fetch(url: &str, dir: &Path) -> rustcurl::curl::response::Response {
+    let config = RequestConfig::new(url)
+        .noproxy("*")
+        .remote_name(true)
+        .output_dir(dir.to_str().unwrap());
+    Client::new().send(&config).unwrap()
+}
+
+#[test]
+fn named_after_last_segment() {
+    let server = TestServer::start();
+    let dir = scratch_dir("named");
+    let resp = fetch(&server.url("/files/notes.txt?v=2"), &dir);
+    assert_eq!(resp.saved_as, Some(dir.join("notes.txt")));
+    assert!(resp.body.is_empty());
+    assert_eq!(std::fs::read(dir.join("notes.txt")).unwrap(), b"ok");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn extension_inferred_and_trailing_slash() {
+    let server = html_server();
+    let dir = scratch_dir("inferred");
+    assert_eq!(fetch(&server.url("/report"), &dir).saved_as, Some(dir.join("report.html")));
+    assert_eq!(fetch(&server.url("/docs/"), &dir).saved_as, Some(dir.join("index.html")));
+    assert_eq!(std::fs::read(dir.join("index.html")).unwrap(), b"<html>hi</html>");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn content_disposition_name_with_j() {
+    let server = TestServer::with_response(CannedResponse {
+        headers: vec![
+            "Content-Type: text/csv".into(),
+            "Content-Disposition: attachment; filename=\"../q3.csv\"; filename*=UTF-8''q3%20%E2%82%AC.csv".into(),
+        ],
+        body: b"a,b\n".to_vec(),
+        ..Default::default()
+    });
+    let dir = scratch_dir("header");
+    let config = RequestConfig::new(&server.url("/export?id=7"))
+        .noproxy("*")
+        .remote_name(true)
+        .remote_header_name(true)
+        .output_dir(dir.to_str().unwrap());
+    let resp = Client::new().send(&config).unwrap();
+    assert_eq!(resp.saved_as, Some(dir.join("q3 \u{20ac}.csv")));
+    assert_eq!(std::fs::read(dir.join("q3 \u{20ac}.csv")).unwrap(), b"a,b\n");
+
+    // Never clobbers a file the server named
+    let err = Client::new().send(&config).unwrap_err();
+    assert_eq!(err.code(), "write_output");
+
+    // Without -J the URL names the file
+    assert_eq!(fetch(&server.url("/export"), &dir).saved_as, Some(dir.join("export.csv")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,46 @@
+// tests/sla.rs
+
+//! Timing assertions turn a slow but successful transfer into a failure.
+
+mod common;
+
+use std::time::Duration;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::sla::EXIT_SLA_EXCEEDED;
+
+#[test]
+fn generous_limit_passes_without_showing_timing() {
+    let server = TestServer::start();
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .assert_time_total_max(Duration::from_secs(30));
+    let resp = Client::new().send(&config).unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert!(resp.timing.is_none());
+}
+
+#[test]
+fn exceeded_total_fails_with_sla_exit_code() {
+    let server = TestServer::start();
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .assert_time_total_max(Duration::ZERO);
+    let err = Client::new().send(&config).unwrap_err();
+    assert_eq!(err.code(), "sla_exceeded");
+    assert_eq!(err.exit_code(), EXIT_SLA_EXCEEDED);
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn exceeded_ttfb_fails() {
+    let server = TestServer::start();
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .show_timing(true)
+        .assert_ttfb_max(Duration::ZERO);
+    let err = Client::new().send(&config).unwrap_err();
+    assert!(err.to_string().contains("--assert-ttfb-max"));
+}
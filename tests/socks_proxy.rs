@@ -0,0 +1,138 @@
+// tests/socks_proxy.rs
+
+//! `-x socks5h://` and `-x socks4a://`: the request reaches the server
+//! through a SOCKS proxy, which is handed the host name to resolve and the
+//! `--proxy-user` credentials.
+
+mod common;
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+
+/// What the proxy was told: credentials (or SOCKS4 user ID) and target.
+type Seen = Arc<Mutex<Vec<(String, String)>>>;
+
+/// A one-connection-at-a-time SOCKS4a/SOCKS5 proxy on a local port.
+fn socks_proxy() -> (u16, Seen) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Seen::default();
+    let record = Arc::clone(&seen);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Ok((who, target, upstream)) = handshake(&stream) {
+                record.lock().unwrap().push((who, target));
+                relay(stream, upstream);
+            }
+        }
+    });
+    (port, seen)
+}
+
+fn byte(stream: &mut TcpStream) -> io::Result<u8> {
+    let mut b = [0u8];
+    stream.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn bytes(stream: &mut TcpStream, n: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; n];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn until_nul(stream: &mut TcpStream) -> io::Result<String> {
+    let mut out = Vec::new();
+    loop {
+        match byte(stream)? {
+            0 => return Ok(String::from_utf8_lossy(&out).into_owned()),
+            b => out.push(b),
+        }
+    }
+}
+
+fn handshake(stream: &TcpStream) -> io::Result<(String, String, TcpStream)> {
+    let mut stream = stream.try_clone()?;
+    if byte(&mut stream)? == 4 {
+        let head = bytes(&mut stream, 7)?;
+        let port = u16::from_be_bytes([head[1], head[2]]);
+        let user = until_nul(&mut stream)?;
+        let host = until_nul(&mut stream)?;
+        let upstream = TcpStream::connect((host.as_str(), port))?;
+        stream.write_all(&[0, 0x5a, 0, 0, 0, 0, 0, 0])?;
+        return Ok((user, format!("{host}:{port}"), upstream));
+    }
+    let methods = byte(&mut stream)? as usize;
+    let offered = bytes(&mut stream, methods)?;
+    let mut who = String::new();
+    if offered.contains(&2) {
+        stream.write_all(&[5, 2])?;
+        byte(&mut stream)?;
+        let user = byte(&mut stream).and_then(|n| bytes(&mut stream, n as usize))?;
+        let pass = byte(&mut stream).and_then(|n| bytes(&mut stream, n as usize))?;
+        who = format!("{}:{}", String::from_utf8_lossy(&user), String::from_utf8_lossy(&pass));
+        stream.write_all(&[1, 0])?;
+    } else {
+        stream.write_all(&[5, 0])?;
+    }
+    let request = bytes(&mut stream, 4)?;
+    let host = match request[3] {
+        3 => byte(&mut stream).and_then(|n| bytes(&mut stream, n as usize))?,
+        _ => return Err(io::Error::other("the proxy should be given a host name")),
+    };
+    let port = bytes(&mut stream, 2)?;
+    let target = format!("{}:{}", String::from_utf8_lossy(&host), u16::from_be_bytes([port[0], port[1]]));
+    let upstream = TcpStream::connect(target.as_str())?;
+    stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])?;
+    Ok((who, target, upstream))
+}
+
+fn relay(client: TcpStream, upstream: TcpStream) {
+    let (mut c_in, mut u_out) = (client.try_clone().unwrap(), upstream.try_clone().unwrap());
+    thread::spawn(move || io::copy(&mut c_in, &mut u_out));
+    let (mut u_in, mut c_out) = (upstream, client);
+    let _ = io::copy(&mut u_in, &mut c_out);
+}
+
+fn through(proxy: &str, server: &TestServer) -> RequestConfig {
+    let url = server.url("/via").replace("127.0.0.1", "localhost");
+    RequestConfig::new(&url).env(EnvSource::Disabled).proxy(proxy)
+}
+
+#[test]
+fn socks5_with_credentials() {
+    let server = TestServer::start();
+    let (port, seen) = socks_proxy();
+    let config = through(&format!("socks5h://127.0.0.1:{port}"), &server).proxy_user("alice").proxy_password("s3cret");
+    let response = Client::new().send(&config).unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(server.last_request().target, "/via");
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen[0].0, "alice:s3cret");
+    assert!(seen[0].1.starts_with("localhost:"), "{:?}", seen[0]);
+}
+
+#[test]
+fn socks4a_user_id() {
+    let server = TestServer::start();
+    let (port, seen) = socks_proxy();
+    let config = through(&format!("socks4a://127.0.0.1:{port}"), &server).proxy_user("bob");
+    assert_eq!(Client::new().send(&config).unwrap().status_code, 200);
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen[0].0, "bob");
+    assert!(seen[0].1.starts_with("localhost:"), "{:?}", seen[0]);
+}
+
+#[test]
+fn http_proxy_auth_rejected_for_socks() {
+    let config = RequestConfig::new("http://localhost/").proxy("socks5://127.0.0.1:9").proxy_ntlm(true);
+    let err = Client::new().send(&config).unwrap_err();
+    assert_eq!(err.code(), "config");
+}
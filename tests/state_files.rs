@@ -0,0 +1,70 @@
+// tests/state_files.rs
+
+//! State files shared between runs are replaced whole and, where a run
+//! reads them first, updated under a lock: concurrent `--session` runs
+//! all count, and the cookie jar is on disk as soon as `send` returns.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::env::EnvSource;
+use rustcurl::curl::error::RequestError;
+use rustcurl::curl::session;
+
+fn scratch(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustcurl-state-{tag}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn concurrent_sessions_add_up() {
+    let dir = scratch("session");
+    let env = EnvSource::from_pairs([("XDG_STATE_HOME", dir.to_str().unwrap())]);
+    let failed: Result<_, RequestError> = Err(RequestError::Io(std::io::Error::other("refused")));
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for _ in 0..5 {
+                    session::record(&env, "shared", &failed).unwrap();
+                }
+            });
+        }
+    });
+    let stats = session::load(&env, "shared").unwrap();
+    assert_eq!((stats.requests, stats.failed), (40, 40));
+    let leftovers: Vec<_> = fs::read_dir(dir.join("rustcurl").join("sessions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn cookie_jar_saved_when_send_returns() {
+    let server = TestServer::with_response(CannedResponse {
+        headers: vec!["Set-Cookie: sid=abc123; Path=/".into()],
+        ..Default::default()
+    });
+    let dir = scratch("jar");
+    let jar = dir.join("cookies.txt");
+    let client = Client::new().env(EnvSource::Disabled);
+    client.send(&RequestConfig::new(&server.url("/login")).cookie_jar(jar.to_str().unwrap())).unwrap();
+    let text = fs::read_to_string(&jar).unwrap();
+    assert!(text.starts_with("# Netscape HTTP Cookie File\n"), "{text}");
+    assert!(text.lines().any(|line| line.ends_with("\tsid\tabc123")), "{text}");
+
+    // The pooled handle is reused without a jar; the saved one stays put
+    client.send(&RequestConfig::new(&server.url("/other"))).unwrap();
+    assert_eq!(fs::read_to_string(&jar).unwrap(), text);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,58 @@
+// tests/stream_output.rs
+
+//! `-o` bodies are written to the file as they arrive rather than kept in
+//! the `Response`, for every compiled-in backend.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn body_streamed_to_output_file() {
+    let payload: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    for client in clients() {
+        let server = TestServer::with_response(CannedResponse {
+            body: payload.clone(),
+            ..Default::default()
+        });
+        let name = client.backend().name();
+        let path = std::env::temp_dir().join(format!("rustcurl-stream-{name}-{}.bin", std::process::id()));
+        let config = RequestConfig::new(&server.url("/big.bin"))
+            .noproxy("*")
+            .output(path.to_str().unwrap());
+        let response = client.send(&config).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(response.body.is_empty(), "{name}");
+        assert_eq!(response.saved_as.as_deref(), Some(path.as_path()), "{name}");
+        assert_eq!(response.transfer.unwrap().size_download, payload.len() as u64, "{name}");
+        assert!(written == payload, "{name}: file differs from the body sent");
+    }
+}
+
+#[test]
+fn failed_transfer_leaves_no_file() {
+    for client in clients() {
+        let name = client.backend().name();
+        let path = std::env::temp_dir().join(format!("rustcurl-stream-fail-{name}-{}.bin", std::process::id()));
+        // Nothing listens on the discard port
+        let config = RequestConfig::new("http://127.0.0.1:9/x")
+            .noproxy("*")
+            .output(path.to_str().unwrap());
+        assert!(client.send(&config).is_err(), "{name}");
+        assert!(!path.exists(), "{name}");
+    }
+}
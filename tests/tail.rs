@@ -0,0 +1,44 @@
+// tests/tail.rs
+
+//! `Client::tail` prints the file, then only the bytes each ranged poll
+//! finds appended.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[test]
+fn prints_only_new_bytes() {
+    let reply = |status, range: &str, body: &str| CannedResponse {
+        status,
+        headers: vec![format!("Content-Range: {range}")],
+        body: body.as_bytes().to_vec(),
+        ..Default::default()
+    };
+    let server = TestServer::with_responses(vec![
+        CannedResponse {
+            body: b"build started\n".to_vec(),
+            ..Default::default()
+        },
+        reply(206, "bytes 14-22/23", "step 1/2\n"),
+        reply(416, "bytes */23", ""),
+        reply(206, "bytes 23-27/28", "done\n"),
+    ]);
+    let config = RequestConfig::new(&server.url("/build.log"))
+        .noproxy("*")
+        .tail(Duration::ZERO);
+    let mut out = Vec::new();
+    Client::new().tail(&config, Some(4), &mut out).unwrap();
+    assert_eq!(out, b"build started\nstep 1/2\ndone\n");
+
+    let ranges: Vec<Option<String>> =
+        server.requests().iter().map(|r| r.header("range").map(str::to_string)).collect();
+    assert_eq!(
+        ranges,
+        [None, Some("bytes=14-".into()), Some("bytes=23-".into()), Some("bytes=23-".into())]
+    );
+}
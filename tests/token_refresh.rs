@@ -0,0 +1,96 @@
+// tests/token_refresh.rs
+
+//! A bearer token rejected with 401 is refreshed at `--oauth2-token-url`
+//! and the request retried once, for every compiled-in backend.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+fn unauthorized() -> CannedResponse {
+    CannedResponse {
+        status: 401,
+        headers: vec!["WWW-Authenticate: Bearer error=\"invalid_token\"".into()],
+        ..Default::default()
+    }
+}
+
+fn token(body: &str, status: u16) -> CannedResponse {
+    CannedResponse {
+        status,
+        headers: vec!["Content-Type: application/json".into()],
+        body: body.as_bytes().to_vec(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn refreshes_and_retries_once() {
+    for client in clients() {
+        let server = TestServer::with_responses(vec![
+            unauthorized(),
+            token(r#"{"access_token":"fresh","token_type":"Bearer"}"#, 200),
+            CannedResponse::default(),
+        ]);
+        let name = client.backend().name();
+        let config = RequestConfig::new(&server.url("/items"))
+            .noproxy("*")
+            .bearer("stale")
+            .oauth2_token_url(&server.url("/token"))
+            .oauth2_refresh_token("r1")
+            .oauth2_client_id("cli");
+        let response = client.send(&config).unwrap();
+        assert_eq!(response.status_code, 200, "{name}");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3, "{name}");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer stale"), "{name}");
+        assert_eq!(requests[1].method, "POST", "{name}");
+        assert_eq!(requests[1].target, "/token", "{name}");
+        assert_eq!(requests[1].header("authorization"), None, "{name}");
+        assert_eq!(requests[1].body, b"grant_type=refresh_token&refresh_token=r1&client_id=cli", "{name}");
+        assert_eq!(requests[2].header("authorization"), Some("Bearer fresh"), "{name}");
+    }
+}
+
+#[test]
+fn failed_refresh_is_an_error() {
+    let server = TestServer::with_responses(vec![unauthorized(), token(r#"{"error":"invalid_grant"}"#, 400)]);
+    let config = RequestConfig::new(&server.url("/items"))
+        .noproxy("*")
+        .bearer("stale")
+        .oauth2_token_url(&server.url("/token"))
+        .oauth2_refresh_token("revoked");
+    let err = Client::new().send(&config).unwrap_err();
+    assert_eq!(err.code(), "token_refresh");
+    assert!(err.to_string().contains("invalid_grant"), "{err}");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn second_401_is_returned() {
+    let server = TestServer::with_responses(vec![
+        unauthorized(),
+        token(r#"{"access_token":"fresh"}"#, 200),
+        unauthorized(),
+    ]);
+    let config = RequestConfig::new(&server.url("/items"))
+        .noproxy("*")
+        .bearer("stale")
+        .oauth2_token_url(&server.url("/token"))
+        .oauth2_refresh_token("r1");
+    assert_eq!(Client::new().send(&config).unwrap().status_code, 401);
+    assert_eq!(server.requests().len(), 3);
+}
@@ -0,0 +1,67 @@
+// tests/verify_sig.rs
+
+//! `--verify-sig`: downloads must match a detached minisign signature,
+//! read from a file or fetched from a URL.
+
+mod common;
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::eol::Eol;
+
+const KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+const SIG: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+
+fn serving(body: &[u8]) -> TestServer {
+    TestServer::with_response(CannedResponse {
+        body: body.to_vec(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn signature_from_url() {
+    let artifact = serving(b"test");
+    let sig = serving(SIG.as_bytes());
+    let config = RequestConfig::new(&artifact.url("/test"))
+        .noproxy("*")
+        .verify_sig(&sig.url("/test.minisig"))
+        .verify_key(KEY);
+    let resp = Client::new().send(&config).unwrap();
+    assert_eq!(resp.body, b"test");
+    assert_eq!(sig.last_request().target, "/test.minisig");
+}
+
+#[test]
+fn signature_fetched_without_download_options() {
+    let artifact = serving(b"test");
+    let sig = serving(SIG.as_bytes());
+    // --eol and friends shape the download, never the signature fetch
+    let config = RequestConfig::new(&artifact.url("/test"))
+        .noproxy("*")
+        .normalize_eol(Eol::Crlf)
+        .header("X-Download: 1")
+        .verify_sig(&sig.url("/test.minisig"))
+        .verify_key(KEY);
+    let resp = Client::new().send(&config).unwrap();
+    assert_eq!(resp.body, b"test");
+    assert_eq!(sig.last_request().header("x-download"), None);
+}
+
+#[test]
+fn tampered_download_fails() {
+    let artifact = serving(b"tampered");
+    let path = std::env::temp_dir().join(format!("rustcurl-verify-{}.minisig", std::process::id()));
+    std::fs::write(&path, SIG).unwrap();
+    let config = RequestConfig::new(&artifact.url("/test"))
+        .noproxy("*")
+        .verify_sig(path.to_str().unwrap())
+        .verify_key(KEY);
+    let result = Client::new().send(&config);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(result.unwrap_err().code(), "signature");
+}
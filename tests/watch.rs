@@ -0,0 +1,97 @@
+// tests/watch.rs
+
+//! `Client::watch` keeps probing on its interval, through failures, and
+//! fires notification hooks on status class changes.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{CannedResponse, TestServer};
+use rustcurl::Client;
+use rustcurl::curl::config::RequestConfig;
+
+#[test]
+fn one_line_per_attempt() {
+    let server = TestServer::start();
+    let config = RequestConfig::new(&server.url("/up"))
+        .noproxy("*")
+        .watch(Duration::from_millis(100))
+        .watch_diff(true);
+    let mut out = Vec::new();
+    let clock = Instant::now();
+    Client::new().watch(&config, Some(3), false, &mut out).unwrap();
+    assert!(clock.elapsed() >= Duration::from_millis(200));
+
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    for line in lines {
+        assert!(line.contains("  200  "), "{line}");
+        assert!(line.ends_with(" B"), "{line}");
+    }
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn failures_do_not_stop_watching() {
+    // Bind then drop a listener so the port refuses connections
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let config = RequestConfig::new(&format!("http://127.0.0.1:{port}/"))
+        .noproxy("*")
+        .watch(Duration::ZERO);
+    let mut out = Vec::new();
+    Client::new().watch(&config, Some(2), false, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.matches("  ERR ").count(), 2, "{text}");
+}
+
+#[cfg(unix)]
+#[test]
+fn notify_cmd_fires_on_debounced_transition() {
+    let status = |status| CannedResponse {
+        status,
+        ..Default::default()
+    };
+    // One 503 blip, then a sustained outage, then recovery
+    let server = TestServer::with_responses(vec![
+        status(200),
+        status(503),
+        status(200),
+        status(503),
+        status(503),
+        status(200),
+        status(200),
+    ]);
+    let log = std::env::temp_dir().join(format!("rustcurl-watch-notify-{}.log", std::process::id()));
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .watch(Duration::ZERO)
+        .notify_cmd(&format!("echo \"$RUSTCURL_FROM $RUSTCURL_TO $RUSTCURL_DETAIL\" >> {}", log.display()))
+        .notify_debounce(2);
+    let mut out = Vec::new();
+    Client::new().watch(&config, Some(7), false, &mut out).unwrap();
+    let fired = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+    assert_eq!(fired, "2xx 5xx 503\n5xx 2xx 200\n");
+}
+
+#[test]
+fn if_changed_prints_only_changes() {
+    let body = |text: &str| CannedResponse {
+        body: text.as_bytes().to_vec(),
+        ..Default::default()
+    };
+    let server = TestServer::with_responses(vec![body("v1"), body("v1"), body("v2"), body("v2")]);
+    let state = std::env::temp_dir().join(format!("rustcurl-watch-changed-{}.state", std::process::id()));
+    let _ = std::fs::remove_file(&state);
+    let config = RequestConfig::new(&server.url("/"))
+        .noproxy("*")
+        .watch(Duration::ZERO)
+        .if_changed(state.to_str().unwrap());
+    let mut out = Vec::new();
+    Client::new().watch(&config, Some(4), false, &mut out).unwrap();
+    let _ = std::fs::remove_file(&state);
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().count(), 2, "{text}");
+}
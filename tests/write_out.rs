@@ -0,0 +1,42 @@
+// tests/write_out.rs
+
+//! `-w` variables filled from what each compiled-in backend reports.
+
+mod common;
+
+use common::TestServer;
+use rustcurl::Client;
+use rustcurl::backend::HttpBackend;
+use rustcurl::curl::config::RequestConfig;
+use rustcurl::curl::writeout;
+
+fn clients() -> Vec<Client> {
+    let backends: Vec<Box<dyn HttpBackend>> = vec![
+        #[cfg(feature = "curl")]
+        Box::new(rustcurl::backend::curl_backend::CurlBackend::new()),
+        #[cfg(feature = "reqwest")]
+        Box::new(rustcurl::backend::reqwest_backend::ReqwestBackend::new()),
+    ];
+    backends.into_iter().map(Client::with_backend).collect()
+}
+
+#[test]
+fn transfer_variables() {
+    for client in clients() {
+        let server = TestServer::start();
+        let name = client.backend().name();
+        let template = "%{http_code} %{http_version} %{content_type} %{size_download} %{size_upload} %{url_effective}";
+        let config = RequestConfig::new(&server.url("/echo")).noproxy("*").data("a=1").write_out(template);
+        let response = client.send(&config).unwrap();
+        let rendered = writeout::render(&writeout::parse(template).unwrap(), &config, Ok(&response));
+        assert_eq!(rendered, format!("200 1.1 text/plain 2 3 {}", server.url("/echo")), "{name}");
+    }
+}
+
+#[test]
+fn timing_kept_for_write_out() {
+    let server = TestServer::start();
+    let config = RequestConfig::new(&server.url("/")).noproxy("*").write_out("%{time_total}");
+    let response = Client::new().send(&config).unwrap();
+    assert!(response.timing.is_some());
+}